@@ -47,8 +47,14 @@ use super::error::Result;
 use super::llm::{ChatConfig, LlmInterface};
 use super::tools::mapper::map_tool_parameters;
 use crate::context::ResourceIndex;
-use neomind_core::{config::agent_env_vars, llm::backend::LlmRuntime, Message};
-use neomind_llm::{CloudConfig, CloudRuntime, OllamaConfig, OllamaRuntime};
+use neomind_core::{
+    config::agent_env_vars,
+    llm::{backend::LlmRuntime, usage::UsageContext},
+    Message,
+};
+use neomind_llm::{
+    AnthropicConfig, AnthropicRuntime, CloudConfig, CloudRuntime, OllamaConfig, OllamaRuntime,
+};
 
 // Type aliases to reduce complexity
 pub type SharedToolRegistry = Arc<neomind_tools::ToolRegistry>;
@@ -691,8 +697,11 @@ impl Agent {
             concurrent_limit: 3,    // Default to 3 concurrent LLM requests
         };
 
-        let llm_interface =
-            Arc::new(LlmInterface::new(llm_config).with_system_prompt(&config.system_prompt));
+        let llm_interface = Arc::new(
+            LlmInterface::new(llm_config)
+                .with_system_prompt(&config.system_prompt)
+                .with_usage_context(UsageContext::for_session(session_id.clone())),
+        );
 
         // Create semantic mapper with resource index
         let resource_index = Arc::new(RwLock::new(ResourceIndex::new()));
@@ -906,15 +915,14 @@ impl Agent {
             } => {
                 tracing::info!(
                     endpoint = %endpoint, model = %model, timeout = cloud_timeout,
-                    "Creating CloudRuntime for Anthropic"
+                    "Creating AnthropicRuntime"
                 );
-                // Always use Anthropic provider to ensure correct vision model detection
-                let config = CloudConfig::anthropic(&api_key)
+                let config = AnthropicConfig::new(&api_key)
                     .with_model(&model)
                     .with_timeout_secs(cloud_timeout)
                     .with_base_url_opt(if endpoint.is_empty() { None } else { Some(endpoint.clone()) });
                 let runtime =
-                    CloudRuntime::new(config).map_err(|e| NeoMindError::llm(e.to_string()))?;
+                    AnthropicRuntime::new(config).map_err(|e| NeoMindError::llm(e.to_string()))?;
                 (Arc::new(runtime) as Arc<dyn LlmRuntime>, model)
             }
             LlmBackend::Google {
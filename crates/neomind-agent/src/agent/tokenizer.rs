@@ -1,14 +1,20 @@
 //! Token estimation for context window management.
 //!
 //! Provides accurate token counting for Chinese, English, and code content.
+//! Counts are exact when the active model's tokenizer is loaded.
 
 /// Estimate token count for a text string.
 ///
-/// This uses a heuristic approach that's more accurate than simple character division:
+/// Counts with the active model's tokenizer when one is loaded. Otherwise
+/// this uses a heuristic approach that's more accurate than simple character division:
 /// - Chinese characters: ~1.8 tokens each
 /// - English words: ~0.8 tokens each
 /// - Special characters/punctuation: ~1.2 tokens each
 pub fn estimate_tokens(text: &str) -> usize {
+    if let Some(encoder) = neomind_core::llm::default_encoder() {
+        return encoder.count(text);
+    }
+
     let mut tokens = 0f64;
 
     for line in text.lines() {
@@ -2,33 +2,44 @@
 
 #![allow(clippy::too_many_arguments)]
 
-use futures::future::join_all;
+use futures::future::{join_all, BoxFuture};
 use neomind_core::llm::backend::LlmRuntime;
+use neomind_core::llm::usage::{with_usage_context, UsageContext};
 use neomind_core::{
     message::{Content, ContentPart, Message, MessageRole},
     EventBus, MetricValue, NeoMindEvent,
 };
-use neomind_devices::DeviceService;
-use neomind_llm::{CloudConfig, CloudRuntime, OllamaConfig, OllamaRuntime};
+use neomind_devices::{DeviceSelector, DeviceService};
+use neomind_llm::{
+    get_response_cache, AnthropicConfig, AnthropicRuntime, CachePolicy, CachedLlmRuntime,
+    CloudConfig, CloudRuntime, OllamaConfig, OllamaRuntime,
+};
 use neomind_messages::MessageManager;
 use neomind_storage::{
     AgentExecutionRecord,
     AgentMemory,
+    AgentMessage,
     AgentResource,
     AgentStore,
     AiAgent,
+    ApprovalRequest,
+    ApprovalStatus,
     // New conversation types
     ConversationTurn,
     DataCollected,
     Decision,
     DecisionProcess,
+    ExecutionCheckpoint,
     ExecutionResult as StorageExecutionResult,
     ExecutionStatus,
     GeneratedReport,
+    InterruptedExecutionPolicy,
     LearnedPattern,
     LlmBackendStore,
+    PendingAction,
     ReasoningStep,
     ResourceType,
+    RoundCheckpoint,
     TrendPoint,
     TurnInput,
     TurnOutput,
@@ -45,6 +56,13 @@ use crate::agent::semantic_mapper::SemanticToolMapper;
 use crate::error::{NeoMindError, Result as AgentResult};
 use crate::prompts::{CONVERSATION_CONTEXT_EN, CONVERSATION_CONTEXT_ZH};
 
+/// Maximum length of a delegation chain (agents delegating to agents).
+const MAX_DELEGATION_DEPTH: usize = 3;
+
+/// How long a delegating agent waits for an answer, unless the agent
+/// resource sets `timeout_secs`.
+const DEFAULT_DELEGATION_TIMEOUT_SECS: u64 = 120;
+
 /// Internal representation of image content for multimodal LLM messages.
 #[allow(dead_code)]
 enum ImageContent {
@@ -105,10 +123,8 @@ fn extract_device_from_description(description: &str) -> Option<String> {
 fn get_time_context() -> String {
     use neomind_storage::SettingsStore;
 
-    const SETTINGS_DB_PATH: &str = "data/settings.redb";
-
     // Try to load timezone from settings
-    let timezone = SettingsStore::open(SETTINGS_DB_PATH)
+    let timezone = SettingsStore::open(neomind_storage::data_path("settings.redb"))
         .ok()
         .map(|store| store.get_global_timezone())
         .unwrap_or_else(|| "Asia/Shanghai".to_string());
@@ -747,6 +763,12 @@ impl ChainState {
         self.depth < self.max_depth
     }
 
+    /// Restore the rounds an interrupted execution had completed
+    fn restore(&mut self, depth: usize, results: &[neomind_storage::ActionExecuted]) {
+        self.advance(results);
+        self.depth = depth;
+    }
+
     fn advance(&mut self, results: &[neomind_storage::ActionExecuted]) {
         self.depth += 1;
         for action in results {
@@ -830,6 +852,14 @@ pub struct ExecutionContext {
     pub llm_backend: Option<LlmBackend>,
     /// Execution ID for event tracking
     pub execution_id: String,
+    /// Record side-effecting actions instead of executing them
+    pub dry_run: bool,
+    /// Agents that delegated down to this execution, outermost first
+    pub delegation_chain: Vec<String>,
+    /// Task delegated by another agent, answered by this execution
+    pub delegated_task: Option<AgentMessage>,
+    /// Checkpoint progress so the execution can resume after a restart
+    pub checkpointed: bool,
 }
 
 /// Result of agent execution.
@@ -843,6 +873,9 @@ pub struct AgentExecutionResult {
 }
 
 /// AI Agent executor - handles execution of user-defined agents.
+///
+/// Clones share the same stores, services and caches.
+#[derive(Clone)]
 pub struct AgentExecutor {
     /// Agent store
     store: Arc<AgentStore>,
@@ -1096,14 +1129,69 @@ impl AgentExecutor {
     }
 
     /// Get the LLM runtime for a specific agent.
-    /// If the agent has a specific backend ID configured, use that.
+    ///
+    /// If the agent opts into response caching, the runtime answers repeated
+    /// requests from the shared response cache, and cached answers are dropped
+    /// when one of the agent's device or metric resources changes.
+    pub async fn get_llm_runtime_for_agent(
+        &self,
+        agent: &AiAgent,
+    ) -> Result<Option<Arc<dyn LlmRuntime + Send + Sync>>, NeoMindError> {
+        let runtime = self.select_llm_runtime_for_agent(agent).await?;
+        let Some(config) = agent.response_cache.as_ref() else {
+            return Ok(runtime);
+        };
+
+        let devices = agent
+            .resources
+            .iter()
+            .filter(|r| r.resource_type == ResourceType::Device)
+            .map(|r| r.resource_id.clone());
+        // Metric resources are "device_id:metric_name"
+        let metrics = agent
+            .resources
+            .iter()
+            .filter(|r| r.resource_type == ResourceType::Metric)
+            .filter_map(|r| r.resource_id.split_once(':'))
+            .map(|(device_id, metric)| (device_id.to_string(), metric.to_string()));
+        let policy = CachePolicy::from(config)
+            .with_devices(devices)
+            .with_metrics(metrics);
+        Ok(runtime.map(|inner| {
+            Arc::new(CachedLlmRuntime::new(inner, get_response_cache(), policy))
+                as Arc<dyn LlmRuntime + Send + Sync>
+        }))
+    }
+
+    /// Select the LLM runtime for a specific agent.
+    /// If the agent has a routing policy configured, route through it.
+    /// Otherwise, if it has a specific backend ID configured, use that.
     /// Otherwise, fall back to the default runtime.
     ///
     /// Runtimes are cached by backend configuration to avoid repeated initialization.
-    pub async fn get_llm_runtime_for_agent(
+    async fn select_llm_runtime_for_agent(
         &self,
         agent: &AiAgent,
     ) -> Result<Option<Arc<dyn LlmRuntime + Send + Sync>>, NeoMindError> {
+        // Routing policies fail over between backends, so they take precedence
+        if let Some(ref policy_id) = agent.llm_routing_policy {
+            let routed = match neomind_llm::get_instance_manager() {
+                Ok(manager) => manager.get_routed_runtime(policy_id).await,
+                Err(e) => Err(e),
+            };
+            match routed {
+                Ok(runtime) => return Ok(Some(runtime)),
+                Err(e) => {
+                    tracing::warn!(
+                        agent_id = %agent.id,
+                        policy = %policy_id,
+                        error = %e,
+                        "Failed to use LLM routing policy for agent '{}'", agent.name
+                    );
+                }
+            }
+        }
+
         // If agent has a specific backend ID, try to use it
         if let Some(ref backend_id) = agent.llm_backend_id {
             if let Some(ref store) = self.llm_backend_store {
@@ -1188,7 +1276,7 @@ impl AgentExecutor {
                         }
                         LlmBackendType::Anthropic => {
                             let api_key = backend.api_key.clone().unwrap_or_default();
-                            let _endpoint = backend
+                            let endpoint = backend
                                 .endpoint
                                 .clone()
                                 .unwrap_or_else(|| "https://api.anthropic.com/v1".to_string());
@@ -1197,10 +1285,11 @@ impl AgentExecutor {
                                 .ok()
                                 .and_then(|s| s.parse().ok())
                                 .unwrap_or(60);
-                            CloudRuntime::new(
-                                CloudConfig::anthropic(&api_key)
+                            AnthropicRuntime::new(
+                                AnthropicConfig::new(&api_key)
                                     .with_model(&model)
-                                    .with_timeout_secs(timeout),
+                                    .with_timeout_secs(timeout)
+                                    .with_base_url_opt(Some(endpoint)),
                             )
                             .map(|rt| Arc::new(rt) as Arc<dyn LlmRuntime + Send + Sync>)
                         }
@@ -1651,6 +1740,19 @@ Respond in JSON format:
         // Build the expected resource IDs for this event
         let device_metric_id = format!("{}:{}", device_id, metric);
 
+        // Device group resources need the device's groups/tags/location
+        let device_config = match &self.device_service {
+            Some(service)
+                if agent
+                    .resources
+                    .iter()
+                    .any(|r| r.resource_type == ResourceType::DeviceGroup) =>
+            {
+                service.get_device(device_id).await
+            }
+            _ => None,
+        };
+
         // Check each resource to see if it matches this event
         let has_matching_resource = agent.resources.iter().any(|r| {
             match r.resource_type {
@@ -1670,6 +1772,10 @@ Respond in JSON format:
                         r.resource_id == metric
                     }
                 }
+                ResourceType::DeviceGroup => device_config.as_ref().is_some_and(|config| {
+                    DeviceSelector::parse(&r.resource_id)
+                        .is_ok_and(|selector| !selector.is_empty() && selector.matches(config))
+                }),
                 _ => false,
             }
         });
@@ -1757,26 +1863,77 @@ Respond in JSON format:
 
     /// Execute an agent and record the full decision process.
     pub async fn execute_agent(&self, agent: AiAgent) -> AgentResult<AgentExecutionRecord> {
-        let agent_id = agent.id.clone();
-        let agent_name = agent.name.clone();
-        let execution_id = uuid::Uuid::new_v4().to_string();
-        let start_time = std::time::Instant::now();
-        let timestamp = chrono::Utc::now().timestamp();
+        self.execute_manual(agent, false).await
+    }
 
-        // Update agent status to executing
-        self.store
-            .update_agent_status(&agent_id, neomind_storage::AgentStatus::Executing, None)
-            .await
-            .map_err(|e| NeoMindError::Storage(format!("Failed to update status: {}", e)))?;
+    /// Simulate an agent execution without side effects.
+    ///
+    /// Data collection and read-only tools run against real data, while device
+    /// commands, alerts, side-effecting extension commands and MCP tools that
+    /// are not read-only are recorded as intended actions instead of executed.
+    /// The agent's status, stats and memory are left untouched, and the saved
+    /// record is marked as simulated.
+    pub async fn execute_agent_dry_run(&self, agent: AiAgent) -> AgentResult<AgentExecutionRecord> {
+        self.execute_manual(agent, true).await
+    }
 
-        // Create execution context
+    async fn execute_manual(
+        &self,
+        agent: AiAgent,
+        dry_run: bool,
+    ) -> AgentResult<AgentExecutionRecord> {
         let context = ExecutionContext {
-            agent: agent.clone(),
+            agent,
             trigger_type: "manual".to_string(),
             event_data: None,
             llm_backend: None,
-            execution_id: execution_id.clone(),
+            execution_id: uuid::Uuid::new_v4().to_string(),
+            dry_run,
+            delegation_chain: vec![],
+            delegated_task: None,
+            checkpointed: !dry_run,
         };
+        self.execute_with_context(context).await
+    }
+
+    /// Run an execution and record it with its conversation turn.
+    ///
+    /// Delegated executions leave the agent's status alone, since the
+    /// delegating agent may stop waiting for them.
+    async fn execute_with_context(
+        &self,
+        mut context: ExecutionContext,
+    ) -> AgentResult<AgentExecutionRecord> {
+        let agent_id = context.agent.id.clone();
+        let agent_name = context.agent.name.clone();
+        let execution_id = context.execution_id.clone();
+        let trigger_type = context.trigger_type.clone();
+        let event_data = context.event_data.clone();
+        let dry_run = context.dry_run;
+        let checkpointed = context.checkpointed;
+        let mut delegated_task = context.delegated_task.clone();
+        let track_status = !dry_run && delegated_task.is_none();
+        let start_time = std::time::Instant::now();
+        let timestamp = chrono::Utc::now().timestamp();
+
+        if let Some(ref task) = delegated_task {
+            context.agent.user_prompt = format!(
+                "{}\n\n{}",
+                context.agent.user_prompt,
+                Self::format_delegated_task(task)
+            );
+        }
+
+        // Update agent status to executing
+        if track_status {
+            self.store
+                .update_agent_status(&agent_id, neomind_storage::AgentStatus::Executing, None)
+                .await
+                .map_err(|e| NeoMindError::Storage(format!("Failed to update status: {}", e)))?;
+        }
+        if checkpointed {
+            self.begin_checkpoint(&context, timestamp).await;
+        }
 
         // Emit agent execution started event
         tracing::info!(
@@ -1791,7 +1948,7 @@ Respond in JSON format:
                     agent_id: agent_id.clone(),
                     agent_name: agent_name.clone(),
                     execution_id: execution_id.clone(),
-                    trigger_type: "manual".to_string(),
+                    trigger_type: trigger_type.clone(),
                     timestamp,
                 })
                 .await;
@@ -1812,38 +1969,43 @@ Respond in JSON format:
             Err(_) => (None, false),
         };
 
-        let record = match execution_result {
+        let mut record = match execution_result {
             Ok((decision_process, result)) => {
                 // Update stats
-                let _ = self
-                    .store
-                    .update_agent_stats(&agent_id, true, duration_ms)
-                    .await;
+                if !dry_run {
+                    let _ = self
+                        .store
+                        .update_agent_stats(&agent_id, true, duration_ms)
+                        .await;
+                }
 
                 AgentExecutionRecord {
                     id: execution_id.clone(),
                     agent_id: agent_id.clone(),
                     timestamp,
-                    trigger_type: "manual".to_string(),
+                    trigger_type: trigger_type.clone(),
                     status: ExecutionStatus::Completed,
                     decision_process,
                     result: Some(result),
                     duration_ms,
                     error: None,
+                    simulated: dry_run,
                 }
             }
             Err(e) => {
                 // Update stats with failure
-                let _ = self
-                    .store
-                    .update_agent_stats(&agent_id, false, duration_ms)
-                    .await;
+                if !dry_run {
+                    let _ = self
+                        .store
+                        .update_agent_stats(&agent_id, false, duration_ms)
+                        .await;
+                }
 
                 AgentExecutionRecord {
                     id: execution_id.clone(),
                     agent_id: agent_id.clone(),
                     timestamp,
-                    trigger_type: "manual".to_string(),
+                    trigger_type: trigger_type.clone(),
                     status: ExecutionStatus::Failed,
                     decision_process: DecisionProcess {
                         situation_analysis: format!("Execution failed: {}", e),
@@ -1856,10 +2018,30 @@ Respond in JSON format:
                     result: None,
                     duration_ms,
                     error: Some(e.to_string()),
+                    simulated: dry_run,
                 }
             }
         };
 
+        // Answer the delegated task with the outcome of this execution
+        if let Some(ref mut message) = delegated_task {
+            message.execution_id = Some(execution_id.clone());
+            message.success = record.status == ExecutionStatus::Completed;
+            message.response = Some(match (&record.result, &record.error) {
+                (Some(result), _) => result.summary.clone(),
+                (None, Some(error)) => format!("Failed: {}", error),
+                (None, None) => "No result".to_string(),
+            });
+            if let Some(ref mut result) = record.result {
+                result.agent_messages.insert(0, message.clone());
+            }
+        }
+        let agent_messages = record
+            .result
+            .as_ref()
+            .map(|r| r.agent_messages.clone())
+            .unwrap_or_default();
+
         // Save execution record and conversation turn in a single transaction
         tracing::debug!(
             agent_id = %agent_id,
@@ -1880,12 +2062,13 @@ Respond in JSON format:
             );
             self.create_conversation_turn(
                 execution_id.clone(),
-                "manual".to_string(),
+                trigger_type.clone(),
                 dp.data_collected.clone(),
-                None, // event_data
+                event_data.clone(),
                 dp,
                 duration_ms,
                 success,
+                agent_messages.clone(),
             )
         });
 
@@ -1896,10 +2079,17 @@ Respond in JSON format:
             "About to save execution with conversation"
         );
 
-        self.store
+        // Simulated runs are kept in the history but not in the agent's conversation
+        let turn = if dry_run { None } else { turn };
+        let saved = self
+            .store
             .save_execution_with_conversation(&record, Some(&agent_id), turn.as_ref())
-            .await
-            .map_err(|e| NeoMindError::Storage(format!("Failed to save execution: {}", e)))?;
+            .await;
+        // The execution is over either way; nothing is left to resume
+        if checkpointed {
+            self.finish_checkpoint(&execution_id).await;
+        }
+        saved.map_err(|e| NeoMindError::Storage(format!("Failed to save execution: {}", e)))?;
 
         tracing::debug!(
             agent_id = %agent_id,
@@ -1908,16 +2098,18 @@ Respond in JSON format:
         );
 
         // Reset agent status based on result
-        let new_status = if record.status == ExecutionStatus::Completed {
-            neomind_storage::AgentStatus::Active
-        } else {
-            neomind_storage::AgentStatus::Error
-        };
+        if track_status {
+            let new_status = if record.status == ExecutionStatus::Completed {
+                neomind_storage::AgentStatus::Active
+            } else {
+                neomind_storage::AgentStatus::Error
+            };
 
-        let _ = self
-            .store
-            .update_agent_status(&agent_id, new_status, record.error.clone())
-            .await;
+            let _ = self
+                .store
+                .update_agent_status(&agent_id, new_status, record.error.clone())
+                .await;
+        }
 
         // Emit agent execution completed event
         let completion_timestamp = chrono::Utc::now().timestamp();
@@ -1946,6 +2138,205 @@ Respond in JSON format:
         Ok(record)
     }
 
+    /// Settle executions interrupted by a crash or restart.
+    ///
+    /// Called on startup for every execution that left a checkpoint behind.
+    /// Agents stuck in `Executing` are set back to `Active`. Executions are
+    /// recorded as failed under the `Fail` policy, and so are event-triggered
+    /// ones under either policy, since the reading that triggered them is
+    /// stale by now, and those of agents that were paused, stopped or failed
+    /// since. The remaining checkpoints are returned so the caller can pass
+    /// them to [`Self::resume_execution`].
+    pub async fn recover_interrupted_executions(
+        &self,
+        policy: InterruptedExecutionPolicy,
+    ) -> AgentResult<Vec<ExecutionCheckpoint>> {
+        let checkpoints = self
+            .store
+            .list_checkpoints()
+            .await
+            .map_err(|e| NeoMindError::Storage(format!("Failed to list checkpoints: {}", e)))?;
+
+        let mut resumable = Vec::new();
+        for checkpoint in checkpoints {
+            let agent = self
+                .store
+                .get_agent(&checkpoint.agent_id)
+                .await
+                .map_err(|e| NeoMindError::Storage(e.to_string()))?;
+            let Some(agent) = agent else {
+                self.finish_checkpoint(&checkpoint.execution_id).await;
+                continue;
+            };
+
+            let resume = policy == InterruptedExecutionPolicy::Resume
+                && !checkpoint.trigger_type.starts_with("event:")
+                && matches!(
+                    agent.status,
+                    neomind_storage::AgentStatus::Active | neomind_storage::AgentStatus::Executing
+                );
+            tracing::warn!(
+                agent_id = %agent.id,
+                execution_id = %checkpoint.execution_id,
+                chain_depth = checkpoint.chain_depth,
+                resume,
+                "Found execution interrupted by a restart"
+            );
+
+            if agent.status == neomind_storage::AgentStatus::Executing {
+                let error = (!resume).then(|| "Execution interrupted by a restart".to_string());
+                let _ = self
+                    .store
+                    .update_agent_status(&agent.id, neomind_storage::AgentStatus::Active, error)
+                    .await;
+            }
+
+            if resume {
+                resumable.push(checkpoint);
+            } else {
+                self.fail_interrupted_execution(checkpoint, "Execution interrupted by a restart")
+                    .await?;
+            }
+        }
+
+        Ok(resumable)
+    }
+
+    /// Resume an interrupted execution from its last checkpoint.
+    ///
+    /// Completed tool chaining rounds and decisions are not repeated, and a
+    /// round whose LLM turn was checkpointed continues with its pending
+    /// decisions. A decision that was executing when the process stopped is
+    /// recorded as interrupted rather than run again. If resuming fails, the
+    /// execution is recorded as failed so it is not picked up again.
+    pub async fn resume_execution(
+        &self,
+        checkpoint: ExecutionCheckpoint,
+    ) -> AgentResult<AgentExecutionRecord> {
+        let execution_id = checkpoint.execution_id.clone();
+        let result = self.resume_from_checkpoint(checkpoint).await;
+        if let Err(ref e) = result {
+            if let Ok(Some(checkpoint)) = self.store.get_checkpoint(&execution_id).await {
+                let error = format!("Resuming the interrupted execution failed: {}", e);
+                if let Err(e) = self.fail_interrupted_execution(checkpoint, &error).await {
+                    tracing::warn!(
+                        execution_id = %execution_id,
+                        error = %e,
+                        "Failed to record the interrupted execution as failed"
+                    );
+                }
+            }
+        }
+        result
+    }
+
+    async fn resume_from_checkpoint(
+        &self,
+        checkpoint: ExecutionCheckpoint,
+    ) -> AgentResult<AgentExecutionRecord> {
+        let agent = self
+            .store
+            .get_agent(&checkpoint.agent_id)
+            .await
+            .map_err(|e| NeoMindError::Storage(e.to_string()))?
+            .ok_or_else(|| NeoMindError::NotFound(format!("Agent {}", checkpoint.agent_id)))?;
+
+        tracing::info!(
+            agent_id = %agent.id,
+            execution_id = %checkpoint.execution_id,
+            chain_depth = checkpoint.chain_depth,
+            "Resuming interrupted execution"
+        );
+
+        let context = ExecutionContext {
+            agent,
+            trigger_type: checkpoint.trigger_type,
+            event_data: checkpoint.event_data,
+            llm_backend: None,
+            execution_id: checkpoint.execution_id,
+            dry_run: false,
+            delegation_chain: vec![],
+            delegated_task: None,
+            checkpointed: true,
+        };
+        self.execute_with_context(context).await
+    }
+
+    /// Record an interrupted execution as failed, keeping what it had done.
+    async fn fail_interrupted_execution(
+        &self,
+        checkpoint: ExecutionCheckpoint,
+        error: &str,
+    ) -> AgentResult<AgentExecutionRecord> {
+        let error = error.to_string();
+        let duration_ms = ((checkpoint.updated_at - checkpoint.started_at).max(0) as u64) * 1000;
+
+        let mut actions_executed = checkpoint.actions_executed;
+        let mut notifications_sent = checkpoint.notifications_sent;
+        let mut agent_messages = checkpoint.agent_messages;
+        let decision_process = match checkpoint.round {
+            Some(round) => {
+                actions_executed.extend(round.actions_executed);
+                notifications_sent.extend(round.notifications_sent);
+                agent_messages.extend(round.agent_messages);
+                DecisionProcess {
+                    situation_analysis: clean_and_truncate_text(&round.situation_analysis, 500),
+                    data_collected: round.data_collected,
+                    reasoning_steps: round.reasoning_steps,
+                    decisions: round.decisions,
+                    conclusion: format!("Failed: {}", error),
+                    confidence: 0.0,
+                }
+            }
+            None => DecisionProcess {
+                situation_analysis: format!("Execution failed: {}", error),
+                data_collected: vec![],
+                reasoning_steps: vec![],
+                decisions: vec![],
+                conclusion: format!("Failed: {}", error),
+                confidence: 0.0,
+            },
+        };
+
+        let success_rate = if actions_executed.is_empty() {
+            0.0
+        } else {
+            actions_executed.iter().filter(|a| a.success).count() as f32
+                / actions_executed.len() as f32
+        };
+        let record = AgentExecutionRecord {
+            id: checkpoint.execution_id.clone(),
+            agent_id: checkpoint.agent_id.clone(),
+            timestamp: checkpoint.started_at,
+            trigger_type: checkpoint.trigger_type,
+            status: ExecutionStatus::Failed,
+            decision_process,
+            result: Some(StorageExecutionResult {
+                actions_executed,
+                report: None,
+                notifications_sent,
+                summary: error.clone(),
+                success_rate,
+                agent_messages,
+            }),
+            duration_ms,
+            error: Some(error),
+            simulated: false,
+        };
+
+        let _ = self
+            .store
+            .update_agent_stats(&checkpoint.agent_id, false, duration_ms)
+            .await;
+        self.store
+            .save_execution(&record)
+            .await
+            .map_err(|e| NeoMindError::Storage(format!("Failed to save execution: {}", e)))?;
+        self.finish_checkpoint(&checkpoint.execution_id).await;
+
+        Ok(record)
+    }
+
     /// Execute an agent with event trigger data.
     /// This method passes the triggering event data directly to avoid storage delays.
     pub async fn execute_agent_with_event(
@@ -1983,6 +2374,10 @@ Respond in JSON format:
             })),
             llm_backend: None,
             execution_id: execution_id.clone(),
+            dry_run: false,
+            delegation_chain: vec![],
+            delegated_task: None,
+            checkpointed: false,
         };
 
         // Emit agent execution started event
@@ -2006,6 +2401,10 @@ Respond in JSON format:
                 .await;
         }
 
+        // Event-triggered executions are not resumed, but the checkpoint
+        // lets a restart record them as failed
+        self.begin_checkpoint(&context, timestamp).await;
+
         // Execute with error handling for stability
         // Use execute_with_chaining_and_event to support multi-round tool chaining
         let execution_result = self
@@ -2038,6 +2437,7 @@ Respond in JSON format:
                     result: Some(result),
                     duration_ms,
                     error: None,
+                    simulated: false,
                 }
             }
             Err(e) => {
@@ -2064,6 +2464,7 @@ Respond in JSON format:
                     result: None,
                     duration_ms,
                     error: Some(e.to_string()),
+                    simulated: false,
                 }
             }
         };
@@ -2082,13 +2483,20 @@ Respond in JSON format:
                 dp,
                 duration_ms,
                 success,
+                record
+                    .result
+                    .as_ref()
+                    .map(|r| r.agent_messages.clone())
+                    .unwrap_or_default(),
             )
         });
 
-        self.store
+        let saved = self
+            .store
             .save_execution_with_conversation(&record, Some(&agent_id), turn.as_ref())
-            .await
-            .map_err(|e| NeoMindError::Storage(format!("Failed to save execution: {}", e)))?;
+            .await;
+        self.finish_checkpoint(&execution_id).await;
+        saved.map_err(|e| NeoMindError::Storage(format!("Failed to save execution: {}", e)))?;
 
         // Reset agent status based on result
         let new_status = if record.status == ExecutionStatus::Completed {
@@ -2228,6 +2636,18 @@ Respond in JSON format:
         let mut final_decision_process: Option<DecisionProcess> = None;
         let mut all_actions_executed: Vec<neomind_storage::ActionExecuted> = Vec::new();
         let mut all_notifications_sent: Vec<neomind_storage::NotificationSent> = Vec::new();
+        let mut all_agent_messages: Vec<AgentMessage> = Vec::new();
+
+        // Pick up the rounds an interrupted execution had completed
+        if let Some(checkpoint) = self.load_checkpoint(&context).await {
+            if checkpoint.chain_depth > 0 {
+                chain_state.restore(checkpoint.chain_depth, &checkpoint.actions_executed);
+                context.agent.user_prompt = checkpoint.prompt;
+                all_actions_executed = checkpoint.actions_executed;
+                all_notifications_sent = checkpoint.notifications_sent;
+                all_agent_messages = checkpoint.agent_messages;
+            }
+        }
 
         tracing::info!(
             agent_id = %agent.id,
@@ -2261,6 +2681,7 @@ Respond in JSON format:
             // Collect results from this round
             all_actions_executed.extend(execution_result.actions_executed.clone());
             all_notifications_sent.extend(execution_result.notifications_sent.clone());
+            all_agent_messages.extend(execution_result.agent_messages.clone());
 
             // Store the final decision process (last round takes precedence)
             final_decision_process = Some(decision_process.clone());
@@ -2296,6 +2717,7 @@ Respond in JSON format:
                     || d.action.to_lowercase().contains("further")
                     || d.action.to_lowercase().contains("下一步")
                     || d.action.to_lowercase().contains("继续")
+                    || d.action.starts_with("agent:")
             });
 
             if !needs_more_work {
@@ -2309,6 +2731,17 @@ Respond in JSON format:
             // Advance to next round
             chain_state.advance(&execution_result.actions_executed);
 
+            // Checkpoint the completed round before starting the next one
+            if let Some(mut checkpoint) = self.load_checkpoint(&context).await {
+                checkpoint.chain_depth = chain_state.depth;
+                checkpoint.prompt = context.agent.user_prompt.clone();
+                checkpoint.actions_executed = all_actions_executed.clone();
+                checkpoint.notifications_sent = all_notifications_sent.clone();
+                checkpoint.agent_messages = all_agent_messages.clone();
+                checkpoint.round = None;
+                self.save_checkpoint(checkpoint).await;
+            }
+
             // Send progress event for chaining
             self.send_progress(
                 &context.agent.id,
@@ -2370,6 +2803,7 @@ Respond in JSON format:
                 summary_conclusion
             },
             success_rate,
+            agent_messages: all_agent_messages,
         };
 
         tracing::info!(
@@ -2400,6 +2834,7 @@ Respond in JSON format:
         let mut final_decision_process: Option<DecisionProcess> = None;
         let mut all_actions_executed: Vec<neomind_storage::ActionExecuted> = Vec::new();
         let mut all_notifications_sent: Vec<neomind_storage::NotificationSent> = Vec::new();
+        let mut all_agent_messages: Vec<AgentMessage> = Vec::new();
 
         tracing::info!(
             agent_id = %agent.id,
@@ -2436,6 +2871,7 @@ Respond in JSON format:
             // Collect results from this round
             all_actions_executed.extend(execution_result.actions_executed.clone());
             all_notifications_sent.extend(execution_result.notifications_sent.clone());
+            all_agent_messages.extend(execution_result.agent_messages.clone());
 
             // Store the final decision process (last round takes precedence)
             final_decision_process = Some(decision_process.clone());
@@ -2471,6 +2907,7 @@ Respond in JSON format:
                     || d.action.to_lowercase().contains("further")
                     || d.action.to_lowercase().contains("下一步")
                     || d.action.to_lowercase().contains("继续")
+                    || d.action.starts_with("agent:")
             });
 
             if !needs_more_work {
@@ -2545,6 +2982,7 @@ Respond in JSON format:
                 summary_conclusion
             },
             success_rate,
+            agent_messages: all_agent_messages,
         };
 
         tracing::info!(
@@ -2565,8 +3003,15 @@ Respond in JSON format:
         let max_retries = 3u32;
         let mut last_error = None;
 
+        // LLM calls made during the execution count against the agent's budget
+        let usage_context = UsageContext::for_agent(&context.agent.id);
         for attempt in 0..=max_retries {
-            match self.execute_internal(context.clone()).await {
+            match with_usage_context(
+                usage_context.clone(),
+                self.execute_internal(context.clone()),
+            )
+            .await
+            {
                 Ok(result) => return Ok(result),
                 Err(e) => {
                     tracing::warn!(
@@ -2598,10 +3043,13 @@ Respond in JSON format:
         let max_retries = 3u32;
         let mut last_error = None;
 
+        let usage_context = UsageContext::for_agent(&context.agent.id);
         for attempt in 0..=max_retries {
-            match self
-                .execute_internal_with_event(context.clone(), event_data.clone())
-                .await
+            match with_usage_context(
+                usage_context.clone(),
+                self.execute_internal_with_event(context.clone(), event_data.clone()),
+            )
+            .await
             {
                 Ok(result) => return Ok(result),
                 Err(e) => {
@@ -2630,10 +3078,16 @@ Respond in JSON format:
         &self,
         context: ExecutionContext,
     ) -> AgentResult<(DecisionProcess, StorageExecutionResult)> {
-        let mut agent = context.agent;
+        let mut agent = context.agent.clone();
         let agent_id = agent.id.clone();
         let execution_id = context.execution_id.clone();
 
+        // A round interrupted after its LLM turn continues with its decisions
+        let resumed_round = self
+            .load_checkpoint(&context)
+            .await
+            .and_then(|checkpoint| checkpoint.round);
+
         // Progress: Collecting data
         self.send_progress(
             &agent_id,
@@ -2645,7 +3099,10 @@ Respond in JSON format:
         .await;
 
         // Step 1: Collect data
-        let data_collected = self.collect_data(&agent).await?;
+        let data_collected = match resumed_round {
+            Some(ref round) => round.data_collected.clone(),
+            None => self.collect_data(&agent).await?,
+        };
 
         // Send thinking events for each data source collected
         let mut step_num = 1;
@@ -2680,10 +3137,12 @@ Respond in JSON format:
             match self.parse_intent(&agent.user_prompt).await {
                 Ok(intent) => {
                     // Update agent with parsed intent
-                    let _ = self
-                        .store
-                        .update_agent_parsed_intent(&agent.id, Some(intent.clone()))
-                        .await;
+                    if !context.dry_run {
+                        let _ = self
+                            .store
+                            .update_agent_parsed_intent(&agent.id, Some(intent.clone()))
+                            .await;
+                    }
                     Some(intent)
                 }
                 Err(e) => {
@@ -2701,14 +3160,35 @@ Respond in JSON format:
         }
 
         // Step 2: Analyze situation with LLM
-        let (situation_analysis, reasoning_steps, decisions, conclusion) = self
-            .analyze_situation_with_intent(
-                &agent,
-                &data_collected,
-                parsed_intent.as_ref(),
-                &context.execution_id,
-            )
-            .await?;
+        let (situation_analysis, reasoning_steps, decisions, conclusion) = match resumed_round {
+            Some(round) => {
+                tracing::info!(
+                    agent_id = %agent_id,
+                    execution_id = %execution_id,
+                    pending_decisions = round.pending_decisions().len(),
+                    "Resuming execution round from checkpoint"
+                );
+                (
+                    round.situation_analysis,
+                    round.reasoning_steps,
+                    round.decisions,
+                    round.conclusion,
+                )
+            }
+            None => {
+                let analysis = self
+                    .analyze_situation_with_intent(
+                        &agent,
+                        &data_collected,
+                        parsed_intent.as_ref(),
+                        &context.execution_id,
+                    )
+                    .await?;
+                self.checkpoint_llm_turn(&context, &data_collected, &analysis)
+                    .await;
+                analysis
+            }
+        };
 
         // Send thinking event for analysis completion
         self.send_thinking(
@@ -2744,8 +3224,9 @@ Respond in JSON format:
         step_num += 1;
 
         // Step 3: Execute decisions
-        let (actions_executed, notifications_sent) =
-            self.execute_decisions(&agent, &decisions).await?;
+        let (actions_executed, notifications_sent, agent_messages) = self
+            .execute_decisions_checkpointed(&agent, &decisions, &context, &situation_analysis)
+            .await?;
 
         // Send thinking events for each action executed
         for action in &actions_executed {
@@ -2815,10 +3296,12 @@ Respond in JSON format:
             .await?;
 
         // Save updated memory
-        self.store
-            .update_agent_memory(&agent.id, updated_memory.clone())
-            .await
-            .map_err(|e| NeoMindError::Storage(format!("Failed to update memory: {}", e)))?;
+        if !context.dry_run {
+            self.store
+                .update_agent_memory(&agent.id, updated_memory.clone())
+                .await
+                .map_err(|e| NeoMindError::Storage(format!("Failed to update memory: {}", e)))?;
+        }
 
         // Calculate confidence from reasoning
         let confidence = if reasoning_steps.is_empty() {
@@ -2833,7 +3316,7 @@ Respond in JSON format:
         let cleaned_conclusion = clean_and_truncate_text(&conclusion, 200);
 
         // Clean reasoning step descriptions
-        let cleaned_steps: Vec<neomind_storage::ReasoningStep> = reasoning_steps
+        let mut cleaned_steps: Vec<neomind_storage::ReasoningStep> = reasoning_steps
             .into_iter()
             .map(|mut step| {
                 step.description = clean_and_truncate_text(&step.description, 150);
@@ -2841,6 +3324,9 @@ Respond in JSON format:
             })
             .collect();
 
+        // Fold answers of delegated tasks into the reasoning
+        Self::add_delegation_steps(&agent.id, &agent_messages, &mut cleaned_steps);
+
         // Clean decision fields
         let cleaned_decisions: Vec<neomind_storage::Decision> = decisions
             .into_iter()
@@ -2874,6 +3360,7 @@ Respond in JSON format:
             notifications_sent,
             summary: conclusion,
             success_rate,
+            agent_messages,
         };
 
         Ok((decision_process, execution_result))
@@ -2999,8 +3486,16 @@ Respond in JSON format:
         step_num += 1;
 
         // Step 3: Execute decisions
-        let (actions_executed, notifications_sent) =
-            self.execute_decisions(&agent, &decisions).await?;
+        let (actions_executed, notifications_sent, agent_messages) = self
+            .execute_decisions(
+                &agent,
+                &decisions,
+                &execution_id,
+                &situation_analysis,
+                context.dry_run,
+                &context.delegation_chain,
+            )
+            .await?;
 
         // Send thinking events for each action executed
         for action in &actions_executed {
@@ -3088,7 +3583,7 @@ Respond in JSON format:
         let cleaned_conclusion = clean_and_truncate_text(&conclusion, 200);
 
         // Clean reasoning step descriptions
-        let cleaned_steps: Vec<neomind_storage::ReasoningStep> = reasoning_steps
+        let mut cleaned_steps: Vec<neomind_storage::ReasoningStep> = reasoning_steps
             .into_iter()
             .map(|mut step| {
                 step.description = clean_and_truncate_text(&step.description, 150);
@@ -3096,6 +3591,9 @@ Respond in JSON format:
             })
             .collect();
 
+        // Fold answers of delegated tasks into the reasoning
+        Self::add_delegation_steps(&agent.id, &agent_messages, &mut cleaned_steps);
+
         // Clean decision fields
         let cleaned_decisions: Vec<neomind_storage::Decision> = decisions
             .into_iter()
@@ -3129,6 +3627,7 @@ Respond in JSON format:
             notifications_sent,
             summary: conclusion,
             success_rate,
+            agent_messages,
         };
 
         Ok((decision_process, execution_result))
@@ -3171,13 +3670,39 @@ Respond in JSON format:
             }
         }
 
-        let device_resources: Vec<_> = agent
+        let mut device_resources: Vec<_> = agent
             .resources
             .iter()
             .filter(|r| r.resource_type == ResourceType::Device)
             .map(|r| r.resource_id.clone())
             .collect();
 
+        // Expand device group resources into their current member devices
+        if let Some(device_service) = &self.device_service {
+            for resource in agent
+                .resources
+                .iter()
+                .filter(|r| r.resource_type == ResourceType::DeviceGroup)
+            {
+                let selector = match DeviceSelector::parse(&resource.resource_id) {
+                    Ok(selector) if !selector.is_empty() => selector,
+                    _ => {
+                        tracing::warn!(
+                            agent_id = %agent.id,
+                            selector = %resource.resource_id,
+                            "[COLLECT] Invalid device group selector"
+                        );
+                        continue;
+                    }
+                };
+                for device in device_service.select_devices(&selector).await {
+                    if !device_resources.contains(&device.device_id) {
+                        device_resources.push(device.device_id);
+                    }
+                }
+            }
+        }
+
         let extension_metric_resources: Vec<_> = agent
             .resources
             .iter()
@@ -4132,6 +4657,8 @@ Respond in JSON format:
             std::collections::HashMap::new();
         let mut extension_commands: std::collections::HashMap<String, Vec<&AgentResource>> =
             std::collections::HashMap::new();
+        let mut mcp_tools: Vec<&AgentResource> = Vec::new();
+        let mut delegates: Vec<&AgentResource> = Vec::new();
 
         // Group commands by device or extension
         for resource in &agent.resources {
@@ -4164,11 +4691,17 @@ Respond in JSON format:
                         .or_default()
                         .push(resource);
                 }
+                ResourceType::McpTool => mcp_tools.push(resource),
+                ResourceType::Agent => delegates.push(resource),
                 _ => {}
             }
         }
 
-        if device_commands.is_empty() && extension_commands.is_empty() {
+        if device_commands.is_empty()
+            && extension_commands.is_empty()
+            && mcp_tools.is_empty()
+            && delegates.is_empty()
+        {
             return "无可用命令".to_string();
         }
 
@@ -4264,12 +4797,79 @@ Respond in JSON format:
             }
         }
 
+        // Add tools of external MCP servers (format: "mcp:server_id:tool_name")
+        if !mcp_tools.is_empty() {
+            descriptions.push("## 可用MCP工具\n".to_string());
+            let manager = neomind_tools::mcp::get_mcp_client_manager();
+
+            for resource in &mcp_tools {
+                let display_name = if !resource.name.is_empty() {
+                    &resource.name
+                } else {
+                    &resource.resource_id
+                };
+                descriptions.push(format!("- `{}` - {}", resource.resource_id, display_name));
+
+                // Parameters come from the schema advertised by the server
+                let parts: Vec<&str> = resource.resource_id.splitn(3, ':').collect();
+                if parts.len() == 3 {
+                    match manager.tool(parts[1], parts[2]) {
+                        Some(tool) => {
+                            let schema = tool.parameters();
+                            let required: Vec<&str> = schema
+                                .get("required")
+                                .and_then(|r| r.as_array())
+                                .map(|r| r.iter().filter_map(|v| v.as_str()).collect())
+                                .unwrap_or_default();
+                            if let Some(props) =
+                                schema.get("properties").and_then(|p| p.as_object())
+                            {
+                                let param_names: Vec<String> = props
+                                    .keys()
+                                    .map(|name| {
+                                        if required.contains(&name.as_str()) {
+                                            format!("{}(必填)", name)
+                                        } else {
+                                            name.clone()
+                                        }
+                                    })
+                                    .collect();
+                                if !param_names.is_empty() {
+                                    descriptions
+                                        .push(format!("  参数: {}", param_names.join(", ")));
+                                }
+                            }
+                        }
+                        None => descriptions.push("  (MCP服务器当前不可用)".to_string()),
+                    }
+                }
+            }
+
+            descriptions.push(String::new());
+        }
+
+        // Add agents that tasks can be delegated to (format: "agent:agent_id")
+        if !delegates.is_empty() {
+            descriptions.push("## 可委派的Agent\n".to_string());
+            for resource in &delegates {
+                let display_name = if !resource.name.is_empty() {
+                    &resource.name
+                } else {
+                    &resource.resource_id
+                };
+                descriptions.push(format!("- `{}` - {}", resource.resource_id, display_name));
+            }
+            descriptions.push(String::new());
+        }
+
         // Add usage instructions
         descriptions.push(
             "### 命令执行说明\n\
              在 decisions 中，如需执行命令，请使用以下格式：\n\
              - 设备命令: action: \"device_id:command_name\" (例如: \"light1:turn_on\")\n\
              - 扩展工具: action: \"extension:ext_id:command_name\" (例如: \"extension:weather:get_forecast\")\n\
+             - MCP工具: action: \"mcp:server_id:tool_name\"，rationale 为 JSON 参数对象\n\
+             - 委派Agent: action: \"agent:agent_id\"，description 为任务描述，rationale 可为 JSON 参数对象\n\
              - decision_type: \"command\"\n\
              - description: 命令描述\n\
              - rationale: 执行原因".to_string()
@@ -4318,7 +4918,7 @@ Respond in JSON format:
                         .or_default()
                         .push(resource);
                 }
-                ResourceType::Device => {
+                ResourceType::Device | ResourceType::DeviceGroup => {
                     device_resources.push(resource);
                 }
                 _ => {}
@@ -5022,6 +5622,8 @@ Respond in JSON format:
 
         // Add timeout for LLM generation (5 minutes max)
         const LLM_TIMEOUT_SECS: u64 = 300;
+        // Agents that opt into response caching get a cached runtime, so an
+        // unchanged situation reuses the earlier answer
         let llm_result = match tokio::time::timeout(
             std::time::Duration::from_secs(LLM_TIMEOUT_SECS),
             llm.generate(input),
@@ -5682,6 +6284,7 @@ Respond in JSON format:
             parameters: serde_json::to_value(&parameters).unwrap_or_default(),
             success,
             result,
+            approval_id: None,
         })
     }
 
@@ -5742,167 +6345,1154 @@ Respond in JSON format:
             parameters: args_value,
             success,
             result,
+            approval_id: None,
         })
     }
 
-    /// Parse command from decision.action field.
-    ///
-    /// Expected formats:
-    /// - "device_id:command_name" -> device command
-    /// - "extension:ext_id:command_name" -> extension command
+    /// Execute a tool of an external MCP server and return ActionExecuted record.
     ///
-    /// Returns: (type, id, command_name) where type is "device" or "extension"
-    fn parse_command_from_action(action: &str) -> Option<(String, String, String)> {
-        let action = action.trim();
+    /// Only tools selected as resources of the agent may be called; the
+    /// arguments are taken from the decision rationale when it is JSON.
+    async fn execute_mcp_tool_for_agent(
+        &self,
+        agent: &AiAgent,
+        server_id: &str,
+        tool_name: &str,
+        decision: &Decision,
+    ) -> neomind_storage::ActionExecuted {
+        let resource_id = format!("mcp:{}:{}", server_id, tool_name);
 
-        // Try to parse as "prefix:id:command_name"
-        if let Some(colon_pos) = action.find(':') {
-            let prefix = &action[..colon_pos];
-            let rest = &action[colon_pos + 1..];
+        tracing::info!(
+            agent_id = %agent.id,
+            server_id = %server_id,
+            tool = %tool_name,
+            "Executing MCP tool from LLM decision"
+        );
 
-            // Check if it's "extension:ext_id:command_name"
-            if prefix == "extension" || prefix == "ext" {
-                if let Some(second_colon) = rest.find(':') {
-                    let ext_id = &rest[..second_colon];
-                    let command_name = &rest[second_colon + 1..];
-                    if !ext_id.is_empty() && !command_name.is_empty() {
-                        return Some((
-                            "extension".to_string(),
-                            ext_id.trim().to_string(),
-                            command_name.trim().to_string(),
-                        ));
+        let args_value = if decision.rationale.trim().is_empty() {
+            serde_json::json!({})
+        } else {
+            serde_json::from_str::<serde_json::Value>(&decision.rationale)
+                .ok()
+                .filter(|v| v.is_object())
+                .unwrap_or_else(|| serde_json::json!({}))
+        };
+
+        let selected = agent
+            .resources
+            .iter()
+            .any(|r| r.resource_type == ResourceType::McpTool && r.resource_id == resource_id);
+
+        let (success, result) = if !selected {
+            (
+                false,
+                Some(format!(
+                    "Failed: {} is not a resource of this agent",
+                    resource_id
+                )),
+            )
+        } else {
+            match neomind_tools::mcp::get_mcp_client_manager().tool(server_id, tool_name) {
+                None => (
+                    false,
+                    Some(format!("Failed: MCP tool {} is not available", resource_id)),
+                ),
+                Some(tool) => match tool.execute(args_value.clone()).await {
+                    Ok(output) if output.success => {
+                        (true, Some(format!("Success: {}", output.data)))
                     }
-                }
+                    Ok(output) => (
+                        false,
+                        Some(format!("Failed: {}", output.error.unwrap_or_default())),
+                    ),
+                    Err(e) => (false, Some(format!("Failed: {}", e))),
+                },
             }
+        };
 
-            // Otherwise treat as "device_id:command_name"
-            if !prefix.is_empty() && !rest.is_empty() {
-                return Some((
-                    "device".to_string(),
-                    prefix.trim().to_string(),
-                    rest.trim().to_string(),
-                ));
-            }
+        if !success {
+            tracing::warn!(
+                agent_id = %agent.id,
+                tool = %resource_id,
+                result = ?result,
+                "Failed to execute MCP tool"
+            );
         }
 
-        // Try to parse as "device:command" (common format)
-        if action.contains("device:") || action.contains("设备:") {
-            // Extract device:command pattern using regex-like parsing
-            let parts: Vec<&str> = action
-                .split([':', '：'])
-                .map(|s| s.trim())
-                .collect();
-            if parts.len() >= 3 {
-                // Format: "device:xxx:command" or similar
-                let cmd_keyword_idx = parts
-                    .iter()
-                    .position(|&p| p == "device" || p == "设备" || p == "command" || p == "指令");
-                if let Some(idx) = cmd_keyword_idx {
-                    if idx + 1 < parts.len() {
-                        return Some((
-                            "device".to_string(),
-                            parts[idx + 1].to_string(),
-                            parts[idx + 2].to_string(),
-                        ));
-                    }
-                }
+        neomind_storage::ActionExecuted {
+            action_type: "mcp_tool".to_string(),
+            description: format!(
+                "Call {} on MCP server {} (reason: {})",
+                tool_name, server_id, decision.description
+            ),
+            target: resource_id,
+            parameters: args_value,
+            success,
+            result,
+            approval_id: None,
+        }
+    }
+
+    /// Run a device, extension or MCP command chosen by the LLM.
+    async fn run_command(
+        &self,
+        agent: &AiAgent,
+        cmd_type: &str,
+        target_id: &str,
+        command_name: &str,
+        decision: &Decision,
+    ) -> Option<neomind_storage::ActionExecuted> {
+        match cmd_type {
+            "device" => {
+                self.execute_single_command(agent, target_id, command_name, decision)
+                    .await
+            }
+            "extension" => {
+                self.execute_extension_command_for_agent(agent, target_id, command_name, decision)
+                    .await
+            }
+            "mcp" => Some(
+                self.execute_mcp_tool_for_agent(agent, target_id, command_name, decision)
+                    .await,
+            ),
+            _ => {
+                tracing::warn!(
+                    agent_id = %agent.id,
+                    cmd_type = %cmd_type,
+                    "Unknown command type"
+                );
+                None
             }
         }
+    }
 
-        None
+    /// Run a command unless the agent's approval policy holds it back.
+    async fn execute_command_for_agent(
+        &self,
+        agent: &AiAgent,
+        execution_id: &str,
+        reasoning: &str,
+        cmd_type: &str,
+        target_id: &str,
+        command_name: &str,
+        decision: &Decision,
+        dry_run: bool,
+    ) -> Option<neomind_storage::ActionExecuted> {
+        if let Some(intercepted) = self
+            .intercept_command(
+                agent,
+                execution_id,
+                reasoning,
+                cmd_type,
+                target_id,
+                command_name,
+                decision,
+                dry_run,
+            )
+            .await
+        {
+            return Some(intercepted);
+        }
+        self.run_command(agent, cmd_type, target_id, command_name, decision)
+            .await
     }
 
-    /// Execute decisions - real command execution.
-    async fn execute_decisions(
+    /// Stop a command before it runs, if it must not run now.
+    ///
+    /// In a dry run side-effecting commands are simulated; otherwise commands
+    /// covered by the approval policy are queued.
+    async fn intercept_command(
         &self,
         agent: &AiAgent,
-        decisions: &[Decision],
-    ) -> AgentResult<(
-        Vec<neomind_storage::ActionExecuted>,
-        Vec<neomind_storage::NotificationSent>,
-    )> {
-        let mut actions_executed = Vec::new();
-        let mut notifications_sent = Vec::new();
+        execution_id: &str,
+        reasoning: &str,
+        cmd_type: &str,
+        target_id: &str,
+        command_name: &str,
+        decision: &Decision,
+        dry_run: bool,
+    ) -> Option<neomind_storage::ActionExecuted> {
+        if dry_run {
+            return self
+                .simulate_command(agent, cmd_type, target_id, command_name, decision)
+                .await;
+        }
+        self.hold_for_approval(
+            agent,
+            execution_id,
+            reasoning,
+            cmd_type,
+            target_id,
+            command_name,
+            decision,
+        )
+        .await
+    }
 
-        for decision in decisions {
-            // === Handle query decisions - Agent requesting specific time range data ===
-            // Format: query:device_id:metric:time_range (e.g., query:sensor1:temperature:24h)
-            if decision.action.starts_with("query:") {
-                let parts: Vec<&str> = decision.action.split(':').collect();
-                if parts.len() >= 4 {
-                    let _device_id = parts[1];
-                    let _metric = parts[2];
-                    let time_spec = parts[3];
+    /// Record a side-effecting command as an intended action without running it.
+    ///
+    /// Returns `None` for read-only commands, which run normally: MCP tools
+    /// with a read-only hint and extension commands whose agent resource is
+    /// flagged with `"read_only": true`.
+    async fn simulate_command(
+        &self,
+        agent: &AiAgent,
+        cmd_type: &str,
+        target_id: &str,
+        command_name: &str,
+        decision: &Decision,
+    ) -> Option<neomind_storage::ActionExecuted> {
+        let side_effects = match cmd_type {
+            "extension" => {
+                let resource_id = format!("extension:{}:{}", target_id, command_name);
+                !agent.resources.iter().any(|r| {
+                    r.resource_type == ResourceType::ExtensionTool
+                        && r.resource_id == resource_id
+                        && r.config
+                            .get("read_only")
+                            .and_then(|v| v.as_bool())
+                            .unwrap_or(false)
+                })
+            }
+            "mcp" => neomind_tools::mcp::get_mcp_client_manager()
+                .tool(target_id, command_name)
+                .is_none_or(|tool| !tool.is_read_only()),
+            _ => true,
+        };
+        if !side_effects {
+            return None;
+        }
 
-                    // Parse time specification and log the request
-                    // Note: This is a informational action - the actual time range
-                    // adjustment needs to happen in the data collection phase
-                    tracing::info!(
-                        agent_id = %agent.id,
-                        time_spec = %time_spec,
-                        decision_action = %decision.action,
-                        "Agent requested data with specific time range"
-                    );
+        let action = self
+            .pending_action(agent, cmd_type, target_id, command_name, decision)
+            .await;
+        let needs_approval = agent
+            .approval_policy
+            .as_ref()
+            .is_some_and(|policy| policy.requires_approval(&action));
 
-                    // Record as a "query" action for tracking
-                    actions_executed.push(neomind_storage::ActionExecuted {
-                        action_type: "data_query".to_string(),
-                        description: format!("Query data with time range: {}", time_spec),
-                        target: format!("{}:{}", parts[1], parts[2]),
-                        parameters: serde_json::json!({"time_spec": time_spec}),
-                        success: true,
-                        result: Some(format!("Time range request noted: {}", time_spec)),
-                    });
-                }
-            }
+        tracing::info!(
+            agent_id = %agent.id,
+            action_type = %action.action_type,
+            target = %action.target,
+            command = %action.command,
+            "Dry run: command recorded, not executed"
+        );
 
-            // === NEW: Handle LLM-driven command decisions ===
-            // When LLM returns decision_type == "command", parse the action field
-            // and execute only that specific command
-            if decision.decision_type == "command" {
-                if let Some((cmd_type, target_id, command_name)) =
-                    Self::parse_command_from_action(&decision.action)
-                {
-                    tracing::info!(
-                        agent_id = %agent.id,
-                        cmd_type = %cmd_type,
-                        target_id = %target_id,
-                        command_name = %command_name,
-                        decision_action = %decision.action,
-                        "Executing LLM-specified command"
-                    );
+        Some(neomind_storage::ActionExecuted {
+            description: format!(
+                "Execute {} on {} (reason: {})",
+                action.command, action.target, decision.description
+            ),
+            action_type: action.action_type,
+            target: action.target,
+            parameters: action.parameters,
+            success: true,
+            result: Some(if needs_approval {
+                "Dry run: not executed (would require approval)".to_string()
+            } else {
+                "Dry run: not executed".to_string()
+            }),
+            approval_id: None,
+        })
+    }
 
-                    match cmd_type.as_str() {
-                        "extension" => {
-                            // Execute extension command
-                            if let Some(action_executed) = self
-                                .execute_extension_command_for_agent(
-                                    agent,
-                                    &target_id,
-                                    &command_name,
-                                    decision,
-                                )
-                                .await
-                            {
-                                actions_executed.push(action_executed);
-                            }
-                        }
-                        "device" => {
-                            // Execute device command
-                            if let Some(action_executed) = self
-                                .execute_single_command(agent, &target_id, &command_name, decision)
-                                .await
-                            {
-                                actions_executed.push(action_executed);
-                            }
-                        }
-                        _ => {
-                            tracing::warn!(
-                                agent_id = %agent.id,
-                                cmd_type = %cmd_type,
-                                "Unknown command type"
-                            );
+    /// Describe a command as an action that may need approval.
+    async fn pending_action(
+        &self,
+        agent: &AiAgent,
+        cmd_type: &str,
+        target_id: &str,
+        command_name: &str,
+        decision: &Decision,
+    ) -> PendingAction {
+        let rationale_args = serde_json::from_str::<serde_json::Value>(&decision.rationale).ok();
+        let (action_type, target, device_type, parameters) = match cmd_type {
+            "device" => {
+                let resource_id = format!("{}:{}", target_id, command_name);
+                let parameters = agent
+                    .resources
+                    .iter()
+                    .find(|r| {
+                        r.resource_type == ResourceType::Command && r.resource_id == resource_id
+                    })
+                    .and_then(|r| r.config.get("parameters").cloned())
+                    .unwrap_or_else(|| serde_json::json!({}));
+                let device_type = match self.device_service {
+                    Some(ref device_service) => device_service
+                        .get_device(target_id)
+                        .await
+                        .map(|device| device.device_type),
+                    None => None,
+                };
+                (
+                    "device_command",
+                    target_id.to_string(),
+                    device_type,
+                    parameters,
+                )
+            }
+            "extension" => (
+                "extension_command",
+                target_id.to_string(),
+                None,
+                rationale_args
+                    .unwrap_or_else(|| serde_json::json!({ "reason": decision.rationale })),
+            ),
+            _ => (
+                "mcp_tool",
+                format!("mcp:{}:{}", target_id, command_name),
+                None,
+                rationale_args
+                    .filter(|v| v.is_object())
+                    .unwrap_or_else(|| serde_json::json!({})),
+            ),
+        };
+
+        PendingAction {
+            action_type: action_type.to_string(),
+            target,
+            command: command_name.to_string(),
+            device_type,
+            parameters,
+        }
+    }
+
+    /// Queue a command for approval if the agent's policy requires it.
+    ///
+    /// Returns the pending action record, or `None` if the command may run.
+    async fn hold_for_approval(
+        &self,
+        agent: &AiAgent,
+        execution_id: &str,
+        reasoning: &str,
+        cmd_type: &str,
+        target_id: &str,
+        command_name: &str,
+        decision: &Decision,
+    ) -> Option<neomind_storage::ActionExecuted> {
+        let policy = agent.approval_policy.as_ref()?;
+        if policy.rules.is_empty() {
+            return None;
+        }
+        let action = self
+            .pending_action(agent, cmd_type, target_id, command_name, decision)
+            .await;
+        if !policy.requires_approval(&action) {
+            return None;
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let request = ApprovalRequest {
+            id: uuid::Uuid::new_v4().to_string(),
+            agent_id: agent.id.clone(),
+            agent_name: agent.name.clone(),
+            execution_id: execution_id.to_string(),
+            action,
+            decision: decision.clone(),
+            reasoning: reasoning.to_string(),
+            status: ApprovalStatus::Pending,
+            created_at: now,
+            expires_at: now + policy.expires_after_secs as i64,
+            decided_at: None,
+            decided_by: None,
+            comment: None,
+            result: None,
+        };
+
+        // The command never runs unattended, even if the request cannot be stored
+        if let Err(e) = self.store.save_approval(&request).await {
+            tracing::warn!(
+                agent_id = %agent.id,
+                error = %e,
+                "Failed to queue action for approval"
+            );
+            return Some(Self::approval_outcome(
+                &request,
+                format!("Failed: could not queue for approval: {}", e),
+            ));
+        }
+
+        tracing::info!(
+            agent_id = %agent.id,
+            approval_id = %request.id,
+            action_type = %request.action.action_type,
+            target = %request.action.target,
+            command = %request.action.command,
+            "Action held for approval"
+        );
+        self.notify_approvers(&request).await;
+
+        let mut held = Self::approval_outcome(
+            &request,
+            format!(
+                "Awaiting approval until {}",
+                chrono::DateTime::from_timestamp(request.expires_at, 0)
+                    .map(|t| t.to_rfc3339())
+                    .unwrap_or_default()
+            ),
+        );
+        held.approval_id = Some(request.id.clone());
+        Some(held)
+    }
+
+    /// Record of an action that did not run (yet) because of an approval request.
+    fn approval_outcome(
+        request: &ApprovalRequest,
+        result: String,
+    ) -> neomind_storage::ActionExecuted {
+        neomind_storage::ActionExecuted {
+            action_type: request.action.action_type.clone(),
+            description: format!(
+                "Execute {} on {} (reason: {})",
+                request.action.command, request.action.target, request.decision.description
+            ),
+            target: request.action.target.clone(),
+            parameters: request.action.parameters.clone(),
+            success: false,
+            result: Some(result),
+            approval_id: None,
+        }
+    }
+
+    /// Notify approvers about a queued action via the message channels.
+    async fn notify_approvers(&self, request: &ApprovalRequest) {
+        let Some(ref message_manager) = self.message_manager else {
+            return;
+        };
+        use neomind_messages::{Message, MessageSeverity};
+
+        let mut msg = Message::alert(
+            MessageSeverity::Warning,
+            format!("Approval required: {}", request.agent_name),
+            format!(
+                "Agent '{}' wants to execute {} on {}.\nReason: {}\nApprove or deny request {} before it expires.",
+                request.agent_name,
+                request.action.command,
+                request.action.target,
+                request.decision.description,
+                request.id
+            ),
+            request.agent_id.clone(),
+        )
+        .with_metadata(serde_json::json!({
+            "approval_id": request.id,
+            "execution_id": request.execution_id,
+            "action": request.action,
+            "expires_at": request.expires_at,
+        }))
+        .with_tags(vec!["approval".to_string()]);
+        msg.source_type = "agent".to_string();
+
+        if let Err(e) = message_manager.create_message(msg).await {
+            tracing::warn!(
+                approval_id = %request.id,
+                error = %e,
+                "Failed to notify approvers"
+            );
+        }
+    }
+
+    /// Approve or deny a queued action.
+    ///
+    /// The request is marked decided before anything runs, so an action is
+    /// executed at most once even if approvers race. An approved action runs
+    /// now; either way the outcome replaces the pending action in the
+    /// execution record.
+    pub async fn resolve_approval(
+        &self,
+        approval_id: &str,
+        approve: bool,
+        decided_by: &str,
+        comment: Option<String>,
+    ) -> AgentResult<ApprovalRequest> {
+        self.expire_approvals().await?;
+
+        let status = if approve {
+            ApprovalStatus::Approved
+        } else {
+            ApprovalStatus::Denied
+        };
+        let mut request = self
+            .store
+            .decide_approval(
+                approval_id,
+                status,
+                decided_by,
+                comment,
+                chrono::Utc::now().timestamp(),
+            )
+            .await?;
+
+        let mut outcome = if approve {
+            let agent = self
+                .store
+                .get_agent(&request.agent_id)
+                .await
+                .map_err(|e| NeoMindError::Storage(e.to_string()))?;
+            let (cmd_type, target_id) = match request.action.action_type.as_str() {
+                "device_command" => ("device", request.action.target.clone()),
+                "extension_command" => ("extension", request.action.target.clone()),
+                _ => (
+                    "mcp",
+                    Self::parse_command_from_action(&request.action.target)
+                        .map(|(_, server_id, _)| server_id)
+                        .unwrap_or_default(),
+                ),
+            };
+            let executed = match agent {
+                Some(agent) => {
+                    self.run_command(
+                        &agent,
+                        cmd_type,
+                        &target_id,
+                        &request.action.command,
+                        &request.decision,
+                    )
+                    .await
+                }
+                None => None,
+            };
+            executed.unwrap_or_else(|| {
+                Self::approval_outcome(
+                    &request,
+                    "Failed: agent or service no longer available".to_string(),
+                )
+            })
+        } else {
+            let reason = match request.comment {
+                Some(ref comment) => format!("Denied by {}: {}", decided_by, comment),
+                None => format!("Denied by {}", decided_by),
+            };
+            Self::approval_outcome(&request, reason)
+        };
+        outcome.approval_id = Some(request.id.clone());
+        request.result = Some(outcome.clone());
+
+        tracing::info!(
+            approval_id = %request.id,
+            agent_id = %request.agent_id,
+            status = ?request.status,
+            decided_by = %decided_by,
+            "Approval request decided"
+        );
+
+        self.store
+            .save_approval(&request)
+            .await
+            .map_err(|e| NeoMindError::Storage(e.to_string()))?;
+        self.store
+            .update_execution_action(&request.execution_id, &outcome)
+            .await
+            .map_err(|e| NeoMindError::Storage(e.to_string()))?;
+        Ok(request)
+    }
+
+    /// Expire approval requests nobody decided on in time.
+    ///
+    /// The agent manager runs this periodically; it also runs before
+    /// approval requests are listed or decided, so an expired request is
+    /// never offered or approved.
+    pub async fn expire_approvals(&self) -> AgentResult<Vec<ApprovalRequest>> {
+        let expired = self
+            .store
+            .expire_approvals(chrono::Utc::now().timestamp())
+            .await
+            .map_err(|e| NeoMindError::Storage(e.to_string()))?;
+
+        for request in &expired {
+            let mut outcome = Self::approval_outcome(request, "Approval expired".to_string());
+            outcome.approval_id = Some(request.id.clone());
+            if let Err(e) = self
+                .store
+                .update_execution_action(&request.execution_id, &outcome)
+                .await
+            {
+                tracing::warn!(
+                    approval_id = %request.id,
+                    error = %e,
+                    "Failed to record expired approval"
+                );
+            }
+        }
+
+        Ok(expired)
+    }
+
+    /// Parse command from decision.action field.
+    ///
+    /// Expected formats:
+    /// - "device_id:command_name" -> device command
+    /// - "extension:ext_id:command_name" -> extension command
+    /// - "mcp:server_id:tool_name" -> MCP tool
+    ///
+    /// Returns: (type, id, command_name) where type is "device", "extension" or "mcp"
+    fn parse_command_from_action(action: &str) -> Option<(String, String, String)> {
+        let action = action.trim();
+
+        // Try to parse as "prefix:id:command_name"
+        if let Some(colon_pos) = action.find(':') {
+            let prefix = &action[..colon_pos];
+            let rest = &action[colon_pos + 1..];
+
+            // Check if it's "extension:ext_id:command_name"
+            if prefix == "extension" || prefix == "ext" {
+                if let Some(second_colon) = rest.find(':') {
+                    let ext_id = &rest[..second_colon];
+                    let command_name = &rest[second_colon + 1..];
+                    if !ext_id.is_empty() && !command_name.is_empty() {
+                        return Some((
+                            "extension".to_string(),
+                            ext_id.trim().to_string(),
+                            command_name.trim().to_string(),
+                        ));
+                    }
+                }
+            }
+
+            // Check if it's "mcp:server_id:tool_name"
+            if prefix == "mcp" {
+                if let Some((server_id, tool_name)) = rest.split_once(':') {
+                    if !server_id.is_empty() && !tool_name.is_empty() {
+                        return Some((
+                            "mcp".to_string(),
+                            server_id.trim().to_string(),
+                            tool_name.trim().to_string(),
+                        ));
+                    }
+                }
+            }
+
+            // Otherwise treat as "device_id:command_name"
+            if !prefix.is_empty() && !rest.is_empty() {
+                return Some((
+                    "device".to_string(),
+                    prefix.trim().to_string(),
+                    rest.trim().to_string(),
+                ));
+            }
+        }
+
+        // Try to parse as "device:command" (common format)
+        if action.contains("device:") || action.contains("设备:") {
+            // Extract device:command pattern using regex-like parsing
+            let parts: Vec<&str> = action
+                .split([':', '：'])
+                .map(|s| s.trim())
+                .collect();
+            if parts.len() >= 3 {
+                // Format: "device:xxx:command" or similar
+                let cmd_keyword_idx = parts
+                    .iter()
+                    .position(|&p| p == "device" || p == "设备" || p == "command" || p == "指令");
+                if let Some(idx) = cmd_keyword_idx {
+                    if idx + 1 < parts.len() {
+                        return Some((
+                            "device".to_string(),
+                            parts[idx + 1].to_string(),
+                            parts[idx + 2].to_string(),
+                        ));
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// Delegate a task to another agent and wait for its answer.
+    ///
+    /// The delegate must be an `Agent` resource of the caller. Delegation is
+    /// refused when the chain would grow beyond [`MAX_DELEGATION_DEPTH`] or
+    /// come back to an agent already in it. The answer is returned as an
+    /// action result, so a following chaining round can reason about it.
+    /// The delegate runs as its own task: when the caller stops waiting for
+    /// it, it still finishes and records its execution.
+    fn delegate_task<'a>(
+        &'a self,
+        agent: &'a AiAgent,
+        target_id: &'a str,
+        decision: &'a Decision,
+        execution_id: &'a str,
+        delegation_chain: &'a [String],
+        dry_run: bool,
+    ) -> BoxFuture<'a, (neomind_storage::ActionExecuted, AgentMessage)> {
+        // Boxed: the delegate runs through this same execution path
+        Box::pin(async move {
+            let resource_id = format!("agent:{}", target_id);
+            let resource = agent
+                .resources
+                .iter()
+                .find(|r| r.resource_type == ResourceType::Agent && r.resource_id == resource_id);
+            let timeout_secs = resource
+                .and_then(|r| r.config.get("timeout_secs"))
+                .and_then(|v| v.as_u64())
+                .unwrap_or(DEFAULT_DELEGATION_TIMEOUT_SECS);
+
+            let mut chain = delegation_chain.to_vec();
+            chain.push(agent.id.clone());
+
+            let mut message = AgentMessage {
+                from_agent_id: agent.id.clone(),
+                from_agent_name: agent.name.clone(),
+                to_agent_id: target_id.to_string(),
+                to_agent_name: resource
+                    .map(|r| r.name.clone())
+                    .filter(|name| !name.is_empty())
+                    .unwrap_or_else(|| target_id.to_string()),
+                task: decision.description.clone(),
+                arguments: serde_json::from_str::<serde_json::Value>(&decision.rationale)
+                    .ok()
+                    .filter(|v| v.is_object())
+                    .unwrap_or_else(|| serde_json::json!({})),
+                response: None,
+                success: false,
+                caller_execution_id: execution_id.to_string(),
+                execution_id: None,
+                timestamp: chrono::Utc::now().timestamp(),
+            };
+
+            let target = if resource.is_none() {
+                Err(format!("{} is not a resource of this agent", resource_id))
+            } else if chain.iter().any(|id| id == target_id) {
+                Err(format!(
+                    "delegation cycle {} -> {}",
+                    chain.join(" -> "),
+                    target_id
+                ))
+            } else if chain.len() > MAX_DELEGATION_DEPTH {
+                Err(format!(
+                    "delegation depth limit of {} reached",
+                    MAX_DELEGATION_DEPTH
+                ))
+            } else {
+                match self.store.get_agent(target_id).await {
+                    Ok(Some(target)) if target.status == neomind_storage::AgentStatus::Paused => {
+                        Err(format!("agent {} is paused", target_id))
+                    }
+                    Ok(Some(target)) => Ok(target),
+                    Ok(None) => Err(format!("agent {} not found", target_id)),
+                    Err(e) => Err(e.to_string()),
+                }
+            };
+
+            match target {
+                Ok(target) => {
+                    message.to_agent_name = target.name.clone();
+                    let delegate_execution_id = uuid::Uuid::new_v4().to_string();
+                    let context = ExecutionContext {
+                        agent: target,
+                        trigger_type: "delegation".to_string(),
+                        event_data: Some(serde_json::json!({
+                            "delegated_by": agent.id,
+                            "task": message.task,
+                            "arguments": message.arguments,
+                        })),
+                        llm_backend: None,
+                        execution_id: delegate_execution_id.clone(),
+                        dry_run,
+                        delegation_chain: chain,
+                        delegated_task: Some(message.clone()),
+                        checkpointed: false,
+                    };
+
+                    tracing::info!(
+                        agent_id = %agent.id,
+                        delegate_id = %target_id,
+                        delegate_execution_id = %delegate_execution_id,
+                        "Delegating task to agent"
+                    );
+
+                    let executor = self.clone();
+                    let run =
+                        tokio::spawn(async move { executor.execute_with_context(context).await });
+                    let timeout = std::time::Duration::from_secs(timeout_secs);
+                    match tokio::time::timeout(timeout, run).await {
+                        Ok(Ok(Ok(record))) => {
+                            if let Some(answer) = record.result.and_then(|r| {
+                                r.agent_messages
+                                    .into_iter()
+                                    .find(|m| m.caller_execution_id == execution_id)
+                            }) {
+                                message = answer;
+                            } else {
+                                message.execution_id = Some(delegate_execution_id);
+                                message.response = record.error.map(|e| format!("Failed: {}", e));
+                            }
+                        }
+                        Ok(Ok(Err(e))) => {
+                            message.execution_id = Some(delegate_execution_id);
+                            message.response = Some(format!("Failed: {}", e));
                         }
+                        Ok(Err(e)) => {
+                            message.execution_id = Some(delegate_execution_id);
+                            message.response = Some(format!("Failed: delegate aborted: {}", e));
+                        }
+                        Err(_) => {
+                            message.execution_id = Some(delegate_execution_id);
+                            message.response =
+                                Some(format!("Failed: no answer within {}s", timeout_secs));
+                        }
+                    }
+                }
+                Err(reason) => {
+                    tracing::warn!(
+                        agent_id = %agent.id,
+                        delegate_id = %target_id,
+                        reason = %reason,
+                        "Delegation refused"
+                    );
+                    message.response = Some(format!("Failed: {}", reason));
+                }
+            }
+
+            let response = message.response.clone().unwrap_or_default();
+            let action = neomind_storage::ActionExecuted {
+                action_type: "agent_delegation".to_string(),
+                description: format!("Delegate to {}: {}", message.to_agent_name, message.task),
+                target: resource_id,
+                parameters: message.arguments.clone(),
+                success: message.success,
+                result: Some(if message.success {
+                    format!("Success: {}", response)
+                } else {
+                    response
+                }),
+                approval_id: None,
+            };
+            (action, message)
+        })
+    }
+
+    /// Describe a delegated task for the delegate's prompt.
+    fn format_delegated_task(message: &AgentMessage) -> String {
+        let mut text = format!(
+            "## 委派任务 (来自Agent: {})\n{}",
+            message.from_agent_name, message.task
+        );
+        if message
+            .arguments
+            .as_object()
+            .is_some_and(|args| !args.is_empty())
+        {
+            text.push_str(&format!("\n参数: {}", message.arguments));
+        }
+        text.push_str("\n请完成该任务，并在 conclusion 中给出答复。");
+        text
+    }
+
+    /// Add a reasoning step for each task this agent delegated.
+    fn add_delegation_steps(
+        agent_id: &str,
+        messages: &[AgentMessage],
+        steps: &mut Vec<neomind_storage::ReasoningStep>,
+    ) {
+        for message in messages.iter().filter(|m| m.from_agent_id == agent_id) {
+            steps.push(neomind_storage::ReasoningStep {
+                step_number: steps.len() as u32 + 1,
+                description: clean_and_truncate_text(
+                    &format!("Delegated to {}: {}", message.to_agent_name, message.task),
+                    150,
+                ),
+                step_type: "delegation".to_string(),
+                input: Some(message.arguments.to_string()),
+                output: message.response.clone().unwrap_or_default(),
+                confidence: if message.success { 1.0 } else { 0.0 },
+            });
+        }
+    }
+
+    /// Write the first checkpoint of an execution, unless it is being resumed.
+    async fn begin_checkpoint(&self, context: &ExecutionContext, started_at: i64) {
+        if let Ok(Some(_)) = self.store.get_checkpoint(&context.execution_id).await {
+            return;
+        }
+        self.save_checkpoint(ExecutionCheckpoint {
+            execution_id: context.execution_id.clone(),
+            agent_id: context.agent.id.clone(),
+            trigger_type: context.trigger_type.clone(),
+            event_data: context.event_data.clone(),
+            started_at,
+            updated_at: started_at,
+            chain_depth: 0,
+            prompt: context.agent.user_prompt.clone(),
+            actions_executed: vec![],
+            notifications_sent: vec![],
+            agent_messages: vec![],
+            round: None,
+        })
+        .await;
+    }
+
+    /// Load the checkpoint of a checkpointed execution.
+    async fn load_checkpoint(&self, context: &ExecutionContext) -> Option<ExecutionCheckpoint> {
+        if !context.checkpointed {
+            return None;
+        }
+        match self.store.get_checkpoint(&context.execution_id).await {
+            Ok(checkpoint) => checkpoint,
+            Err(e) => {
+                tracing::warn!(
+                    execution_id = %context.execution_id,
+                    error = %e,
+                    "Failed to load execution checkpoint"
+                );
+                None
+            }
+        }
+    }
+
+    /// Save a checkpoint. Failures are logged, the execution carries on.
+    async fn save_checkpoint(&self, checkpoint: ExecutionCheckpoint) {
+        let execution_id = checkpoint.execution_id.clone();
+        if let Err(e) = self.try_save_checkpoint(checkpoint).await {
+            tracing::warn!(
+                execution_id = %execution_id,
+                error = %e,
+                "Failed to save execution checkpoint"
+            );
+        }
+    }
+
+    /// Save a checkpoint, returning the failure to the caller.
+    async fn try_save_checkpoint(&self, mut checkpoint: ExecutionCheckpoint) -> AgentResult<()> {
+        checkpoint.updated_at = chrono::Utc::now().timestamp();
+        self.store
+            .save_checkpoint(&checkpoint)
+            .await
+            .map_err(|e| NeoMindError::Storage(format!("Failed to save checkpoint: {}", e)))
+    }
+
+    /// Remove the checkpoint of an execution that has been recorded.
+    async fn finish_checkpoint(&self, execution_id: &str) {
+        if let Err(e) = self.store.delete_checkpoint(execution_id).await {
+            tracing::warn!(
+                execution_id = %execution_id,
+                error = %e,
+                "Failed to remove execution checkpoint"
+            );
+        }
+    }
+
+    /// Checkpoint the LLM turn of a round before any of its decisions run.
+    async fn checkpoint_llm_turn(
+        &self,
+        context: &ExecutionContext,
+        data_collected: &[DataCollected],
+        analysis: &(String, Vec<ReasoningStep>, Vec<Decision>, String),
+    ) {
+        let Some(mut checkpoint) = self.load_checkpoint(context).await else {
+            return;
+        };
+        let (situation_analysis, reasoning_steps, decisions, conclusion) = analysis;
+        checkpoint.round = Some(RoundCheckpoint {
+            data_collected: data_collected.to_vec(),
+            situation_analysis: situation_analysis.clone(),
+            reasoning_steps: reasoning_steps.clone(),
+            decisions: decisions.clone(),
+            conclusion: conclusion.clone(),
+            completed_decisions: 0,
+            in_flight: None,
+            actions_executed: vec![],
+            notifications_sent: vec![],
+            agent_messages: vec![],
+        });
+        self.save_checkpoint(checkpoint).await;
+    }
+
+    /// Execute decisions one at a time, checkpointing around each.
+    ///
+    /// Decisions that finished before an interruption are not run again. One
+    /// that was executing when the process stopped may already have had its
+    /// side effects, so it is recorded as interrupted instead of re-run. If a
+    /// decision cannot be marked as executing, the execution fails before it
+    /// runs.
+    async fn execute_decisions_checkpointed(
+        &self,
+        agent: &AiAgent,
+        decisions: &[Decision],
+        context: &ExecutionContext,
+        reasoning: &str,
+    ) -> AgentResult<(
+        Vec<neomind_storage::ActionExecuted>,
+        Vec<neomind_storage::NotificationSent>,
+        Vec<AgentMessage>,
+    )> {
+        let checkpoint = self.load_checkpoint(context).await.and_then(|mut c| {
+            let round = c.round.take()?;
+            Some((c, round))
+        });
+        let Some((mut checkpoint, mut round)) = checkpoint else {
+            return self
+                .execute_decisions(
+                    agent,
+                    decisions,
+                    &context.execution_id,
+                    reasoning,
+                    context.dry_run,
+                    &context.delegation_chain,
+                )
+                .await;
+        };
+
+        for (index, decision) in decisions.iter().enumerate().skip(round.completed_decisions) {
+            if round.in_flight == Some(index) {
+                tracing::warn!(
+                    agent_id = %agent.id,
+                    execution_id = %context.execution_id,
+                    decision_action = %decision.action,
+                    "Decision was interrupted while executing, not running it again"
+                );
+                let interrupted = neomind_storage::ActionExecuted {
+                    action_type: "interrupted".to_string(),
+                    description: decision.description.clone(),
+                    target: decision.action.clone(),
+                    parameters: serde_json::json!({}),
+                    success: false,
+                    result: Some("Failed: interrupted by a restart".to_string()),
+                    approval_id: None,
+                };
+                round.actions_executed.push(interrupted);
+            } else {
+                // Without the marker a restart would run the decision again
+                round.in_flight = Some(index);
+                checkpoint.round = Some(round.clone());
+                self.try_save_checkpoint(checkpoint.clone()).await?;
+
+                let (actions, notifications, messages) = self
+                    .execute_decisions(
+                        agent,
+                        std::slice::from_ref(decision),
+                        &context.execution_id,
+                        reasoning,
+                        context.dry_run,
+                        &context.delegation_chain,
+                    )
+                    .await?;
+                round.actions_executed.extend(actions);
+                round.notifications_sent.extend(notifications);
+                round.agent_messages.extend(messages);
+            }
+
+            round.in_flight = None;
+            round.completed_decisions = index + 1;
+            checkpoint.round = Some(round.clone());
+            self.save_checkpoint(checkpoint.clone()).await;
+        }
+
+        Ok((
+            round.actions_executed,
+            round.notifications_sent,
+            round.agent_messages,
+        ))
+    }
+
+    /// Execute decisions - real command execution.
+    ///
+    /// Commands matching the agent's approval policy are queued instead of
+    /// executed; `reasoning` is shown to the approvers. With `dry_run`,
+    /// side-effecting commands and alerts are only recorded. Decisions with
+    /// an `agent:{agent_id}` action delegate their task to that agent;
+    /// `delegation_chain` lists the agents that delegated down to this one.
+    async fn execute_decisions(
+        &self,
+        agent: &AiAgent,
+        decisions: &[Decision],
+        execution_id: &str,
+        reasoning: &str,
+        dry_run: bool,
+        delegation_chain: &[String],
+    ) -> AgentResult<(
+        Vec<neomind_storage::ActionExecuted>,
+        Vec<neomind_storage::NotificationSent>,
+        Vec<AgentMessage>,
+    )> {
+        let mut actions_executed = Vec::new();
+        let mut notifications_sent = Vec::new();
+        let mut agent_messages = Vec::new();
+
+        for decision in decisions {
+            // === Delegate a task to another agent ===
+            // Format: agent:agent_id, with the task in the description
+            if let Some(target_id) = decision.action.trim().strip_prefix("agent:") {
+                let (action, message) = self
+                    .delegate_task(
+                        agent,
+                        target_id.trim(),
+                        decision,
+                        execution_id,
+                        delegation_chain,
+                        dry_run,
+                    )
+                    .await;
+                actions_executed.push(action);
+                agent_messages.push(message);
+                continue;
+            }
+
+            // === Handle query decisions - Agent requesting specific time range data ===
+            // Format: query:device_id:metric:time_range (e.g., query:sensor1:temperature:24h)
+            if decision.action.starts_with("query:") {
+                let parts: Vec<&str> = decision.action.split(':').collect();
+                if parts.len() >= 4 {
+                    let _device_id = parts[1];
+                    let _metric = parts[2];
+                    let time_spec = parts[3];
+
+                    // Parse time specification and log the request
+                    // Note: This is a informational action - the actual time range
+                    // adjustment needs to happen in the data collection phase
+                    tracing::info!(
+                        agent_id = %agent.id,
+                        time_spec = %time_spec,
+                        decision_action = %decision.action,
+                        "Agent requested data with specific time range"
+                    );
+
+                    // Record as a "query" action for tracking
+                    actions_executed.push(neomind_storage::ActionExecuted {
+                        action_type: "data_query".to_string(),
+                        description: format!("Query data with time range: {}", time_spec),
+                        target: format!("{}:{}", parts[1], parts[2]),
+                        parameters: serde_json::json!({"time_spec": time_spec}),
+                        success: true,
+                        result: Some(format!("Time range request noted: {}", time_spec)),
+                        approval_id: None,
+                    });
+                }
+            }
+
+            // === NEW: Handle LLM-driven command decisions ===
+            // When LLM returns decision_type == "command", parse the action field
+            // and execute only that specific command
+            if decision.decision_type == "command" {
+                if let Some((cmd_type, target_id, command_name)) =
+                    Self::parse_command_from_action(&decision.action)
+                {
+                    tracing::info!(
+                        agent_id = %agent.id,
+                        cmd_type = %cmd_type,
+                        target_id = %target_id,
+                        command_name = %command_name,
+                        decision_action = %decision.action,
+                        "Executing LLM-specified command"
+                    );
+
+                    // Execute the device command, extension command or MCP tool
+                    if let Some(action_executed) = self
+                        .execute_command_for_agent(
+                            agent,
+                            execution_id,
+                            reasoning,
+                            &cmd_type,
+                            &target_id,
+                            &command_name,
+                            decision,
+                            dry_run,
+                        )
+                        .await
+                    {
+                        actions_executed.push(action_executed);
                     }
                 } else {
                     // Fallback: try to find matching command in resources
@@ -5914,38 +7504,46 @@ Respond in JSON format:
                                 && resource.resource_id.ends_with(&format!(":{}", cmd_name));
                             let is_ext_cmd = resource.resource_type == ResourceType::ExtensionTool
                                 && resource.resource_id.ends_with(&format!(":{}", cmd_name));
+                            let is_mcp_tool = resource.resource_type == ResourceType::McpTool
+                                && resource.resource_id.ends_with(&format!(":{}", cmd_name));
 
-                            if is_device_cmd || is_ext_cmd {
+                            if is_device_cmd || is_ext_cmd || is_mcp_tool {
                                 let parts: Vec<&str> = resource.resource_id.split(':').collect();
 
-                                match resource.resource_type {
-                                    ResourceType::Command => {
-                                        if parts.len() == 2 {
-                                            if let Some(action_executed) = self
-                                                .execute_single_command(
-                                                    agent, parts[0], parts[1], decision,
-                                                )
-                                                .await
-                                            {
-                                                actions_executed.push(action_executed);
-                                            }
-                                            break;
-                                        }
+                                let command = match resource.resource_type {
+                                    ResourceType::Command if parts.len() == 2 => {
+                                        Some(("device", parts[0], parts[1]))
                                     }
-                                    ResourceType::ExtensionTool => {
-                                        if parts.len() >= 3 && parts[0] == "extension" {
-                                            if let Some(action_executed) = self
-                                                .execute_extension_command_for_agent(
-                                                    agent, parts[1], parts[2], decision,
-                                                )
-                                                .await
-                                            {
-                                                actions_executed.push(action_executed);
-                                            }
-                                            break;
-                                        }
+                                    ResourceType::ExtensionTool
+                                        if parts.len() >= 3 && parts[0] == "extension" =>
+                                    {
+                                        Some(("extension", parts[1], parts[2]))
+                                    }
+                                    ResourceType::McpTool
+                                        if parts.len() >= 3 && parts[0] == "mcp" =>
+                                    {
+                                        Some(("mcp", parts[1], parts[2]))
+                                    }
+                                    _ => None,
+                                };
+
+                                if let Some((cmd_type, target_id, command_name)) = command {
+                                    if let Some(action_executed) = self
+                                        .execute_command_for_agent(
+                                            agent,
+                                            execution_id,
+                                            reasoning,
+                                            cmd_type,
+                                            target_id,
+                                            command_name,
+                                            decision,
+                                            dry_run,
+                                        )
+                                        .await
+                                    {
+                                        actions_executed.push(action_executed);
                                     }
-                                    _ => {}
+                                    break;
                                 }
                             }
                         }
@@ -5976,7 +7574,7 @@ Respond in JSON format:
                     decision_action = %decision.action,
                     "Alert-type decision detected, sending notification"
                 );
-                self.send_alert_for_decision(agent, decision, &mut notifications_sent)
+                self.send_alert_for_decision(agent, decision, dry_run, &mut notifications_sent)
                     .await;
             }
 
@@ -5994,6 +7592,23 @@ Respond in JSON format:
                                 let device_id = parts[0];
                                 let command_name = parts[1];
 
+                                if let Some(intercepted) = self
+                                    .intercept_command(
+                                        agent,
+                                        execution_id,
+                                        reasoning,
+                                        "device",
+                                        device_id,
+                                        command_name,
+                                        decision,
+                                        dry_run,
+                                    )
+                                    .await
+                                {
+                                    actions_executed.push(intercepted);
+                                    continue;
+                                }
+
                                 // Get parameters from resource config
                                 let parameters = resource
                                     .config
@@ -6046,6 +7661,7 @@ Respond in JSON format:
                                         .unwrap_or_default(),
                                     success,
                                     result,
+                                    approval_id: None,
                                 });
                             }
                         }
@@ -6077,7 +7693,7 @@ Respond in JSON format:
                 );
 
                 if should_send_alert {
-                    self.send_alert_for_decision(agent, decision, &mut notifications_sent)
+                    self.send_alert_for_decision(agent, decision, dry_run, &mut notifications_sent)
                         .await;
                 }
             }
@@ -6097,7 +7713,7 @@ Respond in JSON format:
                     decision_action = %decision.action,
                     "Alert-type decision detected, sending notification"
                 );
-                self.send_alert_for_decision(agent, decision, &mut notifications_sent)
+                self.send_alert_for_decision(agent, decision, dry_run, &mut notifications_sent)
                     .await;
             }
 
@@ -6154,6 +7770,23 @@ Respond in JSON format:
                             let device_id = parts[0];
                             let command_name = parts[1];
 
+                            if let Some(intercepted) = self
+                                .intercept_command(
+                                    agent,
+                                    execution_id,
+                                    reasoning,
+                                    "device",
+                                    device_id,
+                                    command_name,
+                                    decision,
+                                    dry_run,
+                                )
+                                .await
+                            {
+                                actions_executed.push(intercepted);
+                                continue;
+                            }
+
                             // Get parameters from resource config
                             let parameters = resource
                                 .config
@@ -6211,6 +7844,7 @@ Respond in JSON format:
                                     .unwrap_or_default(),
                                 success,
                                 result,
+                                approval_id: None,
                             });
                         }
                     }
@@ -6218,14 +7852,17 @@ Respond in JSON format:
             }
         }
 
-        Ok((actions_executed, notifications_sent))
+        Ok((actions_executed, notifications_sent, agent_messages))
     }
 
     /// Send an alert for a specific decision.
+    ///
+    /// In a dry run the alert is only recorded on the `dry_run` channel.
     async fn send_alert_for_decision(
         &self,
         agent: &AiAgent,
         decision: &neomind_storage::Decision,
+        dry_run: bool,
         notifications_sent: &mut Vec<neomind_storage::NotificationSent>,
     ) {
         let alert_message = format!(
@@ -6233,6 +7870,17 @@ Respond in JSON format:
             agent.name, decision.decision_type, decision.description
         );
 
+        if dry_run {
+            notifications_sent.push(neomind_storage::NotificationSent {
+                channel: "dry_run".to_string(),
+                recipient: "configured_channels".to_string(),
+                message: alert_message,
+                sent_at: chrono::Utc::now().timestamp(),
+                success: true,
+            });
+            return;
+        }
+
         // Send via MessageManager if available
         if let Some(ref message_manager) = self.message_manager {
             use neomind_messages::{Message, MessageSeverity};
@@ -6755,6 +8403,7 @@ Respond in JSON format:
         decision_process: &DecisionProcess,
         duration_ms: u64,
         success: bool,
+        agent_messages: Vec<AgentMessage>,
     ) -> ConversationTurn {
         // Clean and truncate before storing in conversation history
         // Conversation history can have up to 20 entries, so we need to be conservative
@@ -6799,6 +8448,7 @@ Respond in JSON format:
             },
             duration_ms,
             success,
+            agent_messages,
         }
     }
 }
@@ -7074,6 +8724,11 @@ fn build_parameters_schema(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use neomind_core::llm::backend::{
+        BackendId, FinishReason, LlmError, LlmInput, LlmOutput, StreamChunk,
+    };
+    use std::pin::Pin;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     #[test]
     fn test_extract_metrics() {
@@ -7088,4 +8743,718 @@ mod tests {
         assert_eq!(extract_threshold("大于30"), Some(30.0));
         assert_eq!(extract_threshold("温度超过35.5"), Some(35.5));
     }
+
+    #[test]
+    fn test_parse_mcp_tool_action() {
+        assert_eq!(
+            AgentExecutor::parse_command_from_action("mcp:weather:get_forecast"),
+            Some((
+                "mcp".to_string(),
+                "weather".to_string(),
+                "get_forecast".to_string()
+            ))
+        );
+        assert_eq!(
+            AgentExecutor::parse_command_from_action("light1:turn_on"),
+            Some((
+                "device".to_string(),
+                "light1".to_string(),
+                "turn_on".to_string()
+            ))
+        );
+    }
+
+    async fn memory_executor(store: Arc<AgentStore>) -> AgentExecutor {
+        AgentExecutor::new(AgentExecutorConfig {
+            store,
+            time_series_storage: None,
+            device_service: None,
+            event_bus: None,
+            message_manager: None,
+            llm_runtime: None,
+            llm_backend_store: None,
+            extension_registry: None,
+        })
+        .await
+        .unwrap()
+    }
+
+    /// An agent that may delete rules through MCP, with deletions requiring approval.
+    fn rule_janitor() -> AiAgent {
+        use neomind_storage::{
+            AgentSchedule, AgentStatus, ApprovalPolicy, ApprovalRule, ScheduleType,
+        };
+
+        AiAgent {
+            id: "agent-1".to_string(),
+            name: "Rule Janitor".to_string(),
+            description: None,
+            user_prompt: "Remove rules that never fire".to_string(),
+            llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
+            parsed_intent: None,
+            resources: vec![AgentResource {
+                resource_type: ResourceType::McpTool,
+                resource_id: "mcp:rules:delete_rule".to_string(),
+                name: "delete_rule".to_string(),
+                config: serde_json::json!({}),
+            }],
+            schedule: AgentSchedule {
+                schedule_type: ScheduleType::Interval,
+                cron_expression: None,
+                interval_seconds: Some(3600),
+                event_filter: None,
+                timezone: None,
+            },
+            status: AgentStatus::Active,
+            priority: 128,
+            created_at: 0,
+            updated_at: 0,
+            last_execution_at: None,
+            stats: Default::default(),
+            memory: Default::default(),
+            conversation_history: vec![],
+            user_messages: vec![],
+            conversation_summary: None,
+            context_window_size: 10,
+            enable_tool_chaining: false,
+            max_chain_depth: 3,
+            approval_policy: Some(ApprovalPolicy {
+                rules: vec![ApprovalRule {
+                    command: Some("delete_*".to_string()),
+                    ..Default::default()
+                }],
+                ..Default::default()
+            }),
+            error_message: None,
+        }
+    }
+
+    fn delete_rule_decision() -> Decision {
+        Decision {
+            decision_type: "command".to_string(),
+            description: "Delete unused rule".to_string(),
+            action: "mcp:rules:delete_rule".to_string(),
+            rationale: r#"{"rule_id": "r1"}"#.to_string(),
+            expected_outcome: "Fewer rules".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_approval_policy_holds_commands() {
+        use neomind_storage::ApprovalFilter;
+
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+
+        let agent = rule_janitor();
+        store.save_agent(&agent).await.unwrap();
+
+        let (actions, _, _) = executor
+            .execute_decisions(
+                &agent,
+                &[delete_rule_decision()],
+                "exec-1",
+                "Rule r1 never fired",
+                false,
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(actions.len(), 1);
+        assert!(!actions[0].success);
+        let approval_id = actions[0].approval_id.clone().unwrap();
+
+        let queued = store
+            .query_approvals(ApprovalFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].status, ApprovalStatus::Pending);
+        assert_eq!(queued[0].action.target, "mcp:rules:delete_rule");
+        assert_eq!(queued[0].action.parameters["rule_id"], "r1");
+        assert_eq!(queued[0].reasoning, "Rule r1 never fired");
+
+        store
+            .save_execution(&AgentExecutionRecord {
+                id: "exec-1".to_string(),
+                agent_id: agent.id.clone(),
+                timestamp: 0,
+                trigger_type: "schedule".to_string(),
+                status: ExecutionStatus::Completed,
+                decision_process: DecisionProcess {
+                    situation_analysis: String::new(),
+                    data_collected: vec![],
+                    reasoning_steps: vec![],
+                    decisions: vec![],
+                    conclusion: String::new(),
+                    confidence: 1.0,
+                },
+                result: Some(StorageExecutionResult {
+                    actions_executed: actions,
+                    report: None,
+                    notifications_sent: vec![],
+                    summary: String::new(),
+                    success_rate: 0.0,
+                    agent_messages: vec![],
+                }),
+                duration_ms: 0,
+                error: None,
+                simulated: false,
+            })
+            .await
+            .unwrap();
+
+        let denied = executor
+            .resolve_approval(
+                &approval_id,
+                false,
+                "admin",
+                Some("Still needed".to_string()),
+            )
+            .await
+            .unwrap();
+        assert_eq!(denied.status, ApprovalStatus::Denied);
+        assert_eq!(denied.decided_by.as_deref(), Some("admin"));
+
+        let record = store.get_execution("exec-1").await.unwrap().unwrap();
+        let action = &record.result.unwrap().actions_executed[0];
+        assert_eq!(
+            action.result.as_deref(),
+            Some("Denied by admin: Still needed")
+        );
+
+        // A decided request cannot be decided again
+        assert!(executor
+            .resolve_approval(&approval_id, true, "admin", None)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_records_instead_of_executing() {
+        use neomind_storage::ApprovalFilter;
+
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+        let agent = rule_janitor();
+
+        let alert = Decision {
+            decision_type: "alert".to_string(),
+            description: "Rule r1 never fired".to_string(),
+            action: "notify".to_string(),
+            rationale: String::new(),
+            expected_outcome: "Operator informed".to_string(),
+        };
+        let (actions, notifications, _) = executor
+            .execute_decisions(
+                &agent,
+                &[delete_rule_decision(), alert],
+                "exec-1",
+                "Rule r1 never fired",
+                true,
+                &[],
+            )
+            .await
+            .unwrap();
+
+        // The deletion is recorded as intended, not queued or executed
+        assert_eq!(actions.len(), 1);
+        assert!(actions[0].success);
+        assert_eq!(actions[0].action_type, "mcp_tool");
+        assert_eq!(actions[0].target, "mcp:rules:delete_rule");
+        assert_eq!(actions[0].parameters["rule_id"], "r1");
+        assert_eq!(
+            actions[0].result.as_deref(),
+            Some("Dry run: not executed (would require approval)")
+        );
+        assert!(actions[0].approval_id.is_none());
+        assert!(store
+            .query_approvals(ApprovalFilter::default())
+            .await
+            .unwrap()
+            .is_empty());
+
+        // The alert is recorded on the dry run channel only
+        assert!(!notifications.is_empty());
+        assert!(notifications.iter().all(|n| n.channel == "dry_run"));
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_simulates_extension_commands() {
+        let executor = memory_executor(AgentStore::memory().unwrap()).await;
+        let extension_tool = |command: &str, config: serde_json::Value| AgentResource {
+            resource_type: ResourceType::ExtensionTool,
+            resource_id: format!("extension:irrigation:{}", command),
+            name: command.to_string(),
+            config,
+        };
+        let agent = AiAgent {
+            resources: vec![
+                extension_tool("open_valve", serde_json::json!({})),
+                extension_tool("read_moisture", serde_json::json!({ "read_only": true })),
+            ],
+            approval_policy: None,
+            ..rule_janitor()
+        };
+        let decision = Decision {
+            decision_type: "command".to_string(),
+            description: "Soil is dry".to_string(),
+            action: "extension:irrigation:open_valve".to_string(),
+            rationale: String::new(),
+            expected_outcome: "Watered bed".to_string(),
+        };
+
+        // Commands are simulated unless explicitly marked read-only
+        let simulated = executor
+            .simulate_command(&agent, "extension", "irrigation", "open_valve", &decision)
+            .await
+            .unwrap();
+        assert_eq!(simulated.action_type, "extension_command");
+        assert_eq!(simulated.result.as_deref(), Some("Dry run: not executed"));
+        let read_only = executor
+            .simulate_command(
+                &agent,
+                "extension",
+                "irrigation",
+                "read_moisture",
+                &decision,
+            )
+            .await;
+        assert!(read_only.is_none());
+    }
+
+    /// An agent that may delegate tasks to `delegate_id`.
+    fn delegating_agent(id: &str, name: &str, delegate_id: &str) -> AiAgent {
+        AiAgent {
+            id: id.to_string(),
+            name: name.to_string(),
+            user_prompt: format!("Coordinate with {}", delegate_id),
+            resources: vec![AgentResource {
+                resource_type: ResourceType::Agent,
+                resource_id: format!("agent:{}", delegate_id),
+                name: String::new(),
+                config: serde_json::json!({}),
+            }],
+            approval_policy: None,
+            ..rule_janitor()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_delegation_to_another_agent() {
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+        let supervisor = delegating_agent("supervisor", "Site Supervisor", "hvac");
+        let hvac = delegating_agent("hvac", "HVAC", "supervisor");
+        store.save_agent(&supervisor).await.unwrap();
+        store.save_agent(&hvac).await.unwrap();
+
+        let delegate = |action: &str| Decision {
+            decision_type: "command".to_string(),
+            description: "Analyse zone 3 temperatures".to_string(),
+            action: action.to_string(),
+            rationale: r#"{"zone": 3}"#.to_string(),
+            expected_outcome: "Temperature analysis".to_string(),
+        };
+
+        let (actions, _, messages) = executor
+            .execute_decisions(
+                &supervisor,
+                &[delegate("agent:hvac")],
+                "exec-1",
+                "Zone 3 is warm",
+                false,
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].action_type, "agent_delegation");
+        assert_eq!(actions[0].target, "agent:hvac");
+        assert_eq!(messages.len(), 1);
+        let message = &messages[0];
+        assert_eq!(message.from_agent_id, "supervisor");
+        assert_eq!(message.to_agent_name, "HVAC");
+        assert_eq!(message.arguments["zone"], 3);
+        assert_eq!(message.caller_execution_id, "exec-1");
+        assert!(message.response.is_some());
+
+        // The delegate recorded the same message in its execution and conversation
+        let delegate_execution_id = message.execution_id.clone().unwrap();
+        let record = store
+            .get_execution(&delegate_execution_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(record.trigger_type, "delegation");
+        let history = store.get_conversation_history("hvac", None).await.unwrap();
+        let turn = history.last().unwrap();
+        assert_eq!(turn.execution_id, delegate_execution_id);
+        assert_eq!(turn.agent_messages[0].from_agent_id, "supervisor");
+        assert_eq!(turn.agent_messages[0].task, "Analyse zone 3 temperatures");
+
+        // Delegating back to an agent already in the chain is refused
+        let (actions, _, messages) = executor
+            .execute_decisions(
+                &hvac,
+                &[delegate("agent:supervisor")],
+                "exec-2",
+                "",
+                false,
+                &["supervisor".to_string()],
+            )
+            .await
+            .unwrap();
+        assert!(!actions[0].success);
+        assert_eq!(
+            messages[0].response.as_deref(),
+            Some("Failed: delegation cycle supervisor -> hvac -> supervisor")
+        );
+        assert!(messages[0].execution_id.is_none());
+
+        // Only agents that are resources of the caller can be delegated to
+        let (actions, _, _) = executor
+            .execute_decisions(
+                &supervisor,
+                &[delegate("agent:lighting")],
+                "exec-3",
+                "",
+                false,
+                &[],
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            actions[0].result.as_deref(),
+            Some("Failed: agent:lighting is not a resource of this agent")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_delegation_depth_and_timeout() {
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+        let mut supervisor = delegating_agent("supervisor", "Site Supervisor", "hvac");
+        let hvac = delegating_agent("hvac", "HVAC", "supervisor");
+        store.save_agent(&supervisor).await.unwrap();
+        store.save_agent(&hvac).await.unwrap();
+
+        let decision = Decision {
+            decision_type: "command".to_string(),
+            description: "Check the chiller".to_string(),
+            action: "agent:hvac".to_string(),
+            rationale: String::new(),
+            expected_outcome: "Chiller status".to_string(),
+        };
+
+        // A chain already at the limit cannot grow
+        let chain: Vec<String> = (0..MAX_DELEGATION_DEPTH)
+            .map(|i| format!("agent-{}", i))
+            .collect();
+        let (_, _, messages) = executor
+            .execute_decisions(
+                &supervisor,
+                std::slice::from_ref(&decision),
+                "exec-1",
+                "",
+                false,
+                &chain,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            messages[0].response.as_deref(),
+            Some("Failed: delegation depth limit of 3 reached")
+        );
+        assert!(messages[0].execution_id.is_none());
+
+        // The caller stops waiting, but the delegate still finishes
+        supervisor.resources[0].config = serde_json::json!({ "timeout_secs": 0 });
+        let (actions, _, messages) = executor
+            .execute_decisions(&supervisor, &[decision], "exec-2", "", false, &[])
+            .await
+            .unwrap();
+        assert!(!actions[0].success);
+        assert_eq!(
+            messages[0].response.as_deref(),
+            Some("Failed: no answer within 0s")
+        );
+        let delegate_execution_id = messages[0].execution_id.clone().unwrap();
+        let mut record = None;
+        for _ in 0..100 {
+            record = store.get_execution(&delegate_execution_id).await.unwrap();
+            if record.is_some() {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(50)).await;
+        }
+        assert_eq!(record.unwrap().trigger_type, "delegation");
+    }
+
+    /// A scheduled execution of the rule janitor, stopped by a restart while
+    /// deleting the first of two rules.
+    fn interrupted_checkpoint() -> ExecutionCheckpoint {
+        let second = Decision {
+            action: "mcp:rules:delete_rule".to_string(),
+            rationale: r#"{"rule_id": "r2"}"#.to_string(),
+            ..delete_rule_decision()
+        };
+        ExecutionCheckpoint {
+            execution_id: "exec-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            trigger_type: "schedule".to_string(),
+            event_data: None,
+            started_at: 100,
+            updated_at: 160,
+            chain_depth: 0,
+            prompt: "Remove rules that never fire".to_string(),
+            actions_executed: vec![],
+            notifications_sent: vec![],
+            agent_messages: vec![],
+            round: Some(RoundCheckpoint {
+                data_collected: vec![],
+                situation_analysis: "Rules r1 and r2 never fired".to_string(),
+                reasoning_steps: vec![],
+                decisions: vec![delete_rule_decision(), second],
+                conclusion: "Delete both rules".to_string(),
+                completed_decisions: 0,
+                in_flight: Some(0),
+                actions_executed: vec![],
+                notifications_sent: vec![],
+                agent_messages: vec![],
+            }),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_resume_interrupted_execution() {
+        use neomind_storage::{AgentStatus, ApprovalFilter};
+
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+        let mut agent = rule_janitor();
+        agent.status = AgentStatus::Executing;
+        store.save_agent(&agent).await.unwrap();
+        store
+            .save_checkpoint(&interrupted_checkpoint())
+            .await
+            .unwrap();
+
+        let resumable = executor
+            .recover_interrupted_executions(InterruptedExecutionPolicy::Resume)
+            .await
+            .unwrap();
+        assert_eq!(resumable.len(), 1);
+        let agent = store.get_agent("agent-1").await.unwrap().unwrap();
+        assert_eq!(agent.status, AgentStatus::Active);
+
+        let record = executor
+            .resume_execution(resumable.into_iter().next().unwrap())
+            .await
+            .unwrap();
+        assert_eq!(record.id, "exec-1");
+        assert_eq!(record.status, ExecutionStatus::Completed);
+        assert_eq!(
+            record.decision_process.situation_analysis,
+            "Rules r1 and r2 never fired"
+        );
+
+        // The deletion in flight is not repeated, the pending one runs
+        let actions = record.result.unwrap().actions_executed;
+        assert_eq!(actions.len(), 2);
+        assert_eq!(actions[0].action_type, "interrupted");
+        assert!(!actions[0].success);
+        assert_eq!(actions[1].parameters["rule_id"], "r2");
+        let approvals = store
+            .query_approvals(ApprovalFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(approvals.len(), 1);
+        assert_eq!(approvals[0].action.parameters["rule_id"], "r2");
+
+        assert!(store.get_checkpoint("exec-1").await.unwrap().is_none());
+        let agent = store.get_agent("agent-1").await.unwrap().unwrap();
+        assert_eq!(agent.status, AgentStatus::Active);
+    }
+
+    #[tokio::test]
+    async fn test_fail_interrupted_execution() {
+        use neomind_storage::AgentStatus;
+
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+        let mut agent = rule_janitor();
+        agent.status = AgentStatus::Executing;
+        store.save_agent(&agent).await.unwrap();
+
+        let listed = neomind_storage::ActionExecuted {
+            action_type: "mcp_tool".to_string(),
+            description: "List rules".to_string(),
+            target: "mcp:rules:list_rules".to_string(),
+            parameters: serde_json::json!({}),
+            success: true,
+            result: Some("r1, r2".to_string()),
+            approval_id: None,
+        };
+        let mut checkpoint = interrupted_checkpoint();
+        let round = checkpoint.round.as_mut().unwrap();
+        round.actions_executed.push(listed);
+        store.save_checkpoint(&checkpoint).await.unwrap();
+
+        let resumable = executor
+            .recover_interrupted_executions(InterruptedExecutionPolicy::Fail)
+            .await
+            .unwrap();
+        assert!(resumable.is_empty());
+
+        // The failure is recorded with what had already run
+        let record = store.get_execution("exec-1").await.unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        assert_eq!(record.duration_ms, 60_000);
+        assert_eq!(record.decision_process.decisions.len(), 2);
+        let result = record.result.unwrap();
+        assert_eq!(result.actions_executed.len(), 1);
+        assert_eq!(result.actions_executed[0].target, "mcp:rules:list_rules");
+
+        assert!(store.list_checkpoints().await.unwrap().is_empty());
+        let agent = store.get_agent("agent-1").await.unwrap().unwrap();
+        assert_eq!(agent.status, AgentStatus::Active);
+        assert_eq!(agent.stats.failed_executions, 1);
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_execution_not_resumed() {
+        use neomind_storage::AgentStatus;
+
+        let store = AgentStore::memory().unwrap();
+        let executor = memory_executor(store.clone()).await;
+
+        // Agents paused since the interruption are not resumed
+        let mut agent = rule_janitor();
+        agent.status = AgentStatus::Paused;
+        store.save_agent(&agent).await.unwrap();
+        store
+            .save_checkpoint(&interrupted_checkpoint())
+            .await
+            .unwrap();
+        let resumable = executor
+            .recover_interrupted_executions(InterruptedExecutionPolicy::Resume)
+            .await
+            .unwrap();
+        assert!(resumable.is_empty());
+        let record = store.get_execution("exec-1").await.unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        assert!(store.list_checkpoints().await.unwrap().is_empty());
+        let agent = store.get_agent("agent-1").await.unwrap().unwrap();
+        assert_eq!(agent.status, AgentStatus::Paused);
+
+        // A resume that fails is recorded as failed instead of left behind
+        let checkpoint = ExecutionCheckpoint {
+            execution_id: "exec-2".to_string(),
+            ..interrupted_checkpoint()
+        };
+        store.save_checkpoint(&checkpoint).await.unwrap();
+        store.delete_agent("agent-1").await.unwrap();
+        assert!(executor.resume_execution(checkpoint).await.is_err());
+        let record = store.get_execution("exec-2").await.unwrap().unwrap();
+        assert_eq!(record.status, ExecutionStatus::Failed);
+        assert!(record
+            .error
+            .unwrap()
+            .starts_with("Resuming the interrupted execution failed"));
+        assert!(store.list_checkpoints().await.unwrap().is_empty());
+    }
+
+    /// Runtime that reports the same situation on every call.
+    struct CountingLlm {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmRuntime for CountingLlm {
+        fn backend_id(&self) -> BackendId {
+            BackendId::new("counting")
+        }
+
+        fn model_name(&self) -> &str {
+            "counting"
+        }
+
+        async fn generate(&self, _input: LlmInput) -> Result<LlmOutput, LlmError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(LlmOutput {
+                text: r#"{"situation_analysis": "All rules fire", "reasoning_steps": [], "decisions": [], "conclusion": "Nothing to remove"}"#.to_string(),
+                finish_reason: FinishReason::Stop,
+                usage: None,
+                thinking: None,
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _input: LlmInput,
+        ) -> Result<Pin<Box<dyn futures::Stream<Item = StreamChunk> + Send>>, LlmError> {
+            unimplemented!("analysis does not stream")
+        }
+
+        fn max_context_length(&self) -> usize {
+            8192
+        }
+    }
+
+    #[tokio::test]
+    async fn test_opted_in_agent_reuses_cached_analysis() {
+        let store = AgentStore::memory().unwrap();
+        let llm = Arc::new(CountingLlm {
+            calls: AtomicUsize::new(0),
+        });
+        let executor = AgentExecutor::new(AgentExecutorConfig {
+            store: store.clone(),
+            time_series_storage: None,
+            device_service: None,
+            event_bus: None,
+            message_manager: None,
+            llm_runtime: Some(llm.clone()),
+            llm_backend_store: None,
+            extension_registry: None,
+        })
+        .await
+        .unwrap();
+
+        let agent = AiAgent {
+            id: "cached-agent".to_string(),
+            user_prompt: "Report whether the cached janitor rules fire".to_string(),
+            response_cache: Some(Default::default()),
+            approval_policy: None,
+            ..rule_janitor()
+        };
+        store.save_agent(&agent).await.unwrap();
+
+        // The first run parses the intent and records memory that later prompts
+        // include; after that an unchanged situation is answered from the cache
+        let mut calls = Vec::new();
+        for _ in 0..4 {
+            let agent = store.get_agent("cached-agent").await.unwrap().unwrap();
+            let record = executor.execute_agent(agent).await.unwrap();
+            assert_eq!(record.status, ExecutionStatus::Completed);
+            assert_eq!(record.decision_process.situation_analysis, "All rules fire");
+            calls.push(llm.calls.load(Ordering::SeqCst));
+        }
+        assert_eq!(calls, [2, 3, 3, 3]);
+
+        // Agents that do not opt in always reach the backend
+        let uncached = AiAgent {
+            id: "uncached-agent".to_string(),
+            response_cache: None,
+            ..store.get_agent("cached-agent").await.unwrap().unwrap()
+        };
+        store.save_agent(&uncached).await.unwrap();
+        for _ in 0..2 {
+            let agent = store.get_agent("uncached-agent").await.unwrap().unwrap();
+            executor.execute_agent(agent).await.unwrap();
+        }
+        assert_eq!(llm.calls.load(Ordering::SeqCst), 5);
+    }
 }
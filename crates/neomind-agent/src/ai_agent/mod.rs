@@ -15,7 +15,10 @@ pub mod intent_parser;
 pub mod llm_pool;
 pub mod scheduler;
 
-use neomind_storage::{AgentExecutionRecord, AgentSchedule, AgentStatus, AiAgent, ExecutionStatus};
+use neomind_storage::{
+    AgentExecutionRecord, AgentSchedule, AgentStatus, AiAgent, ApprovalPolicy, ExecutionStatus,
+    InterruptedExecutionPolicy, ResponseCacheConfig,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -24,6 +27,9 @@ pub use executor::{AgentExecutionResult, AgentExecutor, AgentExecutorConfig, Exe
 pub use intent_parser::IntentParser;
 pub use scheduler::{AgentScheduler, ScheduledTask, SchedulerConfig, SchedulerError};
 
+/// How often undecided approval requests are checked for expiry.
+const APPROVAL_EXPIRY_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+
 /// AI Agent manager - the main entry point for user-defined agents.
 ///
 /// Manages the lifecycle of AI agents including:
@@ -38,6 +44,8 @@ pub struct AiAgentManager {
     scheduler: Arc<AgentScheduler>,
     /// Running state
     running: Arc<RwLock<bool>>,
+    /// What to do with executions interrupted by a crash or restart
+    interrupted_execution_policy: Arc<RwLock<InterruptedExecutionPolicy>>,
 }
 
 /// Configuration for creating a new AI Agent.
@@ -64,6 +72,15 @@ pub struct CreateAgentRequest {
     /// Optional LLM backend ID (uses default if not specified)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub llm_backend_id: Option<String>,
+    /// Optional LLM routing policy ID (takes precedence over the backend ID)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_routing_policy: Option<String>,
+    /// Opt-in LLM response caching for the agent's executions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheConfig>,
+    /// Actions that must be approved by a person before they run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<ApprovalPolicy>,
 }
 
 /// A selected metric for monitoring.
@@ -121,6 +138,9 @@ impl AiAgentManager {
             executor,
             scheduler,
             running: Arc::new(RwLock::new(false)),
+            interrupted_execution_policy: Arc::new(RwLock::new(
+                InterruptedExecutionPolicy::default(),
+            )),
         }))
     }
 
@@ -142,6 +162,8 @@ impl AiAgentManager {
             description: request.description.clone(),
             user_prompt: request.user_prompt,
             llm_backend_id: request.llm_backend_id,
+            llm_routing_policy: request.llm_routing_policy,
+            response_cache: request.response_cache,
             parsed_intent: Some(intent.clone()),
             resources,
             schedule: request.schedule,
@@ -159,6 +181,7 @@ impl AiAgentManager {
             context_window_size: Default::default(),
             enable_tool_chaining: false, // Default disabled for backward compatibility
             max_chain_depth: 3,          // Default max depth
+            approval_policy: request.approval_policy,
         };
 
         // Save agent to storage
@@ -242,6 +265,23 @@ impl AiAgentManager {
         Ok(summary)
     }
 
+    /// Simulate an agent execution without side effects (dry run).
+    ///
+    /// Returns the simulated record, whose result lists the intended actions.
+    pub async fn dry_run_agent(
+        &self,
+        agent_id: &str,
+    ) -> Result<AgentExecutionRecord, crate::error::NeoMindError> {
+        let agent = self
+            .executor
+            .store()
+            .get_agent(agent_id)
+            .await?
+            .ok_or_else(|| crate::NeoMindError::NotFound(format!("Agent: {}", agent_id)))?;
+
+        self.executor.execute_agent_dry_run(agent).await
+    }
+
     /// Get an agent by ID.
     pub async fn get_agent(&self, id: &str) -> Result<Option<AiAgent>, crate::error::NeoMindError> {
         Ok(self.executor.store().get_agent(id).await?)
@@ -297,6 +337,8 @@ impl AiAgentManager {
     ///
     /// This will reload all active agents from storage and reschedule them,
     /// ensuring that scheduled tasks continue after server restarts.
+    /// Executions interrupted by the restart are resumed or recorded as
+    /// failed according to the interrupted execution policy.
     pub async fn start(&self) -> Result<(), crate::error::NeoMindError> {
         let mut running = self.running.write().await;
         if *running {
@@ -305,6 +347,16 @@ impl AiAgentManager {
         *running = true;
         drop(running);
 
+        // Settle interrupted executions first, so their agents are active again
+        let policy = *self.interrupted_execution_policy.read().await;
+        let resumable = match self.executor.recover_interrupted_executions(policy).await {
+            Ok(resumable) => resumable,
+            Err(e) => {
+                tracing::warn!(error = %e, "Failed to recover interrupted executions");
+                vec![]
+            }
+        };
+
         // Reload and reschedule all active agents from storage
         // This ensures scheduled tasks continue after server restarts
         self.reload_active_agents().await?;
@@ -312,6 +364,37 @@ impl AiAgentManager {
         // Start the scheduler
         self.scheduler.start(self.executor.clone()).await?;
 
+        // Expire undecided approval requests while running
+        let executor = self.executor.clone();
+        let running = self.running.clone();
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(APPROVAL_EXPIRY_INTERVAL);
+            loop {
+                ticker.tick().await;
+                if !*running.read().await {
+                    break;
+                }
+                if let Err(e) = executor.expire_approvals().await {
+                    tracing::warn!(error = %e, "Failed to expire approval requests");
+                }
+            }
+        });
+
+        // Resume interrupted executions in the background
+        for checkpoint in resumable {
+            let executor = self.executor.clone();
+            tokio::spawn(async move {
+                let execution_id = checkpoint.execution_id.clone();
+                if let Err(e) = executor.resume_execution(checkpoint).await {
+                    tracing::warn!(
+                        execution_id = %execution_id,
+                        error = %e,
+                        "Failed to resume interrupted execution"
+                    );
+                }
+            });
+        }
+
         tracing::info!("AiAgentManager started");
         Ok(())
     }
@@ -414,6 +497,12 @@ impl AiAgentManager {
         self.scheduler.get_default_timezone().await
     }
 
+    /// Set what [`Self::start`] does with executions interrupted by a crash
+    /// or restart.
+    pub async fn set_interrupted_execution_policy(&self, policy: InterruptedExecutionPolicy) {
+        *self.interrupted_execution_policy.write().await = policy;
+    }
+
     /// Build resources from request.
     fn build_resources(request: &CreateAgentRequest) -> Vec<neomind_storage::AgentResource> {
         use neomind_storage::{AgentResource, ResourceType};
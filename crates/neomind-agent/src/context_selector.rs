@@ -628,6 +628,22 @@ impl ContextSelector {
                         } => {
                             format!("{}.{} BETWEEN {} AND {}", extension_id, metric, min, max)
                         }
+                        RuleCondition::DeviceGroup {
+                            selector,
+                            quantifier,
+                            metric,
+                            operator,
+                            threshold,
+                        } => {
+                            format!(
+                                "{}({}).{} {} {}",
+                                quantifier.as_str(),
+                                selector,
+                                metric,
+                                operator.as_str(),
+                                threshold
+                            )
+                        }
                         RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
                             format!(
                                 "(complex condition with {} sub-conditions)",
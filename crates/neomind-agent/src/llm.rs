@@ -14,6 +14,7 @@ use tokio::sync::RwLock;
 use neomind_core::{
     config::agent_env_vars,
     llm::backend::{LlmInput, LlmRuntime},
+    llm::usage::{with_usage_context, UsageContext},
     Message,
 };
 
@@ -261,6 +262,8 @@ pub struct LlmInterface {
     /// Global timezone for time-aware prompts (IANA format, e.g., "Asia/Shanghai").
     /// Loaded from settings and used for all time-related context.
     global_timezone: Arc<RwLock<Option<String>>>,
+    /// Who LLM calls made through this interface are attributed to.
+    usage_context: Arc<RwLock<UsageContext>>,
 }
 
 impl LlmInterface {
@@ -286,6 +289,7 @@ impl LlmInterface {
             tool_filter: ToolFilter::default(),
             context_manager: None,
             global_timezone: Arc::new(RwLock::new(None)), // Will be loaded from settings
+            usage_context: Arc::new(RwLock::new(UsageContext::default())),
         }
     }
 
@@ -314,9 +318,15 @@ impl LlmInterface {
             tool_filter: ToolFilter::default(),
             context_manager: None,
             global_timezone: Arc::new(RwLock::new(None)), // Will be loaded from settings
+            usage_context: Arc::new(RwLock::new(UsageContext::default())),
         }
     }
 
+    /// Set who LLM calls are attributed to in usage records and budgets.
+    pub async fn set_usage_context(&self, context: UsageContext) {
+        *self.usage_context.write().await = context;
+    }
+
     /// Set the thinking mode for direct LLM usage (when not using instance manager).
     pub async fn set_thinking_enabled(&self, enabled: bool) {
         *self.thinking_enabled.write().await = Some(enabled);
@@ -345,9 +355,7 @@ impl LlmInterface {
     pub async fn load_global_timezone(&self) -> AgentResult<String> {
         use neomind_storage::SettingsStore;
 
-        const SETTINGS_DB_PATH: &str = "data/settings.redb";
-
-        let settings_store = SettingsStore::open(SETTINGS_DB_PATH)
+        let settings_store = SettingsStore::open(neomind_storage::data_path("settings.redb"))
             .map_err(|e| NeoMindError::Llm(format!("Failed to open settings store: {}", e)))?;
 
         let timezone = settings_store.get_global_timezone();
@@ -384,7 +392,8 @@ impl LlmInterface {
     }
 
     /// Get the current LLM runtime.
-    /// Priority: Direct runtime (set via configure_llm) > Instance manager active runtime
+    /// Priority: Direct runtime (set via configure_llm) > Instance manager chat runtime
+    /// (the chat routing policy, or the active backend)
     /// This ensures that when a specific backend is configured via backendId, it takes precedence.
     async fn get_runtime(&self) -> AgentResult<Arc<dyn LlmRuntime>> {
         // First, check if a direct runtime is set (via configure_llm)
@@ -399,7 +408,7 @@ impl LlmInterface {
         if self.uses_instance_manager() {
             if let Some(manager) = &self.instance_manager {
                 return manager
-                    .get_active_runtime()
+                    .get_chat_runtime()
                     .await
                     .map_err(|e| NeoMindError::Llm(e.to_string()));
             }
@@ -561,6 +570,12 @@ impl LlmInterface {
         self
     }
 
+    /// Attribute LLM calls made through this interface to `context`.
+    pub fn with_usage_context(mut self, context: UsageContext) -> Self {
+        self.usage_context = Arc::new(RwLock::new(context));
+        self
+    }
+
     /// Set the system prompt (for dynamic updates).
     pub async fn set_system_prompt(&self, prompt: &str) {
         *self.system_prompt.write().await = prompt.to_string();
@@ -1132,6 +1147,7 @@ impl LlmInterface {
             presence_penalty: None,
             thinking_enabled,
             max_context: None,
+            response_format: None,
         };
 
         let system_msg = Message::system(&system_prompt);
@@ -1177,8 +1193,8 @@ impl LlmInterface {
         // Get runtime using instance manager if enabled
         let llm = self.get_runtime().await?;
 
-        let output = llm
-            .generate(input)
+        let usage_context = self.usage_context.read().await.clone();
+        let output = with_usage_context(usage_context, llm.generate(input))
             .await
             .map_err(|e| NeoMindError::Llm(e.to_string()))?;
 
@@ -1273,6 +1289,7 @@ impl LlmInterface {
             presence_penalty: None,
             thinking_enabled,
             max_context: None,
+            response_format: None,
         };
 
         let system_msg = Message::system(&system_prompt);
@@ -1329,8 +1346,8 @@ impl LlmInterface {
         // Get runtime using instance manager if enabled
         let llm = self.get_runtime().await?;
 
-        let output = llm
-            .generate(input)
+        let usage_context = self.usage_context.read().await.clone();
+        let output = with_usage_context(usage_context, llm.generate(input))
             .await
             .map_err(|e| NeoMindError::Llm(e.to_string()))?;
 
@@ -1563,6 +1580,7 @@ impl LlmInterface {
             presence_penalty: None,
             thinking_enabled,
             max_context: None,
+            response_format: None,
         };
 
         let system_msg = Message::system(&system_prompt);
@@ -1614,8 +1632,8 @@ impl LlmInterface {
         // Get runtime using instance manager if enabled
         let llm = self.get_runtime().await?;
 
-        let stream = llm
-            .generate_stream(input)
+        let usage_context = self.usage_context.read().await.clone();
+        let stream = with_usage_context(usage_context, llm.generate_stream(input))
             .await
             .map_err(|e| NeoMindError::Llm(e.to_string()))?;
 
@@ -1753,6 +1771,7 @@ impl LlmInterface {
             presence_penalty: None,
             thinking_enabled,
             max_context: None,
+            response_format: None,
         };
 
         let system_msg = Message::system(&system_prompt);
@@ -1793,8 +1812,8 @@ impl LlmInterface {
         // Get runtime using instance manager if enabled
         let llm = self.get_runtime().await?;
 
-        let stream = llm
-            .generate_stream(input)
+        let usage_context = self.usage_context.read().await.clone();
+        let stream = with_usage_context(usage_context, llm.generate_stream(input))
             .await
             .map_err(|e| NeoMindError::Llm(e.to_string()))?;
 
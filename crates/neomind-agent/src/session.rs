@@ -9,6 +9,7 @@ use futures::Stream;
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+use neomind_core::llm::usage::UsageContext;
 use neomind_storage::SessionStore;
 
 use super::agent::{Agent, AgentConfig, AgentEvent, AgentMessage, LlmBackend};
@@ -206,6 +207,8 @@ pub struct SessionManager {
     default_config: AgentConfig,
     /// Default LLM backend (configured for new sessions)
     default_llm_backend: Arc<RwLock<Option<LlmBackend>>>,
+    /// Routing policy for chat sessions, used instead of the default backend
+    chat_routing_policy: Arc<RwLock<Option<String>>>,
     /// Tool registry for all sessions
     tool_registry: Arc<RwLock<Option<Arc<neomind_tools::ToolRegistry>>>>,
     /// Session cleanup configuration
@@ -217,7 +220,7 @@ pub struct SessionManager {
 impl SessionManager {
     /// Create a new session manager with persistent storage.
     pub fn new() -> Result<Self> {
-        Self::with_path("data/sessions.redb")
+        Self::with_path(neomind_storage::data_path("sessions.redb"))
     }
 
     /// Create a new session manager with in-memory storage.
@@ -238,6 +241,7 @@ impl SessionManager {
             store,
             default_config: AgentConfig::default(),
             default_llm_backend: Arc::new(RwLock::new(None)),
+            chat_routing_policy: Arc::new(RwLock::new(None)),
             tool_registry: Arc::new(RwLock::new(None)),
             cleanup_config: SessionCleanupConfig::default(),
             cleanup_running: Arc::new(RwLock::new(false)),
@@ -256,6 +260,7 @@ impl SessionManager {
             store,
             default_config: AgentConfig::default(),
             default_llm_backend: Arc::new(RwLock::new(None)),
+            chat_routing_policy: Arc::new(RwLock::new(None)),
             tool_registry: Arc::new(RwLock::new(None)),
             cleanup_config: SessionCleanupConfig::default(),
             cleanup_running: Arc::new(RwLock::new(false)),
@@ -465,6 +470,62 @@ impl SessionManager {
         Ok(self.default_llm_backend.read().await.clone())
     }
 
+    /// Route chat sessions through an LLM routing policy, or go back to the
+    /// default backend with `None`. Applies to new and existing sessions.
+    pub async fn set_chat_routing_policy(&self, policy_id: Option<String>) -> Result<()> {
+        let manager = get_instance_manager()
+            .map_err(|e| NeoMindError::Llm(format!("Failed to get instance manager: {}", e)))?;
+        manager
+            .set_chat_policy(policy_id.as_deref())
+            .map_err(|e| NeoMindError::Llm(e.to_string()))?;
+        *self.chat_routing_policy.write().await = policy_id;
+
+        let sessions = self.sessions.read().await;
+        for agent in sessions.values() {
+            self.configure_session_llm(agent).await;
+        }
+        Ok(())
+    }
+
+    /// Get the routing policy used by chat sessions.
+    pub async fn chat_routing_policy(&self) -> Option<String> {
+        self.chat_routing_policy.read().await.clone()
+    }
+
+    /// Load the chat routing policy saved in the instance manager.
+    pub async fn restore_chat_routing_policy(&self) {
+        match get_instance_manager() {
+            Ok(manager) => *self.chat_routing_policy.write().await = manager.chat_policy_id(),
+            Err(e) => tracing::warn!(error = %e, "Failed to load chat routing policy"),
+        }
+    }
+
+    /// Configure a session's LLM: the chat routing policy when one is set,
+    /// otherwise the default backend.
+    async fn configure_session_llm(&self, agent: &Agent) {
+        let policy_id = self.chat_routing_policy.read().await.clone();
+        if let Some(policy_id) = policy_id {
+            let routed = match get_instance_manager() {
+                Ok(manager) => manager.get_routed_runtime(&policy_id).await,
+                Err(e) => Err(e),
+            };
+            match routed {
+                Ok(runtime) => {
+                    agent.set_custom_llm(runtime).await;
+                    return;
+                }
+                Err(e) => {
+                    tracing::warn!(policy = %policy_id, error = %e, "Chat routing policy is unusable, using the default backend")
+                }
+            }
+        }
+
+        let llm_backend = self.default_llm_backend.read().await.clone();
+        if let Some(backend) = llm_backend {
+            let _ = agent.configure_llm(backend).await;
+        }
+    }
+
     /// Configure LLM using the LlmBackendInstanceManager.
     /// This sets the default backend for NEW sessions only.
     pub async fn configure_llm_from_instance_manager(&self) -> Result<()> {
@@ -516,6 +577,11 @@ impl SessionManager {
         *self.tool_registry.write().await = Some(registry);
     }
 
+    /// Get the tool registry used by new sessions, if one has been set.
+    pub async fn tool_registry(&self) -> Option<Arc<neomind_tools::ToolRegistry>> {
+        self.tool_registry.read().await.clone()
+    }
+
     /// P0.3: Get the session store for direct access (for pending stream state management).
     pub fn session_store(&self) -> Arc<SessionStore> {
         self.store.clone()
@@ -534,11 +600,7 @@ impl SessionManager {
             Agent::new(self.default_config.clone(), session_id.clone())
         };
 
-        // Configure LLM if a default backend is set
-        let llm_backend = self.default_llm_backend.read().await.clone();
-        if let Some(backend) = llm_backend {
-            let _ = agent.configure_llm(backend).await;
-        }
+        self.configure_session_llm(&agent).await;
 
         let agent = Arc::new(agent);
 
@@ -591,11 +653,7 @@ impl SessionManager {
             Agent::new(self.default_config.clone(), session_id.to_string())
         };
 
-        // Configure LLM if a default backend is set
-        let llm_backend = self.default_llm_backend.read().await.clone();
-        if let Some(backend) = llm_backend {
-            let _ = agent.configure_llm(backend).await;
-        }
+        self.configure_session_llm(&agent).await;
 
         let agent = Arc::new(agent);
 
@@ -767,6 +825,16 @@ impl SessionManager {
             .map(|meta| meta.title)
     }
 
+    /// Attribute a session's LLM usage to a user.
+    pub async fn set_session_user(&self, session_id: &str, user: &str) -> Result<()> {
+        let agent = self.get_session(session_id).await?;
+        agent
+            .llm_interface()
+            .set_usage_context(UsageContext::for_session(session_id).with_user(user))
+            .await;
+        Ok(())
+    }
+
     /// List all active sessions with their metadata.
     /// Returns sessions from both memory and database (for persistence after restart).
     pub async fn list_sessions_with_info(&self) -> Vec<SessionInfo> {
@@ -1435,6 +1503,7 @@ impl Default for SessionManager {
                 }),
                 default_config: AgentConfig::default(),
                 default_llm_backend: Arc::new(RwLock::new(None)),
+                chat_routing_policy: Arc::new(RwLock::new(None)),
                 tool_registry: Arc::new(RwLock::new(None)),
                 cleanup_config: SessionCleanupConfig::default(),
                 cleanup_running: Arc::new(RwLock::new(false)),
@@ -11,7 +11,7 @@ use serde_json::Value;
 use tokio::sync::RwLock;
 
 use neomind_rules::{
-    dsl::{ComparisonOperator, RuleAction, RuleCondition},
+    dsl::{ComparisonOperator, GroupQuantifier, RuleAction, RuleCondition},
     CompiledRule, HistoryFilter, RuleEngine, RuleHistoryStorage, RuleId, RuleStatus,
 };
 use neomind_tools::{
@@ -211,6 +211,22 @@ impl GetRuleTool {
                     extension_id, metric, min, max
                 ));
             }
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                dsl.push_str(&format!(
+                    "WHEN {}({}).{} {} {}\n",
+                    quantifier.as_str(),
+                    selector,
+                    metric,
+                    operator.as_str(),
+                    threshold
+                ));
+            }
             RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
                 let op = if matches!(&rule.condition, RuleCondition::And(_)) {
                     "AND"
@@ -459,6 +475,22 @@ impl Tool for GetRuleTool {
                     "max": max
                 })
             }
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                serde_json::json!({
+                    "type": "device_group",
+                    "selector": selector.to_string(),
+                    "quantifier": quantifier.as_str(),
+                    "metric": metric,
+                    "operator": operator.as_str(),
+                    "threshold": threshold
+                })
+            }
             RuleCondition::And(conditions) => {
                 serde_json::json!({
                     "type": "and",
@@ -638,6 +670,27 @@ impl ExplainRuleTool {
                     extension_id, metric, min, max, duration_desc
                 )
             }
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                let scope = match quantifier {
+                    GroupQuantifier::Any => "任一",
+                    GroupQuantifier::All => "所有",
+                };
+                format!(
+                    "当{}匹配'{}'的设备的指标'{}' {} {}时，{}触发。",
+                    scope,
+                    selector,
+                    metric,
+                    operator.as_str(),
+                    threshold,
+                    duration_desc
+                )
+            }
             RuleCondition::And(conditions) => {
                 format!(
                     "当{}个条件同时满足时，{}触发。",
@@ -744,6 +797,27 @@ impl ExplainRuleTool {
                     metric, extension_id, min, max, duration_desc
                 )
             }
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                let scope = match quantifier {
+                    GroupQuantifier::Any => "any",
+                    GroupQuantifier::All => "all",
+                };
+                format!(
+                    "When metric '{}' on {} devices matching '{}' is {} {}, trigger {}.",
+                    metric,
+                    scope,
+                    selector,
+                    operator.as_str(),
+                    threshold,
+                    duration_desc
+                )
+            }
             RuleCondition::And(conditions) => {
                 format!(
                     "When {} conditions are all met, trigger {}.",
@@ -1074,6 +1148,15 @@ impl RuleSummary {
                 metric,
                 ..
             } => (extension_id.clone(), metric.clone()),
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                ..
+            } => (
+                format!("{}({})", quantifier.as_str(), selector),
+                metric.clone(),
+            ),
             RuleCondition::And(_) | RuleCondition::Or(_) | RuleCondition::Not(_) => {
                 // For complex conditions, use placeholder
                 ("(complex)".to_string(), "(complex)".to_string())
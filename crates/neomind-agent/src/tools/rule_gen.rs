@@ -266,7 +266,8 @@ impl ValidateRuleDslTool {
                             (None, None)
                         }
                     }
-                    RuleCondition::Not(_) => (None, None),
+                    // Group members are resolved at evaluation time
+                    RuleCondition::DeviceGroup { .. } | RuleCondition::Not(_) => (None, None),
                 };
 
                 // Check if device exists
@@ -397,6 +398,18 @@ impl ValidateRuleDslTool {
                             *max, // Use max as threshold for display
                         )
                     }
+                    RuleCondition::DeviceGroup {
+                        selector,
+                        quantifier,
+                        metric,
+                        operator,
+                        threshold,
+                    } => (
+                        format!("{}({})", quantifier.as_str(), selector),
+                        metric.clone(),
+                        operator.as_str().to_string(),
+                        *threshold,
+                    ),
                     RuleCondition::And(_) | RuleCondition::Or(_) | RuleCondition::Not(_) => (
                         "(complex)".to_string(),
                         "(complex)".to_string(),
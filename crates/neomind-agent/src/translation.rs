@@ -5,7 +5,9 @@
 use serde::{Deserialize, Serialize};
 
 use neomind_devices::mdl_format::DeviceTypeDefinition;
-use neomind_rules::dsl::{ComparisonOperator, ParsedRule, RuleAction, RuleCondition};
+use neomind_rules::dsl::{
+    ComparisonOperator, GroupQuantifier, ParsedRule, RuleAction, RuleCondition,
+};
 
 /// Supported languages for translation.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -326,6 +328,22 @@ impl DslTranslator {
                     extension_id, metric, min, max
                 )
             }
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                format!(
+                    "{}({}).{} {} {}",
+                    quantifier.as_str(),
+                    selector,
+                    metric,
+                    operator.as_str(),
+                    threshold
+                )
+            }
             RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
                 format!(
                     "(complex condition with {} sub-conditions)",
@@ -510,6 +528,44 @@ impl DslTranslator {
                     )
                 }
             },
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => match language {
+                Language::Chinese => {
+                    let scope = match quantifier {
+                        GroupQuantifier::Any => "任一",
+                        GroupQuantifier::All => "所有",
+                    };
+                    format!(
+                        "当{}匹配 '{}' 的设备的指标 '{}' {} {} 时{}",
+                        scope,
+                        selector,
+                        metric,
+                        operator.as_str(),
+                        threshold,
+                        duration_text
+                    )
+                }
+                Language::English => {
+                    let scope = match quantifier {
+                        GroupQuantifier::Any => "any",
+                        GroupQuantifier::All => "all",
+                    };
+                    format!(
+                        "When metric '{}' on {} devices matching '{}' is {} {}{}",
+                        metric,
+                        scope,
+                        selector,
+                        operator.as_str(),
+                        threshold,
+                        duration_text
+                    )
+                }
+            },
             RuleCondition::And(conditions) => match language {
                 Language::Chinese => {
                     format!("当{}个条件同时满足时{}", conditions.len(), duration_text)
@@ -619,6 +675,15 @@ impl DslTranslator {
                 metric,
                 ..
             } => (extension_id.clone(), metric.clone()),
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                ..
+            } => (
+                format!("{}({})", quantifier.as_str(), selector),
+                metric.clone(),
+            ),
             RuleCondition::And(conditions) | RuleCondition::Or(conditions) => (
                 format!("({} devices)", conditions.len()),
                 "(complex)".to_string(),
@@ -53,6 +53,8 @@ impl TestContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -91,6 +93,7 @@ impl TestContext {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
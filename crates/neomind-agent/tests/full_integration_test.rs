@@ -165,6 +165,8 @@ impl FullTestContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources,
             schedule: AgentSchedule {
@@ -203,6 +205,7 @@ impl FullTestContext {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
@@ -246,6 +249,8 @@ impl FullTestContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources,
             schedule: AgentSchedule {
@@ -284,6 +289,7 @@ impl FullTestContext {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
@@ -68,6 +68,8 @@ impl LlmTestContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -106,6 +108,7 @@ impl LlmTestContext {
             error_message: None,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
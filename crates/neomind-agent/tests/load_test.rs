@@ -196,6 +196,8 @@ impl LoadTestContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -234,6 +236,7 @@ impl LoadTestContext {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
@@ -164,6 +164,8 @@ impl SimulationContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources,
             schedule: AgentSchedule {
@@ -202,6 +204,7 @@ impl SimulationContext {
             context_window_size: 20, // 保留更多历史
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
@@ -118,6 +118,8 @@ impl RealPerfTestContext {
             description: None,
             user_prompt: user_prompt.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -156,6 +158,7 @@ impl RealPerfTestContext {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
         };
 
         self.store.save_agent(&agent).await?;
@@ -340,6 +343,8 @@ async fn test_llm_vs_mock_comparison() -> anyhow::Result<()> {
         description: None,
         user_prompt: "监控温度".to_string(),
         llm_backend_id: None,
+        llm_routing_policy: None,
+        response_cache: None,
         parsed_intent: None,
         resources: vec![AgentResource {
             resource_type: ResourceType::Metric,
@@ -383,6 +388,7 @@ async fn test_llm_vs_mock_comparison() -> anyhow::Result<()> {
         context_window_size: 10,
         enable_tool_chaining: false,
         max_chain_depth: 3,
+        approval_policy: None,
     };
 
     ctx.store.save_agent(&agent).await.ok();
@@ -216,7 +216,9 @@ pub struct AuditConfig {
 impl Default for AuditConfig {
     fn default() -> Self {
         Self {
-            log_path: "data/audit.log".to_string(),
+            log_path: neomind_storage::data_path("audit.log")
+                .to_string_lossy()
+                .into_owned(),
             log_to_stdout: true,
             log_to_file: true,
             min_severity: AuditSeverity::Info,
@@ -27,6 +27,7 @@ use uuid::Uuid;
 
 use crate::crypto::CryptoService;
 use crate::server::ServerState;
+use neomind_storage::snapshot::begin_write;
 
 // Table definition for API keys storage (encrypted)
 const API_KEYS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("api_keys");
@@ -56,7 +57,7 @@ pub struct AuthState {
     /// Maps hash -> (encrypted_key, ApiKeyInfo)
     api_keys: Arc<DashMap<String, (String, ApiKeyInfo)>>,
     /// Database path for persistence
-    db_path: &'static str,
+    db_path: String,
     /// Cryptographic service for key encryption
     crypto: Arc<CryptoService>,
 }
@@ -65,11 +66,13 @@ impl AuthState {
     /// Create a new auth state with persistent storage.
     /// Loads existing keys from database, or creates a default key if none exist.
     pub fn new() -> Self {
-        let db_path = "data/api_keys.redb";
+        let db_path = neomind_storage::data_path("api_keys.redb")
+            .to_string_lossy()
+            .into_owned();
         let crypto = Arc::new(CryptoService::from_env_or_generate());
 
         // Try to load from database first
-        let keys = Self::load_from_db(db_path, &crypto).unwrap_or_else(|e| {
+        let keys = Self::load_from_db(&db_path, &crypto).unwrap_or_else(|e| {
             warn!(category = "auth", error = %e, "Failed to load API keys from database, using defaults");
             Self::load_default_keys(&crypto)
         });
@@ -102,7 +105,7 @@ impl AuthState {
 
         Self {
             api_keys: Arc::new(DashMap::new()),
-            db_path: ":memory:",
+            db_path: ":memory:".to_string(),
             crypto,
         }
     }
@@ -170,7 +173,7 @@ impl AuthState {
     /// Save API keys to database with encryption.
     fn save_to_db(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
         let db = Database::open(path)?;
-        let write_txn = db.begin_write()?;
+        let write_txn = begin_write(&db)?;
         {
             let mut table = write_txn.open_table(API_KEYS_TABLE)?;
             let mut hash_table = write_txn.open_table(API_KEY_HASHES_TABLE)?;
@@ -288,7 +291,7 @@ impl AuthState {
         self.api_keys.insert(hash.clone(), (encrypted, info.clone()));
 
         // Persist to database
-        if let Err(e) = self.save_to_db(self.db_path) {
+        if let Err(e) = self.save_to_db(&self.db_path) {
             warn!(category = "auth", error = %e, "Failed to save API key to database");
         }
 
@@ -302,7 +305,7 @@ impl AuthState {
 
         if removed {
             // Persist to database
-            if let Err(e) = self.save_to_db(self.db_path) {
+            if let Err(e) = self.save_to_db(&self.db_path) {
                 warn!(category = "auth", error = %e, "Failed to save API keys to database");
             }
         }
@@ -317,9 +320,9 @@ impl AuthState {
         }
 
         // Try to load from database, or save current keys
-        if Self::load_from_db(self.db_path, &self.crypto).is_ok() {
+        if Self::load_from_db(&self.db_path, &self.crypto).is_ok() {
             info!(category = "auth", "API keys loaded from persistent storage");
-        } else if let Err(e) = self.save_to_db(self.db_path) {
+        } else if let Err(e) = self.save_to_db(&self.db_path) {
             error!(category = "auth", error = %e, "Failed to initialize API key storage");
         }
     }
@@ -47,6 +47,7 @@ use axum::{
     middleware::Next,
     response::{IntoResponse, Json, Response},
 };
+use neomind_storage::snapshot::begin_write;
 
 type HmacSha256 = Hmac<Sha256>;
 
@@ -171,7 +172,7 @@ pub struct AuthUserState {
     /// Active sessions (token -> session info)
     sessions: Arc<RwLock<HashMap<String, SessionInfo>>>,
     /// Database path
-    db_path: &'static str,
+    db_path: String,
     /// JWT secret key
     jwt_secret: String,
     /// Session duration (seconds)
@@ -189,7 +190,9 @@ impl AuthUserState {
             return Self::new_with_memory_store();
         }
 
-        let db_path = "data/users.redb";
+        let db_path = neomind_storage::data_path("users.redb")
+            .to_string_lossy()
+            .into_owned();
         let jwt_secret = std::env::var("NEOMIND_JWT_SECRET").unwrap_or_else(|_| {
             // Generate a random secret (warning: changes on restart!)
             uuid::Uuid::new_v4().to_string().replace("-", "")
@@ -197,7 +200,7 @@ impl AuthUserState {
 
         // Load users from database
         // If no users exist, the setup wizard will handle creating the first admin
-        let users = Self::load_users_from_db(db_path).unwrap_or_default();
+        let users = Self::load_users_from_db(&db_path).unwrap_or_default();
 
         if users.is_empty() {
             info!(
@@ -218,15 +221,12 @@ impl AuthUserState {
     /// Create a new auth state with custom configuration (for testing).
     pub fn with_config(db_path: String, jwt_secret: String) -> Self {
         let users = Self::load_users_from_db(&db_path).unwrap_or_default();
-        // Leak the strings to get &'static str for db_path
-        let db_path_static: &'static str = Box::leak(db_path.into_boxed_str());
-        let jwt_secret_owned = jwt_secret;
 
         Self {
             users: Arc::new(RwLock::new(users)),
             sessions: Arc::new(RwLock::new(HashMap::new())),
-            db_path: db_path_static,
-            jwt_secret: jwt_secret_owned,
+            db_path,
+            jwt_secret,
             session_duration: 7 * 24 * 60 * 60,
         }
     }
@@ -241,7 +241,7 @@ impl AuthUserState {
         Self {
             users: Arc::new(RwLock::new(HashMap::new())),
             sessions: Arc::new(RwLock::new(HashMap::new())),
-            db_path: ":memory:", // Placeholder, won't be used
+            db_path: ":memory:".to_string(), // Placeholder, won't be used
             jwt_secret,
             session_duration: 7 * 24 * 60 * 60,
         }
@@ -303,7 +303,7 @@ impl AuthUserState {
             Database::create(path)?
         };
 
-        let write_txn = db.begin_write()?;
+        let write_txn = begin_write(&db)?;
         {
             let mut table = write_txn.open_table(USERS_TABLE)?;
             table.insert(username.as_str(), user_bytes.as_slice())?;
@@ -455,7 +455,7 @@ impl AuthUserState {
         };
 
         // Save to database synchronously (ensures persistence before returning)
-        if let Err(e) = Self::save_user_to_db(self.db_path, &user) {
+        if let Err(e) = Self::save_user_to_db(&self.db_path, &user) {
             error!(category = "auth", username = username, error = %e, "Failed to save user to database");
             return Err(AuthError::DatabaseError(format!(
                 "Failed to save user: {}",
@@ -725,42 +725,32 @@ pub type CurrentUserExtension = SessionInfo;
 mod tests {
     use super::*;
 
-    fn get_project_data_path(filename: &str) -> std::path::PathBuf {
-        std::path::PathBuf::from(env!("CARGO_MANIFEST_DIR"))
-            .parent()
-            .unwrap()
-            .join("data")
-            .join(filename)
-    }
-
-    fn cleanup_test_db(path: &std::path::Path) {
-        let _ = std::fs::remove_file(path);
-        let _ = std::fs::remove_file(format!("{}.lock", path.display()));
-    }
-
-    fn make_test_auth(test_name: &str) -> (AuthUserState, std::path::PathBuf) {
-        let db_path = get_project_data_path(&format!("test_{}.redb", test_name));
-        cleanup_test_db(&db_path);
+    /// Auth state backed by a database in a fresh temporary directory.
+    ///
+    /// The directory (and the database) is removed when the returned guard drops.
+    fn make_test_auth() -> (AuthUserState, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        let db_path = dir.path().join("users.redb");
         let jwt_secret = std::env::var("NEOMIND_JWT_SECRET")
             .unwrap_or_else(|_| "test_secret_key_12345678".to_string());
-        (AuthUserState::with_config(db_path.display().to_string(), jwt_secret), db_path)
+        let auth = AuthUserState::with_config(db_path.display().to_string(), jwt_secret);
+        (auth, dir)
     }
 
     #[tokio::test]
     async fn test_user_registration() {
-        let (auth, db_path) = make_test_auth("registration");
+        let (auth, _dir) = make_test_auth();
         let (user, token) = auth
             .register("testuser", "password123", UserRole::User)
             .await
             .unwrap();
         assert_eq!(user.username, "testuser");
         assert!(!token.is_empty());
-        cleanup_test_db(&db_path);
     }
 
     #[tokio::test]
     async fn test_user_login() {
-        let (auth, _) = make_test_auth("login");
+        let (auth, _dir) = make_test_auth();
         auth.register("testuser", "password123", UserRole::User)
             .await
             .unwrap();
@@ -772,7 +762,7 @@ mod tests {
 
     #[tokio::test]
     async fn test_token_validation() {
-        let (auth, db_path) = make_test_auth("token_validation");
+        let (auth, _dir) = make_test_auth();
         let (_, token) = auth
             .register("testuser", "password123", UserRole::User)
             .await
@@ -780,6 +770,5 @@ mod tests {
 
         let session = auth.validate_token(&token).unwrap();
         assert_eq!(session.username, "testuser");
-        cleanup_test_db(&db_path);
     }
 }
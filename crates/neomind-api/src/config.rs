@@ -9,8 +9,9 @@ use neomind_agent::LlmBackend;
 use neomind_core::config::{
     endpoints, env_vars, models, normalize_ollama_endpoint, normalize_openai_endpoint,
 };
-use neomind_memory::{EmbeddingConfig, TieredMemoryConfig};
-use neomind_storage::{LlmBackendType, LlmSettings};
+use neomind_devices::SparkplugConfig;
+use neomind_memory::{EmbeddingConfig, FusionMethod, TieredMemoryConfig, DEFAULT_RRF_K};
+use neomind_storage::{BackupConfig, InterruptedExecutionPolicy, LlmBackendType, LlmSettings};
 use serde::Deserialize;
 use std::sync::Arc;
 use tracing::{info, warn};
@@ -19,12 +20,14 @@ use tracing::{info, warn};
 pub use neomind_devices::EmbeddedBrokerConfig;
 
 /// Path to the settings database.
-const SETTINGS_DB_PATH: &str = "data/settings.redb";
+pub(crate) fn settings_db_path() -> std::path::PathBuf {
+    neomind_storage::data_path("settings.redb")
+}
 
 /// Get or create the global settings store (cached).
 fn get_settings_store() -> Result<Arc<neomind_storage::SettingsStore>, Box<dyn std::error::Error>> {
     // SettingsStore::open already has internal caching via SETTINGS_STORE_SINGLETON
-    Ok(neomind_storage::SettingsStore::open(SETTINGS_DB_PATH)?)
+    Ok(neomind_storage::SettingsStore::open(settings_db_path())?)
 }
 
 /// Configuration sources in priority order.
@@ -44,7 +47,8 @@ impl ConfigSource {
             if store.get_llm_settings().model != "default" {
                 info!(
                     category = "config",
-                    "Loading config from: {} (redb database)", SETTINGS_DB_PATH
+                    "Loading config from: {:?} (redb database)",
+                    settings_db_path()
                 );
                 return ConfigSource::Database;
             }
@@ -273,7 +277,7 @@ pub fn load_llm_config() -> Option<LlmBackend> {
 
 /// Save LLM settings to the database (called from Web UI).
 pub async fn save_llm_settings(settings: &LlmSettings) -> Result<(), Box<dyn std::error::Error>> {
-    let store = neomind_storage::SettingsStore::open(SETTINGS_DB_PATH)?;
+    let store = neomind_storage::SettingsStore::open(settings_db_path())?;
     store.save_llm_settings(settings)?;
     info!(category = "ai", backend = format_args!("{:?}", settings.backend), model = %settings.model, "Saved LLM settings to database");
     Ok(())
@@ -303,6 +307,16 @@ struct TomlConfig {
     memory: Option<TomlMemoryConfig>,
     #[serde(default)]
     server: Option<TomlServerConfig>,
+    #[serde(default)]
+    backup: Option<BackupConfig>,
+    #[serde(default)]
+    agents: Option<TomlAgentsConfig>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TomlAgentsConfig {
+    #[serde(default)]
+    interrupted_executions: InterruptedExecutionPolicy,
 }
 
 #[derive(Debug, Deserialize)]
@@ -345,6 +359,9 @@ struct TomlMqttConfig {
     #[serde(default = "default_mqtt_auto_discovery")]
     #[allow(dead_code)] // Reserved for future auto-discovery feature
     auto_discovery: bool,
+    /// Sparkplug B decoding for the internal MQTT adapter
+    #[serde(default)]
+    sparkplug: Option<SparkplugConfig>,
 }
 
 fn default_mqtt_listen() -> String {
@@ -453,10 +470,10 @@ struct TomlMemoryConfig {
     /// Embedding dimension (only used with Simple embedding)
     #[serde(default = "default_embedding_dim")]
     embedding_dim: usize,
-    /// Embedding provider: "simple", "ollama", or "openai"
+    /// Embedding provider: "simple", "local", "ollama", or "openai"
     #[serde(default = "default_embedding_provider")]
     embedding_provider: String,
-    /// Ollama endpoint (for ollama embedding)
+    /// Ollama endpoint (for ollama embedding), or models directory (for local embedding)
     #[serde(default)]
     embedding_endpoint: Option<String>,
     /// Embedding model name
@@ -465,6 +482,9 @@ struct TomlMemoryConfig {
     /// OpenAI API key (for openai embedding)
     #[serde(default)]
     embedding_api_key: Option<String>,
+    /// Inference threads (for local embedding)
+    #[serde(default)]
+    embedding_threads: Option<usize>,
     /// Whether to use hybrid search (semantic + BM25)
     #[serde(default = "default_use_hybrid_search")]
     use_hybrid_search: bool,
@@ -474,6 +494,12 @@ struct TomlMemoryConfig {
     /// BM25 weight for hybrid search (0.0 - 1.0)
     #[serde(default = "default_bm25_weight")]
     bm25_weight: f32,
+    /// Fusion of lexical and semantic rankings: "rrf" or "weighted"
+    #[serde(default)]
+    fusion: FusionMethod,
+    /// RRF rank constant
+    #[serde(default = "default_rrf_k")]
+    rrf_k: f32,
 }
 
 fn default_max_short_term_messages() -> usize {
@@ -503,6 +529,9 @@ fn default_semantic_weight() -> f32 {
 fn default_bm25_weight() -> f32 {
     0.3
 }
+fn default_rrf_k() -> f32 {
+    DEFAULT_RRF_K
+}
 
 /// Load memory configuration from config.toml.
 ///
@@ -523,6 +552,19 @@ pub fn load_memory_config() -> Option<TieredMemoryConfig> {
 
     // Build embedding config from TOML settings
     let embedding_config = match memory.embedding_provider.as_str() {
+        "local" => {
+            let model = memory
+                .embedding_model
+                .unwrap_or_else(|| "bge-small-zh-v1.5".to_string());
+            let mut config = EmbeddingConfig::local(&model);
+            if let Some(models_dir) = memory.embedding_endpoint {
+                config = config.with_endpoint(&models_dir);
+            }
+            if let Some(threads) = memory.embedding_threads {
+                config = config.with_threads(threads);
+            }
+            Some(config)
+        }
         "ollama" => {
             let model = memory
                 .embedding_model
@@ -557,6 +599,8 @@ pub fn load_memory_config() -> Option<TieredMemoryConfig> {
         use_hybrid_search: memory.use_hybrid_search,
         semantic_weight: memory.semantic_weight,
         bm25_weight: memory.bm25_weight,
+        fusion: memory.fusion,
+        rrf_k: memory.rrf_k,
     })
 }
 
@@ -575,6 +619,37 @@ pub fn get_memory_config() -> TieredMemoryConfig {
     TieredMemoryConfig::default()
 }
 
+/// Get snapshot backup configuration (config.toml `[backup]` > default).
+pub fn get_backup_config() -> BackupConfig {
+    std::fs::read_to_string("config.toml")
+        .ok()
+        .and_then(|content| toml::from_str::<TomlConfig>(&content).ok())
+        .and_then(|config| config.backup)
+        .unwrap_or_default()
+}
+
+/// Get Sparkplug B configuration for the internal MQTT adapter
+/// (config.toml `[mqtt.sparkplug]` > disabled).
+pub fn get_sparkplug_config() -> SparkplugConfig {
+    std::fs::read_to_string("config.toml")
+        .ok()
+        .and_then(|content| toml::from_str::<TomlConfig>(&content).ok())
+        .and_then(|config| config.mqtt)
+        .and_then(|mqtt| mqtt.sparkplug)
+        .unwrap_or_default()
+}
+
+/// Get the policy for agent executions interrupted by a crash or restart
+/// (config.toml `[agents] interrupted_executions` > resume).
+pub fn get_interrupted_execution_policy() -> InterruptedExecutionPolicy {
+    std::fs::read_to_string("config.toml")
+        .ok()
+        .and_then(|content| toml::from_str::<TomlConfig>(&content).ok())
+        .and_then(|config| config.agents)
+        .map(|agents| agents.interrupted_executions)
+        .unwrap_or_default()
+}
+
 /// Load server configuration (config.toml > env > default).
 ///
 /// Priority: config.toml > environment variables > default (0.0.0.0:9375)
@@ -617,6 +692,27 @@ pub fn get_server_config() -> (String, u16) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_parse_backup_config() {
+        let config: TomlConfig = toml::from_str(
+            r#"
+[backup]
+backup_dir = "/var/backups/neomind"
+max_backups = 7
+schedule_interval_secs = 86400
+"#,
+        )
+        .unwrap();
+        let backup = config.backup.unwrap();
+        assert_eq!(
+            backup.backup_dir,
+            std::path::PathBuf::from("/var/backups/neomind")
+        );
+        assert_eq!(backup.max_backups, 7);
+        assert_eq!(backup.schedule_interval_secs, Some(86400));
+        assert!(!backup.compress);
+    }
+
     #[test]
     fn test_parse_toml_config() {
         let toml_content = r#"
@@ -653,6 +749,7 @@ embedding_provider = "simple"
 use_hybrid_search = true
 semantic_weight = 0.8
 bm25_weight = 0.2
+fusion = "weighted"
 "#;
         let config: TomlConfig = toml::from_str(toml_content).unwrap();
         assert!(config.memory.is_some());
@@ -664,6 +761,8 @@ bm25_weight = 0.2
         assert!(memory.use_hybrid_search);
         assert_eq!(memory.semantic_weight, 0.8);
         assert_eq!(memory.bm25_weight, 0.2);
+        assert_eq!(memory.fusion, FusionMethod::Weighted);
+        assert_eq!(memory.rrf_k, DEFAULT_RRF_K);
     }
 
     #[test]
@@ -685,6 +784,26 @@ embedding_model = "nomic-embed-text"
         assert_eq!(memory.embedding_model, Some("nomic-embed-text".to_string()));
     }
 
+    #[test]
+    fn test_parse_memory_config_with_local() {
+        let toml_content = r#"
+[memory]
+embedding_provider = "local"
+embedding_endpoint = "/opt/neomind/models"
+embedding_model = "bge-small-zh-v1.5"
+embedding_threads = 2
+"#;
+        let config: TomlConfig = toml::from_str(toml_content).unwrap();
+        let memory = config.memory.unwrap();
+
+        assert_eq!(memory.embedding_provider, "local");
+        assert_eq!(
+            memory.embedding_endpoint,
+            Some("/opt/neomind/models".to_string())
+        );
+        assert_eq!(memory.embedding_threads, Some(2));
+    }
+
     #[test]
     fn test_parse_memory_config_with_openai() {
         let toml_content = r#"
@@ -1,15 +1,16 @@
 //! AI Agents handlers for user-defined automation agents.
 
 use axum::{
-    extract::{Path, State},
+    extract::{Path, Query, State},
     Json,
 };
 use serde_json::{json, Value};
 
 use neomind_llm::instance_manager::get_instance_manager;
 use neomind_storage::{
-    AgentExecutionRecord, AgentFilter, AgentMemory, AgentSchedule, AgentStats, AgentStatus,
-    AiAgent, ResourceType, ScheduleType, UserMessage,
+    AgentExecutionRecord, AgentFilter, AgentMemory, AgentMessage, AgentSchedule, AgentStats,
+    AgentStatus, AiAgent, ApprovalPolicy, ResourceType, ResponseCacheConfig, ScheduleType,
+    UserMessage,
 };
 
 use super::{
@@ -43,6 +44,9 @@ fn resource_type_to_string(resource_type: &ResourceType) -> &'static str {
         ResourceType::DataStream => "data_stream",
         ResourceType::ExtensionTool => "extension_tool",
         ResourceType::ExtensionMetric => "extension_metric",
+        ResourceType::DeviceGroup => "device_group",
+        ResourceType::McpTool => "mcp_tool",
+        ResourceType::Agent => "agent",
     }
 }
 
@@ -56,12 +60,21 @@ fn resource_type_to_string(resource_type: &ResourceType) -> &'static str {
 /// - "extension:id:field" with field containing "." -> ExtensionMetric (nested data field)
 /// - "extension:id:field" without "." -> ExtensionMetric (simple metric, default)
 /// - "extension:id" -> ExtensionTool (extension reference without field)
+/// - "mcp:server:tool" -> McpTool
+/// - "agent:id" -> Agent
 ///
 /// Users SHOULD specify resource_type explicitly to avoid ambiguity.
 ///
 /// # Returns
 /// Inferred ResourceType, or Device as default fallback
 fn infer_resource_type_from_id(resource_id: &str) -> ResourceType {
+    if resource_id.starts_with("mcp:") {
+        return ResourceType::McpTool;
+    }
+    if resource_id.starts_with("agent:") {
+        return ResourceType::Agent;
+    }
+
     // Try parsing as standard DataSourceId (three-part format: type:id:field)
     if let Some(ds_id) = DataSourceId::parse(resource_id) {
         match ds_id.source_type {
@@ -145,6 +158,12 @@ struct AgentDetailDto {
     error_message: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     llm_backend_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    llm_routing_policy: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_cache: Option<ResponseCacheConfig>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    approval_policy: Option<ApprovalPolicy>,
     // Advanced configuration fields
     #[serde(skip_serializing_if = "Option::is_none")]
     enable_tool_chaining: Option<bool>,
@@ -286,6 +305,7 @@ struct AgentExecutionDto {
     duration_ms: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    simulated: bool,
 }
 
 /// Data collected for API responses.
@@ -339,6 +359,9 @@ struct ExecutionResultDto {
     notifications_sent: Vec<NotificationSentDto>,
     summary: String,
     success_rate: f32,
+    /// Tasks delegated to or received from other agents
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    agent_messages: Vec<AgentMessage>,
 }
 
 /// Action executed for API responses.
@@ -375,6 +398,7 @@ struct AgentExecutionDetailDto {
     duration_ms: u64,
     #[serde(skip_serializing_if = "Option::is_none")]
     error: Option<String>,
+    simulated: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     decision_process: Option<DecisionProcessDto>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -403,6 +427,15 @@ pub struct CreateAgentRequest {
     pub schedule: AgentScheduleRequest,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub llm_backend_id: Option<String>,
+    /// Routing policy ID, used instead of `llm_backend_id` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_routing_policy: Option<String>,
+    /// Opt-in LLM response caching
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheConfig>,
+    /// Actions that must be approved before they run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<ApprovalPolicy>,
     /// Enable tool chaining (default: false)
     #[serde(default)]
     pub enable_tool_chaining: Option<bool>,
@@ -475,6 +508,15 @@ pub struct UpdateAgentRequest {
     pub status: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub llm_backend_id: Option<String>,
+    /// Routing policy ID, used instead of `llm_backend_id` when set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_routing_policy: Option<String>,
+    /// Response caching settings; a `ttl_secs` of 0 turns caching off
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheConfig>,
+    /// Approval policy; a policy without rules removes it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<ApprovalPolicy>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub schedule: Option<AgentScheduleRequest>,
     // New format: resources array
@@ -517,6 +559,14 @@ pub struct ExecuteAgentRequest {
     pub event_data: Option<serde_json::Value>,
 }
 
+/// Query parameters for triggering an agent execution.
+#[derive(Debug, Default, serde::Deserialize)]
+pub struct ExecuteAgentQuery {
+    /// Simulate the execution: side-effecting actions are recorded, not executed
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
 // ============================================================================
 // Conversion functions
 // ============================================================================
@@ -671,6 +721,9 @@ impl From<&AiAgent> for AgentDetailDto {
             last_execution_at: agent.last_execution_at.map(format_datetime),
             error_message: agent.error_message.clone(),
             llm_backend_id: agent.llm_backend_id.clone(),
+            llm_routing_policy: agent.llm_routing_policy.clone(),
+            response_cache: agent.response_cache.clone(),
+            approval_policy: agent.approval_policy.clone(),
             // Advanced configuration
             enable_tool_chaining: Some(agent.enable_tool_chaining),
             max_chain_depth: Some(agent.max_chain_depth),
@@ -690,6 +743,7 @@ impl From<AgentExecutionRecord> for AgentExecutionDto {
             status: format!("{:?}", record.status),
             duration_ms: record.duration_ms,
             error: record.error,
+            simulated: record.simulated,
         }
     }
 }
@@ -704,6 +758,7 @@ impl From<AgentExecutionRecord> for AgentExecutionDetailDto {
             status: format!("{:?}", record.status),
             duration_ms: record.duration_ms,
             error: record.error,
+            simulated: record.simulated,
             decision_process: Some(DecisionProcessDto {
                 situation_analysis: record.decision_process.situation_analysis,
                 data_collected: record
@@ -781,6 +836,7 @@ impl From<AgentExecutionRecord> for AgentExecutionDetailDto {
                     .collect(),
                 summary: r.summary,
                 success_rate: r.success_rate,
+                agent_messages: r.agent_messages,
             }),
         }
     }
@@ -834,6 +890,10 @@ pub async fn create_agent(
     State(state): State<ServerState>,
     Json(request): Json<CreateAgentRequest>,
 ) -> HandlerResult<Value> {
+    if let Some(ref cache) = request.response_cache {
+        cache.validate().map_err(ErrorResponse::bad_request)?;
+    }
+
     // Convert request to storage types
     let schedule_type = match request.schedule.schedule_type.as_str() {
         "interval" => ScheduleType::Interval,
@@ -870,6 +930,9 @@ pub async fn create_agent(
                 "extension_metric" | "ExtensionMetric" => ResourceType::ExtensionMetric,
                 "extension_tool" | "ExtensionTool" => ResourceType::ExtensionTool,
                 "data_stream" | "DataStream" => ResourceType::DataStream,
+                "device_group" | "DeviceGroup" => ResourceType::DeviceGroup,
+                "mcp_tool" | "McpTool" => ResourceType::McpTool,
+                "agent" | "Agent" => ResourceType::Agent,
                 _ => {
                     // Use type-safe inference based on DataSourceId parsing
                     infer_resource_type_from_id(&req_resource.resource_id)
@@ -930,6 +993,11 @@ pub async fn create_agent(
         }
     }
 
+    let llm_routing_policy = request.llm_routing_policy.filter(|id| !id.is_empty());
+    if let Some(policy_id) = &llm_routing_policy {
+        validate_routing_policy(policy_id)?;
+    }
+
     // Create the agent
     let agent = AiAgent {
         id: uuid::Uuid::new_v4().to_string(),
@@ -937,6 +1005,8 @@ pub async fn create_agent(
         description: request.description.clone(),
         user_prompt: request.user_prompt,
         llm_backend_id: request.llm_backend_id,
+        llm_routing_policy,
+        response_cache: request.response_cache,
         parsed_intent: None,
         resources,
         schedule,
@@ -954,6 +1024,7 @@ pub async fn create_agent(
         context_window_size: request.context_window_size.unwrap_or(10),
         enable_tool_chaining: request.enable_tool_chaining.unwrap_or(false),
         max_chain_depth: request.max_chain_depth.unwrap_or(3),
+        approval_policy: request.approval_policy.filter(|p| !p.rules.is_empty()),
     };
 
     // Save to storage
@@ -991,6 +1062,19 @@ pub async fn create_agent(
     }))
 }
 
+/// Reject routing policy IDs that name no stored policy.
+fn validate_routing_policy(policy_id: &str) -> Result<(), ErrorResponse> {
+    let manager = get_instance_manager().map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    match manager.get_policy(policy_id) {
+        Ok(Some(_)) => Ok(()),
+        Ok(None) => Err(ErrorResponse::bad_request(format!(
+            "Routing policy not found: {}",
+            policy_id
+        ))),
+        Err(e) => Err(ErrorResponse::internal(e.to_string())),
+    }
+}
+
 /// Update an AI Agent.
 pub async fn update_agent(
     State(state): State<ServerState>,
@@ -1019,6 +1103,24 @@ pub async fn update_agent(
     if let Some(backend_id) = request.llm_backend_id {
         agent.llm_backend_id = Some(backend_id);
     }
+    if let Some(policy_id) = request.llm_routing_policy {
+        // An empty ID clears the policy and falls back to the backend ID
+        if !policy_id.is_empty() {
+            validate_routing_policy(&policy_id)?;
+        }
+        agent.llm_routing_policy = Some(policy_id).filter(|id| !id.is_empty());
+    }
+    if let Some(cache) = request.response_cache {
+        if cache.ttl_secs == 0 {
+            agent.response_cache = None;
+        } else {
+            cache.validate().map_err(ErrorResponse::bad_request)?;
+            agent.response_cache = Some(cache);
+        }
+    }
+    if let Some(policy) = request.approval_policy {
+        agent.approval_policy = Some(policy).filter(|p| !p.rules.is_empty());
+    }
     if let Some(status_str) = request.status {
         agent.status = match status_str.as_str() {
             "active" => AgentStatus::Active,
@@ -1076,6 +1178,9 @@ pub async fn update_agent(
                 "extension_metric" | "ExtensionMetric" => ResourceType::ExtensionMetric,
                 "extension_tool" | "ExtensionTool" => ResourceType::ExtensionTool,
                 "data_stream" | "DataStream" => ResourceType::DataStream,
+                "device_group" | "DeviceGroup" => ResourceType::DeviceGroup,
+                "mcp_tool" | "McpTool" => ResourceType::McpTool,
+                "agent" | "Agent" => ResourceType::Agent,
                 _ => {
                     // Use type-safe inference based on DataSourceId parsing
                     infer_resource_type_from_id(&req_resource.resource_id)
@@ -1250,9 +1355,13 @@ pub async fn delete_agent(
 }
 
 /// Execute an AI Agent immediately.
+///
+/// With `?dry_run=true` the execution is simulated and the full record is
+/// returned, listing the intended actions and notifications.
 pub async fn execute_agent(
     State(state): State<ServerState>,
     Path(id): Path<String>,
+    Query(query): Query<ExecuteAgentQuery>,
     Json(_request): Json<ExecuteAgentRequest>,
 ) -> HandlerResult<Value> {
     // Get or initialize the agent manager
@@ -1261,6 +1370,28 @@ pub async fn execute_agent(
         .await
         .map_err(|e| ErrorResponse::internal(format!("Failed to get agent manager: {}", e)))?;
 
+    if query.dry_run {
+        let record = agent_manager
+            .dry_run_agent(&id)
+            .await
+            .map_err(|e| match e {
+                neomind_agent::NeoMindError::NotFound(_) => {
+                    ErrorResponse::not_found(format!("Agent {}", id))
+                }
+                e => ErrorResponse::internal(format!("Failed to simulate agent: {}", e)),
+            })?;
+
+        tracing::info!(
+            execution_id = %record.id,
+            agent_id = %id,
+            status = ?record.status,
+            intended_actions = record.result.as_ref().map_or(0, |r| r.actions_executed.len()),
+            "Simulated AI Agent execution"
+        );
+
+        return ok(json!(AgentExecutionDetailDto::from(record)));
+    }
+
     // Execute the agent using the manager (this does full execution with data collection, analysis, and actions)
     let summary = agent_manager
         .execute_agent_now(&id)
@@ -0,0 +1,160 @@
+//! Approval queue handlers.
+//!
+//! Agent actions matching an agent's approval policy wait here until a user
+//! approves or denies them. Approved actions run immediately; requests that
+//! are not decided in time expire.
+
+use axum::{
+    extract::{Path, Query, State},
+    http::StatusCode,
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use neomind_agent::NeoMindError;
+use neomind_storage::{ApprovalFilter, ApprovalStatus};
+
+use super::ServerState;
+use crate::auth_users::{SessionInfo, UserRole};
+use crate::handlers::common::{ok, HandlerResult};
+use crate::models::ErrorResponse;
+
+/// Query parameters for listing approval requests.
+#[derive(Debug, Deserialize)]
+pub struct ListApprovalsQuery {
+    pub agent_id: Option<String>,
+    /// pending, approved, denied or expired
+    pub status: Option<String>,
+    pub limit: Option<usize>,
+}
+
+/// Request body for approving or denying an action.
+#[derive(Debug, Default, Deserialize)]
+pub struct ApprovalDecisionRequest {
+    #[serde(default)]
+    pub comment: Option<String>,
+}
+
+fn parse_status(status: &str) -> Result<ApprovalStatus, ErrorResponse> {
+    match status {
+        "pending" => Ok(ApprovalStatus::Pending),
+        "approved" => Ok(ApprovalStatus::Approved),
+        "denied" => Ok(ApprovalStatus::Denied),
+        "expired" => Ok(ApprovalStatus::Expired),
+        _ => Err(ErrorResponse::bad_request(format!(
+            "Invalid approval status: {}",
+            status
+        ))),
+    }
+}
+
+fn approval_error(e: NeoMindError) -> ErrorResponse {
+    match e {
+        NeoMindError::NotFound(resource) => ErrorResponse::not_found(resource),
+        NeoMindError::Validation(message) => ErrorResponse::conflict(message),
+        e => ErrorResponse::internal(e.to_string()),
+    }
+}
+
+/// List approval requests, newest first.
+///
+/// GET /api/approvals?agent_id=...&status=pending
+pub async fn list_approvals_handler(
+    State(state): State<ServerState>,
+    Query(query): Query<ListApprovalsQuery>,
+) -> HandlerResult<Value> {
+    let status = query.status.as_deref().map(parse_status).transpose()?;
+
+    // Settle expired requests first so they are not offered for approval
+    if let Ok(manager) = state.get_or_init_agent_manager().await {
+        manager
+            .executor()
+            .expire_approvals()
+            .await
+            .map_err(approval_error)?;
+    }
+
+    let approvals = state
+        .agents
+        .agent_store
+        .query_approvals(ApprovalFilter {
+            agent_id: query.agent_id,
+            status,
+            limit: query.limit,
+        })
+        .await?;
+
+    ok(json!({
+        "approvals": approvals,
+        "count": approvals.len(),
+    }))
+}
+
+/// Get one approval request.
+///
+/// GET /api/approvals/:id
+pub async fn get_approval_handler(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> HandlerResult<Value> {
+    let approval = state
+        .agents
+        .agent_store
+        .get_approval(&id)
+        .await?
+        .ok_or_else(|| ErrorResponse::not_found(format!("Approval request {}", id)))?;
+    ok(json!(approval))
+}
+
+/// Approve a queued action and execute it.
+///
+/// POST /api/approvals/:id/approve
+pub async fn approve_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    Path(id): Path<String>,
+    request: Option<Json<ApprovalDecisionRequest>>,
+) -> HandlerResult<Value> {
+    decide(state, user, id, true, request).await
+}
+
+/// Deny a queued action.
+///
+/// POST /api/approvals/:id/deny
+pub async fn deny_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    Path(id): Path<String>,
+    request: Option<Json<ApprovalDecisionRequest>>,
+) -> HandlerResult<Value> {
+    decide(state, user, id, false, request).await
+}
+
+async fn decide(
+    state: ServerState,
+    user: SessionInfo,
+    id: String,
+    approve: bool,
+    request: Option<Json<ApprovalDecisionRequest>>,
+) -> HandlerResult<Value> {
+    if user.role == UserRole::Viewer {
+        return Err(ErrorResponse::new(
+            "FORBIDDEN",
+            "Viewers cannot decide on approval requests",
+            StatusCode::FORBIDDEN,
+        ));
+    }
+    let comment = request
+        .and_then(|Json(request)| request.comment)
+        .filter(|comment| !comment.trim().is_empty());
+
+    let manager = state.get_or_init_agent_manager().await?;
+    let approval = manager
+        .executor()
+        .resolve_approval(&id, approve, &user.username, comment)
+        .await
+        .map_err(approval_error)?;
+
+    ok(json!(approval))
+}
@@ -0,0 +1,185 @@
+//! System snapshot handlers (admin only).
+//!
+//! Snapshots capture every store in the data directory into one archive.
+//! Restores are staged and applied on the next startup, before any store is
+//! opened.
+
+use std::time::Duration;
+
+use axum::{
+    extract::{Extension, Path as AxumPath, State},
+    http::StatusCode,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use neomind_storage::snapshot::pause_writes;
+use neomind_storage::{BackupManager, BackupMetadata, SnapshotVerification};
+
+use super::{
+    common::{ok, HandlerResult},
+    ServerState,
+};
+use crate::auth_users::{SessionInfo, UserRole};
+use crate::models::ErrorResponse;
+
+/// How long a snapshot waits for in-flight writes to finish.
+const QUIESCE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Request body for creating a snapshot.
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateSnapshotRequest {
+    /// Optional description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Verify the archive after writing it
+    #[serde(default)]
+    pub verify: bool,
+}
+
+fn require_admin(user: &SessionInfo) -> Result<(), ErrorResponse> {
+    if user.role != UserRole::Admin {
+        return Err(ErrorResponse::new(
+            "FORBIDDEN",
+            "Admin access required",
+            StatusCode::FORBIDDEN,
+        ));
+    }
+    Ok(())
+}
+
+fn backup_manager() -> Result<BackupManager, ErrorResponse> {
+    Ok(BackupManager::with_config(
+        crate::config::get_backup_config(),
+    )?)
+}
+
+/// Run blocking archive work off the async runtime.
+async fn blocking<T: Send + 'static>(
+    work: impl FnOnce() -> neomind_storage::Result<T> + Send + 'static,
+) -> Result<T, ErrorResponse> {
+    tokio::task::spawn_blocking(work)
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Snapshot task failed: {}", e)))?
+        .map_err(Into::into)
+}
+
+/// Take a snapshot of every store with writers paused.
+///
+/// Used by the API and the snapshot schedule.
+pub async fn take_snapshot(
+    state: &ServerState,
+    description: Option<String>,
+) -> Result<BackupMetadata, ErrorResponse> {
+    // Commit buffered telemetry before pausing writers
+    if let Err(e) = state.devices.telemetry.flush().await {
+        tracing::warn!("Telemetry flush before snapshot failed: {}", e);
+    }
+
+    let manager = backup_manager()?;
+    let data_dir = neomind_storage::data_dir();
+    let metadata = blocking(move || {
+        let Some(_paused) = pause_writes(QUIESCE_TIMEOUT) else {
+            return Ok(None);
+        };
+        manager.create_snapshot(&data_dir, description).map(Some)
+    })
+    .await?
+    .ok_or_else(|| ErrorResponse::service_unavailable("Timed out waiting for in-flight writes"))?;
+
+    tracing::info!(
+        id = %metadata.id,
+        files = metadata.table_count,
+        size_bytes = metadata.size_bytes,
+        "System snapshot created"
+    );
+    Ok(metadata)
+}
+
+/// List backups.
+///
+/// GET /api/system/backups
+pub async fn list_backups_handler(
+    Extension(user): Extension<SessionInfo>,
+) -> HandlerResult<Vec<BackupMetadata>> {
+    require_admin(&user)?;
+    ok(backup_manager()?.list_backups()?)
+}
+
+/// Create a snapshot.
+///
+/// POST /api/system/backups
+pub async fn create_backup_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    request: Option<Json<CreateSnapshotRequest>>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let Json(request) = request.unwrap_or_default();
+
+    let metadata = take_snapshot(&state, request.description).await?;
+    let verification = if request.verify {
+        let path = metadata.path.clone();
+        Some(blocking(move || neomind_storage::verify_snapshot_archive(&path)).await?)
+    } else {
+        None
+    };
+
+    ok(json!({
+        "backup": metadata,
+        "verification": verification,
+    }))
+}
+
+/// Verify a snapshot: checksums and opening every store.
+///
+/// GET /api/system/backups/:id/verify
+pub async fn verify_backup_handler(
+    Extension(user): Extension<SessionInfo>,
+    AxumPath(id): AxumPath<String>,
+) -> HandlerResult<SnapshotVerification> {
+    require_admin(&user)?;
+    let manager = backup_manager()?;
+    ok(blocking(move || manager.verify_snapshot(&id)).await?)
+}
+
+/// Stage a snapshot for restore. It is applied on the next startup.
+///
+/// POST /api/system/backups/:id/restore
+pub async fn restore_backup_handler(
+    Extension(user): Extension<SessionInfo>,
+    AxumPath(id): AxumPath<String>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let manager = backup_manager()?;
+    let data_dir = neomind_storage::data_dir();
+    let manifest = blocking(move || manager.stage_restore(&id, &data_dir)).await?;
+
+    tracing::warn!(
+        admin = %user.username,
+        id = %manifest.id,
+        "System restore staged; it will be applied on restart"
+    );
+
+    ok(json!({
+        "staged": manifest.id,
+        "entries": manifest.entries.len(),
+        "created_at": manifest.created_at,
+        "restart_required": true,
+    }))
+}
+
+/// Delete a backup.
+///
+/// DELETE /api/system/backups/:id
+pub async fn delete_backup_handler(
+    Extension(user): Extension<SessionInfo>,
+    AxumPath(id): AxumPath<String>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    if !backup_manager()?.delete_backup(&id)? {
+        return Err(ErrorResponse::not_found(format!("Backup {}", id)));
+    }
+    ok(json!({ "deleted": id }))
+}
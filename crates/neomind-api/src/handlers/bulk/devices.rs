@@ -9,6 +9,7 @@ use neomind_devices::MetricValue;
 use super::models::{BulkDeleteDeviceTypesRequest, BulkOperationResult};
 use crate::handlers::common::HandlerResult;
 use crate::handlers::{common::ok, ServerState};
+use crate::models::ErrorResponse;
 
 /// Bulk delete device types.
 ///
@@ -184,7 +185,22 @@ pub async fn bulk_device_command_handler(
         })
         .collect();
 
-    for (index, device_id) in req.device_ids.into_iter().enumerate() {
+    // Resolve targets: explicit IDs first, then selector matches not already listed
+    let mut device_ids = req.device_ids;
+    if let Some(selector) = &req.selector {
+        if selector.is_empty() {
+            return Err(ErrorResponse::bad_request(
+                "Selector must set at least one of group, tags or location",
+            ));
+        }
+        for config in state.devices.service.select_devices(selector).await {
+            if !device_ids.contains(&config.device_id) {
+                device_ids.push(config.device_id);
+            }
+        }
+    }
+
+    for (index, device_id) in device_ids.into_iter().enumerate() {
         match state
             .devices
             .service
@@ -97,7 +97,11 @@ pub struct BulkDeleteDeviceTypesRequest {
 #[derive(Debug, Deserialize)]
 pub struct BulkDeviceCommandRequest {
     /// Device IDs to target
+    #[serde(default)]
     pub device_ids: Vec<String>,
+    /// Additionally target every device matching this group/tag/location selector
+    #[serde(default)]
+    pub selector: Option<neomind_devices::DeviceSelector>,
     /// Command to execute
     pub command: String,
     /// Optional parameters
@@ -238,6 +238,9 @@ pub async fn approve_draft_device(
                     .adapter_id
                     .clone()
                     .or_else(|| Some("internal-mqtt".to_string())),
+                location: None,
+                tags: Vec::new(),
+                groups: Vec::new(),
             };
 
             // Register the device
@@ -333,6 +336,9 @@ pub async fn approve_draft_device(
                     .adapter_id
                     .clone()
                     .or_else(|| Some("internal-mqtt".to_string())),
+                location: None,
+                tags: Vec::new(),
+                groups: Vec::new(),
             };
 
             // Register the device
@@ -20,7 +20,7 @@ use crate::handlers::{
 use crate::models::ErrorResponse;
 use neomind_devices::{
     adapter::ConnectionStatus as AdapterConnectionStatus,
-    mdl::ConnectionStatus as MdlConnectionStatus,
+    mdl::ConnectionStatus as MdlConnectionStatus, DeviceLocation, DeviceSelector,
 };
 
 /// Transform-generated metric namespaces.
@@ -71,6 +71,16 @@ pub async fn list_devices_handler(
     let limit = pagination.limit.unwrap_or(50).min(1000); // Cap at 1000 items per page
     let offset = (page - 1) * limit;
 
+    // Group/tag/location filters share the selector used by bulk commands and rules
+    let selector = DeviceSelector {
+        group: pagination.group.clone(),
+        tags: pagination.tag.iter().cloned().collect(),
+        location: pagination
+            .location
+            .as_deref()
+            .map(DeviceLocation::from_path),
+    };
+
     // Get all devices
     let configs = state.devices.service.list_devices().await;
     let _total = configs.len();
@@ -84,6 +94,10 @@ pub async fn list_devices_handler(
 
     let mut devices_with_status = Vec::new();
     for config in configs {
+        if !selector.matches(&config) {
+            continue;
+        }
+
         // Query status once per device
         let device_status = state
             .devices
@@ -195,6 +209,9 @@ pub async fn list_devices_handler(
             command_count,
             current_values: None, // Skip for list view to reduce payload
             config: Some(instance.config),
+            location: config.location.clone(),
+            tags: config.tags.clone(),
+            groups: config.groups.clone(),
         });
     }
 
@@ -283,6 +300,9 @@ pub async fn get_device_handler(
         "plugin_id": plugin_id,
         "plugin_name": plugin_name,
         "adapter_id": config.adapter_id,
+        "location": config.location,
+        "tags": config.tags,
+        "groups": config.groups,
     }))
 }
 
@@ -617,6 +637,9 @@ pub async fn add_device_handler(
         adapter_type: req.adapter_type,
         connection_config,
         adapter_id: None, // Will be set by adapter when registered
+        location: req.location,
+        tags: req.tags,
+        groups: req.groups,
     };
 
     // Register device using new DeviceService
@@ -664,6 +687,9 @@ pub async fn update_device_handler(
         adapter_type: req.adapter_type.unwrap_or(existing.adapter_type),
         connection_config,
         adapter_id: req.adapter_id.or(existing.adapter_id),
+        location: req.location.or(existing.location),
+        tags: req.tags.unwrap_or(existing.tags),
+        groups: req.groups.unwrap_or(existing.groups),
     };
 
     // Update device using new DeviceService
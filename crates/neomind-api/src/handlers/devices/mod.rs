@@ -9,6 +9,7 @@ pub mod discovery;
 pub mod mdl;
 pub mod metrics;
 pub mod models;
+pub mod shadow;
 pub mod telemetry;
 pub mod types;
 pub mod webhook;
@@ -19,6 +20,7 @@ pub use crud::*;
 pub use discovery::*;
 pub use mdl::*;
 pub use metrics::*;
+pub use shadow::*;
 pub use telemetry::*;
 pub use types::*;
 pub use webhook::*;
@@ -1,5 +1,6 @@
 //! Device DTOs and request structures.
 
+use neomind_devices::DeviceLocation;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
@@ -34,6 +35,15 @@ pub struct DeviceDto {
     /// Legacy config field for backward compatibility
     #[serde(skip_serializing_if = "Option::is_none")]
     pub config: Option<HashMap<String, String>>,
+    /// Physical location (site/building/floor/room)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<DeviceLocation>,
+    /// Free-form tags
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Static groups this device belongs to
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
 }
 
 /// Device type info for API responses.
@@ -111,6 +121,15 @@ pub struct AddDeviceRequest {
     pub adapter_type: String,
     /// Connection configuration (protocol-specific)
     pub connection_config: serde_json::Value,
+    /// Physical location (site/building/floor/room)
+    #[serde(default)]
+    pub location: Option<DeviceLocation>,
+    /// Free-form tags
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Static groups this device belongs to
+    #[serde(default)]
+    pub groups: Vec<String>,
 }
 
 /// Request to update an existing device.
@@ -129,6 +148,15 @@ pub struct UpdateDeviceRequest {
     /// Adapter/Plugin ID that manages this device
     #[serde(skip_serializing_if = "Option::is_none")]
     pub adapter_id: Option<String>,
+    /// Physical location (site/building/floor/room)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<DeviceLocation>,
+    /// Free-form tags (replaces the existing list)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+    /// Static groups (replaces the existing list)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<String>>,
 }
 
 /// Pagination query parameters
@@ -142,6 +170,12 @@ pub struct PaginationQuery {
     pub device_type: Option<String>,
     /// Filter by connection status
     pub status: Option<String>,
+    /// Filter by static group
+    pub group: Option<String>,
+    /// Filter by tag
+    pub tag: Option<String>,
+    /// Filter by location path prefix, e.g. `store-17/main/2`
+    pub location: Option<String>,
 }
 
 /// Pagination metadata
@@ -184,6 +218,16 @@ pub struct SendCommandRequest {
     pub params: HashMap<String, serde_json::Value>,
 }
 
+/// Request to update the desired section of a device shadow.
+#[derive(Debug, Deserialize)]
+pub struct UpdateShadowRequest {
+    /// Desired values (`null` clears a key)
+    pub desired: HashMap<String, serde_json::Value>,
+    /// Command that applies the values (defaults to the first command with a matching parameter)
+    #[serde(default)]
+    pub command: Option<String>,
+}
+
 /// Discovery request for scanning a host for devices.
 #[derive(Debug, Deserialize)]
 pub struct DiscoveryRequest {
@@ -0,0 +1,76 @@
+//! Device shadow (desired vs. reported state).
+
+use axum::{
+    extract::{Path, State},
+    Json,
+};
+use serde_json::json;
+
+use neomind_devices::DeviceShadow;
+
+use super::models::UpdateShadowRequest;
+use crate::handlers::{
+    common::{ok, HandlerResult},
+    ServerState,
+};
+use crate::models::ErrorResponse;
+
+fn shadow_to_json(shadow: &DeviceShadow) -> serde_json::Value {
+    json!({
+        "device_id": shadow.device_id,
+        "desired": shadow.desired,
+        "reported": shadow.reported,
+        "delta": shadow.delta(),
+        "desired_commands": shadow.desired_commands,
+        "in_sync": shadow.in_sync(),
+        "version": shadow.version,
+        "desired_updated_at": shadow.desired_updated_at,
+        "reported_updated_at": shadow.reported_updated_at,
+    })
+}
+
+/// Get the shadow document of a device.
+///
+/// GET /api/devices/:id/shadow
+pub async fn get_device_shadow_handler(
+    State(state): State<ServerState>,
+    Path(device_id): Path<String>,
+) -> HandlerResult<serde_json::Value> {
+    state
+        .devices
+        .service
+        .get_device(&device_id)
+        .await
+        .ok_or_else(|| ErrorResponse::not_found("Device"))?;
+
+    let shadow = state.devices.service.get_shadow(&device_id).await;
+    ok(shadow_to_json(&shadow))
+}
+
+/// Update the desired state of a device.
+///
+/// PUT /api/devices/:id/shadow
+///
+/// Online devices get the resulting delta applied immediately; offline ones
+/// are reconciled when they come back.
+pub async fn update_device_shadow_handler(
+    State(state): State<ServerState>,
+    Path(device_id): Path<String>,
+    Json(req): Json<UpdateShadowRequest>,
+) -> HandlerResult<serde_json::Value> {
+    state
+        .devices
+        .service
+        .get_device(&device_id)
+        .await
+        .ok_or_else(|| ErrorResponse::not_found("Device"))?;
+
+    let shadow = state
+        .devices
+        .service
+        .update_desired(&device_id, req.desired, req.command.as_deref())
+        .await
+        .map_err(|e| ErrorResponse::bad_request(format!("Failed to update shadow: {}", e)))?;
+
+    ok(shadow_to_json(&shadow))
+}
@@ -13,6 +13,7 @@ use std::sync::Arc;
 
 use neomind_core::llm::backend::LlmRuntime;
 use neomind_devices::registry::DeviceTypeTemplate;
+use neomind_llm::backends::anthropic::{AnthropicConfig, AnthropicRuntime};
 use neomind_llm::backends::openai::{CloudConfig, CloudProvider, CloudRuntime};
 use neomind_llm::{instance_manager::get_instance_manager, OllamaConfig, OllamaRuntime};
 use neomind_storage::{LlmBackendInstance, LlmBackendType};
@@ -433,15 +434,11 @@ fn instance_to_runtime(instance: &LlmBackendInstance) -> Result<Arc<dyn LlmRunti
                 .map_err(|e| format!("Failed to create OpenAI runtime: {}", e))
         }
         LlmBackendType::Anthropic => {
-            let provider = CloudProvider::Anthropic;
-            let config = CloudConfig {
-                api_key: instance.api_key.clone().unwrap_or_default(),
-                provider,
-                model: Some(instance.model.clone()),
-                base_url: instance.endpoint.clone(),
-                timeout_secs: 120,
-            };
-            CloudRuntime::new(config)
+            let config = AnthropicConfig::new(instance.api_key.clone().unwrap_or_default())
+                .with_model(instance.model.clone())
+                .with_base_url_opt(instance.endpoint.clone())
+                .with_timeout_secs(120);
+            AnthropicRuntime::new(config)
                 .map(|runtime| Arc::new(runtime) as Arc<dyn LlmRuntime>)
                 .map_err(|e| format!("Failed to create Anthropic runtime: {}", e))
         }
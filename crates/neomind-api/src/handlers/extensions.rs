@@ -215,11 +215,12 @@ pub async fn list_extensions_handler(
     let loaded_extensions = state.extensions.unified_service.list().await;
     
     // Also get all extension records from storage (including failed ones)
-    let stored_records = if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
-        store.load_all().unwrap_or_default()
-    } else {
-        Vec::new()
-    };
+    let stored_records =
+        if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
+            store.load_all().unwrap_or_default()
+        } else {
+            Vec::new()
+        };
 
     // Build a set of loaded extension IDs for quick lookup
     let loaded_ids: std::collections::HashSet<String> = 
@@ -353,7 +354,7 @@ fn extension_info_to_dto(info: &neomind_core::extension::unified::UnifiedExtensi
 
     // Try to get health status from storage
     let (health_status, last_error, last_error_at) =
-        if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+        if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
             if let Ok(Some(record)) = store.load(&info.metadata.id) {
                 (record.health_status, record.last_error, record.last_error_at)
             } else {
@@ -470,7 +471,7 @@ pub async fn get_extension_handler(
 
     // Try to get health status from storage
     let (health_status, last_error, last_error_at) = 
-        if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+        if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
             if let Ok(Some(record)) = store.load(&id) {
                 (record.health_status, record.last_error, record.last_error_at)
             } else {
@@ -602,7 +603,7 @@ pub async fn register_extension_handler(
 
     // Save to persistent storage for auto-load on server restart
     // V2: Use empty string for extension_type (storage API still requires it)
-    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+    if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
         let record = neomind_storage::ExtensionRecord::new(
             ext_id.clone(),
             ext_name.clone(),
@@ -642,11 +643,12 @@ pub async fn unregister_extension_handler(
 
     // Check if extension exists in memory or storage
     let in_memory = unified.contains(&id).await;
-    let in_storage = if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
-        store.load(&id).ok().flatten().is_some()
-    } else {
-        false
-    };
+    let in_storage =
+        if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
+            store.load(&id).ok().flatten().is_some()
+        } else {
+            false
+        };
 
     // Extension must exist somewhere to unregister
     if !in_memory && !in_storage {
@@ -666,7 +668,7 @@ pub async fn unregister_extension_handler(
 
     // Mark as uninstalled in storage (instead of deleting) to prevent auto-discovery
     // from re-registering it on server restart
-    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+    if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
         if let Err(e) = store.mark_uninstalled(&id) {
             tracing::warn!("Failed to mark extension as uninstalled: {}", e);
         }
@@ -1928,9 +1930,8 @@ pub async fn install_marketplace_extension_handler(
         }
 
         // Prepare target directory
-        let data_dir = std::env::var("NEOMIND_DATA_DIR")
-            .unwrap_or_else(|_| "data".to_string());
-        let target_dir = PathBuf::from(data_dir).join("extensions");
+        let data_dir = neomind_storage::data_dir();
+        let target_dir = data_dir.join("extensions");
 
         // Install the package
         let package_bytes_clone = package_bytes.to_vec();
@@ -1984,7 +1985,9 @@ pub async fn install_marketplace_extension_handler(
                             .to_string();
 
                         // Save to storage
-                        if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+                        if let Ok(store) =
+                            ExtensionStore::open(neomind_storage::data_path("extensions.redb"))
+                        {
                             let record = ExtensionRecord::new(
                                 ext_id.clone(),
                                 ext_metadata.name.clone(),
@@ -2131,9 +2134,8 @@ pub async fn install_marketplace_extension_handler(
         };
 
         // Create extensions directory using NEOMIND_DATA_DIR for consistency
-        let data_dir = std::env::var("NEOMIND_DATA_DIR")
-            .unwrap_or_else(|_| "data".to_string());
-        let extensions_dir = PathBuf::from(data_dir).join("extensions");
+        let data_dir = neomind_storage::data_dir();
+        let extensions_dir = data_dir.join("extensions");
 
         std::fs::create_dir_all(&extensions_dir).map_err(|e| {
             ErrorResponse::internal(format!("Failed to create extensions directory: {}", e))
@@ -2226,7 +2228,9 @@ pub async fn install_marketplace_extension_handler(
         match unified.load(&file_path).await {
             Ok(_) => {
                 // Save to persistent storage
-                if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+                if let Ok(store) =
+                    ExtensionStore::open(neomind_storage::data_path("extensions.redb"))
+                {
                     let record = ExtensionRecord::new(
                         metadata.id.clone(),
                         metadata.name.clone(),
@@ -2346,7 +2350,7 @@ pub async fn get_extension_config_handler(
 
     // Get current config from storage
     let current_config: Option<serde_json::Value> =
-        if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+        if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
             store.load(&id).ok().flatten().and_then(|r| r.config)
         } else {
             None
@@ -2390,7 +2394,7 @@ pub async fn update_extension_config_handler(
     }
 
     // Save config to storage
-    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+    if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
         if let Ok(Some(mut record)) = store.load(&id) {
             record.config = Some(config.clone());
             store.save(&record)?;
@@ -2442,7 +2446,7 @@ pub async fn reload_extension_handler(
 
     // Get current config
     let config: Option<serde_json::Value> =
-        if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+        if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
             store.load(&id).ok().flatten().and_then(|r| r.config)
         } else {
             None
@@ -2884,10 +2888,10 @@ fn load_extension_components(
 ) -> Option<Vec<DashboardComponentDto>> {
 
     // Log path configuration for debugging
-    let data_dir = std::env::var("NEOMIND_DATA_DIR").unwrap_or_else(|_| "./data".to_string());
+    let data_dir = neomind_storage::data_dir();
     tracing::debug!(
         extension_id = %extension_id,
-        data_dir = %data_dir,
+        data_dir = %data_dir.display(),
         "Loading extension components (NEOMIND_DATA_DIR set)"
     );
 
@@ -2896,8 +2900,8 @@ fn load_extension_components(
         fp.clone()
     } else {
         // Try to find extension in data/extensions directory
-        let data_dir = std::env::var("NEOMIND_DATA_DIR").unwrap_or_else(|_| "./data".to_string());
-        std::path::PathBuf::from(data_dir).join("extensions").join(extension_id)
+        let data_dir = neomind_storage::data_dir();
+        data_dir.join("extensions").join(extension_id)
     };
 
     tracing::debug!(
@@ -3066,8 +3070,8 @@ pub async fn serve_extension_asset_handler(
     }
 
     // Extension directory is always data/extensions/{id}
-    let data_dir = std::env::var("NEOMIND_DATA_DIR").unwrap_or_else(|_| "./data".to_string());
-    let ext_dir = std::path::PathBuf::from(data_dir).join("extensions").join(&id);
+    let data_dir = neomind_storage::data_dir();
+    let ext_dir = data_dir.join("extensions").join(&id);
 
     let asset_file = ext_dir.join(&asset_path);
 
@@ -3187,9 +3191,8 @@ pub async fn upload_extension_package_handler(
     }
 
     // Install the package
-    let data_dir = std::env::var("NEOMIND_DATA_DIR")
-        .unwrap_or_else(|_| "data".to_string());
-    let target_dir = PathBuf::from(data_dir).join("extensions");
+    let data_dir = neomind_storage::data_dir();
+    let target_dir = data_dir.join("extensions");
 
     let install_result = package.install(&target_dir).await
         .map_err(|e| ErrorResponse::internal(format!("Installation failed: {}", e)))?;
@@ -3208,7 +3211,7 @@ pub async fn upload_extension_package_handler(
         .map_err(|e| ErrorResponse::internal(format!("Failed to load extension binary: {}", e)))?;
 
     // Save to storage
-    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+    if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
         let record = ExtensionRecord::new(
             ext_id.clone(),
             name.clone(),
@@ -3333,16 +3336,15 @@ pub async fn uninstall_extension_handler(
     }
 
     // Mark as uninstalled in storage
-    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+    if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
         if let Err(e) = store.mark_uninstalled(&id) {
             tracing::warn!("Failed to mark extension as uninstalled: {}", e);
         }
     }
 
     // Clean up extension directory
-    let data_dir = std::env::var("NEOMIND_DATA_DIR")
-        .unwrap_or_else(|_| "data".to_string());
-    let extensions_dir = PathBuf::from(data_dir).join("extensions");
+    let data_dir = neomind_storage::data_dir();
+    let extensions_dir = data_dir.join("extensions");
     let ext_dir = extensions_dir.join(&id);
 
     let mut removed_files = Vec::new();
@@ -3436,9 +3438,8 @@ pub async fn upload_extension_file_handler(
     }
 
     // Prepare target directory
-    let data_dir = std::env::var("NEOMIND_DATA_DIR")
-        .unwrap_or_else(|_| "data".to_string());
-    let target_dir = PathBuf::from(data_dir).join("extensions");
+    let data_dir = neomind_storage::data_dir();
+    let target_dir = data_dir.join("extensions");
 
     // Parse and install the package in a single blocking task
     // (ZIP operations involve dyn Read which is not Send)
@@ -3490,7 +3491,7 @@ pub async fn upload_extension_file_handler(
         .to_string();
 
     // Save to storage
-    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+    if let Ok(store) = ExtensionStore::open(neomind_storage::data_path("extensions.redb")) {
         let record = ExtensionRecord::new(
             ext_id.clone(),
             metadata.name.clone(),
@@ -3542,9 +3543,8 @@ pub async fn sync_extensions_handler(
 ) -> HandlerResult<serde_json::Value> {
     use crate::server::ExtensionInstallService;
 
-    let data_dir = std::env::var("NEOMIND_DATA_DIR")
-        .unwrap_or_else(|_| "data".to_string());
-    let install_dir = std::path::PathBuf::from(data_dir).join("extensions");
+    let data_dir = neomind_storage::data_dir();
+    let install_dir = data_dir.join("extensions");
     let nep_cache_dir = std::path::PathBuf::from("extensions");
 
     let install_service = ExtensionInstallService::new(&install_dir, &nep_cache_dir);
@@ -3568,9 +3568,8 @@ pub async fn get_sync_status_handler(
     use neomind_core::extension::package::ExtensionPackage;
 
     let nep_cache_dir = std::path::PathBuf::from("extensions");
-    let data_dir = std::env::var("NEOMIND_DATA_DIR")
-        .unwrap_or_else(|_| "data".to_string());
-    let install_dir = std::path::PathBuf::from(data_dir).join("extensions");
+    let data_dir = neomind_storage::data_dir();
+    let install_dir = data_dir.join("extensions");
 
     let mut nep_packages = Vec::new();
 
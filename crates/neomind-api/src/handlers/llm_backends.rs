@@ -20,7 +20,12 @@ use neomind_llm::instance_manager::{
     get_instance_manager, BackendTypeDefinition, LlmBackendInstanceManager,
 };
 use neomind_core::llm::detect_vision_capability;
-use neomind_storage::{BackendCapabilities, LlmBackendInstance, LlmBackendStore, LlmBackendType};
+use neomind_llm::{get_response_cache, get_usage_tracker, UsageTracker};
+use neomind_storage::{
+    BackendCapabilities, BudgetAction, BudgetPeriod, BudgetScope, LlmBackendInstance,
+    LlmBackendStore, LlmBackendType, ModelPricing, RoutingPolicy, RoutingStrategy, UsageBudget,
+    UsageDimension, UsageGrouping, UsageQuery,
+};
 
 /// Query parameters for listing LLM backends
 #[derive(Debug, Deserialize)]
@@ -624,6 +629,16 @@ pub async fn activate_backend_handler(
         }
     };
 
+    // Choosing a single backend replaces the chat routing policy
+    if manager.chat_policy_id().is_some() {
+        state
+            .agents
+            .session_manager
+            .set_chat_routing_policy(None)
+            .await
+            .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    }
+
     // Update all existing sessions to use the new backend
     state
         .agents
@@ -759,19 +774,478 @@ pub async fn get_backend_schema_handler(
     }))
 }
 
-/// Get backend statistics
+/// Get backend statistics and a token usage report
 ///
-/// GET /api/llm-backends/stats
+/// GET /api/llm-backends/stats?from=&to=&group_by=day&dimension=model
 pub async fn get_backend_stats_handler(
     State(_state): State<ServerState>,
+    Query(query): Query<UsageStatsQuery>,
 ) -> HandlerResult<serde_json::Value> {
-    let stats = get_backend_stats()
+    let mut stats = get_backend_stats()
         .await
         .map_err(|e| ErrorResponse::internal(e.to_string()))?;
 
+    let query = UsageQuery {
+        from: Some(query.from.unwrap_or_else(|| {
+            chrono::Utc::now().timestamp() - DEFAULT_USAGE_RANGE_SECS
+        })),
+        to: query.to,
+        group_by: query.group_by.unwrap_or_default(),
+        dimension: query.dimension,
+    };
+    let report = get_tracker()?
+        .report(&query)
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    stats["usage"] = json!(report);
+
     ok(stats)
 }
 
+/// Usage reports cover the last 30 days unless `from` is given
+const DEFAULT_USAGE_RANGE_SECS: i64 = 30 * 86_400;
+
+/// Query parameters for the usage report in backend stats
+#[derive(Debug, Deserialize)]
+pub struct UsageStatsQuery {
+    /// Start timestamp in seconds (default: 30 days ago)
+    pub from: Option<i64>,
+    /// End timestamp in seconds (exclusive)
+    pub to: Option<i64>,
+    /// Time bucket size: hour, day (default) or month
+    pub group_by: Option<UsageGrouping>,
+    /// Break buckets down by backend, model, agent, user, session or extension
+    pub dimension: Option<UsageDimension>,
+}
+
+/// Helper to get the usage tracker
+fn get_tracker() -> Result<Arc<UsageTracker>, ErrorResponse> {
+    get_usage_tracker().map_err(|e| ErrorResponse::internal(e.to_string()))
+}
+
+/// Request body for creating or updating a routing policy
+#[derive(Debug, Deserialize)]
+pub struct RoutingPolicyRequest {
+    /// Policy ID (generated when creating without one)
+    pub id: Option<String>,
+    pub name: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub strategy: RoutingStrategy,
+    /// Backend instance IDs, in failover order
+    pub backends: Vec<String>,
+    /// Relative cost per backend instance ID
+    #[serde(default)]
+    pub costs: std::collections::HashMap<String, f64>,
+    pub failure_threshold: Option<u32>,
+    pub open_secs: Option<u64>,
+}
+
+impl RoutingPolicyRequest {
+    fn into_policy(self, id: String) -> RoutingPolicy {
+        let mut policy = RoutingPolicy::new(id, self.name, self.strategy, self.backends);
+        policy.description = self.description;
+        policy.costs = self.costs;
+        if let Some(failure_threshold) = self.failure_threshold {
+            policy.failure_threshold = failure_threshold;
+        }
+        if let Some(open_secs) = self.open_secs {
+            policy.open_secs = open_secs;
+        }
+        policy
+    }
+}
+
+/// List routing policies
+///
+/// GET /api/llm-backends/routing-policies
+pub async fn list_routing_policies_handler(
+    State(_state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    let manager = get_manager()?;
+    let policies = manager
+        .list_policies()
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+
+    ok(json!({
+        "policies": policies,
+        "count": policies.len(),
+        "chat_policy": manager.chat_policy_id(),
+    }))
+}
+
+/// Get a routing policy
+///
+/// GET /api/llm-backends/routing-policies/:id
+pub async fn get_routing_policy_handler(
+    State(_state): State<ServerState>,
+    Path(id): Path<String>,
+) -> HandlerResult<RoutingPolicy> {
+    let manager = get_manager()?;
+    let policy = manager
+        .get_policy(&id)
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?
+        .ok_or_else(|| ErrorResponse::not_found(format!("Routing policy {}", id)))?;
+
+    ok(policy)
+}
+
+/// Create a routing policy
+///
+/// POST /api/llm-backends/routing-policies
+pub async fn create_routing_policy_handler(
+    State(_state): State<ServerState>,
+    Json(request): Json<RoutingPolicyRequest>,
+) -> HandlerResult<serde_json::Value> {
+    let manager = get_manager()?;
+    let id = request
+        .id
+        .clone()
+        .unwrap_or_else(|| LlmBackendStore::generate_id("policy"));
+
+    let existing = manager
+        .get_policy(&id)
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    if existing.is_some() {
+        return Err(ErrorResponse::conflict(format!(
+            "Routing policy {} already exists",
+            id
+        )));
+    }
+
+    manager
+        .upsert_policy(request.into_policy(id.clone()))
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+
+    ok(json!({
+        "id": id,
+        "message": "Routing policy created successfully",
+    }))
+}
+
+/// Update a routing policy
+///
+/// PUT /api/llm-backends/routing-policies/:id
+pub async fn update_routing_policy_handler(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+    Json(request): Json<RoutingPolicyRequest>,
+) -> HandlerResult<serde_json::Value> {
+    let manager = get_manager()?;
+    let existing = manager
+        .get_policy(&id)
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    if existing.is_none() {
+        return Err(ErrorResponse::not_found(format!("Routing policy {}", id)));
+    }
+
+    manager
+        .upsert_policy(request.into_policy(id.clone()))
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+
+    // Chat sessions pick up the new backends
+    let session_manager = &state.agents.session_manager;
+    if session_manager.chat_routing_policy().await.as_deref() == Some(id.as_str()) {
+        session_manager
+            .set_chat_routing_policy(Some(id.clone()))
+            .await
+            .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    }
+
+    ok(json!({
+        "id": id,
+        "message": "Routing policy updated successfully",
+    }))
+}
+
+/// Delete a routing policy
+///
+/// DELETE /api/llm-backends/routing-policies/:id
+pub async fn delete_routing_policy_handler(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> HandlerResult<serde_json::Value> {
+    let manager = get_manager()?;
+    let deleted = manager
+        .remove_policy(&id)
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    if !deleted {
+        return Err(ErrorResponse::not_found(format!("Routing policy {}", id)));
+    }
+
+    // Chat sessions routed through the policy go back to the active backend
+    let session_manager = &state.agents.session_manager;
+    if session_manager.chat_routing_policy().await.as_deref() == Some(id.as_str()) {
+        session_manager
+            .set_chat_routing_policy(None)
+            .await
+            .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    }
+
+    ok(json!({
+        "message": format!("Routing policy {} deleted", id),
+    }))
+}
+
+/// Request body for setting the chat routing policy
+#[derive(Debug, Deserialize)]
+pub struct ChatPolicyRequest {
+    /// Routing policy ID; `null` routes chat to the active backend again
+    pub policy_id: Option<String>,
+}
+
+/// Route chat sessions through a routing policy
+///
+/// PUT /api/llm-backends/routing/chat-policy
+pub async fn set_chat_policy_handler(
+    State(state): State<ServerState>,
+    Json(request): Json<ChatPolicyRequest>,
+) -> HandlerResult<serde_json::Value> {
+    let policy_id = request.policy_id.filter(|id| !id.is_empty());
+    state
+        .agents
+        .session_manager
+        .set_chat_routing_policy(policy_id.clone())
+        .await
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+
+    ok(json!({
+        "chat_policy": policy_id,
+        "message": "Chat routing policy updated successfully",
+    }))
+}
+
+/// Circuit breaker state and observed latency of routed backends
+///
+/// GET /api/llm-backends/routing/health
+pub async fn get_routing_health_handler(
+    State(_state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    let manager = get_manager()?;
+
+    ok(json!({
+        "backends": manager.routing_health(),
+    }))
+}
+
+/// Request body for replacing the model pricing
+#[derive(Debug, Deserialize)]
+pub struct PricingRequest {
+    pub pricing: Vec<ModelPricing>,
+}
+
+/// List model pricing
+///
+/// GET /api/llm-backends/pricing
+pub async fn get_pricing_handler(
+    State(_state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    let pricing = get_tracker()?.pricing();
+
+    ok(json!({
+        "pricing": pricing,
+        "count": pricing.len(),
+    }))
+}
+
+/// Replace the model pricing
+///
+/// PUT /api/llm-backends/pricing
+pub async fn update_pricing_handler(
+    State(_state): State<ServerState>,
+    Json(request): Json<PricingRequest>,
+) -> HandlerResult<serde_json::Value> {
+    let now = chrono::Utc::now().timestamp();
+    let pricing: Vec<ModelPricing> = request
+        .pricing
+        .into_iter()
+        .map(|mut entry| {
+            entry.updated_at = now;
+            entry
+        })
+        .collect();
+
+    get_tracker()?
+        .set_pricing(pricing)
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+
+    ok(json!({
+        "message": "Model pricing updated successfully",
+    }))
+}
+
+/// Request body for creating or updating a usage budget
+#[derive(Debug, Deserialize)]
+pub struct BudgetRequest {
+    /// Budget ID (generated when creating without one)
+    pub id: Option<String>,
+    pub scope: BudgetScope,
+    /// Agent ID or user name
+    pub target: String,
+    pub period: BudgetPeriod,
+    pub max_cost: Option<f64>,
+    pub max_tokens: Option<u64>,
+    #[serde(default)]
+    pub action: BudgetAction,
+    pub throttle_delay_secs: Option<u64>,
+    pub enabled: Option<bool>,
+}
+
+impl BudgetRequest {
+    fn into_budget(self, id: String) -> UsageBudget {
+        let mut budget = UsageBudget::new(id, self.scope, self.target, self.period);
+        budget.max_cost = self.max_cost;
+        budget.max_tokens = self.max_tokens;
+        budget.action = self.action;
+        if let Some(throttle_delay_secs) = self.throttle_delay_secs {
+            budget.throttle_delay_secs = throttle_delay_secs;
+        }
+        if let Some(enabled) = self.enabled {
+            budget.enabled = enabled;
+        }
+        budget
+    }
+}
+
+/// A budget together with its spend in the current period
+fn budget_json(tracker: &UsageTracker, budget: &UsageBudget) -> serde_json::Value {
+    let spend = tracker.budget_spend(budget);
+    json!({
+        "budget": budget,
+        "exceeded": budget.is_exceeded(&spend),
+        "spend": spend,
+    })
+}
+
+/// List usage budgets with their current spend
+///
+/// GET /api/llm-backends/budgets
+pub async fn list_budgets_handler(
+    State(_state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    let tracker = get_tracker()?;
+    let budgets: Vec<_> = tracker
+        .budgets()
+        .iter()
+        .map(|budget| budget_json(&tracker, budget))
+        .collect();
+
+    ok(json!({
+        "budgets": budgets,
+        "count": budgets.len(),
+    }))
+}
+
+/// Get a usage budget with its current spend
+///
+/// GET /api/llm-backends/budgets/:id
+pub async fn get_budget_handler(
+    State(_state): State<ServerState>,
+    Path(id): Path<String>,
+) -> HandlerResult<serde_json::Value> {
+    let tracker = get_tracker()?;
+    let budget = tracker
+        .get_budget(&id)
+        .ok_or_else(|| ErrorResponse::not_found(format!("Budget {}", id)))?;
+
+    ok(budget_json(&tracker, &budget))
+}
+
+/// Create a usage budget
+///
+/// POST /api/llm-backends/budgets
+pub async fn create_budget_handler(
+    State(_state): State<ServerState>,
+    Json(request): Json<BudgetRequest>,
+) -> HandlerResult<serde_json::Value> {
+    let tracker = get_tracker()?;
+    let id = request
+        .id
+        .clone()
+        .unwrap_or_else(|| LlmBackendStore::generate_id("budget"));
+    if tracker.get_budget(&id).is_some() {
+        return Err(ErrorResponse::conflict(format!(
+            "Budget {} already exists",
+            id
+        )));
+    }
+
+    tracker
+        .save_budget(request.into_budget(id.clone()))
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+
+    ok(json!({
+        "id": id,
+        "message": "Budget created successfully",
+    }))
+}
+
+/// Update a usage budget
+///
+/// PUT /api/llm-backends/budgets/:id
+pub async fn update_budget_handler(
+    State(_state): State<ServerState>,
+    Path(id): Path<String>,
+    Json(request): Json<BudgetRequest>,
+) -> HandlerResult<serde_json::Value> {
+    let tracker = get_tracker()?;
+    if tracker.get_budget(&id).is_none() {
+        return Err(ErrorResponse::not_found(format!("Budget {}", id)));
+    }
+
+    tracker
+        .save_budget(request.into_budget(id.clone()))
+        .map_err(|e| ErrorResponse::bad_request(e.to_string()))?;
+
+    ok(json!({
+        "id": id,
+        "message": "Budget updated successfully",
+    }))
+}
+
+/// Delete a usage budget
+///
+/// DELETE /api/llm-backends/budgets/:id
+pub async fn delete_budget_handler(
+    State(_state): State<ServerState>,
+    Path(id): Path<String>,
+) -> HandlerResult<serde_json::Value> {
+    let deleted = get_tracker()?
+        .delete_budget(&id)
+        .map_err(|e| ErrorResponse::internal(e.to_string()))?;
+    if !deleted {
+        return Err(ErrorResponse::not_found(format!("Budget {}", id)));
+    }
+
+    ok(json!({
+        "message": format!("Budget {} deleted", id),
+    }))
+}
+
+/// Get LLM response cache statistics
+///
+/// GET /api/llm-backends/cache
+pub async fn get_response_cache_stats_handler(
+    State(_state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    ok(json!({
+        "stats": get_response_cache().stats(),
+    }))
+}
+
+/// Remove every cached LLM response
+///
+/// DELETE /api/llm-backends/cache
+pub async fn clear_response_cache_handler(
+    State(_state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    let removed = get_response_cache().clear();
+
+    ok(json!({
+        "message": format!("Removed {} cached responses", removed),
+        "removed": removed,
+    }))
+}
+
 /// Fetch available models from an Ollama server
 ///
 /// GET /api/llm-backends/ollama/models?endpoint=http://localhost:11434
@@ -0,0 +1,329 @@
+//! Model Context Protocol handlers.
+//!
+//! `/api/mcp` serves NeoMind's own tools over Streamable HTTP;
+//! `/api/mcp/servers` manages the external MCP servers whose tools are
+//! imported (admin only, since stdio servers launch local processes).
+
+use std::collections::HashMap;
+
+use axum::{
+    body::Bytes,
+    extract::{Path, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Extension, Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use neomind_storage::{McpServerConfig, McpTransportConfig};
+use neomind_tools::mcp::{error_codes, get_mcp_client_manager, JsonRpcError};
+
+use super::ServerState;
+use crate::auth::ValidatedApiKey;
+use crate::auth_users::{SessionInfo, UserRole};
+use crate::config;
+use crate::handlers::common::{ok, HandlerResult};
+use crate::mcp::{error_response, McpAccess, McpServer};
+use crate::models::ErrorResponse;
+
+/// Handle MCP JSON-RPC messages.
+///
+/// Requests are answered with a JSON body; notifications and responses
+/// are acknowledged with 202 Accepted.
+///
+/// POST /api/mcp
+pub async fn mcp_post_handler(
+    State(state): State<ServerState>,
+    user: Option<Extension<SessionInfo>>,
+    api_key: Option<Extension<ValidatedApiKey>>,
+    body: Bytes,
+) -> Response {
+    let access = caller_access(
+        &state,
+        user.as_ref().map(|Extension(user)| user),
+        api_key.as_ref().map(|Extension(key)| key),
+    );
+
+    let message: Value = match serde_json::from_slice(&body) {
+        Ok(message) => message,
+        Err(e) => {
+            let error = JsonRpcError::new(error_codes::PARSE_ERROR, e.to_string());
+            return (
+                StatusCode::BAD_REQUEST,
+                Json(error_response(Value::Null, error)),
+            )
+                .into_response();
+        }
+    };
+
+    let server = McpServer::for_state(&state).await;
+    match server.handle_message(message, access).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::ACCEPTED.into_response(),
+    }
+}
+
+/// The server does not open server-initiated SSE streams.
+///
+/// GET /api/mcp
+pub async fn mcp_get_handler() -> StatusCode {
+    StatusCode::METHOD_NOT_ALLOWED
+}
+
+/// Viewers and API keys without the `write` permission only get read-only tools.
+fn caller_access(
+    state: &ServerState,
+    user: Option<&SessionInfo>,
+    api_key: Option<&ValidatedApiKey>,
+) -> McpAccess {
+    if let Some(user) = user {
+        return match user.role {
+            UserRole::Viewer => McpAccess::ReadOnly,
+            UserRole::Admin | UserRole::User => McpAccess::ReadWrite,
+        };
+    }
+
+    match api_key {
+        Some(ValidatedApiKey(key)) if state.auth.api_key_state.check_permission(key, "write") => {
+            McpAccess::ReadWrite
+        }
+        _ => McpAccess::ReadOnly,
+    }
+}
+
+/// Placeholder returned instead of header and environment values.
+const MASKED_VALUE: &str = "********";
+
+/// Request body for creating or updating an external MCP server.
+#[derive(Debug, Deserialize)]
+pub struct McpServerRequest {
+    /// Server ID; generated when omitted on create
+    pub id: Option<String>,
+    pub name: String,
+    pub transport: McpTransportConfig,
+    pub enabled: Option<bool>,
+    pub timeout_secs: Option<u64>,
+}
+
+fn require_admin(user: &SessionInfo) -> Result<(), ErrorResponse> {
+    if user.role != UserRole::Admin {
+        return Err(ErrorResponse::new(
+            "FORBIDDEN",
+            "Admin access required",
+            StatusCode::FORBIDDEN,
+        ));
+    }
+    Ok(())
+}
+
+fn transport_values(transport: &mut McpTransportConfig) -> &mut HashMap<String, String> {
+    match transport {
+        McpTransportConfig::Stdio { env, .. } => env,
+        McpTransportConfig::Http { headers, .. } => headers,
+    }
+}
+
+/// Server configuration with secrets masked and its connection status.
+fn server_json(server: &McpServerConfig) -> Value {
+    let mut masked = server.clone();
+    for value in transport_values(&mut masked.transport).values_mut() {
+        *value = MASKED_VALUE.to_string();
+    }
+    let mut value = serde_json::to_value(masked).unwrap_or_default();
+    value["status"] = json!(get_mcp_client_manager().server_status(&server.id));
+    value
+}
+
+/// Keep the stored value for headers and variables sent back masked.
+fn restore_masked_values(transport: &mut McpTransportConfig, previous: &McpTransportConfig) {
+    let mut previous = previous.clone();
+    let previous = transport_values(&mut previous);
+    for (key, value) in transport_values(transport).iter_mut() {
+        if value == MASKED_VALUE {
+            if let Some(stored) = previous.get(key) {
+                *value = stored.clone();
+            }
+        }
+    }
+}
+
+/// Reconnect a changed server and rebuild the chat tool registry.
+async fn apply_server(state: &ServerState, server: McpServerConfig) {
+    let id = server.id.clone();
+    if let Err(e) = get_mcp_client_manager().upsert(server).await {
+        tracing::warn!(category = "mcp", server_id = %id, error = %e, "MCP server not connected");
+    }
+    state.init_tools().await;
+}
+
+/// List external MCP servers.
+///
+/// GET /api/mcp/servers
+pub async fn list_mcp_servers_handler(
+    Extension(user): Extension<SessionInfo>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let store = config::open_settings_store()
+        .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
+    let servers = store
+        .load_all_mcp_servers()
+        .map_err(|e| ErrorResponse::internal(format!("Failed to load MCP servers: {}", e)))?;
+
+    let servers: Vec<Value> = servers.iter().map(server_json).collect();
+    ok(json!({
+        "count": servers.len(),
+        "servers": servers,
+    }))
+}
+
+/// Get an external MCP server and the tools it provides.
+///
+/// GET /api/mcp/servers/:id
+pub async fn get_mcp_server_handler(
+    Extension(user): Extension<SessionInfo>,
+    Path(id): Path<String>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let store = config::open_settings_store()
+        .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
+    let server = store
+        .load_mcp_server(&id)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to load MCP server: {}", e)))?
+        .ok_or_else(|| ErrorResponse::not_found(format!("MCP server {}", id)))?;
+
+    ok(json!({
+        "server": server_json(&server),
+        "tools": get_mcp_client_manager().server_tools(&id).unwrap_or_default(),
+    }))
+}
+
+/// Register an external MCP server and connect to it.
+///
+/// POST /api/mcp/servers
+pub async fn create_mcp_server_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    Json(req): Json<McpServerRequest>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let store = config::open_settings_store()
+        .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
+
+    let id = req.id.unwrap_or_else(McpServerConfig::generate_id);
+    if store.load_mcp_server(&id).is_ok_and(|s| s.is_some()) {
+        return Err(ErrorResponse::conflict(format!(
+            "MCP server already exists: {}",
+            id
+        )));
+    }
+
+    let mut server = McpServerConfig::new(id, req.name, req.transport);
+    if let Some(enabled) = req.enabled {
+        server.enabled = enabled;
+    }
+    if let Some(timeout_secs) = req.timeout_secs {
+        server.timeout_secs = timeout_secs;
+    }
+    server.validate().map_err(ErrorResponse::validation)?;
+
+    store
+        .save_mcp_server(&server)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to save MCP server: {}", e)))?;
+    tracing::info!(category = "mcp", server_id = %server.id, "Created MCP server");
+
+    apply_server(&state, server.clone()).await;
+    ok(json!({ "server": server_json(&server) }))
+}
+
+/// Update an external MCP server; enabling or disabling it connects or
+/// disconnects the server.
+///
+/// PUT /api/mcp/servers/:id
+pub async fn update_mcp_server_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    Path(id): Path<String>,
+    Json(req): Json<McpServerRequest>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let store = config::open_settings_store()
+        .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
+    let mut server = store
+        .load_mcp_server(&id)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to load MCP server: {}", e)))?
+        .ok_or_else(|| ErrorResponse::not_found(format!("MCP server {}", id)))?;
+
+    let mut transport = req.transport;
+    restore_masked_values(&mut transport, &server.transport);
+    server.name = req.name;
+    server.transport = transport;
+    if let Some(enabled) = req.enabled {
+        server.enabled = enabled;
+    }
+    if let Some(timeout_secs) = req.timeout_secs {
+        server.timeout_secs = timeout_secs;
+    }
+    server.validate().map_err(ErrorResponse::validation)?;
+    server.touch();
+
+    store
+        .save_mcp_server(&server)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to save MCP server: {}", e)))?;
+    tracing::info!(category = "mcp", server_id = %id, "Updated MCP server");
+
+    apply_server(&state, server.clone()).await;
+    ok(json!({ "server": server_json(&server) }))
+}
+
+/// Remove an external MCP server and its tools.
+///
+/// DELETE /api/mcp/servers/:id
+pub async fn delete_mcp_server_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    Path(id): Path<String>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let store = config::open_settings_store()
+        .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
+    let existed = store
+        .delete_mcp_server(&id)
+        .map_err(|e| ErrorResponse::internal(format!("Failed to delete MCP server: {}", e)))?;
+    if !existed {
+        return Err(ErrorResponse::not_found(format!("MCP server {}", id)));
+    }
+
+    get_mcp_client_manager().remove(&id).await;
+    state.init_tools().await;
+    tracing::info!(category = "mcp", server_id = %id, "Deleted MCP server");
+    ok(json!({ "message": "MCP server deleted successfully" }))
+}
+
+/// Reconnect an external MCP server and rediscover its tools.
+///
+/// POST /api/mcp/servers/:id/reconnect
+pub async fn reconnect_mcp_server_handler(
+    State(state): State<ServerState>,
+    Extension(user): Extension<SessionInfo>,
+    Path(id): Path<String>,
+) -> HandlerResult<Value> {
+    require_admin(&user)?;
+    let manager = get_mcp_client_manager();
+    if manager.server_status(&id).is_none() {
+        return Err(ErrorResponse::not_found(format!("MCP server {}", id)));
+    }
+
+    let result = manager.reconnect(&id).await;
+    state.init_tools().await;
+    match result {
+        Ok(()) => ok(json!({
+            "status": manager.server_status(&id),
+            "tools": manager.server_tools(&id).unwrap_or_default(),
+        })),
+        Err(e) => Err(ErrorResponse::service_unavailable(format!(
+            "Failed to connect to MCP server {}: {}",
+            id, e
+        ))),
+    }
+}
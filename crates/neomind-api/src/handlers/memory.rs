@@ -1,7 +1,7 @@
 //! Memory system handlers.
 
 use axum::{
-    extract::{Path, Query, State},
+    extract::{Multipart, Path, Query, State},
     Json,
 };
 use serde::{Deserialize, Serialize};
@@ -9,7 +9,10 @@ use serde_json::json;
 use std::sync::Arc;
 
 use neomind_memory::{
-    ConversationEntry, KnowledgeCategory, KnowledgeEntry, MemoryMessage, SearchResult, TieredMemory,
+    ConversationEntry, DocumentFormat, DocumentIngestor, DocumentInput, DocumentSummary,
+    KnowledgeCategory, KnowledgeEntry, LabelledQuery, MemoryMessage, RetrievalFilter,
+    RetrievalMetrics, RetrievalQuery, RetrievalSource, RetrievedItem, SearchMethod, SearchResult,
+    TieredMemory,
 };
 
 use super::{
@@ -32,6 +35,39 @@ fn default_top_k() -> usize {
     5
 }
 
+fn default_search_method() -> SearchMethod {
+    SearchMethod::Hybrid
+}
+
+/// Request body for unified retrieval.
+#[derive(Debug, Deserialize)]
+pub struct RetrieveRequest {
+    /// Query text
+    pub query: String,
+    /// Maximum results (default: 5)
+    #[serde(default = "default_top_k")]
+    pub top_k: usize,
+    /// "hybrid" (default), "semantic" or "bm25"
+    #[serde(default = "default_search_method")]
+    pub method: SearchMethod,
+    /// Device type, category, recency and source filters
+    #[serde(default)]
+    pub filter: RetrievalFilter,
+}
+
+/// Request body for retrieval evaluation.
+#[derive(Debug, Deserialize)]
+pub struct EvaluateRetrievalRequest {
+    /// Labelled queries with the IDs of their relevant entries
+    pub queries: Vec<LabelledQuery>,
+    /// Cutoff for the @k metrics (default: 5)
+    #[serde(default = "default_top_k")]
+    pub k: usize,
+    /// Methods to compare (default: hybrid, semantic and bm25)
+    #[serde(default)]
+    pub methods: Vec<SearchMethod>,
+}
+
 /// DTO for memory stats.
 #[derive(Debug, Serialize)]
 struct MemoryStatsDto {
@@ -107,6 +143,8 @@ struct KnowledgeEntryDto {
     created_at: i64,
     updated_at: i64,
     access_count: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    citation: Option<String>,
 }
 
 impl From<&KnowledgeEntry> for KnowledgeEntryDto {
@@ -121,6 +159,38 @@ impl From<&KnowledgeEntry> for KnowledgeEntryDto {
             created_at: e.created_at,
             updated_at: e.updated_at,
             access_count: e.access_count,
+            citation: e.citation(),
+        }
+    }
+}
+
+/// DTO for a fused retrieval result.
+#[derive(Debug, Serialize)]
+struct RetrievedItemDto {
+    id: String,
+    source: RetrievalSource,
+    title: String,
+    content: String,
+    score: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    lexical_rank: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    semantic_rank: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    citation: Option<String>,
+}
+
+impl From<&RetrievedItem> for RetrievedItemDto {
+    fn from(item: &RetrievedItem) -> Self {
+        Self {
+            id: item.id.clone(),
+            source: item.source(),
+            title: item.title().to_string(),
+            content: item.text(),
+            score: item.score,
+            lexical_rank: item.lexical_rank,
+            semantic_rank: item.semantic_rank,
+            citation: item.citation(),
         }
     }
 }
@@ -227,6 +297,77 @@ pub async fn query_memory_handler(
     }))
 }
 
+/// Retrieve knowledge and conversations with fused BM25 + vector search.
+///
+/// POST /api/memory/retrieve
+pub async fn retrieve_memory_handler(
+    State(state): State<ServerState>,
+    Json(req): Json<RetrieveRequest>,
+) -> HandlerResult<serde_json::Value> {
+    if req.query.trim().is_empty() {
+        return Err(ErrorResponse::bad_request("Query must not be empty"));
+    }
+
+    let memory = get_global_memory(&state);
+    let mem = memory.read().await;
+    let query = RetrievalQuery::new(&req.query, req.top_k)
+        .with_method(req.method)
+        .with_filter(req.filter);
+    let items = mem
+        .retrieve(&query)
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to retrieve: {}", e)))?;
+
+    ok(json!({
+        "query": req.query,
+        "fusion": mem.config().fusion.as_str(),
+        "results": items.iter().map(RetrievedItemDto::from).collect::<Vec<_>>(),
+    }))
+}
+
+/// Score retrieval methods against a labelled query set.
+///
+/// POST /api/memory/retrieve/evaluate
+pub async fn evaluate_retrieval_handler(
+    State(state): State<ServerState>,
+    Json(req): Json<EvaluateRetrievalRequest>,
+) -> HandlerResult<serde_json::Value> {
+    if req.queries.is_empty() {
+        return Err(ErrorResponse::bad_request(
+            "At least one labelled query is required",
+        ));
+    }
+
+    let methods = if req.methods.is_empty() {
+        vec![
+            SearchMethod::Hybrid,
+            SearchMethod::Semantic,
+            SearchMethod::BM25,
+        ]
+    } else {
+        req.methods
+    };
+
+    let memory = get_global_memory(&state);
+    let retriever = memory.read().await.retriever();
+    let mut results: Vec<(SearchMethod, RetrievalMetrics)> = Vec::with_capacity(methods.len());
+    for method in methods {
+        let metrics = retriever
+            .evaluate(&req.queries, req.k, method)
+            .await
+            .map_err(|e| ErrorResponse::internal(format!("Failed to evaluate: {}", e)))?;
+        results.push((method, metrics));
+    }
+
+    ok(json!({
+        "k": req.k,
+        "results": results
+            .into_iter()
+            .map(|(method, metrics)| json!({ "method": method, "metrics": metrics }))
+            .collect::<Vec<_>>(),
+    }))
+}
+
 /// Consolidate short-term to mid-term memory.
 ///
 /// POST /api/memory/consolidate/:session_id
@@ -484,3 +625,137 @@ pub async fn clear_long_term_handler(
         "message": "Long-term memory cleared"
     }))
 }
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+        .collect()
+}
+
+/// Upload a document (Markdown, HTML, plain text or PDF) into the knowledge base.
+///
+/// Multipart fields: `file` (required), `document_id`, `category`,
+/// `device_types` and `tags` (comma-separated). Uploading a document with an
+/// existing ID replaces its chunks.
+///
+/// POST /api/memory/documents
+pub async fn upload_document_handler(
+    State(state): State<ServerState>,
+    mut multipart: Multipart,
+) -> HandlerResult<serde_json::Value> {
+    let mut file: Option<(String, Option<String>, Vec<u8>)> = None;
+    let mut document_id = None;
+    let mut category = None;
+    let mut device_types = Vec::new();
+    let mut tags = Vec::new();
+
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| ErrorResponse::bad_request(format!("Invalid multipart body: {}", e)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let file_name = field.file_name().unwrap_or("document").to_string();
+            let content_type = field.content_type().map(String::from);
+            let data = field
+                .bytes()
+                .await
+                .map_err(|e| ErrorResponse::bad_request(format!("Failed to read file: {}", e)))?;
+            file = Some((file_name, content_type, data.to_vec()));
+            continue;
+        }
+
+        let value = field
+            .text()
+            .await
+            .map_err(|e| ErrorResponse::bad_request(format!("Invalid field {}: {}", name, e)))?;
+        match name.as_str() {
+            "document_id" => document_id = Some(value),
+            "category" => category = Some(KnowledgeCategory::parse(&value)),
+            "device_types" => device_types = split_list(&value),
+            "tags" => tags = split_list(&value),
+            _ => {}
+        }
+    }
+
+    let (file_name, content_type, data) =
+        file.ok_or_else(|| ErrorResponse::bad_request("Missing file field"))?;
+    let format = DocumentFormat::from_filename(&file_name)
+        .or_else(|| {
+            content_type
+                .as_deref()
+                .and_then(DocumentFormat::from_content_type)
+        })
+        .ok_or_else(|| {
+            ErrorResponse::bad_request(format!(
+                "Unsupported document type: {} (expected Markdown, HTML, text or PDF)",
+                file_name
+            ))
+        })?;
+
+    let mut input = DocumentInput::new(file_name, format, data)
+        .with_device_types(device_types)
+        .with_tags(tags);
+    if let Some(id) = document_id {
+        input = input.with_document_id(id);
+    }
+    if let Some(category) = category {
+        input = input.with_category(category);
+    }
+
+    let report = document_ingestor(&state)
+        .await
+        .ingest(input)
+        .await
+        .map_err(|e| match e {
+            neomind_memory::MemoryError::InvalidFormat(msg) => ErrorResponse::bad_request(msg),
+            e => ErrorResponse::internal(format!("Failed to ingest document: {}", e)),
+        })?;
+
+    ok(json!({ "document": report }))
+}
+
+/// List ingested documents.
+///
+/// GET /api/memory/documents
+pub async fn list_documents_handler(
+    State(state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
+    let documents: Vec<DocumentSummary> = document_ingestor(&state).await.list().await;
+
+    ok(json!({
+        "count": documents.len(),
+        "documents": documents,
+    }))
+}
+
+/// Remove an ingested document and its chunks.
+///
+/// DELETE /api/memory/documents/:id
+pub async fn delete_document_handler(
+    State(state): State<ServerState>,
+    Path(id): Path<String>,
+) -> HandlerResult<serde_json::Value> {
+    let removed = document_ingestor(&state)
+        .await
+        .remove(&id)
+        .await
+        .map_err(|e| ErrorResponse::internal(format!("Failed to remove document: {}", e)))?;
+    if removed == 0 {
+        return Err(ErrorResponse::not_found(format!("Document {}", id)));
+    }
+
+    ok(json!({
+        "deleted": id,
+        "chunks": removed,
+    }))
+}
+
+async fn document_ingestor(state: &ServerState) -> DocumentIngestor {
+    let memory = get_global_memory(state);
+    let mem = memory.read().await;
+    DocumentIngestor::new(mem.long_term_ref().clone(), mem.embedding_model())
+}
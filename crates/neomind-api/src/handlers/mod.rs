@@ -1,9 +1,11 @@
 //! API handlers organized by domain.
 
 pub mod agents;
+pub mod approvals;
 pub mod auth;
 pub mod auth_users;
 pub mod automations;
+pub mod backups;
 pub mod basic;
 pub mod bulk;
 pub mod commands;
@@ -16,6 +18,7 @@ pub mod extensions;
 pub mod capabilities;
 pub mod extension_stream;
 pub mod llm_backends;
+pub mod mcp;
 pub mod memory;
 pub mod message_channels;
 pub mod messages;
@@ -5,14 +5,17 @@ use axum::{
     Json,
 };
 use serde_json::json;
+use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::net::TcpStream;
+use tokio::sync::RwLock;
 use tokio::time::timeout;
 
 use neomind_devices::adapter::DeviceAdapter;
 use neomind_devices::adapters::{create_adapter, mqtt::MqttAdapterConfig};
-use neomind_storage::{ExternalBroker, SecurityLevel};
+use neomind_devices::{BridgeEndpoint, BridgeStatus, BridgeTls, MqttBridge};
+use neomind_storage::{BrokerBridge, ExternalBroker, SecurityLevel};
 
 use crate::config;
 use crate::handlers::common::{ok, HandlerResult};
@@ -41,6 +44,19 @@ struct ExternalBrokerDto {
     updated_at: i64,
     /// Topics to subscribe to
     subscribe_topics: Vec<String>,
+    /// Sparkplug B decoding enabled
+    sparkplug: bool,
+    /// Outbound store-and-forward enabled
+    store_forward: bool,
+    /// Topic prefix telemetry is republished under
+    #[serde(skip_serializing_if = "Option::is_none")]
+    forward_telemetry: Option<String>,
+    /// Bridge configuration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bridge: Option<BrokerBridge>,
+    /// Bridge health (present while the bridge is running)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bridge_status: Option<BridgeStatus>,
 }
 
 impl From<ExternalBroker> for ExternalBrokerDto {
@@ -65,7 +81,25 @@ impl From<ExternalBroker> for ExternalBrokerDto {
             last_error: b.last_error,
             updated_at: b.updated_at,
             subscribe_topics: b.subscribe_topics,
+            sparkplug: b.sparkplug,
+            store_forward: b.store_forward,
+            forward_telemetry: b.forward_telemetry,
+            bridge: b.bridge,
+            bridge_status: None,
+        }
+    }
+}
+
+impl ExternalBrokerDto {
+    /// Attach live bridge health from the running bridges.
+    async fn with_bridge_status(
+        mut self,
+        bridges: &RwLock<HashMap<String, Arc<MqttBridge>>>,
+    ) -> Self {
+        if let Some(bridge) = bridges.read().await.get(&self.id) {
+            self.bridge_status = Some(bridge.status().await);
         }
+        self
     }
 }
 
@@ -97,6 +131,18 @@ pub struct ExternalBrokerRequest {
     /// Topics to subscribe to. Defaults to ["#"] for all topics.
     #[serde(default)]
     pub subscribe_topics: Option<Vec<String>>,
+    /// Decode Sparkplug B payloads from this broker.
+    #[serde(default)]
+    pub sparkplug: bool,
+    /// Buffer outbound publishes while the broker is unreachable.
+    #[serde(default = "default_external_broker_store_forward")]
+    pub store_forward: bool,
+    /// Republish device telemetry to this broker under this topic prefix.
+    #[serde(default)]
+    pub forward_telemetry: Option<String>,
+    /// Mirror topics between the embedded broker and this broker.
+    #[serde(default)]
+    pub bridge: Option<BrokerBridge>,
 }
 
 fn default_external_broker_port() -> u16 {
@@ -105,12 +151,63 @@ fn default_external_broker_port() -> u16 {
 fn default_external_broker_enabled() -> bool {
     true
 }
+fn default_external_broker_store_forward() -> bool {
+    true
+}
 
 /// Context for creating and connecting to an external MQTT broker.
 /// This is used both by API handlers and by the startup reconnection logic.
 pub struct ExternalBrokerContext {
     pub device_service: Arc<neomind_devices::service::DeviceService>,
     pub event_bus: Arc<neomind_core::EventBus>,
+    pub bridges: Arc<RwLock<HashMap<String, Arc<MqttBridge>>>>,
+}
+
+/// Stop and remove the bridge for a broker, if one is running.
+pub async fn stop_bridge(bridges: &RwLock<HashMap<String, Arc<MqttBridge>>>, broker_id: &str) {
+    if let Some(bridge) = bridges.write().await.remove(broker_id) {
+        bridge.stop().await;
+        tracing::info!(category = "mqtt", broker_id = %broker_id, "Stopped MQTT bridge");
+    }
+}
+
+/// (Re)start the bridge between the embedded broker and an external broker.
+async fn start_bridge(broker: &ExternalBroker, context: &ExternalBrokerContext) {
+    stop_bridge(&context.bridges, &broker.id).await;
+
+    let Some(config) = broker.bridge.clone().filter(|b| b.enabled) else {
+        return;
+    };
+
+    let local_port = crate::config::open_settings_store()
+        .map(|store| store.get_mqtt_settings().port)
+        .unwrap_or(1883);
+    let local = BridgeEndpoint::new("127.0.0.1", local_port);
+    let mut remote = BridgeEndpoint::new(broker.broker.clone(), broker.port)
+        .with_auth(broker.username.clone(), broker.password.clone());
+    if broker.tls {
+        let client_auth = broker.client_cert.clone().zip(broker.client_key.clone());
+        if client_auth.is_some() && broker.ca_cert.is_none() {
+            tracing::warn!(
+                category = "mqtt",
+                broker_id = %broker.id,
+                "Not starting bridge: a client certificate needs a CA certificate"
+            );
+            return;
+        }
+        remote = remote.with_tls(BridgeTls {
+            ca_cert: broker.ca_cert.clone(),
+            client_auth,
+        });
+    }
+
+    let bridge = Arc::new(MqttBridge::new(broker.id.clone(), config, local, remote));
+    bridge.start().await;
+    context
+        .bridges
+        .write()
+        .await
+        .insert(broker.id.clone(), bridge);
 }
 
 /// Create and connect to an external MQTT broker.
@@ -146,7 +243,16 @@ pub async fn create_and_connect_broker(
         discovery_topic: None,
         discovery_prefix: "neomind".to_string(),
         auto_discovery: false,
-        storage_dir: Some("data".to_string()),
+        storage_dir: Some(neomind_storage::data_dir().to_string_lossy().into_owned()),
+        sparkplug: neomind_devices::SparkplugConfig {
+            enabled: broker.sparkplug,
+            ..Default::default()
+        },
+        store_forward: neomind_devices::StoreForwardConfig {
+            enabled: broker.store_forward,
+            ..Default::default()
+        },
+        forward_telemetry_prefix: broker.forward_telemetry.clone(),
     };
 
     // Create the MQTT adapter
@@ -207,6 +313,9 @@ pub async fn create_and_connect_broker(
         }
     }
 
+    // The bridge reconnects on its own, so start it regardless of the adapter result
+    start_bridge(broker, context).await;
+
     // Update broker connection status
     if let Err(e) =
         update_broker_connection_status_no_store(&broker.id, connected, connection_error).await
@@ -245,7 +354,9 @@ async fn update_broker_connection_status_no_store(
 /// List all external brokers.
 ///
 /// GET /api/brokers
-pub async fn list_brokers_handler() -> HandlerResult<serde_json::Value> {
+pub async fn list_brokers_handler(
+    State(state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
     let store = config::open_settings_store()
         .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
 
@@ -254,7 +365,14 @@ pub async fn list_brokers_handler() -> HandlerResult<serde_json::Value> {
         ErrorResponse::internal(format!("Failed to load brokers: {}", e))
     })?;
 
-    let dtos: Vec<ExternalBrokerDto> = brokers.into_iter().map(ExternalBrokerDto::from).collect();
+    let mut dtos: Vec<ExternalBrokerDto> = Vec::with_capacity(brokers.len());
+    for broker in brokers {
+        dtos.push(
+            ExternalBrokerDto::from(broker)
+                .with_bridge_status(&state.devices.bridges)
+                .await,
+        );
+    }
     ok(json!({
         "brokers": dtos,
         "count": dtos.len(),
@@ -264,7 +382,10 @@ pub async fn list_brokers_handler() -> HandlerResult<serde_json::Value> {
 /// Get a specific external broker.
 ///
 /// GET /api/brokers/:id
-pub async fn get_broker_handler(Path(id): Path<String>) -> HandlerResult<serde_json::Value> {
+pub async fn get_broker_handler(
+    Path(id): Path<String>,
+    State(state): State<ServerState>,
+) -> HandlerResult<serde_json::Value> {
     let store = config::open_settings_store()
         .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
 
@@ -277,7 +398,9 @@ pub async fn get_broker_handler(Path(id): Path<String>) -> HandlerResult<serde_j
         .ok_or_else(|| ErrorResponse::not_found(format!("Broker not found: {}", id)))?;
 
     ok(json!({
-        "broker": ExternalBrokerDto::from(broker),
+        "broker": ExternalBrokerDto::from(broker)
+            .with_bridge_status(&state.devices.bridges)
+            .await,
     }))
 }
 
@@ -315,6 +438,10 @@ pub async fn create_broker_handler(
     if let Some(topics) = &req.subscribe_topics {
         broker.subscribe_topics = topics.clone();
     }
+    broker.sparkplug = req.sparkplug;
+    broker.store_forward = req.store_forward;
+    broker.forward_telemetry = req.forward_telemetry.clone();
+    broker.bridge = req.bridge.clone();
 
     // Run security validation
     let warnings = broker.validate_security();
@@ -370,6 +497,7 @@ pub async fn create_broker_handler(
         let context = ExternalBrokerContext {
             device_service: state.devices.service.clone(),
             event_bus: event_bus.clone(),
+            bridges: state.devices.bridges.clone(),
         };
 
         match create_and_connect_broker(&broker, &context).await {
@@ -460,6 +588,10 @@ pub async fn update_broker_handler(
     if let Some(topics) = req.subscribe_topics {
         broker.subscribe_topics = topics;
     }
+    broker.sparkplug = req.sparkplug;
+    broker.store_forward = req.store_forward;
+    broker.forward_telemetry = req.forward_telemetry;
+    broker.bridge = req.bridge;
     broker.touch();
 
     store
@@ -468,6 +600,10 @@ pub async fn update_broker_handler(
 
     tracing::info!(category = "mqtt", name = %broker.name, url = %broker.broker_url(), "Updated external broker");
 
+    if !broker.enabled {
+        stop_bridge(&state.devices.bridges, &id).await;
+    }
+
     // If enabled, restart the MQTT adapter with new configuration using shared function
     let mut connected = false;
     let mut _connection_error: Option<String> = None;
@@ -486,6 +622,7 @@ pub async fn update_broker_handler(
         let context = ExternalBrokerContext {
             device_service: state.devices.service.clone(),
             event_bus: event_bus.clone(),
+            bridges: state.devices.bridges.clone(),
         };
 
         match create_and_connect_broker(&broker, &context).await {
@@ -531,9 +668,10 @@ pub async fn delete_broker_handler(
     let store = config::open_settings_store()
         .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
 
-    // First, stop the MQTT adapter if it's running
+    // First, stop the MQTT adapter and bridge if they are running
     let adapter_id = format!("external-{}", id);
     let _ = state.devices.service.stop_adapter(&adapter_id).await;
+    stop_bridge(&state.devices.bridges, &id).await;
     tracing::info!(category = "mqtt", broker_id = %id, "Stopped MQTT adapter for external broker");
 
     // Then delete from storage
@@ -49,6 +49,9 @@ pub struct MqttStatusDto {
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub external_brokers: Vec<ExternalBrokerConnectionDto>,
     pub last_error: Option<String>,
+    /// Store-and-forward backlog of the internal broker connection
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbound_queue: Option<neomind_devices::OutboxStats>,
 }
 
 /// DTO for external broker connection status in MQTT status.
@@ -66,6 +69,9 @@ pub struct ExternalBrokerConnectionDto {
     /// Client ID prefix
     #[serde(skip_serializing_if = "Option::is_none")]
     pub client_id_prefix: Option<String>,
+    /// Store-and-forward backlog for this broker
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub outbound_queue: Option<neomind_devices::OutboxStats>,
 }
 
 /// DTO for MQTT subscription.
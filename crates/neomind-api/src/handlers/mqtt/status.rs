@@ -55,6 +55,19 @@ fn get_server_ip() -> String {
     std::env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string())
 }
 
+/// Aggregate store-and-forward backlog of an MQTT adapter, if it has one.
+async fn outbound_queue_stats(
+    state: &ServerState,
+    adapter_id: &str,
+) -> Option<neomind_devices::OutboxStats> {
+    use neomind_devices::adapters::mqtt::MqttAdapter;
+
+    let adapter = state.devices.service.get_adapter(adapter_id).await?;
+    let mqtt = adapter.as_any().downcast_ref::<MqttAdapter>()?;
+    // One adapter normally holds a single broker connection
+    mqtt.outbox_stats().await.into_values().next()
+}
+
 /// Get MQTT connection status.
 ///
 /// GET /api/mqtt/status
@@ -97,24 +110,30 @@ pub async fn get_mqtt_status_handler(
     let clients_count = devices_count;
 
     // Load external brokers
-    let external_brokers: Vec<ExternalBrokerConnectionDto> = match store.load_all_external_brokers()
-    {
-        Ok(brokers) => brokers
-            .into_iter()
-            .map(|b| ExternalBrokerConnectionDto {
-                id: b.id,
-                name: b.name,
-                host: b.broker,
-                port: b.port,
-                tls: b.tls,
-                connected: b.connected,
-                enabled: b.enabled,
-                last_error: b.last_error,
-                client_id_prefix: None,
-            })
-            .collect(),
-        Err(_) => Vec::new(),
-    };
+    let mut external_brokers: Vec<ExternalBrokerConnectionDto> =
+        match store.load_all_external_brokers() {
+            Ok(brokers) => brokers
+                .into_iter()
+                .map(|b| ExternalBrokerConnectionDto {
+                    id: b.id,
+                    name: b.name,
+                    host: b.broker,
+                    port: b.port,
+                    tls: b.tls,
+                    connected: b.connected,
+                    enabled: b.enabled,
+                    last_error: b.last_error,
+                    client_id_prefix: None,
+                    outbound_queue: None,
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+    for broker in &mut external_brokers {
+        broker.outbound_queue =
+            outbound_queue_stats(&state, &format!("external-{}", broker.id)).await;
+    }
+    let outbound_queue = outbound_queue_stats(&state, "internal-mqtt").await;
 
     ok(json!({
         "status": MqttStatusDto {
@@ -127,6 +146,7 @@ pub async fn get_mqtt_status_handler(
             listen_port,
             external_brokers,
             last_error,
+            outbound_queue,
         },
     }))
 }
@@ -157,6 +157,21 @@ fn condition_to_json(cond: &RuleCondition) -> Value {
                 "threshold": max,
             })
         }
+        RuleCondition::DeviceGroup {
+            selector,
+            quantifier,
+            metric,
+            operator,
+            threshold,
+        } => {
+            json!({
+                "selector": selector,
+                "quantifier": quantifier.as_str(),
+                "metric": metric,
+                "operator": operator_to_symbol(operator),
+                "threshold": threshold,
+            })
+        }
         RuleCondition::And(conditions) => {
             json!({
                 "operator": "and",
@@ -6,6 +6,7 @@ use axum::extract::ws::{Message as AxumMessage, WebSocket, WebSocketUpgrade};
 use axum::{
     extract::{Path, Query, State},
     response::Json,
+    Extension,
 };
 use futures::stream::{Stream, StreamExt};
 use serde::{Deserialize, Serialize};
@@ -630,6 +631,7 @@ pub async fn cleanup_sessions_handler(
 pub async fn chat_handler(
     State(state): State<ServerState>,
     Path(id): Path<String>,
+    user: Option<Extension<crate::auth_users::SessionInfo>>,
     Json(req): Json<ChatRequest>,
 ) -> Result<Json<ChatResponse>, ErrorResponse> {
     use tokio::time::{timeout, Duration};
@@ -639,6 +641,15 @@ pub async fn chat_handler(
         id, req.message
     );
 
+    // Attribute the session's LLM usage to the authenticated user
+    if let Some(Extension(user)) = &user {
+        let _ = state
+            .agents
+            .session_manager
+            .set_session_user(&id, &user.username)
+            .await;
+    }
+
     // Add a 120-second timeout to support thinking models
     // QWEN3 with thinking enabled can take 60-90 seconds for complex queries
     // due to the model's repetitive thinking generation, especially with longer context
@@ -794,7 +805,7 @@ async fn handle_ws_socket(
     mut socket: WebSocket,
     state: ServerState,
     session_id: Option<String>,
-    _session_info: Option<crate::auth_users::SessionInfo>,
+    session_info: Option<crate::auth_users::SessionInfo>,
 ) {
     // Create connection metadata for tracking state and heartbeat
     let conn_meta = create_connection_metadata();
@@ -961,6 +972,11 @@ async fn handle_ws_socket(
                                         continue;
                                     }
 
+                                    // Attribute the session's LLM usage to the connected user
+                                    if let Some(ref info) = session_info {
+                                        let _ = state.agents.session_manager.set_session_user(&session_id, &info.username).await;
+                                    }
+
                                     // Try event streaming first (rich response with tool calls)
                                     // Spawn a task to process the stream asynchronously, keeping the main loop responsive
                                     let backend_id = chat_req.backend_id.as_deref();
@@ -14,6 +14,9 @@ use crate::models::ErrorResponse;
 #[derive(serde::Deserialize)]
 pub struct LlmGenerateRequest {
     pub prompt: String,
+    /// Serve repeated prompts from the LLM response cache
+    #[serde(default)]
+    pub cache: Option<neomind_storage::ResponseCacheConfig>,
 }
 
 /// Generate LLM response (one-shot, no session required).
@@ -132,12 +135,26 @@ pub async fn llm_generate_handler(
             presence_penalty: None,
             thinking_enabled: None,
             max_context: None,
+            response_format: None,
         },
         model: Some(model_name),
         stream: false,
         tools: None,
     };
 
+    // Opt-in response caching for repeated prompts
+    let llm_runtime: Box<dyn LlmRuntime> = match req.cache {
+        Some(ref cache) => {
+            cache.validate().map_err(ErrorResponse::bad_request)?;
+            Box::new(neomind_llm::CachedLlmRuntime::new(
+                llm_runtime.into(),
+                neomind_llm::get_response_cache(),
+                cache.into(),
+            ))
+        }
+        None => llm_runtime,
+    };
+
     let start = std::time::Instant::now();
 
     // Call LLM directly (bypassing agent's tool calling)
@@ -177,9 +194,7 @@ pub struct TimezoneResponse {
 pub async fn get_timezone(State(_state): State<ServerState>) -> HandlerResult<TimezoneResponse> {
     use neomind_storage::SettingsStore;
 
-    const SETTINGS_DB_PATH: &str = "data/settings.redb";
-
-    let settings_store = SettingsStore::open(SETTINGS_DB_PATH)
+    let settings_store = SettingsStore::open(crate::config::settings_db_path())
         .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
 
     let timezone = settings_store.get_global_timezone();
@@ -198,8 +213,6 @@ pub async fn update_timezone(
 ) -> HandlerResult<serde_json::Value> {
     use neomind_storage::SettingsStore;
 
-    const SETTINGS_DB_PATH: &str = "data/settings.redb";
-
     // Validate timezone using chrono-tz
     if req.timezone.parse::<chrono_tz::Tz>().is_err() {
         return Err(ErrorResponse::bad_request(format!(
@@ -208,7 +221,7 @@ pub async fn update_timezone(
         )));
     }
 
-    let settings_store = SettingsStore::open(SETTINGS_DB_PATH)
+    let settings_store = SettingsStore::open(crate::config::settings_db_path())
         .map_err(|e| ErrorResponse::internal(format!("Failed to open settings store: {}", e)))?;
 
     settings_store
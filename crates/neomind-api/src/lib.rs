@@ -10,6 +10,7 @@ pub mod capability_providers;
 pub mod config;
 pub mod crypto;
 pub mod event_services;
+pub mod mcp;
 pub mod handlers;
 pub mod models;
 pub mod openapi;
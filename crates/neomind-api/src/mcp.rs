@@ -0,0 +1,512 @@
+//! Model Context Protocol server.
+//!
+//! Exposes the tool registry as MCP tools and devices and device types as
+//! MCP resources. Both transports share [`McpServer`]: stdio (`neomind mcp`)
+//! and Streamable HTTP (`POST /api/mcp`).
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt};
+
+use neomind_devices::DeviceService;
+use neomind_tools::mcp::{
+    call_result, error_codes, error_result, JsonRpcError, JsonRpcRequest, JsonRpcResponse, McpTool,
+    PROTOCOL_VERSION, SUPPORTED_PROTOCOL_VERSIONS,
+};
+use neomind_tools::{Tool, ToolRegistry};
+
+use crate::server::ServerState;
+
+const DEVICE_URI_PREFIX: &str = "neomind://devices/";
+const DEVICE_TYPE_URI_PREFIX: &str = "neomind://device-types/";
+
+/// What an MCP caller is allowed to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum McpAccess {
+    /// Only read-only tools are listed and callable
+    ReadOnly,
+    /// All tools, including those that control devices or change configuration
+    ReadWrite,
+}
+
+impl McpAccess {
+    fn allows(self, tool: &dyn Tool) -> bool {
+        self == McpAccess::ReadWrite || tool.is_read_only()
+    }
+}
+
+/// MCP request dispatcher.
+#[derive(Clone)]
+pub struct McpServer {
+    tools: Arc<ToolRegistry>,
+    devices: Arc<DeviceService>,
+}
+
+impl McpServer {
+    /// Create a server over a tool registry and device service
+    pub fn new(tools: Arc<ToolRegistry>, devices: Arc<DeviceService>) -> Self {
+        Self { tools, devices }
+    }
+
+    /// Create a server over the tools and devices of a running system
+    pub async fn for_state(state: &ServerState) -> Self {
+        let tools = state
+            .agents
+            .session_manager
+            .tool_registry()
+            .await
+            .unwrap_or_else(|| Arc::new(ToolRegistry::new()));
+        Self::new(tools, state.devices.service.clone())
+    }
+
+    /// Handle a JSON-RPC message or batch.
+    ///
+    /// Returns `None` when nothing is to be sent back (notifications and
+    /// client responses).
+    pub async fn handle_message(&self, message: Value, access: McpAccess) -> Option<Value> {
+        match message {
+            Value::Array(batch) if batch.is_empty() => Some(error_response(
+                Value::Null,
+                JsonRpcError::new(error_codes::INVALID_REQUEST, "Empty batch"),
+            )),
+            Value::Array(batch) => {
+                let mut responses = Vec::new();
+                for message in batch {
+                    if let Some(response) = self.handle_single(message, access).await {
+                        responses.push(response);
+                    }
+                }
+                (!responses.is_empty()).then_some(Value::Array(responses))
+            }
+            message => self.handle_single(message, access).await,
+        }
+    }
+
+    async fn handle_single(&self, message: Value, access: McpAccess) -> Option<Value> {
+        // This server never sends requests, so responses from the client are dropped
+        if message.get("method").is_none()
+            && (message.get("result").is_some() || message.get("error").is_some())
+        {
+            return None;
+        }
+
+        match serde_json::from_value::<JsonRpcRequest>(message) {
+            Ok(request) => self
+                .handle(request, access)
+                .await
+                .map(|response| serde_json::to_value(response).unwrap_or_default()),
+            Err(e) => Some(error_response(
+                Value::Null,
+                JsonRpcError::new(error_codes::INVALID_REQUEST, e.to_string()),
+            )),
+        }
+    }
+
+    /// Handle a request; notifications produce no response.
+    pub async fn handle(
+        &self,
+        request: JsonRpcRequest,
+        access: McpAccess,
+    ) -> Option<JsonRpcResponse> {
+        let Some(id) = request.id else {
+            tracing::debug!(category = "mcp", "Notification: {}", request.method);
+            return None;
+        };
+
+        let params = request.params.unwrap_or(Value::Null);
+        let result = match request.method.as_str() {
+            "initialize" => Ok(initialize(&params)),
+            "ping" => Ok(json!({})),
+            "tools/list" => Ok(self.list_tools(access)),
+            "tools/call" => self.call_tool(&params, access).await,
+            "resources/list" => Ok(self.list_resources().await),
+            "resources/templates/list" => Ok(resource_templates()),
+            "resources/read" => self.read_resource(&params).await,
+            method => Err(JsonRpcError::method_not_found(method)),
+        };
+
+        Some(match result {
+            Ok(result) => JsonRpcResponse::success(id, result),
+            Err(error) => JsonRpcResponse::failure(id, error),
+        })
+    }
+
+    fn list_tools(&self, access: McpAccess) -> Value {
+        let mut names = self.tools.list();
+        names.sort();
+        let tools: Vec<McpTool> = names
+            .iter()
+            .filter_map(|name| self.tools.get(name))
+            .filter(|tool| access.allows(tool.as_ref()))
+            .map(|tool| McpTool::from_tool(tool.as_ref()))
+            .collect();
+        json!({ "tools": tools })
+    }
+
+    async fn call_tool(&self, params: &Value, access: McpAccess) -> Result<Value, JsonRpcError> {
+        let name = params
+            .get("name")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("Missing tool name"))?;
+        let tool = self
+            .tools
+            .get(name)
+            .ok_or_else(|| JsonRpcError::invalid_params(format!("Unknown tool: {}", name)))?;
+
+        if !access.allows(tool.as_ref()) {
+            return Ok(error_result(&format!(
+                "Tool '{}' modifies system state and requires write access",
+                name
+            )));
+        }
+
+        let args = params
+            .get("arguments")
+            .cloned()
+            .unwrap_or_else(|| json!({}));
+        if let Err(e) = tool.validate_args(&args) {
+            return Ok(error_result(&e.to_string()));
+        }
+
+        tracing::info!(category = "mcp", tool = name, "Tool call");
+        Ok(match tool.execute(args).await {
+            Ok(output) => call_result(&output),
+            Err(e) => error_result(&e.to_string()),
+        })
+    }
+
+    async fn list_resources(&self) -> Value {
+        let mut resources = Vec::new();
+        for device in self.devices.list_devices().await {
+            resources.push(json!({
+                "uri": format!("{}{}", DEVICE_URI_PREFIX, device.device_id),
+                "name": device.device_id,
+                "title": device.name,
+                "description": format!("{} device", device.device_type),
+                "mimeType": "application/json",
+            }));
+        }
+        for template in self.devices.list_templates().await {
+            resources.push(json!({
+                "uri": format!("{}{}", DEVICE_TYPE_URI_PREFIX, template.device_type),
+                "name": template.device_type,
+                "title": template.name,
+                "description": template.description,
+                "mimeType": "application/json",
+            }));
+        }
+        json!({ "resources": resources })
+    }
+
+    async fn read_resource(&self, params: &Value) -> Result<Value, JsonRpcError> {
+        let uri = params
+            .get("uri")
+            .and_then(Value::as_str)
+            .ok_or_else(|| JsonRpcError::invalid_params("Missing resource URI"))?;
+        let not_found = || {
+            JsonRpcError::new(
+                error_codes::RESOURCE_NOT_FOUND,
+                format!("Resource not found: {}", uri),
+            )
+        };
+
+        let contents = if let Some(device_id) = uri.strip_prefix(DEVICE_URI_PREFIX) {
+            let device = self
+                .devices
+                .get_device(device_id)
+                .await
+                .ok_or_else(not_found)?;
+            let status = self.devices.get_device_status(device_id).await;
+            let metrics = self
+                .devices
+                .get_current_metrics(device_id)
+                .await
+                .unwrap_or_default();
+            json!({
+                "device_id": device.device_id,
+                "name": device.name,
+                "device_type": device.device_type,
+                "adapter_type": device.adapter_type,
+                "location": device.location,
+                "tags": device.tags,
+                "groups": device.groups,
+                "status": status.status,
+                "last_seen": status.last_seen,
+                "metrics": metrics,
+            })
+        } else if let Some(device_type) = uri.strip_prefix(DEVICE_TYPE_URI_PREFIX) {
+            let template = self
+                .devices
+                .get_template(device_type)
+                .await
+                .ok_or_else(not_found)?;
+            serde_json::to_value(template).unwrap_or_default()
+        } else {
+            return Err(not_found());
+        };
+
+        Ok(json!({
+            "contents": [{
+                "uri": uri,
+                "mimeType": "application/json",
+                "text": serde_json::to_string_pretty(&contents).unwrap_or_default(),
+            }]
+        }))
+    }
+}
+
+fn initialize(params: &Value) -> Value {
+    let version = params
+        .get("protocolVersion")
+        .and_then(Value::as_str)
+        .filter(|v| SUPPORTED_PROTOCOL_VERSIONS.contains(v))
+        .unwrap_or(PROTOCOL_VERSION);
+    json!({
+        "protocolVersion": version,
+        "capabilities": {
+            "tools": { "listChanged": false },
+            "resources": { "subscribe": false, "listChanged": false },
+        },
+        "serverInfo": {
+            "name": "neomind",
+            "title": "NeoMind",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "instructions": "NeoMind edge AI platform. Devices and device types are \
+            available as resources; tools query and control devices, rules and agents.",
+    })
+}
+
+fn resource_templates() -> Value {
+    json!({
+        "resourceTemplates": [
+            {
+                "uriTemplate": format!("{}{{device_id}}", DEVICE_URI_PREFIX),
+                "name": "device",
+                "title": "Device",
+                "description": "Device configuration, connection status and current metric values",
+                "mimeType": "application/json",
+            },
+            {
+                "uriTemplate": format!("{}{{device_type}}", DEVICE_TYPE_URI_PREFIX),
+                "name": "device-type",
+                "title": "Device type",
+                "description": "Device type template with metric and command definitions",
+                "mimeType": "application/json",
+            },
+        ]
+    })
+}
+
+/// Serialized JSON-RPC error response.
+pub(crate) fn error_response(id: Value, error: JsonRpcError) -> Value {
+    serde_json::to_value(JsonRpcResponse::failure(id, error)).unwrap_or_default()
+}
+
+/// Serve newline-delimited JSON-RPC messages until the reader is exhausted.
+pub async fn serve_lines<R, W>(
+    server: &McpServer,
+    reader: R,
+    mut writer: W,
+    access: McpAccess,
+) -> std::io::Result<()>
+where
+    R: AsyncBufRead + Unpin,
+    W: AsyncWrite + Unpin,
+{
+    let mut lines = reader.lines();
+    while let Some(line) = lines.next_line().await? {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str::<Value>(line) {
+            Ok(message) => server.handle_message(message, access).await,
+            Err(e) => Some(error_response(
+                Value::Null,
+                JsonRpcError::new(error_codes::PARSE_ERROR, e.to_string()),
+            )),
+        };
+        if let Some(response) = response {
+            let mut text = serde_json::to_string(&response).unwrap_or_default();
+            text.push('\n');
+            writer.write_all(text.as_bytes()).await?;
+            writer.flush().await?;
+        }
+    }
+    Ok(())
+}
+
+/// Run the MCP server on stdin/stdout.
+///
+/// Opens the data directory and starts the device runtime in this process,
+/// so it cannot run alongside `neomind serve`; use `POST /api/mcp` to reach
+/// a running server instead.
+pub async fn serve_stdio(access: McpAccess) -> anyhow::Result<()> {
+    crate::startup::reserve_stdout();
+    let state = ServerState::new().await;
+    state.init_device_storage().await;
+    state.init_llm().await;
+    state.init_tools().await;
+    state.init_mqtt().await;
+
+    let server = McpServer::for_state(&state).await;
+    tracing::info!(category = "mcp", ?access, "MCP server listening on stdio");
+    let stdin = tokio::io::BufReader::new(tokio::io::stdin());
+    serve_lines(&server, stdin, tokio::io::stdout(), access).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_trait::async_trait;
+    use neomind_core::EventBus;
+    use neomind_devices::{DeviceRegistry, DeviceTypeTemplate};
+    use neomind_tools::{ToolOutput, ToolRegistryBuilder};
+
+    struct StubTool {
+        name: &'static str,
+        read_only: bool,
+    }
+
+    #[async_trait]
+    impl Tool for StubTool {
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn description(&self) -> &str {
+            "Stub tool"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({
+                "type": "object",
+                "properties": { "device_id": { "type": "string" } },
+                "required": ["device_id"]
+            })
+        }
+
+        async fn execute(&self, args: Value) -> neomind_tools::Result<ToolOutput> {
+            Ok(ToolOutput::success(
+                json!({ "tool": self.name, "args": args }),
+            ))
+        }
+
+        fn is_read_only(&self) -> bool {
+            self.read_only
+        }
+    }
+
+    async fn test_server() -> McpServer {
+        let tools = ToolRegistryBuilder::new()
+            .with_tool(Arc::new(StubTool {
+                name: "read_device",
+                read_only: true,
+            }))
+            .with_tool(Arc::new(StubTool {
+                name: "switch_device",
+                read_only: false,
+            }))
+            .build();
+        let devices = Arc::new(DeviceService::new(
+            Arc::new(DeviceRegistry::new()),
+            EventBus::new(),
+        ));
+        devices
+            .register_template(DeviceTypeTemplate::new("dht22", "DHT22 Sensor"))
+            .await
+            .unwrap();
+        McpServer::new(Arc::new(tools), devices)
+    }
+
+    async fn call(server: &McpServer, method: &str, params: Value, access: McpAccess) -> Value {
+        let request = JsonRpcRequest::new(1, method, Some(params));
+        let response = server.handle(request, access).await.unwrap();
+        serde_json::to_value(response).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_tools_respect_access() {
+        let server = test_server().await;
+
+        let list = call(&server, "tools/list", json!({}), McpAccess::ReadOnly).await;
+        let tools = list["result"]["tools"].as_array().unwrap();
+        assert_eq!(tools.len(), 1);
+        assert_eq!(tools[0]["name"], "read_device");
+        assert_eq!(tools[0]["inputSchema"]["required"][0], "device_id");
+
+        let params = json!({ "name": "switch_device", "arguments": { "device_id": "lamp" } });
+        let denied = call(&server, "tools/call", params.clone(), McpAccess::ReadOnly).await;
+        assert_eq!(denied["result"]["isError"], true);
+        let allowed = call(&server, "tools/call", params, McpAccess::ReadWrite).await;
+        assert_eq!(allowed["result"]["isError"], false);
+        assert_eq!(
+            allowed["result"]["structuredContent"]["args"]["device_id"],
+            "lamp"
+        );
+
+        let invalid = json!({ "name": "read_device", "arguments": {} });
+        let invalid = call(&server, "tools/call", invalid, McpAccess::ReadOnly).await;
+        assert_eq!(invalid["result"]["isError"], true);
+
+        let unknown = call(
+            &server,
+            "tools/call",
+            json!({ "name": "x" }),
+            McpAccess::ReadWrite,
+        )
+        .await;
+        assert_eq!(unknown["error"]["code"], error_codes::INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_resources() {
+        let server = test_server().await;
+
+        let list = call(&server, "resources/list", json!({}), McpAccess::ReadOnly).await;
+        let resources = list["result"]["resources"].as_array().unwrap();
+        assert_eq!(resources.len(), 1);
+        assert_eq!(resources[0]["uri"], "neomind://device-types/dht22");
+
+        let read = json!({ "uri": "neomind://device-types/dht22" });
+        let read = call(&server, "resources/read", read, McpAccess::ReadOnly).await;
+        let text = read["result"]["contents"][0]["text"].as_str().unwrap();
+        let template: Value = serde_json::from_str(text).unwrap();
+        assert_eq!(template["name"], "DHT22 Sensor");
+
+        let missing = json!({ "uri": "neomind://devices/nope" });
+        let missing = call(&server, "resources/read", missing, McpAccess::ReadOnly).await;
+        assert_eq!(missing["error"]["code"], error_codes::RESOURCE_NOT_FOUND);
+    }
+
+    #[tokio::test]
+    async fn test_stdio_session() {
+        let server = test_server().await;
+        let input = [
+            r#"{"jsonrpc":"2.0","id":1,"method":"initialize","params":{"protocolVersion":"2025-03-26","capabilities":{},"clientInfo":{"name":"test","version":"1"}}}"#,
+            r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#,
+            "not json",
+            r#"{"jsonrpc":"2.0","id":2,"method":"prompts/list"}"#,
+        ]
+        .join("\n");
+
+        let mut output = Vec::new();
+        serve_lines(&server, input.as_bytes(), &mut output, McpAccess::ReadOnly)
+            .await
+            .unwrap();
+
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(responses.len(), 3);
+        assert_eq!(responses[0]["result"]["protocolVersion"], "2025-03-26");
+        assert_eq!(responses[1]["error"]["code"], error_codes::PARSE_ERROR);
+        assert_eq!(responses[2]["error"]["code"], error_codes::METHOD_NOT_FOUND);
+    }
+}
@@ -36,6 +36,17 @@ pub async fn run(bind: SocketAddr) -> anyhow::Result<()> {
     let mut startup = StartupLogger::new();
     startup.banner();
 
+    // Apply a restore staged through the admin API before any store is opened
+    match neomind_storage::apply_pending_restore(&neomind_storage::data_dir()) {
+        Ok(Some(outcome)) => tracing::warn!(
+            entries = outcome.entries_restored,
+            previous_data = %outcome.previous_data.display(),
+            "Restored system snapshot"
+        ),
+        Ok(None) => {}
+        Err(e) => tracing::error!("Failed to apply staged snapshot restore: {}", e),
+    }
+
     let state = ServerState::new().await;
 
     // Initialization phase
@@ -52,6 +63,9 @@ pub async fn run(bind: SocketAddr) -> anyhow::Result<()> {
     state.init_transform_event_service().await;
     startup.service("Transform event service", ServiceStatus::Started);
 
+    // Connect to external MCP servers (tools are added once discovered)
+    state.init_mcp_clients().await;
+
     // Initialize tools
     state.init_tools().await;
     startup.service("AI tools", ServiceStatus::Started);
@@ -142,6 +156,29 @@ pub async fn run(bind: SocketAddr) -> anyhow::Result<()> {
         }
     });
 
+    // Scheduled snapshots ([backup] schedule_interval_secs in config.toml)
+    if let Some(secs) = crate::config::get_backup_config()
+        .schedule_interval_secs
+        .filter(|secs| *secs > 0)
+    {
+        let state_for_backups = state.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(secs));
+            // The first tick completes immediately
+            interval.tick().await;
+            loop {
+                interval.tick().await;
+                let description = Some("scheduled".to_string());
+                if let Err(e) =
+                    crate::handlers::backups::take_snapshot(&state_for_backups, description).await
+                {
+                    tracing::warn!("Scheduled snapshot failed: {}", e);
+                }
+            }
+        });
+        startup.service("Snapshot schedule", ServiceStatus::Started);
+    }
+
     let app = create_router_with_state(state);
 
     let listener = tokio::net::TcpListener::bind(bind).await?;
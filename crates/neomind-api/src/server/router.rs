@@ -22,10 +22,10 @@ pub async fn create_router() -> Router {
 /// Create the application router with a specific state.
 pub fn create_router_with_state(state: ServerState) -> Router {
     use crate::handlers::{
-        agents, auth as auth_handlers, auth_users, automations, basic, bulk, commands, config,
-        dashboards, devices, events, extensions, extension_stream, llm_backends, memory,
-        message_channels, messages, mqtt, rules, search, sessions, settings, setup, stats,
-        suggestions, test_data, tools, capabilities,
+        agents, approvals, auth as auth_handlers, auth_users, automations, backups, basic, bulk,
+        capabilities, commands, config, dashboards, devices, events, extension_stream, extensions,
+        llm_backends, mcp, memory, message_channels, messages, mqtt, rules, search, sessions,
+        settings, setup, stats, suggestions, test_data, tools,
     };
 
     // Public routes (no authentication required)
@@ -73,6 +73,34 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/llm-backends/stats",
             get(llm_backends::get_backend_stats_handler),
         )
+        .route(
+            "/api/llm-backends/routing-policies",
+            get(llm_backends::list_routing_policies_handler),
+        )
+        .route(
+            "/api/llm-backends/routing-policies/:id",
+            get(llm_backends::get_routing_policy_handler),
+        )
+        .route(
+            "/api/llm-backends/routing/health",
+            get(llm_backends::get_routing_health_handler),
+        )
+        .route(
+            "/api/llm-backends/pricing",
+            get(llm_backends::get_pricing_handler),
+        )
+        .route(
+            "/api/llm-backends/budgets",
+            get(llm_backends::list_budgets_handler),
+        )
+        .route(
+            "/api/llm-backends/budgets/:id",
+            get(llm_backends::get_budget_handler),
+        )
+        .route(
+            "/api/llm-backends/cache",
+            get(llm_backends::get_response_cache_stats_handler),
+        )
         // Ollama models API (public - fetch available models with capabilities)
         .route(
             "/api/llm-backends/ollama/models",
@@ -231,6 +259,14 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/auth/change-password",
             post(auth_users::change_password_handler),
         )
+        // Approval queue for agent actions (approver identity comes from the JWT)
+        .route("/api/approvals", get(approvals::list_approvals_handler))
+        .route("/api/approvals/:id", get(approvals::get_approval_handler))
+        .route(
+            "/api/approvals/:id/approve",
+            post(approvals::approve_handler),
+        )
+        .route("/api/approvals/:id/deny", post(approvals::deny_handler))
         // Apply JWT authentication middleware
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
@@ -349,6 +385,14 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/devices/:id/commands",
             get(devices::get_device_command_history_handler),
         )
+        .route(
+            "/api/devices/:id/shadow",
+            get(devices::get_device_shadow_handler),
+        )
+        .route(
+            "/api/devices/:id/shadow",
+            put(devices::update_device_shadow_handler),
+        )
         // Device Types API
         .route("/api/device-types", get(devices::list_device_types_handler))
         .route(
@@ -700,6 +744,21 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/memory/long-term",
             delete(memory::clear_long_term_handler),
         )
+        .route("/api/memory/retrieve", post(memory::retrieve_memory_handler))
+        .route(
+            "/api/memory/retrieve/evaluate",
+            post(memory::evaluate_retrieval_handler),
+        )
+        .route("/api/memory/documents", get(memory::list_documents_handler))
+        .route(
+            "/api/memory/documents",
+            post(memory::upload_document_handler)
+                .layer(DefaultBodyLimit::max(MAX_REQUEST_BODY_SIZE)),
+        )
+        .route(
+            "/api/memory/documents/:id",
+            delete(memory::delete_document_handler),
+        )
         // Tools API
         .route("/api/tools", get(tools::list_tools_handler))
         .route(
@@ -715,6 +774,11 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/tools/format-for-llm",
             get(tools::format_for_llm_handler),
         )
+        // Model Context Protocol (Streamable HTTP)
+        .route(
+            "/api/mcp",
+            post(mcp::mcp_post_handler).get(mcp::mcp_get_handler),
+        )
         // MQTT Management API
         .route("/api/mqtt/status", get(mqtt::get_mqtt_status_handler))
         .route(
@@ -906,6 +970,42 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/llm-backends/:id/test",
             post(llm_backends::test_backend_handler),
         )
+        .route(
+            "/api/llm-backends/routing-policies",
+            post(llm_backends::create_routing_policy_handler),
+        )
+        .route(
+            "/api/llm-backends/routing-policies/:id",
+            put(llm_backends::update_routing_policy_handler),
+        )
+        .route(
+            "/api/llm-backends/routing-policies/:id",
+            delete(llm_backends::delete_routing_policy_handler),
+        )
+        .route(
+            "/api/llm-backends/routing/chat-policy",
+            put(llm_backends::set_chat_policy_handler),
+        )
+        .route(
+            "/api/llm-backends/pricing",
+            put(llm_backends::update_pricing_handler),
+        )
+        .route(
+            "/api/llm-backends/budgets",
+            post(llm_backends::create_budget_handler),
+        )
+        .route(
+            "/api/llm-backends/budgets/:id",
+            put(llm_backends::update_budget_handler),
+        )
+        .route(
+            "/api/llm-backends/budgets/:id",
+            delete(llm_backends::delete_budget_handler),
+        )
+        .route(
+            "/api/llm-backends/cache",
+            delete(llm_backends::clear_response_cache_handler),
+        )
         // Apply rate limiting middleware to all protected routes
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
@@ -926,6 +1026,36 @@ pub fn create_router_with_state(state: ServerState) -> Router {
             "/api/users/:username",
             delete(auth_users::delete_user_handler),
         )
+        // System snapshots (admin only)
+        .route("/api/system/backups", get(backups::list_backups_handler))
+        .route("/api/system/backups", post(backups::create_backup_handler))
+        .route(
+            "/api/system/backups/:id",
+            delete(backups::delete_backup_handler),
+        )
+        .route(
+            "/api/system/backups/:id/verify",
+            get(backups::verify_backup_handler),
+        )
+        .route(
+            "/api/system/backups/:id/restore",
+            post(backups::restore_backup_handler),
+        )
+        // External MCP servers (admin only)
+        .route(
+            "/api/mcp/servers",
+            get(mcp::list_mcp_servers_handler).post(mcp::create_mcp_server_handler),
+        )
+        .route(
+            "/api/mcp/servers/:id",
+            get(mcp::get_mcp_server_handler)
+                .put(mcp::update_mcp_server_handler)
+                .delete(mcp::delete_mcp_server_handler),
+        )
+        .route(
+            "/api/mcp/servers/:id/reconnect",
+            post(mcp::reconnect_mcp_server_handler),
+        )
         // Apply JWT authentication middleware
         .route_layer(axum::middleware::from_fn_with_state(
             state.clone(),
@@ -5,12 +5,14 @@
 //! - DeviceService for unified device operations
 //! - TimeSeriesStorage for device metrics/telemetry
 //! - EmbeddedBroker (optional) for MQTT
+//! - MQTT bridges to external brokers
 //! - Device status broadcast channel
 
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::broadcast;
+use tokio::sync::{broadcast, RwLock};
 
-use neomind_devices::{DeviceRegistry, DeviceService, TimeSeriesStorage};
+use neomind_devices::{DeviceRegistry, DeviceService, MqttBridge, TimeSeriesStorage};
 
 #[cfg(feature = "embedded-broker")]
 use neomind_devices::EmbeddedBroker;
@@ -35,6 +37,9 @@ pub struct DeviceState {
 
     /// Device status update broadcast sender.
     pub update_tx: broadcast::Sender<DeviceStatusUpdate>,
+
+    /// Running MQTT bridges, keyed by external broker ID.
+    pub bridges: Arc<RwLock<HashMap<String, Arc<MqttBridge>>>>,
 }
 
 impl DeviceState {
@@ -51,6 +56,7 @@ impl DeviceState {
             service,
             telemetry,
             update_tx,
+            bridges: Arc::new(RwLock::new(HashMap::new())),
             #[cfg(feature = "embedded-broker")]
             embedded_broker: None,
         }
@@ -197,7 +197,7 @@ impl ExtensionState {
     /// Create extension state with persistent storage.
     pub async fn with_persistence(storage_path: &str) -> Result<Self, String> {
         // Ensure data directory exists
-        if let Err(e) = std::fs::create_dir_all("data") {
+        if let Err(e) = std::fs::create_dir_all(neomind_storage::data_dir()) {
             return Err(format!("Failed to create data directory: {}", e));
         }
 
@@ -245,7 +245,7 @@ impl ExtensionState {
     /// Extensions are loaded via UnifiedExtensionService with process isolation by default.
     pub async fn load_from_storage(&self) -> Result<usize, String> {
         // Open extension store
-        let store = ExtensionStore::open("data/extensions.redb")
+        let store = ExtensionStore::open(neomind_storage::data_path("extensions.redb"))
             .map_err(|e| format!("Failed to open extension store: {}", e))?;
 
         // Load all auto-start extensions
@@ -330,7 +330,9 @@ impl ExtensionState {
                         "Failed to load extension from storage"
                     );
                     // Record the error in the extension store
-                    if let Ok(store) = ExtensionStore::open("data/extensions.redb") {
+                    if let Ok(store) =
+                        ExtensionStore::open(neomind_storage::data_path("extensions.redb"))
+                    {
                         if let Err(update_e) = store.update_error_status(&record.id, &e.to_string()) {
                             tracing::warn!(
                                 extension_id = %record.id,
@@ -383,7 +385,7 @@ impl ExtensionState {
         }
 
         // Open the store for checking uninstalled status and saving records
-        let store = ExtensionStore::open("data/extensions.redb")
+        let store = ExtensionStore::open(neomind_storage::data_path("extensions.redb"))
             .map_err(|e| format!("Failed to open extension store: {}", e))?;
 
         let mut registered_count = 0;
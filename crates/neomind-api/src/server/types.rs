@@ -215,7 +215,8 @@ impl ServerState {
         let value_provider = Arc::new(UnifiedValueProvider::new().with_ttl(5000));
 
         // Ensure data directory exists
-        if let Err(e) = std::fs::create_dir_all("data") {
+        let data_dir = neomind_storage::data_dir();
+        if let Err(e) = std::fs::create_dir_all(&data_dir) {
             tracing::warn!(category = "storage", error = %e, "Failed to create data directory");
         }
 
@@ -229,9 +230,12 @@ impl ServerState {
         let command_manager = Some(Arc::new(CommandManager::new(command_queue, command_state)));
 
         // Create message manager with persistent storage
-        let message_manager = match MessageManager::with_storage("data") {
+        let message_manager = match MessageManager::with_storage(&data_dir) {
             Ok(manager) => {
-                tracing::info!("Message store initialized at data/messages.redb");
+                tracing::info!(
+                    "Message store initialized at {:?}",
+                    data_dir.join("messages.redb")
+                );
                 Arc::new(manager)
             }
             Err(e) => {
@@ -245,10 +249,12 @@ impl ServerState {
 
         // ========== Build DEVICE STATE ==========
         // Create device registry with persistent storage
-        let device_registry = match DeviceRegistry::with_persistence("data/devices.redb").await {
+        let devices_path = data_dir.join("devices.redb");
+        let device_registry = match DeviceRegistry::with_persistence(&devices_path).await {
             Ok(registry) => {
                 tracing::info!(
-                    "Device registry initialized with persistent storage at data/devices.redb"
+                    "Device registry initialized with persistent storage at {:?}",
+                    devices_path
                 );
                 Arc::new(registry)
             }
@@ -258,9 +264,13 @@ impl ServerState {
             }
         };
 
-        // Create time series storage
-        let telemetry_path = std::path::Path::new("data").join("telemetry.redb");
-        let time_series_storage = Arc::new(match TimeSeriesStorage::open(&telemetry_path) {
+        // Create time series storage; MQTT bursts are group-committed
+        let telemetry_path = data_dir.join("telemetry.redb");
+        let opened = TimeSeriesStorage::open_with_group_commit(
+            &telemetry_path,
+            neomind_storage::GroupCommitConfig::default(),
+        );
+        let time_series_storage = Arc::new(match opened {
             Ok(storage) => {
                 tracing::info!("Time series storage initialized at {:?}", telemetry_path);
                 storage
@@ -291,6 +301,11 @@ impl ServerState {
         let device_update_tx: tokio::sync::broadcast::Sender<super::state::DeviceStatusUpdate> =
             tokio::sync::broadcast::channel(100).0;
 
+        // Let rule conditions resolve device groups/tags/locations
+        value_provider
+            .set_device_registry(device_registry.clone())
+            .await;
+
         let devices = DeviceState::new(
             device_registry,
             device_service,
@@ -300,15 +315,10 @@ impl ServerState {
 
         // ========== Build EXTENSION STATE ==========
         // Create extension registry with default directories
-        // Use NEOMIND_DATA_DIR if set, otherwise use relative path
-        // NOTE: Only use data/extensions for consistent behavior
+        // NOTE: Only use <data dir>/extensions for consistent behavior
         // Extensions should be installed via frontend upload (.nep packages)
         // Development builds should also output to data/extensions/
-        let extensions_dir = if let Ok(data_dir) = std::env::var("NEOMIND_DATA_DIR") {
-            std::path::PathBuf::from(data_dir).join("extensions")
-        } else {
-            std::path::PathBuf::from("data/extensions")
-        };
+        let extensions_dir = data_dir.join("extensions");
 
         let default_ext_dirs = vec![extensions_dir];
 
@@ -392,10 +402,31 @@ impl ServerState {
             core.message_manager.set_event_bus(bus.clone()).await;
         }
 
+        // Wire event bus to LLM routing for failover events
+        if let Some(ref bus) = event_bus {
+            match neomind_llm::get_instance_manager() {
+                Ok(manager) => manager.set_event_bus(bus.clone()),
+                Err(e) => tracing::warn!(category = "llm", error = %e, "Failed to wire LLM routing events"),
+            }
+        }
+
+        // Record LLM usage and enforce budgets for every backend call
+        if let Err(e) = neomind_llm::get_usage_tracker() {
+            tracing::warn!(category = "llm", error = %e, "Failed to open LLM usage store");
+        }
+
+        // Drop cached LLM responses when the devices they depend on change
+        if let Some(ref bus) = event_bus {
+            neomind_llm::get_response_cache().watch_devices(bus);
+        }
+
         // Create rule store
-        let rule_store = match RuleStore::open("data/rules.redb") {
+        let rule_store = match RuleStore::open(data_dir.join("rules.redb")) {
             Ok(store) => {
-                tracing::info!("Rule store initialized at data/rules.redb");
+                tracing::info!(
+                    "Rule store initialized at {:?}",
+                    data_dir.join("rules.redb")
+                );
                 Some(store)
             }
             Err(e) => {
@@ -429,9 +460,10 @@ impl ServerState {
         }
 
         // Create automation store
-        let automation_store = match SharedAutomationStore::open("data/automations.redb").await {
+        let automations_path = data_dir.join("automations.redb");
+        let automation_store = match SharedAutomationStore::open(&automations_path).await {
             Ok(store) => {
-                tracing::info!("Automation store initialized at data/automations.redb");
+                tracing::info!("Automation store initialized at {:?}", automations_path);
                 Some(Arc::new(store))
             }
             Err(e) => {
@@ -456,11 +488,12 @@ impl ServerState {
         tracing::info!("Transform engine initialized with extension registry");
 
         // Create rule history store
+        let rule_history_path = data_dir.join("rule_history.redb");
         let rule_history_store = match neomind_storage::business::RuleHistoryStore::open(
-            "data/rule_history.redb",
+            &rule_history_path,
         ) {
             Ok(store) => {
-                tracing::info!("Rule history store initialized at data/rule_history.redb");
+                tracing::info!("Rule history store initialized at {:?}", rule_history_path);
                 Some(Arc::new(store))
             }
             Err(e) => {
@@ -485,16 +518,39 @@ impl ServerState {
             SessionManager::memory()
         });
 
-        // Create tiered memory
+        // Create tiered memory, persisted in memory.redb
         let memory_config = crate::config::get_memory_config();
-        let memory = Arc::new(tokio::sync::RwLock::new(TieredMemory::with_config(
-            memory_config,
-        )));
+        let has_embedding_model = memory_config.embedding_config.is_some();
+        let memory_path = data_dir.join("memory.redb");
+        let memory = match neomind_memory::MemoryStore::open(&memory_path) {
+            Ok(store) => match TieredMemory::with_store(memory_config.clone(), store).await {
+                Ok(memory) => {
+                    tracing::info!("Memory store initialized at {:?}", memory_path);
+                    memory
+                }
+                Err(e) => {
+                    tracing::warn!(category = "storage", error = %e, "Failed to load memory store, using in-memory");
+                    TieredMemory::with_config(memory_config)
+                }
+            },
+            Err(e) => {
+                tracing::warn!(category = "storage", error = %e, "Failed to open memory store, using in-memory");
+                TieredMemory::with_config(memory_config)
+            }
+        };
+        // Similar prompts hit the LLM response cache only with a real embedding model
+        if has_embedding_model {
+            neomind_llm::get_response_cache().set_embedder(Some(Arc::new(MemoryPromptEmbedder(
+                memory.embedding_model(),
+            ))));
+        }
+        let memory = Arc::new(tokio::sync::RwLock::new(memory));
 
         // Create agent store
-        let agent_store = match neomind_storage::AgentStore::open("data/agents.redb") {
+        let agents_path = data_dir.join("agents.redb");
+        let agent_store = match neomind_storage::AgentStore::open(&agents_path) {
             Ok(store) => {
-                tracing::info!("AI Agent store initialized at data/agents.redb");
+                tracing::info!("AI Agent store initialized at {:?}", agents_path);
                 store
             }
             Err(e) => {
@@ -536,7 +592,7 @@ impl ServerState {
             tracing::info!("GPU information cached at startup: {} GPU(s) detected", lock.get().map(|g| g.len()).unwrap_or(0));
             lock
         };
-        let dashboard_store = match DashboardStore::open("data/dashboards.redb") {
+        let dashboard_store = match DashboardStore::open(data_dir.join("dashboards.redb")) {
             Ok(store) => store,
             Err(_e) => {
 
@@ -618,6 +674,11 @@ impl ServerState {
         let device_update_tx: tokio::sync::broadcast::Sender<super::state::DeviceStatusUpdate> =
             tokio::sync::broadcast::channel(100).0;
 
+        // Let rule conditions resolve device groups/tags/locations
+        value_provider
+            .set_device_registry(device_registry.clone())
+            .await;
+
         let devices = DeviceState::new(
             device_registry,
             device_service,
@@ -734,7 +795,7 @@ impl ServerState {
 
     /// Initialize device type storage.
     pub async fn init_device_storage(&self) {
-        if let Err(e) = tokio::fs::create_dir_all("data").await {
+        if let Err(e) = tokio::fs::create_dir_all(neomind_storage::data_dir()).await {
             tracing::error!(category = "storage", error = %e, "Failed to create data directory");
         }
 
@@ -775,10 +836,9 @@ impl ServerState {
         //
         // This ensures all extension data is in the app data directory, avoiding
         // path inconsistencies between development and production modes.
-        let data_dir = std::env::var("NEOMIND_DATA_DIR")
-            .unwrap_or_else(|_| "data".to_string());
-        let install_dir = std::path::PathBuf::from(data_dir.clone()).join("extensions");
-        let nep_cache_dir = std::path::PathBuf::from(data_dir).join("extensions").join("packages");
+        let data_dir = neomind_storage::data_dir();
+        let install_dir = data_dir.join("extensions");
+        let nep_cache_dir = data_dir.join("extensions").join("packages");
 
         tracing::info!(
             install_dir = %install_dir.display(),
@@ -820,6 +880,12 @@ impl ServerState {
     /// Falls back to LlmBackendInstanceManager if no config file is found.
     /// Only sets the default backend for NEW sessions.
     pub async fn init_llm(&self) {
+        // The chat routing policy takes precedence over either source
+        self.agents
+            .session_manager
+            .restore_chat_routing_policy()
+            .await;
+
         // First try to load from config file
         if let Some(backend) = crate::config::load_llm_config() {
             self.agents.session_manager.set_default_llm_backend(backend).await;
@@ -857,6 +923,7 @@ impl ServerState {
 
         // Start device service to listen for EventBus events
         self.devices.service.start().await;
+        self.devices.service.start_shadow_reconciler();
 
         #[cfg(feature = "embedded-broker")]
         {
@@ -895,7 +962,13 @@ impl ServerState {
             discovery_topic: Some("device/+/+/uplink".to_string()),
             discovery_prefix: "device".to_string(),
             auto_discovery: true,
-            storage_dir: Some("data".to_string()),
+            storage_dir: Some(neomind_storage::data_dir().to_string_lossy().into_owned()),
+            sparkplug: crate::config::get_sparkplug_config(),
+            store_forward: neomind_devices::StoreForwardConfig {
+                enabled: true,
+                ..Default::default()
+            },
+            forward_telemetry_prefix: None,
         };
 
         // Create the MQTT adapter
@@ -995,6 +1068,7 @@ impl ServerState {
         let context = ExternalBrokerContext {
             device_service: self.devices.service.clone(),
             event_bus: event_bus.clone(),
+            bridges: self.devices.bridges.clone(),
         };
 
         for broker in brokers {
@@ -1032,13 +1106,57 @@ impl ServerState {
         }
     }
 
+    /// Connect to the external MCP servers configured in settings.
+    ///
+    /// Connections are made in the background so slow servers do not delay
+    /// startup; the tool registry is rebuilt once they are discovered.
+    pub async fn init_mcp_clients(&self) {
+        let store = match crate::config::open_settings_store() {
+            Ok(s) => s,
+            Err(e) => {
+                tracing::warn!("Failed to open settings store for MCP servers: {}", e);
+                return;
+            }
+        };
+
+        let servers = match store.load_all_mcp_servers() {
+            Ok(servers) => servers,
+            Err(e) => {
+                tracing::warn!("Failed to load MCP servers: {}", e);
+                return;
+            }
+        };
+
+        if servers.is_empty() {
+            return;
+        }
+
+        tracing::info!(
+            category = "mcp",
+            "Connecting to {} MCP server(s)",
+            servers.len()
+        );
+        let state = self.clone();
+        tokio::spawn(async move {
+            neomind_tools::mcp::get_mcp_client_manager()
+                .start(servers)
+                .await;
+            state.init_tools().await;
+        });
+    }
+
     /// Initialize tool registry with real service connections.
     pub async fn init_tools(&self) {
         use neomind_tools::ToolRegistryBuilder;
         use std::sync::Arc;
 
+        let (long_term, embedding) = {
+            let memory = self.agents.memory.read().await;
+            (memory.long_term_ref().clone(), memory.embedding_model())
+        };
+
         // Build tool registry with real implementations that connect to actual services
-        let builder = ToolRegistryBuilder::new()
+        let mut builder = ToolRegistryBuilder::new()
             // Real implementations
             .with_query_data_tool(
                 self.devices.telemetry.clone(),
@@ -1053,9 +1171,16 @@ impl ServerState {
             .with_delete_rule_tool(self.automation.rule_engine.clone())
             // AI Agent tools for Chat integration
             .with_agent_tools(self.agents.agent_store.clone())
+            // Knowledge base search with source citations
+            .with_knowledge_tools(long_term, embedding)
             // System help tool for onboarding
             .with_system_help_tool_named("NeoMind");
 
+        // Tools imported from external MCP servers
+        for tool in neomind_tools::mcp::get_mcp_client_manager().tools() {
+            builder = builder.with_tool(tool);
+        }
+
         let tool_registry = Arc::new(builder.build());
         self.agents
             .session_manager
@@ -1593,7 +1718,10 @@ impl ServerState {
         {
             use neomind_agent::LlmBackend;
             use neomind_core::llm::backend::LlmRuntime;
-            use neomind_llm::{CloudConfig, CloudRuntime, OllamaConfig, OllamaRuntime};
+            use neomind_llm::{
+                AnthropicConfig, AnthropicRuntime, CloudConfig, CloudRuntime, OllamaConfig,
+                OllamaRuntime,
+            };
 
             match backend {
                 LlmBackend::Ollama { endpoint, model , capabilities: _} => {
@@ -1636,16 +1764,34 @@ impl ServerState {
                         }
                     }
                 }
-                // Other cloud backends (Anthropic, Google, XAi, Qwen, DeepSeek, GLM, MiniMax)
+                LlmBackend::Anthropic {
+                    api_key,
+                    endpoint,
+                    model,
+                    capabilities: _,
+                } => {
+                    // Native Messages API runtime
+                    let timeout = std::env::var("ANTHROPIC_TIMEOUT_SECS")
+                        .ok()
+                        .and_then(|s| s.parse().ok())
+                        .unwrap_or(60);
+                    match AnthropicRuntime::new(
+                        AnthropicConfig::new(&api_key)
+                            .with_model(&model)
+                            .with_timeout_secs(timeout)
+                            .with_base_url_opt(Some(endpoint)),
+                    ) {
+                        Ok(runtime) => Some(Arc::new(runtime) as Arc<dyn LlmRuntime + Send + Sync>),
+                        Err(e) => {
+                            tracing::warn!(category = "ai", error = %e, "Failed to create Anthropic runtime for agents");
+                            None
+                        }
+                    }
+                }
+                // Other cloud backends (Google, XAi, Qwen, DeepSeek, GLM, MiniMax)
                 _backend => {
                     let (api_key, endpoint, model) = match &_backend {
-                        LlmBackend::Anthropic {
-                            api_key,
-                            endpoint,
-                            model,
-                            capabilities: _,
-                        }
-                        | LlmBackend::Google {
+                        LlmBackend::Google {
                             api_key,
                             endpoint,
                             model,
@@ -1681,7 +1827,7 @@ impl ServerState {
                             model,
                             capabilities: _,
                         } => (api_key.clone(), endpoint.clone(), model.clone()),
-                        // This is unreachable since we've excluded Ollama and OpenAi above
+                        // This is unreachable since we've excluded Ollama, OpenAi and Anthropic above
                         _ => unreachable!("Unexpected LLM backend type"),
                     };
                     let timeout = std::env::var("OPENAI_TIMEOUT_SECS")
@@ -1710,7 +1856,9 @@ impl ServerState {
         let has_time_series = time_series_store.is_some();
 
         // Open LLM backend store for per-agent backend lookup
-        let llm_backend_store = match LlmBackendStore::open("data/llm_backends.redb") {
+        let llm_backend_store = match LlmBackendStore::open(neomind_storage::data_path(
+            "llm_backends.redb",
+        )) {
             Ok(store) => Some(store),
             Err(e) => {
                 tracing::warn!(category = "storage", error = %e, "Failed to open LlmBackendStore");
@@ -1747,6 +1895,9 @@ impl ServerState {
     /// Start the AI Agent manager scheduler.
     pub async fn start_agent_manager(&self) -> Result<(), crate::models::ErrorResponse> {
         let manager = self.get_or_init_agent_manager().await?;
+        manager
+            .set_interrupted_execution_policy(crate::config::get_interrupted_execution_policy())
+            .await;
         manager.start().await.map_err(|e| {
             crate::models::ErrorResponse::internal(format!("Failed to start agent manager: {}", e))
         })?;
@@ -1888,5 +2039,18 @@ impl ServerState {
     }
 }
 
+/// Embeds prompts for the LLM response cache with the memory embedding model.
+struct MemoryPromptEmbedder(Arc<dyn neomind_memory::EmbeddingModel>);
+
+#[async_trait::async_trait]
+impl neomind_llm::PromptEmbedder for MemoryPromptEmbedder {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, neomind_core::LlmError> {
+        self.0
+            .embed(text)
+            .await
+            .map_err(|e| neomind_core::LlmError::Generation(e.to_string()))
+    }
+}
+
 // Note: Default implementation removed because ServerState::new() is now async
 // to support persistent device registry initialization.
@@ -66,8 +66,17 @@ pub async fn cleanup_resources(state: &ServerState) {
     // 3. Flush any pending database writes
     tracing::info!("Flushing storage...");
 
-    // Note: TimeSeriesStorage doesn't have explicit flush/close
-    // The redb database handles this via Drop
+    // Telemetry is group-committed; commit what is still buffered.
+    // Other redb databases commit per write and are closed via Drop.
+    match state.devices.telemetry.flush().await {
+        Ok(count) => tracing::info!("Committed {} buffered telemetry points", count),
+        Err(e) => tracing::warn!("Telemetry flush error: {}", e),
+    }
+
+    // Knowledge access counts are batched as well
+    if let Err(e) = state.agents.memory.read().await.flush().await {
+        tracing::warn!("Memory flush error: {}", e);
+    }
 
     // 4. Log session counts
     let sessions = state.agents.session_manager.list_sessions().await;
@@ -1,5 +1,6 @@
 //! Unified startup logging and console output formatting.
 
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::OnceLock;
 
 /// ANSI color codes for terminal output.
@@ -13,6 +14,25 @@ const ANSI_RED: &str = "\x1b[31m";
 const ANSI_CYAN: &str = "\x1b[36m";
 const ANSI_GRAY: &str = "\x1b[90m";
 
+/// Set while stdout carries a protocol (MCP over stdio).
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+/// Send console output to stderr from now on, keeping stdout free for a protocol.
+pub fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
+/// `println!` unless stdout is reserved, `eprintln!` otherwise.
+macro_rules! console {
+    ($($arg:tt)*) => {
+        if STDOUT_RESERVED.load(Ordering::Relaxed) {
+            eprintln!($($arg)*)
+        } else {
+            println!($($arg)*)
+        }
+    };
+}
+
 /// Whether colors are enabled (disabled in CI/logs, can be forced via env var).
 fn colors_enabled() -> bool {
     static ENABLED: OnceLock<bool> = OnceLock::new();
@@ -82,28 +102,28 @@ impl StartupLogger {
         }
         self.phase = StartupPhase::Banner;
 
-        println!();
-        println!(
+        console!();
+        console!(
             "{}",
             color("┌─────────────────────────────────────────┐", ANSI_CYAN)
         );
-        println!(
+        console!(
             "{}{}{}",
             color("│ ", ANSI_CYAN),
             color("NeoMind Edge AI Agent", ANSI_BOLD),
             color("                       │", ANSI_CYAN)
         );
-        println!(
+        console!(
             "{}{}{}",
             color("│ ", ANSI_CYAN),
             color("Edge AI Agent - Web Server", ANSI_DIM),
             color("                    │", ANSI_CYAN)
         );
-        println!(
+        console!(
             "{}",
             color("└─────────────────────────────────────────┘", ANSI_CYAN)
         );
-        println!();
+        console!();
     }
 
     /// Transition to initialization phase.
@@ -112,7 +132,7 @@ impl StartupLogger {
             return;
         }
         if self.phase != StartupPhase::Initialization {
-            println!(
+            console!(
                 "{} {} {}",
                 color("›", ANSI_BOLD),
                 color("Initialization", ANSI_BLUE),
@@ -128,7 +148,7 @@ impl StartupLogger {
             return;
         }
         if self.phase != StartupPhase::Configuration {
-            println!(
+            console!(
                 "{} {} {}",
                 color("›", ANSI_BOLD),
                 color("Configuration", ANSI_BLUE),
@@ -144,7 +164,7 @@ impl StartupLogger {
             return;
         }
         if self.phase != StartupPhase::Services {
-            println!(
+            console!(
                 "{} {} {}",
                 color("›", ANSI_BOLD),
                 color("Services", ANSI_BLUE),
@@ -160,8 +180,8 @@ impl StartupLogger {
             return;
         }
         if self.phase != StartupPhase::Ready {
-            println!();
-            println!(
+            console!();
+            console!(
                 "{} {}",
                 color("✓", ANSI_GREEN),
                 color("Server ready", ANSI_BOLD)
@@ -175,7 +195,7 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!("  {} {}", color("●", ANSI_BLUE), message);
+        console!("  {} {}", color("●", ANSI_BLUE), message);
     }
 
     /// Log a success message with icon.
@@ -183,7 +203,7 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!("  {} {}", color("✓", ANSI_GREEN), message);
+        console!("  {} {}", color("✓", ANSI_GREEN), message);
     }
 
     /// Log a warning message with icon.
@@ -191,7 +211,7 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!("  {} {}", color("⚠", ANSI_YELLOW), message);
+        console!("  {} {}", color("⚠", ANSI_YELLOW), message);
     }
 
     /// Log an error message with icon.
@@ -199,7 +219,7 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!("  {} {}", color("✗", ANSI_RED), message);
+        console!("  {} {}", color("✗", ANSI_RED), message);
     }
 
     /// Log a detail message (indented, dim).
@@ -207,7 +227,7 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!("    {}", color(message, ANSI_GRAY));
+        console!("    {}", color(message, ANSI_GRAY));
     }
 
     /// Print API key banner.
@@ -215,47 +235,47 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!();
-        println!(
+        console!();
+        console!(
             "{}",
             color(
                 "  ╔═════════════════════════════════════════════════════╗",
                 ANSI_YELLOW
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_YELLOW),
             color("⚠ DEFAULT API KEY GENERATED", ANSI_BOLD),
             color("                               ║", ANSI_YELLOW)
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╠═════════════════════════════════════════════════════╣",
                 ANSI_YELLOW
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_YELLOW),
             color("Key:", ANSI_BOLD),
             color(format!(" {:44} ", key), ANSI_CYAN)
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_YELLOW),
             color("Name:", ANSI_BOLD),
             color(format!(" {:43} ", name), ANSI_DIM)
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╠═════════════════════════════════════════════════════╣",
                 ANSI_YELLOW
             )
         );
-        println!(
+        console!(
             "{} {}",
             color("  ║", ANSI_YELLOW),
             color(
@@ -263,7 +283,7 @@ impl StartupLogger {
                 ANSI_DIM
             )
         );
-        println!(
+        console!(
             "{} {}",
             color("  ║", ANSI_YELLOW),
             color(
@@ -271,14 +291,14 @@ impl StartupLogger {
                 ANSI_DIM
             )
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╚══════════════════════════════════════════════════════╝",
                 ANSI_YELLOW
             )
         );
-        println!();
+        console!();
     }
 
     /// Print default admin user banner.
@@ -286,47 +306,47 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!();
-        println!(
+        console!();
+        console!(
             "{}",
             color(
                 "  ╔═════════════════════════════════════════════════════╗",
                 ANSI_BLUE
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_BLUE),
             color("👤 DEFAULT ADMIN USER CREATED", ANSI_BOLD),
             color("                          ║", ANSI_BLUE)
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╠═════════════════════════════════════════════════════╣",
                 ANSI_BLUE
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_BLUE),
             color("Username:", ANSI_BOLD),
             color(format!(" {:39} ", username), ANSI_CYAN)
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_BLUE),
             color("Password:", ANSI_BOLD),
             color(format!(" {:39} ", password), ANSI_CYAN)
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╠═════════════════════════════════════════════════════╣",
                 ANSI_BLUE
             )
         );
-        println!(
+        console!(
             "{} {}",
             color("  ║", ANSI_BLUE),
             color(
@@ -334,14 +354,14 @@ impl StartupLogger {
                 ANSI_DIM
             )
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╚══════════════════════════════════════════════════════╝",
                 ANSI_BLUE
             )
         );
-        println!();
+        console!();
     }
 
     /// Print server ready info with URL.
@@ -349,34 +369,34 @@ impl StartupLogger {
         if self.quiet {
             return;
         }
-        println!();
-        println!(
+        console!();
+        console!(
             "{}",
             color(
                 "  ╔═════════════════════════════════════════════════════╗",
                 ANSI_GREEN
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_GREEN),
             color("✓ Server is running!", ANSI_BOLD),
             color("                                ║", ANSI_GREEN)
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╠═════════════════════════════════════════════════════╣",
                 ANSI_GREEN
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_GREEN),
             color("Local:", ANSI_BOLD),
             color(format!("  http://{}                     ", addr), ANSI_CYAN)
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_GREEN),
             color("API:", ANSI_BOLD),
@@ -385,26 +405,26 @@ impl StartupLogger {
                 ANSI_CYAN
             )
         );
-        println!(
+        console!(
             "{} {} {}",
             color("  ║", ANSI_GREEN),
             color("Docs:", ANSI_BOLD),
             color(format!("  http://{}/api-docs            ", addr), ANSI_CYAN)
         );
-        println!(
+        console!(
             "{}",
             color(
                 "  ╚══════════════════════════════════════════════════════╝",
                 ANSI_GREEN
             )
         );
-        println!();
-        println!(
+        console!();
+        console!(
             "{} {}",
             color("Press", ANSI_BOLD),
             color("Ctrl+C to stop.", ANSI_DIM)
         );
-        println!();
+        console!();
     }
 
     /// Log service startup.
@@ -418,7 +438,7 @@ impl StartupLogger {
             ServiceStatus::Error => ("✗", ANSI_RED),
             ServiceStatus::Disabled => ("○", ANSI_GRAY),
         };
-        println!("    {} {:30}", color(icon, color_code), name);
+        console!("    {} {:30}", color(icon, color_code), name);
     }
 }
 
@@ -2,7 +2,7 @@
 
 use neomind_api::handlers::devices::models::{
     AddDeviceRequest, BatchCurrentValuesRequest, PaginationQuery, TimeRangeQuery,
-    UpdateDeviceRequest,
+    UpdateDeviceRequest, UpdateShadowRequest,
 };
 use serde_json::json;
 use uuid::Uuid;
@@ -28,6 +28,9 @@ mod tests {
             limit: None,
             device_type: None,
             status: None,
+            group: None,
+            tag: None,
+            location: None,
         };
         assert_eq!(query.page, None);
         assert_eq!(query.limit, None);
@@ -42,6 +45,9 @@ mod tests {
             limit: Some(20),
             device_type: Some("sensor".to_string()),
             status: Some("connected".to_string()),
+            group: None,
+            tag: None,
+            location: None,
         };
         assert_eq!(query.page, Some(2));
         assert_eq!(query.limit, Some(20));
@@ -85,6 +91,9 @@ mod tests {
                 "address": "localhost:1883",
                 "topic": "test/topic",
             }),
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
 
         assert_eq!(request.device_id, Some(device_id));
@@ -100,6 +109,9 @@ mod tests {
             connection_config: Some(json!({"key": "value"})),
             adapter_type: None,
             adapter_id: None,
+            location: None,
+            tags: None,
+            groups: None,
         };
 
         assert_eq!(request.name, Some("Updated Device".to_string()));
@@ -191,6 +203,9 @@ mod tests {
             connection_config: json!({
                 "address": "localhost:1883",
             }),
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
 
         assert!(request.device_id.is_none());
@@ -206,6 +221,9 @@ mod tests {
             adapter_type: None,
             connection_config: None,
             adapter_id: None,
+            location: None,
+            tags: None,
+            groups: None,
         };
 
         assert!(request.name.is_none());
@@ -222,6 +240,9 @@ mod tests {
             adapter_type: None,
             connection_config: None,
             adapter_id: Some("adapter-123".to_string()),
+            location: None,
+            tags: None,
+            groups: None,
         };
 
         assert_eq!(request.name, Some("New Name".to_string()));
@@ -229,4 +250,27 @@ mod tests {
         assert!(request.connection_config.is_none());
         assert_eq!(request.adapter_id, Some("adapter-123".to_string()));
     }
+
+    #[tokio::test]
+    async fn test_update_shadow_request() {
+        let request: UpdateShadowRequest = serde_json::from_value(json!({
+            "desired": {"brightness": 70, "color": null}
+        }))
+        .unwrap();
+
+        assert_eq!(request.desired.get("brightness"), Some(&json!(70)));
+        assert!(request.desired.get("color").unwrap().is_null());
+        assert!(request.command.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_shadow_handler_unknown_device() {
+        use axum::extract::{Path, State};
+        use neomind_api::handlers::devices::get_device_shadow_handler;
+
+        let state = crate::common::create_test_server_state().await;
+        let result =
+            get_device_shadow_handler(State(state), Path("non-existent-device".to_string())).await;
+        assert!(result.is_err());
+    }
 }
@@ -124,6 +124,7 @@ mod tests {
         let result = chat_handler(
             State(state),
             Path("nonexistent_session".to_string()),
+            None,
             Json(req),
         )
         .await;
@@ -155,7 +156,8 @@ mod tests {
             images: None,
             backend_id: None,
         };
-        let result = chat_handler(State(state), Path(session_id.clone()), Json(req)).await;
+        let result =
+            chat_handler(State(state), Path(session_id.clone()), None, Json(req)).await;
         // Either Ok with timeout message or Err with something other than NOT_FOUND
         match result {
             Ok(resp) => {
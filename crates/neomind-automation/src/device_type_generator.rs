@@ -9,6 +9,7 @@ use crate::discovery::*;
 use crate::error::{AutomationError, Result};
 use neomind_core::llm::backend::LlmInput;
 use neomind_core::{GenerationParams, LlmRuntime, Message};
+use neomind_llm::{OutputSchema, StructuredGenerator};
 use serde_json::json;
 
 /// Device type generator for auto-generating MDL definitions
@@ -195,10 +196,21 @@ Respond with a JSON object:
             tools: None,
         };
 
-        let response = self.llm.generate(input).await?;
-        let json_str = extract_json_from_response(&response.text)?;
-        let result: serde_json::Value = serde_json::from_str(&json_str)
-            .map_err(|e| AutomationError::IntentAnalysisFailed(format!("Invalid JSON: {}", e)))?;
+        let schema = OutputSchema::new(
+            "device_category",
+            json!({
+                "type": "object",
+                "required": ["category", "confidence"],
+                "properties": {
+                    "category": { "enum": CATEGORY_NAMES },
+                    "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                    "reasoning": { "type": "string" }
+                }
+            }),
+        );
+        let result = StructuredGenerator::new(self.llm.clone())
+            .generate_value(input, &schema)
+            .await?;
 
         let category_str = result
             .get("category")
@@ -471,17 +483,26 @@ pub struct ValidationResult {
     pub warnings: Vec<String>,
 }
 
-fn extract_json_from_response(response: &str) -> Result<String> {
-    let start = response
-        .find('{')
-        .ok_or_else(|| AutomationError::IntentAnalysisFailed("No JSON object found".into()))?;
-
-    let end = response
-        .rfind('}')
-        .ok_or_else(|| AutomationError::IntentAnalysisFailed("Incomplete JSON object".into()))?;
-
-    Ok(response[start..=end].to_string())
-}
+/// Category names the LLM may answer with
+const CATEGORY_NAMES: [&str; 17] = [
+    "temperature_sensor",
+    "humidity_sensor",
+    "multi_sensor",
+    "motion_sensor",
+    "light_sensor",
+    "switch",
+    "dimmer",
+    "thermostat",
+    "camera",
+    "energy_monitor",
+    "gateway",
+    "controller",
+    "actuator",
+    "display",
+    "alarm",
+    "lock",
+    "unknown",
+];
 
 #[cfg(test)]
 mod tests {
@@ -13,6 +13,8 @@ use crate::discovery::{
     ValueRange, ValueStatistics,
 };
 use neomind_core::{llm::backend::LlmInput, GenerationParams, LlmRuntime, Message};
+use neomind_llm::{OutputSchema, StructuredGenerator};
+use serde_json::json;
 use std::collections::HashMap;
 use std::sync::Arc;
 use std::time::Duration;
@@ -741,24 +743,16 @@ impl SemanticInference {
             tools: None,
         };
 
-        match llm.generate(input).await {
-            Ok(output) => {
-                let response = output
-                    .text
-                    .trim()
-                    .trim_start_matches("```json")
-                    .trim_start_matches("```")
-                    .trim();
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(response) {
-                    return Some(Self::parse_llm_semantic_result(
-                        field_name,
-                        parsed,
-                        InferenceSource::AI,
-                    ));
-                }
-                tracing::warn!("Failed to parse LLM response as JSON: {}", response);
-                None
-            }
+        let schema = OutputSchema::new("field_semantic", field_semantic_schema());
+        match StructuredGenerator::new(llm.clone())
+            .generate_value(input, &schema)
+            .await
+        {
+            Ok(parsed) => Some(Self::parse_llm_semantic_result(
+                field_name,
+                parsed,
+                InferenceSource::AI,
+            )),
             Err(e) => {
                 tracing::warn!("LLM generation failed for field '{}': {}", field_name, e);
                 None
@@ -835,14 +829,18 @@ impl SemanticInference {
             tools: None,
         };
 
-        if let Ok(output) = llm.generate(input).await {
-            let response = output
-                .text
-                .trim()
-                .trim_start_matches("```json")
-                .trim_start_matches("```")
-                .trim();
-            if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(response) {
+        let schema = OutputSchema::new(
+            "field_semantics",
+            json!({
+                "type": "object",
+                "additionalProperties": field_semantic_schema()
+            }),
+        );
+        match StructuredGenerator::new(llm.clone())
+            .generate_value(input, &schema)
+            .await
+        {
+            Ok(parsed) => {
                 if let Some(obj) = parsed.as_object() {
                     for (field_name, semantic_data) in obj {
                         let semantic = Self::parse_llm_semantic_result(
@@ -854,6 +852,7 @@ impl SemanticInference {
                     }
                 }
             }
+            Err(e) => tracing::warn!("LLM batch field inference failed: {}", e),
         }
 
         results
@@ -949,49 +948,63 @@ impl SemanticInference {
             tools: None,
         };
 
+        let nullable_string = json!({ "type": ["string", "null"] });
+        let schema = OutputSchema::new(
+            "metric_enhancements",
+            json!({
+                "type": "object",
+                "required": ["metrics"],
+                "properties": {
+                    "metrics": {
+                        "type": "object",
+                        "additionalProperties": {
+                            "type": "object",
+                            "required": ["display_name"],
+                            "properties": {
+                                "display_name": { "type": "string" },
+                                "description": nullable_string,
+                                "unit": nullable_string
+                            }
+                        }
+                    }
+                }
+            }),
+        );
+
         match tokio::time::timeout(
             Duration::from_secs(45), // Longer timeout for enhancement
-            llm.generate(input),
+            StructuredGenerator::new(llm.clone()).generate_value(input, &schema),
         )
         .await
         {
-            Ok(Ok(output)) => {
-                let response = output
-                    .text
-                    .trim()
-                    .trim_start_matches("```json")
-                    .trim_start_matches("```")
-                    .trim();
-                if let Ok(parsed) = serde_json::from_str::<serde_json::Value>(response) {
-                    if let Some(obj) = parsed.get("metrics").and_then(|v| v.as_object()) {
-                        tracing::info!("LLM enhancement completed for {} metrics", obj.len());
-                        let mut results = Vec::new();
-                        for (metric_name, enhancement_data) in obj {
-                            results.push((
-                                metric_name.clone(),
-                                MetricEnhancement {
-                                    display_name: enhancement_data
-                                        .get("display_name")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or(metric_name)
-                                        .to_string(),
-                                    description: enhancement_data
-                                        .get("description")
-                                        .and_then(|v| v.as_str())
-                                        .unwrap_or("")
-                                        .to_string(),
-                                    unit: enhancement_data
-                                        .get("unit")
-                                        .and_then(|v| if v.is_null() { None } else { v.as_str() })
-                                        .map(|s| s.to_string()),
-                                },
-                            ));
-                        }
-                        return results;
-                    }
+            Ok(Ok(parsed)) => {
+                let Some(obj) = parsed.get("metrics").and_then(|v| v.as_object()) else {
+                    return vec![];
+                };
+                tracing::info!("LLM enhancement completed for {} metrics", obj.len());
+                let mut results = Vec::new();
+                for (metric_name, enhancement_data) in obj {
+                    results.push((
+                        metric_name.clone(),
+                        MetricEnhancement {
+                            display_name: enhancement_data
+                                .get("display_name")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or(metric_name)
+                                .to_string(),
+                            description: enhancement_data
+                                .get("description")
+                                .and_then(|v| v.as_str())
+                                .unwrap_or("")
+                                .to_string(),
+                            unit: enhancement_data
+                                .get("unit")
+                                .and_then(|v| if v.is_null() { None } else { v.as_str() })
+                                .map(|s| s.to_string()),
+                        },
+                    ));
                 }
-                tracing::warn!("Failed to parse LLM enhancement response");
-                vec![]
+                results
             }
             Ok(Err(e)) => {
                 tracing::warn!("LLM enhancement generation failed: {}", e);
@@ -1351,6 +1364,22 @@ impl SemanticInference {
     }
 }
 
+/// Schema for one field's LLM semantic analysis
+fn field_semantic_schema() -> serde_json::Value {
+    json!({
+        "type": "object",
+        "required": ["semantic_type", "standard_name", "confidence"],
+        "properties": {
+            "semantic_type": { "type": "string" },
+            "standard_name": { "type": "string" },
+            "display_name": { "type": "string" },
+            "unit": { "type": ["string", "null"] },
+            "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+            "reasoning": { "type": "string" }
+        }
+    })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -111,6 +111,17 @@ impl From<neomind_core::LlmError> for AutomationError {
     }
 }
 
+impl From<neomind_llm::StructuredError> for AutomationError {
+    fn from(err: neomind_llm::StructuredError) -> Self {
+        match err {
+            neomind_llm::StructuredError::Llm(e) => e.into(),
+            e @ neomind_llm::StructuredError::Invalid { .. } => {
+                AutomationError::IntentAnalysisFailed(e.to_string())
+            }
+        }
+    }
+}
+
 impl From<neomind_rules::RuleError> for AutomationError {
     fn from(err: neomind_rules::RuleError) -> Self {
         AutomationError::InvalidDefinition(format!("Rule error: {}", err))
@@ -5,9 +5,10 @@
 
 use std::sync::Arc;
 
-use crate::error::{AutomationError, Result};
+use crate::error::Result;
 use neomind_core::llm::backend::LlmInput;
 use neomind_core::{GenerationParams, LlmRuntime, Message};
+use neomind_llm::{OutputSchema, StructuredGenerator};
 use serde_json::json;
 
 /// Language for prompts
@@ -104,12 +105,9 @@ impl Nl2Automation {
             tools: None,
         };
 
-        let response = self.llm.generate(input).await?;
-
-        // Parse the JSON response
-        let json_str = extract_json_from_response(&response.text)?;
-        let entities: serde_json::Value = serde_json::from_str(&json_str)
-            .map_err(|e| AutomationError::IntentAnalysisFailed(format!("Invalid JSON: {}", e)))?;
+        let entities = StructuredGenerator::new(self.llm.clone())
+            .generate_value(input, &entities_schema())
+            .await?;
 
         Ok(self.parse_entities(entities))
     }
@@ -377,17 +375,71 @@ pub struct TimeConstraints {
     pub days: Vec<String>,
 }
 
-/// Extract JSON from an LLM response
-fn extract_json_from_response(response: &str) -> Result<String> {
-    let start = response
-        .find('{')
-        .ok_or_else(|| AutomationError::IntentAnalysisFailed("No JSON object found".into()))?;
-
-    let end = response
-        .rfind('}')
-        .ok_or_else(|| AutomationError::IntentAnalysisFailed("Incomplete JSON object".into()))?;
-
-    Ok(response[start..=end].to_string())
+/// Schema for the entity extraction response
+fn entities_schema() -> OutputSchema {
+    let nullable_string = json!({ "type": ["string", "null"] });
+    OutputSchema::new(
+        "automation_entities",
+        json!({
+            "type": "object",
+            "required": ["triggers", "conditions", "actions", "devices"],
+            "properties": {
+                "triggers": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["type", "description"],
+                        "properties": {
+                            "type": { "enum": ["device_state", "schedule", "manual"] },
+                            "device_id": nullable_string,
+                            "metric": nullable_string,
+                            "condition": nullable_string,
+                            "cron": nullable_string,
+                            "description": { "type": "string" }
+                        }
+                    }
+                },
+                "conditions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["device_id", "metric", "operator"],
+                        "properties": {
+                            "device_id": { "type": "string" },
+                            "metric": { "type": "string" },
+                            "operator": { "enum": ["gt", "lt", "eq", "ne", "gte", "lte"] },
+                            "threshold": { "type": "number" },
+                            "description": { "type": "string" }
+                        }
+                    }
+                },
+                "actions": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "required": ["type", "description"],
+                        "properties": {
+                            "type": {
+                                "enum": ["notify", "execute_command", "set_value", "create_alert"]
+                            },
+                            "target": nullable_string,
+                            "parameters": { "type": "object" },
+                            "description": { "type": "string" }
+                        }
+                    }
+                },
+                "devices": { "type": "array", "items": { "type": "string" } },
+                "time_constraints": {
+                    "type": ["object", "null"],
+                    "properties": {
+                        "start_time": nullable_string,
+                        "end_time": nullable_string,
+                        "days": { "type": ["array", "null"], "items": { "type": "string" } }
+                    }
+                }
+            }
+        }),
+    )
 }
 
 // ============================================================================
@@ -9,6 +9,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 
 use crate::error::{AutomationError, Result};
 use crate::types::*;
+use neomind_storage::snapshot::begin_write;
 
 // Table definitions
 const AUTOMATIONS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("automations");
@@ -32,7 +33,7 @@ impl AutomationStore {
         let db = Database::create(path.as_ref())?;
 
         // Write transaction to create tables
-        let write_txn = db.begin_write()?;
+        let write_txn = begin_write(&db)?;
         {
             write_txn.open_table(AUTOMATIONS_TABLE)?;
             write_txn.open_table(EXECUTIONS_TABLE)?;
@@ -51,7 +52,7 @@ impl AutomationStore {
         let db = Database::create(&temp_path)?;
 
         // Write transaction to create tables
-        let write_txn = db.begin_write()?;
+        let write_txn = begin_write(&db)?;
         {
             write_txn.open_table(AUTOMATIONS_TABLE)?;
             write_txn.open_table(EXECUTIONS_TABLE)?;
@@ -67,7 +68,7 @@ impl AutomationStore {
         let key = automation.id();
         let value = serde_json::to_vec(automation)?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AUTOMATIONS_TABLE)?;
             table.insert(key, value.as_slice())?;
@@ -108,7 +109,7 @@ impl AutomationStore {
 
     /// Delete an automation
     pub fn delete_automation(&self, id: &str) -> Result<bool> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(AUTOMATIONS_TABLE)?;
             let result = table.remove(id)?.is_some();
@@ -123,7 +124,7 @@ impl AutomationStore {
         let key = format!("{}:{}", execution.automation_id, execution.id);
         let value = serde_json::to_vec(execution)?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(EXECUTIONS_TABLE)?;
             table.insert(key.as_str(), value.as_slice())?;
@@ -168,7 +169,7 @@ impl AutomationStore {
         let key = format!("{}:{}", template.automation_type.as_str(), template.id);
         let value = serde_json::to_vec(template)?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(TEMPLATES_TABLE)?;
             table.insert(key.as_str(), value.as_slice())?;
@@ -217,7 +218,7 @@ impl AutomationStore {
     pub fn delete_template(&self, id: &str, automation_type: AutomationType) -> Result<bool> {
         let key = format!("{}:{}", automation_type.as_str(), id);
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(TEMPLATES_TABLE)?;
             let result = table.remove(key.as_str())?.is_some();
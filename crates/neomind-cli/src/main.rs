@@ -9,6 +9,7 @@ use neomind_agent::{LlmBackend, SessionManager};
 use neomind_core::config::{
     endpoints, env_vars, models, normalize_ollama_endpoint, normalize_openai_endpoint,
 };
+use tracing_subscriber::fmt::writer::BoxMakeWriter;
 
 /// NeoMind AI Agent - Run LLMs on edge devices.
 #[derive(Parser, Debug)]
@@ -85,6 +86,73 @@ enum Command {
         #[command(subcommand)]
         extension_cmd: ExtensionCommand,
     },
+    /// Convert a telemetry database to the compressed chunk layout.
+    /// Stop the server first; the database cannot be opened twice.
+    MigrateTimeseries {
+        /// Path to the time series database.
+        #[arg(long, default_value = "data/telemetry.redb")]
+        path: std::path::PathBuf,
+        /// Chunk size in seconds.
+        #[arg(long, default_value_t = 3600)]
+        chunk_secs: i64,
+    },
+    /// Whole-system snapshots of every store.
+    /// Stop the server before restoring.
+    Snapshot {
+        #[command(subcommand)]
+        snapshot_cmd: SnapshotCommand,
+    },
+    /// Serve the Model Context Protocol over stdio.
+    /// Uses the local data directory; stop the server first, or use /api/mcp instead.
+    Mcp {
+        /// Only expose tools that do not change system state.
+        #[arg(long)]
+        read_only: bool,
+    },
+}
+
+/// Snapshot subcommands.
+#[derive(Subcommand, Debug)]
+enum SnapshotCommand {
+    /// Capture the data directory into a snapshot archive.
+    Create {
+        /// Data directory.
+        #[arg(long, default_value = "data")]
+        data_dir: std::path::PathBuf,
+        /// Backup directory.
+        #[arg(long, default_value = "backups")]
+        backup_dir: std::path::PathBuf,
+        /// Description stored with the snapshot.
+        #[arg(short, long)]
+        description: Option<String>,
+        /// Open every store in the archive after writing it.
+        #[arg(long)]
+        verify: bool,
+    },
+    /// List snapshots.
+    List {
+        /// Backup directory.
+        #[arg(long, default_value = "backups")]
+        backup_dir: std::path::PathBuf,
+    },
+    /// Check checksums and open every store in a snapshot archive.
+    Verify {
+        /// Path to the snapshot archive.
+        #[arg(required = true)]
+        archive: std::path::PathBuf,
+    },
+    /// Restore a snapshot archive into the data directory.
+    Restore {
+        /// Path to the snapshot archive.
+        #[arg(required = true)]
+        archive: std::path::PathBuf,
+        /// Data directory.
+        #[arg(long, default_value = "data")]
+        data_dir: std::path::PathBuf,
+        /// Only verify the archive; do not restore.
+        #[arg(long)]
+        verify: bool,
+    },
 }
 
 /// Extension subcommands.
@@ -168,16 +236,25 @@ async fn main() -> Result<()> {
             .add_directive(tracing::Level::WARN.into())
     });
 
+    // MCP over stdio owns stdout for protocol messages
+    let log_writer = if matches!(args.command, Command::Mcp { .. }) {
+        BoxMakeWriter::new(std::io::stderr)
+    } else {
+        BoxMakeWriter::new(std::io::stdout)
+    };
+
     if json_logging {
         // JSON format for production/container environments
         tracing_subscriber::fmt()
             .json()
+            .with_writer(log_writer)
             .with_env_filter(env_filter)
             .with_target(true)
             .init();
     } else {
         // Human-readable format for development - clean and compact
         tracing_subscriber::fmt()
+            .with_writer(log_writer)
             .with_env_filter(env_filter)
             .with_target(false)
             .with_thread_ids(false)
@@ -201,6 +278,11 @@ async fn main() -> Result<()> {
         Command::Health => run_health().await,
         Command::Logs { tail, follow, level, since } => run_logs(tail, follow, level, since).await,
         Command::Extension { extension_cmd } => run_extension_cmd(extension_cmd).await,
+        Command::MigrateTimeseries { path, chunk_secs } => {
+            run_migrate_timeseries(path, chunk_secs).await
+        }
+        Command::Snapshot { snapshot_cmd } => run_snapshot_cmd(snapshot_cmd),
+        Command::Mcp { read_only } => run_mcp(read_only).await,
     }
 }
 
@@ -493,6 +575,130 @@ async fn run_server(host: String, port: u16) -> Result<()> {
     neomind_api::run(addr).await
 }
 
+/// Serve MCP over stdin/stdout.
+async fn run_mcp(read_only: bool) -> Result<()> {
+    let access = if read_only {
+        neomind_api::mcp::McpAccess::ReadOnly
+    } else {
+        neomind_api::mcp::McpAccess::ReadWrite
+    };
+    neomind_api::mcp::serve_stdio(access).await
+}
+
+/// Convert a time series database to the chunked layout.
+async fn run_migrate_timeseries(path: std::path::PathBuf, chunk_secs: i64) -> Result<()> {
+    if !path.exists() {
+        anyhow::bail!("Time series database not found: {}", path.display());
+    }
+
+    println!("Migrating {} to {}s chunks...", path.display(), chunk_secs);
+    let store = neomind_storage::TimeSeriesStore::open(&path)
+        .map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?;
+    let report = store
+        .migrate_to_chunked(chunk_secs)
+        .await
+        .map_err(|e| anyhow::anyhow!("Migration failed: {}", e))?;
+
+    println!("Series converted:  {}", report.series);
+    println!("Points migrated:   {}", report.points_migrated);
+    println!(
+        "Chunked data:      {} bytes (was {} bytes as rows)",
+        report.chunk_stored_bytes, report.chunk_raw_bytes
+    );
+    if report.chunk_stored_bytes > 0 {
+        println!(
+            "Compression ratio: {:.1}x",
+            report.chunk_raw_bytes as f64 / report.chunk_stored_bytes as f64
+        );
+    }
+    println!("Note: redb reuses freed pages; the file itself does not shrink until compacted.");
+    Ok(())
+}
+
+/// Run snapshot commands.
+fn run_snapshot_cmd(cmd: SnapshotCommand) -> Result<()> {
+    use neomind_storage::{BackupConfig, BackupManager};
+
+    match cmd {
+        SnapshotCommand::Create {
+            data_dir,
+            backup_dir,
+            description,
+            verify,
+        } => {
+            let manager = BackupManager::with_config(BackupConfig {
+                backup_dir,
+                ..Default::default()
+            })?;
+            let metadata = manager.create_snapshot(&data_dir, description)?;
+            println!("Snapshot {} created", metadata.id);
+            println!("  Archive: {}", metadata.path.display());
+            println!("  Files:   {}", metadata.table_count);
+            println!("  Size:    {} bytes", metadata.size_bytes);
+            if verify {
+                print_verification(&neomind_storage::verify_snapshot_archive(&metadata.path)?)?;
+            }
+            Ok(())
+        }
+        SnapshotCommand::List { backup_dir } => {
+            let manager = BackupManager::new(&backup_dir)?;
+            let backups = manager.list_backups()?;
+            if backups.is_empty() {
+                println!("No backups in {}", backup_dir.display());
+            }
+            for backup in backups {
+                println!(
+                    "{}  {:?}  {} bytes  {}  {}",
+                    backup.id,
+                    backup.backup_type,
+                    backup.size_bytes,
+                    backup.timestamp,
+                    backup.path.display()
+                );
+            }
+            Ok(())
+        }
+        SnapshotCommand::Verify { archive } => {
+            print_verification(&neomind_storage::verify_snapshot_archive(&archive)?)
+        }
+        SnapshotCommand::Restore {
+            archive,
+            data_dir,
+            verify,
+        } => {
+            if verify {
+                return print_verification(&neomind_storage::verify_snapshot_archive(&archive)?);
+            }
+            let manifest = neomind_storage::stage_snapshot_restore(&archive, &data_dir)?;
+            let outcome = neomind_storage::apply_pending_restore(&data_dir)?
+                .ok_or_else(|| anyhow::anyhow!("Restore was not staged"))?;
+            println!(
+                "Restored snapshot {} ({} files) into {}",
+                manifest.id,
+                manifest.entries.len(),
+                data_dir.display()
+            );
+            println!("Previous data moved to {}", outcome.previous_data.display());
+            Ok(())
+        }
+    }
+}
+
+/// Print a snapshot verification report, failing if it found problems.
+fn print_verification(report: &neomind_storage::SnapshotVerification) -> Result<()> {
+    println!("Snapshot {}", report.id);
+    println!("  Entries checked: {}", report.entries_checked);
+    println!("  Stores opened:   {}", report.stores_opened);
+    for error in &report.errors {
+        println!("  ERROR: {}", error);
+    }
+    if !report.is_ok() {
+        anyhow::bail!("Snapshot verification failed");
+    }
+    println!("  OK");
+    Ok(())
+}
+
 /// Run health check command.
 async fn run_health() -> Result<()> {
     
@@ -261,6 +261,19 @@ pub enum NeoMindEvent {
         timestamp: i64,
     },
 
+    /// LLM request failed on a backend and was routed to the next one
+    LlmBackendFailover {
+        policy_id: String,
+        from_backend: String,
+        /// Backend tried next, `None` when the policy ran out of backends
+        #[serde(skip_serializing_if = "Option::is_none")]
+        to_backend: Option<String>,
+        error: String,
+        /// Whether the failure opened the backend's circuit breaker
+        circuit_opened: bool,
+        timestamp: i64,
+    },
+
     // ========== Tool Execution Events ==========
     /// Tool execution started
     ToolExecutionStart {
@@ -433,6 +446,7 @@ impl NeoMindEvent {
             Self::LlmDecisionExecuted { .. } => "LlmDecisionExecuted",
             Self::UserMessage { .. } => "UserMessage",
             Self::LlmResponse { .. } => "LlmResponse",
+            Self::LlmBackendFailover { .. } => "LlmBackendFailover",
             Self::ToolExecutionStart { .. } => "ToolExecutionStart",
             Self::ToolExecutionSuccess { .. } => "ToolExecutionSuccess",
             Self::ToolExecutionFailure { .. } => "ToolExecutionFailure",
@@ -484,6 +498,7 @@ impl NeoMindEvent {
             | Self::LlmDecisionExecuted { timestamp, .. }
             | Self::UserMessage { timestamp, .. }
             | Self::LlmResponse { timestamp, .. }
+            | Self::LlmBackendFailover { timestamp, .. }
             | Self::ToolExecutionStart { timestamp, .. }
             | Self::ToolExecutionSuccess { timestamp, .. }
             | Self::ToolExecutionFailure { timestamp, .. }
@@ -557,6 +572,7 @@ impl NeoMindEvent {
                 | Self::LlmDecisionExecuted { .. }
                 | Self::UserMessage { .. }
                 | Self::LlmResponse { .. }
+                | Self::LlmBackendFailover { .. }
                 | Self::ToolExecutionStart { .. }
                 | Self::ToolExecutionSuccess { .. }
                 | Self::ToolExecutionFailure { .. }
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 use crate::event::NeoMindEvent;
 use crate::EventBus;
+use crate::llm::usage::{with_usage_context, UsageContext};
 
 // ============================================================================
 // Capability Definition Macro
@@ -328,7 +329,12 @@ impl ExtensionContext {
             .get(&package_name)
             .ok_or_else(|| CapabilityError::ProviderError(format!("Provider '{}' not found", package_name)))?;
 
-        provider.invoke_capability(capability, params).await
+        // LLM calls made on the extension's behalf (e.g. by a triggered agent) count for it
+        with_usage_context(
+            UsageContext::for_extension(&self.config.extension_id),
+            provider.invoke_capability(capability, params),
+        )
+        .await
     }
 
     pub async fn has_capability(&self, capability: &ExtensionCapability) -> bool {
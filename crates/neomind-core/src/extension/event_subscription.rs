@@ -423,7 +423,7 @@ fn infer_source_from_event_type(event_type: &str) -> &'static str {
         "AgentExecutionStarted" | "AgentThinking" | "AgentDecision" | "AgentProgress"
         | "AgentExecutionCompleted" | "AgentMemoryUpdated" => "agents",
         "PeriodicReviewTriggered" | "LlmDecisionProposed" | "LlmDecisionExecuted"
-        | "UserMessage" | "LlmResponse" => "llm",
+        | "UserMessage" | "LlmResponse" | "LlmBackendFailover" => "llm",
         "ToolExecutionStart" | "ToolExecutionSuccess" | "ToolExecutionFailure" => "tools",
         "ExtensionOutput" | "ExtensionLifecycle" | "ExtensionCommandStarted"
         | "ExtensionCommandCompleted" | "ExtensionCommandFailed" => "extensions",
@@ -29,6 +29,7 @@ use super::in_flight::InFlightRequests;
 use super::ipc::{IpcFrame, IpcMessage, IpcResponse};
 use super::{IsolatedExtensionError, IsolatedResult};
 use crate::extension::system::{ExtensionMetadata, ExtensionMetricValue};
+use crate::llm::usage::{with_usage_context, UsageContext};
 use serde_json::Value;
 
 /// Helper function to send a message to the extension process
@@ -893,9 +894,11 @@ impl IsolatedExtension {
 
                             // Invoke capability using the runtime handle
                             // This thread is not in a Tokio runtime, so block_on is safe here
-                            let result = rt_handle.block_on(async {
-                                provider.invoke_capability(cap, &params).await
-                            });
+                            // LLM calls made on the extension's behalf count for it
+                            let result = rt_handle.block_on(with_usage_context(
+                                UsageContext::for_extension(extension_id.as_str()),
+                                provider.invoke_capability(cap, &params),
+                            ));
 
                             // Send response back to extension as IpcMessage
                             let message = match result {
@@ -7,6 +7,7 @@ use async_trait::async_trait;
 use futures::Stream;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
 use std::pin::Pin;
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
@@ -89,6 +90,23 @@ pub struct GenerationParams {
     /// Maximum context window size in tokens
     /// CRITICAL for Qwen3: must be >= 16384 to avoid infinite repetition loops
     pub max_context: Option<usize>,
+
+    /// Constrain the output to JSON (native JSON mode where the backend has one)
+    pub response_format: Option<ResponseFormat>,
+}
+
+/// Output format constraint for backends with a native JSON mode.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResponseFormat {
+    /// Any JSON value
+    Json,
+    /// JSON matching a JSON Schema
+    JsonSchema {
+        /// Schema name (OpenAI requires one)
+        name: String,
+        /// The JSON Schema
+        schema: serde_json::Value,
+    },
 }
 
 impl Default for GenerationParams {
@@ -103,6 +121,7 @@ impl Default for GenerationParams {
             presence_penalty: Some(0.0),
             thinking_enabled: None, // Let backend decide based on model capabilities
             max_context: None,      // Let backend decide based on model capabilities
+            response_format: None,
         }
     }
 }
@@ -260,6 +279,40 @@ impl TokenUsage {
 /// (e.g., qwen3-vl's thinking field vs actual content).
 pub type StreamChunk = Result<(String, bool), LlmError>;
 
+tokio::task_local! {
+    static STREAM_FINISH: StreamFinish;
+}
+
+/// Why a streamed generation ended, which the chunks themselves do not say.
+///
+/// Backends report it on [`StreamFinish::current`]; a wrapper that needs it
+/// starts the inner stream inside [`StreamFinish::watch`].
+#[derive(Debug, Clone, Default)]
+pub struct StreamFinish(Arc<parking_lot::Mutex<Option<FinishReason>>>);
+
+impl StreamFinish {
+    /// Run `f`, which starts a stream, with its finish reason reported here.
+    pub async fn watch<F: Future>(&self, f: F) -> F::Output {
+        STREAM_FINISH.scope(self.clone(), f).await
+    }
+
+    /// Where the stream being started reports its finish reason; nowhere if
+    /// nobody watches it.
+    pub fn current() -> Self {
+        STREAM_FINISH.try_with(Clone::clone).unwrap_or_default()
+    }
+
+    /// Report why the stream ended; this must happen before the stream ends.
+    pub fn set(&self, reason: FinishReason) {
+        *self.0.lock() = Some(reason);
+    }
+
+    /// The reported finish reason, `None` if the backend reported none.
+    pub fn get(&self) -> Option<FinishReason> {
+        *self.0.lock()
+    }
+}
+
 /// Stream configuration for LLM backends.
 ///
 /// This configuration controls timeouts, thinking limits, and progress reporting
@@ -470,6 +523,10 @@ pub enum LlmError {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    /// Call refused because a usage budget is spent
+    #[error("Usage budget exceeded: {0}")]
+    BudgetExceeded(String),
+
     /// Unknown error
     #[error("Unknown error: {0}")]
     Unknown(String),
@@ -905,6 +962,8 @@ impl BackendCapabilitiesBuilder {
 pub struct DynamicLlmRuntime {
     backends: std::collections::HashMap<String, Box<dyn LlmRuntime>>,
     default_backend: String,
+    /// Order in which `first_available` probes backends
+    route: Vec<String>,
 }
 
 impl DynamicLlmRuntime {
@@ -913,6 +972,7 @@ impl DynamicLlmRuntime {
         Self {
             backends: std::collections::HashMap::new(),
             default_backend: default_backend.into(),
+            route: Vec::new(),
         }
     }
 
@@ -937,10 +997,31 @@ impl DynamicLlmRuntime {
         self.default_backend = backend_id.into();
     }
 
+    /// Set the order in which [`first_available`](Self::first_available)
+    /// probes backends, such as the backend list of a routing policy.
+    pub fn set_route(&mut self, backend_ids: Vec<String>) {
+        self.route = backend_ids;
+    }
+
     /// Get the first available backend.
-    pub fn first_available(&self) -> Option<(&str, &dyn LlmRuntime)> {
-        if let Some((backend_id, backend)) = self.backends.iter().next() {
-            return Some((backend_id.as_str(), backend.as_ref()));
+    ///
+    /// Backends on the route are probed in order, then the default backend,
+    /// then the remaining backends by ID.
+    pub async fn first_available(&self) -> Option<(&str, &dyn LlmRuntime)> {
+        let mut rest: Vec<&String> = self
+            .backends
+            .keys()
+            .filter(|id| **id != self.default_backend && !self.route.contains(id))
+            .collect();
+        rest.sort();
+
+        let default = Some(&self.default_backend).filter(|id| !self.route.contains(id));
+        for id in self.route.iter().chain(default).chain(rest) {
+            if let Some((id, backend)) = self.backends.get_key_value(id) {
+                if backend.is_available().await {
+                    return Some((id.as_str(), backend.as_ref()));
+                }
+            }
         }
         None
     }
@@ -1016,4 +1097,53 @@ mod tests {
         assert_eq!(input.model.as_deref(), Some("qwen2"));
         assert!(input.stream);
     }
+
+    struct StubRuntime {
+        id: &'static str,
+        available: bool,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmRuntime for StubRuntime {
+        fn backend_id(&self) -> BackendId {
+            BackendId::new(self.id)
+        }
+
+        fn model_name(&self) -> &str {
+            self.id
+        }
+
+        async fn is_available(&self) -> bool {
+            self.available
+        }
+
+        async fn generate(&self, _input: LlmInput) -> Result<LlmOutput, LlmError> {
+            Err(LlmError::BackendUnavailable(self.id.to_string()))
+        }
+
+        async fn generate_stream(
+            &self,
+            _input: LlmInput,
+        ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+            Err(LlmError::BackendUnavailable(self.id.to_string()))
+        }
+
+        fn max_context_length(&self) -> usize {
+            4096
+        }
+    }
+
+    #[tokio::test]
+    async fn test_first_available_follows_route() {
+        let mut runtime = DynamicLlmRuntime::new("ollama");
+        for (id, available) in [("ollama", false), ("openai", true), ("anthropic", true)] {
+            runtime.add_backend(Box::new(StubRuntime { id, available }));
+        }
+
+        // The default is down, so the rest are probed by ID
+        assert_eq!(runtime.first_available().await.unwrap().0, "anthropic");
+
+        runtime.set_route(vec!["ollama".to_string(), "openai".to_string()]);
+        assert_eq!(runtime.first_available().await.unwrap().0, "openai");
+    }
 }
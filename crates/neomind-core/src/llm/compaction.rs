@@ -300,11 +300,23 @@ pub fn compact_messages(
 
 /// Estimate token count for a text string.
 ///
+/// Counts exactly with the active model's tokenizer when one is loaded (see
+/// [`set_default_encoder`](super::token_counter::set_default_encoder)),
+/// otherwise falls back to [`heuristic_tokens`].
+pub fn estimate_tokens(text: &str) -> usize {
+    match super::token_counter::default_encoder() {
+        Some(encoder) => encoder.count(text),
+        None => heuristic_tokens(text),
+    }
+}
+
+/// Estimate token count for a text string without a tokenizer.
+///
 /// Uses a heuristic approach:
 /// - Chinese characters: ~1.8 tokens each
 /// - English words: ~0.25 tokens per character (4 chars = 1 token)
 /// - Special characters: ~0.5 tokens each
-pub fn estimate_tokens(text: &str) -> usize {
+pub(crate) fn heuristic_tokens(text: &str) -> usize {
     let mut tokens = 0f64;
 
     for line in text.lines() {
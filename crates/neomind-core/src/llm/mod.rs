@@ -9,10 +9,11 @@ pub mod memory_consolidation;
 pub mod modality;
 pub mod models;
 pub mod token_counter;
+pub mod usage;
 
 pub use backend::{
     BackendCapabilities, BackendId, DynamicLlmRuntime, FinishReason, GenerationParams, LlmError,
-    LlmInput, LlmOutput, LlmRuntime, StreamChunk, TokenUsage,
+    LlmInput, LlmOutput, LlmRuntime, ResponseFormat, StreamChunk, StreamFinish, TokenUsage,
 };
 pub use capability::{
     detect_vision_capability, get_max_context, model_supports, CapabilityDetectionResult, CapabilityDetector,
@@ -25,7 +26,14 @@ pub use memory_consolidation::{
 };
 pub use modality::{ImageContent, ImageInput, ModalityContent};
 pub use models::*;
-pub use token_counter::{count_tokens, heuristic_count, CounterMode, EncodingType, TokenCounter};
+pub use token_counter::{
+    count_tokens, default_encoder, encoder_for_model, heuristic_count, register_encoder,
+    set_default_encoder, CounterMode, EncodingType, TokenCounter, TokenEncoder,
+};
+pub use usage::{
+    set_usage_recorder, with_usage_context, Admission, LlmCall, StreamUsage, UsageContext,
+    UsageMeter, UsageRecorder,
+};
 
 use std::pin::Pin;
 use std::time::Duration;
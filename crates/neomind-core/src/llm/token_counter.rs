@@ -1,7 +1,12 @@
 //! Token counting for LLM context management.
 //!
-//! This module provides accurate token counting using tiktoken when available,
-//! with fallback to heuristic estimation when not.
+//! This module provides exact token counting with a model's own tokenizer
+//! (registered as a [`TokenEncoder`]) or tiktoken when available, with
+//! fallback to heuristic estimation when not.
+//!
+//! Tokenizers are registered per model with [`register_encoder`]; the
+//! tokenizer of the active model is set with [`set_default_encoder`] and used
+//! by [`count_tokens`] and counters created in [`CounterMode::Auto`].
 //!
 //! ## Example
 //!
@@ -23,19 +28,58 @@
 //! let total = counter.count_messages(&messages);
 //! ```
 
-#[cfg(feature = "tiktoken")]
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use crate::message::{Content, ContentPart, Message, MessageRole};
 use once_cell::sync::Lazy;
+use parking_lot::RwLock;
 
 #[cfg(feature = "tiktoken")]
 use tiktoken_rs::{cl100k_base, p50k_base, CoreBPE};
 
+/// An exact tokenizer for a model, e.g. loaded from its `tokenizer.json`.
+pub trait TokenEncoder: Send + Sync {
+    /// Count the tokens `text` encodes to, without special tokens.
+    fn count(&self, text: &str) -> usize;
+
+    /// Tokenizer name, for logs.
+    fn name(&self) -> &str;
+}
+
+/// Exact tokenizers by model name.
+static ENCODERS: Lazy<RwLock<HashMap<String, Arc<dyn TokenEncoder>>>> =
+    Lazy::new(|| RwLock::new(HashMap::new()));
+
+/// Tokenizer of the active model.
+static DEFAULT_ENCODER: Lazy<RwLock<Option<Arc<dyn TokenEncoder>>>> =
+    Lazy::new(|| RwLock::new(None));
+
+/// Register the exact tokenizer for a model.
+pub fn register_encoder(model_name: &str, encoder: Arc<dyn TokenEncoder>) {
+    ENCODERS.write().insert(model_name.to_lowercase(), encoder);
+}
+
+/// Get the exact tokenizer registered for a model.
+pub fn encoder_for_model(model_name: &str) -> Option<Arc<dyn TokenEncoder>> {
+    ENCODERS.read().get(&model_name.to_lowercase()).cloned()
+}
+
+/// Set (or clear) the tokenizer of the active model.
+pub fn set_default_encoder(encoder: Option<Arc<dyn TokenEncoder>>) {
+    *DEFAULT_ENCODER.write() = encoder;
+}
+
+/// Get the tokenizer of the active model, if one is loaded.
+pub fn default_encoder() -> Option<Arc<dyn TokenEncoder>> {
+    DEFAULT_ENCODER.read().clone()
+}
+
 /// Mode for token counting.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CounterMode {
-    /// Use tiktoken if available, otherwise fall back to heuristic.
+    /// Use the model's tokenizer or tiktoken if available, otherwise fall
+    /// back to heuristic.
     Auto,
     /// Force use of heuristic estimation (always available).
     Heuristic,
@@ -67,6 +111,7 @@ pub struct TokenCounter {
     pub encoding: EncodingType,
     #[cfg(feature = "tiktoken")]
     tiktoken: Option<Arc<CoreBPE>>,
+    encoder: Option<Arc<dyn TokenEncoder>>,
 }
 
 impl TokenCounter {
@@ -87,24 +132,44 @@ impl TokenCounter {
         #[cfg(not(feature = "tiktoken"))]
         let _ = encoding; // Unused without feature
 
+        let encoder = if mode == CounterMode::Auto {
+            default_encoder()
+        } else {
+            None
+        };
+
         Self {
             mode,
             encoding,
             #[cfg(feature = "tiktoken")]
             tiktoken,
+            encoder,
         }
     }
 
     /// Create a counter for a specific model.
     ///
-    /// This selects the appropriate encoding based on the model name.
+    /// Uses the model's registered tokenizer if there is one, otherwise
+    /// selects the appropriate encoding based on the model name.
     pub fn for_model(model_name: &str) -> Self {
         let encoding = Self::detect_encoding(model_name);
-        Self::with_encoding(CounterMode::Auto, encoding)
+        let mut counter = Self::with_encoding(CounterMode::Auto, encoding);
+        counter.encoder = encoder_for_model(model_name);
+        counter
+    }
+
+    /// Count with an exact tokenizer.
+    pub fn with_encoder(mut self, encoder: Arc<dyn TokenEncoder>) -> Self {
+        self.encoder = Some(encoder);
+        self
     }
 
     /// Count tokens in a text string.
     pub fn count(&self, text: &str) -> usize {
+        if let Some(encoder) = &self.encoder {
+            return encoder.count(text);
+        }
+
         #[cfg(feature = "tiktoken")]
         {
             if let Some(bpe) = &self.tiktoken {
@@ -218,6 +283,11 @@ impl TokenCounter {
         }
     }
 
+    /// Check if counts come from the model's own tokenizer.
+    pub fn is_exact(&self) -> bool {
+        self.encoder.is_some()
+    }
+
     /// Check if tiktoken is available.
     pub fn is_tiktoken_available(&self) -> bool {
         #[cfg(feature = "tiktoken")]
@@ -246,16 +316,26 @@ impl Default for TokenCounter {
 /// - Numbers: ~0.3 tokens per digit
 /// - Special characters: ~0.5 tokens each
 pub fn heuristic_count(text: &str) -> usize {
-    use crate::llm::compaction::estimate_tokens;
-    estimate_tokens(text)
+    crate::llm::compaction::heuristic_tokens(text)
 }
 
 /// Global token counter instance.
 ///
 /// This is a convenience for quick token counting without
-/// creating a counter instance.
+/// creating a counter instance. Counts are exact when the active model's
+/// tokenizer is loaded.
 pub fn count_tokens(text: &str) -> usize {
-    static COUNTER: Lazy<TokenCounter> = Lazy::new(|| TokenCounter::new(CounterMode::Auto));
+    static COUNTER: Lazy<TokenCounter> = Lazy::new(|| TokenCounter::new(CounterMode::Heuristic));
+    if let Some(encoder) = default_encoder() {
+        return encoder.count(text);
+    }
+    #[cfg(feature = "tiktoken")]
+    {
+        static TIKTOKEN: Lazy<TokenCounter> = Lazy::new(|| TokenCounter::new(CounterMode::Auto));
+        if TIKTOKEN.is_tiktoken_available() {
+            return TIKTOKEN.count(text);
+        }
+    }
     COUNTER.count(text)
 }
 
@@ -363,4 +443,31 @@ mod tests {
         let count = count_tokens("Hello, world!");
         assert!(count > 0);
     }
+
+    /// One token per character.
+    struct CharEncoder;
+
+    impl TokenEncoder for CharEncoder {
+        fn count(&self, text: &str) -> usize {
+            text.chars().count()
+        }
+
+        fn name(&self) -> &str {
+            "chars"
+        }
+    }
+
+    #[test]
+    fn test_exact_encoder() {
+        let counter = TokenCounter::new(CounterMode::Heuristic).with_encoder(Arc::new(CharEncoder));
+        assert!(counter.is_exact());
+        assert_eq!(counter.count("你好世界"), 4);
+        assert!(!TokenCounter::new(CounterMode::Heuristic).is_exact());
+
+        register_encoder("Test-Char-Model:7B", Arc::new(CharEncoder));
+        let counter = TokenCounter::for_model("test-char-model:7b");
+        assert!(counter.is_exact());
+        assert_eq!(counter.count("hello"), 5);
+        assert!(!TokenCounter::for_model("another-model").is_exact());
+    }
 }
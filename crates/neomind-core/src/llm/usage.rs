@@ -0,0 +1,426 @@
+//! Usage accounting hooks for LLM calls.
+//!
+//! Backends report every call to a global [`UsageRecorder`], which can
+//! persist it, price it, and hold back calls that exceed a budget. Calls are
+//! attributed through a task-local [`UsageContext`] that callers set with
+//! [`with_usage_context`]:
+//!
+//! ```rust,no_run
+//! use neomind_core::llm::usage::{with_usage_context, UsageContext};
+//!
+//! # async fn run() {
+//! let context = UsageContext::for_agent("agent-1").with_user("alice");
+//! with_usage_context(context, async {
+//!     // LLM calls made here are attributed to agent-1 and alice
+//! })
+//! .await;
+//! # }
+//! ```
+
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+
+use futures::Stream;
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use serde::{Deserialize, Serialize};
+
+use super::backend::{LlmError, LlmInput, StreamChunk, TokenUsage};
+
+tokio::task_local! {
+    static USAGE_CONTEXT: UsageContext;
+}
+
+/// Global usage recorder
+static RECORDER: Lazy<RwLock<Option<Arc<dyn UsageRecorder>>>> = Lazy::new(|| RwLock::new(None));
+
+/// Who an LLM call is made on behalf of.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UsageContext {
+    /// Chat session ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+    /// AI agent ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// User name
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+    /// Extension ID
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub extension_id: Option<String>,
+}
+
+impl UsageContext {
+    /// Context for a chat session.
+    pub fn for_session(session_id: impl Into<String>) -> Self {
+        Self {
+            session_id: Some(session_id.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Context for an AI agent.
+    pub fn for_agent(agent_id: impl Into<String>) -> Self {
+        Self {
+            agent_id: Some(agent_id.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Context for an extension.
+    pub fn for_extension(extension_id: impl Into<String>) -> Self {
+        Self {
+            extension_id: Some(extension_id.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Attribute to a user as well.
+    pub fn with_user(mut self, user_id: impl Into<String>) -> Self {
+        self.user_id = Some(user_id.into());
+        self
+    }
+
+    /// Fill fields that are not set from `fallback`.
+    pub fn or(self, fallback: UsageContext) -> Self {
+        Self {
+            session_id: self.session_id.or(fallback.session_id),
+            agent_id: self.agent_id.or(fallback.agent_id),
+            user_id: self.user_id.or(fallback.user_id),
+            extension_id: self.extension_id.or(fallback.extension_id),
+        }
+    }
+
+    /// The usage context of the current task.
+    pub fn current() -> Self {
+        USAGE_CONTEXT.try_with(Clone::clone).unwrap_or_default()
+    }
+}
+
+/// Run `f` with calls attributed to `context`, on top of any enclosing context.
+pub async fn with_usage_context<F: Future>(context: UsageContext, f: F) -> F::Output {
+    let context = context.or(UsageContext::current());
+    USAGE_CONTEXT.scope(context, f).await
+}
+
+/// A finished LLM call.
+#[derive(Debug, Clone)]
+pub struct LlmCall {
+    /// Backend type (e.g. "ollama", "openai")
+    pub backend: String,
+    /// Model name
+    pub model: String,
+    /// Attribution
+    pub context: UsageContext,
+    /// Prompt tokens (exact when the backend reports them)
+    pub prompt_tokens: u32,
+    /// Completion tokens
+    pub completion_tokens: u32,
+    /// Wall-clock latency in milliseconds
+    pub latency_ms: u64,
+    /// Whether the call succeeded
+    pub success: bool,
+}
+
+/// Whether a call may proceed.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Admission {
+    /// Proceed immediately
+    Allow,
+    /// Proceed after waiting (throttled)
+    Delay(Duration),
+    /// Refuse the call, with the reason
+    Block(String),
+}
+
+/// Receives every LLM call made by the backends.
+pub trait UsageRecorder: Send + Sync {
+    /// Decide whether a call may start.
+    fn admit(&self, backend: &str, model: &str, context: &UsageContext) -> Admission;
+
+    /// Record a finished call.
+    fn record(&self, call: LlmCall);
+}
+
+/// Install the global usage recorder, or remove it with `None`.
+pub fn set_usage_recorder(recorder: Option<Arc<dyn UsageRecorder>>) {
+    *RECORDER.write() = recorder;
+}
+
+fn recorder() -> Option<Arc<dyn UsageRecorder>> {
+    RECORDER.read().clone()
+}
+
+/// An LLM call in progress, created by [`UsageMeter::start`].
+///
+/// Backends start a meter before sending a request and finish it with the
+/// reported token usage, or wrap their response stream with
+/// [`UsageMeter::stream`]. Dropping an unfinished meter records a failed call.
+#[derive(Debug)]
+pub struct UsageMeter {
+    backend: String,
+    model: String,
+    context: UsageContext,
+    prompt_tokens: u32,
+    started: Instant,
+    done: bool,
+}
+
+impl UsageMeter {
+    /// Admit a call for the current usage context, waiting if it is
+    /// throttled. Fails with [`LlmError::BudgetExceeded`] if it is blocked.
+    pub async fn start(backend: &str, model: &str, input: &LlmInput) -> Result<Self, LlmError> {
+        let context = UsageContext::current();
+        if let Some(recorder) = recorder() {
+            match recorder.admit(backend, model, &context) {
+                Admission::Allow => {}
+                Admission::Delay(delay) => {
+                    tracing::info!(
+                        backend,
+                        model,
+                        ?context,
+                        "LLM call throttled for {:?}",
+                        delay
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Admission::Block(reason) => return Err(LlmError::BudgetExceeded(reason)),
+            }
+        }
+
+        let prompt_tokens = input
+            .messages
+            .iter()
+            .map(|m| super::compaction::estimate_tokens(&m.text()))
+            .sum::<usize>() as u32;
+
+        Ok(Self {
+            backend: backend.to_string(),
+            model: model.to_string(),
+            context,
+            prompt_tokens,
+            started: Instant::now(),
+            done: false,
+        })
+    }
+
+    /// Record a successful call, using the backend's token counts when it
+    /// has them and estimating from the response `text` otherwise.
+    pub fn finish(mut self, usage: Option<TokenUsage>, text: &str) {
+        let (prompt_tokens, completion_tokens) = match usage {
+            Some(usage) => (usage.prompt_tokens, usage.completion_tokens),
+            None => (
+                self.prompt_tokens,
+                super::compaction::estimate_tokens(text) as u32,
+            ),
+        };
+        self.record(prompt_tokens, completion_tokens, true);
+    }
+
+    fn record(&mut self, prompt_tokens: u32, completion_tokens: u32, success: bool) {
+        self.done = true;
+        if let Some(recorder) = recorder() {
+            recorder.record(LlmCall {
+                backend: self.backend.clone(),
+                model: self.model.clone(),
+                context: self.context.clone(),
+                prompt_tokens,
+                completion_tokens,
+                latency_ms: self.started.elapsed().as_millis() as u64,
+                success,
+            });
+        }
+    }
+
+    /// Wrap a response stream so the call is recorded when it ends, with
+    /// completion tokens estimated from the streamed content.
+    pub fn stream<S>(self, inner: S) -> MeteredStream<S>
+    where
+        S: Stream<Item = StreamChunk> + Unpin,
+    {
+        MeteredStream {
+            inner,
+            meter: Some(self),
+            content: String::new(),
+            failed: false,
+            usage: None,
+        }
+    }
+
+    /// Like [`stream`](Self::stream), but records the token counts the
+    /// backend reports through `usage`, estimating only when it reports none.
+    pub fn stream_with_usage<S>(self, inner: S, usage: StreamUsage) -> MeteredStream<S>
+    where
+        S: Stream<Item = StreamChunk> + Unpin,
+    {
+        let mut stream = self.stream(inner);
+        stream.usage = Some(usage);
+        stream
+    }
+}
+
+/// Token usage a backend reports while streaming, shared between the task
+/// reading the response and the [`MeteredStream`] returned to the caller.
+#[derive(Debug, Clone, Default)]
+pub struct StreamUsage(Arc<parking_lot::Mutex<Option<TokenUsage>>>);
+
+impl StreamUsage {
+    /// Report the call's token usage; this must happen before the stream ends.
+    pub fn set(&self, usage: TokenUsage) {
+        *self.0.lock() = Some(usage);
+    }
+
+    fn get(&self) -> Option<TokenUsage> {
+        *self.0.lock()
+    }
+}
+
+/// Response stream that records its call when it ends or is dropped.
+pub struct MeteredStream<S> {
+    inner: S,
+    meter: Option<UsageMeter>,
+    content: String,
+    failed: bool,
+    usage: Option<StreamUsage>,
+}
+
+impl<S> MeteredStream<S> {
+    fn finish(&mut self) {
+        if let Some(mut meter) = self.meter.take() {
+            let (prompt_tokens, completion_tokens) =
+                match self.usage.as_ref().and_then(StreamUsage::get) {
+                    Some(usage) => (usage.prompt_tokens, usage.completion_tokens),
+                    None => (
+                        meter.prompt_tokens,
+                        super::compaction::estimate_tokens(&self.content) as u32,
+                    ),
+                };
+            meter.record(prompt_tokens, completion_tokens, !self.failed);
+        }
+    }
+}
+
+impl Drop for UsageMeter {
+    fn drop(&mut self) {
+        if !self.done {
+            self.record(self.prompt_tokens, 0, false);
+        }
+    }
+}
+
+impl<S> Stream for MeteredStream<S>
+where
+    S: Stream<Item = StreamChunk> + Unpin,
+{
+    type Item = StreamChunk;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = Pin::new(&mut self.inner).poll_next(cx);
+        match &poll {
+            Poll::Ready(Some(Ok((text, _)))) => self.content.push_str(text),
+            Poll::Ready(Some(Err(_))) => self.failed = true,
+            Poll::Ready(None) => self.finish(),
+            Poll::Pending => {}
+        }
+        poll
+    }
+}
+
+impl<S> Drop for MeteredStream<S> {
+    fn drop(&mut self) {
+        self.finish();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use parking_lot::Mutex;
+
+    #[derive(Default)]
+    struct TestRecorder {
+        calls: Mutex<Vec<LlmCall>>,
+    }
+
+    impl UsageRecorder for TestRecorder {
+        fn admit(&self, _backend: &str, _model: &str, context: &UsageContext) -> Admission {
+            match context.agent_id.as_deref() {
+                Some("over-budget") => Admission::Block("daily budget of 1.00 spent".to_string()),
+                _ => Admission::Allow,
+            }
+        }
+
+        fn record(&self, call: LlmCall) {
+            self.calls.lock().push(call);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usage_meter() {
+        let recorder = Arc::new(TestRecorder::default());
+        set_usage_recorder(Some(recorder.clone()));
+
+        let input = LlmInput::new("How warm is the greenhouse?");
+        let context = UsageContext::for_agent("agent-1");
+        with_usage_context(context, async {
+            // Inner contexts add to the outer one
+            with_usage_context(UsageContext::default().with_user("alice"), async {
+                let meter = UsageMeter::start("ollama", "qwen3:8b", &input)
+                    .await
+                    .unwrap();
+                meter.finish(Some(TokenUsage::new(12, 30)), "");
+
+                let meter = UsageMeter::start("ollama", "qwen3:8b", &input)
+                    .await
+                    .unwrap();
+                let chunks = vec![
+                    Ok(("It is ".to_string(), false)),
+                    Ok(("24 °C".to_string(), false)),
+                ];
+                let stream = meter.stream(futures::stream::iter(chunks));
+                assert_eq!(stream.count().await, 2);
+
+                // Counts the backend reports replace the estimates
+                let meter = UsageMeter::start("ollama", "qwen3:8b", &input)
+                    .await
+                    .unwrap();
+                let usage = StreamUsage::default();
+                let chunks = vec![Ok(("Dry".to_string(), false))];
+                let mut stream =
+                    meter.stream_with_usage(futures::stream::iter(chunks), usage.clone());
+                assert!(stream.next().await.is_some());
+                usage.set(TokenUsage::new(40, 7));
+                assert!(stream.next().await.is_none());
+            })
+            .await;
+        })
+        .await;
+
+        let blocked = with_usage_context(
+            UsageContext::for_agent("over-budget"),
+            UsageMeter::start("openai", "gpt-4o", &input),
+        )
+        .await;
+        assert!(matches!(blocked, Err(LlmError::BudgetExceeded(_))));
+
+        // An unfinished meter counts as a failed call
+        drop(UsageMeter::start("openai", "gpt-4o", &input).await.unwrap());
+
+        set_usage_recorder(None);
+        let calls = recorder.calls.lock();
+        assert_eq!(calls.len(), 4);
+        assert!(!calls[3].success);
+        assert_eq!(calls[0].context.agent_id.as_deref(), Some("agent-1"));
+        assert_eq!(calls[0].context.user_id.as_deref(), Some("alice"));
+        assert_eq!(calls[0].completion_tokens, 30);
+        assert!(calls[1].prompt_tokens > 0);
+        assert!(calls[1].completion_tokens > 0);
+        assert_eq!(calls[2].prompt_tokens, 40);
+        assert_eq!(calls[2].completion_tokens, 7);
+    }
+}
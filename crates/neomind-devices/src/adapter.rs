@@ -491,6 +491,7 @@ pub struct MockAdapter {
     running: std::sync::Arc<std::sync::atomic::AtomicBool>,
     event_tx: broadcast::Sender<DeviceEvent>,
     devices: Vec<String>,
+    sent_commands: std::sync::Arc<std::sync::Mutex<Vec<(String, String, String)>>>,
 }
 
 impl MockAdapter {
@@ -502,6 +503,7 @@ impl MockAdapter {
             running: std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false)),
             event_tx,
             devices: Vec::new(),
+            sent_commands: std::sync::Arc::new(std::sync::Mutex::new(Vec::new())),
         }
     }
 
@@ -518,6 +520,11 @@ impl MockAdapter {
     ) -> Result<(), broadcast::error::SendError<DeviceEvent>> {
         self.event_tx.send(event).map(|_| ())
     }
+
+    /// Commands sent through this adapter as `(device_id, command_name, payload)`.
+    pub fn sent_commands(&self) -> Vec<(String, String, String)> {
+        self.sent_commands.lock().unwrap().clone()
+    }
 }
 
 #[async_trait]
@@ -562,12 +569,17 @@ impl DeviceAdapter for MockAdapter {
 
     async fn send_command(
         &self,
-        _device_id: &str,
-        _command_name: &str,
-        _payload: String,
+        device_id: &str,
+        command_name: &str,
+        payload: String,
         _topic: Option<String>,
     ) -> AdapterResult<()> {
         // Mock adapter always succeeds
+        self.sent_commands.lock().unwrap().push((
+            device_id.to_string(),
+            command_name.to_string(),
+            payload,
+        ));
         Ok(())
     }
 
@@ -18,6 +18,27 @@
 //! ├─ humidity capability     ──→ sensor/${id}/humidity
 //! └─ set_interval command    ──→ sensor/${id}/command
 //! ```
+//!
+//! ## Sparkplug B
+//!
+//! With `sparkplug.enabled`, the adapter also subscribes to `spBv1.0/...` and decodes
+//! Sparkplug B payloads (see [`crate::sparkplug`]). Birth certificates register device
+//! types and devices automatically; commands to Sparkplug devices are sent as NCMD/DCMD.
+//!
+//! ## Store-and-Forward
+//!
+//! With `store_forward.enabled`, QoS 1/2 publishes are appended to a per-broker queue
+//! (see [`crate::store_forward`]) and sent from there; an entry is removed only when
+//! the broker acknowledges it, and anything left over is replayed in order once the
+//! broker reconnects. The broker connection then retries forever with backoff instead
+//! of giving up after repeated errors.
+//!
+//! ## Telemetry Forwarding
+//!
+//! With `forward_telemetry_prefix` set, every `DeviceMetric` on the event bus is
+//! republished to the adapter's brokers as `{prefix}/{device_id}/{metric}` with a
+//! `{"value": .., "timestamp": ..}` payload, through the store-and-forward queue.
+//! Messages under the prefix are not ingested again by the same adapter.
 
 use crate::adapter::{
     AdapterError, AdapterResult, ConnectionStatus, DeviceAdapter, DeviceEvent, DiscoveredDeviceInfo,
@@ -25,7 +46,14 @@ use crate::adapter::{
 use crate::mdl::MetricValue;
 use crate::mqtt::MqttConfig;
 use crate::protocol::ProtocolMapping;
-use crate::registry::{DeviceConfig, DeviceRegistry};
+use crate::registry::{ConnectionConfig, DeviceConfig, DeviceRegistry};
+use crate::sparkplug::{
+    encode_rebirth_request, SparkplugConfig, SparkplugEvent, SparkplugPayload, SparkplugState,
+    SparkplugTopic, SPARKPLUG_NAMESPACE,
+};
+use crate::store_forward::{
+    OutboundQueue, OutboxStats, PublishTracker, QueuedPublish, StoreForwardConfig,
+};
 use crate::telemetry::TimeSeriesStorage;
 use crate::unified_extractor::UnifiedExtractor;
 
@@ -62,6 +90,15 @@ pub struct MqttAdapterConfig {
     pub auto_discovery: bool,
     /// Storage directory for persistence
     pub storage_dir: Option<String>,
+    /// Sparkplug B mode
+    #[serde(default)]
+    pub sparkplug: SparkplugConfig,
+    /// Outbound store-and-forward buffering
+    #[serde(default)]
+    pub store_forward: StoreForwardConfig,
+    /// Republish device telemetry under this topic prefix (disabled when unset)
+    #[serde(default)]
+    pub forward_telemetry_prefix: Option<String>,
 }
 
 impl MqttAdapterConfig {
@@ -76,6 +113,9 @@ impl MqttAdapterConfig {
             discovery_prefix: "neomind".to_string(),
             auto_discovery: true,
             storage_dir: None,
+            sparkplug: SparkplugConfig::default(),
+            store_forward: StoreForwardConfig::default(),
+            forward_telemetry_prefix: None,
         }
     }
 
@@ -126,6 +166,24 @@ impl MqttAdapterConfig {
         self.auto_discovery = enabled;
         self
     }
+
+    /// Set Sparkplug B configuration.
+    pub fn with_sparkplug(mut self, sparkplug: SparkplugConfig) -> Self {
+        self.sparkplug = sparkplug;
+        self
+    }
+
+    /// Set store-and-forward configuration.
+    pub fn with_store_forward(mut self, store_forward: StoreForwardConfig) -> Self {
+        self.store_forward = store_forward;
+        self
+    }
+
+    /// Republish device telemetry from the event bus under the given topic prefix.
+    pub fn with_telemetry_forwarding(mut self, prefix: impl Into<String>) -> Self {
+        self.forward_telemetry_prefix = Some(prefix.into());
+        self
+    }
 }
 
 impl Default for MqttAdapterConfig {
@@ -146,6 +204,58 @@ struct MqttClientInner {
     running: Arc<RwLock<bool>>,
     /// Subscribed topics for this broker
     subscribed_topics: Arc<RwLock<std::collections::HashSet<String>>>,
+    /// Whether the broker has acknowledged the current connection
+    connected: Arc<std::sync::atomic::AtomicBool>,
+    /// Acknowledgement tracking for outbox entries (store-and-forward only)
+    tracker: Option<Arc<PublishTracker>>,
+}
+
+impl MqttClientInner {
+    fn publisher(&self) -> BrokerPublisher {
+        BrokerPublisher {
+            client: self.client.clone(),
+            tracker: self.tracker.clone(),
+        }
+    }
+}
+
+/// Publishing side of one broker connection.
+///
+/// With store-and-forward enabled, every publish on the connection is registered
+/// with the broker's [`PublishTracker`], so the event loop can match packet ids
+/// and acknowledgements to outbox entries.
+#[derive(Clone)]
+struct BrokerPublisher {
+    client: rumqttc::AsyncClient,
+    tracker: Option<Arc<PublishTracker>>,
+}
+
+impl BrokerPublisher {
+    /// Hand a message to the client; `seq` is its outbox entry, if any.
+    async fn publish(
+        &self,
+        seq: Option<u64>,
+        msg: QueuedPublish,
+    ) -> Result<(), rumqttc::ClientError> {
+        let qos = qos_from_u8(msg.qos);
+        let Some(tracker) = &self.tracker else {
+            return self
+                .client
+                .publish(msg.topic, qos, msg.retain, msg.payload)
+                .await;
+        };
+
+        let _order = tracker.lock_order().await;
+        tracker.sending(seq, msg.qos);
+        let result = self
+            .client
+            .publish(msg.topic, qos, msg.retain, msg.payload)
+            .await;
+        if result.is_err() {
+            tracker.cancel_last();
+        }
+        result
+    }
 }
 
 /// MQTT device adapter.
@@ -182,6 +292,12 @@ pub struct MqttAdapter {
     topic_to_device: Arc<RwLock<HashMap<String, String>>>,
     /// Unified data extractor
     extractor: Arc<UnifiedExtractor>,
+    /// Sparkplug B session state (aliases, sequence numbers, online nodes)
+    sparkplug_state: Arc<RwLock<SparkplugState>>,
+    /// Store-and-forward queues (broker_id -> queue), kept across broker re-adds
+    outboxes: Arc<RwLock<HashMap<String, Arc<OutboundQueue>>>>,
+    /// Telemetry forwarding task, while running
+    telemetry_forwarder: std::sync::Mutex<Option<tokio::task::JoinHandle<()>>>,
 }
 
 #[allow(dead_code)]
@@ -207,6 +323,9 @@ impl MqttAdapter {
             metric_cache: Arc::new(RwLock::new(HashMap::new())),
             topic_to_device: Arc::new(RwLock::new(HashMap::new())),
             extractor,
+            sparkplug_state: Arc::new(RwLock::new(SparkplugState::new())),
+            outboxes: Arc::new(RwLock::new(HashMap::new())),
+            telemetry_forwarder: std::sync::Mutex::new(None),
         }
     }
 
@@ -267,6 +386,9 @@ impl MqttAdapter {
             metric_cache: Arc::new(RwLock::new(HashMap::new())),
             topic_to_device: Arc::new(RwLock::new(HashMap::new())),
             extractor,
+            sparkplug_state: Arc::new(RwLock::new(SparkplugState::new())),
+            outboxes: Arc::new(RwLock::new(HashMap::new())),
+            telemetry_forwarder: std::sync::Mutex::new(None),
         }
     }
 
@@ -335,6 +457,9 @@ impl MqttAdapter {
         for topic in &self.config.subscribe_topics {
             initial_topics.push(topic.clone());
         }
+        if self.config.sparkplug.enabled {
+            initial_topics.push(self.config.sparkplug.subscription());
+        }
 
         for topic in &initial_topics {
             debug!(
@@ -362,14 +487,26 @@ impl MqttAdapter {
             subscribed_topics.read().await
         );
 
+        // Open the store-and-forward queue for this broker
+        let outbox = if self.config.store_forward.enabled {
+            Some(self.outbox(&broker_id).await?)
+        } else {
+            None
+        };
+
         // Store the client
+        let connected = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        let tracker = outbox.as_ref().map(|_| Arc::new(PublishTracker::new()));
         let inner = MqttClientInner {
             _broker_id: broker_id.clone(),
             _broker_addr: broker_addr.clone(),
             client,
             running: running.clone(),
             subscribed_topics,
+            connected: connected.clone(),
+            tracker: tracker.clone(),
         };
+        let publisher = inner.publisher();
         self.mqtt_clients
             .write()
             .await
@@ -381,10 +518,28 @@ impl MqttAdapter {
         let devices = registry.list_devices().await;
         let mut topic_mapping = self.topic_to_device.write().await;
         let mut type_mapping = self.device_types.write().await;
+        let mut sparkplug_state = self.sparkplug_state.write().await;
         let mut restored_topic_count = 0;
         let mut restored_type_count = 0;
 
         for device in devices {
+            // Restore Sparkplug addresses so commands work before the next rebirth
+            let extra = &device.connection_config.extra;
+            if let (Some(group), Some(node)) = (
+                extra.get("sparkplug_group").and_then(|v| v.as_str()),
+                extra.get("sparkplug_edge_node").and_then(|v| v.as_str()),
+            ) {
+                sparkplug_state.register_address(
+                    device.device_id.clone(),
+                    group,
+                    node,
+                    extra
+                        .get("sparkplug_device")
+                        .and_then(|v| v.as_str())
+                        .map(String::from),
+                );
+            }
+
             // Restore topic_to_device mapping
             if let Some(ref telemetry_topic) = device.connection_config.telemetry_topic {
                 topic_mapping.insert(telemetry_topic.clone(), device.device_id.clone());
@@ -406,6 +561,7 @@ impl MqttAdapter {
 
         drop(topic_mapping);
         drop(type_mapping);
+        drop(sparkplug_state);
         info!(
             "Restored {} topic-to-device and {} device_type mappings from device registry for broker {}",
             restored_topic_count, restored_type_count, broker_id
@@ -428,6 +584,7 @@ impl MqttAdapter {
         let broker_id_clone = broker_id.clone();
         let extractor = self.extractor.clone();
         let topic_to_device = self.topic_to_device.clone();
+        let sparkplug_state = self.sparkplug_state.clone();
 
         info!(
             "Starting event loop task for broker '{}', connecting to {}...",
@@ -443,6 +600,28 @@ impl MqttAdapter {
                 match eventloop.poll().await {
                     Ok(notification) => {
                         error_count = 0; // Reset error count on success
+                        if matches!(
+                            notification,
+                            rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(_))
+                        ) {
+                            connected.store(true, std::sync::atomic::Ordering::SeqCst);
+                            if let Some(outbox) = &outbox {
+                                tokio::spawn(Self::replay_outbox(
+                                    broker_id_clone.clone(),
+                                    publisher.clone(),
+                                    outbox.clone(),
+                                    connected.clone(),
+                                ));
+                            }
+                        }
+                        if let (Some(outbox), Some(tracker)) = (&outbox, &tracker) {
+                            Self::track_acknowledgement(
+                                &notification,
+                                &broker_id_clone,
+                                outbox,
+                                tracker,
+                            );
+                        }
                         Self::handle_mqtt_notification(
                             notification,
                             &config,
@@ -456,11 +635,30 @@ impl MqttAdapter {
                             &broker_id_clone,
                             &extractor,
                             &topic_to_device,
+                            &sparkplug_state,
+                            &publisher,
                         )
                         .await;
                     }
                     Err(e) => {
+                        connected.store(false, std::sync::atomic::Ordering::SeqCst);
+                        if let Some(tracker) = &tracker {
+                            Self::reset_in_flight(&mut eventloop, tracker).await;
+                        }
                         error_count += 1;
+
+                        // With store-and-forward the backlog needs the connection back,
+                        // so keep retrying with capped exponential backoff
+                        if outbox.is_some() {
+                            let delay = Duration::from_secs(1 << error_count.min(5));
+                            warn!(
+                                "MQTT broker {} unreachable (attempt {}), retrying in {:?}: {}",
+                                broker_id_clone, error_count, delay, e
+                            );
+                            tokio::time::sleep(delay).await;
+                            continue;
+                        }
+
                         if error_count >= max_errors {
                             error!(
                                 "MQTT broker {} error count reached {}, stopping: {}",
@@ -507,6 +705,281 @@ impl MqttAdapter {
         self.mqtt_clients.read().await.keys().cloned().collect()
     }
 
+    /// Get or open the store-and-forward queue for a broker.
+    ///
+    /// Queues are disk-backed under `{storage_dir}/mqtt_outbox/` when a storage
+    /// directory is configured, otherwise kept in memory.
+    async fn outbox(&self, broker_id: &str) -> AdapterResult<Arc<OutboundQueue>> {
+        let mut outboxes = self.outboxes.write().await;
+        if let Some(queue) = outboxes.get(broker_id) {
+            return Ok(queue.clone());
+        }
+
+        let config = self.config.store_forward.clone();
+        let queue = match &self.config.storage_dir {
+            Some(dir) => {
+                let file = format!("{}_{}.redb", self.config.name, broker_id)
+                    .replace(|c: char| !c.is_ascii_alphanumeric() && c != '.', "_");
+                OutboundQueue::open(
+                    std::path::Path::new(dir).join("mqtt_outbox").join(file),
+                    config,
+                )
+            }
+            None => OutboundQueue::memory(config),
+        }
+        .map_err(|e| AdapterError::Configuration(format!("Failed to open MQTT outbox: {}", e)))?;
+
+        let queue = Arc::new(queue);
+        outboxes.insert(broker_id.to_string(), queue.clone());
+        Ok(queue)
+    }
+
+    /// Backlog statistics per broker (empty when store-and-forward is disabled).
+    pub async fn outbox_stats(&self) -> HashMap<String, OutboxStats> {
+        self.outboxes
+            .read()
+            .await
+            .iter()
+            .map(|(id, q)| (id.clone(), q.stats()))
+            .collect()
+    }
+
+    /// Whether the given broker currently has an acknowledged connection.
+    pub async fn is_broker_connected(&self, broker_id: &str) -> bool {
+        self.mqtt_clients
+            .read()
+            .await
+            .get(broker_id)
+            .is_some_and(|c| c.connected.load(std::sync::atomic::Ordering::SeqCst))
+    }
+
+    /// Publish a message on all brokers, buffering it when a broker is unreachable.
+    ///
+    /// Device commands go through the same path; forwarded telemetry uses it via
+    /// [`Self::forward_telemetry`].
+    pub async fn publish(
+        &self,
+        topic: impl Into<String>,
+        payload: impl Into<Vec<u8>>,
+        qos: u8,
+        retain: bool,
+        device_id: Option<&str>,
+    ) -> AdapterResult<()> {
+        let mut msg = QueuedPublish::new(topic, payload, qos).with_retain(retain);
+        msg.device_id = device_id.map(String::from);
+        Self::publish_all(&self.mqtt_clients, &self.outboxes, msg).await
+    }
+
+    async fn publish_all(
+        mqtt_clients: &RwLock<HashMap<String, MqttClientInner>>,
+        outboxes: &RwLock<HashMap<String, Arc<OutboundQueue>>>,
+        msg: QueuedPublish,
+    ) -> AdapterResult<()> {
+        let clients = mqtt_clients.read().await;
+        if clients.is_empty() {
+            return Err(AdapterError::Connection(
+                "No MQTT brokers connected".to_string(),
+            ));
+        }
+
+        let mut last_error = None;
+        let mut success_count = 0;
+        for (broker_id, inner) in clients.iter() {
+            match Self::publish_or_queue(outboxes, broker_id, inner, msg.clone()).await {
+                Ok(()) => success_count += 1,
+                Err(e) => last_error = Some(e),
+            }
+        }
+
+        if success_count == 0 {
+            Err(last_error.unwrap_or_else(|| {
+                AdapterError::Communication("Failed to publish on any broker".to_string())
+            }))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// With store-and-forward, append to the broker's queue and send from there so
+    /// the message survives until the broker acknowledges it and per-device ordering
+    /// is preserved. QoS 0 messages that are not queued are sent directly while the
+    /// broker is up.
+    async fn publish_or_queue(
+        outboxes: &RwLock<HashMap<String, Arc<OutboundQueue>>>,
+        broker_id: &str,
+        inner: &MqttClientInner,
+        msg: QueuedPublish,
+    ) -> AdapterResult<()> {
+        let outbox = outboxes.read().await.get(broker_id).cloned();
+        let connected = inner.connected.load(std::sync::atomic::Ordering::SeqCst);
+
+        if let Some(outbox) = outbox {
+            let outcome = outbox.enqueue(msg.clone()).map_err(|e| {
+                AdapterError::Communication(format!("Failed to queue publish: {}", e))
+            })?;
+            if outcome == crate::store_forward::EnqueueOutcome::Queued {
+                debug!(
+                    "Queued publish for broker {} ({} pending)",
+                    broker_id,
+                    outbox.stats().pending
+                );
+                if connected {
+                    tokio::spawn(Self::replay_outbox(
+                        broker_id.to_string(),
+                        inner.publisher(),
+                        outbox,
+                        inner.connected.clone(),
+                    ));
+                }
+                return Ok(());
+            }
+            if !connected {
+                return Err(AdapterError::Connection(format!(
+                    "Broker {} unreachable, QoS 0 publish discarded",
+                    broker_id
+                )));
+            }
+        }
+
+        inner.publisher().publish(None, msg).await.map_err(|e| {
+            AdapterError::Communication(format!("Failed to publish on {}: {}", broker_id, e))
+        })
+    }
+
+    /// Send a broker's queued messages in FIFO order while the connection stays up.
+    ///
+    /// Entries are only acked by the event loop once the broker confirms them (see
+    /// [`Self::track_acknowledgement`]); entries already handed to the client are
+    /// skipped so a replay never sends them twice.
+    async fn replay_outbox(
+        broker_id: String,
+        publisher: BrokerPublisher,
+        outbox: Arc<OutboundQueue>,
+        connected: Arc<std::sync::atomic::AtomicBool>,
+    ) {
+        let is_connected = || connected.load(std::sync::atomic::Ordering::SeqCst);
+        let in_flight = |seq| {
+            publisher
+                .tracker
+                .as_ref()
+                .is_some_and(|t| t.is_in_flight(seq))
+        };
+        let mut cursor = 0;
+
+        while is_connected() && outbox.try_begin_replay() {
+            let mut replayed = 0;
+            'drain: while is_connected() {
+                let batch = match outbox.peek_from(cursor, 100, chrono::Utc::now().timestamp()) {
+                    Ok(batch) if !batch.is_empty() => batch,
+                    Ok(_) => break,
+                    Err(e) => {
+                        error!("Failed to read MQTT outbox for {}: {}", broker_id, e);
+                        break;
+                    }
+                };
+                for (seq, msg) in batch {
+                    if !is_connected() {
+                        break 'drain;
+                    }
+                    if !in_flight(seq) {
+                        if let Err(e) = publisher.publish(Some(seq), msg).await {
+                            warn!("Replay to broker {} interrupted: {}", broker_id, e);
+                            break 'drain;
+                        }
+                        replayed += 1;
+                    }
+                    cursor = seq + 1;
+                }
+            }
+            outbox.end_replay();
+            if replayed > 0 {
+                info!(
+                    "Replayed {} buffered publishes to broker {}",
+                    replayed, broker_id
+                );
+            }
+
+            // Loop so that messages enqueued while the replay slot was held are not stranded
+            let stranded = outbox
+                .peek_from(cursor, 1, chrono::Utc::now().timestamp())
+                .is_ok_and(|rest| !rest.is_empty());
+            if !stranded {
+                break;
+            }
+        }
+    }
+
+    /// Ack outbox entries as the broker confirms them.
+    fn track_acknowledgement(
+        notification: &rumqttc::Event,
+        broker_id: &str,
+        outbox: &OutboundQueue,
+        tracker: &PublishTracker,
+    ) {
+        let done = match notification {
+            rumqttc::Event::Outgoing(rumqttc::Outgoing::Publish(pkid)) => {
+                tracker.on_outgoing(*pkid)
+            }
+            rumqttc::Event::Incoming(rumqttc::Packet::PubAck(ack)) => tracker.on_ack(ack.pkid),
+            rumqttc::Event::Incoming(rumqttc::Packet::PubComp(comp)) => tracker.on_ack(comp.pkid),
+            _ => None,
+        };
+        if let Some(seq) = done {
+            if let Err(e) = outbox.ack(seq) {
+                error!(
+                    "Failed to ack MQTT outbox entry {} for {}: {}",
+                    seq, broker_id, e
+                );
+            }
+        }
+    }
+
+    /// Forget unacknowledged publishes after the broker connection dropped.
+    ///
+    /// The replay on the next ConnAck resends every outbox entry the broker has not
+    /// confirmed, so the client's own retransmissions are discarded; their packet ids
+    /// would otherwise be matched against registrations the tracker no longer holds.
+    async fn reset_in_flight(eventloop: &mut rumqttc::EventLoop, tracker: &PublishTracker) {
+        let _order = tracker.lock_order().await;
+        // Also pick up requests handed to the client since the error
+        eventloop.clean();
+        eventloop
+            .pending
+            .retain(|request| !matches!(request, rumqttc::Request::Publish(_)));
+        tracker.reset();
+    }
+
+    /// Republish `DeviceMetric` events to all brokers under `prefix`.
+    async fn forward_telemetry(
+        prefix: String,
+        mut rx: neomind_core::EventBusReceiver,
+        mqtt_clients: Arc<RwLock<HashMap<String, MqttClientInner>>>,
+        outboxes: Arc<RwLock<HashMap<String, Arc<OutboundQueue>>>>,
+    ) {
+        while let Some((event, _)) = rx.recv().await {
+            let NeoMindEvent::DeviceMetric {
+                device_id,
+                metric,
+                value,
+                timestamp,
+                ..
+            } = event
+            else {
+                continue;
+            };
+            let payload = serde_json::json!({ "value": value, "timestamp": timestamp });
+            let msg = QueuedPublish::new(
+                format!("{}/{}/{}", prefix, device_id, metric),
+                payload.to_string(),
+                1,
+            )
+            .with_device(device_id);
+            if let Err(e) = Self::publish_all(&mqtt_clients, &outboxes, msg).await {
+                debug!("Failed to forward telemetry: {}", e);
+            }
+        }
+    }
+
     /// Update overall connection status based on connected brokers.
     async fn update_connection_status(&self) {
         let has_connected = !self.mqtt_clients.read().await.is_empty();
@@ -725,6 +1198,9 @@ impl MqttAdapter {
             adapter_type: "mqtt".to_string(),
             connection_config: crate::registry::ConnectionConfig::default(),
             adapter_id: Some(self.config.name.clone()),
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
 
         if let Err(e) = self
@@ -989,32 +1465,55 @@ impl MqttAdapter {
         // Or use default: {device_id}/command/{command}
         let device_type = self.device_types.read().await.get(device_id).cloned();
 
-        let topic = if let Some(dt) = device_type {
-            format!("device/{}/{}/downlink", dt, device_id)
+        let (topic, payload): (String, Vec<u8>) = if self.config.sparkplug.enabled
+            && self
+                .sparkplug_state
+                .read()
+                .await
+                .is_sparkplug_device(device_id)
+        {
+            // Sparkplug devices receive protobuf NCMD/DCMD built from the MDL command
+            let command_def = match &device_type {
+                Some(dt) => self
+                    .device_registry
+                    .read()
+                    .await
+                    .get_template(dt)
+                    .await
+                    .and_then(|t| t.commands.into_iter().find(|c| c.name == command)),
+                None => None,
+            };
+            self.sparkplug_state
+                .read()
+                .await
+                .encode_command(
+                    device_id,
+                    command_def.as_ref(),
+                    params,
+                    chrono::Utc::now().timestamp_millis(),
+                )
+                .map_err(|e| AdapterError::Communication(e.to_string()))?
         } else {
-            format!("{}/command/{}", device_id, command)
-        };
+            let topic = if let Some(dt) = device_type {
+                format!("device/{}/{}/downlink", dt, device_id)
+            } else {
+                format!("{}/command/{}", device_id, command)
+            };
 
-        // Build payload
-        let payload = serde_json::to_string(params).map_err(|e| {
-            AdapterError::Communication(format!("Failed to serialize params: {}", e))
-        })?;
+            // Build payload
+            let payload = serde_json::to_string(params).map_err(|e| {
+                AdapterError::Communication(format!("Failed to serialize params: {}", e))
+            })?;
+            (topic, payload.into_bytes())
+        };
 
         // Send to all connected brokers
         let mut last_error = None;
         let mut success_count = 0;
+        let msg = QueuedPublish::new(topic, payload, 1).with_device(device_id);
 
         for (broker_id, inner) in clients.iter() {
-            match inner
-                .client
-                .publish(
-                    topic.clone(),
-                    rumqttc::QoS::AtLeastOnce,
-                    false,
-                    payload.clone(),
-                )
-                .await
-            {
+            match Self::publish_or_queue(&self.outboxes, broker_id, inner, msg.clone()).await {
                 Ok(_) => {
                     success_count += 1;
                     info!(
@@ -1023,10 +1522,7 @@ impl MqttAdapter {
                     );
                 }
                 Err(e) => {
-                    last_error = Some(AdapterError::Communication(format!(
-                        "Failed to publish on {}: {}",
-                        broker_id, e
-                    )));
+                    last_error = Some(e);
                 }
             }
         }
@@ -1086,6 +1582,24 @@ impl DeviceAdapter for MqttAdapter {
         )
         .await?;
 
+        if let (Some(prefix), Some(bus)) = (&self.config.forward_telemetry_prefix, &self.event_bus)
+        {
+            let task = tokio::spawn(Self::forward_telemetry(
+                prefix.trim_end_matches('/').to_string(),
+                bus.subscribe(),
+                self.mqtt_clients.clone(),
+                self.outboxes.clone(),
+            ));
+            if let Some(old) = self
+                .telemetry_forwarder
+                .lock()
+                .ok()
+                .and_then(|mut t| t.replace(task))
+            {
+                old.abort();
+            }
+        }
+
         self.running
             .store(true, std::sync::atomic::Ordering::Relaxed);
         info!("MQTT adapter '{}' started", self.config.name);
@@ -1098,6 +1612,15 @@ impl DeviceAdapter for MqttAdapter {
         self.running
             .store(false, std::sync::atomic::Ordering::Relaxed);
 
+        if let Some(task) = self
+            .telemetry_forwarder
+            .lock()
+            .ok()
+            .and_then(|mut t| t.take())
+        {
+            task.abort();
+        }
+
         // Stop all broker connections
         let mut clients = self.mqtt_clients.write().await;
         for inner in clients.values() {
@@ -1380,17 +1903,47 @@ impl MqttAdapter {
             RwLock<HashMap<String, HashMap<String, (MetricValue, chrono::DateTime<chrono::Utc>)>>>,
         >,
         telemetry_storage: &Arc<RwLock<Option<Arc<TimeSeriesStorage>>>>,
-        _device_registry: &Arc<RwLock<Arc<DeviceRegistry>>>,
+        device_registry: &Arc<RwLock<Arc<DeviceRegistry>>>,
         _connection_status: &Arc<RwLock<ConnectionStatus>>,
         broker_id: &str,
         extractor: &Arc<UnifiedExtractor>,
         topic_to_device: &Arc<RwLock<HashMap<String, String>>>,
+        sparkplug_state: &Arc<RwLock<SparkplugState>>,
+        publisher: &BrokerPublisher,
     ) {
         match notification {
             rumqttc::Event::Incoming(rumqttc::Packet::Publish(publish)) => {
                 let topic = publish.topic.to_string();
                 let payload = publish.payload.to_vec();
 
+                // Sparkplug B payloads are protobuf and never go through the JSON paths below
+                if config.sparkplug.enabled
+                    && topic.starts_with(&format!("{}/", SPARKPLUG_NAMESPACE))
+                {
+                    Self::handle_sparkplug_message(
+                        &topic,
+                        &payload,
+                        config,
+                        event_tx,
+                        event_bus,
+                        device_types,
+                        metric_cache,
+                        telemetry_storage,
+                        device_registry,
+                        sparkplug_state,
+                        publisher,
+                    )
+                    .await;
+                    return;
+                }
+
+                // Our own forwarded telemetry, seen again through a wildcard subscription
+                if let Some(prefix) = &config.forward_telemetry_prefix {
+                    if topic.starts_with(&format!("{}/", prefix.trim_end_matches('/'))) {
+                        return;
+                    }
+                }
+
                 info!(
                     "Received MQTT message on topic: {}, payload length: {}",
                     topic,
@@ -1810,6 +2363,188 @@ impl MqttAdapter {
             _ => {}
         }
     }
+
+    /// Handle a Sparkplug B message: decode, update session state, register
+    /// devices from birth certificates and emit metrics / offline events.
+    async fn handle_sparkplug_message(
+        topic: &str,
+        payload: &[u8],
+        config: &MqttAdapterConfig,
+        event_tx: &broadcast::Sender<DeviceEvent>,
+        event_bus: &Option<Arc<EventBus>>,
+        device_types: &Arc<RwLock<HashMap<String, String>>>,
+        metric_cache: &Arc<
+            RwLock<HashMap<String, HashMap<String, (MetricValue, chrono::DateTime<chrono::Utc>)>>>,
+        >,
+        telemetry_storage: &Arc<RwLock<Option<Arc<TimeSeriesStorage>>>>,
+        device_registry: &Arc<RwLock<Arc<DeviceRegistry>>>,
+        sparkplug_state: &Arc<RwLock<SparkplugState>>,
+        publisher: &BrokerPublisher,
+    ) {
+        let parsed = match SparkplugTopic::parse(topic) {
+            Ok(t) => t,
+            Err(e) => {
+                debug!("Ignoring Sparkplug message: {}", e);
+                return;
+            }
+        };
+        let decoded = match SparkplugPayload::decode(payload) {
+            Ok(p) => p,
+            Err(e) => {
+                warn!("Failed to decode Sparkplug payload on {}: {}", topic, e);
+                return;
+            }
+        };
+
+        let now = chrono::Utc::now();
+        let events =
+            sparkplug_state
+                .write()
+                .await
+                .process(&parsed, &decoded, now.timestamp_millis());
+
+        for event in events {
+            match event {
+                SparkplugEvent::Birth {
+                    device_id,
+                    template,
+                    group_id,
+                    edge_node_id,
+                    sparkplug_device_id,
+                } => {
+                    let device_type = template.device_type.clone();
+                    device_types
+                        .write()
+                        .await
+                        .insert(device_id.clone(), device_type.clone());
+
+                    if config.sparkplug.auto_register {
+                        let registry = device_registry.read().await.clone();
+                        if let Err(e) = registry.register_template(template).await {
+                            warn!(
+                                "Failed to register Sparkplug device type {}: {}",
+                                device_type, e
+                            );
+                        } else if registry.get_device(&device_id).await.is_none() {
+                            let mut connection_config = ConnectionConfig::new();
+                            connection_config
+                                .extra
+                                .insert("sparkplug_group".into(), Value::String(group_id));
+                            connection_config
+                                .extra
+                                .insert("sparkplug_edge_node".into(), Value::String(edge_node_id));
+                            if let Some(device) = sparkplug_device_id {
+                                connection_config
+                                    .extra
+                                    .insert("sparkplug_device".into(), Value::String(device));
+                            }
+                            let device_config = DeviceConfig {
+                                device_id: device_id.clone(),
+                                name: device_id.clone(),
+                                device_type: device_type.clone(),
+                                adapter_type: "mqtt".to_string(),
+                                connection_config,
+                                adapter_id: Some(config.name.clone()),
+                                location: None,
+                                tags: Vec::new(),
+                                groups: Vec::new(),
+                            };
+                            if let Err(e) = registry.register_device(device_config).await {
+                                warn!("Failed to register Sparkplug device {}: {}", device_id, e);
+                            } else {
+                                info!(
+                                    "Registered Sparkplug device {} ({})",
+                                    device_id, device_type
+                                );
+                            }
+                        }
+                    }
+
+                    if let Some(bus) = event_bus {
+                        bus.publish(NeoMindEvent::DeviceOnline {
+                            device_id,
+                            device_type,
+                            timestamp: now.timestamp(),
+                        })
+                        .await;
+                    }
+                }
+                SparkplugEvent::Metric {
+                    device_id,
+                    name,
+                    value,
+                    timestamp,
+                } => {
+                    let ts = chrono::DateTime::from_timestamp_millis(timestamp).unwrap_or(now);
+                    metric_cache
+                        .write()
+                        .await
+                        .entry(device_id.clone())
+                        .or_default()
+                        .insert(name.clone(), (value.clone(), ts));
+
+                    if let Some(storage) = telemetry_storage.read().await.as_ref() {
+                        let data_point = crate::telemetry::DataPoint {
+                            timestamp: ts.timestamp(),
+                            value: value.clone(),
+                            quality: None,
+                        };
+                        if let Err(e) = storage.write(&device_id, &name, data_point).await {
+                            error!(
+                                "Failed to write telemetry for {}/{}: {}",
+                                device_id, name, e
+                            );
+                        }
+                    }
+
+                    let _ = event_tx.send(DeviceEvent::Metric {
+                        device_id,
+                        metric: name,
+                        value,
+                        timestamp: ts.timestamp(),
+                    });
+                }
+                SparkplugEvent::Offline { device_id } => {
+                    info!("Sparkplug device {} went offline", device_id);
+                    let _ = event_tx.send(DeviceEvent::State {
+                        device_id: device_id.clone(),
+                        old_state: ConnectionStatus::Connected,
+                        new_state: ConnectionStatus::Disconnected,
+                        timestamp: now.timestamp(),
+                    });
+                    if let Some(bus) = event_bus {
+                        bus.publish(NeoMindEvent::DeviceOffline {
+                            device_id,
+                            reason: Some("Sparkplug death certificate".to_string()),
+                            timestamp: now.timestamp(),
+                        })
+                        .await;
+                    }
+                }
+                SparkplugEvent::RebirthRequired {
+                    group_id,
+                    edge_node_id,
+                    reason,
+                } => {
+                    if !config.sparkplug.request_rebirth {
+                        continue;
+                    }
+                    info!(
+                        "Requesting Sparkplug rebirth from {}/{}: {}",
+                        group_id, edge_node_id, reason
+                    );
+                    let (cmd_topic, cmd_payload) =
+                        encode_rebirth_request(&group_id, &edge_node_id, now.timestamp_millis());
+                    if let Err(e) = publisher
+                        .publish(None, QueuedPublish::new(cmd_topic, cmd_payload, 0))
+                        .await
+                    {
+                        warn!("Failed to publish Sparkplug rebirth request: {}", e);
+                    }
+                }
+            }
+        }
+    }
 }
 
 /// Helper function to extract device ID from topic.
@@ -1896,6 +2631,15 @@ fn extract_device_type_from_topic(topic: &str) -> Option<String> {
     }
 }
 
+/// Map a numeric QoS level to the rumqttc enum (anything above 2 is clamped).
+pub(crate) fn qos_from_u8(qos: u8) -> rumqttc::QoS {
+    match qos {
+        0 => rumqttc::QoS::AtMostOnce,
+        1 => rumqttc::QoS::AtLeastOnce,
+        _ => rumqttc::QoS::ExactlyOnce,
+    }
+}
+
 /// Create an MQTT adapter connected to an event bus.
 pub fn create_mqtt_adapter(
     config: MqttAdapterConfig,
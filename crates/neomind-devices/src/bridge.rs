@@ -0,0 +1,750 @@
+//! MQTT bridge between the embedded broker and an upstream broker.
+//!
+//! A bridge keeps two MQTT client connections open — one to the local
+//! (embedded) broker and one to the upstream broker — and mirrors messages
+//! between them according to a list of [`BridgeRule`]s.
+//!
+//! ## Topic Remapping
+//!
+//! Topics on the upstream broker are namespaced by a prefix, by default
+//! `{gateway_id}/`:
+//!
+//! ```text
+//! local                         upstream
+//! sensors/t1/temp   ── out ──→  gw-17/sensors/t1/temp
+//! cmd/lamp1         ←── in ───  gw-17/cmd/lamp1
+//! ```
+//!
+//! ## Loop Prevention
+//!
+//! The upstream connection speaks MQTT 5. Every message the bridge mirrors
+//! upstream carries an [`ORIGIN_PROPERTY`] user property with the bridge id, and
+//! upstream subscriptions set the no-local option, so the upstream broker never
+//! hands the bridge its own publishes back; a tagged message that arrives anyway
+//! (brokers that ignore no-local) is dropped. The embedded broker speaks MQTT
+//! 3.1.1 and cannot carry the marker, so a message mirrored down on a topic that
+//! overlapping rules also mirror up comes back over the local subscription and is
+//! mirrored up once, tagged, where it stops.
+//!
+//! ## Reconnect
+//!
+//! Each side reconnects independently with exponential backoff between
+//! `reconnect_min_secs` and `reconnect_max_secs`, re-subscribing on every
+//! successful connection.
+//!
+//! ## TLS
+//!
+//! An endpoint with [`BridgeTls`] connects over TLS, verifying the server against
+//! the configured CA certificate (or the platform roots when none is set) and
+//! presenting a client certificate for mutual TLS when one is configured.
+
+use crate::adapters::mqtt::qos_from_u8;
+use neomind_storage::{BridgeDirection, BridgeRule, BrokerBridge};
+use rumqttc::v5::mqttbytes::v5::{Filter, Packet as V5Packet, PublishProperties};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+/// User property that marks a message as mirrored by a bridge; the value is the bridge id.
+pub const ORIGIN_PROPERTY: &str = "neomind-bridge";
+
+/// Connection parameters for one side of the bridge.
+#[derive(Debug, Clone)]
+pub struct BridgeEndpoint {
+    pub host: String,
+    pub port: u16,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    /// Connect over TLS (plain TCP when unset)
+    pub tls: Option<BridgeTls>,
+}
+
+/// TLS settings for a bridge endpoint (PEM encoded).
+#[derive(Debug, Clone, Default)]
+pub struct BridgeTls {
+    /// CA certificate to verify the server with; platform roots when unset
+    pub ca_cert: Option<String>,
+    /// Client certificate and private key for mutual TLS (requires `ca_cert`)
+    pub client_auth: Option<(String, String)>,
+}
+
+impl BridgeEndpoint {
+    pub fn new(host: impl Into<String>, port: u16) -> Self {
+        Self {
+            host: host.into(),
+            port,
+            username: None,
+            password: None,
+            tls: None,
+        }
+    }
+
+    pub fn with_auth(mut self, username: Option<String>, password: Option<String>) -> Self {
+        self.username = username;
+        self.password = password;
+        self
+    }
+
+    pub fn with_tls(mut self, tls: BridgeTls) -> Self {
+        self.tls = Some(tls);
+        self
+    }
+}
+
+/// Bridge health, reported through `/api/brokers`.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BridgeStatus {
+    pub id: String,
+    pub running: bool,
+    pub local_connected: bool,
+    pub remote_connected: bool,
+    /// Messages mirrored local → upstream
+    pub forwarded_out: u64,
+    /// Messages mirrored upstream → local
+    pub forwarded_in: u64,
+    /// Messages dropped by loop prevention
+    pub loops_suppressed: u64,
+    /// Reconnect attempts on either side
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+    /// Unix seconds of the last forwarded message
+    pub last_forward_at: Option<i64>,
+}
+
+/// Side of the bridge a message was seen on or published to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum BridgeSide {
+    Local,
+    Remote,
+}
+
+/// Resolved topic mapping for a bridge.
+#[derive(Debug, Clone)]
+pub struct TopicMapper {
+    prefix: String,
+    rules: Vec<BridgeRule>,
+}
+
+impl TopicMapper {
+    pub fn new(config: &BrokerBridge) -> Self {
+        let mut prefix = config
+            .remote_prefix
+            .clone()
+            .unwrap_or_else(|| config.gateway_id.clone());
+        if !prefix.is_empty() && !prefix.ends_with('/') {
+            prefix.push('/');
+        }
+        Self {
+            prefix,
+            rules: config.rules.clone(),
+        }
+    }
+
+    /// Upstream topic prefix (e.g. `gw-17/`).
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    /// Topic filters to subscribe to on the given side.
+    pub fn subscriptions(&self, side: BridgeSide) -> Vec<(String, u8)> {
+        self.rules
+            .iter()
+            .filter(|r| match side {
+                BridgeSide::Local => r.direction != BridgeDirection::In,
+                BridgeSide::Remote => r.direction != BridgeDirection::Out,
+            })
+            .map(|r| match side {
+                BridgeSide::Local => (r.topic.clone(), r.qos),
+                BridgeSide::Remote => (format!("{}{}", self.prefix, r.topic), r.qos),
+            })
+            .collect()
+    }
+
+    /// Map a local topic to its upstream topic and QoS, if an outbound rule matches.
+    pub fn map_outbound(&self, local_topic: &str) -> Option<(String, u8)> {
+        self.find_rule(local_topic, BridgeDirection::Out)
+            .map(|rule| (format!("{}{}", self.prefix, local_topic), rule.qos))
+    }
+
+    /// Map an upstream topic to its local topic and QoS, if an inbound rule matches.
+    pub fn map_inbound(&self, remote_topic: &str) -> Option<(String, u8)> {
+        let local_topic = remote_topic.strip_prefix(&self.prefix)?;
+        self.find_rule(local_topic, BridgeDirection::In)
+            .map(|rule| (local_topic.to_string(), rule.qos))
+    }
+
+    fn find_rule(&self, local_topic: &str, direction: BridgeDirection) -> Option<&BridgeRule> {
+        self.rules.iter().find(|r| {
+            (r.direction == direction || r.direction == BridgeDirection::Both)
+                && topic_matches(&r.topic, local_topic)
+                && !r.exclude.iter().any(|ex| topic_matches(ex, local_topic))
+        })
+    }
+}
+
+/// MQTT topic filter matching with `+` and `#` wildcards.
+pub fn topic_matches(filter: &str, topic: &str) -> bool {
+    let mut filter_parts = filter.split('/');
+    let mut topic_parts = topic.split('/');
+    loop {
+        match (filter_parts.next(), topic_parts.next()) {
+            (Some("#"), _) => return true,
+            (Some("+"), Some(_)) => {}
+            (Some(f), Some(t)) if f == t => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+/// Client for one side of the bridge: MQTT 3.1.1 towards the embedded broker,
+/// MQTT 5 upstream.
+#[derive(Clone)]
+enum SideClient {
+    Local(rumqttc::AsyncClient),
+    Remote(rumqttc::v5::AsyncClient),
+}
+
+impl SideClient {
+    async fn subscribe(&self, filter: &str, qos: u8) -> Result<(), String> {
+        match self {
+            Self::Local(client) => client
+                .subscribe(filter, qos_from_u8(qos))
+                .await
+                .map_err(|e| e.to_string()),
+            Self::Remote(client) => {
+                let filter = Filter {
+                    nolocal: true,
+                    ..Filter::new(filter, v5_qos(qos))
+                };
+                client
+                    .subscribe_many([filter])
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    /// Publish a mirrored message, tagged with the bridge id where the protocol allows.
+    async fn publish(
+        &self,
+        topic: String,
+        qos: u8,
+        retain: bool,
+        payload: Vec<u8>,
+        origin: &str,
+    ) -> Result<(), String> {
+        match self {
+            Self::Local(client) => client
+                .publish(topic, qos_from_u8(qos), retain, payload)
+                .await
+                .map_err(|e| e.to_string()),
+            Self::Remote(client) => {
+                let properties = PublishProperties {
+                    user_properties: vec![(ORIGIN_PROPERTY.to_string(), origin.to_string())],
+                    ..Default::default()
+                };
+                client
+                    .publish_with_properties(topic, v5_qos(qos), retain, payload, properties)
+                    .await
+                    .map_err(|e| e.to_string())
+            }
+        }
+    }
+
+    async fn disconnect(&self) {
+        let _ = match self {
+            Self::Local(client) => client.disconnect().await.map_err(|e| e.to_string()),
+            Self::Remote(client) => client.disconnect().await.map_err(|e| e.to_string()),
+        };
+    }
+}
+
+/// Event loop for one side of the bridge.
+enum SideLoop {
+    Local(Box<rumqttc::EventLoop>),
+    Remote(Box<rumqttc::v5::EventLoop>),
+}
+
+/// What a side's event loop reported.
+enum SideEvent {
+    Connected,
+    Message {
+        topic: String,
+        payload: Vec<u8>,
+        retain: bool,
+        /// Bridge that mirrored the message, from [`ORIGIN_PROPERTY`]
+        origin: Option<String>,
+    },
+    Other,
+}
+
+impl SideLoop {
+    async fn poll(&mut self) -> Result<SideEvent, String> {
+        Ok(match self {
+            Self::Local(eventloop) => match eventloop.poll().await.map_err(|e| e.to_string())? {
+                rumqttc::Event::Incoming(rumqttc::Packet::ConnAck(_)) => SideEvent::Connected,
+                rumqttc::Event::Incoming(rumqttc::Packet::Publish(publish)) => SideEvent::Message {
+                    topic: publish.topic,
+                    payload: publish.payload.to_vec(),
+                    retain: publish.retain,
+                    origin: None,
+                },
+                _ => SideEvent::Other,
+            },
+            Self::Remote(eventloop) => match eventloop.poll().await.map_err(|e| e.to_string())? {
+                rumqttc::v5::Event::Incoming(V5Packet::ConnAck(_)) => SideEvent::Connected,
+                rumqttc::v5::Event::Incoming(V5Packet::Publish(publish)) => SideEvent::Message {
+                    topic: String::from_utf8_lossy(&publish.topic).into_owned(),
+                    payload: publish.payload.to_vec(),
+                    retain: publish.retain,
+                    origin: origin(publish.properties.as_ref()).map(str::to_string),
+                },
+                _ => SideEvent::Other,
+            },
+        })
+    }
+}
+
+/// Bridge id in a message's [`ORIGIN_PROPERTY`], if another bridge mirrored it.
+fn origin(properties: Option<&PublishProperties>) -> Option<&str> {
+    properties?
+        .user_properties
+        .iter()
+        .find(|(key, _)| key == ORIGIN_PROPERTY)
+        .map(|(_, value)| value.as_str())
+}
+
+fn v5_qos(qos: u8) -> rumqttc::v5::mqttbytes::QoS {
+    match qos {
+        0 => rumqttc::v5::mqttbytes::QoS::AtMostOnce,
+        1 => rumqttc::v5::mqttbytes::QoS::AtLeastOnce,
+        _ => rumqttc::v5::mqttbytes::QoS::ExactlyOnce,
+    }
+}
+
+/// A running bridge between the embedded broker and an upstream broker.
+pub struct MqttBridge {
+    id: String,
+    config: BrokerBridge,
+    mapper: TopicMapper,
+    local: BridgeEndpoint,
+    remote: BridgeEndpoint,
+    status: Arc<RwLock<BridgeStatus>>,
+    running: Arc<AtomicBool>,
+    tasks: Mutex<Vec<JoinHandle<()>>>,
+}
+
+impl MqttBridge {
+    /// Create a bridge. Call [`MqttBridge::start`] to connect.
+    pub fn new(
+        id: impl Into<String>,
+        config: BrokerBridge,
+        local: BridgeEndpoint,
+        remote: BridgeEndpoint,
+    ) -> Self {
+        let id = id.into();
+        Self {
+            mapper: TopicMapper::new(&config),
+            status: Arc::new(RwLock::new(BridgeStatus {
+                id: id.clone(),
+                ..Default::default()
+            })),
+            id,
+            config,
+            local,
+            remote,
+            running: Arc::new(AtomicBool::new(false)),
+            tasks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Current health snapshot.
+    pub async fn status(&self) -> BridgeStatus {
+        self.status.read().await.clone()
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::Relaxed)
+    }
+
+    /// Connect both sides and start mirroring.
+    pub async fn start(&self) {
+        if self.running.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.status.write().await.running = true;
+
+        let (local_client, local_loop) = Self::client(
+            BridgeSide::Local,
+            &self.local,
+            &format!("{}-local", self.id),
+        );
+        let (remote_client, remote_loop) = Self::client(
+            BridgeSide::Remote,
+            &self.remote,
+            &format!("{}-remote", self.id),
+        );
+
+        let backoff = (
+            Duration::from_secs(self.config.reconnect_min_secs.max(1)),
+            Duration::from_secs(self.config.reconnect_max_secs.max(1)),
+        );
+
+        let mut tasks = Vec::with_capacity(2);
+        for (side, eventloop, own, peer) in [
+            (
+                BridgeSide::Local,
+                local_loop,
+                local_client.clone(),
+                remote_client.clone(),
+            ),
+            (BridgeSide::Remote, remote_loop, remote_client, local_client),
+        ] {
+            tasks.push(tokio::spawn(Self::run_side(
+                side,
+                eventloop,
+                own,
+                peer,
+                self.id.clone(),
+                self.mapper.clone(),
+                self.status.clone(),
+                self.running.clone(),
+                backoff,
+            )));
+        }
+        if let Ok(mut current) = self.tasks.lock() {
+            for stale in current.drain(..) {
+                stale.abort();
+            }
+            *current = tasks;
+        }
+
+        info!(
+            "MQTT bridge '{}' started ({}:{} <-> {}:{}, prefix '{}')",
+            self.id,
+            self.local.host,
+            self.local.port,
+            self.remote.host,
+            self.remote.port,
+            self.mapper.prefix()
+        );
+    }
+
+    /// Disconnect both sides and stop mirroring.
+    pub async fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+        let tasks = self
+            .tasks
+            .lock()
+            .map(|mut t| std::mem::take(&mut *t))
+            .unwrap_or_default();
+        // Dropping the event loops closes both connections
+        for task in tasks {
+            task.abort();
+        }
+        let mut status = self.status.write().await;
+        status.running = false;
+        status.local_connected = false;
+        status.remote_connected = false;
+        info!("MQTT bridge '{}' stopped", self.id);
+    }
+
+    fn client(
+        side: BridgeSide,
+        endpoint: &BridgeEndpoint,
+        client_id: &str,
+    ) -> (SideClient, SideLoop) {
+        let client_id = format!("neomind-bridge-{}", client_id);
+        let transport = endpoint.tls.as_ref().map(|tls| match &tls.ca_cert {
+            Some(ca) => rumqttc::Transport::tls(
+                ca.clone().into_bytes(),
+                tls.client_auth
+                    .clone()
+                    .map(|(cert, key)| (cert.into_bytes(), key.into_bytes())),
+                None,
+            ),
+            None => rumqttc::Transport::tls_with_default_config(),
+        });
+        let credentials = endpoint.username.clone().zip(endpoint.password.clone());
+
+        match side {
+            BridgeSide::Local => {
+                let mut options =
+                    rumqttc::MqttOptions::new(client_id, &endpoint.host, endpoint.port);
+                options.set_keep_alive(Duration::from_secs(30));
+                options.set_max_packet_size(10 * 1024 * 1024, 10 * 1024 * 1024);
+                if let Some((user, pass)) = credentials {
+                    options.set_credentials(user, pass);
+                }
+                if let Some(transport) = transport {
+                    options.set_transport(transport);
+                }
+                let (client, eventloop) = rumqttc::AsyncClient::new(options, 100);
+                (
+                    SideClient::Local(client),
+                    SideLoop::Local(Box::new(eventloop)),
+                )
+            }
+            BridgeSide::Remote => {
+                let mut options =
+                    rumqttc::v5::MqttOptions::new(client_id, &endpoint.host, endpoint.port);
+                options.set_keep_alive(Duration::from_secs(30));
+                options.set_max_packet_size(Some(10 * 1024 * 1024));
+                if let Some((user, pass)) = credentials {
+                    options.set_credentials(user, pass);
+                }
+                if let Some(transport) = transport {
+                    options.set_transport(transport);
+                }
+                let (client, eventloop) = rumqttc::v5::AsyncClient::new(options, 100);
+                (
+                    SideClient::Remote(client),
+                    SideLoop::Remote(Box::new(eventloop)),
+                )
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn run_side(
+        side: BridgeSide,
+        mut eventloop: SideLoop,
+        own: SideClient,
+        peer: SideClient,
+        bridge_id: String,
+        mapper: TopicMapper,
+        status: Arc<RwLock<BridgeStatus>>,
+        running: Arc<AtomicBool>,
+        (min_backoff, max_backoff): (Duration, Duration),
+    ) {
+        let mut backoff = min_backoff;
+
+        while running.load(Ordering::Relaxed) {
+            match eventloop.poll().await {
+                Ok(SideEvent::Connected) => {
+                    backoff = min_backoff;
+                    {
+                        let mut s = status.write().await;
+                        match side {
+                            BridgeSide::Local => s.local_connected = true,
+                            BridgeSide::Remote => s.remote_connected = true,
+                        }
+                    }
+                    for (filter, qos) in mapper.subscriptions(side) {
+                        if let Err(e) = own.subscribe(&filter, qos).await {
+                            warn!(
+                                "Bridge failed to subscribe to {} ({:?}): {}",
+                                filter, side, e
+                            );
+                        }
+                    }
+                    debug!("Bridge {:?} side connected", side);
+                }
+                Ok(SideEvent::Message {
+                    topic,
+                    payload,
+                    retain,
+                    origin,
+                }) => {
+                    if origin.as_deref() == Some(bridge_id.as_str()) {
+                        status.write().await.loops_suppressed += 1;
+                        continue;
+                    }
+
+                    let mapped = match side {
+                        BridgeSide::Local => mapper.map_outbound(&topic),
+                        BridgeSide::Remote => mapper.map_inbound(&topic),
+                    };
+                    let Some((target, qos)) = mapped else {
+                        continue;
+                    };
+
+                    match peer
+                        .publish(target.clone(), qos, retain, payload, &bridge_id)
+                        .await
+                    {
+                        Ok(()) => {
+                            let mut s = status.write().await;
+                            match side {
+                                BridgeSide::Local => s.forwarded_out += 1,
+                                BridgeSide::Remote => s.forwarded_in += 1,
+                            }
+                            s.last_forward_at = Some(chrono::Utc::now().timestamp());
+                        }
+                        Err(e) => {
+                            warn!("Bridge failed to forward {} -> {}: {}", topic, target, e);
+                            status.write().await.last_error = Some(e);
+                        }
+                    }
+                }
+                Ok(SideEvent::Other) => {}
+                Err(e) => {
+                    {
+                        let mut s = status.write().await;
+                        match side {
+                            BridgeSide::Local => s.local_connected = false,
+                            BridgeSide::Remote => s.remote_connected = false,
+                        }
+                        s.reconnects += 1;
+                        s.last_error = Some(format!("{:?}: {}", side, e));
+                    }
+                    warn!(
+                        "Bridge {:?} connection error, retrying in {:?}: {}",
+                        side, backoff, e
+                    );
+                    tokio::time::sleep(backoff).await;
+                    backoff = (backoff * 2).min(max_backoff);
+                }
+            }
+        }
+
+        own.disconnect().await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> BrokerBridge {
+        BrokerBridge {
+            enabled: true,
+            gateway_id: "gw-17".to_string(),
+            remote_prefix: None,
+            rules: vec![
+                BridgeRule {
+                    topic: "sensors/#".to_string(),
+                    direction: BridgeDirection::Out,
+                    qos: 1,
+                    exclude: vec!["sensors/debug/#".to_string()],
+                },
+                BridgeRule {
+                    topic: "cmd/+".to_string(),
+                    direction: BridgeDirection::In,
+                    qos: 1,
+                    exclude: Vec::new(),
+                },
+                BridgeRule {
+                    topic: "shared/#".to_string(),
+                    direction: BridgeDirection::Both,
+                    qos: 0,
+                    exclude: Vec::new(),
+                },
+            ],
+            reconnect_min_secs: 1,
+            reconnect_max_secs: 60,
+        }
+    }
+
+    #[test]
+    fn test_endpoint_transport() {
+        let plain = BridgeEndpoint::new("localhost", 1883);
+        let (_, SideLoop::Local(eventloop)) =
+            MqttBridge::client(BridgeSide::Local, &plain, "plain")
+        else {
+            panic!("expected an MQTT 3.1.1 local client");
+        };
+        assert!(matches!(
+            eventloop.mqtt_options.transport(),
+            rumqttc::Transport::Tcp
+        ));
+
+        let tls = BridgeEndpoint::new("upstream", 8883).with_tls(BridgeTls {
+            ca_cert: Some("-----BEGIN CERTIFICATE-----".to_string()),
+            client_auth: None,
+        });
+        let (_, SideLoop::Remote(eventloop)) = MqttBridge::client(BridgeSide::Remote, &tls, "tls")
+        else {
+            panic!("expected an MQTT 5 upstream client");
+        };
+        assert!(matches!(
+            eventloop.options.transport(),
+            rumqttc::Transport::Tls(_)
+        ));
+    }
+
+    #[test]
+    fn test_topic_matches() {
+        assert!(topic_matches("sensors/#", "sensors/a/b"));
+        assert!(topic_matches("sensors/+/temp", "sensors/a/temp"));
+        assert!(!topic_matches("sensors/+/temp", "sensors/a/b/temp"));
+        assert!(topic_matches("#", "anything"));
+        assert!(!topic_matches("cmd/+", "cmd"));
+    }
+
+    #[test]
+    fn test_mapping_and_excludes() {
+        let mapper = TopicMapper::new(&config());
+        assert_eq!(
+            mapper.map_outbound("sensors/t1/temp"),
+            Some(("gw-17/sensors/t1/temp".to_string(), 1))
+        );
+        assert_eq!(mapper.map_outbound("sensors/debug/x"), None);
+        assert_eq!(mapper.map_outbound("cmd/lamp"), None);
+        assert_eq!(
+            mapper.map_inbound("gw-17/cmd/lamp"),
+            Some(("cmd/lamp".to_string(), 1))
+        );
+        assert_eq!(mapper.map_inbound("gw-18/cmd/lamp"), None);
+        assert!(mapper.map_inbound("gw-17/shared/x").is_some());
+        assert!(mapper.map_outbound("shared/x").is_some());
+
+        let remote: Vec<_> = mapper
+            .subscriptions(BridgeSide::Remote)
+            .into_iter()
+            .map(|(t, _)| t)
+            .collect();
+        assert_eq!(remote, vec!["gw-17/cmd/+", "gw-17/shared/#"]);
+    }
+
+    #[test]
+    fn test_origin_marker() {
+        let tagged = PublishProperties {
+            user_properties: vec![
+                ("trace".to_string(), "abc".to_string()),
+                (ORIGIN_PROPERTY.to_string(), "gw-17".to_string()),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(origin(Some(&tagged)), Some("gw-17"));
+        assert_eq!(origin(Some(&PublishProperties::default())), None);
+        assert_eq!(origin(None), None);
+    }
+
+    #[tokio::test]
+    async fn test_stop_ends_forwarding_tasks() {
+        let unreachable = || BridgeEndpoint::new("127.0.0.1", 1);
+        let bridge = MqttBridge::new("b1", config(), unreachable(), unreachable());
+
+        for _ in 0..2 {
+            bridge.start().await;
+            let tasks: Vec<_> = bridge
+                .tasks
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|t| t.abort_handle())
+                .collect();
+            assert_eq!(tasks.len(), 2);
+
+            bridge.stop().await;
+            tokio::time::timeout(Duration::from_secs(5), async {
+                while !tasks.iter().all(|t| t.is_finished()) {
+                    tokio::task::yield_now().await;
+                }
+            })
+            .await
+            .expect("forwarding tasks still running after stop");
+            assert!(!bridge.status().await.running);
+        }
+    }
+}
@@ -22,11 +22,14 @@
 //! Devices are configured using `DeviceConfig` and accessed through `DeviceService`.
 //! Protocol adapters are registered as plugins for unified management.
 
+pub mod bridge;
 pub mod discovery;
 pub mod mdl;
 pub mod mdl_format;
 pub mod mqtt;
 pub mod mqtt_v2;
+pub mod sparkplug;
+pub mod store_forward;
 pub mod telemetry;
 
 // Simplified device management
@@ -71,13 +74,19 @@ pub use mdl_format::{
 };
 
 // New architecture exports
+pub use bridge::{BridgeEndpoint, BridgeStatus, BridgeTls, MqttBridge};
 pub use discovery::{DeviceDiscovery, DiscoveredDevice, DiscoveryResult};
 pub use registry::{
-    ConnectionConfig, DeviceConfig, DeviceRegistry, DeviceTypeMode, DeviceTypeTemplate,
+    ConnectionConfig, DeviceConfig, DeviceLocation, DeviceRegistry, DeviceSelector, DeviceTypeMode,
+    DeviceTypeTemplate,
 };
 pub use service::{
     AdapterInfo, AdapterStats, CommandHistoryRecord, CommandStatus, DeviceHealth, DeviceService,
-    DeviceStatus, HeartbeatConfig,
+    DeviceShadow, DeviceStatus, HeartbeatConfig,
+};
+pub use sparkplug::{SparkplugConfig, SparkplugPayload, SparkplugState, SparkplugTopic};
+pub use store_forward::{
+    OutboundQueue, OutboxStats, PublishTracker, QueuedPublish, StoreForwardConfig,
 };
 pub use telemetry::{AggregatedData, DataPoint, MetricCache, TimeSeriesStorage};
 
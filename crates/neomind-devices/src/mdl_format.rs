@@ -70,6 +70,7 @@ use std::sync::{Arc, Mutex as StdMutex};
 use tokio::sync::RwLock;
 
 use super::mdl::{DeviceError, MetricDataType, MetricValue};
+use neomind_storage::snapshot::begin_write;
 
 /// Custom deserializer for `default_value` in `ParameterDefinition`
 ///
@@ -1044,9 +1045,7 @@ impl MdlStorage {
 
     /// Ensure all required tables exist
     fn ensure_tables(&self) -> Result<(), DeviceError> {
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))?;
 
         // Create tables if they don't exist (redb creates them on first open_table)
@@ -1094,9 +1093,7 @@ impl MdlStorage {
         let value =
             serde_json::to_vec(def).map_err(|e| DeviceError::Serialization(e.to_string()))?;
 
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))?;
 
         {
@@ -1146,9 +1143,7 @@ impl MdlStorage {
     pub async fn delete(&self, device_type: &str) -> Result<(), DeviceError> {
         let key = self.key(device_type);
 
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))?;
 
         {
@@ -1234,9 +1229,7 @@ impl MdlStorage {
         let value =
             serde_json::to_vec(instance).map_err(|e| DeviceError::Serialization(e.to_string()))?;
 
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))?;
 
         {
@@ -1286,9 +1279,7 @@ impl MdlStorage {
     pub async fn delete_device_instance(&self, device_id: &str) -> Result<(), DeviceError> {
         let key = format!("device:{}", device_id);
 
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))?;
 
         {
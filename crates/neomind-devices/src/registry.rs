@@ -29,6 +29,7 @@ use super::mdl::MetricValue;
 use super::mdl_format::{CommandDefinition, MetricDefinition, ParameterDefinition};
 
 // Storage types conversion
+pub use neomind_storage::device_registry::DeviceLocation;
 use neomind_storage::device_registry::{
     CommandDefinition as StorageCommandDefinition,
     DeviceConfig as StorageConfig,
@@ -237,6 +238,121 @@ pub struct DeviceConfig {
     /// Adapter/Plugin ID that manages this device
     #[serde(skip_serializing_if = "Option::is_none")]
     pub adapter_id: Option<String>,
+    /// Physical location (site → building → floor → room)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<DeviceLocation>,
+    /// Free-form tags (e.g. "freezer", "outdoor")
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Static groups this device belongs to
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+}
+
+/// Selects devices by group, tags and location.
+///
+/// All set criteria must match; an empty selector matches every device.
+/// The string form is a comma-separated list of `group:<name>`, `tag:<name>`
+/// and `location:<site/building/floor/room>` terms, e.g. `group:freezers,location:store-17`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DeviceSelector {
+    /// Static group the device must belong to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    /// Tags the device must all carry
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// Location the device must lie within
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub location: Option<DeviceLocation>,
+}
+
+impl DeviceSelector {
+    /// Select the members of a static group.
+    pub fn group(name: impl Into<String>) -> Self {
+        Self {
+            group: Some(name.into()),
+            ..Default::default()
+        }
+    }
+
+    /// Select devices carrying a tag.
+    pub fn tag(name: impl Into<String>) -> Self {
+        Self {
+            tags: vec![name.into()],
+            ..Default::default()
+        }
+    }
+
+    /// Select devices within a location.
+    pub fn location(location: DeviceLocation) -> Self {
+        Self {
+            location: Some(location),
+            ..Default::default()
+        }
+    }
+
+    /// Parse the string form (see type docs).
+    pub fn parse(input: &str) -> Result<Self, DeviceError> {
+        let mut selector = Self::default();
+        for term in input.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+            let (kind, value) = term.split_once(':').ok_or_else(|| {
+                DeviceError::InvalidParameter(format!("Invalid device selector term: {}", term))
+            })?;
+            let value = value.trim().to_string();
+            match kind.trim() {
+                "group" => selector.group = Some(value),
+                "tag" => selector.tags.push(value),
+                "location" => selector.location = Some(DeviceLocation::from_path(&value)),
+                other => {
+                    return Err(DeviceError::InvalidParameter(format!(
+                        "Unknown device selector kind: {}",
+                        other
+                    )))
+                }
+            }
+        }
+        Ok(selector)
+    }
+
+    /// Whether no criteria are set.
+    pub fn is_empty(&self) -> bool {
+        self.group.is_none()
+            && self.tags.is_empty()
+            && self.location.as_ref().is_none_or(DeviceLocation::is_empty)
+    }
+
+    /// Whether a device matches all criteria.
+    pub fn matches(&self, device: &DeviceConfig) -> bool {
+        if let Some(group) = &self.group {
+            if !device.groups.contains(group) {
+                return false;
+            }
+        }
+        if !self.tags.iter().all(|t| device.tags.contains(t)) {
+            return false;
+        }
+        match (&self.location, &device.location) {
+            (Some(wanted), Some(actual)) => wanted.contains(actual),
+            (Some(wanted), None) => wanted.is_empty(),
+            (None, _) => true,
+        }
+    }
+}
+
+impl std::fmt::Display for DeviceSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut terms = Vec::new();
+        if let Some(group) = &self.group {
+            terms.push(format!("group:{}", group));
+        }
+        terms.extend(self.tags.iter().map(|t| format!("tag:{}", t)));
+        if let Some(location) = self.location.as_ref().filter(|l| !l.is_empty()) {
+            terms.push(format!("location:{}", location.path()));
+        }
+        write!(f, "{}", terms.join(","))
+    }
 }
 
 /// Unified connection configuration for different protocols
@@ -524,6 +640,9 @@ impl DeviceRegistry {
                 adapter_type: storage_device.adapter_type,
                 connection_config: convert_connection_config(storage_device.connection_config),
                 adapter_id: storage_device.adapter_id,
+                location: storage_device.location,
+                tags: storage_device.tags,
+                groups: storage_device.groups,
             };
 
             let device_id = config.device_id.clone();
@@ -655,6 +774,9 @@ impl DeviceRegistry {
                     device.connection_config.clone(),
                 ),
                 adapter_id: device.adapter_id.clone(),
+                location: device.location.clone(),
+                tags: device.tags.clone(),
+                groups: device.groups.clone(),
             };
             store
                 .save_device(&storage_config)
@@ -910,6 +1032,9 @@ impl DeviceRegistry {
                     config.connection_config.clone(),
                 ),
                 adapter_id: config.adapter_id.clone(),
+                location: config.location.clone(),
+                tags: config.tags.clone(),
+                groups: config.groups.clone(),
             })
         } else {
             None
@@ -955,6 +1080,30 @@ impl DeviceRegistry {
         devices.values().cloned().collect()
     }
 
+    /// List devices matching a selector
+    pub async fn select_devices(&self, selector: &DeviceSelector) -> Vec<DeviceConfig> {
+        let devices = self.devices.read().await;
+        devices
+            .values()
+            .filter(|d| selector.matches(d))
+            .cloned()
+            .collect()
+    }
+
+    /// IDs of devices matching a selector, for synchronous callers.
+    ///
+    /// Returns `None` if the registry is currently locked for writing.
+    pub fn try_select_device_ids(&self, selector: &DeviceSelector) -> Option<Vec<String>> {
+        let devices = self.devices.try_read().ok()?;
+        Some(
+            devices
+                .values()
+                .filter(|d| selector.matches(d))
+                .map(|d| d.device_id.clone())
+                .collect(),
+        )
+    }
+
     /// Find a device by its telemetry topic
     /// This is used by MQTT adapters to route messages from custom topics
     pub async fn find_device_by_telemetry_topic(
@@ -1056,6 +1205,9 @@ impl DeviceRegistry {
                     config.connection_config.clone(),
                 ),
                 adapter_id: config.adapter_id.clone(),
+                location: config.location.clone(),
+                tags: config.tags.clone(),
+                groups: config.groups.clone(),
             })
         } else {
             None
@@ -1184,6 +1336,9 @@ mod tests {
             adapter_type: "mqtt".to_string(),
             connection_config: ConnectionConfig::mqtt("sensors/sensor1/data", None::<String>),
             adapter_id: None,
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
 
         registry.register_device(config).await.unwrap();
@@ -1204,6 +1359,9 @@ mod tests {
             adapter_type: "mqtt".to_string(),
             connection_config: ConnectionConfig::new(),
             adapter_id: None,
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
 
         let result = registry.register_device(config).await;
@@ -1227,6 +1385,9 @@ mod tests {
                 adapter_type: "mqtt".to_string(),
                 connection_config: ConnectionConfig::new(),
                 adapter_id: None,
+                location: None,
+                tags: Vec::new(),
+                groups: Vec::new(),
             };
             registry.register_device(config).await.unwrap();
         }
@@ -1250,6 +1411,9 @@ mod tests {
             adapter_type: "mqtt".to_string(),
             connection_config: ConnectionConfig::new(),
             adapter_id: None,
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
         registry.register_device(config).await.unwrap();
 
@@ -1258,6 +1422,84 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[tokio::test]
+    async fn test_select_devices_by_group_tag_and_location() {
+        let registry = DeviceRegistry::new();
+        registry
+            .register_template(DeviceTypeTemplate::new("freezer", "Freezer"))
+            .await
+            .unwrap();
+
+        for (id, floor, tags, groups) in [
+            ("f1", "2", vec!["cold"], vec!["freezers"]),
+            ("f2", "3", vec!["cold", "backup"], vec!["freezers"]),
+            ("f3", "2", vec![], vec![]),
+        ] {
+            let config = DeviceConfig {
+                device_id: id.to_string(),
+                name: id.to_string(),
+                device_type: "freezer".to_string(),
+                adapter_type: "mqtt".to_string(),
+                connection_config: ConnectionConfig::new(),
+                adapter_id: None,
+                location: Some(DeviceLocation::from_path(&format!(
+                    "store-17/main/{}",
+                    floor
+                ))),
+                tags: tags.into_iter().map(String::from).collect(),
+                groups: groups.into_iter().map(String::from).collect(),
+            };
+            registry.register_device(config).await.unwrap();
+        }
+
+        let ids = |mut devices: Vec<DeviceConfig>| {
+            devices.sort_by(|a, b| a.device_id.cmp(&b.device_id));
+            devices.into_iter().map(|d| d.device_id).collect::<Vec<_>>()
+        };
+
+        let freezers = registry
+            .select_devices(&DeviceSelector::group("freezers"))
+            .await;
+        assert_eq!(ids(freezers), vec!["f1", "f2"]);
+
+        let floor2 = DeviceSelector::location(DeviceLocation::from_path("store-17/main/2"));
+        assert_eq!(
+            ids(registry.select_devices(&floor2).await),
+            vec!["f1", "f3"]
+        );
+
+        let selector = DeviceSelector::parse("group:freezers, tag:backup").unwrap();
+        assert_eq!(ids(registry.select_devices(&selector).await), vec!["f2"]);
+
+        let store = DeviceSelector::parse("location:store-17").unwrap();
+        assert_eq!(registry.select_devices(&store).await.len(), 3);
+        assert_eq!(
+            registry.try_select_device_ids(&store).map(|v| v.len()),
+            Some(3)
+        );
+    }
+
+    #[test]
+    fn test_device_selector_string_roundtrip() {
+        let selector = DeviceSelector::parse("group:freezers,tag:cold,location:s1/b2").unwrap();
+        assert_eq!(selector.group.as_deref(), Some("freezers"));
+        assert_eq!(
+            selector.location.as_ref().unwrap().building.as_deref(),
+            Some("b2")
+        );
+        assert_eq!(
+            selector.to_string(),
+            "group:freezers,tag:cold,location:s1/b2"
+        );
+        assert_eq!(
+            DeviceSelector::parse(&selector.to_string()).unwrap(),
+            selector
+        );
+
+        assert!(DeviceSelector::parse("").unwrap().is_empty());
+        assert!(DeviceSelector::parse("floor:2").is_err());
+    }
+
     /// Test that GitHub device type JSON files can be deserialized correctly
     /// This validates compatibility with the camthink-ai/NeoMind-DeviceTypes repository
     #[test]
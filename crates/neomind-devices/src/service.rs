@@ -6,14 +6,14 @@
 //! - Data querying
 //! - Integration with adapters and telemetry storage
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 use tokio::time::{interval, Duration};
 
 use super::adapter::{ConnectionStatus, DeviceAdapter};
 use super::mdl::{DeviceError, MetricValue};
-use super::registry::{DeviceConfig, DeviceRegistry, DeviceTypeTemplate};
+use super::registry::{DeviceConfig, DeviceRegistry, DeviceSelector, DeviceTypeTemplate};
 use super::telemetry::TimeSeriesStorage;
 use neomind_core::EventBus;
 use std::sync::atomic::{AtomicU64, Ordering};
@@ -21,7 +21,13 @@ use std::sync::atomic::{AtomicU64, Ordering};
 // Import storage types for command history persistence
 use neomind_storage::device_registry::{
     CommandHistoryRecord as StorageCommandRecord, CommandStatus as StorageCommandStatus,
+    DeviceRegistryStore,
 };
+pub use neomind_storage::device_shadow::DeviceShadow;
+
+/// One device's shadow, locked on its own so telemetry for different devices
+/// does not contend on a single lock.
+type ShadowCell = Arc<Mutex<DeviceShadow>>;
 
 /// Command history record
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -274,6 +280,8 @@ pub struct DeviceService {
     heartbeat_config: HeartbeatConfig,
     /// Whether heartbeat monitoring is running
     heartbeat_running: Arc<RwLock<bool>>,
+    /// Device shadows (device_id -> desired/reported state)
+    shadows: Arc<RwLock<HashMap<String, ShadowCell>>>,
 }
 
 impl DeviceService {
@@ -290,6 +298,7 @@ impl DeviceService {
             max_history_entries: 100, // Keep last 100 commands per device
             heartbeat_config: HeartbeatConfig::default(),
             heartbeat_running: Arc::new(RwLock::new(false)),
+            shadows: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -310,6 +319,7 @@ impl DeviceService {
             max_history_entries: 100,
             heartbeat_config,
             heartbeat_running: Arc::new(RwLock::new(false)),
+            shadows: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -335,9 +345,16 @@ impl DeviceService {
                 tracing::warn!("Failed to load command history from storage: {}", e);
             });
 
+        // Load device shadows from storage if available
+        self.load_shadows_from_storage().await.unwrap_or_else(|e| {
+            tracing::warn!("Failed to load device shadows from storage: {}", e);
+        });
+
         let event_bus = self.event_bus.clone();
         let device_status = self.device_status.clone();
         let telemetry_storage = self.telemetry_storage.clone();
+        let shadows = self.shadows.clone();
+        let shadow_store = self.registry.storage().cloned();
 
         tokio::spawn(async move {
             let event_bus_for_publish = event_bus.clone();
@@ -395,6 +412,16 @@ impl DeviceService {
                             drop(status);
                         }
 
+                        // Telemetry is the device's reported state
+                        record_reported(
+                            &shadows,
+                            shadow_store.as_ref(),
+                            &device_id,
+                            &metric,
+                            &value,
+                        )
+                        .await;
+
                         // Write to telemetry storage if available.
                         // Use the event's timestamp (not Utc::now()) so we don't create duplicate
                         // data points. Adapters (MQTT, HTTP, Webhook) already write with their
@@ -943,6 +970,11 @@ impl DeviceService {
         self.registry.list_devices().await
     }
 
+    /// List devices matching a group/tag/location selector
+    pub async fn select_devices(&self, selector: &DeviceSelector) -> Vec<DeviceConfig> {
+        self.registry.select_devices(selector).await
+    }
+
     /// Find a device by its telemetry topic
     /// This is used by MQTT adapters to route messages from custom topics
     pub async fn find_device_by_telemetry_topic(
@@ -974,6 +1006,15 @@ impl DeviceService {
     pub async fn unregister_device(&self, device_id: &str) -> Result<(), DeviceError> {
         self.registry.unregister_device(device_id).await?;
 
+        // Drop the device's shadow along with it
+        if self.shadows.write().await.remove(device_id).is_some() {
+            if let Some(store) = self.registry.storage() {
+                if let Err(e) = store.delete_shadow(device_id) {
+                    tracing::warn!("Failed to delete shadow for {}: {}", device_id, e);
+                }
+            }
+        }
+
         // Publish event for UI refresh
         tokio::spawn({
             let event_bus = self.event_bus.clone();
@@ -1031,6 +1072,30 @@ impl DeviceService {
         // Validate and convert parameters
         let validated_params = self.validate_command_params(command_def, params)?;
 
+        // Parameters that correspond to a template metric describe device state;
+        // record them as desired even if the send below fails, so the shadow
+        // reconciler can re-apply them when the device comes back.
+        let desired: HashMap<String, serde_json::Value> = validated_params
+            .iter()
+            .filter(|(name, _)| template.metrics.iter().any(|m| &m.name == *name))
+            .filter_map(|(name, value)| metric_value_to_json(value).map(|v| (name.clone(), v)))
+            .collect();
+        if !desired.is_empty() {
+            self.apply_desired(device_id, desired, Some(command_name))
+                .await;
+        } else {
+            // Nothing the device reports back covers this command, so track the
+            // command itself until it reaches the device
+            let params = validated_params
+                .iter()
+                .filter_map(|(name, value)| metric_value_to_json(value).map(|v| (name.clone(), v)))
+                .collect();
+            let shadow = shadow_cell(&self.shadows, device_id).await;
+            let mut shadow = shadow.lock().await;
+            shadow.set_desired_command(command_name, params);
+            persist_shadow(self.registry.storage(), &shadow);
+        }
+
         // Build command payload from template
         let payload = self.build_command_payload(command_def, &validated_params)?;
 
@@ -1059,6 +1124,10 @@ impl DeviceService {
                 DeviceError::InvalidParameter(format!("Failed to send command via adapter: {}", e))
             })?;
 
+        if self.get_device_connection_status(device_id).await == ConnectionStatus::Connected {
+            self.mark_command_delivered(device_id, command_name).await;
+        }
+
         // Return None for now (could return command result in the future)
         Ok(None)
     }
@@ -1216,6 +1285,238 @@ impl DeviceService {
         Ok(payload)
     }
 
+    // ========== Device Shadow ==========
+
+    /// Get the shadow document for a device (empty if nothing recorded yet)
+    pub async fn get_shadow(&self, device_id: &str) -> DeviceShadow {
+        let shadow = self.shadows.read().await.get(device_id).cloned();
+        match shadow {
+            Some(shadow) => shadow.lock().await.clone(),
+            None => DeviceShadow::new(device_id),
+        }
+    }
+
+    /// Update the desired state of a device.
+    ///
+    /// `null` values clear the key. `command` names the template command that
+    /// applies the values; when omitted, the reconciler picks the first command
+    /// with a parameter of the same name. If the device is online, the new
+    /// delta is applied right away.
+    pub async fn update_desired(
+        &self,
+        device_id: &str,
+        desired: HashMap<String, serde_json::Value>,
+        command: Option<&str>,
+    ) -> Result<DeviceShadow, DeviceError> {
+        let (_, template) = self.get_device_with_template(device_id).await?;
+        if let Some(command) = command {
+            if !template.commands.iter().any(|c| c.name == command) {
+                return Err(DeviceError::InvalidCommand(format!(
+                    "Command '{}' not found in template '{}'",
+                    command, template.device_type
+                )));
+            }
+        }
+
+        self.apply_desired(device_id, desired, command).await;
+
+        if self.get_device_connection_status(device_id).await == ConnectionStatus::Connected {
+            if let Err(e) = self.reconcile_shadow(device_id).await {
+                tracing::warn!("Failed to reconcile shadow for {}: {}", device_id, e);
+            }
+        }
+
+        Ok(self.get_shadow(device_id).await)
+    }
+
+    /// Re-send commands for every desired value the device has not reported yet,
+    /// and every desired command it has not received.
+    ///
+    /// Returns the number of commands sent.
+    pub async fn reconcile_shadow(&self, device_id: &str) -> Result<usize, DeviceError> {
+        let shadow = self.get_shadow(device_id).await;
+        let delta = shadow.delta();
+        if shadow.in_sync() {
+            return Ok(0);
+        }
+
+        let (_, template) = self.get_device_with_template(device_id).await?;
+
+        // Group the delta by the command that applies each key
+        let mut by_command: BTreeMap<&str, Vec<&str>> = BTreeMap::new();
+        for key in delta.keys() {
+            let command = shadow
+                .desired_by
+                .get(key)
+                .and_then(|name| template.commands.iter().find(|c| &c.name == name))
+                .or_else(|| {
+                    template
+                        .commands
+                        .iter()
+                        .find(|c| c.parameters.iter().any(|p| &p.name == key))
+                });
+            match command {
+                Some(command) => by_command.entry(&command.name).or_default().push(key),
+                None => tracing::warn!(
+                    "No command applies desired '{}' for device {}, skipping",
+                    key,
+                    device_id
+                ),
+            }
+        }
+
+        let mut sent = 0;
+        for (command_name, keys) in by_command {
+            let Some(command) = template.commands.iter().find(|c| c.name == command_name) else {
+                continue;
+            };
+            // Send every desired value the command takes, not only the drifted ones,
+            // so parameters without defaults are filled in.
+            let params: HashMap<String, serde_json::Value> = command
+                .parameters
+                .iter()
+                .filter_map(|p| {
+                    shadow
+                        .desired
+                        .get(&p.name)
+                        .map(|v| (p.name.clone(), v.clone()))
+                })
+                .collect();
+
+            tracing::info!(
+                "Reconciling {:?} on device {} via command '{}'",
+                keys,
+                device_id,
+                command_name
+            );
+            match self.send_command(device_id, command_name, params).await {
+                Ok(_) => sent += 1,
+                Err(e) => tracing::warn!(
+                    "Shadow reconcile command '{}' failed for {}: {}",
+                    command_name,
+                    device_id,
+                    e
+                ),
+            }
+        }
+
+        for (command_name, command) in shadow.pending_commands() {
+            if !template.commands.iter().any(|c| c.name == command_name) {
+                tracing::warn!(
+                    "Desired command '{}' no longer exists for device {}, skipping",
+                    command_name,
+                    device_id
+                );
+                continue;
+            }
+            let params = command
+                .params
+                .iter()
+                .map(|(k, v)| (k.clone(), v.clone()))
+                .collect();
+
+            tracing::info!(
+                "Reconciling pending command '{}' on device {}",
+                command_name,
+                device_id
+            );
+            match self.send_command(device_id, command_name, params).await {
+                Ok(_) => {
+                    // Reconciling because the device is back, so this reached it
+                    self.mark_command_delivered(device_id, command_name).await;
+                    sent += 1;
+                }
+                Err(e) => tracing::warn!(
+                    "Shadow reconcile command '{}' failed for {}: {}",
+                    command_name,
+                    device_id,
+                    e
+                ),
+            }
+        }
+
+        Ok(sent)
+    }
+
+    /// Start the shadow reconciler: whenever a device comes online, re-send
+    /// commands for its shadow delta.
+    pub fn start_shadow_reconciler(self: &Arc<Self>) {
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            let mut rx = service.event_bus.subscribe_filtered(|event| {
+                matches!(event, neomind_core::NeoMindEvent::DeviceOnline { .. })
+            });
+            while let Some((event, _)) = rx.recv().await {
+                if let neomind_core::NeoMindEvent::DeviceOnline { device_id, .. } = event {
+                    match service.reconcile_shadow(&device_id).await {
+                        Ok(0) => {}
+                        Ok(n) => tracing::info!(
+                            "Shadow reconciler re-sent {} command(s) to {}",
+                            n,
+                            device_id
+                        ),
+                        Err(e) => {
+                            tracing::warn!("Shadow reconcile failed for {}: {}", device_id, e)
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Merge values into a device's desired state, persisting if anything changed
+    async fn apply_desired(
+        &self,
+        device_id: &str,
+        desired: HashMap<String, serde_json::Value>,
+        command: Option<&str>,
+    ) {
+        let shadow = shadow_cell(&self.shadows, device_id).await;
+        let mut shadow = shadow.lock().await;
+
+        let mut changed = false;
+        for (key, value) in desired {
+            let unchanged = shadow.desired.get(&key) == Some(&value)
+                && shadow.desired_by.get(&key).map(String::as_str) == command;
+            if !unchanged {
+                shadow.set_desired(&key, value, command);
+                changed = true;
+            }
+        }
+
+        if changed {
+            persist_shadow(self.registry.storage(), &shadow);
+        }
+    }
+
+    /// Mark the latest request of a desired command as delivered
+    async fn mark_command_delivered(&self, device_id: &str, command: &str) {
+        let Some(shadow) = self.shadows.read().await.get(device_id).cloned() else {
+            return;
+        };
+        let mut shadow = shadow.lock().await;
+        if shadow.mark_delivered(command) {
+            persist_shadow(self.registry.storage(), &shadow);
+        }
+    }
+
+    /// Load device shadows from storage
+    async fn load_shadows_from_storage(&self) -> Result<(), DeviceError> {
+        let Some(store) = self.registry.storage() else {
+            return Ok(());
+        };
+
+        let stored = store
+            .list_shadows()
+            .map_err(|e| DeviceError::Storage(format!("Failed to load device shadows: {}", e)))?;
+
+        let mut shadows = self.shadows.write().await;
+        for shadow in stored {
+            shadows.insert(shadow.device_id.clone(), Arc::new(Mutex::new(shadow)));
+        }
+        Ok(())
+    }
+
     // ========== Data Querying ==========
 
     /// Query telemetry data for a device metric
@@ -1621,6 +1922,71 @@ impl DeviceService {
     }
 }
 
+/// Record a telemetry value as reported state of the device's shadow, if it
+/// has one.
+///
+/// Only changes to keys the shadow has a desired value for are persisted;
+/// the rest is refreshed by telemetry and not worth a write per sample.
+async fn record_reported(
+    shadows: &RwLock<HashMap<String, ShadowCell>>,
+    store: Option<&Arc<DeviceRegistryStore>>,
+    device_id: &str,
+    metric: &str,
+    value: &neomind_core::MetricValue,
+) {
+    let value = match value {
+        neomind_core::MetricValue::Integer(i) => serde_json::json!(i),
+        neomind_core::MetricValue::Float(f) => serde_json::json!(f),
+        neomind_core::MetricValue::String(s) => serde_json::json!(s),
+        neomind_core::MetricValue::Boolean(b) => serde_json::json!(b),
+        neomind_core::MetricValue::Json(j) => j.clone(),
+    };
+
+    // Shadows are created by the first desired-state write; devices nobody
+    // has commanded don't need one
+    let Some(shadow) = shadows.read().await.get(device_id).cloned() else {
+        return;
+    };
+    let mut shadow = shadow.lock().await;
+    if shadow.set_reported(metric, value) && shadow.desired.contains_key(metric) {
+        persist_shadow(store, &shadow);
+    }
+}
+
+/// Get a device's shadow cell, creating an empty shadow on first use.
+///
+/// Only the first use per device takes the map's write lock.
+async fn shadow_cell(shadows: &RwLock<HashMap<String, ShadowCell>>, device_id: &str) -> ShadowCell {
+    if let Some(shadow) = shadows.read().await.get(device_id) {
+        return shadow.clone();
+    }
+    shadows
+        .write()
+        .await
+        .entry(device_id.to_string())
+        .or_insert_with(|| Arc::new(Mutex::new(DeviceShadow::new(device_id))))
+        .clone()
+}
+
+fn persist_shadow(store: Option<&Arc<DeviceRegistryStore>>, shadow: &DeviceShadow) {
+    if let Some(store) = store {
+        if let Err(e) = store.save_shadow(shadow) {
+            tracing::warn!("Failed to persist shadow for {}: {}", shadow.device_id, e);
+        }
+    }
+}
+
+/// Convert a command parameter value to JSON for the shadow document
+fn metric_value_to_json(value: &MetricValue) -> Option<serde_json::Value> {
+    match value {
+        MetricValue::Integer(i) => Some(serde_json::json!(i)),
+        MetricValue::Float(f) => Some(serde_json::json!(f)),
+        MetricValue::String(s) => Some(serde_json::json!(s)),
+        MetricValue::Boolean(b) => Some(serde_json::json!(b)),
+        MetricValue::Array(_) | MetricValue::Binary(_) | MetricValue::Null => None,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1657,6 +2023,9 @@ mod tests {
             adapter_type: "mqtt".to_string(),
             connection_config: ConnectionConfig::new(),
             adapter_id: None,
+            location: None,
+            tags: Vec::new(),
+            groups: Vec::new(),
         };
 
         service.register_device(config).await.unwrap();
@@ -1745,4 +2114,218 @@ mod tests {
             .unwrap();
         assert!(payload.contains("25.5"));
     }
+
+    #[tokio::test]
+    async fn test_shadow_reconcile_on_device_online() {
+        use crate::adapter::MockAdapter;
+        use crate::mdl::MetricDataType;
+        use crate::mdl_format::{CommandDefinition, MetricDefinition, ParameterDefinition};
+
+        let event_bus = EventBus::new();
+        let registry = Arc::new(DeviceRegistry::new());
+        let service = Arc::new(DeviceService::new(registry.clone(), event_bus.clone()));
+
+        let template = DeviceTypeTemplate::new("dimmer", "Dimmer")
+            .with_metric(MetricDefinition {
+                name: "brightness".to_string(),
+                display_name: "Brightness".to_string(),
+                data_type: MetricDataType::Integer,
+                unit: "%".to_string(),
+                min: None,
+                max: None,
+                required: false,
+            })
+            .with_command(CommandDefinition {
+                name: "set_brightness".to_string(),
+                display_name: "Set Brightness".to_string(),
+                payload_template: r#"{"brightness": ${{brightness}}}"#.to_string(),
+                parameters: vec![ParameterDefinition {
+                    name: "brightness".to_string(),
+                    display_name: "Brightness".to_string(),
+                    data_type: MetricDataType::Integer,
+                    default_value: None,
+                    min: None,
+                    max: None,
+                    unit: "%".to_string(),
+                    allowed_values: vec![],
+                    required: true,
+                    visible_when: None,
+                    group: None,
+                    help_text: String::new(),
+                    validation: vec![],
+                }],
+                samples: vec![],
+                description: String::new(),
+                fixed_values: HashMap::new(),
+                parameter_groups: vec![],
+            });
+        service.register_template(template).await.unwrap();
+        service
+            .register_device(DeviceConfig {
+                device_id: "lamp1".to_string(),
+                name: "Lamp".to_string(),
+                device_type: "dimmer".to_string(),
+                adapter_type: "mock".to_string(),
+                connection_config: ConnectionConfig::new(),
+                adapter_id: Some("mock".to_string()),
+                location: None,
+                tags: Vec::new(),
+                groups: Vec::new(),
+            })
+            .await
+            .unwrap();
+        let adapter = Arc::new(MockAdapter::new("mock"));
+        service
+            .register_adapter("mock".to_string(), adapter.clone())
+            .await;
+
+        // Command updates desired
+        let mut params = HashMap::new();
+        params.insert("brightness".to_string(), serde_json::json!(70));
+        service
+            .send_command("lamp1", "set_brightness", params)
+            .await
+            .unwrap();
+        let shadow = service.get_shadow("lamp1").await;
+        assert_eq!(
+            shadow.desired.get("brightness"),
+            Some(&serde_json::json!(70))
+        );
+        assert_eq!(shadow.delta().len(), 1);
+
+        // Device comes online without having applied it: the reconciler re-sends
+        service.start().await;
+        service.start_shadow_reconciler();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        event_bus
+            .publish(neomind_core::NeoMindEvent::DeviceOnline {
+                device_id: "lamp1".to_string(),
+                device_type: "dimmer".to_string(),
+                timestamp: chrono::Utc::now().timestamp(),
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let sent = adapter.sent_commands();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].1, "set_brightness");
+        assert!(sent[1].2.contains("70"));
+
+        // Telemetry reports the value: shadow is in sync, nothing more to send
+        event_bus
+            .publish(neomind_core::NeoMindEvent::DeviceMetric {
+                device_id: "lamp1".to_string(),
+                metric: "brightness".to_string(),
+                value: neomind_core::MetricValue::Integer(70),
+                timestamp: chrono::Utc::now().timestamp(),
+                quality: None,
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(service.get_shadow("lamp1").await.in_sync());
+        assert_eq!(service.reconcile_shadow("lamp1").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_shadow_tracks_parameterless_command() {
+        use crate::adapter::MockAdapter;
+        use crate::mdl_format::CommandDefinition;
+
+        let event_bus = EventBus::new();
+        let registry = Arc::new(DeviceRegistry::new());
+        let service = Arc::new(DeviceService::new(registry.clone(), event_bus.clone()));
+
+        let template = DeviceTypeTemplate::new("lamp", "Lamp").with_command(CommandDefinition {
+            name: "turn_on".to_string(),
+            display_name: "Turn On".to_string(),
+            payload_template: r#"{"on": true}"#.to_string(),
+            parameters: vec![],
+            samples: vec![],
+            description: String::new(),
+            fixed_values: HashMap::new(),
+            parameter_groups: vec![],
+        });
+        service.register_template(template).await.unwrap();
+        service
+            .register_device(DeviceConfig {
+                device_id: "lamp2".to_string(),
+                name: "Lamp".to_string(),
+                device_type: "lamp".to_string(),
+                adapter_type: "mock".to_string(),
+                connection_config: ConnectionConfig::new(),
+                adapter_id: Some("mock".to_string()),
+                location: None,
+                tags: Vec::new(),
+                groups: Vec::new(),
+            })
+            .await
+            .unwrap();
+        let adapter = Arc::new(MockAdapter::new("mock"));
+        service
+            .register_adapter("mock".to_string(), adapter.clone())
+            .await;
+
+        // Sent while the device is offline: the command stays pending
+        service
+            .send_command("lamp2", "turn_on", HashMap::new())
+            .await
+            .unwrap();
+        let shadow = service.get_shadow("lamp2").await;
+        assert_eq!(shadow.pending_commands().len(), 1);
+        assert!(!shadow.in_sync());
+
+        // The reconciler re-sends it once the device is back
+        service.start().await;
+        service.start_shadow_reconciler();
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        event_bus
+            .publish(neomind_core::NeoMindEvent::DeviceOnline {
+                device_id: "lamp2".to_string(),
+                device_type: "lamp".to_string(),
+                timestamp: chrono::Utc::now().timestamp(),
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        let sent = adapter.sent_commands();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].1, "turn_on");
+        assert!(service.get_shadow("lamp2").await.in_sync());
+        assert_eq!(service.reconcile_shadow("lamp2").await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_telemetry_does_not_create_shadows() {
+        let event_bus = EventBus::new();
+        let registry = Arc::new(DeviceRegistry::new());
+        let service = Arc::new(DeviceService::new(registry, event_bus.clone()));
+        service.start().await;
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        event_bus
+            .publish(neomind_core::NeoMindEvent::DeviceMetric {
+                device_id: "sensor1".to_string(),
+                metric: "temperature".to_string(),
+                value: neomind_core::MetricValue::Float(21.5),
+                timestamp: chrono::Utc::now().timestamp(),
+                quality: None,
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(service.shadows.read().await.is_empty());
+
+        // Once something is desired, telemetry keeps the shadow's reported state
+        let mut desired = HashMap::new();
+        desired.insert("temperature".to_string(), serde_json::json!(22.0));
+        service.apply_desired("sensor1", desired, None).await;
+        event_bus
+            .publish(neomind_core::NeoMindEvent::DeviceMetric {
+                device_id: "sensor1".to_string(),
+                metric: "temperature".to_string(),
+                value: neomind_core::MetricValue::Float(22.0),
+                timestamp: chrono::Utc::now().timestamp(),
+                quality: None,
+            })
+            .await;
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert!(service.get_shadow("sensor1").await.in_sync());
+    }
 }
@@ -0,0 +1,1495 @@
+//! Sparkplug B support for MQTT devices.
+//!
+//! Sparkplug B edge nodes publish protobuf payloads on topics of the form
+//! `spBv1.0/<group_id>/<message_type>/<edge_node_id>[/<device_id>]`.
+//! This module provides:
+//!
+//! - A minimal protobuf codec for the Sparkplug B `Payload` message
+//! - Topic parsing for all Sparkplug message types
+//! - A session tracker that turns NBIRTH/DBIRTH metric definitions into
+//!   MDL device type templates, resolves metric aliases, validates sequence
+//!   numbers and marks devices offline on NDEATH/DDEATH
+//! - NCMD/DCMD encoding from MDL commands
+//!
+//! ## Device Mapping
+//!
+//! ```text
+//! spBv1.0/plant1/NBIRTH/gw01          ──→ device "plant1.gw01"
+//! spBv1.0/plant1/DBIRTH/gw01/press3   ──→ device "plant1.gw01.press3"
+//! ```
+//!
+//! Each node or device gets its own device type (`sparkplug_<group>_<node>[_<device>]`)
+//! whose metrics come from the birth certificate. Every birth metric also yields a
+//! `write_<metric>` command whose single parameter is the Sparkplug metric name, so
+//! MDL commands encode directly into NCMD/DCMD metrics.
+
+use crate::mdl::{MetricDataType, MetricValue};
+use crate::mdl_format::{CommandDefinition, MetricDefinition, ParameterDefinition};
+use crate::registry::{DeviceTypeMode, DeviceTypeTemplate};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use thiserror::Error;
+use tracing::{debug, warn};
+
+/// Sparkplug B topic namespace.
+pub const SPARKPLUG_NAMESPACE: &str = "spBv1.0";
+
+/// Metric name used by edge nodes to request a rebirth.
+pub const REBIRTH_METRIC: &str = "Node Control/Rebirth";
+
+/// Birth/death sequence metric carried in NBIRTH and NDEATH.
+pub const BDSEQ_METRIC: &str = "bdSeq";
+
+/// Sparkplug B errors.
+#[derive(Debug, Error, PartialEq)]
+pub enum SparkplugError {
+    #[error("Invalid Sparkplug topic: {0}")]
+    InvalidTopic(String),
+
+    #[error("Malformed Sparkplug payload: {0}")]
+    Decode(String),
+
+    #[error("Cannot encode metric '{0}': {1}")]
+    Encode(String, String),
+}
+
+/// Sparkplug B configuration for the MQTT adapter.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SparkplugConfig {
+    /// Enable Sparkplug B decoding
+    pub enabled: bool,
+    /// Restrict to a single Sparkplug group (None = all groups)
+    pub group_id: Option<String>,
+    /// Automatically register device types and devices from birth certificates
+    pub auto_register: bool,
+    /// Publish a rebirth request (NCMD `Node Control/Rebirth`) on sequence gaps
+    /// or data from nodes without a known birth certificate
+    pub request_rebirth: bool,
+}
+
+impl Default for SparkplugConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            group_id: None,
+            auto_register: true,
+            request_rebirth: true,
+        }
+    }
+}
+
+impl SparkplugConfig {
+    /// Topic filter to subscribe to.
+    pub fn subscription(&self) -> String {
+        match &self.group_id {
+            Some(group) => format!("{}/{}/#", SPARKPLUG_NAMESPACE, group),
+            None => format!("{}/#", SPARKPLUG_NAMESPACE),
+        }
+    }
+}
+
+// ============================================================================
+// Topics
+// ============================================================================
+
+/// Sparkplug B message type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+pub enum SparkplugMessageType {
+    NBirth,
+    NDeath,
+    DBirth,
+    DDeath,
+    NData,
+    DData,
+    NCmd,
+    DCmd,
+    State,
+}
+
+impl SparkplugMessageType {
+    fn parse(s: &str) -> Option<Self> {
+        Some(match s {
+            "NBIRTH" => Self::NBirth,
+            "NDEATH" => Self::NDeath,
+            "DBIRTH" => Self::DBirth,
+            "DDEATH" => Self::DDeath,
+            "NDATA" => Self::NData,
+            "DDATA" => Self::DData,
+            "NCMD" => Self::NCmd,
+            "DCMD" => Self::DCmd,
+            "STATE" => Self::State,
+            _ => return None,
+        })
+    }
+
+    /// Topic segment for this message type.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::NBirth => "NBIRTH",
+            Self::NDeath => "NDEATH",
+            Self::DBirth => "DBIRTH",
+            Self::DDeath => "DDEATH",
+            Self::NData => "NDATA",
+            Self::DData => "DDATA",
+            Self::NCmd => "NCMD",
+            Self::DCmd => "DCMD",
+            Self::State => "STATE",
+        }
+    }
+
+    /// Whether this message type targets a device (as opposed to an edge node).
+    pub fn is_device_message(&self) -> bool {
+        matches!(self, Self::DBirth | Self::DDeath | Self::DData | Self::DCmd)
+    }
+}
+
+/// Parsed Sparkplug B topic.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparkplugTopic {
+    pub group_id: String,
+    pub message_type: SparkplugMessageType,
+    pub edge_node_id: String,
+    pub device_id: Option<String>,
+}
+
+impl SparkplugTopic {
+    /// Parse a Sparkplug B topic.
+    pub fn parse(topic: &str) -> Result<Self, SparkplugError> {
+        let parts: Vec<&str> = topic.split('/').collect();
+        if parts.first() != Some(&SPARKPLUG_NAMESPACE) || parts.len() < 3 {
+            return Err(SparkplugError::InvalidTopic(topic.to_string()));
+        }
+
+        // Host application state: spBv1.0/STATE/<host_id>
+        if parts[1] == "STATE" {
+            return Ok(Self {
+                group_id: String::new(),
+                message_type: SparkplugMessageType::State,
+                edge_node_id: parts[2].to_string(),
+                device_id: None,
+            });
+        }
+
+        let message_type = SparkplugMessageType::parse(parts[2])
+            .ok_or_else(|| SparkplugError::InvalidTopic(topic.to_string()))?;
+        let expected_len = if message_type.is_device_message() { 5 } else { 4 };
+        if parts.len() != expected_len || parts.iter().any(|p| p.is_empty()) {
+            return Err(SparkplugError::InvalidTopic(topic.to_string()));
+        }
+
+        Ok(Self {
+            group_id: parts[1].to_string(),
+            message_type,
+            edge_node_id: parts[3].to_string(),
+            device_id: parts.get(4).map(|s| s.to_string()),
+        })
+    }
+
+    /// Build a topic for the given message type and target.
+    pub fn format(
+        group_id: &str,
+        message_type: SparkplugMessageType,
+        edge_node_id: &str,
+        device_id: Option<&str>,
+    ) -> String {
+        match device_id {
+            Some(device) => format!(
+                "{}/{}/{}/{}/{}",
+                SPARKPLUG_NAMESPACE,
+                group_id,
+                message_type.as_str(),
+                edge_node_id,
+                device
+            ),
+            None => format!(
+                "{}/{}/{}/{}",
+                SPARKPLUG_NAMESPACE,
+                group_id,
+                message_type.as_str(),
+                edge_node_id
+            ),
+        }
+    }
+
+    /// NeoMind device ID for the node or device addressed by this topic.
+    pub fn neomind_device_id(&self) -> String {
+        neomind_device_id(&self.group_id, &self.edge_node_id, self.device_id.as_deref())
+    }
+}
+
+/// NeoMind device ID for a Sparkplug node (`device_id = None`) or device.
+pub fn neomind_device_id(group_id: &str, edge_node_id: &str, device_id: Option<&str>) -> String {
+    match device_id {
+        Some(device) => format!("{}.{}.{}", group_id, edge_node_id, device),
+        None => format!("{}.{}", group_id, edge_node_id),
+    }
+}
+
+/// NeoMind device type for a Sparkplug node or device.
+pub fn neomind_device_type(group_id: &str, edge_node_id: &str, device_id: Option<&str>) -> String {
+    let mut parts = vec!["sparkplug", group_id, edge_node_id];
+    if let Some(device) = device_id {
+        parts.push(device);
+    }
+    parts
+        .iter()
+        .map(|p| sanitize_identifier(p))
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+fn sanitize_identifier(s: &str) -> String {
+    s.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+// ============================================================================
+// Payload model
+// ============================================================================
+
+/// Sparkplug B metric data types (subset relevant to MDL).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum SparkplugDataType {
+    Unknown,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    UInt8,
+    UInt16,
+    UInt32,
+    UInt64,
+    Float,
+    Double,
+    Boolean,
+    String,
+    DateTime,
+    Text,
+    Uuid,
+    DataSet,
+    Bytes,
+    File,
+    Template,
+}
+
+impl SparkplugDataType {
+    pub fn from_code(code: u32) -> Self {
+        match code {
+            1 => Self::Int8,
+            2 => Self::Int16,
+            3 => Self::Int32,
+            4 => Self::Int64,
+            5 => Self::UInt8,
+            6 => Self::UInt16,
+            7 => Self::UInt32,
+            8 => Self::UInt64,
+            9 => Self::Float,
+            10 => Self::Double,
+            11 => Self::Boolean,
+            12 => Self::String,
+            13 => Self::DateTime,
+            14 => Self::Text,
+            15 => Self::Uuid,
+            16 => Self::DataSet,
+            17 => Self::Bytes,
+            18 => Self::File,
+            19 => Self::Template,
+            _ => Self::Unknown,
+        }
+    }
+
+    pub fn code(&self) -> u32 {
+        match self {
+            Self::Unknown => 0,
+            Self::Int8 => 1,
+            Self::Int16 => 2,
+            Self::Int32 => 3,
+            Self::Int64 => 4,
+            Self::UInt8 => 5,
+            Self::UInt16 => 6,
+            Self::UInt32 => 7,
+            Self::UInt64 => 8,
+            Self::Float => 9,
+            Self::Double => 10,
+            Self::Boolean => 11,
+            Self::String => 12,
+            Self::DateTime => 13,
+            Self::Text => 14,
+            Self::Uuid => 15,
+            Self::DataSet => 16,
+            Self::Bytes => 17,
+            Self::File => 18,
+            Self::Template => 19,
+        }
+    }
+
+    /// Corresponding MDL data type.
+    pub fn to_mdl(&self) -> MetricDataType {
+        match self {
+            Self::Int8
+            | Self::Int16
+            | Self::Int32
+            | Self::Int64
+            | Self::UInt8
+            | Self::UInt16
+            | Self::UInt32
+            | Self::UInt64
+            | Self::DateTime => MetricDataType::Integer,
+            Self::Float | Self::Double => MetricDataType::Float,
+            Self::Boolean => MetricDataType::Boolean,
+            Self::Bytes | Self::File => MetricDataType::Binary,
+            Self::String | Self::Text | Self::Uuid => MetricDataType::String,
+            Self::DataSet | Self::Template | Self::Unknown => MetricDataType::String,
+        }
+    }
+
+    /// Best-effort Sparkplug type for an MDL data type.
+    pub fn from_mdl(data_type: &MetricDataType) -> Self {
+        match data_type {
+            MetricDataType::Integer => Self::Int64,
+            MetricDataType::Float => Self::Double,
+            MetricDataType::Boolean => Self::Boolean,
+            MetricDataType::Binary => Self::Bytes,
+            _ => Self::String,
+        }
+    }
+}
+
+/// Decoded metric value as carried on the wire.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SparkplugValue {
+    Int(u32),
+    Long(u64),
+    Float(f32),
+    Double(f64),
+    Boolean(bool),
+    String(String),
+    Bytes(Vec<u8>),
+    /// DataSet / Template / Extension values are not decoded
+    Unsupported,
+    None,
+}
+
+/// A single Sparkplug B metric.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SparkplugMetric {
+    pub name: Option<String>,
+    pub alias: Option<u64>,
+    pub timestamp: Option<u64>,
+    pub datatype: SparkplugDataType,
+    pub is_null: bool,
+    pub value: SparkplugValue,
+}
+
+impl SparkplugMetric {
+    /// Create a named metric.
+    pub fn new(name: impl Into<String>, datatype: SparkplugDataType, value: SparkplugValue) -> Self {
+        Self {
+            name: Some(name.into()),
+            alias: None,
+            timestamp: None,
+            datatype,
+            is_null: false,
+            value,
+        }
+    }
+
+    /// Convert the wire value into an MDL value using the given data type.
+    pub fn to_metric_value(&self, datatype: SparkplugDataType) -> MetricValue {
+        if self.is_null {
+            return MetricValue::Null;
+        }
+        match (&self.value, datatype) {
+            (SparkplugValue::Int(v), SparkplugDataType::Int8) => MetricValue::Integer(*v as i8 as i64),
+            (SparkplugValue::Int(v), SparkplugDataType::Int16) => {
+                MetricValue::Integer(*v as i16 as i64)
+            }
+            (SparkplugValue::Int(v), SparkplugDataType::Int32) => {
+                MetricValue::Integer(*v as i32 as i64)
+            }
+            (SparkplugValue::Int(v), _) => MetricValue::Integer(*v as i64),
+            (SparkplugValue::Long(v), SparkplugDataType::Int64) => MetricValue::Integer(*v as i64),
+            (SparkplugValue::Long(v), SparkplugDataType::UInt64) if *v > i64::MAX as u64 => {
+                MetricValue::Float(*v as f64)
+            }
+            (SparkplugValue::Long(v), _) => MetricValue::Integer(*v as i64),
+            (SparkplugValue::Float(v), _) => MetricValue::Float(*v as f64),
+            (SparkplugValue::Double(v), _) => MetricValue::Float(*v),
+            (SparkplugValue::Boolean(v), _) => MetricValue::Boolean(*v),
+            (SparkplugValue::String(v), _) => MetricValue::String(v.clone()),
+            (SparkplugValue::Bytes(v), _) => MetricValue::Binary(v.clone()),
+            (SparkplugValue::Unsupported, _) | (SparkplugValue::None, _) => MetricValue::Null,
+        }
+    }
+}
+
+/// Sparkplug B payload.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SparkplugPayload {
+    pub timestamp: Option<u64>,
+    pub metrics: Vec<SparkplugMetric>,
+    pub seq: Option<u64>,
+    pub uuid: Option<String>,
+    pub body: Option<Vec<u8>>,
+}
+
+// ============================================================================
+// Protobuf codec
+// ============================================================================
+
+const WIRE_VARINT: u8 = 0;
+const WIRE_FIXED64: u8 = 1;
+const WIRE_LEN: u8 = 2;
+const WIRE_FIXED32: u8 = 5;
+
+struct Reader<'a> {
+    buf: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(buf: &'a [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.buf.len()
+    }
+
+    fn varint(&mut self) -> Result<u64, SparkplugError> {
+        let mut result = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = *self
+                .buf
+                .get(self.pos)
+                .ok_or_else(|| SparkplugError::Decode("truncated varint".to_string()))?;
+            self.pos += 1;
+            result |= ((byte & 0x7f) as u64) << shift;
+            if byte & 0x80 == 0 {
+                return Ok(result);
+            }
+        }
+        Err(SparkplugError::Decode("varint too long".to_string()))
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'a [u8], SparkplugError> {
+        let end = self
+            .pos
+            .checked_add(n)
+            .filter(|end| *end <= self.buf.len())
+            .ok_or_else(|| SparkplugError::Decode("truncated field".to_string()))?;
+        let slice = &self.buf[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn key(&mut self) -> Result<(u32, u8), SparkplugError> {
+        let key = self.varint()?;
+        Ok(((key >> 3) as u32, (key & 0x7) as u8))
+    }
+
+    fn bytes(&mut self) -> Result<&'a [u8], SparkplugError> {
+        let len = self.varint()? as usize;
+        self.take(len)
+    }
+
+    fn string(&mut self) -> Result<String, SparkplugError> {
+        String::from_utf8(self.bytes()?.to_vec())
+            .map_err(|e| SparkplugError::Decode(format!("invalid UTF-8: {}", e)))
+    }
+
+    fn fixed32(&mut self) -> Result<u32, SparkplugError> {
+        let b = self.take(4)?;
+        Ok(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    fn fixed64(&mut self) -> Result<u64, SparkplugError> {
+        let b = self.take(8)?;
+        let mut arr = [0u8; 8];
+        arr.copy_from_slice(b);
+        Ok(u64::from_le_bytes(arr))
+    }
+
+    fn skip(&mut self, wire_type: u8) -> Result<(), SparkplugError> {
+        match wire_type {
+            WIRE_VARINT => self.varint().map(|_| ()),
+            WIRE_FIXED64 => self.take(8).map(|_| ()),
+            WIRE_LEN => self.bytes().map(|_| ()),
+            WIRE_FIXED32 => self.take(4).map(|_| ()),
+            other => Err(SparkplugError::Decode(format!(
+                "unsupported wire type {}",
+                other
+            ))),
+        }
+    }
+}
+
+#[derive(Default)]
+struct Writer {
+    buf: Vec<u8>,
+}
+
+impl Writer {
+    fn varint(&mut self, mut value: u64) {
+        while value >= 0x80 {
+            self.buf.push((value as u8) | 0x80);
+            value >>= 7;
+        }
+        self.buf.push(value as u8);
+    }
+
+    fn key(&mut self, field: u32, wire_type: u8) {
+        self.varint(((field as u64) << 3) | wire_type as u64);
+    }
+
+    fn varint_field(&mut self, field: u32, value: u64) {
+        self.key(field, WIRE_VARINT);
+        self.varint(value);
+    }
+
+    fn bytes_field(&mut self, field: u32, value: &[u8]) {
+        self.key(field, WIRE_LEN);
+        self.varint(value.len() as u64);
+        self.buf.extend_from_slice(value);
+    }
+}
+
+impl SparkplugPayload {
+    /// Decode a protobuf-encoded Sparkplug B payload.
+    pub fn decode(buf: &[u8]) -> Result<Self, SparkplugError> {
+        let mut reader = Reader::new(buf);
+        let mut payload = SparkplugPayload::default();
+
+        while !reader.is_empty() {
+            let (field, wire_type) = reader.key()?;
+            match (field, wire_type) {
+                (1, WIRE_VARINT) => payload.timestamp = Some(reader.varint()?),
+                (2, WIRE_LEN) => payload.metrics.push(decode_metric(reader.bytes()?)?),
+                (3, WIRE_VARINT) => payload.seq = Some(reader.varint()?),
+                (4, WIRE_LEN) => payload.uuid = Some(reader.string()?),
+                (5, WIRE_LEN) => payload.body = Some(reader.bytes()?.to_vec()),
+                (_, wt) => reader.skip(wt)?,
+            }
+        }
+
+        Ok(payload)
+    }
+
+    /// Encode this payload as protobuf.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut w = Writer::default();
+        if let Some(ts) = self.timestamp {
+            w.varint_field(1, ts);
+        }
+        for metric in &self.metrics {
+            w.bytes_field(2, &encode_metric(metric));
+        }
+        if let Some(seq) = self.seq {
+            w.varint_field(3, seq);
+        }
+        if let Some(uuid) = &self.uuid {
+            w.bytes_field(4, uuid.as_bytes());
+        }
+        if let Some(body) = &self.body {
+            w.bytes_field(5, body);
+        }
+        w.buf
+    }
+
+    /// Value of the `bdSeq` metric, if present.
+    pub fn bd_seq(&self) -> Option<u64> {
+        self.metrics
+            .iter()
+            .find(|m| m.name.as_deref() == Some(BDSEQ_METRIC))
+            .and_then(|m| match m.value {
+                SparkplugValue::Long(v) => Some(v),
+                SparkplugValue::Int(v) => Some(v as u64),
+                _ => None,
+            })
+    }
+}
+
+fn decode_metric(buf: &[u8]) -> Result<SparkplugMetric, SparkplugError> {
+    let mut reader = Reader::new(buf);
+    let mut metric = SparkplugMetric {
+        name: None,
+        alias: None,
+        timestamp: None,
+        datatype: SparkplugDataType::Unknown,
+        is_null: false,
+        value: SparkplugValue::None,
+    };
+
+    while !reader.is_empty() {
+        let (field, wire_type) = reader.key()?;
+        match (field, wire_type) {
+            (1, WIRE_LEN) => metric.name = Some(reader.string()?),
+            (2, WIRE_VARINT) => metric.alias = Some(reader.varint()?),
+            (3, WIRE_VARINT) => metric.timestamp = Some(reader.varint()?),
+            (4, WIRE_VARINT) => metric.datatype = SparkplugDataType::from_code(reader.varint()? as u32),
+            (7, WIRE_VARINT) => metric.is_null = reader.varint()? != 0,
+            (10, WIRE_VARINT) => metric.value = SparkplugValue::Int(reader.varint()? as u32),
+            (11, WIRE_VARINT) => metric.value = SparkplugValue::Long(reader.varint()?),
+            (12, WIRE_FIXED32) => {
+                metric.value = SparkplugValue::Float(f32::from_bits(reader.fixed32()?))
+            }
+            (13, WIRE_FIXED64) => {
+                metric.value = SparkplugValue::Double(f64::from_bits(reader.fixed64()?))
+            }
+            (14, WIRE_VARINT) => metric.value = SparkplugValue::Boolean(reader.varint()? != 0),
+            (15, WIRE_LEN) => metric.value = SparkplugValue::String(reader.string()?),
+            (16, WIRE_LEN) => metric.value = SparkplugValue::Bytes(reader.bytes()?.to_vec()),
+            (17..=19, wt) => {
+                reader.skip(wt)?;
+                metric.value = SparkplugValue::Unsupported;
+            }
+            (_, wt) => reader.skip(wt)?,
+        }
+    }
+
+    Ok(metric)
+}
+
+fn encode_metric(metric: &SparkplugMetric) -> Vec<u8> {
+    let mut w = Writer::default();
+    if let Some(name) = &metric.name {
+        w.bytes_field(1, name.as_bytes());
+    }
+    if let Some(alias) = metric.alias {
+        w.varint_field(2, alias);
+    }
+    if let Some(ts) = metric.timestamp {
+        w.varint_field(3, ts);
+    }
+    if metric.datatype != SparkplugDataType::Unknown {
+        w.varint_field(4, metric.datatype.code() as u64);
+    }
+    if metric.is_null {
+        w.varint_field(7, 1);
+    }
+    match &metric.value {
+        SparkplugValue::Int(v) => w.varint_field(10, *v as u64),
+        SparkplugValue::Long(v) => w.varint_field(11, *v),
+        SparkplugValue::Float(v) => {
+            w.key(12, WIRE_FIXED32);
+            w.buf.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        SparkplugValue::Double(v) => {
+            w.key(13, WIRE_FIXED64);
+            w.buf.extend_from_slice(&v.to_bits().to_le_bytes());
+        }
+        SparkplugValue::Boolean(v) => w.varint_field(14, *v as u64),
+        SparkplugValue::String(v) => w.bytes_field(15, v.as_bytes()),
+        SparkplugValue::Bytes(v) => w.bytes_field(16, v),
+        SparkplugValue::Unsupported | SparkplugValue::None => {}
+    }
+    w.buf
+}
+
+/// Convert a JSON command argument into a typed Sparkplug metric value.
+pub fn json_to_sparkplug_value(
+    name: &str,
+    datatype: SparkplugDataType,
+    value: &serde_json::Value,
+) -> Result<SparkplugValue, SparkplugError> {
+    let err = || SparkplugError::Encode(name.to_string(), format!("incompatible value {}", value));
+    Ok(match datatype {
+        // Signed types are sign-extended to 32 bits like the Tahu reference
+        // encoder; readers truncate back to the declared width
+        SparkplugDataType::Int8 => {
+            let v = i8::try_from(value.as_i64().ok_or_else(err)?).map_err(|_| err())?;
+            SparkplugValue::Int(v as i32 as u32)
+        }
+        SparkplugDataType::Int16 => {
+            let v = i16::try_from(value.as_i64().ok_or_else(err)?).map_err(|_| err())?;
+            SparkplugValue::Int(v as i32 as u32)
+        }
+        SparkplugDataType::Int32 => {
+            let v = i32::try_from(value.as_i64().ok_or_else(err)?).map_err(|_| err())?;
+            SparkplugValue::Int(v as u32)
+        }
+        SparkplugDataType::UInt8 => {
+            let v = u8::try_from(value.as_u64().ok_or_else(err)?).map_err(|_| err())?;
+            SparkplugValue::Int(v.into())
+        }
+        SparkplugDataType::UInt16 => {
+            let v = u16::try_from(value.as_u64().ok_or_else(err)?).map_err(|_| err())?;
+            SparkplugValue::Int(v.into())
+        }
+        SparkplugDataType::UInt32 => {
+            let v = u32::try_from(value.as_u64().ok_or_else(err)?).map_err(|_| err())?;
+            SparkplugValue::Int(v)
+        }
+        SparkplugDataType::Int64 => SparkplugValue::Long(value.as_i64().ok_or_else(err)? as u64),
+        SparkplugDataType::UInt64 | SparkplugDataType::DateTime => {
+            SparkplugValue::Long(value.as_u64().ok_or_else(err)?)
+        }
+        SparkplugDataType::Float => SparkplugValue::Float(value.as_f64().ok_or_else(err)? as f32),
+        SparkplugDataType::Double => SparkplugValue::Double(value.as_f64().ok_or_else(err)?),
+        SparkplugDataType::Boolean => SparkplugValue::Boolean(value.as_bool().ok_or_else(err)?),
+        SparkplugDataType::String | SparkplugDataType::Text | SparkplugDataType::Uuid => {
+            match value {
+                serde_json::Value::String(s) => SparkplugValue::String(s.clone()),
+                other => SparkplugValue::String(other.to_string()),
+            }
+        }
+        SparkplugDataType::Bytes | SparkplugDataType::File => {
+            use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+            let s = value.as_str().ok_or_else(err)?;
+            SparkplugValue::Bytes(
+                BASE64
+                    .decode(s)
+                    .map_err(|e| SparkplugError::Encode(name.to_string(), e.to_string()))?,
+            )
+        }
+        SparkplugDataType::DataSet | SparkplugDataType::Template | SparkplugDataType::Unknown => {
+            return Err(SparkplugError::Encode(
+                name.to_string(),
+                format!("unsupported data type {:?}", datatype),
+            ))
+        }
+    })
+}
+
+// ============================================================================
+// Session tracking
+// ============================================================================
+
+/// Result of processing a Sparkplug message.
+#[derive(Debug, Clone)]
+pub enum SparkplugEvent {
+    /// A node or device published a birth certificate
+    Birth {
+        device_id: String,
+        template: DeviceTypeTemplate,
+        group_id: String,
+        edge_node_id: String,
+        sparkplug_device_id: Option<String>,
+    },
+    /// A metric value was received
+    Metric {
+        device_id: String,
+        name: String,
+        value: MetricValue,
+        /// Milliseconds since epoch
+        timestamp: i64,
+    },
+    /// A node or device went offline
+    Offline { device_id: String },
+    /// The edge node should be asked to rebirth (sequence gap or unknown aliases)
+    RebirthRequired {
+        group_id: String,
+        edge_node_id: String,
+        reason: String,
+    },
+}
+
+/// Known metric definitions for a node or device scope.
+#[derive(Debug, Clone, Default)]
+struct MetricScope {
+    /// alias -> metric name
+    aliases: HashMap<u64, String>,
+    /// metric name -> (alias, datatype)
+    metrics: HashMap<String, (Option<u64>, SparkplugDataType)>,
+}
+
+/// Per edge-node session state.
+#[derive(Debug, Clone, Default)]
+struct NodeSession {
+    last_seq: Option<u64>,
+    bd_seq: Option<u64>,
+    online: bool,
+    devices: HashSet<String>,
+}
+
+/// Tracks Sparkplug sessions, aliases and sequence numbers.
+#[derive(Debug, Default)]
+pub struct SparkplugState {
+    nodes: HashMap<(String, String), NodeSession>,
+    /// NeoMind device ID -> metric scope
+    scopes: HashMap<String, MetricScope>,
+    /// NeoMind device ID -> (group, edge node, sparkplug device)
+    addresses: HashMap<String, (String, String, Option<String>)>,
+}
+
+impl SparkplugState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Whether a NeoMind device ID belongs to a known Sparkplug node or device.
+    pub fn is_sparkplug_device(&self, device_id: &str) -> bool {
+        self.addresses.contains_key(device_id)
+    }
+
+    /// Whether a node or device is currently online.
+    pub fn is_online(&self, device_id: &str) -> bool {
+        let Some((group, node, device)) = self.addresses.get(device_id) else {
+            return false;
+        };
+        let Some(session) = self.nodes.get(&(group.clone(), node.clone())) else {
+            return false;
+        };
+        session.online && device.as_ref().is_none_or(|d| session.devices.contains(d))
+    }
+
+    /// Remember the Sparkplug address of a device restored from the registry.
+    pub fn register_address(
+        &mut self,
+        device_id: impl Into<String>,
+        group_id: impl Into<String>,
+        edge_node_id: impl Into<String>,
+        sparkplug_device_id: Option<String>,
+    ) {
+        self.addresses.insert(
+            device_id.into(),
+            (group_id.into(), edge_node_id.into(), sparkplug_device_id),
+        );
+    }
+
+    /// Process a decoded message and return the resulting events.
+    pub fn process(
+        &mut self,
+        topic: &SparkplugTopic,
+        payload: &SparkplugPayload,
+        now_ms: i64,
+    ) -> Vec<SparkplugEvent> {
+        let node_key = (topic.group_id.clone(), topic.edge_node_id.clone());
+        let device_id = topic.neomind_device_id();
+        let mut events = Vec::new();
+
+        match topic.message_type {
+            SparkplugMessageType::NBirth => {
+                let session = self.nodes.entry(node_key).or_default();
+                session.last_seq = payload.seq;
+                session.bd_seq = payload.bd_seq();
+                session.online = true;
+                session.devices.clear();
+                events.extend(self.birth(topic, payload, now_ms));
+            }
+            SparkplugMessageType::NDeath => {
+                let Some(session) = self.nodes.get_mut(&node_key) else {
+                    return events;
+                };
+                // A stale NDEATH (from a previous session) must not kill the new one
+                if let (Some(expected), Some(actual)) = (session.bd_seq, payload.bd_seq()) {
+                    if expected != actual {
+                        debug!(
+                            "Ignoring stale NDEATH for {} (bdSeq {} != {})",
+                            device_id, actual, expected
+                        );
+                        return events;
+                    }
+                }
+                session.online = false;
+                session.last_seq = None;
+                for device in session.devices.drain() {
+                    events.push(SparkplugEvent::Offline {
+                        device_id: neomind_device_id(&topic.group_id, &topic.edge_node_id, Some(&device)),
+                    });
+                }
+                events.push(SparkplugEvent::Offline { device_id });
+            }
+            SparkplugMessageType::DBirth
+            | SparkplugMessageType::DData
+            | SparkplugMessageType::DDeath
+            | SparkplugMessageType::NData => {
+                if let Some(event) = self.check_sequence(topic, payload) {
+                    events.push(event);
+                }
+                match topic.message_type {
+                    SparkplugMessageType::DBirth => {
+                        if let (Some(session), Some(device)) =
+                            (self.nodes.get_mut(&node_key), &topic.device_id)
+                        {
+                            session.devices.insert(device.clone());
+                        }
+                        events.extend(self.birth(topic, payload, now_ms));
+                    }
+                    SparkplugMessageType::DDeath => {
+                        if let (Some(session), Some(device)) =
+                            (self.nodes.get_mut(&node_key), &topic.device_id)
+                        {
+                            session.devices.remove(device);
+                        }
+                        events.push(SparkplugEvent::Offline { device_id });
+                    }
+                    _ => events.extend(self.data(topic, payload, now_ms)),
+                }
+            }
+            // Commands and host state are outbound-only from our perspective
+            SparkplugMessageType::NCmd
+            | SparkplugMessageType::DCmd
+            | SparkplugMessageType::State => {}
+        }
+
+        events
+    }
+
+    /// Validate the payload sequence number against the node session.
+    fn check_sequence(
+        &mut self,
+        topic: &SparkplugTopic,
+        payload: &SparkplugPayload,
+    ) -> Option<SparkplugEvent> {
+        let rebirth = |reason: String| SparkplugEvent::RebirthRequired {
+            group_id: topic.group_id.clone(),
+            edge_node_id: topic.edge_node_id.clone(),
+            reason,
+        };
+
+        let Some(session) = self
+            .nodes
+            .get_mut(&(topic.group_id.clone(), topic.edge_node_id.clone()))
+            .filter(|s| s.online)
+        else {
+            return Some(rebirth("no NBIRTH received for edge node".to_string()));
+        };
+
+        let seq = payload.seq?;
+        let gap = match session.last_seq {
+            Some(last) => (last + 1) % 256 != seq,
+            None => false,
+        };
+        session.last_seq = Some(seq);
+
+        if gap {
+            warn!(
+                "Sparkplug sequence gap on {}/{}: got {}",
+                topic.group_id, topic.edge_node_id, seq
+            );
+            Some(rebirth(format!("sequence gap (received {})", seq)))
+        } else {
+            None
+        }
+    }
+
+    fn birth(
+        &mut self,
+        topic: &SparkplugTopic,
+        payload: &SparkplugPayload,
+        now_ms: i64,
+    ) -> Vec<SparkplugEvent> {
+        let device_id = topic.neomind_device_id();
+        let device_type = neomind_device_type(
+            &topic.group_id,
+            &topic.edge_node_id,
+            topic.device_id.as_deref(),
+        );
+
+        let mut scope = MetricScope::default();
+        let mut template = DeviceTypeTemplate::new(
+            device_type,
+            match &topic.device_id {
+                Some(device) => format!("Sparkplug device {}", device),
+                None => format!("Sparkplug edge node {}", topic.edge_node_id),
+            },
+        )
+        .with_description(format!(
+            "Auto-generated from Sparkplug B {} on group {}",
+            topic.message_type.as_str(),
+            topic.group_id
+        ))
+        .with_category("sparkplug");
+        template.mode = DeviceTypeMode::Full;
+
+        let mut events = Vec::new();
+        for metric in &payload.metrics {
+            let Some(name) = metric.name.clone() else {
+                continue;
+            };
+            if let Some(alias) = metric.alias {
+                scope.aliases.insert(alias, name.clone());
+            }
+            scope
+                .metrics
+                .insert(name.clone(), (metric.alias, metric.datatype));
+
+            if name == BDSEQ_METRIC {
+                continue;
+            }
+
+            let mdl_type = metric.datatype.to_mdl();
+            if name == REBIRTH_METRIC || name.starts_with("Node Control/") {
+                template.commands.push(write_command(&name, &mdl_type));
+                continue;
+            }
+
+            template.metrics.push(MetricDefinition {
+                name: name.clone(),
+                display_name: name.clone(),
+                data_type: mdl_type.clone(),
+                unit: String::new(),
+                min: None,
+                max: None,
+                required: false,
+            });
+            template.commands.push(write_command(&name, &mdl_type));
+
+            events.push(SparkplugEvent::Metric {
+                device_id: device_id.clone(),
+                name,
+                value: metric.to_metric_value(metric.datatype),
+                timestamp: metric_timestamp(metric, payload, now_ms),
+            });
+        }
+
+        self.scopes.insert(device_id.clone(), scope);
+        self.addresses.insert(
+            device_id.clone(),
+            (
+                topic.group_id.clone(),
+                topic.edge_node_id.clone(),
+                topic.device_id.clone(),
+            ),
+        );
+
+        events.insert(
+            0,
+            SparkplugEvent::Birth {
+                device_id,
+                template,
+                group_id: topic.group_id.clone(),
+                edge_node_id: topic.edge_node_id.clone(),
+                sparkplug_device_id: topic.device_id.clone(),
+            },
+        );
+        events
+    }
+
+    fn data(
+        &mut self,
+        topic: &SparkplugTopic,
+        payload: &SparkplugPayload,
+        now_ms: i64,
+    ) -> Vec<SparkplugEvent> {
+        let device_id = topic.neomind_device_id();
+        let scope = self.scopes.get(&device_id);
+        let mut events = Vec::new();
+        let mut unknown_alias = false;
+
+        for metric in &payload.metrics {
+            let name = match (&metric.name, metric.alias) {
+                (Some(name), _) => Some(name.clone()),
+                (None, Some(alias)) => scope.and_then(|s| s.aliases.get(&alias).cloned()),
+                (None, None) => None,
+            };
+            let Some(name) = name else {
+                unknown_alias = true;
+                continue;
+            };
+
+            // DATA messages may omit the datatype; fall back to the birth definition
+            let datatype = if metric.datatype != SparkplugDataType::Unknown {
+                metric.datatype
+            } else {
+                scope
+                    .and_then(|s| s.metrics.get(&name))
+                    .map(|(_, dt)| *dt)
+                    .unwrap_or(SparkplugDataType::Unknown)
+            };
+
+            events.push(SparkplugEvent::Metric {
+                device_id: device_id.clone(),
+                name,
+                value: metric.to_metric_value(datatype),
+                timestamp: metric_timestamp(metric, payload, now_ms),
+            });
+        }
+
+        if unknown_alias {
+            events.push(SparkplugEvent::RebirthRequired {
+                group_id: topic.group_id.clone(),
+                edge_node_id: topic.edge_node_id.clone(),
+                reason: "unknown metric alias".to_string(),
+            });
+        }
+
+        events
+    }
+
+    /// Encode an NCMD/DCMD for a NeoMind device from an MDL command.
+    ///
+    /// Every command parameter (and fixed value) becomes one Sparkplug metric named
+    /// after the parameter. Aliases and data types from the birth certificate are
+    /// used when known; otherwise the MDL parameter type decides the wire type.
+    /// Returns the topic and encoded payload.
+    pub fn encode_command(
+        &self,
+        device_id: &str,
+        command: Option<&CommandDefinition>,
+        params: &HashMap<String, serde_json::Value>,
+        now_ms: i64,
+    ) -> Result<(String, Vec<u8>), SparkplugError> {
+        let (group, node, device) = self
+            .addresses
+            .get(device_id)
+            .ok_or_else(|| SparkplugError::Encode(device_id.to_string(), "unknown device".into()))?;
+        let scope = self.scopes.get(device_id);
+
+        let mut values: Vec<(String, Option<MetricDataType>, serde_json::Value)> = Vec::new();
+        match command {
+            Some(cmd) => {
+                for param in &cmd.parameters {
+                    let value = params.get(&param.name).cloned().or_else(|| {
+                        param
+                            .default_value
+                            .as_ref()
+                            .and_then(|v| serde_json::to_value(v).ok())
+                    });
+                    match value {
+                        Some(v) => values.push((param.name.clone(), Some(param.data_type.clone()), v)),
+                        None if param.required => {
+                            return Err(SparkplugError::Encode(
+                                param.name.clone(),
+                                "missing required parameter".into(),
+                            ))
+                        }
+                        None => {}
+                    }
+                }
+                for (name, value) in &cmd.fixed_values {
+                    values.push((name.clone(), None, value.clone()));
+                }
+            }
+            None => {
+                for (name, value) in params {
+                    values.push((name.clone(), None, value.clone()));
+                }
+            }
+        }
+
+        let mut metrics = Vec::with_capacity(values.len());
+        for (name, mdl_type, value) in values {
+            let known = scope.and_then(|s| s.metrics.get(&name)).copied();
+            let datatype = match (known, &mdl_type) {
+                (Some((_, dt)), _) if dt != SparkplugDataType::Unknown => dt,
+                (_, Some(t)) => SparkplugDataType::from_mdl(t),
+                _ => infer_datatype(&value),
+            };
+            let mut metric = SparkplugMetric::new(
+                name.clone(),
+                datatype,
+                json_to_sparkplug_value(&name, datatype, &value)?,
+            );
+            metric.alias = known.and_then(|(alias, _)| alias);
+            metric.timestamp = Some(now_ms as u64);
+            metrics.push(metric);
+        }
+
+        let message_type = if device.is_some() {
+            SparkplugMessageType::DCmd
+        } else {
+            SparkplugMessageType::NCmd
+        };
+        let topic = SparkplugTopic::format(group, message_type, node, device.as_deref());
+        let payload = SparkplugPayload {
+            timestamp: Some(now_ms as u64),
+            metrics,
+            ..Default::default()
+        };
+        Ok((topic, payload.encode()))
+    }
+}
+
+/// Encode an NCMD asking an edge node to republish its birth certificates.
+pub fn encode_rebirth_request(group_id: &str, edge_node_id: &str, now_ms: i64) -> (String, Vec<u8>) {
+    let mut metric = SparkplugMetric::new(
+        REBIRTH_METRIC,
+        SparkplugDataType::Boolean,
+        SparkplugValue::Boolean(true),
+    );
+    metric.timestamp = Some(now_ms as u64);
+    let payload = SparkplugPayload {
+        timestamp: Some(now_ms as u64),
+        metrics: vec![metric],
+        ..Default::default()
+    };
+    (
+        SparkplugTopic::format(group_id, SparkplugMessageType::NCmd, edge_node_id, None),
+        payload.encode(),
+    )
+}
+
+fn infer_datatype(value: &serde_json::Value) -> SparkplugDataType {
+    match value {
+        serde_json::Value::Bool(_) => SparkplugDataType::Boolean,
+        serde_json::Value::Number(n) if n.is_i64() || n.is_u64() => SparkplugDataType::Int64,
+        serde_json::Value::Number(_) => SparkplugDataType::Double,
+        _ => SparkplugDataType::String,
+    }
+}
+
+fn metric_timestamp(metric: &SparkplugMetric, payload: &SparkplugPayload, now_ms: i64) -> i64 {
+    metric
+        .timestamp
+        .or(payload.timestamp)
+        .map(|ts| ts as i64)
+        .unwrap_or(now_ms)
+}
+
+/// MDL command that writes a single Sparkplug metric.
+fn write_command(metric_name: &str, data_type: &MetricDataType) -> CommandDefinition {
+    let command_name = if metric_name == REBIRTH_METRIC {
+        "rebirth".to_string()
+    } else {
+        format!("write_{}", sanitize_identifier(metric_name))
+    };
+    CommandDefinition {
+        name: command_name,
+        display_name: format!("Write {}", metric_name),
+        description: format!("Write Sparkplug metric '{}' via NCMD/DCMD", metric_name),
+        payload_template: String::new(),
+        parameters: vec![ParameterDefinition {
+            name: metric_name.to_string(),
+            display_name: metric_name.to_string(),
+            data_type: data_type.clone(),
+            default_value: None,
+            min: None,
+            max: None,
+            unit: String::new(),
+            allowed_values: Vec::new(),
+            required: true,
+            visible_when: None,
+            group: None,
+            help_text: String::new(),
+            validation: Vec::new(),
+        }],
+        fixed_values: HashMap::new(),
+        samples: Vec::new(),
+        parameter_groups: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metric(name: &str, alias: u64, dt: SparkplugDataType, value: SparkplugValue) -> SparkplugMetric {
+        let mut m = SparkplugMetric::new(name, dt, value);
+        m.alias = Some(alias);
+        m
+    }
+
+    fn nbirth() -> (SparkplugTopic, SparkplugPayload) {
+        let topic = SparkplugTopic::parse("spBv1.0/plant1/NBIRTH/gw01").unwrap();
+        let payload = SparkplugPayload {
+            timestamp: Some(1_700_000_000_000),
+            seq: Some(0),
+            metrics: vec![
+                SparkplugMetric::new(BDSEQ_METRIC, SparkplugDataType::Int64, SparkplugValue::Long(3)),
+                metric(REBIRTH_METRIC, 1, SparkplugDataType::Boolean, SparkplugValue::Boolean(false)),
+                metric("Temperature", 2, SparkplugDataType::Float, SparkplugValue::Float(21.5)),
+                metric("Offset", 3, SparkplugDataType::Int16, SparkplugValue::Int((-5i16) as u32)),
+            ],
+            ..Default::default()
+        };
+        (topic, payload)
+    }
+
+    #[test]
+    fn test_topic_parse() {
+        let t = SparkplugTopic::parse("spBv1.0/plant1/DDATA/gw01/press3").unwrap();
+        assert_eq!(t.message_type, SparkplugMessageType::DData);
+        assert_eq!(t.neomind_device_id(), "plant1.gw01.press3");
+        assert!(SparkplugTopic::parse("spBv1.0/plant1/DDATA/gw01").is_err());
+        assert!(SparkplugTopic::parse("sensors/temp").is_err());
+        assert_eq!(
+            SparkplugTopic::parse("spBv1.0/STATE/scada").unwrap().message_type,
+            SparkplugMessageType::State
+        );
+    }
+
+    #[test]
+    fn test_payload_roundtrip() {
+        let (_, payload) = nbirth();
+        let decoded = SparkplugPayload::decode(&payload.encode()).unwrap();
+        assert_eq!(decoded, payload);
+        assert_eq!(decoded.bd_seq(), Some(3));
+        assert!(SparkplugPayload::decode(&[0x12, 0x05, 0x01]).is_err());
+    }
+
+    #[test]
+    fn test_birth_generates_template_and_aliases() {
+        let mut state = SparkplugState::new();
+        let (topic, payload) = nbirth();
+        let events = state.process(&topic, &payload, 0);
+
+        let SparkplugEvent::Birth { template, device_id, .. } = &events[0] else {
+            panic!("expected birth event");
+        };
+        assert_eq!(device_id, "plant1.gw01");
+        assert_eq!(template.device_type, "sparkplug_plant1_gw01");
+        assert_eq!(template.metrics.len(), 2);
+        assert!(template.commands.iter().any(|c| c.name == "rebirth"));
+        assert!(state.is_online("plant1.gw01"));
+
+        // NDATA by alias only, without datatype
+        let data = SparkplugPayload {
+            seq: Some(1),
+            metrics: vec![SparkplugMetric {
+                name: None,
+                alias: Some(3),
+                timestamp: Some(42),
+                datatype: SparkplugDataType::Unknown,
+                is_null: false,
+                value: SparkplugValue::Int((-7i16) as u16 as u32),
+            }],
+            ..Default::default()
+        };
+        let topic = SparkplugTopic::parse("spBv1.0/plant1/NDATA/gw01").unwrap();
+        let events = state.process(&topic, &data, 0);
+        assert_eq!(events.len(), 1);
+        match &events[0] {
+            SparkplugEvent::Metric {
+                device_id,
+                name,
+                value,
+                timestamp,
+            } => {
+                assert_eq!(device_id, "plant1.gw01");
+                assert_eq!(name, "Offset");
+                assert!(matches!(value, MetricValue::Integer(-7)));
+                assert_eq!(*timestamp, 42);
+            }
+            other => panic!("unexpected event: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_sequence_gap_and_death() {
+        let mut state = SparkplugState::new();
+        let (topic, payload) = nbirth();
+        state.process(&topic, &payload, 0);
+
+        let dbirth = SparkplugTopic::parse("spBv1.0/plant1/DBIRTH/gw01/press3").unwrap();
+        let events = state.process(
+            &dbirth,
+            &SparkplugPayload {
+                seq: Some(1),
+                ..Default::default()
+            },
+            0,
+        );
+        assert!(matches!(events[0], SparkplugEvent::Birth { .. }));
+
+        let ndata = SparkplugTopic::parse("spBv1.0/plant1/NDATA/gw01").unwrap();
+        let events = state.process(
+            &ndata,
+            &SparkplugPayload {
+                seq: Some(5),
+                ..Default::default()
+            },
+            0,
+        );
+        assert!(matches!(events[0], SparkplugEvent::RebirthRequired { .. }));
+
+        // Stale NDEATH is ignored
+        let ndeath = SparkplugTopic::parse("spBv1.0/plant1/NDEATH/gw01").unwrap();
+        let stale = SparkplugPayload {
+            metrics: vec![SparkplugMetric::new(BDSEQ_METRIC, SparkplugDataType::Int64, SparkplugValue::Long(2))],
+            ..Default::default()
+        };
+        assert!(state.process(&ndeath, &stale, 0).is_empty());
+
+        let death = SparkplugPayload {
+            metrics: vec![SparkplugMetric::new(BDSEQ_METRIC, SparkplugDataType::Int64, SparkplugValue::Long(3))],
+            ..Default::default()
+        };
+        let events = state.process(&ndeath, &death, 0);
+        assert_eq!(events.len(), 2);
+        assert!(!state.is_online("plant1.gw01.press3"));
+        assert!(!state.is_online("plant1.gw01"));
+    }
+
+    #[test]
+    fn test_encode_command_uses_birth_alias() {
+        let mut state = SparkplugState::new();
+        let (topic, payload) = nbirth();
+        let events = state.process(&topic, &payload, 0);
+        let SparkplugEvent::Birth { template, .. } = &events[0] else {
+            panic!("expected birth event");
+        };
+        let cmd = template
+            .commands
+            .iter()
+            .find(|c| c.name == "write_offset")
+            .unwrap();
+
+        let params = HashMap::from([("Offset".to_string(), serde_json::json!(-2))]);
+        let (topic, bytes) = state.encode_command("plant1.gw01", Some(cmd), &params, 7).unwrap();
+        assert_eq!(topic, "spBv1.0/plant1/NCMD/gw01");
+
+        let decoded = SparkplugPayload::decode(&bytes).unwrap();
+        assert_eq!(decoded.metrics[0].alias, Some(3));
+        assert_eq!(decoded.metrics[0].datatype, SparkplugDataType::Int16);
+        assert!(matches!(
+            decoded.metrics[0].to_metric_value(SparkplugDataType::Int16),
+            MetricValue::Integer(-2)
+        ));
+        assert!(state.encode_command("missing", None, &params, 0).is_err());
+    }
+
+    #[test]
+    fn test_negative_small_ints_round_trip() {
+        use serde_json::json;
+
+        let cases = [
+            (SparkplugDataType::Int8, -1i64),
+            (SparkplugDataType::Int8, i8::MIN as i64),
+            (SparkplugDataType::Int16, -2),
+            (SparkplugDataType::Int16, i16::MIN as i64),
+        ];
+        for (datatype, v) in cases {
+            let value = json_to_sparkplug_value("v", datatype, &json!(v)).unwrap();
+            // Tahu writes the 32-bit sign extension into int_value
+            assert!(matches!(value, SparkplugValue::Int(bits) if bits == v as i32 as u32));
+
+            let payload = SparkplugPayload {
+                metrics: vec![SparkplugMetric::new("v", datatype, value)],
+                ..Default::default()
+            };
+            let decoded = SparkplugPayload::decode(&payload.encode()).unwrap();
+            assert!(matches!(
+                decoded.metrics[0].to_metric_value(datatype),
+                MetricValue::Integer(got) if got == v
+            ));
+        }
+
+        // Zero-extended values from older encoders still decode
+        let legacy = SparkplugMetric::new("v", SparkplugDataType::Int8, SparkplugValue::Int(0xFF));
+        assert!(matches!(
+            legacy.to_metric_value(SparkplugDataType::Int8),
+            MetricValue::Integer(-1)
+        ));
+    }
+
+    #[test]
+    fn test_json_to_sparkplug_value_rejects_out_of_range() {
+        use serde_json::json;
+
+        let int = |datatype, value| json_to_sparkplug_value("v", datatype, &value);
+        assert!(matches!(
+            int(SparkplugDataType::Int8, json!(-1)),
+            Ok(SparkplugValue::Int(u32::MAX))
+        ));
+        assert!(matches!(
+            int(SparkplugDataType::UInt32, json!(u32::MAX)),
+            Ok(SparkplugValue::Int(u32::MAX))
+        ));
+        assert!(int(SparkplugDataType::Int8, json!(128)).is_err());
+        assert!(int(SparkplugDataType::Int16, json!(70000)).is_err());
+        assert!(int(SparkplugDataType::UInt8, json!(-1)).is_err());
+        assert!(int(SparkplugDataType::UInt32, json!(1u64 << 32)).is_err());
+        assert!(int(SparkplugDataType::UInt64, json!(-1)).is_err());
+    }
+}
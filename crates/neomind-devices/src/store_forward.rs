@@ -0,0 +1,599 @@
+//! Store-and-forward buffering for outbound MQTT publishes.
+//!
+//! Outbound publishes (device commands, forwarded telemetry) are appended to a
+//! disk-backed queue and sent from there, so they survive a broker outage or a
+//! restart. The queue is replayed in FIFO order once the broker reconnects.
+//!
+//! ## Guarantees
+//!
+//! - **Ordering**: messages are stored under a monotonically increasing sequence
+//!   number and replayed strictly in that order, so messages for the same device
+//!   are never reordered.
+//! - **Acknowledged**: an entry leaves the queue only once the broker has confirmed
+//!   it (PubAck for QoS 1, PubComp for QoS 2; see [`PublishTracker`]). A crash or
+//!   disconnect after the hand-over to the client replays it instead of losing it.
+//! - **QoS-aware**: messages are replayed with their original QoS. QoS 0 messages
+//!   are only buffered when `queue_qos0` is enabled, matching their
+//!   fire-and-forget semantics.
+//! - **Bounded**: `max_messages` and `max_bytes` evict the oldest entries on
+//!   overflow; `max_age_secs` expires stale entries before replay.
+
+use crate::mdl::DeviceError;
+use neomind_storage::snapshot::begin_write;
+use redb::{ReadableTable, ReadableTableMetadata};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+const OUTBOX_TABLE: redb::TableDefinition<u64, &[u8]> = redb::TableDefinition::new("mqtt_outbox");
+
+fn io_err(e: impl std::fmt::Display) -> DeviceError {
+    DeviceError::Io(std::io::Error::other(e.to_string()))
+}
+
+/// Store-and-forward configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StoreForwardConfig {
+    /// Buffer publishes while the broker is unreachable
+    pub enabled: bool,
+    /// Maximum number of queued messages per broker
+    pub max_messages: usize,
+    /// Maximum total payload bytes queued per broker
+    pub max_bytes: u64,
+    /// Messages older than this are dropped instead of replayed
+    pub max_age_secs: u64,
+    /// Also buffer QoS 0 publishes
+    pub queue_qos0: bool,
+}
+
+impl Default for StoreForwardConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_messages: 10_000,
+            max_bytes: 64 * 1024 * 1024,
+            max_age_secs: 24 * 3600,
+            queue_qos0: false,
+        }
+    }
+}
+
+/// A buffered outbound publish.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct QueuedPublish {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub qos: u8,
+    #[serde(default)]
+    pub retain: bool,
+    /// Originating device (for diagnostics)
+    #[serde(default)]
+    pub device_id: Option<String>,
+    /// Enqueue time (Unix seconds)
+    pub enqueued_at: i64,
+}
+
+impl QueuedPublish {
+    pub fn new(topic: impl Into<String>, payload: impl Into<Vec<u8>>, qos: u8) -> Self {
+        Self {
+            topic: topic.into(),
+            payload: payload.into(),
+            qos,
+            retain: false,
+            device_id: None,
+            enqueued_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    pub fn with_device(mut self, device_id: impl Into<String>) -> Self {
+        self.device_id = Some(device_id.into());
+        self
+    }
+
+    pub fn with_retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+}
+
+/// Backlog statistics reported through the MQTT status API.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct OutboxStats {
+    /// Messages waiting for replay
+    pub pending: usize,
+    /// Total payload bytes waiting for replay
+    pub pending_bytes: u64,
+    /// Enqueue time of the oldest pending message (Unix seconds)
+    pub oldest_enqueued_at: Option<i64>,
+    /// Messages evicted because of size limits
+    pub dropped_overflow: u64,
+    /// Messages dropped because they exceeded `max_age_secs`
+    pub dropped_expired: u64,
+    /// Messages successfully replayed since startup
+    pub replayed: u64,
+}
+
+#[derive(Debug, Default)]
+struct QueueState {
+    next_seq: u64,
+    stats: OutboxStats,
+}
+
+/// Result of an enqueue attempt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EnqueueOutcome {
+    /// Message stored for later replay
+    Queued,
+    /// QoS 0 message discarded (queueing QoS 0 disabled)
+    Discarded,
+}
+
+/// Disk-backed FIFO queue of outbound publishes for one broker.
+pub struct OutboundQueue {
+    db: redb::Database,
+    config: StoreForwardConfig,
+    state: Mutex<QueueState>,
+    replaying: AtomicBool,
+}
+
+impl OutboundQueue {
+    /// Open (or create) a queue at the given path. Existing entries are kept
+    /// so a backlog survives restarts.
+    pub fn open<P: AsRef<Path>>(path: P, config: StoreForwardConfig) -> Result<Self, DeviceError> {
+        if let Some(parent) = path.as_ref().parent() {
+            std::fs::create_dir_all(parent).map_err(DeviceError::Io)?;
+        }
+        let db = redb::Database::create(path.as_ref()).map_err(io_err)?;
+        Self::with_db(db, config)
+    }
+
+    /// Create a queue that lives only in memory.
+    pub fn memory(config: StoreForwardConfig) -> Result<Self, DeviceError> {
+        let db = redb::Database::builder()
+            .create_with_backend(redb::backends::InMemoryBackend::new())
+            .map_err(io_err)?;
+        Self::with_db(db, config)
+    }
+
+    fn with_db(db: redb::Database, config: StoreForwardConfig) -> Result<Self, DeviceError> {
+        let mut state = QueueState::default();
+
+        let write_txn = begin_write(&db).map_err(io_err)?;
+        {
+            let table = write_txn.open_table(OUTBOX_TABLE).map_err(io_err)?;
+            for entry in table.iter().map_err(io_err)? {
+                let (seq, value) = entry.map_err(io_err)?;
+                state.next_seq = seq.value() + 1;
+                if let Ok(msg) = serde_json::from_slice::<QueuedPublish>(value.value()) {
+                    state.stats.pending += 1;
+                    state.stats.pending_bytes += msg.payload.len() as u64;
+                    if state.stats.oldest_enqueued_at.is_none() {
+                        state.stats.oldest_enqueued_at = Some(msg.enqueued_at);
+                    }
+                }
+            }
+        }
+        write_txn.commit().map_err(io_err)?;
+
+        Ok(Self {
+            db,
+            config,
+            state: Mutex::new(state),
+            replaying: AtomicBool::new(false),
+        })
+    }
+
+    pub fn config(&self) -> &StoreForwardConfig {
+        &self.config
+    }
+
+    /// Current backlog statistics.
+    pub fn stats(&self) -> OutboxStats {
+        self.state
+            .lock()
+            .map(|s| s.stats.clone())
+            .unwrap_or_default()
+    }
+
+    /// Whether there is nothing waiting for replay.
+    pub fn is_empty(&self) -> bool {
+        self.stats().pending == 0
+    }
+
+    /// Append a message, evicting the oldest entries if limits are exceeded.
+    pub fn enqueue(&self, msg: QueuedPublish) -> Result<EnqueueOutcome, DeviceError> {
+        if msg.qos == 0 && !self.config.queue_qos0 {
+            return Ok(EnqueueOutcome::Discarded);
+        }
+
+        let bytes =
+            serde_json::to_vec(&msg).map_err(|e| DeviceError::Serialization(e.to_string()))?;
+        let mut state = self.state.lock().map_err(io_err)?;
+
+        let write_txn = begin_write(&self.db).map_err(io_err)?;
+        {
+            let mut table = write_txn.open_table(OUTBOX_TABLE).map_err(io_err)?;
+            table
+                .insert(state.next_seq, bytes.as_slice())
+                .map_err(io_err)?;
+
+            let mut pending = table.len().map_err(io_err)? as usize;
+            let mut pending_bytes = state.stats.pending_bytes + msg.payload.len() as u64;
+            let mut evicted = 0u64;
+            while pending > 1
+                && (pending > self.config.max_messages || pending_bytes > self.config.max_bytes)
+            {
+                let Some((_, value)) = table.pop_first().map_err(io_err)? else {
+                    break;
+                };
+                if let Ok(old) = serde_json::from_slice::<QueuedPublish>(value.value()) {
+                    pending_bytes = pending_bytes.saturating_sub(old.payload.len() as u64);
+                }
+                pending -= 1;
+                evicted += 1;
+            }
+
+            state.stats.pending = pending;
+            state.stats.pending_bytes = pending_bytes;
+            state.stats.dropped_overflow += evicted;
+            state.stats.oldest_enqueued_at = first_enqueued_at(&table)?;
+        }
+        write_txn.commit().map_err(io_err)?;
+
+        state.next_seq += 1;
+        Ok(EnqueueOutcome::Queued)
+    }
+
+    /// Return up to `limit` pending messages in FIFO order, dropping expired ones.
+    pub fn peek(&self, limit: usize, now: i64) -> Result<Vec<(u64, QueuedPublish)>, DeviceError> {
+        self.peek_from(0, limit, now)
+    }
+
+    /// Like [`Self::peek`], but starting at sequence number `from`.
+    pub fn peek_from(
+        &self,
+        from: u64,
+        limit: usize,
+        now: i64,
+    ) -> Result<Vec<(u64, QueuedPublish)>, DeviceError> {
+        self.purge_expired(now)?;
+
+        let read_txn = self.db.begin_read().map_err(io_err)?;
+        let table = read_txn.open_table(OUTBOX_TABLE).map_err(io_err)?;
+        let mut out = Vec::with_capacity(limit.min(256));
+        for entry in table.range(from..).map_err(io_err)?.take(limit) {
+            let (seq, value) = entry.map_err(io_err)?;
+            let msg = serde_json::from_slice(value.value())
+                .map_err(|e| DeviceError::Serialization(e.to_string()))?;
+            out.push((seq.value(), msg));
+        }
+        Ok(out)
+    }
+
+    /// Remove a message after the broker has acknowledged it.
+    pub fn ack(&self, seq: u64) -> Result<(), DeviceError> {
+        let mut state = self.state.lock().map_err(io_err)?;
+        let write_txn = begin_write(&self.db).map_err(io_err)?;
+        {
+            let mut table = write_txn.open_table(OUTBOX_TABLE).map_err(io_err)?;
+            if let Some(value) = table.remove(seq).map_err(io_err)? {
+                if let Ok(msg) = serde_json::from_slice::<QueuedPublish>(value.value()) {
+                    state.stats.pending_bytes = state
+                        .stats
+                        .pending_bytes
+                        .saturating_sub(msg.payload.len() as u64);
+                }
+                state.stats.pending = state.stats.pending.saturating_sub(1);
+                state.stats.replayed += 1;
+            }
+            state.stats.oldest_enqueued_at = first_enqueued_at(&table)?;
+        }
+        write_txn.commit().map_err(io_err)
+    }
+
+    /// Drop messages older than `max_age_secs`. Returns the number dropped.
+    pub fn purge_expired(&self, now: i64) -> Result<usize, DeviceError> {
+        let cutoff = now - self.config.max_age_secs as i64;
+        let mut state = self.state.lock().map_err(io_err)?;
+        if state
+            .stats
+            .oldest_enqueued_at
+            .is_none_or(|oldest| oldest >= cutoff)
+        {
+            return Ok(0);
+        }
+
+        let write_txn = begin_write(&self.db).map_err(io_err)?;
+        let mut dropped = 0;
+        {
+            let mut table = write_txn.open_table(OUTBOX_TABLE).map_err(io_err)?;
+            loop {
+                let expired = match table.first().map_err(io_err)? {
+                    Some((_, value)) => serde_json::from_slice::<QueuedPublish>(value.value())
+                        .map(|m| (m.enqueued_at < cutoff, m.payload.len() as u64))
+                        .unwrap_or((true, 0)),
+                    None => break,
+                };
+                if !expired.0 {
+                    break;
+                }
+                table.pop_first().map_err(io_err)?;
+                state.stats.pending = state.stats.pending.saturating_sub(1);
+                state.stats.pending_bytes = state.stats.pending_bytes.saturating_sub(expired.1);
+                dropped += 1;
+            }
+            state.stats.oldest_enqueued_at = first_enqueued_at(&table)?;
+        }
+        write_txn.commit().map_err(io_err)?;
+
+        state.stats.dropped_expired += dropped as u64;
+        Ok(dropped)
+    }
+
+    /// Claim the replay slot. Returns `false` if a replay is already running.
+    pub fn try_begin_replay(&self) -> bool {
+        self.replaying
+            .compare_exchange(false, true, Ordering::AcqRel, Ordering::Acquire)
+            .is_ok()
+    }
+
+    /// Release the replay slot.
+    pub fn end_replay(&self) {
+        self.replaying.store(false, Ordering::Release);
+    }
+}
+
+/// Matches outbox entries with the broker's acknowledgements for them.
+///
+/// `rumqttc::AsyncClient::publish` only hands a message to the event loop, so an
+/// outbox entry has to stay queued until the broker confirms it. Every publish on
+/// a tracked connection is registered here in hand-over order; the event loop then
+/// reports the packet id it assigned ([`Self::on_outgoing`]) and the
+/// acknowledgement for that packet ([`Self::on_ack`]).
+#[derive(Debug, Default)]
+pub struct PublishTracker {
+    order: tokio::sync::Mutex<()>,
+    state: Mutex<TrackerState>,
+}
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    /// Handed to the client but not yet written by the event loop: (outbox seq, qos)
+    unsent: VecDeque<(Option<u64>, u8)>,
+    /// Written under a packet id and waiting for PubAck/PubComp
+    awaiting_ack: HashMap<u16, Option<u64>>,
+}
+
+impl PublishTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, TrackerState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Serialize hand-overs so registration order matches the order the event loop
+    /// sees publishes in. Hold the guard across [`Self::sending`] and the client call.
+    pub async fn lock_order(&self) -> tokio::sync::MutexGuard<'_, ()> {
+        self.order.lock().await
+    }
+
+    /// Register a publish that is about to be handed to the client.
+    pub fn sending(&self, seq: Option<u64>, qos: u8) {
+        self.state().unsent.push_back((seq, qos));
+    }
+
+    /// Forget the latest registration after the hand-over failed.
+    pub fn cancel_last(&self) {
+        self.state().unsent.pop_back();
+    }
+
+    /// The event loop wrote a publish under packet id `pkid`.
+    ///
+    /// Returns the outbox entry that is already complete: QoS 0 is never acknowledged.
+    pub fn on_outgoing(&self, pkid: u16) -> Option<u64> {
+        let mut state = self.state();
+        // Publishes retransmitted after a reconnect keep their packet id
+        if pkid != 0 && state.awaiting_ack.contains_key(&pkid) {
+            return None;
+        }
+        let (seq, qos) = state.unsent.pop_front()?;
+        if qos == 0 {
+            return seq;
+        }
+        state.awaiting_ack.insert(pkid, seq);
+        None
+    }
+
+    /// The broker acknowledged packet `pkid` (PubAck for QoS 1, PubComp for QoS 2).
+    ///
+    /// Returns the outbox entry that can now be acked.
+    pub fn on_ack(&self, pkid: u16) -> Option<u64> {
+        self.state().awaiting_ack.remove(&pkid).flatten()
+    }
+
+    /// Forget every registration after the broker connection dropped.
+    ///
+    /// Unacknowledged entries stay in the outbox and are resent by the replay that
+    /// follows the reconnect, so the caller must also drop the client's own
+    /// retransmissions of them.
+    pub fn reset(&self) {
+        let mut state = self.state();
+        state.unsent.clear();
+        state.awaiting_ack.clear();
+    }
+
+    /// Whether an outbox entry was handed to the client and is not yet acknowledged.
+    pub fn is_in_flight(&self, seq: u64) -> bool {
+        let state = self.state();
+        state.unsent.iter().any(|(s, _)| *s == Some(seq))
+            || state.awaiting_ack.values().any(|s| *s == Some(seq))
+    }
+}
+
+fn first_enqueued_at(
+    table: &redb::Table<'_, u64, &'static [u8]>,
+) -> Result<Option<i64>, DeviceError> {
+    Ok(table
+        .first()
+        .map_err(io_err)?
+        .and_then(|(_, v)| serde_json::from_slice::<QueuedPublish>(v.value()).ok())
+        .map(|m| m.enqueued_at))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> StoreForwardConfig {
+        StoreForwardConfig {
+            enabled: true,
+            max_messages: 3,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_fifo_and_ack() {
+        let queue = OutboundQueue::memory(config()).unwrap();
+        for i in 0..3 {
+            let msg = QueuedPublish::new("device/t/d1/downlink", format!("{}", i), 1);
+            assert_eq!(queue.enqueue(msg).unwrap(), EnqueueOutcome::Queued);
+        }
+        assert_eq!(
+            queue.enqueue(QueuedPublish::new("x", "qos0", 0)).unwrap(),
+            EnqueueOutcome::Discarded
+        );
+
+        let now = chrono::Utc::now().timestamp();
+        let batch = queue.peek(10, now).unwrap();
+        let payloads: Vec<_> = batch.iter().map(|(_, m)| m.payload.clone()).collect();
+        assert_eq!(payloads, vec![b"0".to_vec(), b"1".to_vec(), b"2".to_vec()]);
+
+        queue.ack(batch[0].0).unwrap();
+        let stats = queue.stats();
+        assert_eq!(stats.pending, 2);
+        assert_eq!(stats.replayed, 1);
+    }
+
+    #[test]
+    fn test_overflow_and_expiry() {
+        let queue = OutboundQueue::memory(config()).unwrap();
+        for i in 0..5 {
+            let mut msg = QueuedPublish::new("t", format!("{}", i), 1);
+            msg.enqueued_at = i;
+            queue.enqueue(msg).unwrap();
+        }
+        let stats = queue.stats();
+        assert_eq!(stats.pending, 3);
+        assert_eq!(stats.dropped_overflow, 2);
+        assert_eq!(stats.oldest_enqueued_at, Some(2));
+
+        // max_age_secs = 1 day; "now" far in the future expires everything but the last
+        let now = 4 + queue.config().max_age_secs as i64;
+        let batch = queue.peek(10, now).unwrap();
+        assert_eq!(batch.len(), 1);
+        assert_eq!(queue.stats().dropped_expired, 2);
+    }
+
+    #[test]
+    fn test_backlog_survives_reopen() {
+        let path = std::env::temp_dir().join(format!("outbox_test_{}.redb", uuid::Uuid::new_v4()));
+        {
+            let queue = OutboundQueue::open(&path, config()).unwrap();
+            queue.enqueue(QueuedPublish::new("a", "1", 1)).unwrap();
+            queue.enqueue(QueuedPublish::new("b", "2", 2)).unwrap();
+        }
+        let queue = OutboundQueue::open(&path, config()).unwrap();
+        assert_eq!(queue.stats().pending, 2);
+        queue.enqueue(QueuedPublish::new("c", "3", 1)).unwrap();
+        let topics: Vec<_> = queue
+            .peek(10, chrono::Utc::now().timestamp())
+            .unwrap()
+            .into_iter()
+            .map(|(_, m)| m.topic)
+            .collect();
+        assert_eq!(topics, vec!["a", "b", "c"]);
+        let _ = std::fs::remove_file(path);
+    }
+
+    #[test]
+    fn test_peek_from() {
+        let queue = OutboundQueue::memory(config()).unwrap();
+        for topic in ["a", "b", "c"] {
+            queue.enqueue(QueuedPublish::new(topic, "", 1)).unwrap();
+        }
+        let now = chrono::Utc::now().timestamp();
+        let first = queue.peek(1, now).unwrap();
+        let rest: Vec<_> = queue
+            .peek_from(first[0].0 + 1, 10, now)
+            .unwrap()
+            .into_iter()
+            .map(|(_, m)| m.topic)
+            .collect();
+        assert_eq!(rest, vec!["b", "c"]);
+    }
+
+    #[test]
+    fn test_tracker_acks_only_on_broker_ack() {
+        let tracker = PublishTracker::new();
+        tracker.sending(Some(7), 1);
+        tracker.sending(None, 0);
+        tracker.sending(Some(8), 0);
+        tracker.sending(Some(9), 2);
+        assert!(tracker.is_in_flight(7));
+
+        // Written to the socket: QoS 1/2 still wait, QoS 0 is done
+        assert_eq!(tracker.on_outgoing(1), None);
+        assert_eq!(tracker.on_outgoing(0), None);
+        assert_eq!(tracker.on_outgoing(0), Some(8));
+        assert_eq!(tracker.on_outgoing(2), None);
+        assert!(tracker.is_in_flight(7));
+
+        // A retransmission after reconnect does not consume a registration
+        tracker.sending(Some(10), 1);
+        assert_eq!(tracker.on_outgoing(1), None);
+        assert!(tracker.is_in_flight(10));
+
+        assert_eq!(tracker.on_ack(1), Some(7));
+        assert_eq!(tracker.on_ack(2), Some(9));
+        assert_eq!(tracker.on_ack(1), None);
+        assert!(!tracker.is_in_flight(7));
+        assert!(!tracker.is_in_flight(9));
+    }
+
+    #[test]
+    fn test_tracker_reset_on_disconnect() {
+        let tracker = PublishTracker::new();
+        tracker.sending(Some(1), 1);
+        tracker.sending(Some(2), 1);
+        assert_eq!(tracker.on_outgoing(1), None);
+
+        // Connection lost with 1 awaiting its PubAck and 2 never written
+        tracker.reset();
+        assert!(!tracker.is_in_flight(1));
+        assert!(!tracker.is_in_flight(2));
+        assert_eq!(tracker.on_ack(1), None);
+
+        // The replay after the reconnect registers them again
+        tracker.sending(Some(1), 1);
+        assert!(tracker.is_in_flight(1));
+        assert_eq!(tracker.on_outgoing(1), None);
+        assert_eq!(tracker.on_ack(1), Some(1));
+    }
+
+    #[test]
+    fn test_tracker_cancel_last() {
+        let tracker = PublishTracker::new();
+        tracker.sending(Some(1), 1);
+        tracker.sending(Some(2), 1);
+        tracker.cancel_last();
+        assert!(!tracker.is_in_flight(2));
+        assert_eq!(tracker.on_outgoing(5), None);
+        assert_eq!(tracker.on_ack(5), Some(1));
+    }
+}
@@ -13,6 +13,7 @@ use serde_json::Value;
 
 use neomind_storage::DataPoint as StorageDataPoint;
 use neomind_storage::TimeSeriesStore as StorageTimeSeriesStore;
+use neomind_storage::{GroupCommitConfig, TimeSeriesConfig};
 
 use super::mdl::{DeviceError, MetricValue};
 
@@ -151,6 +152,20 @@ impl TimeSeriesStorage {
         Ok(Self { store })
     }
 
+    /// Create a time series storage that group-commits buffered writes
+    pub fn open_with_group_commit<P: AsRef<Path>>(
+        path: P,
+        group_commit: GroupCommitConfig,
+    ) -> Result<Self, DeviceError> {
+        let config = TimeSeriesConfig {
+            group_commit: Some(group_commit),
+            ..Default::default()
+        };
+        let store = StorageTimeSeriesStore::with_config(path, config)
+            .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))?;
+        Ok(Self { store })
+    }
+
     /// Create an in-memory time series storage
     pub fn memory() -> Result<Self, DeviceError> {
         let store = StorageTimeSeriesStore::memory()
@@ -375,6 +390,14 @@ impl TimeSeriesStorage {
         Ok(metrics)
     }
 
+    /// Commit buffered writes to disk (used on shutdown)
+    pub async fn flush(&self) -> Result<usize, DeviceError> {
+        self.store
+            .flush_buffered()
+            .await
+            .map_err(|e| DeviceError::Io(std::io::Error::other(e.to_string())))
+    }
+
     /// Get a reference to the underlying storage time series store
     ///
     /// This allows sharing the same storage instance between components.
@@ -193,6 +193,9 @@ async fn test_e2e_device_registration_and_command() {
             Some("device/test_sensor/sensor_001/commands"),
         ),
         adapter_id: Some("test_adapter".to_string()),
+        location: None,
+        tags: Vec::new(),
+        groups: Vec::new(),
     };
 
     service
@@ -353,6 +356,9 @@ async fn test_e2e_device_with_template() {
             None::<String>,
         ),
         adapter_id: None,
+        location: None,
+        tags: Vec::new(),
+        groups: Vec::new(),
     };
 
     service.register_device(device_config).await.unwrap();
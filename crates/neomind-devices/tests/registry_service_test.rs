@@ -77,6 +77,9 @@ async fn test_registry_device_crud() {
         adapter_type: "mqtt".to_string(),
         connection_config,
         adapter_id: Some("mqtt_adapter".to_string()),
+        location: None,
+        tags: Vec::new(),
+        groups: Vec::new(),
     };
 
     // Register device
@@ -189,6 +192,9 @@ async fn test_service_device_operations() {
         adapter_type: "mqtt".to_string(),
         connection_config,
         adapter_id: None,
+        location: None,
+        tags: Vec::new(),
+        groups: Vec::new(),
     };
 
     service.register_device(device_config).await.unwrap();
@@ -254,6 +260,9 @@ async fn test_service_command_validation() {
         adapter_type: "mqtt".to_string(),
         connection_config: ConnectionConfig::new(),
         adapter_id: None,
+        location: None,
+        tags: Vec::new(),
+        groups: Vec::new(),
     };
     service.register_device(device_config).await.unwrap();
 
@@ -0,0 +1,224 @@
+//! Store-and-Forward Integration Tests
+//!
+//! Runs the embedded broker behind a TCP proxy so the adapter's broker
+//! connection can be cut and restored, and checks that publishes made during
+//! the outage are delivered in order after reconnect.
+#![cfg(feature = "embedded-broker")]
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use neomind_core::{EventBus, MetricValue, NeoMindEvent};
+use neomind_devices::adapter::DeviceAdapter;
+use neomind_devices::adapters::mqtt::{MqttAdapter, MqttAdapterConfig};
+use neomind_devices::embedded_broker::{EmbeddedBroker, EmbeddedBrokerConfig};
+use neomind_devices::StoreForwardConfig;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::task::{JoinHandle, JoinSet};
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port()
+}
+
+/// Forward `listen_port` to `target_port`; aborting the handle drops all connections.
+async fn start_proxy(listen_port: u16, target_port: u16) -> JoinHandle<()> {
+    let listener = TcpListener::bind(("127.0.0.1", listen_port)).await.unwrap();
+    tokio::spawn(async move {
+        let mut connections = JoinSet::new();
+        while let Ok((mut inbound, _)) = listener.accept().await {
+            connections.spawn(async move {
+                if let Ok(mut outbound) = TcpStream::connect(("127.0.0.1", target_port)).await {
+                    let _ = tokio::io::copy_bidirectional(&mut inbound, &mut outbound).await;
+                }
+            });
+        }
+    })
+}
+
+async fn wait_until<F, Fut>(timeout: Duration, mut check: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    while tokio::time::Instant::now() < deadline {
+        if check().await {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+#[tokio::test]
+async fn test_publishes_survive_broker_outage() {
+    let broker_port = free_port();
+    EmbeddedBroker::new(
+        EmbeddedBrokerConfig::new()
+            .with_listen("127.0.0.1")
+            .with_port(broker_port),
+    )
+    .start()
+    .expect("embedded broker should start");
+
+    let proxy_port = free_port();
+    let proxy = start_proxy(proxy_port, broker_port).await;
+
+    // Observer connected directly to the broker, unaffected by the outage
+    let mut options = rumqttc::MqttOptions::new("sf-observer", "127.0.0.1", broker_port);
+    options.set_keep_alive(Duration::from_secs(5));
+    let (observer, mut observer_loop) = rumqttc::AsyncClient::new(options, 10);
+    observer
+        .subscribe("sf/#", rumqttc::QoS::AtLeastOnce)
+        .await
+        .unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            match observer_loop.poll().await {
+                Ok(rumqttc::Event::Incoming(rumqttc::Packet::Publish(p))) => {
+                    let _ = tx.send(String::from_utf8_lossy(&p.payload).to_string());
+                }
+                Ok(_) => {}
+                Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        }
+    });
+
+    let mut config =
+        MqttAdapterConfig::new("sf-test", "127.0.0.1").with_store_forward(StoreForwardConfig {
+            enabled: true,
+            ..Default::default()
+        });
+    config.mqtt.port = proxy_port;
+    let adapter = MqttAdapter::new(config);
+    adapter.start().await.unwrap();
+    assert!(
+        wait_until(Duration::from_secs(10), || adapter
+            .is_broker_connected("default"))
+        .await,
+        "adapter should connect through the proxy"
+    );
+
+    // Cut the connection
+    proxy.abort();
+    assert!(
+        wait_until(Duration::from_secs(30), || async {
+            !adapter.is_broker_connected("default").await
+        })
+        .await,
+        "adapter should notice the outage"
+    );
+
+    for i in 0..5 {
+        adapter
+            .publish("sf/dev1/data", format!("msg-{}", i), 1, false, Some("dev1"))
+            .await
+            .expect("publish should be queued while offline");
+    }
+    let stats = adapter.outbox_stats().await;
+    assert_eq!(stats["default"].pending, 5);
+
+    // Restore the connection; the backlog is replayed in order
+    let _proxy = start_proxy(proxy_port, broker_port).await;
+    let mut received = Vec::new();
+    while received.len() < 5 {
+        match tokio::time::timeout(Duration::from_secs(60), rx.recv()).await {
+            Ok(Some(msg)) => received.push(msg),
+            _ => break,
+        }
+    }
+    let expected: Vec<String> = (0..5).map(|i| format!("msg-{}", i)).collect();
+    assert_eq!(received, expected);
+    assert!(
+        wait_until(Duration::from_secs(5), || async {
+            adapter.outbox_stats().await["default"].pending == 0
+        })
+        .await
+    );
+
+    adapter.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn test_telemetry_forwarded_and_acked() {
+    let broker_port = free_port();
+    EmbeddedBroker::new(
+        EmbeddedBrokerConfig::new()
+            .with_listen("127.0.0.1")
+            .with_port(broker_port),
+    )
+    .start()
+    .expect("embedded broker should start");
+
+    let mut options = rumqttc::MqttOptions::new("fwd-observer", "127.0.0.1", broker_port);
+    options.set_keep_alive(Duration::from_secs(5));
+    let (observer, mut observer_loop) = rumqttc::AsyncClient::new(options, 10);
+    observer
+        .subscribe("fwd/#", rumqttc::QoS::AtLeastOnce)
+        .await
+        .unwrap();
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        loop {
+            match observer_loop.poll().await {
+                Ok(rumqttc::Event::Incoming(rumqttc::Packet::Publish(p))) => {
+                    let _ = tx.send((p.topic.clone(), p.payload.to_vec()));
+                }
+                Ok(_) => {}
+                Err(_) => tokio::time::sleep(Duration::from_millis(100)).await,
+            }
+        }
+    });
+
+    let mut config = MqttAdapterConfig::new("fwd-test", "127.0.0.1")
+        .with_store_forward(StoreForwardConfig {
+            enabled: true,
+            ..Default::default()
+        })
+        .with_telemetry_forwarding("fwd");
+    config.mqtt.port = broker_port;
+    let event_bus = Arc::new(EventBus::new());
+    let adapter = MqttAdapter::new(config).with_event_bus(event_bus.clone());
+    adapter.start().await.unwrap();
+    assert!(
+        wait_until(Duration::from_secs(10), || adapter
+            .is_broker_connected("default"))
+        .await,
+        "adapter should connect"
+    );
+
+    event_bus
+        .publish(NeoMindEvent::DeviceMetric {
+            device_id: "dev1".to_string(),
+            metric: "temperature".to_string(),
+            value: MetricValue::Float(21.5),
+            timestamp: 1_700_000_000,
+            quality: None,
+        })
+        .await;
+
+    let (topic, payload) = tokio::time::timeout(Duration::from_secs(10), rx.recv())
+        .await
+        .expect("forwarded telemetry should arrive")
+        .unwrap();
+    assert_eq!(topic, "fwd/dev1/temperature");
+    let payload: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+    assert_eq!(payload["value"], 21.5);
+    assert_eq!(payload["timestamp"], 1_700_000_000);
+
+    // The outbox entry is removed once the broker acknowledges it
+    assert!(
+        wait_until(Duration::from_secs(5), || async {
+            adapter.outbox_stats().await["default"].pending == 0
+        })
+        .await
+    );
+    assert_eq!(adapter.outbox_stats().await["default"].replayed, 1);
+
+    adapter.stop().await.unwrap();
+}
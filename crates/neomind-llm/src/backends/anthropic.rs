@@ -0,0 +1,1280 @@
+//! Native Anthropic Messages API backend.
+//!
+//! Unlike the OpenAI-compatible path in [`super::openai`], this speaks the
+//! Messages API directly:
+//! - system prompts are sent as top-level blocks with prompt-cache markers,
+//!   so long agent system prompts and tool lists are cached between turns
+//! - tool calls use `tool_use` / `tool_result` content blocks
+//! - extended thinking is requested with a token budget on models that
+//!   support it and streamed as thinking chunks; thinking blocks are sent
+//!   back unchanged with the assistant turn that produced them
+//! - streaming handles the Messages event types (`content_block_delta`,
+//!   `message_delta`, ...)
+//! - token usage, including cache reads and writes, is reported
+
+use std::collections::{BTreeMap, VecDeque};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+use futures::{Stream, StreamExt};
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use neomind_core::llm::backend::{
+    BackendCapabilities, BackendId, BackendMetrics, FinishReason, LlmError, LlmInput, LlmOutput,
+    LlmRuntime, StreamChunk, StreamFinish, TokenUsage, ToolDefinition,
+};
+use neomind_core::llm::usage::{StreamUsage, UsageMeter};
+use neomind_core::message::{Content, ContentPart, Message, MessageRole};
+
+use super::openai::{extract_data_url, hash_api_key, is_vision_model, CloudProvider};
+use crate::rate_limited_client::{ProviderRateLimits, RateLimitedClient};
+
+/// Messages API version sent in the `anthropic-version` header.
+pub const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Default API base URL.
+const DEFAULT_BASE_URL: &str = "https://api.anthropic.com/v1";
+
+/// Default model.
+const DEFAULT_MODEL: &str = "claude-3-5-sonnet-20241022";
+
+/// Output tokens when the caller sets no limit (the API requires one).
+const DEFAULT_MAX_TOKENS: u32 = 8192;
+
+/// Upper bound for requested output tokens.
+const MAX_TOKENS_CAP: u32 = 32768;
+
+/// Smallest thinking budget the API accepts.
+const MIN_THINKING_BUDGET: u32 = 1024;
+
+/// Recent answers whose thinking blocks are kept for the next turn.
+const THINKING_HISTORY_LEN: usize = 32;
+
+/// Configuration for the Anthropic backend.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct AnthropicConfig {
+    /// API key for authentication.
+    pub api_key: String,
+
+    /// Model to use (default: claude-3-5-sonnet).
+    pub model: Option<String>,
+
+    /// Base URL (default: https://api.anthropic.com/v1).
+    pub base_url: Option<String>,
+
+    /// Request timeout in seconds (default: 60).
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Mark the system prompt and tool definitions as cacheable (default: true).
+    #[serde(default = "default_prompt_cache")]
+    pub prompt_cache: bool,
+
+    /// Thinking budget in tokens when extended thinking is enabled.
+    /// Defaults to half of the output token limit.
+    #[serde(default)]
+    pub thinking_budget_tokens: Option<u32>,
+}
+
+fn default_timeout_secs() -> u64 {
+    60
+}
+
+fn default_prompt_cache() -> bool {
+    true
+}
+
+impl AnthropicConfig {
+    /// Create a new config.
+    pub fn new(api_key: impl Into<String>) -> Self {
+        Self {
+            api_key: api_key.into(),
+            model: None,
+            base_url: None,
+            timeout_secs: default_timeout_secs(),
+            prompt_cache: default_prompt_cache(),
+            thinking_budget_tokens: None,
+        }
+    }
+
+    /// Set the model.
+    pub fn with_model(mut self, model: impl Into<String>) -> Self {
+        self.model = Some(model.into());
+        self
+    }
+
+    /// Set the timeout in seconds.
+    pub fn with_timeout_secs(mut self, timeout_secs: u64) -> Self {
+        self.timeout_secs = timeout_secs;
+        self
+    }
+
+    /// Set the base URL (optional, for proxies and test servers).
+    pub fn with_base_url_opt(mut self, base_url: Option<String>) -> Self {
+        self.base_url = base_url;
+        self
+    }
+
+    /// Enable or disable prompt-cache markers.
+    pub fn with_prompt_cache(mut self, prompt_cache: bool) -> Self {
+        self.prompt_cache = prompt_cache;
+        self
+    }
+
+    /// Set the thinking budget in tokens.
+    pub fn with_thinking_budget(mut self, budget_tokens: u32) -> Self {
+        self.thinking_budget_tokens = Some(budget_tokens);
+        self
+    }
+
+    /// Get the timeout as a Duration.
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.timeout_secs)
+    }
+
+    /// Get the effective base URL.
+    fn get_base_url(&self) -> &str {
+        self.base_url
+            .as_deref()
+            .filter(|url| !url.is_empty())
+            .unwrap_or(DEFAULT_BASE_URL)
+            .trim_end_matches('/')
+    }
+
+    /// Get the effective model name.
+    fn get_model(&self) -> String {
+        self.model
+            .clone()
+            .filter(|model| !model.is_empty())
+            .unwrap_or_else(|| DEFAULT_MODEL.to_string())
+    }
+}
+
+/// Token usage reported by the Messages API.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AnthropicUsage {
+    /// Uncached input tokens.
+    #[serde(default)]
+    pub input_tokens: u32,
+    /// Output tokens, including thinking.
+    #[serde(default)]
+    pub output_tokens: u32,
+    /// Input tokens written to the prompt cache.
+    #[serde(default)]
+    pub cache_creation_input_tokens: u32,
+    /// Input tokens read from the prompt cache.
+    #[serde(default)]
+    pub cache_read_input_tokens: u32,
+}
+
+impl AnthropicUsage {
+    /// Total input tokens, cached or not.
+    pub fn prompt_tokens(&self) -> u32 {
+        self.input_tokens + self.cache_creation_input_tokens + self.cache_read_input_tokens
+    }
+}
+
+impl From<AnthropicUsage> for TokenUsage {
+    fn from(usage: AnthropicUsage) -> Self {
+        TokenUsage::new(usage.prompt_tokens(), usage.output_tokens)
+    }
+}
+
+/// Anthropic LLM runtime backend.
+pub struct AnthropicRuntime {
+    config: AnthropicConfig,
+    client: RateLimitedClient,
+    model: String,
+    metrics: Arc<RwLock<BackendMetrics>>,
+    last_usage: Arc<RwLock<Option<AnthropicUsage>>>,
+    thinking_history: Arc<Mutex<ThinkingHistory>>,
+}
+
+impl AnthropicRuntime {
+    /// Create a new Anthropic runtime.
+    pub fn new(config: AnthropicConfig) -> Result<Self, LlmError> {
+        let http_client = Client::builder()
+            .timeout(config.timeout())
+            .pool_max_idle_per_host(10)
+            .pool_idle_timeout(Duration::from_secs(120))
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| LlmError::Network(e.to_string()))?;
+
+        let (max_requests, window_duration) = ProviderRateLimits::default().anthropic;
+        let client =
+            RateLimitedClient::with_rate_limits(http_client, max_requests, window_duration);
+
+        let model = config.get_model();
+
+        Ok(Self {
+            config,
+            client,
+            model,
+            metrics: Arc::new(RwLock::new(BackendMetrics::default())),
+            last_usage: Arc::new(RwLock::new(None)),
+            thinking_history: Arc::new(Mutex::new(ThinkingHistory::default())),
+        })
+    }
+
+    /// Token usage of the last completed request, including cache statistics.
+    pub fn last_usage(&self) -> Option<AnthropicUsage> {
+        *self.last_usage.read().unwrap()
+    }
+
+    fn messages_url(&self) -> String {
+        format!("{}/messages", self.config.get_base_url())
+    }
+
+    fn rate_limit_key(&self) -> String {
+        format!("Anthropic:{:x}", hash_api_key(&self.config.api_key))
+    }
+
+    fn post(&self, request: &MessagesRequest) -> reqwest::RequestBuilder {
+        self.client
+            .inner()
+            .post(self.messages_url())
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", ANTHROPIC_VERSION)
+            .json(request)
+    }
+
+    /// Build a Messages API request from the generic input.
+    fn build_request(&self, input: &LlmInput, stream: bool) -> MessagesRequest {
+        let cache_control = self.config.prompt_cache.then(CacheControl::ephemeral);
+
+        let mut max_tokens = match input.params.max_tokens {
+            Some(v) if v >= usize::MAX - 1000 => DEFAULT_MAX_TOKENS,
+            Some(v) => (v as u32).clamp(1, MAX_TOKENS_CAP),
+            None => DEFAULT_MAX_TOKENS,
+        };
+
+        let model = input.model.clone().unwrap_or_else(|| self.model.clone());
+
+        // Older models reject the thinking parameter, even when the backend
+        // settings enable thinking
+        let thinking_enabled = supports_thinking(&model)
+            && input
+                .params
+                .thinking_enabled
+                .unwrap_or(self.config.thinking_budget_tokens.is_some());
+        let thinking = thinking_enabled.then(|| {
+            let budget_tokens = self
+                .config
+                .thinking_budget_tokens
+                .unwrap_or(max_tokens / 2)
+                .max(MIN_THINKING_BUDGET);
+            // The budget counts towards max_tokens and must leave room for the answer
+            max_tokens = max_tokens.max(budget_tokens + MIN_THINKING_BUDGET);
+            ThinkingConfig {
+                typ: "enabled",
+                budget_tokens,
+            }
+        });
+
+        let system: Vec<SystemBlock> = input
+            .messages
+            .iter()
+            .filter(|msg| msg.role == MessageRole::System)
+            .map(|msg| msg.content.as_text())
+            .filter(|text| !text.is_empty())
+            .map(|text| SystemBlock {
+                typ: "text",
+                text,
+                cache_control: None,
+            })
+            .collect();
+
+        let mut tools: Option<Vec<ApiTool>> = input
+            .tools
+            .as_ref()
+            .filter(|tools| !tools.is_empty())
+            .map(|tools| tools.iter().map(ApiTool::from).collect());
+
+        let messages = {
+            let history = self.thinking_history.lock().unwrap();
+            messages_to_api(&input.messages, thinking.is_some().then_some(&*history))
+        };
+
+        let mut request = MessagesRequest {
+            model,
+            system: (!system.is_empty()).then_some(system),
+            messages,
+            max_tokens,
+            // Sampling parameters cannot be combined with extended thinking
+            temperature: input.params.temperature.filter(|_| thinking.is_none()),
+            top_p: input.params.top_p.filter(|_| thinking.is_none()),
+            top_k: input.params.top_k.filter(|_| thinking.is_none()),
+            stop_sequences: input.params.stop.clone().filter(|stop| !stop.is_empty()),
+            stream,
+            tools: None,
+            thinking,
+        };
+
+        // Cache breakpoints go at the end of the stable prefix: tools, then system
+        if let Some(cache_control) = cache_control {
+            if let Some(last) = tools.as_mut().and_then(|tools| tools.last_mut()) {
+                last.cache_control = Some(cache_control.clone());
+            }
+            if let Some(last) = request.system.as_mut().and_then(|s| s.last_mut()) {
+                last.cache_control = Some(cache_control);
+            }
+        }
+        request.tools = tools;
+
+        request
+    }
+
+    fn record_usage(&self, usage: AnthropicUsage, latency_ms: u64) {
+        *self.last_usage.write().unwrap() = Some(usage);
+        self.metrics
+            .write()
+            .unwrap()
+            .record_success(usage.output_tokens as u64, latency_ms);
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmRuntime for AnthropicRuntime {
+    fn backend_id(&self) -> BackendId {
+        BackendId::new("anthropic")
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model
+    }
+
+    async fn is_available(&self) -> bool {
+        !self.config.api_key.is_empty()
+    }
+
+    async fn generate(&self, input: LlmInput) -> Result<LlmOutput, LlmError> {
+        let start_time = Instant::now();
+        let meter = UsageMeter::start(
+            "anthropic",
+            input.model.as_deref().unwrap_or(&self.model),
+            &input,
+        )
+        .await?;
+        let request = self.build_request(&input, false);
+
+        let response = self
+            .client
+            .execute_request(&self.rate_limit_key(), self.post(&request).build().unwrap())
+            .await
+            .map_err(|e| {
+                self.metrics.write().unwrap().record_failure();
+                LlmError::Network(e.to_string())
+            })?;
+
+        let status = response.status();
+        let body = response
+            .text()
+            .await
+            .map_err(|e| LlmError::Network(e.to_string()))?;
+
+        if !status.is_success() {
+            self.metrics.write().unwrap().record_failure();
+            return Err(api_error(status.as_u16(), &body));
+        }
+
+        let response: MessagesResponse =
+            serde_json::from_str(&body).map_err(LlmError::Serialization)?;
+
+        let mut text = String::new();
+        let mut thinking = String::new();
+        let mut thinking_blocks = Vec::new();
+        let mut tool_calls = Vec::new();
+        for block in response.content {
+            match block {
+                ResponseBlock::Text { text: t } => text.push_str(&t),
+                ResponseBlock::Thinking {
+                    thinking: t,
+                    signature,
+                } => {
+                    thinking.push_str(&t);
+                    thinking_blocks.push(ContentBlock::Thinking {
+                        thinking: t,
+                        signature,
+                    });
+                }
+                ResponseBlock::RedactedThinking { data } => {
+                    thinking_blocks.push(ContentBlock::RedactedThinking { data });
+                }
+                ResponseBlock::ToolUse { id, name, input } => tool_calls.push(serde_json::json!({
+                    "id": id,
+                    "name": name,
+                    "arguments": input
+                })),
+                ResponseBlock::Other => {}
+            }
+        }
+
+        // Tool calls are appended as a JSON array, like the OpenAI-compatible backends
+        if !tool_calls.is_empty() {
+            tracing::debug!("Anthropic: received {} tool calls", tool_calls.len());
+            text.push_str(&serde_json::to_string(&tool_calls).unwrap_or_default());
+        }
+        self.thinking_history
+            .lock()
+            .unwrap()
+            .remember(&text, thinking_blocks);
+
+        self.record_usage(response.usage, start_time.elapsed().as_millis() as u64);
+        meter.finish(Some(response.usage.into()), &text);
+
+        Ok(LlmOutput {
+            text,
+            finish_reason: finish_reason(response.stop_reason.as_deref()),
+            usage: Some(response.usage.into()),
+            thinking: (!thinking.is_empty()).then_some(thinking),
+        })
+    }
+
+    async fn generate_stream(
+        &self,
+        input: LlmInput,
+    ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+        use tokio::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel(64);
+
+        let meter = UsageMeter::start(
+            "anthropic",
+            input.model.as_deref().unwrap_or(&self.model),
+            &input,
+        )
+        .await?;
+        let start_time = Instant::now();
+        let request = self.post(&self.build_request(&input, true));
+        let rate_limiter = self.client.clone();
+        let rate_limit_key = self.rate_limit_key();
+        let metrics = self.metrics.clone();
+        let last_usage = self.last_usage.clone();
+        let thinking_history = self.thinking_history.clone();
+        let stream_usage = StreamUsage::default();
+        let reported_usage = stream_usage.clone();
+        let stream_finish = StreamFinish::current();
+
+        tokio::spawn(async move {
+            rate_limiter.acquire(&rate_limit_key).await;
+
+            let response = match request.send().await {
+                Ok(response) => response,
+                Err(e) => {
+                    metrics.write().unwrap().record_failure();
+                    let _ = tx.send(Err(LlmError::Network(e.to_string()))).await;
+                    return;
+                }
+            };
+
+            let status = response.status();
+            if !status.is_success() {
+                metrics.write().unwrap().record_failure();
+                let body = response.text().await.unwrap_or_default();
+                let _ = tx.send(Err(api_error(status.as_u16(), &body))).await;
+                return;
+            }
+
+            let mut stream = response.bytes_stream();
+            let mut buffer = Vec::new();
+            let mut state = StreamState::default();
+
+            while let Some(chunk_result) = stream.next().await {
+                let chunk = match chunk_result {
+                    Ok(chunk) => chunk,
+                    Err(e) => {
+                        metrics.write().unwrap().record_failure();
+                        let _ = tx.send(Err(LlmError::Network(e.to_string()))).await;
+                        return;
+                    }
+                };
+                buffer.extend_from_slice(&chunk);
+
+                // Process complete lines; event names are repeated in the data
+                while let Some(nl_pos) = buffer.iter().position(|&b| b == b'\n') {
+                    let line: Vec<u8> = buffer.drain(..=nl_pos).collect();
+                    let line = String::from_utf8_lossy(&line);
+                    let Some(data) = line.trim().strip_prefix("data:") else {
+                        continue;
+                    };
+                    let event = match serde_json::from_str::<StreamEvent>(data.trim()) {
+                        Ok(event) => event,
+                        Err(e) => {
+                            tracing::debug!("Anthropic: skipping stream event: {}", e);
+                            continue;
+                        }
+                    };
+
+                    let items = state.handle(event);
+                    if state.done {
+                        // Before the final chunk goes out, so the meter sees it
+                        reported_usage.set(state.usage.into());
+                        stream_finish.set(finish_reason(state.stop_reason.as_deref()));
+                    }
+                    for item in items {
+                        if tx.send(item).await.is_err() {
+                            return;
+                        }
+                    }
+                    if state.done {
+                        *last_usage.write().unwrap() = Some(state.usage);
+                        if state.failed {
+                            metrics.write().unwrap().record_failure();
+                            return;
+                        }
+                        state.remember_thinking(&mut thinking_history.lock().unwrap());
+                        metrics.write().unwrap().record_success(
+                            state.usage.output_tokens as u64,
+                            start_time.elapsed().as_millis() as u64,
+                        );
+                        return;
+                    }
+                }
+            }
+
+            // The connection closed before message_stop: the answer is cut off
+            metrics.write().unwrap().record_failure();
+            let _ = tx
+                .send(Err(LlmError::Network(
+                    "Stream ended before the message was complete".to_string(),
+                )))
+                .await;
+        });
+
+        Ok(Box::pin(meter.stream_with_usage(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+            stream_usage,
+        )))
+    }
+
+    fn max_context_length(&self) -> usize {
+        200000
+    }
+
+    fn supports_multimodal(&self) -> bool {
+        is_vision_model(&CloudProvider::Anthropic, &self.model)
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        let supports_multimodal = self.supports_multimodal();
+
+        BackendCapabilities {
+            streaming: true,
+            multimodal: supports_multimodal,
+            function_calling: true,
+            multiple_models: true,
+            max_context: Some(self.max_context_length()),
+            modalities: vec!["text".to_string()],
+            thinking_display: supports_thinking(&self.model),
+            supports_images: supports_multimodal,
+            supports_audio: false,
+        }
+    }
+
+    fn metrics(&self) -> BackendMetrics {
+        self.metrics.read().unwrap().clone()
+    }
+}
+
+/// Accumulates streaming events into chunks.
+#[derive(Default)]
+struct StreamState {
+    usage: AnthropicUsage,
+    /// Tool calls being streamed, by content block index
+    tool_calls: BTreeMap<u32, StreamedToolCall>,
+    /// Thinking blocks with their signatures, by content block index
+    thinking_blocks: BTreeMap<u32, ContentBlock>,
+    /// Answer text sent so far, tool call JSON included
+    text: String,
+    stop_reason: Option<String>,
+    done: bool,
+    /// The stream ended with an error event
+    failed: bool,
+}
+
+struct StreamedToolCall {
+    id: String,
+    name: String,
+    input_json: String,
+}
+
+impl StreamState {
+    fn handle(&mut self, event: StreamEvent) -> Vec<StreamChunk> {
+        let chunks = self.handle_event(event);
+        for (text, _) in chunks.iter().flatten().filter(|(_, thinking)| !thinking) {
+            self.text.push_str(text);
+        }
+        chunks
+    }
+
+    /// Hand the answer's thinking blocks to `history`.
+    fn remember_thinking(&mut self, history: &mut ThinkingHistory) {
+        let blocks = std::mem::take(&mut self.thinking_blocks)
+            .into_values()
+            .collect();
+        history.remember(&self.text, blocks);
+    }
+
+    fn handle_event(&mut self, event: StreamEvent) -> Vec<StreamChunk> {
+        match event {
+            StreamEvent::MessageStart { message } => {
+                self.usage = message.usage;
+                vec![]
+            }
+            StreamEvent::ContentBlockStart {
+                index,
+                content_block,
+            } => match content_block {
+                ResponseBlock::ToolUse { id, name, .. } => {
+                    self.tool_calls.insert(
+                        index,
+                        StreamedToolCall {
+                            id,
+                            name,
+                            input_json: String::new(),
+                        },
+                    );
+                    vec![]
+                }
+                ResponseBlock::Text { text } if !text.is_empty() => vec![Ok((text, false))],
+                ResponseBlock::Thinking {
+                    thinking,
+                    signature,
+                } => {
+                    self.thinking_blocks.insert(
+                        index,
+                        ContentBlock::Thinking {
+                            thinking: thinking.clone(),
+                            signature,
+                        },
+                    );
+                    if thinking.is_empty() {
+                        vec![]
+                    } else {
+                        vec![Ok((thinking, true))]
+                    }
+                }
+                ResponseBlock::RedactedThinking { data } => {
+                    self.thinking_blocks
+                        .insert(index, ContentBlock::RedactedThinking { data });
+                    vec![]
+                }
+                _ => vec![],
+            },
+            StreamEvent::ContentBlockDelta { index, delta } => match delta {
+                Delta::Text { text } => vec![Ok((text, false))],
+                Delta::Thinking { thinking } => {
+                    if let Some(ContentBlock::Thinking {
+                        thinking: block, ..
+                    }) = self.thinking_blocks.get_mut(&index)
+                    {
+                        block.push_str(&thinking);
+                    }
+                    vec![Ok((thinking, true))]
+                }
+                Delta::Signature { signature } => {
+                    if let Some(ContentBlock::Thinking {
+                        signature: block, ..
+                    }) = self.thinking_blocks.get_mut(&index)
+                    {
+                        block.push_str(&signature);
+                    }
+                    vec![]
+                }
+                Delta::InputJson { partial_json } => {
+                    if let Some(call) = self.tool_calls.get_mut(&index) {
+                        call.input_json.push_str(&partial_json);
+                    }
+                    vec![]
+                }
+                Delta::Other => vec![],
+            },
+            StreamEvent::MessageDelta { delta, usage } => {
+                if let Some(usage) = usage {
+                    self.usage.output_tokens = usage.output_tokens;
+                }
+                if let Some(stop_reason) = delta.and_then(|d| d.stop_reason) {
+                    self.stop_reason = Some(stop_reason);
+                }
+                vec![]
+            }
+            StreamEvent::MessageStop => {
+                self.done = true;
+                if self.tool_calls.is_empty() {
+                    return vec![];
+                }
+                let tool_calls: Vec<serde_json::Value> = std::mem::take(&mut self.tool_calls)
+                    .into_values()
+                    .map(|call| {
+                        let arguments: serde_json::Value = serde_json::from_str(&call.input_json)
+                            .unwrap_or_else(|_| serde_json::json!({}));
+                        serde_json::json!({
+                            "id": call.id,
+                            "name": call.name,
+                            "arguments": arguments
+                        })
+                    })
+                    .collect();
+                vec![Ok((
+                    serde_json::to_string(&tool_calls).unwrap_or_default(),
+                    false,
+                ))]
+            }
+            StreamEvent::Error { error } => {
+                self.done = true;
+                self.failed = true;
+                vec![Err(LlmError::Generation(format!(
+                    "API error {}: {}",
+                    error.typ, error.message
+                )))]
+            }
+            StreamEvent::ContentBlockStop | StreamEvent::Other => vec![],
+        }
+    }
+}
+
+// Helper functions
+
+/// Convert conversation messages to API messages.
+///
+/// System messages are sent separately. Consecutive messages with the same
+/// role are merged, as the API requires alternating turns. An assistant turn
+/// ending in a tool call JSON array (as produced by this backend) followed by
+/// tool messages becomes `tool_use` blocks answered by `tool_result` blocks.
+/// With `thinking`, an assistant turn this backend produced starts with its
+/// thinking blocks, as the API requires while thinking is enabled.
+fn messages_to_api(messages: &[Message], thinking: Option<&ThinkingHistory>) -> Vec<ApiMessage> {
+    let conversation: Vec<&Message> = messages
+        .iter()
+        .filter(|msg| msg.role != MessageRole::System)
+        .collect();
+
+    let mut api_messages: Vec<ApiMessage> = Vec::new();
+    let mut pending_tool_ids: std::collections::VecDeque<String> = Default::default();
+
+    for (i, msg) in conversation.iter().enumerate() {
+        let (role, blocks) = match msg.role {
+            MessageRole::Assistant => {
+                let text = msg.content.as_text();
+                let followed_by_tool = conversation
+                    .get(i + 1)
+                    .is_some_and(|next| next.role == MessageRole::Tool);
+                let (prefix, tool_calls) = match split_tool_calls(&text) {
+                    Some((prefix, calls)) if followed_by_tool => (prefix, calls),
+                    _ => (text.as_str(), Vec::new()),
+                };
+
+                // Thinking blocks must lead the turn, so merged turns go without
+                let starts_turn = api_messages
+                    .last()
+                    .is_none_or(|last| last.role != "assistant");
+                let mut blocks = thinking
+                    .filter(|_| starts_turn)
+                    .and_then(|history| history.get(&text))
+                    .map(<[ContentBlock]>::to_vec)
+                    .unwrap_or_default();
+                if !prefix.trim().is_empty() {
+                    blocks.push(ContentBlock::Text {
+                        text: prefix.trim_end().to_string(),
+                    });
+                }
+                pending_tool_ids.clear();
+                for (n, call) in tool_calls.into_iter().enumerate() {
+                    let id = call.id.unwrap_or_else(|| format!("toolu_{}_{}", i, n));
+                    pending_tool_ids.push_back(id.clone());
+                    blocks.push(ContentBlock::ToolUse {
+                        id,
+                        name: call.name,
+                        input: call.arguments,
+                    });
+                }
+                ("assistant", blocks)
+            }
+            MessageRole::Tool => {
+                let blocks = match pending_tool_ids.pop_front() {
+                    Some(tool_use_id) => vec![ContentBlock::ToolResult {
+                        tool_use_id,
+                        content: msg.content.as_text(),
+                    }],
+                    None => content_to_blocks(&msg.content),
+                };
+                ("user", blocks)
+            }
+            _ => ("user", content_to_blocks(&msg.content)),
+        };
+
+        if blocks.is_empty() {
+            continue;
+        }
+        match api_messages.last_mut() {
+            Some(last) if last.role == role => last.content.extend(blocks),
+            _ => api_messages.push(ApiMessage {
+                role,
+                content: blocks,
+            }),
+        }
+    }
+
+    api_messages
+}
+
+/// Thinking blocks of recent answers, keyed by the answer text.
+///
+/// Conversation messages only carry text, but the API checks that thinking
+/// blocks come back unchanged, signatures included. The answer text this
+/// backend returned identifies the turn they belong to.
+#[derive(Debug, Default)]
+struct ThinkingHistory {
+    entries: VecDeque<(String, Vec<ContentBlock>)>,
+}
+
+impl ThinkingHistory {
+    fn remember(&mut self, text: &str, blocks: Vec<ContentBlock>) {
+        let key = text.trim();
+        if key.is_empty() || blocks.is_empty() {
+            return;
+        }
+        self.entries.retain(|(k, _)| k != key);
+        if self.entries.len() == THINKING_HISTORY_LEN {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((key.to_string(), blocks));
+    }
+
+    fn get(&self, text: &str) -> Option<&[ContentBlock]> {
+        let key = text.trim();
+        self.entries
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, blocks)| blocks.as_slice())
+    }
+}
+
+fn content_to_blocks(content: &Content) -> Vec<ContentBlock> {
+    match content {
+        Content::Text(text) if text.is_empty() => vec![],
+        Content::Text(text) => vec![ContentBlock::Text { text: text.clone() }],
+        Content::Parts(parts) => parts
+            .iter()
+            .map(|part| match part {
+                ContentPart::Text { text } => ContentBlock::Text { text: text.clone() },
+                ContentPart::ImageUrl { url, .. } if !url.starts_with("data:") => {
+                    ContentBlock::Image {
+                        source: ImageSource::Url { url: url.clone() },
+                    }
+                }
+                ContentPart::ImageUrl { url, .. } => {
+                    let (media_type, data) = extract_data_url(url);
+                    ContentBlock::Image {
+                        source: ImageSource::Base64 { media_type, data },
+                    }
+                }
+                ContentPart::ImageBase64 {
+                    data, mime_type, ..
+                } => ContentBlock::Image {
+                    source: ImageSource::Base64 {
+                        media_type: mime_type.clone(),
+                        data: data.clone(),
+                    },
+                },
+            })
+            .collect(),
+    }
+}
+
+/// Tool call as serialized into response text.
+#[derive(Debug, Deserialize)]
+struct EmittedToolCall {
+    #[serde(default)]
+    id: Option<String>,
+    name: String,
+    #[serde(default)]
+    arguments: serde_json::Value,
+}
+
+/// Split trailing tool calls (a JSON array) off an assistant response.
+fn split_tool_calls(text: &str) -> Option<(&str, Vec<EmittedToolCall>)> {
+    let trimmed = text.trim_end();
+    if !trimmed.ends_with(']') {
+        return None;
+    }
+    trimmed.match_indices('[').find_map(|(start, _)| {
+        serde_json::from_str::<Vec<EmittedToolCall>>(&trimmed[start..])
+            .ok()
+            .filter(|calls| !calls.is_empty())
+            .map(|calls| (&text[..start], calls))
+    })
+}
+
+fn finish_reason(stop_reason: Option<&str>) -> FinishReason {
+    match stop_reason {
+        Some("end_turn" | "stop_sequence" | "tool_use" | "pause_turn") | None => FinishReason::Stop,
+        Some("max_tokens") => FinishReason::Length,
+        Some("refusal") => FinishReason::ContentFilter,
+        Some(_) => FinishReason::Error,
+    }
+}
+
+fn api_error(status: u16, body: &str) -> LlmError {
+    let message = serde_json::from_str::<ErrorResponse>(body)
+        .map(|e| format!("{}: {}", e.error.typ, e.error.message))
+        .unwrap_or_else(|_| body.to_string());
+    LlmError::Generation(format!("API error {}: {}", status, message))
+}
+
+/// Whether a model supports extended thinking (Claude 3.7 and later).
+fn supports_thinking(model: &str) -> bool {
+    let name = model.to_lowercase();
+    name.contains("claude-3-7")
+        || name.contains("claude-4")
+        || ["sonnet-4", "opus-4", "haiku-4"]
+            .iter()
+            .any(|family| name.contains(family))
+}
+
+// API types
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<Vec<SystemBlock>>,
+    messages: Vec<ApiMessage>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_k: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    tools: Option<Vec<ApiTool>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    thinking: Option<ThinkingConfig>,
+}
+
+/// Prompt-cache breakpoint.
+#[derive(Debug, Clone, Serialize)]
+struct CacheControl {
+    #[serde(rename = "type")]
+    typ: &'static str,
+}
+
+impl CacheControl {
+    fn ephemeral() -> Self {
+        Self { typ: "ephemeral" }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct SystemBlock {
+    #[serde(rename = "type")]
+    typ: &'static str,
+    text: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+#[derive(Debug, Serialize)]
+struct ThinkingConfig {
+    #[serde(rename = "type")]
+    typ: &'static str,
+    budget_tokens: u32,
+}
+
+#[derive(Debug, Serialize)]
+struct ApiTool {
+    name: String,
+    description: String,
+    input_schema: serde_json::Value,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cache_control: Option<CacheControl>,
+}
+
+impl From<&ToolDefinition> for ApiTool {
+    fn from(tool: &ToolDefinition) -> Self {
+        Self {
+            name: tool.name.clone(),
+            description: tool.description.clone(),
+            input_schema: tool.parameters.clone(),
+            cache_control: None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct ApiMessage {
+    role: &'static str,
+    content: Vec<ContentBlock>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ContentBlock {
+    Text {
+        text: String,
+    },
+    Thinking {
+        thinking: String,
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    Image {
+        source: ImageSource,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        input: serde_json::Value,
+    },
+    ToolResult {
+        tool_use_id: String,
+        content: String,
+    },
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ImageSource {
+    Base64 { media_type: String, data: String },
+    Url { url: String },
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    content: Vec<ResponseBlock>,
+    #[serde(default)]
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: AnthropicUsage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ResponseBlock {
+    Text {
+        #[serde(default)]
+        text: String,
+    },
+    Thinking {
+        #[serde(default)]
+        thinking: String,
+        #[serde(default)]
+        signature: String,
+    },
+    RedactedThinking {
+        data: String,
+    },
+    ToolUse {
+        id: String,
+        name: String,
+        #[serde(default)]
+        input: serde_json::Value,
+    },
+    /// Future block types
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum StreamEvent {
+    MessageStart {
+        message: StreamMessage,
+    },
+    ContentBlockStart {
+        index: u32,
+        content_block: ResponseBlock,
+    },
+    ContentBlockDelta {
+        index: u32,
+        delta: Delta,
+    },
+    ContentBlockStop,
+    MessageDelta {
+        #[serde(default)]
+        delta: Option<MessageDeltaBody>,
+        #[serde(default)]
+        usage: Option<OutputUsage>,
+    },
+    MessageStop,
+    Error {
+        error: ApiErrorBody,
+    },
+    /// Pings and future event types
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamMessage {
+    #[serde(default)]
+    usage: AnthropicUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessageDeltaBody {
+    #[serde(default)]
+    stop_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OutputUsage {
+    #[serde(default)]
+    output_tokens: u32,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type")]
+enum Delta {
+    #[serde(rename = "text_delta")]
+    Text { text: String },
+    #[serde(rename = "thinking_delta")]
+    Thinking { thinking: String },
+    #[serde(rename = "input_json_delta")]
+    InputJson { partial_json: String },
+    #[serde(rename = "signature_delta")]
+    Signature { signature: String },
+    /// Future delta types
+    #[serde(other)]
+    Other,
+}
+
+#[derive(Debug, Deserialize)]
+struct ErrorResponse {
+    error: ApiErrorBody,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiErrorBody {
+    #[serde(rename = "type", default)]
+    typ: String,
+    #[serde(default)]
+    message: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neomind_core::llm::backend::GenerationParams;
+
+    #[test]
+    fn test_split_tool_calls() {
+        let text = r#"Checking.[{"id":"toolu_1","name":"get_device","arguments":{"id":"t1"}}]"#;
+        let (prefix, calls) = split_tool_calls(text).unwrap();
+        assert_eq!(prefix, "Checking.");
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].id.as_deref(), Some("toolu_1"));
+        assert_eq!(calls[0].arguments["id"], "t1");
+
+        assert!(split_tool_calls("Readings: [1, 2, 3]").is_none());
+        assert!(split_tool_calls("No tools here").is_none());
+    }
+
+    #[test]
+    fn test_messages_merge_roles_and_map_tool_results() {
+        let messages = vec![
+            Message::system("You are NeoMind."),
+            Message::user("Check the sensor"),
+            Message::assistant(r#"[{"id":"toolu_1","name":"get_device","arguments":{"id":"t1"}}]"#),
+            Message::new(MessageRole::Tool, Content::text("22.5 °C")),
+            Message::user("Thanks"),
+        ];
+        let api = serde_json::to_value(messages_to_api(&messages, None)).unwrap();
+
+        assert_eq!(api.as_array().unwrap().len(), 3);
+        assert_eq!(api[1]["content"][0]["type"], "tool_use");
+        assert_eq!(api[1]["content"][0]["input"]["id"], "t1");
+        // The tool result and the next user message form one user turn
+        assert_eq!(api[2]["role"], "user");
+        assert_eq!(api[2]["content"][0]["type"], "tool_result");
+        assert_eq!(api[2]["content"][0]["tool_use_id"], "toolu_1");
+        assert_eq!(api[2]["content"][1]["text"], "Thanks");
+    }
+
+    #[test]
+    fn test_usage_includes_cached_input() {
+        let usage = AnthropicUsage {
+            input_tokens: 10,
+            output_tokens: 5,
+            cache_creation_input_tokens: 100,
+            cache_read_input_tokens: 1000,
+        };
+        let usage = TokenUsage::from(usage);
+        assert_eq!(usage.prompt_tokens, 1110);
+        assert_eq!(usage.total_tokens, 1115);
+    }
+
+    #[test]
+    fn test_thinking_only_for_supported_models() {
+        let runtime = AnthropicRuntime::new(AnthropicConfig::new("key")).unwrap();
+        let input = LlmInput::new("Hi").with_params(GenerationParams {
+            thinking_enabled: Some(true),
+            ..Default::default()
+        });
+
+        let request = runtime.build_request(&input, false);
+        assert!(request.thinking.is_none());
+
+        let request = runtime.build_request(&input.with_model("claude-sonnet-4-20250514"), false);
+        assert!(request.thinking.is_some());
+    }
+
+    #[test]
+    fn test_thinking_blocks_are_sent_back() {
+        let events = [
+            r#"{"type":"content_block_start","index":0,"content_block":{"type":"thinking","thinking":""}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"thinking_delta","thinking":"Check t1."}}"#,
+            r#"{"type":"content_block_delta","index":0,"delta":{"type":"signature_delta","signature":"sig=="}}"#,
+            r#"{"type":"content_block_start","index":1,"content_block":{"type":"redacted_thinking","data":"opaque"}}"#,
+            r#"{"type":"content_block_start","index":2,"content_block":{"type":"tool_use","id":"toolu_1","name":"get_device","input":{}}}"#,
+            r#"{"type":"content_block_delta","index":2,"delta":{"type":"input_json_delta","partial_json":"{\"id\":\"t1\"}"}}"#,
+            r#"{"type":"message_stop"}"#,
+        ];
+        let mut state = StreamState::default();
+        let mut answer = String::new();
+        for event in events {
+            for chunk in state.handle(serde_json::from_str(event).unwrap()) {
+                let (text, thinking) = chunk.unwrap();
+                if !thinking {
+                    answer.push_str(&text);
+                }
+            }
+        }
+        let mut history = ThinkingHistory::default();
+        state.remember_thinking(&mut history);
+
+        let messages = vec![
+            Message::user("Check the sensor"),
+            Message::assistant(answer),
+            Message::new(MessageRole::Tool, Content::text("22.5 °C")),
+        ];
+        let api = serde_json::to_value(messages_to_api(&messages, Some(&history))).unwrap();
+        let content = &api[1]["content"];
+        assert_eq!(content[0]["type"], "thinking");
+        assert_eq!(content[0]["thinking"], "Check t1.");
+        assert_eq!(content[0]["signature"], "sig==");
+        assert_eq!(content[1]["type"], "redacted_thinking");
+        assert_eq!(content[1]["data"], "opaque");
+        assert_eq!(content[2]["type"], "tool_use");
+
+        // Without thinking enabled the blocks are left out
+        let api = serde_json::to_value(messages_to_api(&messages, None)).unwrap();
+        assert_eq!(api[1]["content"][0]["type"], "tool_use");
+    }
+
+    #[test]
+    fn test_supports_thinking() {
+        assert!(supports_thinking("claude-3-7-sonnet-20250219"));
+        assert!(supports_thinking("claude-sonnet-4-20250514"));
+        assert!(!supports_thinking("claude-3-5-sonnet-20241022"));
+    }
+}
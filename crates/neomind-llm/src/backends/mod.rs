@@ -18,6 +18,12 @@ pub mod openai;
 #[cfg(feature = "cloud")]
 pub use openai::{CloudConfig, CloudProvider, CloudRuntime};
 
+// Native Anthropic Messages API backend
+#[cfg(feature = "cloud")]
+pub mod anthropic;
+#[cfg(feature = "cloud")]
+pub use anthropic::{AnthropicConfig, AnthropicRuntime, AnthropicUsage};
+
 /// Create a backend by type identifier.
 ///
 /// This function provides a unified way to create LLM backends
@@ -62,10 +68,9 @@ pub fn create_backend(
 
         #[cfg(feature = "anthropic")]
         "anthropic" => {
-            let mut cfg: CloudConfig = serde_json::from_value(config.clone())
+            let cfg: AnthropicConfig = serde_json::from_value(config.clone())
                 .map_err(|e| anyhow::anyhow!("Invalid Anthropic config: {}", e))?;
-            cfg.provider = CloudProvider::Anthropic;
-            Ok(std::sync::Arc::new(CloudRuntime::new(cfg)?))
+            Ok(std::sync::Arc::new(AnthropicRuntime::new(cfg)?))
         }
 
         #[cfg(feature = "google")]
@@ -17,8 +17,9 @@ use serde::{Deserialize, Serialize};
 
 use neomind_core::llm::backend::{
     BackendCapabilities, BackendId, BackendMetrics, FinishReason, LlmError, LlmOutput, LlmRuntime,
-    StreamChunk, StreamConfig, TokenUsage,
+    ResponseFormat, StreamChunk, StreamConfig, StreamFinish, TokenUsage,
 };
+use neomind_core::llm::usage::{StreamUsage, UsageMeter};
 use neomind_core::message::{Content, ContentPart, Message, MessageRole};
 
 /// Ollama configuration.
@@ -435,6 +436,12 @@ impl LlmRuntime for OllamaRuntime {
         input: neomind_core::llm::backend::LlmInput,
     ) -> Result<LlmOutput, LlmError> {
         let start_time = Instant::now();
+        let meter = UsageMeter::start(
+            "ollama",
+            input.model.as_deref().unwrap_or(&self.model),
+            &input,
+        )
+        .await?;
         let model = input.model.unwrap_or_else(|| self.model.clone());
 
         let url = format!("{}/api/chat", self.config.endpoint);
@@ -546,9 +553,8 @@ impl LlmRuntime for OllamaRuntime {
             None => None,       // Use model default
         };
 
-        // When tools are present, disable thinking to prevent wasting tokens
-        // and ensure tool calls are generated efficiently
-        let format: Option<String> = None;
+        // Native JSON mode / structured outputs
+        let format = ollama_format(input.params.response_format.as_ref());
 
         // Convert messages with tool injection for non-native models
         let messages = self.messages_to_ollama_with_tools(
@@ -707,6 +713,10 @@ impl LlmRuntime for OllamaRuntime {
             }
         }
 
+        if let Ok(output) = &result {
+            meter.finish(output.usage, &output.text);
+        }
+
         result
     }
 
@@ -718,6 +728,12 @@ impl LlmRuntime for OllamaRuntime {
 
         let (tx, rx) = mpsc::channel(64);
 
+        let meter = UsageMeter::start(
+            "ollama",
+            input.model.as_deref().unwrap_or(&self.model),
+            &input,
+        )
+        .await?;
         let model = input.model.unwrap_or_else(|| self.model.clone());
         let url = format!("{}/api/chat", self.config.endpoint);
         let client = self.client.clone();
@@ -849,9 +865,8 @@ impl LlmRuntime for OllamaRuntime {
             !supports_native_tools && has_tools
         );
 
-        // When tools are present, disable thinking to prevent wasting tokens
-        // and ensure tool calls are generated efficiently
-        let format: Option<String> = None;
+        // Native JSON mode / structured outputs
+        let format = ollama_format(input.params.response_format.as_ref());
 
         // Log request details before creating the request
         tracing::debug!(
@@ -862,6 +877,9 @@ impl LlmRuntime for OllamaRuntime {
 
         // Capture stream_config for use in async block
         let stream_config = self.stream_config.clone();
+        let stream_usage = StreamUsage::default();
+        let reported_usage = stream_usage.clone();
+        let stream_finish = StreamFinish::current();
 
         tokio::spawn(async move {
             let request = OllamaChatRequest {
@@ -1337,6 +1355,22 @@ impl LlmRuntime for OllamaRuntime {
                                             }
 
                                             if ollama_chunk.done {
+                                                stream_finish.set(
+                                                    match ollama_chunk.done_reason.as_deref() {
+                                                        Some("length") => FinishReason::Length,
+                                                        _ => FinishReason::Stop,
+                                                    },
+                                                );
+
+                                                // The final chunk carries the token counts
+                                                if let Some(count) = ollama_chunk.eval_count {
+                                                    reported_usage.set(TokenUsage::new(
+                                                        ollama_chunk.prompt_eval_count.unwrap_or(0)
+                                                            as u32,
+                                                        count as u32,
+                                                    ));
+                                                }
+
                                                 // BUSINESS LOG: Stream completion summary
                                                 // Use accumulated counters, not final chunk (which is often empty)
                                                 let actual_content_len =
@@ -1396,7 +1430,10 @@ impl LlmRuntime for OllamaRuntime {
             }
         });
 
-        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+        Ok(Box::pin(meter.stream_with_usage(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+            stream_usage,
+        )))
     }
 
     fn max_context_length(&self) -> usize {
@@ -1456,9 +1493,17 @@ struct OllamaChatRequest {
     /// Tools for function calling (OpenAI-compatible format)
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<OllamaTool>>,
-    /// Output format - use "json" to disable thinking for tool calls
+    /// Output format - "json" or a JSON Schema for structured outputs
     #[serde(skip_serializing_if = "Option::is_none")]
-    format: Option<String>,
+    format: Option<serde_json::Value>,
+}
+
+/// Map a response format to Ollama's `format` parameter.
+fn ollama_format(format: Option<&ResponseFormat>) -> Option<serde_json::Value> {
+    match format? {
+        ResponseFormat::Json => Some(serde_json::Value::String("json".to_string())),
+        ResponseFormat::JsonSchema { schema, .. } => Some(schema.clone()),
+    }
 }
 
 /// Thinking level for Ollama models that support reasoning.
@@ -1828,6 +1873,13 @@ struct OllamaStreamResponse {
     done: bool,
     #[serde(default)]
     message: OllamaResponseMessage,
+    /// Why generation stopped, e.g. "stop" or "length"
+    #[serde(default)]
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: Option<usize>,
+    #[serde(default)]
+    eval_count: Option<usize>,
 }
 
 #[derive(Debug, Deserialize, Default)]
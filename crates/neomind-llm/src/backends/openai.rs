@@ -2,7 +2,7 @@
 //!
 //! Supports cloud APIs that are compatible with OpenAI's format:
 //! - OpenAI (GPT-4, GPT-3.5, o1, etc.)
-//! - Anthropic Claude (via compatibility layer)
+//! - Anthropic Claude (via compatibility layer; see [`super::anthropic`] for the native API)
 //! - Google Gemini (via compatibility layer)
 //! - xAI Grok
 //! - Other OpenAI-compatible providers
@@ -17,8 +17,9 @@ use serde::{Deserialize, Serialize};
 
 use neomind_core::llm::backend::{
     BackendCapabilities, BackendId, BackendMetrics, FinishReason, LlmError, LlmOutput, LlmRuntime,
-    StreamChunk, TokenUsage,
+    ResponseFormat, StreamChunk, StreamFinish, TokenUsage,
 };
+use neomind_core::llm::usage::{StreamUsage, UsageMeter};
 use neomind_core::message::{Content, ContentPart, ImageDetail, Message, MessageRole};
 
 use crate::rate_limited_client::{ProviderRateLimits, RateLimitedClient};
@@ -100,6 +101,33 @@ impl CloudProvider {
             Self::MiniMax => "/chat/completions",
         }
     }
+
+    /// Get the `stream_options` asking for token usage at the end of a
+    /// stream, for APIs known to accept it.
+    fn stream_options(&self) -> Option<serde_json::Value> {
+        match self {
+            Self::OpenAI | Self::Qwen | Self::DeepSeek => {
+                Some(serde_json::json!({ "include_usage": true }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Get the `response_format` for a requested output format: a JSON Schema
+    /// where the API accepts one, plain JSON mode otherwise.
+    fn response_format(&self, format: Option<&ResponseFormat>) -> Option<serde_json::Value> {
+        match (self, format?) {
+            (Self::Anthropic, _) => None,
+            (
+                Self::OpenAI | Self::Google | Self::Grok | Self::Custom,
+                ResponseFormat::JsonSchema { name, schema },
+            ) => Some(serde_json::json!({
+                "type": "json_schema",
+                "json_schema": { "name": name, "schema": schema },
+            })),
+            _ => Some(serde_json::json!({ "type": "json_object" })),
+        }
+    }
 }
 
 /// Configuration for cloud LLM backend.
@@ -439,6 +467,12 @@ impl LlmRuntime for CloudRuntime {
         input: neomind_core::llm::backend::LlmInput,
     ) -> Result<LlmOutput, LlmError> {
         let start_time = Instant::now();
+        let meter = UsageMeter::start(
+            self.backend_id().as_str(),
+            input.model.as_deref().unwrap_or(&self.model),
+            &input,
+        )
+        .await?;
         let model = input.model.unwrap_or_else(|| self.model.clone());
 
         let url = format!(
@@ -465,6 +499,11 @@ impl LlmRuntime for CloudRuntime {
             frequency_penalty: input.params.frequency_penalty,
             presence_penalty: input.params.presence_penalty,
             stream: false,
+            stream_options: None,
+            response_format: self
+                .config
+                .provider
+                .response_format(input.params.response_format.as_ref()),
             tools: input.tools.map(|tools| tools.into_iter().map(OpenAiTool::from).collect()),
         };
 
@@ -544,13 +583,7 @@ impl LlmRuntime for CloudRuntime {
 
         let result = Ok(LlmOutput {
             text: response_text,
-            finish_reason: match choice.finish_reason.as_str() {
-                "stop" => FinishReason::Stop,
-                "length" => FinishReason::Length,
-                "content_filter" => FinishReason::ContentFilter,
-                "tool_calls" => FinishReason::Stop, // Tool calls are a valid stop reason
-                _ => FinishReason::Error,
-            },
+            finish_reason: finish_reason(&choice.finish_reason),
             usage: chat_response.usage.map(|u| TokenUsage {
                 prompt_tokens: u.prompt_tokens,
                 completion_tokens: u.completion_tokens,
@@ -575,6 +608,10 @@ impl LlmRuntime for CloudRuntime {
             }
         }
 
+        if let Ok(output) = &result {
+            meter.finish(output.usage, &output.text);
+        }
+
         result
     }
 
@@ -586,6 +623,12 @@ impl LlmRuntime for CloudRuntime {
 
         let (tx, rx) = mpsc::channel(64);
 
+        let meter = UsageMeter::start(
+            self.backend_id().as_str(),
+            input.model.as_deref().unwrap_or(&self.model),
+            &input,
+        )
+        .await?;
         let model = input.model.unwrap_or_else(|| self.model.clone());
         let url = format!(
             "{}{}",
@@ -615,9 +658,18 @@ impl LlmRuntime for CloudRuntime {
             frequency_penalty: input.params.frequency_penalty,
             presence_penalty: input.params.presence_penalty,
             stream: true,
+            stream_options: self.config.provider.stream_options(),
+            response_format: self
+                .config
+                .provider
+                .response_format(input.params.response_format.as_ref()),
             tools: input.tools.map(|tools| tools.into_iter().map(OpenAiTool::from).collect()),
         };
 
+        let stream_usage = StreamUsage::default();
+        let reported_usage = stream_usage.clone();
+        let stream_finish = StreamFinish::current();
+
         tokio::spawn(async move {
             // Create rate limit key
             let rate_limit_key = format!("{:?}:{:x}", provider, hash_api_key(&api_key));
@@ -707,6 +759,10 @@ impl LlmRuntime for CloudRuntime {
                                             if let Ok(evt) =
                                                 serde_json::from_str::<StreamChunkEvent>(json)
                                             {
+                                                // Sent in a final chunk without choices
+                                                if let Some(usage) = evt.usage {
+                                                    reported_usage.set(TokenUsage::new(usage.prompt_tokens, usage.completion_tokens));
+                                                }
                                                 if let Some(choice) = evt.choices.first() {
                                                     // Handle content
                                                     if let Some(ref content) = choice.delta.content {
@@ -741,6 +797,10 @@ impl LlmRuntime for CloudRuntime {
                                                         }
                                                     }
 
+                                                    if let Some(ref reason) = choice.finish_reason {
+                                                        stream_finish.set(finish_reason(reason));
+                                                    }
+
                                                     // Check for finish reason - flush tool calls
                                                     if choice.finish_reason.as_deref() == Some("tool_calls")
                                                         && !accumulated_tool_calls.is_empty() {
@@ -781,7 +841,10 @@ impl LlmRuntime for CloudRuntime {
             }
         });
 
-        Ok(Box::pin(tokio_stream::wrappers::ReceiverStream::new(rx)))
+        Ok(Box::pin(meter.stream_with_usage(
+            tokio_stream::wrappers::ReceiverStream::new(rx),
+            stream_usage,
+        )))
     }
 
     fn max_context_length(&self) -> usize {
@@ -839,7 +902,7 @@ impl LlmRuntime for CloudRuntime {
 
 /// Extract media type and base64 data from a data URL.
 /// Returns (media_type, base64_data).
-fn extract_data_url(url: &str) -> (String, String) {
+pub(super) fn extract_data_url(url: &str) -> (String, String) {
     if url.starts_with("data:") {
         // Format: data:image/png;base64,iVBORw0KGgo...
         if let Some(rest) = url.strip_prefix("data:") {
@@ -868,7 +931,7 @@ fn image_detail_to_string(detail: &ImageDetail) -> String {
 
 /// Hash an API key for use as a rate limit key.
 /// This avoids exposing actual API keys in logs.
-fn hash_api_key(api_key: &str) -> u64 {
+pub(super) fn hash_api_key(api_key: &str) -> u64 {
     use std::collections::hash_map::DefaultHasher;
     use std::hash::{Hash, Hasher};
 
@@ -877,6 +940,17 @@ fn hash_api_key(api_key: &str) -> u64 {
     hasher.finish()
 }
 
+/// Map an API `finish_reason` to a [`FinishReason`].
+fn finish_reason(reason: &str) -> FinishReason {
+    match reason {
+        "stop" => FinishReason::Stop,
+        "length" => FinishReason::Length,
+        "content_filter" => FinishReason::ContentFilter,
+        "tool_calls" => FinishReason::Stop, // Tool calls are a valid stop reason
+        _ => FinishReason::Error,
+    }
+}
+
 // API types
 
 #[derive(Debug, Serialize)]
@@ -896,6 +970,12 @@ struct ChatCompletionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     presence_penalty: Option<f32>,
     stream: bool,
+    /// Streaming options, e.g. whether to report token usage
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+    /// Native JSON mode / structured outputs
+    #[serde(skip_serializing_if = "Option::is_none")]
+    response_format: Option<serde_json::Value>,
     /// Tools for function calling (OpenAI-compatible format)
     #[serde(skip_serializing_if = "Option::is_none")]
     tools: Option<Vec<OpenAiTool>>,
@@ -1041,7 +1121,11 @@ struct AccumulatedToolCall {
 
 #[derive(Debug, Deserialize)]
 struct StreamChunkEvent {
+    #[serde(default)]
     choices: Vec<StreamChoice>,
+    /// Token usage, when requested through `stream_options`
+    #[serde(default)]
+    usage: Option<Usage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -1089,7 +1173,7 @@ use tokio_stream;
 
 /// Check if a model supports vision (image input) based on provider and model name.
 /// This uses name-based heuristic detection for common vision-capable models.
-fn is_vision_model(provider: &CloudProvider, model_name: &str) -> bool {
+pub(super) fn is_vision_model(provider: &CloudProvider, model_name: &str) -> bool {
     let name_lower = model_name.to_lowercase();
 
     match provider {
@@ -0,0 +1,911 @@
+//! Response cache for LLM generations.
+//!
+//! [`CachedLlmRuntime`] wraps a backend and answers repeated requests from a
+//! shared [`ResponseCache`]. Requests are keyed on the model, generation
+//! parameters and whitespace- and case-normalised messages. With a
+//! [`PromptEmbedder`] installed, a request whose final user message is close
+//! enough to a cached one (and whose earlier context is identical) is also
+//! a hit.
+//!
+//! Caching is opt-in: only runtimes wrapped with a [`CachePolicy`] use the
+//! cache, and calls made inside [`without_response_cache`] skip it. Turns
+//! that carry tool results or images, or that offer tools not declared
+//! read-only, are never cached, and neither are answers that did not finish
+//! normally. Entries tied to devices or metrics are dropped when those report
+//! a change on the event bus; metric updates that repeat the last value are
+//! not a change.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::future::Future;
+use std::hash::{Hash, Hasher};
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use neomind_core::event::NeoMindEvent;
+use neomind_core::eventbus::EventBus;
+use neomind_core::llm::backend::{
+    BackendCapabilities, BackendId, BackendMetrics, FinishReason, LlmError, LlmInput, LlmOutput,
+    LlmRuntime, StreamChunk, StreamFinish,
+};
+use neomind_core::message::{Content, ContentPart, MessageRole};
+use neomind_storage::ResponseCacheConfig;
+
+/// Default maximum number of cached responses.
+pub const DEFAULT_MAX_ENTRIES: usize = 1000;
+
+/// Global response cache shared by all cached runtimes
+static RESPONSE_CACHE: OnceLock<Arc<ResponseCache>> = OnceLock::new();
+
+tokio::task_local! {
+    static BYPASS_CACHE: ();
+}
+
+/// Run `f` with its LLM calls neither answered from nor stored in the
+/// response cache, for turns that must see the current state.
+pub async fn without_response_cache<F: Future>(f: F) -> F::Output {
+    BYPASS_CACHE.scope((), f).await
+}
+
+/// Embeds prompts for similarity lookups.
+#[async_trait::async_trait]
+pub trait PromptEmbedder: Send + Sync {
+    /// Embed a normalised prompt
+    async fn embed(&self, text: &str) -> Result<Vec<f32>, LlmError>;
+}
+
+/// How a call site uses the response cache.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    /// How long a response stays valid
+    pub ttl: Duration,
+    /// Minimum cosine similarity for a similarity hit; `None` allows exact hits only
+    pub similarity_threshold: Option<f32>,
+    /// Devices whose state changes invalidate the cached responses
+    pub devices: Vec<String>,
+    /// `(device_id, metric)` pairs whose changes invalidate the cached responses
+    pub metrics: Vec<(String, String)>,
+    /// Tools without side effects; turns offering any other tool are not cached
+    pub read_only_tools: Vec<String>,
+}
+
+impl CachePolicy {
+    /// Exact-match caching for `ttl`
+    pub fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            similarity_threshold: None,
+            devices: Vec::new(),
+            metrics: Vec::new(),
+            read_only_tools: Vec::new(),
+        }
+    }
+
+    /// Also accept similar prompts at or above `threshold`
+    pub fn with_similarity(mut self, threshold: f32) -> Self {
+        self.similarity_threshold = Some(threshold);
+        self
+    }
+
+    /// Invalidate cached responses when any of these devices changes
+    pub fn with_devices(mut self, devices: impl IntoIterator<Item = String>) -> Self {
+        self.devices.extend(devices);
+        self
+    }
+
+    /// Invalidate cached responses when any of these device metrics changes
+    pub fn with_metrics(mut self, metrics: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.metrics.extend(metrics);
+        self
+    }
+}
+
+impl From<&ResponseCacheConfig> for CachePolicy {
+    fn from(config: &ResponseCacheConfig) -> Self {
+        Self {
+            ttl: Duration::from_secs(config.ttl_secs),
+            similarity_threshold: config.similarity_threshold,
+            devices: Vec::new(),
+            metrics: Vec::new(),
+            read_only_tools: config.read_only_tools.clone(),
+        }
+    }
+}
+
+/// Cache counters.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct CacheStats {
+    /// Responses currently cached
+    pub entries: usize,
+    /// Hits on an identical normalised request
+    pub exact_hits: u64,
+    /// Hits on a similar prompt
+    pub semantic_hits: u64,
+    /// Cacheable requests sent to the backend
+    pub misses: u64,
+    /// Requests that were not cacheable (tool results, images, side-effecting tools)
+    pub bypassed: u64,
+    /// Responses stored
+    pub stores: u64,
+    /// Entries evicted to stay within the size limit
+    pub evictions: u64,
+    /// Entries dropped because a device changed
+    pub invalidations: u64,
+    /// Hits divided by cacheable requests
+    pub hit_rate: f64,
+}
+
+#[derive(Default)]
+struct Counters {
+    exact_hits: AtomicU64,
+    semantic_hits: AtomicU64,
+    misses: AtomicU64,
+    bypassed: AtomicU64,
+    stores: AtomicU64,
+    evictions: AtomicU64,
+    invalidations: AtomicU64,
+}
+
+impl Counters {
+    fn bump(counter: &AtomicU64) {
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+/// Cache key of a request.
+struct RequestKey {
+    /// Hash of everything except the final user message
+    context: u64,
+    /// Normalised final user message
+    prompt: String,
+    /// Hash of the context and prompt together
+    exact: u64,
+}
+
+struct CacheEntry {
+    context: u64,
+    prompt: String,
+    embedding: Option<Vec<f32>>,
+    output: LlmOutput,
+    devices: Vec<String>,
+    metrics: Vec<(String, String)>,
+    expires_at: Instant,
+    last_used: Instant,
+}
+
+/// In-memory store of LLM responses.
+pub struct ResponseCache {
+    entries: Mutex<HashMap<u64, CacheEntry>>,
+    max_entries: usize,
+    embedder: RwLock<Option<Arc<dyn PromptEmbedder>>>,
+    counters: Counters,
+    watching: AtomicBool,
+}
+
+impl ResponseCache {
+    /// Create a cache holding at most `max_entries` responses
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            max_entries: max_entries.max(1),
+            embedder: RwLock::new(None),
+            counters: Counters::default(),
+            watching: AtomicBool::new(false),
+        }
+    }
+
+    /// Install the embedder used for similarity hits
+    pub fn set_embedder(&self, embedder: Option<Arc<dyn PromptEmbedder>>) {
+        *self.embedder.write().unwrap() = embedder;
+    }
+
+    /// Current counters
+    pub fn stats(&self) -> CacheStats {
+        let load = |c: &AtomicU64| c.load(Ordering::Relaxed);
+        let exact_hits = load(&self.counters.exact_hits);
+        let semantic_hits = load(&self.counters.semantic_hits);
+        let misses = load(&self.counters.misses);
+        let lookups = exact_hits + semantic_hits + misses;
+        CacheStats {
+            entries: self.entries.lock().unwrap().len(),
+            exact_hits,
+            semantic_hits,
+            misses,
+            bypassed: load(&self.counters.bypassed),
+            stores: load(&self.counters.stores),
+            evictions: load(&self.counters.evictions),
+            invalidations: load(&self.counters.invalidations),
+            hit_rate: if lookups > 0 {
+                (exact_hits + semantic_hits) as f64 / lookups as f64
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Remove every cached response, returning how many were removed
+    pub fn clear(&self) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let removed = entries.len();
+        entries.clear();
+        removed
+    }
+
+    /// Drop responses that depend on a device or any of its metrics,
+    /// returning how many were dropped
+    pub fn invalidate_device(&self, device_id: &str) -> usize {
+        self.invalidate(device_id, None)
+    }
+
+    /// Drop responses that depend on a device or on one of its metrics,
+    /// returning how many were dropped
+    pub fn invalidate_metric(&self, device_id: &str, metric: &str) -> usize {
+        self.invalidate(device_id, Some(metric))
+    }
+
+    fn invalidate(&self, device_id: &str, metric: Option<&str>) -> usize {
+        let mut entries = self.entries.lock().unwrap();
+        let before = entries.len();
+        entries.retain(|_, e| {
+            !e.devices.iter().any(|d| d == device_id)
+                && !e
+                    .metrics
+                    .iter()
+                    .any(|(d, m)| d == device_id && metric.is_none_or(|metric| m == metric))
+        });
+        let removed = before - entries.len();
+        if removed > 0 {
+            self.counters
+                .invalidations
+                .fetch_add(removed as u64, Ordering::Relaxed);
+            tracing::debug!(
+                device_id,
+                metric,
+                removed,
+                "Invalidated cached LLM responses"
+            );
+        }
+        removed
+    }
+
+    /// Invalidate responses when their devices publish events on `event_bus`.
+    ///
+    /// Metric events only count when the value differs from the last one seen
+    /// for that metric. Only the first call starts a watcher.
+    pub fn watch_devices(self: &Arc<Self>, event_bus: &EventBus) {
+        if self.watching.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let mut rx = event_bus.filter().device_events();
+        let cache = Arc::downgrade(self);
+        tokio::spawn(async move {
+            let mut last_values: HashMap<(String, String), serde_json::Value> = HashMap::new();
+            while let Some((event, _)) = rx.recv().await {
+                let Some(cache) = cache.upgrade() else {
+                    break;
+                };
+                match &event {
+                    NeoMindEvent::DeviceMetric {
+                        device_id,
+                        metric,
+                        value,
+                        ..
+                    } => {
+                        let value = serde_json::to_value(value).unwrap_or_default();
+                        let key = (device_id.clone(), metric.clone());
+                        if last_values.get(&key) != Some(&value) {
+                            last_values.insert(key, value);
+                            cache.invalidate_metric(device_id, metric);
+                        }
+                    }
+                    _ => {
+                        if let Some(device_id) = event_device_id(&event) {
+                            cache.invalidate_device(device_id);
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    fn get_exact(&self, key: &RequestKey) -> Option<LlmOutput> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let entry = entries.get_mut(&key.exact)?;
+        if entry.expires_at <= now {
+            entries.remove(&key.exact);
+            return None;
+        }
+        if entry.prompt != key.prompt || entry.context != key.context {
+            return None;
+        }
+        entry.last_used = now;
+        Some(entry.output.clone())
+    }
+
+    fn get_similar(
+        &self,
+        key: &RequestKey,
+        embedding: &[f32],
+        threshold: f32,
+    ) -> Option<LlmOutput> {
+        let mut entries = self.entries.lock().unwrap();
+        let now = Instant::now();
+        let best = entries
+            .values_mut()
+            .filter(|e| e.context == key.context && e.expires_at > now)
+            .filter_map(|e| {
+                let similarity = cosine_similarity(e.embedding.as_deref()?, embedding);
+                (similarity >= threshold).then_some((similarity, e))
+            })
+            .max_by(|a, b| a.0.total_cmp(&b.0))?;
+        best.1.last_used = now;
+        Some(best.1.output.clone())
+    }
+
+    fn insert(
+        &self,
+        key: RequestKey,
+        embedding: Option<Vec<f32>>,
+        output: LlmOutput,
+        policy: &CachePolicy,
+    ) {
+        let now = Instant::now();
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, e| e.expires_at > now);
+        while entries.len() >= self.max_entries {
+            let Some(oldest) = entries
+                .iter()
+                .min_by_key(|(_, e)| e.last_used)
+                .map(|(k, _)| *k)
+            else {
+                break;
+            };
+            entries.remove(&oldest);
+            Counters::bump(&self.counters.evictions);
+        }
+        entries.insert(
+            key.exact,
+            CacheEntry {
+                context: key.context,
+                prompt: key.prompt,
+                embedding,
+                output,
+                devices: policy.devices.clone(),
+                metrics: policy.metrics.clone(),
+                expires_at: now + policy.ttl,
+                last_used: now,
+            },
+        );
+        Counters::bump(&self.counters.stores);
+    }
+
+    /// Embed the prompt when the policy allows similarity hits
+    async fn embed(&self, key: &RequestKey, policy: &CachePolicy) -> Option<Vec<f32>> {
+        policy.similarity_threshold?;
+        let embedder = self.embedder.read().unwrap().clone()?;
+        match embedder.embed(&key.prompt).await {
+            Ok(embedding) => Some(embedding),
+            Err(e) => {
+                tracing::warn!("Failed to embed prompt for response cache: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Look a request up, counting the outcome.
+    ///
+    /// Returns the cached output on a hit, otherwise the prompt embedding to
+    /// store alongside the backend's response.
+    async fn lookup(
+        &self,
+        key: &RequestKey,
+        policy: &CachePolicy,
+    ) -> Result<LlmOutput, Option<Vec<f32>>> {
+        if let Some(output) = self.get_exact(key) {
+            Counters::bump(&self.counters.exact_hits);
+            return Ok(output);
+        }
+
+        let embedding = self.embed(key, policy).await;
+        if let (Some(embedding), Some(threshold)) = (&embedding, policy.similarity_threshold) {
+            if let Some(output) = self.get_similar(key, embedding, threshold) {
+                Counters::bump(&self.counters.semantic_hits);
+                return Ok(output);
+            }
+        }
+
+        Counters::bump(&self.counters.misses);
+        Err(embedding)
+    }
+}
+
+/// Get the global response cache
+pub fn get_response_cache() -> Arc<ResponseCache> {
+    RESPONSE_CACHE
+        .get_or_init(|| Arc::new(ResponseCache::new(DEFAULT_MAX_ENTRIES)))
+        .clone()
+}
+
+/// A runtime whose responses are served from and stored in a [`ResponseCache`].
+pub struct CachedLlmRuntime {
+    inner: Arc<dyn LlmRuntime>,
+    cache: Arc<ResponseCache>,
+    policy: CachePolicy,
+}
+
+impl CachedLlmRuntime {
+    /// Wrap `inner`, caching through `cache` according to `policy`
+    pub fn new(inner: Arc<dyn LlmRuntime>, cache: Arc<ResponseCache>, policy: CachePolicy) -> Self {
+        Self {
+            inner,
+            cache,
+            policy,
+        }
+    }
+
+    /// The cache key for a request, or `None` if it must not be cached
+    fn key(&self, input: &LlmInput) -> Option<RequestKey> {
+        if BYPASS_CACHE.try_with(|_| ()).is_ok() {
+            return None;
+        }
+        let (last, history) = input.messages.split_last()?;
+        if last.role != MessageRole::User {
+            return None;
+        }
+        if input.messages.iter().any(|m| {
+            m.role == MessageRole::Tool
+                || matches!(&m.content, Content::Parts(parts)
+                    if parts.iter().any(|p| !matches!(p, ContentPart::Text { .. })))
+        }) {
+            return None;
+        }
+
+        let mut hasher = DefaultHasher::new();
+        self.inner.model_name().hash(&mut hasher);
+        input.model.hash(&mut hasher);
+        format!("{:?}", input.params).hash(&mut hasher);
+        if let Some(tools) = input.tools.as_ref().filter(|t| !t.is_empty()) {
+            if tools
+                .iter()
+                .any(|t| !self.policy.read_only_tools.contains(&t.name))
+            {
+                return None;
+            }
+            for tool in tools {
+                tool.name.hash(&mut hasher);
+                tool.parameters.to_string().hash(&mut hasher);
+            }
+        }
+        for message in history {
+            message.role.hash(&mut hasher);
+            normalize(&message.content.as_text()).hash(&mut hasher);
+        }
+        let context = hasher.finish();
+
+        let prompt = normalize(&last.content.as_text());
+        let mut hasher = DefaultHasher::new();
+        context.hash(&mut hasher);
+        prompt.hash(&mut hasher);
+        Some(RequestKey {
+            context,
+            prompt,
+            exact: hasher.finish(),
+        })
+    }
+
+    fn key_or_bypass(&self, input: &LlmInput) -> Option<RequestKey> {
+        let key = self.key(input);
+        if key.is_none() {
+            Counters::bump(&self.cache.counters.bypassed);
+        }
+        key
+    }
+}
+
+/// Only complete answers are worth replaying
+fn cacheable(output: &LlmOutput) -> bool {
+    output.finish_reason == FinishReason::Stop && !output.text.trim().is_empty()
+}
+
+#[async_trait::async_trait]
+impl LlmRuntime for CachedLlmRuntime {
+    fn backend_id(&self) -> BackendId {
+        self.inner.backend_id()
+    }
+
+    fn model_name(&self) -> &str {
+        self.inner.model_name()
+    }
+
+    async fn is_available(&self) -> bool {
+        self.inner.is_available().await
+    }
+
+    async fn warmup(&self) -> Result<(), LlmError> {
+        self.inner.warmup().await
+    }
+
+    async fn generate(&self, input: LlmInput) -> Result<LlmOutput, LlmError> {
+        let Some(key) = self.key_or_bypass(&input) else {
+            return self.inner.generate(input).await;
+        };
+        let embedding = match self.cache.lookup(&key, &self.policy).await {
+            Ok(output) => return Ok(output),
+            Err(embedding) => embedding,
+        };
+
+        let output = self.inner.generate(input).await?;
+        if cacheable(&output) {
+            self.cache
+                .insert(key, embedding, output.clone(), &self.policy);
+        }
+        Ok(output)
+    }
+
+    async fn generate_stream(
+        &self,
+        input: LlmInput,
+    ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+        let Some(key) = self.key_or_bypass(&input) else {
+            return self.inner.generate_stream(input).await;
+        };
+        let embedding = match self.cache.lookup(&key, &self.policy).await {
+            Ok(output) => {
+                let mut chunks: Vec<StreamChunk> = Vec::new();
+                if let Some(thinking) = output.thinking {
+                    chunks.push(Ok((thinking, true)));
+                }
+                chunks.push(Ok((output.text, false)));
+                return Ok(Box::pin(futures::stream::iter(chunks)));
+            }
+            Err(embedding) => embedding,
+        };
+
+        // Collect the streamed answer and store it if the backend reports a
+        // normal finish
+        let captured = Arc::new(Mutex::new((String::new(), String::new(), false)));
+        let capture = captured.clone();
+        let finish = StreamFinish::default();
+        let stream = finish
+            .watch(self.inner.generate_stream(input))
+            .await?
+            .inspect(move |chunk| {
+                let mut captured = capture.lock().unwrap();
+                match chunk {
+                    Ok((text, false)) => captured.0.push_str(text),
+                    Ok((thinking, true)) => captured.1.push_str(thinking),
+                    Err(_) => captured.2 = true,
+                }
+            });
+
+        let cache = self.cache.clone();
+        let policy = self.policy.clone();
+        let store = futures::stream::once(async move {
+            let (text, thinking, failed) = std::mem::take(&mut *captured.lock().unwrap());
+            let output = LlmOutput {
+                text,
+                finish_reason: finish.get().unwrap_or(FinishReason::Error),
+                usage: None,
+                thinking: (!thinking.is_empty()).then_some(thinking),
+            };
+            if !failed && cacheable(&output) {
+                cache.insert(key, embedding, output, &policy);
+            }
+        })
+        .filter_map(|_| futures::future::ready(None::<StreamChunk>));
+
+        Ok(Box::pin(stream.chain(store)))
+    }
+
+    fn max_context_length(&self) -> usize {
+        self.inner.max_context_length()
+    }
+
+    fn estimate_tokens(&self, text: &str) -> usize {
+        self.inner.estimate_tokens(text)
+    }
+
+    fn supports_multimodal(&self) -> bool {
+        self.inner.supports_multimodal()
+    }
+
+    fn capabilities(&self) -> BackendCapabilities {
+        self.inner.capabilities()
+    }
+
+    fn metrics(&self) -> BackendMetrics {
+        self.inner.metrics()
+    }
+}
+
+/// Lowercase and collapse whitespace
+fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+fn cosine_similarity(a: &[f32], b: &[f32]) -> f32 {
+    if a.len() != b.len() || a.is_empty() {
+        return 0.0;
+    }
+    let dot: f32 = a.iter().zip(b).map(|(x, y)| x * y).sum();
+    let norm_a = a.iter().map(|x| x * x).sum::<f32>().sqrt();
+    let norm_b = b.iter().map(|x| x * x).sum::<f32>().sqrt();
+    if norm_a == 0.0 || norm_b == 0.0 {
+        0.0
+    } else {
+        dot / (norm_a * norm_b)
+    }
+}
+
+/// Device a device event is about
+fn event_device_id(event: &NeoMindEvent) -> Option<&str> {
+    match event {
+        NeoMindEvent::DeviceOnline { device_id, .. }
+        | NeoMindEvent::DeviceOffline { device_id, .. }
+        | NeoMindEvent::DeviceCommandResult { device_id, .. } => Some(device_id),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neomind_core::event::MetricValue;
+    use neomind_core::llm::backend::ToolDefinition;
+    use neomind_core::message::Message;
+    use std::sync::atomic::AtomicUsize;
+
+    /// Runtime that numbers its answers.
+    struct CountingRuntime {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait::async_trait]
+    impl LlmRuntime for CountingRuntime {
+        fn backend_id(&self) -> BackendId {
+            BackendId::new("counting")
+        }
+
+        fn model_name(&self) -> &str {
+            "counting"
+        }
+
+        async fn generate(&self, _input: LlmInput) -> Result<LlmOutput, LlmError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(LlmOutput {
+                text: format!("answer {}", call),
+                finish_reason: FinishReason::Stop,
+                usage: None,
+                thinking: None,
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            input: LlmInput,
+        ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+            // Prompts asking for a long answer run out of tokens
+            let truncated = input.messages.last().unwrap().text().contains("long");
+            let output = self.generate(input).await?;
+            StreamFinish::current().set(if truncated {
+                FinishReason::Length
+            } else {
+                FinishReason::Stop
+            });
+            Ok(Box::pin(futures::stream::iter(vec![Ok((
+                output.text,
+                false,
+            ))])))
+        }
+
+        fn max_context_length(&self) -> usize {
+            8192
+        }
+    }
+
+    /// Embeds text as letter counts, so anagrams are identical.
+    struct LetterEmbedder;
+
+    #[async_trait::async_trait]
+    impl PromptEmbedder for LetterEmbedder {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>, LlmError> {
+            let mut counts = vec![0.0; 26];
+            for c in text.bytes().filter(u8::is_ascii_lowercase) {
+                counts[(c - b'a') as usize] += 1.0;
+            }
+            Ok(counts)
+        }
+    }
+
+    fn runtime(
+        policy: CachePolicy,
+    ) -> (Arc<CountingRuntime>, Arc<ResponseCache>, CachedLlmRuntime) {
+        let inner = Arc::new(CountingRuntime {
+            calls: AtomicUsize::new(0),
+        });
+        let cache = Arc::new(ResponseCache::new(10));
+        let cached = CachedLlmRuntime::new(inner.clone(), cache.clone(), policy);
+        (inner, cache, cached)
+    }
+
+    fn ask(system: &str, prompt: &str) -> LlmInput {
+        let mut input = LlmInput::new(prompt);
+        input.messages.insert(0, Message::system(system));
+        input
+    }
+
+    async fn text(runtime: &CachedLlmRuntime, input: LlmInput) -> String {
+        runtime.generate(input).await.unwrap().text
+    }
+
+    #[tokio::test]
+    async fn test_exact_hits_and_bypass() {
+        let (inner, cache, cached) = runtime(CachePolicy::new(Duration::from_secs(60)));
+
+        assert_eq!(
+            text(&cached, ask("sys", "Room temperature?")).await,
+            "answer 1"
+        );
+        assert_eq!(
+            text(&cached, ask("sys", "  room   TEMPERATURE? ")).await,
+            "answer 1"
+        );
+        assert_eq!(
+            text(&cached, ask("other", "Room temperature?")).await,
+            "answer 2"
+        );
+
+        // Streaming replays the cached answer
+        let chunks: Vec<StreamChunk> = cached
+            .generate_stream(ask("sys", "room temperature?"))
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert_eq!(chunks[0].as_ref().unwrap().0, "answer 1");
+
+        // Tool results and side-effecting tools always reach the backend
+        let mut tool_turn = ask("sys", "Room temperature?");
+        tool_turn
+            .messages
+            .insert(1, Message::new(MessageRole::Tool, "21.5"));
+        assert_eq!(text(&cached, tool_turn).await, "answer 3");
+        let mut with_tools = ask("sys", "Room temperature?");
+        with_tools.tools = Some(vec![ToolDefinition {
+            name: "turn_off_heater".to_string(),
+            description: String::new(),
+            parameters: serde_json::json!({}),
+        }]);
+        assert_eq!(text(&cached, with_tools.clone()).await, "answer 4");
+        assert_eq!(text(&cached, with_tools).await, "answer 5");
+
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 5);
+        let stats = cache.stats();
+        assert_eq!((stats.exact_hits, stats.misses, stats.bypassed), (2, 2, 3));
+        assert_eq!(stats.entries, 2);
+        assert!((stats.hit_rate - 0.5).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_opt_out_and_unfinished_streams() {
+        let (inner, cache, cached) = runtime(CachePolicy::new(Duration::from_secs(60)));
+
+        for answer in ["answer 1", "answer 2"] {
+            let output = without_response_cache(cached.generate(ask("sys", "status"))).await;
+            assert_eq!(output.unwrap().text, answer);
+        }
+
+        async fn stream(runtime: &CachedLlmRuntime, prompt: &str) -> String {
+            let chunks: Vec<StreamChunk> = runtime
+                .generate_stream(ask("sys", prompt))
+                .await
+                .unwrap()
+                .collect()
+                .await;
+            chunks[0].as_ref().unwrap().0.clone()
+        }
+
+        // Only streams that finished normally are stored
+        assert_eq!(stream(&cached, "status").await, "answer 3");
+        assert_eq!(stream(&cached, "status").await, "answer 3");
+        assert_eq!(stream(&cached, "a long report").await, "answer 4");
+        assert_eq!(stream(&cached, "a long report").await, "answer 5");
+
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 5);
+        let stats = cache.stats();
+        assert_eq!((stats.exact_hits, stats.misses, stats.bypassed), (1, 3, 2));
+        assert_eq!(stats.entries, 1);
+    }
+
+    #[tokio::test]
+    async fn test_similarity_hits() {
+        let policy = CachePolicy::new(Duration::from_secs(60)).with_similarity(0.95);
+        let (inner, cache, cached) = runtime(policy);
+        cache.set_embedder(Some(Arc::new(LetterEmbedder)));
+
+        assert_eq!(text(&cached, ask("sys", "listen")).await, "answer 1");
+        assert_eq!(text(&cached, ask("sys", "silent")).await, "answer 1");
+        // Similar prompts only match within the same context
+        assert_eq!(text(&cached, ask("other", "silent")).await, "answer 2");
+        assert_eq!(text(&cached, ask("sys", "unrelated")).await, "answer 3");
+
+        assert_eq!(inner.calls.load(Ordering::SeqCst), 3);
+        assert_eq!(cache.stats().semantic_hits, 1);
+    }
+
+    #[tokio::test]
+    async fn test_expiry_and_device_invalidation() {
+        let (_, cache, expiring) = runtime(CachePolicy::new(Duration::ZERO));
+        assert_eq!(text(&expiring, ask("sys", "status")).await, "answer 1");
+        assert_eq!(text(&expiring, ask("sys", "status")).await, "answer 2");
+
+        let policy =
+            CachePolicy::new(Duration::from_secs(60)).with_devices(vec!["sensor-1".to_string()]);
+        let cached = CachedLlmRuntime::new(expiring.inner.clone(), cache.clone(), policy);
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 3");
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 3");
+
+        let event_bus = EventBus::new();
+        cache.watch_devices(&event_bus);
+        event_bus
+            .publish(NeoMindEvent::DeviceOnline {
+                device_id: "sensor-1".to_string(),
+                device_type: "sensor".to_string(),
+                timestamp: 0,
+            })
+            .await;
+        for _ in 0..50 {
+            if cache.stats().invalidations > 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(cache.stats().invalidations, 1);
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 4");
+    }
+
+    #[tokio::test]
+    async fn test_metric_invalidation() {
+        let policy = CachePolicy::new(Duration::from_secs(60))
+            .with_metrics(vec![("sensor-1".to_string(), "temp".to_string())]);
+        let (_, cache, cached) = runtime(policy);
+        let event_bus = EventBus::new();
+        cache.watch_devices(&event_bus);
+
+        async fn publish(event_bus: &EventBus, metric: &str, value: f64) {
+            event_bus
+                .publish(NeoMindEvent::DeviceMetric {
+                    device_id: "sensor-1".to_string(),
+                    metric: metric.to_string(),
+                    value: MetricValue::Float(value),
+                    timestamp: 0,
+                    quality: None,
+                })
+                .await;
+            // Give the watcher time to handle the event
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 1");
+        publish(&event_bus, "temp", 21.5).await;
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 2");
+
+        // Unrelated metrics and repeated values keep the entry
+        publish(&event_bus, "humidity", 40.0).await;
+        publish(&event_bus, "temp", 21.5).await;
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 2");
+
+        publish(&event_bus, "temp", 22.0).await;
+        assert_eq!(text(&cached, ask("sys", "status")).await, "answer 3");
+        assert_eq!(cache.stats().invalidations, 2);
+    }
+}
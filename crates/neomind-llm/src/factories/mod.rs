@@ -13,6 +13,8 @@ use serde_json::Value;
 #[cfg(feature = "ollama")]
 pub use crate::backends::ollama::{OllamaConfig, OllamaRuntime};
 
+#[cfg(feature = "cloud")]
+use crate::backends::anthropic::{AnthropicConfig, AnthropicRuntime};
 #[cfg(feature = "cloud")]
 pub use crate::backends::openai::{CloudConfig, CloudProvider, CloudRuntime};
 
@@ -150,6 +152,16 @@ impl BackendFactory for CloudFactory {
             .map(String::from);
         let endpoint = config.get("endpoint").and_then(|v| v.as_str());
 
+        // Anthropic has a native Messages API runtime
+        if provider == CloudProvider::Anthropic {
+            let mut anthropic_config =
+                AnthropicConfig::new(api_key).with_base_url_opt(endpoint.map(String::from));
+            if let Some(m) = model {
+                anthropic_config = anthropic_config.with_model(m);
+            }
+            return Ok(Box::new(AnthropicRuntime::new(anthropic_config)?));
+        }
+
         let mut cloud_config = match provider {
             CloudProvider::OpenAI => CloudConfig::openai(api_key),
             CloudProvider::Anthropic => CloudConfig::anthropic(api_key),
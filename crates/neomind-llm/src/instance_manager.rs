@@ -7,10 +7,15 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+use crate::routing::{BackendHealth, RouteCandidate, RoutedLlmRuntime, RouterHealth};
+use crate::tokenizer::TokenizerRegistry;
 use dashmap::DashMap;
+use neomind_core::eventbus::EventBus;
 use neomind_core::llm::backend::{LlmError, LlmInput, LlmRuntime};
+use neomind_core::llm::{register_encoder, set_default_encoder, TokenEncoder};
 use neomind_storage::{
     BackendCapabilities, ConnectionTestResult, LlmBackendInstance, LlmBackendStore, LlmBackendType,
+    RoutingPolicy,
 };
 
 /// Detect model capabilities from model name (for Ollama instances)
@@ -57,6 +62,47 @@ fn ensure_instance_capabilities(mut instance: LlmBackendInstance) -> LlmBackendI
     instance
 }
 
+/// Capabilities of an instance in the form routing filters use
+fn routing_capabilities(
+    capabilities: &BackendCapabilities,
+) -> neomind_core::llm::backend::BackendCapabilities {
+    let mut builder = neomind_core::llm::backend::BackendCapabilities::builder()
+        .max_context(capabilities.max_context);
+    if capabilities.supports_streaming {
+        builder = builder.streaming();
+    }
+    if capabilities.supports_multimodal {
+        builder = builder.multimodal();
+    }
+    if capabilities.supports_thinking {
+        builder = builder.thinking_display();
+    }
+    if capabilities.supports_tools {
+        builder = builder.function_calling();
+    }
+    builder.build()
+}
+
+/// Load the tokenizer for a model and register it for exact token counting.
+fn register_tokenizer(model: &str) -> Option<Arc<dyn TokenEncoder>> {
+    let tokenizer: Arc<dyn TokenEncoder> = Arc::new(TokenizerRegistry::global().get(model)?);
+    register_encoder(model, tokenizer.clone());
+    Some(tokenizer)
+}
+
+/// Use the tokenizer of the active model for context budgeting, falling back
+/// to heuristic estimates when it has none.
+fn activate_tokenizer(instance: Option<&LlmBackendInstance>) {
+    let tokenizer = instance.and_then(|instance| register_tokenizer(&instance.model));
+    if let (Some(instance), None) = (instance, &tokenizer) {
+        tracing::debug!(
+            "No tokenizer for model {}, estimating token counts",
+            instance.model
+        );
+    }
+    set_default_encoder(tokenizer);
+}
+
 /// LLM backend instance manager
 ///
 /// Manages multiple LLM backend instances with runtime caching,
@@ -76,6 +122,12 @@ pub struct LlmBackendInstanceManager {
 
     /// Health check results cache
     health_cache: Arc<DashMap<String, (bool, Instant)>>,
+
+    /// Circuit breakers and metrics of routed instances
+    router_health: Arc<RouterHealth>,
+
+    /// Event bus for routing failover events
+    event_bus: Arc<Mutex<Option<Arc<EventBus>>>>,
 }
 
 impl LlmBackendInstanceManager {
@@ -101,13 +153,17 @@ impl LlmBackendInstanceManager {
             .map(|inst| (inst.id.clone(), inst))
             .collect();
 
-        Self {
+        let manager = Self {
             storage,
             instances: Arc::new(DashMap::from_iter(instances)),
             active_id: Arc::new(Mutex::new(active_id)),
             runtime_cache: Arc::new(DashMap::new()),
             health_cache: Arc::new(DashMap::new()),
-        }
+            router_health: Arc::new(RouterHealth::new()),
+            event_bus: Arc::new(Mutex::new(None)),
+        };
+        activate_tokenizer(manager.get_active_instance().as_ref());
+        manager
     }
 
     /// Get the active backend instance
@@ -184,6 +240,8 @@ impl LlmBackendInstanceManager {
             })
         };
 
+        register_tokenizer(&instance.model);
+
         create_backend(instance.backend_name(), &config)
             .map_err(|e| LlmError::BackendUnavailable(e.to_string()))
     }
@@ -211,6 +269,9 @@ impl LlmBackendInstanceManager {
             LlmError::InvalidInput("Failed to acquire active_id lock".to_string())
         })?;
         *active_id = Some(id.to_string());
+        drop(active_id);
+
+        activate_tokenizer(self.instances.get(id).as_deref());
 
         Ok(())
     }
@@ -230,8 +291,15 @@ impl LlmBackendInstanceManager {
         // Update in-memory cache - DashMap insert is lock-free
         self.instances.insert(id.clone(), instance);
 
-        // Clear runtime cache for this instance
+        // Clear runtime cache and routing health for this instance
         self.runtime_cache.remove(&id);
+        self.router_health.reset(&id);
+
+        // The active instance's model may have changed
+        let active = self.get_active_instance();
+        if active.as_ref().is_some_and(|active| active.id == id) {
+            activate_tokenizer(active.as_ref());
+        }
 
         Ok(())
     }
@@ -263,6 +331,7 @@ impl LlmBackendInstanceManager {
 
         // Clear health cache
         self.health_cache.remove(id);
+        self.router_health.reset(id);
 
         Ok(())
     }
@@ -592,6 +661,139 @@ impl LlmBackendInstanceManager {
             .filter(|item| item.value().1.elapsed() < std::time::Duration::from_secs(60))
             .map(|item| item.value().0)
     }
+
+    /// Publish routing failover events on this event bus
+    pub fn set_event_bus(&self, event_bus: Arc<EventBus>) {
+        if let Ok(mut guard) = self.event_bus.lock() {
+            *guard = Some(event_bus);
+        }
+    }
+
+    /// List all routing policies
+    pub fn list_policies(&self) -> Result<Vec<RoutingPolicy>, LlmError> {
+        self.storage
+            .load_all_policies()
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))
+    }
+
+    /// Get a routing policy
+    pub fn get_policy(&self, id: &str) -> Result<Option<RoutingPolicy>, LlmError> {
+        self.storage
+            .load_policy(id)
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))
+    }
+
+    /// Add or update a routing policy
+    pub fn upsert_policy(&self, mut policy: RoutingPolicy) -> Result<(), LlmError> {
+        policy.validate().map_err(LlmError::InvalidInput)?;
+
+        if let Some(missing) = policy
+            .backends
+            .iter()
+            .find(|id| !self.instances.contains_key(id.as_str()))
+        {
+            return Err(LlmError::InvalidInput(format!(
+                "Backend instance {} does not exist",
+                missing
+            )));
+        }
+
+        policy.touch();
+        self.storage
+            .save_policy(&policy)
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))
+    }
+
+    /// Remove a routing policy
+    pub fn remove_policy(&self, id: &str) -> Result<bool, LlmError> {
+        self.storage
+            .delete_policy(id)
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))
+    }
+
+    /// Get a runtime that routes requests according to a policy
+    ///
+    /// Backends that were deleted or fail to initialize are left out.
+    pub async fn get_routed_runtime(
+        &self,
+        policy_id: &str,
+    ) -> Result<Arc<RoutedLlmRuntime>, LlmError> {
+        let policy = self
+            .get_policy(policy_id)?
+            .ok_or_else(|| LlmError::InvalidInput(format!("Routing policy {}", policy_id)))?;
+
+        let mut candidates = Vec::new();
+        for id in &policy.backends {
+            let Some(instance) = self.get_instance(id) else {
+                tracing::warn!("Routing policy {} references missing backend {}", policy.id, id);
+                continue;
+            };
+            let runtime = match self.get_runtime(id).await {
+                Ok(runtime) => runtime,
+                Err(e) => {
+                    tracing::warn!("Routing policy {} skips backend {}: {}", policy.id, id, e);
+                    continue;
+                }
+            };
+
+            let mut candidate = RouteCandidate::new(id.clone(), runtime)
+                .with_capabilities(routing_capabilities(&instance.capabilities));
+            if matches!(instance.backend_type, LlmBackendType::Ollama) {
+                candidate = candidate.local();
+            }
+            if let Some(cost) = policy.costs.get(id) {
+                candidate = candidate.with_cost(*cost);
+            }
+            candidates.push(candidate);
+        }
+
+        if candidates.is_empty() {
+            return Err(LlmError::BackendUnavailable(format!(
+                "routing policy '{}' (no usable backends)",
+                policy.id
+            )));
+        }
+
+        let mut runtime = RoutedLlmRuntime::new(policy, candidates, self.router_health.clone());
+        let event_bus = self.event_bus.lock().ok().and_then(|guard| guard.clone());
+        if let Some(event_bus) = event_bus {
+            runtime = runtime.with_event_bus(event_bus);
+        }
+        Ok(Arc::new(runtime))
+    }
+
+    /// ID of the routing policy used by chat sessions
+    pub fn chat_policy_id(&self) -> Option<String> {
+        self.storage.get_chat_policy_id().ok().flatten()
+    }
+
+    /// Route chat sessions through a policy, or clear it with `None`
+    pub fn set_chat_policy(&self, policy_id: Option<&str>) -> Result<(), LlmError> {
+        self.storage
+            .set_chat_policy(policy_id)
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))
+    }
+
+    /// Get the runtime for chat: the chat routing policy when one is set,
+    /// otherwise the active backend
+    pub async fn get_chat_runtime(&self) -> Result<Arc<dyn LlmRuntime>, LlmError> {
+        if let Some(policy_id) = self.chat_policy_id() {
+            match self.get_routed_runtime(&policy_id).await {
+                Ok(runtime) => return Ok(runtime as Arc<dyn LlmRuntime>),
+                Err(e) => tracing::warn!(
+                    "Chat routing policy {} is unusable, using the active backend: {}",
+                    policy_id,
+                    e
+                ),
+            }
+        }
+        self.get_active_runtime().await
+    }
+
+    /// Circuit breaker state and metrics of routed backends
+    pub fn routing_health(&self) -> Vec<BackendHealth> {
+        self.router_health.snapshot()
+    }
 }
 
 /// Backend type definition
@@ -650,8 +852,8 @@ pub fn get_instance_manager() -> Result<Arc<LlmBackendInstanceManager>, LlmError
     }
 
     // Use a separate database file to avoid conflicts with settings store
-    // The settings store uses data/settings.redb, so we use data/llm_backends.redb
-    let backend_store = LlmBackendStore::open("data/llm_backends.redb")
+    // The settings store uses settings.redb, so we use llm_backends.redb
+    let backend_store = LlmBackendStore::open(neomind_storage::data_path("llm_backends.redb"))
         .map_err(|e| LlmError::InvalidInput(format!("Failed to open backend store: {}", e)))?;
 
     let manager = Arc::new(LlmBackendInstanceManager::new(backend_store));
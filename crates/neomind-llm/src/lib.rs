@@ -21,11 +21,15 @@
 
 pub mod backend_plugin;
 pub mod backends;
+pub mod cache;
 pub mod config;
 pub mod factories;
 pub mod instance_manager;
 pub mod rate_limited_client;
+pub mod routing;
+pub mod structured;
 pub mod tokenizer;
+pub mod usage;
 
 // Re-export backend types based on features
 #[cfg(feature = "ollama")]
@@ -34,11 +38,31 @@ pub use backends::ollama::{OllamaConfig, OllamaRuntime};
 #[cfg(feature = "cloud")]
 pub use backends::openai::{CloudConfig, CloudProvider, CloudRuntime};
 
+#[cfg(feature = "cloud")]
+pub use backends::anthropic::{AnthropicConfig, AnthropicRuntime, AnthropicUsage};
+
 // Config and utilities
 pub use config::{
     GenerationParams as LlmGenerationParams, LlmBackendConfig, LlmConfig, LlmRuntimeManager,
 };
-pub use tokenizer::TokenizerWrapper;
+pub use tokenizer::{TokenizerRegistry, TokenizerWrapper};
+
+// Routing policies
+pub use routing::{
+    BackendHealth, CircuitBreaker, CircuitState, RouteCandidate, RoutedLlmRuntime, RouterHealth,
+};
+
+// Response cache
+pub use cache::{
+    get_response_cache, without_response_cache, CachePolicy, CacheStats, CachedLlmRuntime,
+    PromptEmbedder, ResponseCache,
+};
+
+// Structured output
+pub use structured::{JsonSchema, OutputSchema, SchemaError, StructuredError, StructuredGenerator};
+
+// Usage tracking
+pub use usage::{get_usage_tracker, UsageTracker};
 
 // Plugin system
 pub use backend_plugin::{BackendRegistry, DynBackendPlugin, LlmBackendPlugin};
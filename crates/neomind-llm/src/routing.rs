@@ -0,0 +1,883 @@
+//! LLM request routing across backend instances.
+//!
+//! A [`RoutingPolicy`] names a set of backend instances and a strategy for
+//! choosing between them. [`RoutedLlmRuntime`] applies a policy per request:
+//!
+//! 1. Backends lacking a capability the request needs (images, tools,
+//!    context length) are skipped.
+//! 2. The remaining backends are ordered by the policy's strategy.
+//! 3. Each backend is tried in turn; a failure falls through to the next one
+//!    and publishes an `LlmBackendFailover` event.
+//!
+//! Every instance has a [`CircuitBreaker`], shared by all policies that use
+//! it, which takes the instance out of rotation after repeated failures.
+
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+use futures::{Stream, StreamExt};
+use serde::Serialize;
+
+use neomind_core::event::NeoMindEvent;
+use neomind_core::eventbus::EventBus;
+use neomind_core::llm::backend::{
+    BackendCapabilities, BackendId, BackendMetrics, LlmError, LlmInput, LlmOutput, LlmRuntime,
+    StreamChunk,
+};
+use neomind_storage::{RoutingPolicy, RoutingStrategy};
+
+/// State of a backend's circuit breaker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Requests flow normally
+    Closed,
+    /// Requests are rejected until the open period elapses
+    Open,
+    /// The open period elapsed; one trial request decides the next state
+    HalfOpen,
+}
+
+#[derive(Debug, Default)]
+struct BreakerState {
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trial_started: Option<Instant>,
+}
+
+/// Per-instance circuit breaker.
+///
+/// Opens after `failure_threshold` consecutive failures. Once `open_duration`
+/// has passed, a single trial request is let through: success closes the
+/// circuit, failure opens it again.
+#[derive(Debug)]
+pub struct CircuitBreaker {
+    failure_threshold: u32,
+    open_duration: Duration,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    /// Create a closed circuit breaker.
+    pub fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            failure_threshold: failure_threshold.max(1),
+            open_duration,
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    /// Current state.
+    pub fn state(&self) -> CircuitState {
+        let state = self.state.lock().unwrap();
+        match state.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.open_duration => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// Consecutive failures since the last success.
+    pub fn consecutive_failures(&self) -> u32 {
+        self.state.lock().unwrap().consecutive_failures
+    }
+
+    /// Ask to send a request. Returns `false` while the circuit is open, or
+    /// while another half-open trial is in flight.
+    pub fn try_acquire(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let Some(opened_at) = state.opened_at else {
+            return true;
+        };
+        if opened_at.elapsed() < self.open_duration {
+            return false;
+        }
+        // A trial that never reported back (e.g. a cancelled request)
+        // does not block the circuit forever
+        if state
+            .trial_started
+            .is_some_and(|started| started.elapsed() < self.open_duration)
+        {
+            return false;
+        }
+        state.trial_started = Some(Instant::now());
+        true
+    }
+
+    /// Record a successful request, closing the circuit.
+    pub fn record_success(&self) {
+        *self.state.lock().unwrap() = BreakerState::default();
+    }
+
+    /// End a half-open trial without a verdict on the backend's health, so
+    /// the next request can try again.
+    pub fn release_trial(&self) {
+        self.state.lock().unwrap().trial_started = None;
+    }
+
+    /// Record a failed request. Returns `true` if this failure opened the
+    /// circuit.
+    pub fn record_failure(&self) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.consecutive_failures += 1;
+        let half_open_trial = state.trial_started.take().is_some();
+        if half_open_trial || state.consecutive_failures == self.failure_threshold {
+            state.opened_at = Some(Instant::now());
+            return true;
+        }
+        false
+    }
+}
+
+/// Health of one backend instance: its circuit breaker and request metrics.
+#[derive(Debug)]
+pub struct InstanceHealth {
+    pub breaker: CircuitBreaker,
+    metrics: Mutex<BackendMetrics>,
+}
+
+impl InstanceHealth {
+    fn new(failure_threshold: u32, open_duration: Duration) -> Self {
+        Self {
+            breaker: CircuitBreaker::new(failure_threshold, open_duration),
+            metrics: Mutex::new(BackendMetrics::default()),
+        }
+    }
+
+    /// Request metrics observed by the router.
+    pub fn metrics(&self) -> BackendMetrics {
+        self.metrics.lock().unwrap().clone()
+    }
+
+    fn record_success(&self, tokens: u64, latency: Duration) {
+        self.breaker.record_success();
+        self.metrics
+            .lock()
+            .unwrap()
+            .record_success(tokens, latency.as_millis() as u64);
+    }
+
+    fn record_failure(&self, error: &LlmError) -> bool {
+        self.metrics.lock().unwrap().record_failure();
+        // Bad requests say nothing about the backend's health
+        if matches!(error, LlmError::InvalidInput(_)) {
+            self.breaker.release_trial();
+            return false;
+        }
+        self.breaker.record_failure()
+    }
+}
+
+/// Health snapshot of one backend instance, as reported by the API.
+#[derive(Debug, Clone, Serialize)]
+pub struct BackendHealth {
+    pub instance_id: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    pub total_requests: u64,
+    pub failed_requests: u64,
+    pub success_rate: f64,
+    pub avg_latency_ms: f64,
+}
+
+/// Health of all routed backend instances, keyed by instance ID.
+#[derive(Debug, Default)]
+pub struct RouterHealth {
+    instances: DashMap<String, Arc<InstanceHealth>>,
+}
+
+impl RouterHealth {
+    /// Create an empty health registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Health of an instance, created with the policy's breaker settings on
+    /// first use.
+    pub fn get(&self, instance_id: &str, policy: &RoutingPolicy) -> Arc<InstanceHealth> {
+        self.instances
+            .entry(instance_id.to_string())
+            .or_insert_with(|| {
+                Arc::new(InstanceHealth::new(
+                    policy.failure_threshold,
+                    Duration::from_secs(policy.open_secs),
+                ))
+            })
+            .clone()
+    }
+
+    /// Forget an instance, e.g. after its configuration changed.
+    pub fn reset(&self, instance_id: &str) {
+        self.instances.remove(instance_id);
+    }
+
+    /// Snapshot of every instance that has served routed requests.
+    pub fn snapshot(&self) -> Vec<BackendHealth> {
+        let mut health: Vec<BackendHealth> = self
+            .instances
+            .iter()
+            .map(|entry| {
+                let metrics = entry.metrics();
+                BackendHealth {
+                    instance_id: entry.key().clone(),
+                    state: entry.breaker.state(),
+                    consecutive_failures: entry.breaker.consecutive_failures(),
+                    total_requests: metrics.total_requests,
+                    failed_requests: metrics.failed_requests,
+                    success_rate: metrics.success_rate(),
+                    avg_latency_ms: metrics.avg_latency_ms,
+                }
+            })
+            .collect();
+        health.sort_by(|a, b| a.instance_id.cmp(&b.instance_id));
+        health
+    }
+}
+
+/// A backend instance a routing policy can send requests to.
+pub struct RouteCandidate {
+    /// Backend instance ID
+    pub instance_id: String,
+    /// Runtime for the instance
+    pub runtime: Arc<dyn LlmRuntime>,
+    /// Capabilities used to filter requests
+    pub capabilities: BackendCapabilities,
+    /// Whether the backend runs locally (e.g. Ollama)
+    pub local: bool,
+    /// Relative cost for `LowestCost` routing
+    pub cost: f64,
+}
+
+impl RouteCandidate {
+    /// Create a cloud candidate with the runtime's own capabilities.
+    pub fn new(instance_id: impl Into<String>, runtime: Arc<dyn LlmRuntime>) -> Self {
+        Self {
+            instance_id: instance_id.into(),
+            capabilities: runtime.capabilities(),
+            runtime,
+            local: false,
+            cost: 1.0,
+        }
+    }
+
+    /// Mark the candidate as local, which also makes it free by default.
+    pub fn local(mut self) -> Self {
+        self.local = true;
+        self.cost = 0.0;
+        self
+    }
+
+    /// Override the capabilities used for filtering.
+    pub fn with_capabilities(mut self, capabilities: BackendCapabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Set the relative cost.
+    pub fn with_cost(mut self, cost: f64) -> Self {
+        self.cost = cost;
+        self
+    }
+
+    /// Why this candidate cannot serve a request, if it cannot.
+    fn unsupported(&self, input: &LlmInput) -> Option<&'static str> {
+        let caps = &self.capabilities;
+        if input.messages.iter().any(|m| m.has_images())
+            && !(caps.multimodal || caps.supports_images)
+        {
+            return Some("images");
+        }
+        if input.tools.as_ref().is_some_and(|tools| !tools.is_empty()) && !caps.function_calling {
+            return Some("tools");
+        }
+        if let Some(max_context) = caps.max_context {
+            let tokens: usize = input
+                .messages
+                .iter()
+                .map(|m| self.runtime.estimate_tokens(&m.text()))
+                .sum();
+            if tokens > max_context {
+                return Some("context length");
+            }
+        }
+        None
+    }
+}
+
+/// LLM runtime that routes each request according to a [`RoutingPolicy`].
+pub struct RoutedLlmRuntime {
+    policy: RoutingPolicy,
+    candidates: Vec<RouteCandidate>,
+    health: Arc<RouterHealth>,
+    event_bus: Option<Arc<EventBus>>,
+}
+
+impl RoutedLlmRuntime {
+    /// Create a routed runtime. `candidates` should follow the policy's
+    /// backend order.
+    pub fn new(
+        policy: RoutingPolicy,
+        candidates: Vec<RouteCandidate>,
+        health: Arc<RouterHealth>,
+    ) -> Self {
+        Self {
+            policy,
+            candidates,
+            health,
+            event_bus: None,
+        }
+    }
+
+    /// Publish failover events on this bus.
+    pub fn with_event_bus(mut self, event_bus: Arc<EventBus>) -> Self {
+        self.event_bus = Some(event_bus);
+        self
+    }
+
+    /// The routing policy.
+    pub fn policy(&self) -> &RoutingPolicy {
+        &self.policy
+    }
+
+    /// Candidates able to serve `input`, in the order they should be tried.
+    pub fn route(&self, input: &LlmInput) -> Result<Vec<&RouteCandidate>, LlmError> {
+        let mut unsupported = Vec::new();
+        let mut order: Vec<&RouteCandidate> = self
+            .candidates
+            .iter()
+            .filter(|candidate| match candidate.unsupported(input) {
+                Some(reason) => {
+                    unsupported.push(reason);
+                    false
+                }
+                None => true,
+            })
+            .collect();
+
+        if order.is_empty() {
+            unsupported.dedup();
+            return Err(LlmError::InvalidInput(format!(
+                "No backend in routing policy '{}' supports this request ({})",
+                self.policy.id,
+                unsupported.join(", ")
+            )));
+        }
+
+        // Stable sorts keep the configured order among equal backends
+        match self.policy.strategy {
+            RoutingStrategy::Failover => {}
+            RoutingStrategy::LowestLatency => {
+                // Untried backends sort first so they get measured
+                let latency = |c: &RouteCandidate| {
+                    let metrics = self.health.get(&c.instance_id, &self.policy).metrics();
+                    if metrics.successful_requests == 0 {
+                        0.0
+                    } else {
+                        metrics.avg_latency_ms
+                    }
+                };
+                order.sort_by(|a, b| latency(a).total_cmp(&latency(b)));
+            }
+            RoutingStrategy::LowestCost => {
+                order.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+            }
+            RoutingStrategy::LocalFirst => {
+                let has_images = input.messages.iter().any(|m| m.has_images());
+                order.sort_by_key(|c| c.local == has_images);
+            }
+        }
+
+        Ok(order)
+    }
+
+    fn publish_failover(
+        &self,
+        from: &RouteCandidate,
+        to: Option<&RouteCandidate>,
+        error: &LlmError,
+        circuit_opened: bool,
+    ) {
+        tracing::warn!(
+            policy = %self.policy.id,
+            from = %from.instance_id,
+            to = to.map(|c| c.instance_id.as_str()).unwrap_or("none"),
+            circuit_opened,
+            "LLM backend failed: {}",
+            error
+        );
+
+        if let Some(event_bus) = &self.event_bus {
+            event_bus.publish_sync(NeoMindEvent::LlmBackendFailover {
+                policy_id: self.policy.id.clone(),
+                from_backend: from.instance_id.clone(),
+                to_backend: to.map(|c| c.instance_id.clone()),
+                error: error.to_string(),
+                circuit_opened,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs() as i64)
+                    .unwrap_or_default(),
+            });
+        }
+    }
+
+    /// Next candidate after `index` whose circuit is not open.
+    fn next_candidate<'a>(
+        &self,
+        order: &[&'a RouteCandidate],
+        index: usize,
+    ) -> Option<&'a RouteCandidate> {
+        order[index + 1..].iter().copied().find(|c| {
+            self.health
+                .get(&c.instance_id, &self.policy)
+                .breaker
+                .state()
+                != CircuitState::Open
+        })
+    }
+
+    fn exhausted(&self, last_error: Option<LlmError>) -> LlmError {
+        last_error.unwrap_or_else(|| {
+            LlmError::BackendUnavailable(format!(
+                "routing policy '{}' (all circuits open)",
+                self.policy.id
+            ))
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl LlmRuntime for RoutedLlmRuntime {
+    /// Backend ID of the policy's first backend.
+    fn backend_id(&self) -> BackendId {
+        self.candidates
+            .first()
+            .map(|c| c.runtime.backend_id())
+            .unwrap_or_else(|| BackendId::new(format!("routing:{}", self.policy.id)))
+    }
+
+    /// Model of the policy's first backend.
+    fn model_name(&self) -> &str {
+        self.candidates
+            .first()
+            .map(|c| c.runtime.model_name())
+            .unwrap_or("none")
+    }
+
+    async fn is_available(&self) -> bool {
+        for candidate in &self.candidates {
+            let health = self.health.get(&candidate.instance_id, &self.policy);
+            if health.breaker.state() != CircuitState::Open
+                && candidate.runtime.is_available().await
+            {
+                return true;
+            }
+        }
+        false
+    }
+
+    async fn generate(&self, input: LlmInput) -> Result<LlmOutput, LlmError> {
+        let order = self.route(&input)?;
+        let mut last_error = None;
+
+        for (index, candidate) in order.iter().enumerate() {
+            let health = self.health.get(&candidate.instance_id, &self.policy);
+            if !health.breaker.try_acquire() {
+                continue;
+            }
+
+            let start = Instant::now();
+            match candidate.runtime.generate(input.clone()).await {
+                Ok(output) => {
+                    let tokens = output.usage.map(|u| u.total_tokens).unwrap_or(0);
+                    health.record_success(tokens as u64, start.elapsed());
+                    return Ok(output);
+                }
+                // Budgets limit the caller, not the backend; another backend won't help
+                Err(e @ LlmError::BudgetExceeded(_)) => {
+                    health.breaker.release_trial();
+                    return Err(e);
+                }
+                Err(e) => {
+                    let opened = health.record_failure(&e);
+                    self.publish_failover(
+                        candidate,
+                        self.next_candidate(&order, index),
+                        &e,
+                        opened,
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(self.exhausted(last_error))
+    }
+
+    /// Fails over until a backend produces its first chunk; errors after
+    /// that are passed through, since part of the answer was already sent.
+    async fn generate_stream(
+        &self,
+        input: LlmInput,
+    ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+        let order = self.route(&input)?;
+        let mut last_error = None;
+
+        for (index, candidate) in order.iter().enumerate() {
+            let health = self.health.get(&candidate.instance_id, &self.policy);
+            if !health.breaker.try_acquire() {
+                continue;
+            }
+
+            let start = Instant::now();
+            let first = match candidate.runtime.generate_stream(input.clone()).await {
+                Ok(mut stream) => match stream.next().await {
+                    Some(Err(e)) => Err(e),
+                    first => Ok((first, stream)),
+                },
+                Err(e) => Err(e),
+            };
+
+            match first {
+                Ok((first, rest)) => {
+                    health.record_success(0, start.elapsed());
+                    return Ok(Box::pin(futures::stream::iter(first).chain(rest)));
+                }
+                Err(e @ LlmError::BudgetExceeded(_)) => {
+                    health.breaker.release_trial();
+                    return Err(e);
+                }
+                Err(e) => {
+                    let opened = health.record_failure(&e);
+                    self.publish_failover(
+                        candidate,
+                        self.next_candidate(&order, index),
+                        &e,
+                        opened,
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        Err(self.exhausted(last_error))
+    }
+
+    /// Smallest context window, so prompts fit whichever backend serves them.
+    fn max_context_length(&self) -> usize {
+        self.candidates
+            .iter()
+            .map(|c| c.runtime.max_context_length())
+            .min()
+            .unwrap_or(0)
+    }
+
+    fn estimate_tokens(&self, text: &str) -> usize {
+        self.candidates
+            .first()
+            .map(|c| c.runtime.estimate_tokens(text))
+            .unwrap_or(text.len() / 4)
+    }
+
+    fn supports_multimodal(&self) -> bool {
+        self.candidates
+            .iter()
+            .any(|c| c.capabilities.multimodal || c.capabilities.supports_images)
+    }
+
+    /// Union of the backends' capabilities; requests needing a capability
+    /// are routed to backends that have it.
+    fn capabilities(&self) -> BackendCapabilities {
+        self.candidates.iter().fold(
+            BackendCapabilities {
+                max_context: Some(self.max_context_length()),
+                ..Default::default()
+            },
+            |mut caps, c| {
+                let other = &c.capabilities;
+                caps.streaming |= other.streaming;
+                caps.multimodal |= other.multimodal;
+                caps.function_calling |= other.function_calling;
+                caps.multiple_models |= other.multiple_models;
+                caps.thinking_display |= other.thinking_display;
+                caps.supports_images |= other.supports_images;
+                caps.supports_audio |= other.supports_audio;
+                for modality in &other.modalities {
+                    if !caps.modalities.contains(modality) {
+                        caps.modalities.push(modality.clone());
+                    }
+                }
+                caps
+            },
+        )
+    }
+
+    /// Router-observed metrics, summed over the policy's backends.
+    fn metrics(&self) -> BackendMetrics {
+        let mut total = BackendMetrics::default();
+        let mut latency_sum = 0.0;
+        for candidate in &self.candidates {
+            let metrics = self
+                .health
+                .get(&candidate.instance_id, &self.policy)
+                .metrics();
+            total.total_requests += metrics.total_requests;
+            total.successful_requests += metrics.successful_requests;
+            total.failed_requests += metrics.failed_requests;
+            total.total_tokens += metrics.total_tokens;
+            latency_sum += metrics.avg_latency_ms * metrics.successful_requests as f64;
+            total.last_request = total.last_request.max(metrics.last_request);
+        }
+        if total.successful_requests > 0 {
+            total.avg_latency_ms = latency_sum / total.successful_requests as f64;
+        }
+        total
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neomind_core::llm::backend::FinishReason;
+    use neomind_core::message::Message;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// Runtime that fails a fixed number of times, then answers with its name.
+    struct FlakyRuntime {
+        name: &'static str,
+        failures: AtomicUsize,
+        calls: AtomicUsize,
+        multimodal: bool,
+    }
+
+    impl FlakyRuntime {
+        fn new(name: &'static str, failures: usize) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                failures: AtomicUsize::new(failures),
+                calls: AtomicUsize::new(0),
+                multimodal: false,
+            })
+        }
+
+        fn vision(name: &'static str) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                failures: AtomicUsize::new(0),
+                calls: AtomicUsize::new(0),
+                multimodal: true,
+            })
+        }
+
+        fn fail(&self) -> Option<LlmError> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            let remaining = self.failures.load(Ordering::SeqCst);
+            (remaining > 0).then(|| {
+                self.failures.store(remaining - 1, Ordering::SeqCst);
+                LlmError::Network(format!("{} is busy", self.name))
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LlmRuntime for FlakyRuntime {
+        fn backend_id(&self) -> BackendId {
+            BackendId::new(self.name)
+        }
+
+        fn model_name(&self) -> &str {
+            self.name
+        }
+
+        async fn generate(&self, _input: LlmInput) -> Result<LlmOutput, LlmError> {
+            if let Some(e) = self.fail() {
+                return Err(e);
+            }
+            Ok(LlmOutput {
+                text: self.name.to_string(),
+                finish_reason: FinishReason::Stop,
+                usage: None,
+                thinking: None,
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _input: LlmInput,
+        ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+            let first = match self.fail() {
+                Some(e) => Err(e),
+                None => Ok((self.name.to_string(), false)),
+            };
+            Ok(Box::pin(futures::stream::iter(vec![first])))
+        }
+
+        fn max_context_length(&self) -> usize {
+            8192
+        }
+
+        fn capabilities(&self) -> BackendCapabilities {
+            let builder = BackendCapabilities::builder().streaming();
+            if self.multimodal {
+                builder.multimodal().build()
+            } else {
+                builder.build()
+            }
+        }
+    }
+
+    fn policy(strategy: RoutingStrategy, backends: &[&str]) -> RoutingPolicy {
+        RoutingPolicy::new(
+            "test",
+            "Test",
+            strategy,
+            backends.iter().map(|b| b.to_string()).collect(),
+        )
+    }
+
+    #[test]
+    fn test_circuit_breaker() {
+        let breaker = CircuitBreaker::new(2, Duration::from_millis(20));
+        assert!(breaker.try_acquire());
+        assert!(!breaker.record_failure());
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Open);
+        assert!(!breaker.try_acquire());
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(breaker.state(), CircuitState::HalfOpen);
+        assert!(breaker.try_acquire());
+        // Only one trial at a time
+        assert!(!breaker.try_acquire());
+        // A failed trial opens the circuit again
+        assert!(breaker.record_failure());
+        assert_eq!(breaker.state(), CircuitState::Open);
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(breaker.try_acquire());
+        breaker.record_success();
+        assert_eq!(breaker.state(), CircuitState::Closed);
+        assert_eq!(breaker.consecutive_failures(), 0);
+    }
+
+    #[test]
+    fn test_trial_without_verdict_is_released() {
+        let health = InstanceHealth::new(1, Duration::from_millis(20));
+        assert!(health.record_failure(&LlmError::Network("down".to_string())));
+
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(health.breaker.try_acquire());
+        // A bad request says nothing about the backend; the next request
+        // gets the trial instead of waiting for the open period
+        assert!(!health.record_failure(&LlmError::InvalidInput("bad".to_string())));
+        assert_eq!(health.breaker.state(), CircuitState::HalfOpen);
+        assert!(health.breaker.try_acquire());
+
+        health.breaker.release_trial();
+        assert!(health.breaker.try_acquire());
+    }
+
+    #[tokio::test]
+    async fn test_failover_and_events() {
+        let local = FlakyRuntime::new("local", 10);
+        let cloud = FlakyRuntime::new("cloud", 0);
+        let mut policy = policy(RoutingStrategy::Failover, &["local", "cloud"]);
+        policy.failure_threshold = 2;
+
+        let event_bus = Arc::new(EventBus::new());
+        let mut events = event_bus.subscribe();
+        let health = Arc::new(RouterHealth::new());
+        let runtime = RoutedLlmRuntime::new(
+            policy,
+            vec![
+                RouteCandidate::new("local", local.clone()).local(),
+                RouteCandidate::new("cloud", cloud.clone()),
+            ],
+            health.clone(),
+        )
+        .with_event_bus(event_bus);
+
+        for _ in 0..3 {
+            let output = runtime.generate(LlmInput::new("Hi")).await.unwrap();
+            assert_eq!(output.text, "cloud");
+        }
+        // The circuit opened after two failures, so the third call skipped it
+        assert_eq!(local.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(health.snapshot()[1].instance_id, "local");
+        assert_eq!(health.snapshot()[1].state, CircuitState::Open);
+
+        let (event, _) = events.recv().await.unwrap();
+        match event {
+            NeoMindEvent::LlmBackendFailover {
+                from_backend,
+                to_backend,
+                circuit_opened,
+                ..
+            } => {
+                assert_eq!(from_backend, "local");
+                assert_eq!(to_backend.as_deref(), Some("cloud"));
+                assert!(!circuit_opened);
+            }
+            other => panic!("unexpected event {:?}", other),
+        }
+
+        // Streams fail over on the first chunk too
+        let mut stream = runtime.generate_stream(LlmInput::new("Hi")).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap().0, "cloud");
+    }
+
+    #[tokio::test]
+    async fn test_capability_filters_and_strategies() {
+        let local = FlakyRuntime::new("local", 0);
+        let cloud = FlakyRuntime::vision("cloud");
+        let candidates = || {
+            vec![
+                RouteCandidate::new("local", local.clone()).local(),
+                RouteCandidate::new("cloud", cloud.clone()).with_cost(0.5),
+            ]
+        };
+        let health = Arc::new(RouterHealth::new());
+        let image = LlmInput {
+            messages: vec![Message::user_with_image("What is this?", "http://x/a.png")],
+            ..LlmInput::new("")
+        };
+
+        let local_first = RoutedLlmRuntime::new(
+            policy(RoutingStrategy::LocalFirst, &["cloud", "local"]),
+            candidates(),
+            health.clone(),
+        );
+        let text_order = local_first.route(&LlmInput::new("Hi")).unwrap();
+        assert_eq!(text_order[0].instance_id, "local");
+        let image_order = local_first.route(&image).unwrap();
+        assert_eq!(image_order.len(), 1);
+        assert_eq!(image_order[0].instance_id, "cloud");
+
+        let mut cheapest = RoutedLlmRuntime::new(
+            policy(RoutingStrategy::LowestCost, &["cloud", "local"]),
+            candidates(),
+            health.clone(),
+        );
+        assert_eq!(
+            cheapest.route(&LlmInput::new("Hi")).unwrap()[0].instance_id,
+            "local"
+        );
+
+        // Nothing supports tools
+        let mut tools = LlmInput::new("Hi");
+        tools.tools = Some(vec![neomind_core::llm::backend::ToolDefinition {
+            name: "t".to_string(),
+            description: String::new(),
+            parameters: serde_json::json!({}),
+        }]);
+        let err = cheapest.route(&tools).err().unwrap();
+        assert!(err.to_string().contains("tools"));
+
+        // Capabilities are the union over backends
+        assert!(cheapest.capabilities().multimodal);
+        cheapest.candidates.truncate(1);
+        assert!(!cheapest.capabilities().multimodal);
+    }
+}
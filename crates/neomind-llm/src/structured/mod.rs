@@ -0,0 +1,407 @@
+//! Schema-constrained structured output.
+//!
+//! [`StructuredGenerator`] asks a backend for JSON matching an
+//! [`OutputSchema`]. Backends with a native JSON mode (Ollama `format`,
+//! OpenAI-compatible `response_format`) receive the schema directly; all
+//! backends also get it in a system message. The response is extracted,
+//! validated and, when it does not conform, sent back to the model with the
+//! validation errors for a bounded number of repair attempts.
+
+mod schema;
+
+pub use schema::{extract_json, validate, SchemaError};
+
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+
+use neomind_core::llm::backend::{LlmError, LlmInput, LlmRuntime, ResponseFormat};
+use neomind_core::message::{Message, MessageRole};
+
+/// Default number of repair round-trips after the first attempt.
+pub const DEFAULT_MAX_REPAIRS: usize = 2;
+
+/// A type with a known JSON Schema.
+pub trait JsonSchema {
+    /// Schema name sent to backends that require one
+    fn schema_name() -> String {
+        let name = std::any::type_name::<Self>();
+        name.rsplit("::").next().unwrap_or(name).to_string()
+    }
+
+    /// JSON Schema describing the serialized form of the type
+    fn json_schema() -> Value;
+}
+
+/// A named JSON Schema for model output.
+#[derive(Debug, Clone, PartialEq)]
+pub struct OutputSchema {
+    /// Name, restricted to `[a-zA-Z0-9_-]`
+    pub name: String,
+    /// JSON Schema document
+    pub schema: Value,
+}
+
+impl OutputSchema {
+    /// Create a schema; characters not allowed in names become `_`.
+    pub fn new(name: impl Into<String>, schema: Value) -> Self {
+        let name: String = name
+            .into()
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Self {
+            name: if name.is_empty() {
+                "output".to_string()
+            } else {
+                name
+            },
+            schema,
+        }
+    }
+
+    /// The schema of a [`JsonSchema`] type
+    pub fn of<T: JsonSchema>() -> Self {
+        Self::new(T::schema_name(), T::json_schema())
+    }
+
+    /// Validate a value against the schema
+    pub fn validate(&self, value: &Value) -> Vec<SchemaError> {
+        validate(&self.schema, value)
+    }
+
+    fn response_format(&self) -> ResponseFormat {
+        ResponseFormat::JsonSchema {
+            name: self.name.clone(),
+            schema: self.schema.clone(),
+        }
+    }
+}
+
+/// Structured output errors.
+#[derive(Debug, thiserror::Error)]
+pub enum StructuredError {
+    /// The backend call failed
+    #[error(transparent)]
+    Llm(#[from] LlmError),
+
+    /// No attempt produced output matching the schema
+    #[error("Output did not match schema after {attempts} attempts: {}", format_errors(.errors))]
+    Invalid {
+        attempts: usize,
+        errors: Vec<SchemaError>,
+        /// Raw text of the last attempt
+        output: String,
+    },
+}
+
+impl From<StructuredError> for LlmError {
+    fn from(e: StructuredError) -> Self {
+        match e {
+            StructuredError::Llm(e) => e,
+            e @ StructuredError::Invalid { .. } => LlmError::Generation(e.to_string()),
+        }
+    }
+}
+
+fn format_errors(errors: &[SchemaError]) -> String {
+    errors
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("; ")
+}
+
+/// Generates schema-conforming JSON with validation and repair.
+pub struct StructuredGenerator {
+    runtime: Arc<dyn LlmRuntime>,
+    max_repairs: usize,
+    native_format: bool,
+}
+
+impl StructuredGenerator {
+    /// Create a generator for a backend
+    pub fn new(runtime: Arc<dyn LlmRuntime>) -> Self {
+        Self {
+            runtime,
+            max_repairs: DEFAULT_MAX_REPAIRS,
+            native_format: true,
+        }
+    }
+
+    /// Set the number of repair attempts after the first response
+    pub fn with_max_repairs(mut self, max_repairs: usize) -> Self {
+        self.max_repairs = max_repairs;
+        self
+    }
+
+    /// Enable or disable the backend's native JSON mode (enabled by default).
+    ///
+    /// When disabled the schema is only described in the prompt.
+    pub fn with_native_format(mut self, enabled: bool) -> Self {
+        self.native_format = enabled;
+        self
+    }
+
+    /// Generate a value of a [`JsonSchema`] type
+    pub async fn generate_typed<T>(&self, input: LlmInput) -> Result<T, StructuredError>
+    where
+        T: JsonSchema + DeserializeOwned,
+    {
+        self.generate(input, &OutputSchema::of::<T>()).await
+    }
+
+    /// Generate output matching `schema` and deserialize it
+    pub async fn generate<T: DeserializeOwned>(
+        &self,
+        input: LlmInput,
+        schema: &OutputSchema,
+    ) -> Result<T, StructuredError> {
+        self.run(input, schema, |value| {
+            serde_json::from_value(value).map_err(|e| SchemaError {
+                path: "$".to_string(),
+                message: e.to_string(),
+            })
+        })
+        .await
+    }
+
+    /// Generate a JSON value matching `schema`
+    pub async fn generate_value(
+        &self,
+        input: LlmInput,
+        schema: &OutputSchema,
+    ) -> Result<Value, StructuredError> {
+        self.run(input, schema, Ok).await
+    }
+
+    async fn run<T>(
+        &self,
+        mut input: LlmInput,
+        schema: &OutputSchema,
+        convert: impl Fn(Value) -> Result<T, SchemaError>,
+    ) -> Result<T, StructuredError> {
+        input.stream = false;
+        if self.native_format {
+            input.params.response_format = Some(schema.response_format());
+        }
+        let position = input
+            .messages
+            .iter()
+            .take_while(|m| m.role == MessageRole::System)
+            .count();
+        input
+            .messages
+            .insert(position, Message::system(schema_instructions(schema)));
+
+        let attempts = self.max_repairs + 1;
+        let mut errors = Vec::new();
+        let mut output = String::new();
+        for attempt in 1..=attempts {
+            output = self.runtime.generate(input.clone()).await?.text;
+
+            errors = match extract_json(&output) {
+                None => vec![SchemaError {
+                    path: "$".to_string(),
+                    message: "response is not valid JSON".to_string(),
+                }],
+                Some(value) => match schema.validate(&value) {
+                    errors if !errors.is_empty() => errors,
+                    _ => match convert(value) {
+                        Ok(result) => return Ok(result),
+                        Err(e) => vec![e],
+                    },
+                },
+            };
+
+            tracing::debug!(
+                schema = %schema.name,
+                attempt,
+                "Structured output invalid: {}",
+                format_errors(&errors)
+            );
+            input.messages.push(Message::assistant(output.clone()));
+            input.messages.push(Message::user(repair_prompt(&errors)));
+        }
+
+        Err(StructuredError::Invalid {
+            attempts,
+            errors,
+            output,
+        })
+    }
+}
+
+fn schema_instructions(schema: &OutputSchema) -> String {
+    format!(
+        "Respond with a single JSON value that conforms to this JSON Schema. \
+         Output only the JSON, without explanations or code fences.\n\n{}",
+        serde_json::to_string_pretty(&schema.schema).unwrap_or_default()
+    )
+}
+
+fn repair_prompt(errors: &[SchemaError]) -> String {
+    let list: Vec<String> = errors.iter().map(|e| format!("- {}", e)).collect();
+    format!(
+        "Your response does not match the required JSON Schema:\n{}\n\n\
+         Reply again with only the corrected JSON.",
+        list.join("\n")
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::Pin;
+    use std::sync::Mutex;
+
+    use futures::Stream;
+    use neomind_core::llm::backend::{BackendId, FinishReason, LlmOutput, StreamChunk};
+    use serde::Deserialize;
+    use serde_json::json;
+
+    /// Runtime that replays canned responses and records its inputs.
+    struct ScriptedRuntime {
+        responses: Mutex<Vec<&'static str>>,
+        inputs: Mutex<Vec<LlmInput>>,
+    }
+
+    impl ScriptedRuntime {
+        fn new(responses: &[&'static str]) -> Arc<Self> {
+            Arc::new(Self {
+                responses: Mutex::new(responses.iter().rev().copied().collect()),
+                inputs: Mutex::new(Vec::new()),
+            })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl LlmRuntime for ScriptedRuntime {
+        fn backend_id(&self) -> BackendId {
+            BackendId::new("scripted")
+        }
+
+        fn model_name(&self) -> &str {
+            "scripted"
+        }
+
+        async fn generate(&self, input: LlmInput) -> Result<LlmOutput, LlmError> {
+            self.inputs.lock().unwrap().push(input);
+            let text = self.responses.lock().unwrap().pop().unwrap_or("");
+            Ok(LlmOutput {
+                text: text.to_string(),
+                finish_reason: FinishReason::Stop,
+                usage: None,
+                thinking: None,
+            })
+        }
+
+        async fn generate_stream(
+            &self,
+            _input: LlmInput,
+        ) -> Result<Pin<Box<dyn Stream<Item = StreamChunk> + Send>>, LlmError> {
+            Err(LlmError::Generation("not supported".to_string()))
+        }
+
+        fn max_context_length(&self) -> usize {
+            8192
+        }
+    }
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Classification {
+        category: String,
+        confidence: f32,
+    }
+
+    impl JsonSchema for Classification {
+        fn json_schema() -> Value {
+            json!({
+                "type": "object",
+                "required": ["category", "confidence"],
+                "properties": {
+                    "category": { "enum": ["switch", "sensor"] },
+                    "confidence": { "type": "number", "minimum": 0, "maximum": 1 }
+                }
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn test_structured_generation_repairs() {
+        let runtime = ScriptedRuntime::new(&[
+            "I think it is a lamp.",
+            r#"{"category": "lamp", "confidence": 0.8}"#,
+            "```json\n{\"category\": \"switch\", \"confidence\": 0.8}\n```",
+        ]);
+        let generator = StructuredGenerator::new(runtime.clone());
+
+        let mut input = LlmInput::new("Classify this device");
+        input
+            .messages
+            .insert(0, Message::system("You are helpful."));
+        input.stream = true;
+        let result: Classification = generator.generate_typed(input).await.unwrap();
+        assert_eq!(
+            result,
+            Classification {
+                category: "switch".to_string(),
+                confidence: 0.8
+            }
+        );
+
+        let inputs = runtime.inputs.lock().unwrap();
+        assert_eq!(inputs.len(), 3);
+        let first = &inputs[0];
+        assert!(!first.stream);
+        assert!(matches!(
+            &first.params.response_format,
+            Some(ResponseFormat::JsonSchema { name, .. }) if name == "Classification"
+        ));
+        assert_eq!(first.messages[1].role, MessageRole::System);
+        assert_eq!(first.messages[2].role, MessageRole::User);
+
+        // Each repair replays the bad answer and the validation errors
+        let last = &inputs[2];
+        assert_eq!(last.messages.len(), 7);
+        assert!(last.messages[6]
+            .content
+            .as_text()
+            .contains("$.category: must be one of"));
+    }
+
+    #[tokio::test]
+    async fn test_structured_generation_gives_up() {
+        let runtime = ScriptedRuntime::new(&["[]", "[]"]);
+        let generator = StructuredGenerator::new(runtime.clone())
+            .with_max_repairs(1)
+            .with_native_format(false);
+
+        let schema = OutputSchema::new("device type!", json!({ "type": "object" }));
+        assert_eq!(schema.name, "device_type_");
+        let err = generator
+            .generate_value(LlmInput::new("Describe"), &schema)
+            .await
+            .unwrap_err();
+        match err {
+            StructuredError::Invalid {
+                attempts, output, ..
+            } => {
+                assert_eq!(attempts, 2);
+                assert_eq!(output, "[]");
+            }
+            e => panic!("unexpected error: {}", e),
+        }
+        assert!(runtime.inputs.lock().unwrap()[0]
+            .params
+            .response_format
+            .is_none());
+    }
+}
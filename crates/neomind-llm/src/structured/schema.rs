@@ -0,0 +1,459 @@
+//! JSON Schema validation and JSON extraction for model output.
+//!
+//! Supports the subset of JSON Schema used for structured output:
+//! `type`, `enum`, `const`, `properties`, `required`,
+//! `additionalProperties`, `items`, `minItems`/`maxItems`, `uniqueItems`,
+//! `minLength`/`maxLength`, `minimum`/`maximum` (and their exclusive forms),
+//! `allOf`/`anyOf`/`oneOf`/`not`, `nullable` and local `$ref`s.
+//! Other keywords (such as `pattern` and `format`) are ignored.
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// A single validation failure.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SchemaError {
+    /// Location in the value, e.g. `$.actions[0].device`
+    pub path: String,
+    /// What is wrong
+    pub message: String,
+}
+
+impl SchemaError {
+    fn new(path: &str, message: impl Into<String>) -> Self {
+        Self {
+            path: path.to_string(),
+            message: message.into(),
+        }
+    }
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.path, self.message)
+    }
+}
+
+/// Validate `value` against `schema`, returning every failure found.
+pub fn validate(schema: &Value, value: &Value) -> Vec<SchemaError> {
+    let mut errors = Vec::new();
+    Validator { root: schema }.check(schema, value, "$", &mut errors);
+    errors
+}
+
+struct Validator<'a> {
+    root: &'a Value,
+}
+
+impl<'a> Validator<'a> {
+    /// Resolve a local `$ref` such as `#/$defs/Action`.
+    fn resolve(&self, reference: &str) -> Option<&'a Value> {
+        let pointer = reference.strip_prefix('#')?;
+        self.root.pointer(pointer)
+    }
+
+    fn is_valid(&self, schema: &Value, value: &Value) -> bool {
+        let mut errors = Vec::new();
+        self.check(schema, value, "$", &mut errors);
+        errors.is_empty()
+    }
+
+    fn check(&self, schema: &Value, value: &Value, path: &str, errors: &mut Vec<SchemaError>) {
+        let schema = match schema {
+            Value::Bool(true) => return,
+            Value::Bool(false) => {
+                errors.push(SchemaError::new(path, "no value is allowed here"));
+                return;
+            }
+            Value::Object(schema) => schema,
+            _ => return,
+        };
+
+        if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+            match self.resolve(reference) {
+                Some(target) => self.check(target, value, path, errors),
+                None => errors.push(SchemaError::new(
+                    path,
+                    format!("unresolvable schema reference '{}'", reference),
+                )),
+            }
+        }
+
+        if value.is_null() && schema.get("nullable").and_then(Value::as_bool) == Some(true) {
+            return;
+        }
+
+        if let Some(expected) = schema.get("type") {
+            let types: Vec<&str> = match expected {
+                Value::String(t) => vec![t.as_str()],
+                Value::Array(ts) => ts.iter().filter_map(Value::as_str).collect(),
+                _ => Vec::new(),
+            };
+            if !types.is_empty() && !types.iter().any(|t| matches_type(t, value)) {
+                errors.push(SchemaError::new(
+                    path,
+                    format!("expected {}, got {}", types.join(" or "), type_name(value)),
+                ));
+                // Further keywords would only repeat the type mismatch
+                return;
+            }
+        }
+
+        if let Some(allowed) = schema.get("enum").and_then(Value::as_array) {
+            if !allowed.contains(value) {
+                let options: Vec<String> = allowed.iter().map(Value::to_string).collect();
+                errors.push(SchemaError::new(
+                    path,
+                    format!("must be one of {}, got {}", options.join(", "), value),
+                ));
+            }
+        }
+
+        if let Some(expected) = schema.get("const") {
+            if expected != value {
+                errors.push(SchemaError::new(
+                    path,
+                    format!("must be {}, got {}", expected, value),
+                ));
+            }
+        }
+
+        match value {
+            Value::Object(object) => self.check_object(schema, object, path, errors),
+            Value::Array(items) => self.check_array(schema, items, path, errors),
+            Value::String(s) => check_string(schema, s, path, errors),
+            Value::Number(n) => {
+                if let Some(n) = n.as_f64() {
+                    check_number(schema, n, path, errors);
+                }
+            }
+            _ => {}
+        }
+
+        if let Some(all) = schema.get("allOf").and_then(Value::as_array) {
+            for sub in all {
+                self.check(sub, value, path, errors);
+            }
+        }
+
+        if let Some(any) = schema.get("anyOf").and_then(Value::as_array) {
+            if !any.iter().any(|sub| self.is_valid(sub, value)) {
+                errors.push(SchemaError::new(
+                    path,
+                    "does not match any of the allowed schemas",
+                ));
+            }
+        }
+
+        if let Some(one) = schema.get("oneOf").and_then(Value::as_array) {
+            let matching = one.iter().filter(|sub| self.is_valid(sub, value)).count();
+            if matching != 1 {
+                errors.push(SchemaError::new(
+                    path,
+                    format!("must match exactly one schema, matched {}", matching),
+                ));
+            }
+        }
+
+        if let Some(not) = schema.get("not") {
+            if self.is_valid(not, value) {
+                errors.push(SchemaError::new(path, "matches a disallowed schema"));
+            }
+        }
+    }
+
+    fn check_object(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        object: &serde_json::Map<String, Value>,
+        path: &str,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        if let Some(required) = schema.get("required").and_then(Value::as_array) {
+            for name in required.iter().filter_map(Value::as_str) {
+                if !object.contains_key(name) {
+                    errors.push(SchemaError::new(
+                        path,
+                        format!("missing required property '{}'", name),
+                    ));
+                }
+            }
+        }
+
+        let properties = schema.get("properties").and_then(Value::as_object);
+        for (name, item) in object {
+            let item_path = format!("{}.{}", path, name);
+            match properties.and_then(|p| p.get(name)) {
+                Some(property) => self.check(property, item, &item_path, errors),
+                None => match schema.get("additionalProperties") {
+                    Some(Value::Bool(false)) => {
+                        errors.push(SchemaError::new(&item_path, "property is not allowed"))
+                    }
+                    Some(additional) => self.check(additional, item, &item_path, errors),
+                    None => {}
+                },
+            }
+        }
+    }
+
+    fn check_array(
+        &self,
+        schema: &serde_json::Map<String, Value>,
+        items: &[Value],
+        path: &str,
+        errors: &mut Vec<SchemaError>,
+    ) {
+        if let Some(min) = schema.get("minItems").and_then(Value::as_u64) {
+            if (items.len() as u64) < min {
+                errors.push(SchemaError::new(
+                    path,
+                    format!("must have at least {} items, has {}", min, items.len()),
+                ));
+            }
+        }
+
+        if let Some(max) = schema.get("maxItems").and_then(Value::as_u64) {
+            if items.len() as u64 > max {
+                errors.push(SchemaError::new(
+                    path,
+                    format!("must have at most {} items, has {}", max, items.len()),
+                ));
+            }
+        }
+
+        if schema.get("uniqueItems").and_then(Value::as_bool) == Some(true) {
+            for (i, item) in items.iter().enumerate() {
+                if items[..i].contains(item) {
+                    errors.push(SchemaError::new(
+                        &format!("{}[{}]", path, i),
+                        "duplicates an earlier item",
+                    ));
+                }
+            }
+        }
+
+        if let Some(item_schema) = schema.get("items") {
+            for (i, item) in items.iter().enumerate() {
+                self.check(item_schema, item, &format!("{}[{}]", path, i), errors);
+            }
+        }
+    }
+}
+
+fn check_string(
+    schema: &serde_json::Map<String, Value>,
+    s: &str,
+    path: &str,
+    errors: &mut Vec<SchemaError>,
+) {
+    let len = s.chars().count() as u64;
+    if let Some(min) = schema.get("minLength").and_then(Value::as_u64) {
+        if len < min {
+            errors.push(SchemaError::new(
+                path,
+                format!("must be at least {} characters", min),
+            ));
+        }
+    }
+    if let Some(max) = schema.get("maxLength").and_then(Value::as_u64) {
+        if len > max {
+            errors.push(SchemaError::new(
+                path,
+                format!("must be at most {} characters", max),
+            ));
+        }
+    }
+}
+
+fn check_number(
+    schema: &serde_json::Map<String, Value>,
+    n: f64,
+    path: &str,
+    errors: &mut Vec<SchemaError>,
+) {
+    let bound = |key: &str| schema.get(key).and_then(Value::as_f64);
+    if let Some(min) = bound("minimum") {
+        if n < min {
+            errors.push(SchemaError::new(path, format!("must be >= {}", min)));
+        }
+    }
+    if let Some(max) = bound("maximum") {
+        if n > max {
+            errors.push(SchemaError::new(path, format!("must be <= {}", max)));
+        }
+    }
+    if let Some(min) = bound("exclusiveMinimum") {
+        if n <= min {
+            errors.push(SchemaError::new(path, format!("must be > {}", min)));
+        }
+    }
+    if let Some(max) = bound("exclusiveMaximum") {
+        if n >= max {
+            errors.push(SchemaError::new(path, format!("must be < {}", max)));
+        }
+    }
+}
+
+fn matches_type(expected: &str, value: &Value) -> bool {
+    match expected {
+        "object" => value.is_object(),
+        "array" => value.is_array(),
+        "string" => value.is_string(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        "number" => value.is_number(),
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        _ => true,
+    }
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "boolean",
+        Value::Number(_) => "number",
+        Value::String(_) => "string",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+    }
+}
+
+/// Find the JSON value in a model response.
+///
+/// Handles bare JSON, fenced code blocks, reasoning before a `</think>` tag
+/// and JSON embedded in surrounding prose.
+pub fn extract_json(text: &str) -> Option<Value> {
+    let text = match text.rfind("</think>") {
+        Some(end) => &text[end + "</think>".len()..],
+        None => text,
+    };
+    let text = text.trim();
+
+    if let Ok(value) = serde_json::from_str(text) {
+        return Some(value);
+    }
+
+    // Fenced code blocks, e.g. ```json ... ```
+    let mut rest = text;
+    while let Some(start) = rest.find("```") {
+        let block = &rest[start + 3..];
+        let block = block.strip_prefix("json").unwrap_or(block);
+        let Some(end) = block.find("```") else {
+            break;
+        };
+        if let Ok(value) = serde_json::from_str(block[..end].trim()) {
+            return Some(value);
+        }
+        rest = &block[end + 3..];
+    }
+
+    // The first balanced object or array that parses
+    for (start, c) in text.char_indices() {
+        if c != '{' && c != '[' {
+            continue;
+        }
+        if let Some(end) = balanced_end(&text[start..]) {
+            if let Ok(value) = serde_json::from_str(&text[start..start + end]) {
+                return Some(value);
+            }
+        }
+    }
+
+    None
+}
+
+/// Length of the bracketed JSON value at the start of `text`, if it closes.
+fn balanced_end(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_validate() {
+        let schema = json!({
+            "type": "object",
+            "required": ["category", "confidence"],
+            "additionalProperties": false,
+            "properties": {
+                "category": { "enum": ["switch", "sensor"] },
+                "confidence": { "type": "number", "minimum": 0, "maximum": 1 },
+                "tags": { "type": "array", "items": { "$ref": "#/$defs/tag" }, "maxItems": 2 },
+                "note": { "type": "string", "nullable": true }
+            },
+            "$defs": { "tag": { "type": "string", "minLength": 1 } }
+        });
+
+        let valid = json!({ "category": "switch", "confidence": 0.9, "tags": ["a"], "note": null });
+        assert!(validate(&schema, &valid).is_empty());
+
+        let invalid =
+            json!({ "category": "lamp", "confidence": 2, "tags": ["", "b", "c"], "extra": 1 });
+        let errors = validate(&schema, &invalid);
+        let paths: Vec<&str> = errors.iter().map(|e| e.path.as_str()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "$.category",
+                "$.confidence",
+                "$.tags",
+                "$.tags[0]",
+                "$.extra"
+            ]
+        );
+
+        let missing = validate(&schema, &json!({ "category": "switch" }));
+        assert_eq!(
+            missing[0].to_string(),
+            "$: missing required property 'confidence'"
+        );
+        assert_eq!(
+            validate(&schema, &json!([]))[0].message,
+            "expected object, got array"
+        );
+    }
+
+    #[test]
+    fn test_extract_json() {
+        assert_eq!(extract_json(r#"{"a": 1}"#), Some(json!({ "a": 1 })));
+        assert_eq!(
+            extract_json("Sure!\n```json\n{\"a\": [1, 2]}\n```\nDone."),
+            Some(json!({ "a": [1, 2] }))
+        );
+        assert_eq!(
+            extract_json("<think>maybe {a}</think>Result: {\"a\": \"}\"} trailing {"),
+            Some(json!({ "a": "}" }))
+        );
+        assert_eq!(extract_json("no json here"), None);
+    }
+}
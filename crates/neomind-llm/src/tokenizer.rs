@@ -1,63 +1,130 @@
 //! Tokenizer wrapper for consistent tokenization.
 //!
-//! Note: For cloud backends (Ollama, OpenAI), tokenization is handled by the server.
-//! This module provides a simple placeholder interface for compatibility.
+//! Loads a model's HuggingFace `tokenizer.json` (BPE, WordPiece or Unigram)
+//! so prompts can be counted exactly before they are sent. Without one, a
+//! placeholder falls back to heuristic estimates.
+//!
+//! Tokenizers live in a local directory (`$NEOMIND_DATA_DIR/tokenizers` or
+//! `data/tokenizers`), one per model family:
+//!
+//! ```text
+//! tokenizers/
+//!   qwen/tokenizer.json        # used for qwen2.5:7b, qwen3:14b, ...
+//!   llama3.1/tokenizer.json    # used for llama3.1:8b
+//!   gpt-4o.json
+//! ```
 
-use anyhow::Result as AnyhowResult;
-use std::path::Path;
+use anyhow::{anyhow, Context, Result as AnyhowResult};
+use dashmap::DashMap;
+use neomind_core::llm::{heuristic_count, TokenEncoder};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use tokenizers::Tokenizer;
+
+/// File name of a tokenizer inside a model directory.
+const TOKENIZER_FILE: &str = "tokenizer.json";
 
 /// Wrapper around tokenizer with common interface.
 ///
-/// For cloud backends, tokenization is handled server-side.
-/// This is a placeholder for future local model support.
+/// Either a real tokenizer loaded from `tokenizer.json`, or a placeholder
+/// that estimates counts for backends whose tokenizer is not available.
+#[derive(Clone)]
 pub struct TokenizerWrapper {
-    /// Placeholder for tokenizer
-    _placeholder: (),
+    /// Loaded tokenizer, `None` for the placeholder
+    inner: Option<Arc<Tokenizer>>,
+    /// Tokenizer name (file stem or directory name)
+    name: String,
 }
 
 impl TokenizerWrapper {
-    /// Load a tokenizer from a file or directory.
-    ///
-    /// Note: For cloud backends, this returns a placeholder.
-    pub fn from_path(_path: impl AsRef<Path>) -> AnyhowResult<Self> {
-        // For cloud backends, tokenization is handled by the server
-        Ok(Self { _placeholder: () })
+    /// Load a tokenizer from a `tokenizer.json` file or a directory containing one.
+    pub fn from_path(path: impl AsRef<Path>) -> AnyhowResult<Self> {
+        let path = path.as_ref();
+        let file = if path.is_dir() {
+            path.join(TOKENIZER_FILE)
+        } else {
+            path.to_path_buf()
+        };
+
+        let tokenizer = Tokenizer::from_file(&file)
+            .map_err(|e| anyhow!("{}", e))
+            .with_context(|| format!("Failed to load tokenizer from {}", file.display()))?;
+
+        let name = if path.is_dir() { path } else { file.as_path() }
+            .file_stem()
+            .map(|s| s.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        Ok(Self {
+            inner: Some(Arc::new(tokenizer)),
+            name,
+        })
     }
 
     /// Create a placeholder tokenizer.
     pub fn placeholder() -> Self {
-        Self { _placeholder: () }
+        Self {
+            inner: None,
+            name: "heuristic".to_string(),
+        }
+    }
+
+    /// Whether this wraps a real tokenizer rather than the placeholder.
+    pub fn is_exact(&self) -> bool {
+        self.inner.is_some()
+    }
+
+    /// Tokenizer name.
+    pub fn name(&self) -> &str {
+        &self.name
     }
 
     /// Encode text to tokens.
     ///
-    /// Note: This returns a placeholder estimate for cloud backends.
-    pub fn encode(&self, text: &str, _add_special_tokens: bool) -> Vec<u32> {
-        // Rough estimate: ~4 characters per token
-        let estimated_tokens = (text.len() / 4).max(1) as u32;
-        (0..estimated_tokens).collect()
+    /// Note: The placeholder returns dummy ids, one per estimated token.
+    pub fn encode(&self, text: &str, add_special_tokens: bool) -> Vec<u32> {
+        match &self.inner {
+            Some(tokenizer) => tokenizer
+                .encode(text, add_special_tokens)
+                .map(|encoding| encoding.get_ids().to_vec())
+                .unwrap_or_default(),
+            None => {
+                // Rough estimate: ~4 characters per token
+                let estimated_tokens = (text.len() / 4).max(1) as u32;
+                (0..estimated_tokens).collect()
+            }
+        }
     }
 
     /// Decode tokens to text.
     ///
-    /// Note: This is a placeholder for cloud backends.
-    pub fn decode(&self, _tokens: &[u32], _skip_special_tokens: bool) -> String {
-        // Placeholder - decoding is handled by the server
-        String::new()
+    /// Note: The placeholder cannot decode and returns an empty string.
+    pub fn decode(&self, tokens: &[u32], skip_special_tokens: bool) -> String {
+        match &self.inner {
+            Some(tokenizer) => tokenizer
+                .decode(tokens, skip_special_tokens)
+                .unwrap_or_default(),
+            None => String::new(),
+        }
+    }
+
+    /// Count the tokens in `text`, without special tokens.
+    pub fn count(&self, text: &str) -> usize {
+        match &self.inner {
+            Some(_) => self.encode(text, false).len(),
+            None => heuristic_count(text),
+        }
     }
 
     /// Get the vocab size.
     ///
-    /// Note: This returns a placeholder value for cloud backends.
+    /// Note: The placeholder returns a typical value.
     pub fn vocab_size(&self) -> usize {
-        // Placeholder for cloud backends
-        32000
-    }
-}
-
-impl Clone for TokenizerWrapper {
-    fn clone(&self) -> Self {
-        Self { _placeholder: () }
+        match &self.inner {
+            Some(tokenizer) => tokenizer.get_vocab_size(true),
+            None => 32000,
+        }
     }
 }
 
@@ -67,10 +134,142 @@ impl Default for TokenizerWrapper {
     }
 }
 
+impl TokenEncoder for TokenizerWrapper {
+    fn count(&self, text: &str) -> usize {
+        TokenizerWrapper::count(self, text)
+    }
+
+    fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Maps backend model names to tokenizers in a local directory.
+///
+/// A model name like `Qwen/Qwen2.5-7B-Instruct` or `qwen2.5:7b` is looked up
+/// as (lowercased) a configured alias, the full name, the name without its
+/// `:tag`, the part after the last `/`, and finally the model family (`qwen`).
+/// Each candidate matches `<dir>/<candidate>/tokenizer.json` or
+/// `<dir>/<candidate>.json`. Results, including misses, are cached.
+pub struct TokenizerRegistry {
+    dir: PathBuf,
+    aliases: HashMap<String, String>,
+    cache: DashMap<String, Option<TokenizerWrapper>>,
+}
+
+impl TokenizerRegistry {
+    /// Create a registry for tokenizers in `dir`.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            aliases: HashMap::new(),
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Registry for the default tokenizers directory.
+    pub fn global() -> &'static TokenizerRegistry {
+        static REGISTRY: OnceLock<TokenizerRegistry> = OnceLock::new();
+        REGISTRY.get_or_init(|| TokenizerRegistry::new(Self::default_dir()))
+    }
+
+    /// Default tokenizers directory: `$NEOMIND_DATA_DIR/tokenizers`, or
+    /// `data/tokenizers`.
+    pub fn default_dir() -> PathBuf {
+        neomind_storage::data_path("tokenizers")
+    }
+
+    /// Use the tokenizer named `tokenizer` for `model`.
+    pub fn with_alias(mut self, model: impl Into<String>, tokenizer: impl Into<String>) -> Self {
+        self.aliases
+            .insert(model.into().to_lowercase(), tokenizer.into());
+        self
+    }
+
+    /// Tokenizers directory.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Get the tokenizer for a model, if one is available.
+    pub fn get(&self, model: &str) -> Option<TokenizerWrapper> {
+        let model = model.to_lowercase();
+        if let Some(cached) = self.cache.get(&model) {
+            return cached.clone();
+        }
+
+        let tokenizer = self.candidates(&model).into_iter().find_map(|name| {
+            let path = self.path_for(&name)?;
+            match TokenizerWrapper::from_path(&path) {
+                Ok(tokenizer) => {
+                    tracing::debug!("Loaded tokenizer {} for model {}", path.display(), model);
+                    Some(tokenizer)
+                }
+                Err(e) => {
+                    tracing::warn!("{:#}", e);
+                    None
+                }
+            }
+        });
+
+        self.cache.insert(model, tokenizer.clone());
+        tokenizer
+    }
+
+    /// Tokenizer names to try for a (lowercased) model, most specific first.
+    fn candidates(&self, model: &str) -> Vec<String> {
+        let untagged = model.split(':').next().unwrap_or(model);
+        let base = untagged.rsplit('/').next().unwrap_or(untagged);
+        let family: String = base
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+
+        let mut candidates = Vec::new();
+        for alias in [model, untagged]
+            .iter()
+            .filter_map(|m| self.aliases.get(*m))
+        {
+            candidates.push(alias.clone());
+        }
+        candidates.extend([model, untagged, base, &family].map(String::from));
+        candidates.retain(|c| !c.is_empty() && !c.contains('/') && !c.contains(".."));
+        candidates.dedup();
+        candidates
+    }
+
+    /// Existing tokenizer file or directory for a tokenizer name.
+    fn path_for(&self, name: &str) -> Option<PathBuf> {
+        let dir = self.dir.join(name);
+        if dir.join(TOKENIZER_FILE).is_file() {
+            return Some(dir);
+        }
+        let file = self.dir.join(format!("{}.json", name));
+        file.is_file().then_some(file)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    /// A word-level tokenizer over a tiny vocabulary.
+    const TINY_TOKENIZER: &str = r#"{
+        "version": "1.0",
+        "truncation": null,
+        "padding": null,
+        "added_tokens": [],
+        "normalizer": {"type": "Lowercase"},
+        "pre_tokenizer": {"type": "Whitespace"},
+        "post_processor": null,
+        "decoder": null,
+        "model": {
+            "type": "WordLevel",
+            "vocab": {"[UNK]": 0, "hello": 1, "world": 2, "temperature": 3, ",": 4, "!": 5},
+            "unk_token": "[UNK]"
+        }
+    }"#;
+
     #[test]
     fn test_placeholder_tokenizer() {
         let tokenizer = TokenizerWrapper::placeholder();
@@ -82,5 +281,40 @@ mod tests {
 
         // Vocab size should be a reasonable placeholder
         assert_eq!(tokenizer.vocab_size(), 32000);
+        assert!(!tokenizer.is_exact());
+    }
+
+    #[test]
+    fn test_real_tokenizer() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tiny.json");
+        std::fs::write(&path, TINY_TOKENIZER).unwrap();
+
+        let tokenizer = TokenizerWrapper::from_path(&path).unwrap();
+        assert!(tokenizer.is_exact());
+        assert_eq!(tokenizer.name(), "tiny");
+        assert_eq!(tokenizer.vocab_size(), 6);
+
+        let tokens = tokenizer.encode("Hello, world!", false);
+        assert_eq!(tokens, vec![1, 4, 2, 5]);
+        assert_eq!(tokenizer.count("hello unknown temperature"), 3);
+        assert_eq!(tokenizer.decode(&tokens, false), "hello , world !");
+
+        assert!(TokenizerWrapper::from_path(dir.path().join("missing.json")).is_err());
+    }
+
+    #[test]
+    fn test_registry_lookup() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("qwen")).unwrap();
+        std::fs::write(dir.path().join("qwen").join(TOKENIZER_FILE), TINY_TOKENIZER).unwrap();
+        std::fs::write(dir.path().join("llama3.1.json"), TINY_TOKENIZER).unwrap();
+
+        let registry = TokenizerRegistry::new(dir.path()).with_alias("my-model", "qwen");
+        assert_eq!(registry.get("qwen2.5:7b").unwrap().name(), "qwen");
+        assert_eq!(registry.get("Qwen/Qwen3-14B").unwrap().name(), "qwen");
+        assert_eq!(registry.get("llama3.1:8b").unwrap().name(), "llama3.1");
+        assert_eq!(registry.get("MY-MODEL:latest").unwrap().name(), "qwen");
+        assert!(registry.get("mistral:7b").is_none());
     }
 }
@@ -0,0 +1,309 @@
+//! LLM usage tracking, pricing and budgets.
+//!
+//! [`UsageTracker`] is the [`UsageRecorder`] installed for the server: it
+//! prices each call from the configured model pricing, persists it to the
+//! usage store, and throttles or blocks calls for agents and users whose
+//! daily or monthly budget is spent.
+
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use dashmap::DashMap;
+use neomind_core::llm::backend::LlmError;
+use neomind_core::llm::usage::{
+    set_usage_recorder, Admission, LlmCall, UsageContext, UsageRecorder,
+};
+use neomind_storage::{
+    BudgetAction, BudgetScope, LlmUsageStore, ModelPricing, UsageBudget, UsageQuery, UsageRecord,
+    UsageReport, UsageTotals,
+};
+
+/// Global singleton for the usage tracker
+static USAGE_TRACKER: Mutex<Option<Arc<UsageTracker>>> = Mutex::new(None);
+
+fn now_secs() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs() as i64)
+        .unwrap_or_default()
+}
+
+fn storage_error(e: neomind_storage::Error) -> LlmError {
+    LlmError::Generation(format!("Usage store error: {}", e))
+}
+
+/// Prices, persists and limits LLM calls.
+pub struct UsageTracker {
+    store: Arc<LlmUsageStore>,
+    pricing: RwLock<Vec<ModelPricing>>,
+    budgets: RwLock<Vec<UsageBudget>>,
+    /// Spend per budget ID for the budget's current period (period start, totals)
+    spend: DashMap<String, (i64, UsageTotals)>,
+}
+
+impl UsageTracker {
+    /// Create a tracker, loading pricing and budgets from the store
+    pub fn new(store: Arc<LlmUsageStore>) -> Result<Self, LlmError> {
+        let tracker = Self {
+            store,
+            pricing: RwLock::new(Vec::new()),
+            budgets: RwLock::new(Vec::new()),
+            spend: DashMap::new(),
+        };
+        tracker.reload()?;
+        Ok(tracker)
+    }
+
+    /// Reload pricing and budgets from the store
+    pub fn reload(&self) -> Result<(), LlmError> {
+        let pricing = self.store.load_pricing().map_err(storage_error)?;
+        let budgets = self.store.load_all_budgets().map_err(storage_error)?;
+        *self.pricing.write().unwrap() = pricing;
+        *self.budgets.write().unwrap() = budgets;
+        self.spend.clear();
+        Ok(())
+    }
+
+    /// The underlying usage store
+    pub fn store(&self) -> &Arc<LlmUsageStore> {
+        &self.store
+    }
+
+    /// Aggregate recorded usage
+    pub fn report(&self, query: &UsageQuery) -> Result<UsageReport, LlmError> {
+        self.store.report(query).map_err(storage_error)
+    }
+
+    /// Configured model pricing
+    pub fn pricing(&self) -> Vec<ModelPricing> {
+        self.pricing.read().unwrap().clone()
+    }
+
+    /// Replace the model pricing
+    pub fn set_pricing(&self, pricing: Vec<ModelPricing>) -> Result<(), LlmError> {
+        self.store
+            .replace_pricing(&pricing)
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))?;
+        *self.pricing.write().unwrap() = pricing;
+        Ok(())
+    }
+
+    /// Configured budgets
+    pub fn budgets(&self) -> Vec<UsageBudget> {
+        self.budgets.read().unwrap().clone()
+    }
+
+    /// Get a budget by ID
+    pub fn get_budget(&self, id: &str) -> Option<UsageBudget> {
+        self.budgets
+            .read()
+            .unwrap()
+            .iter()
+            .find(|b| b.id == id)
+            .cloned()
+    }
+
+    /// Create or replace a budget
+    pub fn save_budget(&self, mut budget: UsageBudget) -> Result<UsageBudget, LlmError> {
+        budget.touch();
+        self.store
+            .save_budget(&budget)
+            .map_err(|e| LlmError::InvalidInput(e.to_string()))?;
+
+        let mut budgets = self.budgets.write().unwrap();
+        budgets.retain(|b| b.id != budget.id);
+        budgets.push(budget.clone());
+        self.spend.remove(&budget.id);
+        Ok(budget)
+    }
+
+    /// Delete a budget
+    pub fn delete_budget(&self, id: &str) -> Result<bool, LlmError> {
+        let existed = self.store.delete_budget(id).map_err(storage_error)?;
+        self.budgets.write().unwrap().retain(|b| b.id != id);
+        self.spend.remove(id);
+        Ok(existed)
+    }
+
+    /// Usage counted against a budget in its current period
+    pub fn budget_spend(&self, budget: &UsageBudget) -> UsageTotals {
+        let period_start = budget.period.start(now_secs());
+        if let Some(entry) = self.spend.get(&budget.id) {
+            if entry.0 == period_start {
+                return entry.1.clone();
+            }
+        }
+
+        let totals = self
+            .store
+            .totals_since(budget.scope, &budget.target, period_start)
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to load spend for budget {}: {}", budget.id, e);
+                UsageTotals::default()
+            });
+        self.spend
+            .insert(budget.id.clone(), (period_start, totals.clone()));
+        totals
+    }
+
+    fn applicable_budgets(&self, context: &UsageContext) -> Vec<UsageBudget> {
+        self.budgets
+            .read()
+            .unwrap()
+            .iter()
+            .filter(|b| b.applies_to(context.agent_id.as_deref(), context.user_id.as_deref()))
+            .cloned()
+            .collect()
+    }
+}
+
+impl UsageRecorder for UsageTracker {
+    fn admit(&self, _backend: &str, _model: &str, context: &UsageContext) -> Admission {
+        let mut admission = Admission::Allow;
+        for budget in self.applicable_budgets(context) {
+            if !budget.is_exceeded(&self.budget_spend(&budget)) {
+                continue;
+            }
+
+            let scope = match budget.scope {
+                BudgetScope::Agent => "agent",
+                BudgetScope::User => "user",
+            };
+            match budget.action {
+                BudgetAction::Block => {
+                    return Admission::Block(format!(
+                        "{:?} budget '{}' for {} '{}' is spent",
+                        budget.period, budget.id, scope, budget.target
+                    ));
+                }
+                BudgetAction::Throttle => {
+                    let delay = Duration::from_secs(budget.throttle_delay_secs);
+                    admission = match admission {
+                        Admission::Delay(current) if current >= delay => admission,
+                        _ => Admission::Delay(delay),
+                    };
+                }
+            }
+        }
+        admission
+    }
+
+    fn record(&self, call: LlmCall) {
+        let cost = ModelPricing::find(&self.pricing.read().unwrap(), &call.model)
+            .map(|p| p.cost(call.prompt_tokens, call.completion_tokens))
+            .unwrap_or(0.0);
+
+        let mut record = UsageRecord::new(call.backend, call.model);
+        record.prompt_tokens = call.prompt_tokens;
+        record.completion_tokens = call.completion_tokens;
+        record.latency_ms = call.latency_ms;
+        record.success = call.success;
+        record.cost = cost;
+        record.session_id = call.context.session_id;
+        record.agent_id = call.context.agent_id;
+        record.user_id = call.context.user_id;
+        record.extension_id = call.context.extension_id;
+
+        if let Err(e) = self.store.record(&record) {
+            tracing::warn!("Failed to record LLM usage: {}", e);
+            return;
+        }
+
+        // Keep cached spend current; uncached budgets load it from the store
+        for budget in self.budgets.read().unwrap().iter() {
+            if !budget.applies_to(record.agent_id.as_deref(), record.user_id.as_deref()) {
+                continue;
+            }
+            if let Some(mut entry) = self.spend.get_mut(&budget.id) {
+                if entry.0 <= record.timestamp {
+                    entry.1.add(&record);
+                }
+            }
+        }
+    }
+}
+
+/// Get or create the global usage tracker and install it as the usage recorder
+pub fn get_usage_tracker() -> Result<Arc<UsageTracker>, LlmError> {
+    let mut guard = USAGE_TRACKER
+        .lock()
+        .map_err(|_| LlmError::InvalidInput("Failed to acquire usage tracker lock".to_string()))?;
+    if let Some(ref tracker) = *guard {
+        return Ok(tracker.clone());
+    }
+
+    let store = LlmUsageStore::open(neomind_storage::data_path("llm_usage.redb"))
+        .map_err(|e| LlmError::InvalidInput(format!("Failed to open usage store: {}", e)))?;
+    let tracker = Arc::new(UsageTracker::new(store)?);
+    set_usage_recorder(Some(tracker.clone()));
+    *guard = Some(tracker.clone());
+    Ok(tracker)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neomind_storage::BudgetPeriod;
+
+    fn call(agent: &str, prompt_tokens: u32, completion_tokens: u32) -> LlmCall {
+        LlmCall {
+            backend: "openai".to_string(),
+            model: "gpt-4o-2024-08-06".to_string(),
+            context: UsageContext::for_agent(agent),
+            prompt_tokens,
+            completion_tokens,
+            latency_ms: 250,
+            success: true,
+        }
+    }
+
+    #[test]
+    fn test_usage_tracker_budgets() {
+        let tracker = UsageTracker::new(LlmUsageStore::memory().unwrap()).unwrap();
+        tracker
+            .set_pricing(vec![ModelPricing::new("gpt-4o*", 2.0, 8.0)])
+            .unwrap();
+
+        let mut budget = UsageBudget::new("b1", BudgetScope::Agent, "agent-1", BudgetPeriod::Daily);
+        budget.max_cost = Some(0.01);
+        tracker.save_budget(budget.clone()).unwrap();
+
+        let mut throttle =
+            UsageBudget::new("b2", BudgetScope::Agent, "agent-2", BudgetPeriod::Monthly);
+        throttle.max_tokens = Some(100);
+        throttle.action = BudgetAction::Throttle;
+        throttle.throttle_delay_secs = 5;
+        tracker.save_budget(throttle).unwrap();
+
+        let context = UsageContext::for_agent("agent-1");
+        assert_eq!(
+            tracker.admit("openai", "gpt-4o", &context),
+            Admission::Allow
+        );
+
+        // 1000 * 2 / 1M + 1000 * 8 / 1M = 0.01
+        tracker.record(call("agent-1", 1_000, 1_000));
+        assert!((tracker.budget_spend(&budget).cost - 0.01).abs() < 1e-9);
+        assert!(matches!(
+            tracker.admit("openai", "gpt-4o", &context),
+            Admission::Block(_)
+        ));
+
+        tracker.record(call("agent-2", 80, 40));
+        assert_eq!(
+            tracker.admit("openai", "gpt-4o", &UsageContext::for_agent("agent-2")),
+            Admission::Delay(Duration::from_secs(5))
+        );
+
+        // Raising the limit lets calls through again
+        budget.max_cost = Some(1.0);
+        tracker.save_budget(budget).unwrap();
+        assert_eq!(
+            tracker.admit("openai", "gpt-4o", &context),
+            Admission::Allow
+        );
+
+        let report = tracker.report(&UsageQuery::default()).unwrap();
+        assert_eq!(report.totals.calls, 2);
+    }
+}
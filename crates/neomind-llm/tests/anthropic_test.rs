@@ -0,0 +1,323 @@
+//! Anthropic backend against a local fake Messages API server.
+
+#![cfg(feature = "cloud")]
+
+use std::sync::{Arc, Mutex};
+
+use axum::extract::State;
+use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::post;
+use axum::{Json, Router};
+use futures::StreamExt;
+use serde_json::{json, Value};
+
+use neomind_core::llm::backend::{
+    FinishReason, GenerationParams, LlmInput, LlmRuntime, ToolDefinition,
+};
+use neomind_core::message::{Content, Message, MessageRole};
+use neomind_llm::{AnthropicConfig, AnthropicRuntime, AnthropicUsage};
+
+/// Requests received by the fake server, with their headers.
+#[derive(Clone, Default)]
+struct Recorded {
+    requests: Arc<Mutex<Vec<(HeaderMap, Value)>>>,
+    response: Arc<Mutex<Option<(StatusCode, String)>>>,
+}
+
+impl Recorded {
+    fn last(&self) -> (HeaderMap, Value) {
+        self.requests.lock().unwrap().last().cloned().unwrap()
+    }
+}
+
+async fn messages(
+    State(recorded): State<Recorded>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Response {
+    recorded
+        .requests
+        .lock()
+        .unwrap()
+        .push((headers, body.clone()));
+    let (status, response) = recorded.response.lock().unwrap().clone().unwrap();
+    let content_type = if body["stream"] == true {
+        "text/event-stream"
+    } else {
+        "application/json"
+    };
+    (status, [("content-type", content_type)], response).into_response()
+}
+
+/// Start a fake server that answers every request with `status` and `body`.
+async fn fake_server(status: StatusCode, body: String) -> (AnthropicRuntime, Recorded) {
+    let recorded = Recorded::default();
+    *recorded.response.lock().unwrap() = Some((status, body));
+
+    let app = Router::new()
+        .route("/v1/messages", post(messages))
+        .with_state(recorded.clone());
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+    let config = AnthropicConfig::new("sk-ant-test")
+        .with_model("claude-sonnet-4-20250514")
+        .with_base_url_opt(Some(format!("http://{}/v1", addr)));
+    (AnthropicRuntime::new(config).unwrap(), recorded)
+}
+
+fn sse(events: &[Value]) -> String {
+    events
+        .iter()
+        .map(|event| {
+            format!(
+                "event: {}\ndata: {}\n\n",
+                event["type"].as_str().unwrap(),
+                event
+            )
+        })
+        .collect()
+}
+
+fn input(messages: Vec<Message>) -> LlmInput {
+    LlmInput {
+        messages,
+        params: GenerationParams::default(),
+        model: None,
+        stream: false,
+        tools: None,
+    }
+}
+
+fn device_tool() -> ToolDefinition {
+    ToolDefinition {
+        name: "get_device".to_string(),
+        description: "Read a device".to_string(),
+        parameters: json!({"type": "object", "properties": {"id": {"type": "string"}}}),
+    }
+}
+
+#[tokio::test]
+async fn test_generate_with_tool_use() {
+    let response = json!({
+        "id": "msg_1",
+        "type": "message",
+        "role": "assistant",
+        "content": [
+            {"type": "text", "text": "Let me check."},
+            {"type": "tool_use", "id": "toolu_1", "name": "get_device", "input": {"id": "t1"}}
+        ],
+        "stop_reason": "tool_use",
+        "usage": {
+            "input_tokens": 12,
+            "output_tokens": 30,
+            "cache_creation_input_tokens": 0,
+            "cache_read_input_tokens": 2048
+        }
+    });
+    let (runtime, recorded) = fake_server(StatusCode::OK, response.to_string()).await;
+
+    let mut input = input(vec![
+        Message::system("You are NeoMind, an edge AI assistant."),
+        Message::user("How hot is sensor t1?"),
+    ]);
+    input.tools = Some(vec![device_tool()]);
+    let output = runtime.generate(input).await.unwrap();
+
+    let (text, tool_calls) = output.text.split_once('[').unwrap();
+    assert_eq!(text, "Let me check.");
+    let tool_calls: Value = serde_json::from_str(&format!("[{}", tool_calls)).unwrap();
+    assert_eq!(tool_calls[0]["id"], "toolu_1");
+    assert_eq!(tool_calls[0]["name"], "get_device");
+    assert_eq!(tool_calls[0]["arguments"]["id"], "t1");
+    assert_eq!(output.finish_reason, FinishReason::Stop);
+
+    let usage = output.usage.unwrap();
+    assert_eq!(usage.prompt_tokens, 2060);
+    assert_eq!(usage.completion_tokens, 30);
+    assert_eq!(runtime.last_usage().unwrap().cache_read_input_tokens, 2048);
+
+    let (headers, request) = recorded.last();
+    assert_eq!(headers["x-api-key"], "sk-ant-test");
+    assert_eq!(headers["anthropic-version"], "2023-06-01");
+    assert_eq!(request["model"], "claude-sonnet-4-20250514");
+    assert_eq!(request["max_tokens"], 8192);
+    assert_eq!(
+        request["system"][0]["text"],
+        "You are NeoMind, an edge AI assistant."
+    );
+    assert_eq!(request["system"][0]["cache_control"]["type"], "ephemeral");
+    assert_eq!(request["tools"][0]["input_schema"]["type"], "object");
+    assert_eq!(request["tools"][0]["cache_control"]["type"], "ephemeral");
+    assert_eq!(request["messages"].as_array().unwrap().len(), 1);
+    assert_eq!(
+        request["messages"][0]["content"][0]["text"],
+        "How hot is sensor t1?"
+    );
+}
+
+#[tokio::test]
+async fn test_tool_results_sent_as_blocks() {
+    let response = json!({
+        "content": [{"type": "text", "text": "Sensor t1 reads 22.5 °C."}],
+        "stop_reason": "end_turn",
+        "usage": {"input_tokens": 40, "output_tokens": 9}
+    });
+    let (runtime, recorded) = fake_server(StatusCode::OK, response.to_string()).await;
+
+    let output = runtime
+        .generate(input(vec![
+            Message::user("How hot is sensor t1?"),
+            Message::assistant(
+                r#"Let me check.[{"id":"toolu_1","name":"get_device","arguments":{"id":"t1"}}]"#,
+            ),
+            Message::new(MessageRole::Tool, Content::text("22.5")),
+        ]))
+        .await
+        .unwrap();
+    assert_eq!(output.text, "Sensor t1 reads 22.5 °C.");
+    assert!(output.thinking.is_none());
+
+    let (_, request) = recorded.last();
+    let messages = &request["messages"];
+    assert_eq!(messages[1]["role"], "assistant");
+    assert_eq!(messages[1]["content"][0]["text"], "Let me check.");
+    assert_eq!(messages[1]["content"][1]["type"], "tool_use");
+    assert_eq!(messages[1]["content"][1]["id"], "toolu_1");
+    assert_eq!(messages[2]["role"], "user");
+    assert_eq!(messages[2]["content"][0]["type"], "tool_result");
+    assert_eq!(messages[2]["content"][0]["tool_use_id"], "toolu_1");
+    assert_eq!(messages[2]["content"][0]["content"], "22.5");
+}
+
+#[tokio::test]
+async fn test_stream_thinking_text_and_tool_use() {
+    let events = sse(&[
+        json!({"type": "message_start", "message": {"usage": {"input_tokens": 25, "output_tokens": 1, "cache_creation_input_tokens": 1500}}}),
+        json!({"type": "content_block_start", "index": 0, "content_block": {"type": "thinking", "thinking": ""}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "The user wants "}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "thinking_delta", "thinking": "a reading."}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "signature_delta", "signature": "abc"}}),
+        json!({"type": "content_block_stop", "index": 0}),
+        json!({"type": "ping"}),
+        json!({"type": "content_block_start", "index": 1, "content_block": {"type": "text", "text": ""}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "Checking "}}),
+        json!({"type": "content_block_delta", "index": 1, "delta": {"type": "text_delta", "text": "t1."}}),
+        json!({"type": "content_block_stop", "index": 1}),
+        json!({"type": "content_block_start", "index": 2, "content_block": {"type": "tool_use", "id": "toolu_2", "name": "get_device", "input": {}}}),
+        json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "{\"id\": "}}),
+        json!({"type": "content_block_delta", "index": 2, "delta": {"type": "input_json_delta", "partial_json": "\"t1\"}"}}),
+        json!({"type": "content_block_stop", "index": 2}),
+        json!({"type": "message_delta", "delta": {"stop_reason": "tool_use"}, "usage": {"output_tokens": 57}}),
+        json!({"type": "message_stop"}),
+    ]);
+    let (runtime, recorded) = fake_server(StatusCode::OK, events).await;
+
+    let mut input = input(vec![Message::user("How hot is sensor t1?")]);
+    input.params.thinking_enabled = Some(true);
+    input.tools = Some(vec![device_tool()]);
+    let chunks: Vec<(String, bool)> = runtime
+        .generate_stream(input)
+        .await
+        .unwrap()
+        .map(|chunk| chunk.unwrap())
+        .collect()
+        .await;
+
+    let thinking: String = chunks
+        .iter()
+        .filter(|c| c.1)
+        .map(|c| c.0.as_str())
+        .collect();
+    assert_eq!(thinking, "The user wants a reading.");
+    let content: Vec<&str> = chunks
+        .iter()
+        .filter(|c| !c.1)
+        .map(|c| c.0.as_str())
+        .collect();
+    assert_eq!(content[..2], ["Checking ", "t1."]);
+    let tool_calls: Value = serde_json::from_str(content[2]).unwrap();
+    assert_eq!(tool_calls[0]["id"], "toolu_2");
+    assert_eq!(tool_calls[0]["arguments"]["id"], "t1");
+
+    assert_eq!(
+        runtime.last_usage(),
+        Some(AnthropicUsage {
+            input_tokens: 25,
+            output_tokens: 57,
+            cache_creation_input_tokens: 1500,
+            cache_read_input_tokens: 0,
+        })
+    );
+
+    let (_, request) = recorded.last();
+    assert_eq!(request["stream"], true);
+    assert_eq!(request["thinking"]["type"], "enabled");
+    assert_eq!(request["thinking"]["budget_tokens"], 4096);
+    // Sampling parameters are not allowed with thinking
+    assert!(request.get("temperature").is_none());
+    assert!(request.get("top_p").is_none());
+}
+
+#[tokio::test]
+async fn test_api_errors() {
+    let error = json!({
+        "type": "error",
+        "error": {"type": "overloaded_error", "message": "Overloaded"}
+    });
+    let (runtime, _) = fake_server(StatusCode::from_u16(529).unwrap(), error.to_string()).await;
+
+    let err = runtime
+        .generate(input(vec![Message::user("Hi")]))
+        .await
+        .unwrap_err();
+    assert!(err.to_string().contains("529"));
+    assert!(err.to_string().contains("overloaded_error: Overloaded"));
+    assert_eq!(runtime.metrics().failed_requests, 1);
+
+    let mut stream = runtime
+        .generate_stream(input(vec![Message::user("Hi")]))
+        .await
+        .unwrap();
+    assert!(stream.next().await.unwrap().is_err());
+    assert!(stream.next().await.is_none());
+}
+
+#[tokio::test]
+async fn test_stream_failures_are_reported() {
+    // Cut off before message_stop
+    let events = sse(&[
+        json!({"type": "message_start", "message": {"usage": {"input_tokens": 5, "output_tokens": 1}}}),
+        json!({"type": "content_block_delta", "index": 0, "delta": {"type": "text_delta", "text": "Partial"}}),
+    ]);
+    let (runtime, _) = fake_server(StatusCode::OK, events).await;
+    let chunks: Vec<_> = runtime
+        .generate_stream(input(vec![Message::user("Hi")]))
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert_eq!(chunks.len(), 2);
+    assert_eq!(chunks[0].as_ref().unwrap().0, "Partial");
+    assert!(chunks[1].is_err());
+    assert_eq!(runtime.metrics().failed_requests, 1);
+    assert_eq!(runtime.metrics().successful_requests, 0);
+
+    // An error event mid-stream
+    let events = sse(&[
+        json!({"type": "message_start", "message": {"usage": {"input_tokens": 5, "output_tokens": 1}}}),
+        json!({"type": "error", "error": {"type": "overloaded_error", "message": "Overloaded"}}),
+    ]);
+    let (runtime, _) = fake_server(StatusCode::OK, events).await;
+    let chunks: Vec<_> = runtime
+        .generate_stream(input(vec![Message::user("Hi")]))
+        .await
+        .unwrap()
+        .collect()
+        .await;
+    assert_eq!(chunks.len(), 1);
+    assert!(chunks[0].is_err());
+    assert_eq!(runtime.metrics().failed_requests, 1);
+}
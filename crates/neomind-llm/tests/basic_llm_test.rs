@@ -45,6 +45,7 @@ async fn test_basic_chat() {
             top_k: None,
             max_tokens: Some(50),
             max_context: None,
+            response_format: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
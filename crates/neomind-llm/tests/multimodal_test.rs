@@ -86,6 +86,7 @@ async fn test_multimodal_basic_image_description() {
             top_k: None,
             max_tokens: Some(100),
             max_context: None,
+            response_format: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
@@ -158,6 +159,7 @@ async fn test_multimodal_image_in_history() {
             top_k: None,
             max_tokens: Some(100),
             max_context: None,
+            response_format: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
@@ -218,6 +220,7 @@ async fn test_multimodal_streaming() {
             top_k: None,
             max_tokens: Some(200),
             max_context: None,
+            response_format: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
@@ -325,6 +328,7 @@ async fn test_multimodal_with_tools() {
             top_k: None,
             max_tokens: Some(200),
             max_context: None,
+            response_format: None,
             stop: None,
             frequency_penalty: None,
             presence_penalty: None,
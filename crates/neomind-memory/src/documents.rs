@@ -0,0 +1,961 @@
+//! Document ingestion for the long-term knowledge base.
+//!
+//! Device manuals and SOPs (Markdown, HTML, plain text and PDF) are converted
+//! to text, split into chunks along headings with some overlap, embedded and
+//! stored as [`KnowledgeEntry`] values in [`LongTermMemory`]. Every chunk keeps
+//! its source document, page and heading in its metadata so answers can cite
+//! them (see [`KnowledgeEntry::citation`]).
+//!
+//! Ingesting a document again under the same ID replaces its chunks.
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use std::sync::Arc;
+//! use neomind_memory::{DocumentFormat, DocumentIngestor, DocumentInput, LongTermMemory, TieredMemory};
+//!
+//! # async fn example() -> neomind_memory::Result<()> {
+//! let memory = TieredMemory::new();
+//! let ingestor = DocumentIngestor::new(memory.long_term_ref().clone(), memory.embedding_model());
+//!
+//! let input = DocumentInput::new("pump.md", DocumentFormat::Markdown, b"# Pump\nPrime before use.".to_vec())
+//!     .with_device_types(vec!["pump".to_string()]);
+//! let report = ingestor.ingest(input).await?;
+//! println!("{} chunks", report.chunks);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+
+use super::embeddings::EmbeddingModel;
+use super::error::{MemoryError, Result};
+use super::long_term::{KnowledgeCategory, KnowledgeEntry, LongTermMemory};
+
+/// Default maximum chunk length in characters.
+pub const DEFAULT_MAX_CHUNK_CHARS: usize = 1200;
+
+/// Default overlap between consecutive chunks of a section, in characters.
+pub const DEFAULT_CHUNK_OVERLAP: usize = 200;
+
+/// Supported document formats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DocumentFormat {
+    /// Markdown
+    Markdown,
+    /// HTML
+    Html,
+    /// Plain text
+    PlainText,
+    /// PDF
+    Pdf,
+}
+
+impl DocumentFormat {
+    /// Convert to string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Markdown => "markdown",
+            Self::Html => "html",
+            Self::PlainText => "plain_text",
+            Self::Pdf => "pdf",
+        }
+    }
+
+    /// Detect the format from a file name extension.
+    pub fn from_filename(name: &str) -> Option<Self> {
+        let extension = name.rsplit_once('.')?.1.to_ascii_lowercase();
+        match extension.as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            "txt" | "text" => Some(Self::PlainText),
+            "pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+
+    /// Detect the format from a MIME content type.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        let mime = content_type.split(';').next()?.trim().to_ascii_lowercase();
+        match mime.as_str() {
+            "text/markdown" | "text/x-markdown" => Some(Self::Markdown),
+            "text/html" | "application/xhtml+xml" => Some(Self::Html),
+            "text/plain" => Some(Self::PlainText),
+            "application/pdf" => Some(Self::Pdf),
+            _ => None,
+        }
+    }
+}
+
+/// Chunking settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChunkingConfig {
+    /// Maximum chunk length in characters
+    pub max_chunk_chars: usize,
+    /// Characters repeated from the end of the previous chunk of a section
+    pub overlap_chars: usize,
+}
+
+impl Default for ChunkingConfig {
+    fn default() -> Self {
+        Self {
+            max_chunk_chars: DEFAULT_MAX_CHUNK_CHARS,
+            overlap_chars: DEFAULT_CHUNK_OVERLAP,
+        }
+    }
+}
+
+/// A document to ingest.
+#[derive(Debug, Clone)]
+pub struct DocumentInput {
+    /// Source name shown in citations, usually the file name
+    pub source: String,
+    /// Document ID; derived from `source` when not set
+    pub document_id: Option<String>,
+    /// Document format
+    pub format: DocumentFormat,
+    /// Raw document bytes
+    pub data: Vec<u8>,
+    /// Knowledge category of the chunks
+    pub category: KnowledgeCategory,
+    /// Device types the document applies to (stored as tags)
+    pub device_types: Vec<String>,
+    /// Additional tags
+    pub tags: Vec<String>,
+}
+
+impl DocumentInput {
+    /// Create a new document input.
+    pub fn new(source: impl Into<String>, format: DocumentFormat, data: Vec<u8>) -> Self {
+        Self {
+            source: source.into(),
+            document_id: None,
+            format,
+            data,
+            category: KnowledgeCategory::DeviceManual,
+            device_types: Vec::new(),
+            tags: Vec::new(),
+        }
+    }
+
+    /// Set the document ID.
+    pub fn with_document_id(mut self, document_id: impl Into<String>) -> Self {
+        self.document_id = Some(document_id.into());
+        self
+    }
+
+    /// Set the knowledge category.
+    pub fn with_category(mut self, category: KnowledgeCategory) -> Self {
+        self.category = category;
+        self
+    }
+
+    /// Set the device types.
+    pub fn with_device_types(mut self, device_types: Vec<String>) -> Self {
+        self.device_types = device_types;
+        self
+    }
+
+    /// Set additional tags.
+    pub fn with_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+}
+
+/// A chunk of an ingested document.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DocumentChunk {
+    /// Position of the chunk in the document
+    pub index: usize,
+    /// Heading path, e.g. `Installation > Wiring`
+    pub heading: Option<String>,
+    /// Page number (PDF only, 1-based)
+    pub page: Option<u32>,
+    /// Chunk text
+    pub text: String,
+}
+
+/// Result of ingesting a document.
+#[derive(Debug, Clone, Serialize)]
+pub struct IngestReport {
+    /// Document ID
+    pub document_id: String,
+    /// Source name
+    pub source: String,
+    /// Number of chunks stored
+    pub chunks: usize,
+    /// Number of pages (PDF only)
+    pub pages: Option<u32>,
+    /// Number of chunks of a previous version that were replaced
+    pub replaced: usize,
+}
+
+/// An ingested document.
+#[derive(Debug, Clone, Serialize)]
+pub struct DocumentSummary {
+    /// Document ID
+    pub document_id: String,
+    /// Source name
+    pub source: String,
+    /// Document format
+    pub format: Option<String>,
+    /// Knowledge category
+    pub category: String,
+    /// Number of chunks
+    pub chunks: usize,
+    /// Highest page number (PDF only)
+    pub pages: Option<u32>,
+    /// Device types
+    pub device_types: Vec<String>,
+    /// Ingestion time
+    pub ingested_at: i64,
+}
+
+/// Text of a document section before chunking.
+struct Section {
+    heading: Option<String>,
+    page: Option<u32>,
+    text: String,
+}
+
+/// Ingests documents into long-term memory.
+pub struct DocumentIngestor {
+    long_term: Arc<LongTermMemory>,
+    embedding: Arc<dyn EmbeddingModel>,
+    config: ChunkingConfig,
+}
+
+impl DocumentIngestor {
+    /// Create a new ingestor.
+    pub fn new(long_term: Arc<LongTermMemory>, embedding: Arc<dyn EmbeddingModel>) -> Self {
+        Self {
+            long_term,
+            embedding,
+            config: ChunkingConfig::default(),
+        }
+    }
+
+    /// Set the chunking configuration.
+    pub fn with_chunking(mut self, config: ChunkingConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Derive a document ID from a source name.
+    pub fn document_id_for(source: &str) -> String {
+        let mut id = String::with_capacity(source.len());
+        for c in source.chars() {
+            if c.is_alphanumeric() {
+                id.extend(c.to_lowercase());
+            } else if !id.ends_with('-') {
+                id.push('-');
+            }
+        }
+        id.trim_matches('-').to_string()
+    }
+
+    /// Extract, chunk, embed and store a document, replacing any previous
+    /// version with the same ID.
+    pub async fn ingest(&self, input: DocumentInput) -> Result<IngestReport> {
+        let document_id = match &input.document_id {
+            Some(id) if !id.trim().is_empty() => id.trim().to_string(),
+            _ => Self::document_id_for(&input.source),
+        };
+        if document_id.is_empty() {
+            return Err(MemoryError::InvalidFormat(
+                "Document needs a source name or ID".to_string(),
+            ));
+        }
+
+        let format = input.format;
+        let config = self.config.clone();
+        let data = input.data;
+        let chunks = tokio::task::spawn_blocking(move || chunk_document(format, &data, &config))
+            .await
+            .map_err(|e| MemoryError::Other(format!("Extraction task failed: {}", e)))??;
+        if chunks.is_empty() {
+            return Err(MemoryError::InvalidFormat(format!(
+                "No text found in {}",
+                input.source
+            )));
+        }
+
+        // Embed everything before touching the previous version
+        let texts: Vec<String> = chunks
+            .iter()
+            .map(|chunk| match &chunk.heading {
+                Some(heading) => format!("{}\n\n{}", heading, chunk.text),
+                None => chunk.text.clone(),
+            })
+            .collect();
+        let embeddings = self.embedding.embed_batch(&texts).await?;
+        if embeddings.len() != chunks.len() {
+            return Err(MemoryError::Embedding(format!(
+                "Expected {} embeddings, got {}",
+                chunks.len(),
+                embeddings.len()
+            )));
+        }
+
+        let previous = self.chunk_ids(&document_id).await;
+        let available = self.long_term.max_knowledge() + previous.len();
+        if self.long_term.len().await + chunks.len() > available {
+            return Err(MemoryError::CapacityExceeded(format!(
+                "{} chunks do not fit in long-term memory (limit {})",
+                chunks.len(),
+                self.long_term.max_knowledge()
+            )));
+        }
+
+        let tags: Vec<String> = input
+            .device_types
+            .iter()
+            .chain(&input.tags)
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect();
+
+        let chunk_count = chunks.len();
+        let pages = chunks.iter().filter_map(|c| c.page).max();
+        // Chunk IDs carry a revision so the new version can be stored next
+        // to the previous one before that is removed
+        let revision = uuid::Uuid::new_v4().simple().to_string();
+        let mut entries = Vec::with_capacity(chunk_count);
+        for (chunk, embedding) in chunks.into_iter().zip(embeddings) {
+            let title = match (&chunk.heading, chunk.page) {
+                (Some(heading), _) => format!("{} - {}", input.source, heading),
+                (None, Some(page)) => format!("{} (p. {})", input.source, page),
+                (None, None) => input.source.clone(),
+            };
+            let mut entry = KnowledgeEntry::new(title, chunk.text, input.category.clone())
+                .with_tags(tags.clone())
+                .with_metadata(serde_json::json!({
+                    "document_id": document_id,
+                    "source": input.source,
+                    "format": format.as_str(),
+                    "page": chunk.page,
+                    "heading": chunk.heading,
+                    "chunk_index": chunk.index,
+                    "chunk_count": chunk_count,
+                    "device_types": input.device_types,
+                }))
+                .with_embedding(embedding);
+            entry.id = format!("doc:{}:{}:{}", document_id, &revision[..8], chunk.index);
+            entries.push(entry);
+        }
+        self.long_term.replace(&previous, entries).await?;
+
+        tracing::info!(
+            category = "memory",
+            document_id = %document_id,
+            source = %input.source,
+            chunks = chunk_count,
+            replaced = previous.len(),
+            "Document ingested"
+        );
+
+        Ok(IngestReport {
+            document_id,
+            source: input.source,
+            chunks: chunk_count,
+            pages,
+            replaced: previous.len(),
+        })
+    }
+
+    /// Remove all chunks of a document. Returns the number removed.
+    pub async fn remove(&self, document_id: &str) -> Result<usize> {
+        let ids = self.chunk_ids(document_id).await;
+        for id in &ids {
+            self.long_term.delete(id).await?;
+        }
+        Ok(ids.len())
+    }
+
+    /// List ingested documents.
+    pub async fn list(&self) -> Vec<DocumentSummary> {
+        let mut documents: BTreeMap<String, DocumentSummary> = BTreeMap::new();
+        for entry in self.long_term.get_all().await {
+            let Some(metadata) = &entry.metadata else {
+                continue;
+            };
+            let Some(document_id) = metadata.get("document_id").and_then(|v| v.as_str()) else {
+                continue;
+            };
+            let page = metadata
+                .get("page")
+                .and_then(|v| v.as_u64())
+                .map(|p| p as u32);
+
+            let summary =
+                documents
+                    .entry(document_id.to_string())
+                    .or_insert_with(|| DocumentSummary {
+                        document_id: document_id.to_string(),
+                        source: metadata
+                            .get("source")
+                            .and_then(|v| v.as_str())
+                            .unwrap_or(document_id)
+                            .to_string(),
+                        format: metadata
+                            .get("format")
+                            .and_then(|v| v.as_str())
+                            .map(String::from),
+                        category: entry.category.as_str().to_string(),
+                        chunks: 0,
+                        pages: None,
+                        device_types: metadata
+                            .get("device_types")
+                            .and_then(|v| serde_json::from_value(v.clone()).ok())
+                            .unwrap_or_default(),
+                        ingested_at: entry.created_at,
+                    });
+            summary.chunks += 1;
+            summary.pages = summary.pages.max(page);
+            summary.ingested_at = summary.ingested_at.max(entry.created_at);
+        }
+        documents.into_values().collect()
+    }
+
+    async fn chunk_ids(&self, document_id: &str) -> Vec<String> {
+        self.long_term
+            .get_all()
+            .await
+            .into_iter()
+            .filter(|entry| {
+                entry
+                    .metadata
+                    .as_ref()
+                    .and_then(|m| m.get("document_id"))
+                    .and_then(|v| v.as_str())
+                    == Some(document_id)
+            })
+            .map(|entry| entry.id)
+            .collect()
+    }
+}
+
+/// Extract the text of a document and split it into chunks.
+pub fn chunk_document(
+    format: DocumentFormat,
+    data: &[u8],
+    config: &ChunkingConfig,
+) -> Result<Vec<DocumentChunk>> {
+    let sections = match format {
+        DocumentFormat::Markdown => markdown_sections(&decode_text(data), None),
+        DocumentFormat::Html => markdown_sections(&html_to_markdown(&decode_text(data)), None),
+        DocumentFormat::PlainText => vec![Section {
+            heading: None,
+            page: None,
+            text: decode_text(data),
+        }],
+        DocumentFormat::Pdf => pdf_sections(data)?,
+    };
+
+    let max_chars = config.max_chunk_chars.max(100);
+    let overlap = config.overlap_chars.min(max_chars / 2);
+    let mut chunks = Vec::new();
+    for section in sections {
+        for text in split_text(&section.text, max_chars, overlap) {
+            chunks.push(DocumentChunk {
+                index: chunks.len(),
+                heading: section.heading.clone(),
+                page: section.page,
+                text,
+            });
+        }
+    }
+    Ok(chunks)
+}
+
+fn decode_text(data: &[u8]) -> String {
+    let text = String::from_utf8_lossy(data);
+    text.strip_prefix('\u{feff}')
+        .unwrap_or(&text)
+        .replace("\r\n", "\n")
+}
+
+/// Split Markdown into sections at headings. Each section carries the path
+/// of headings above it.
+fn markdown_sections(text: &str, page: Option<u32>) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let mut headings: Vec<(usize, String)> = Vec::new();
+    let mut current = String::new();
+    let mut in_code_block = false;
+
+    let heading_path = |headings: &[(usize, String)]| {
+        (!headings.is_empty()).then(|| {
+            headings
+                .iter()
+                .map(|(_, title)| title.as_str())
+                .collect::<Vec<_>>()
+                .join(" > ")
+        })
+    };
+
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_code_block = !in_code_block;
+        }
+        let heading = if in_code_block {
+            None
+        } else {
+            parse_heading(line)
+        };
+
+        match heading {
+            Some((level, title)) => {
+                sections.push(Section {
+                    heading: heading_path(&headings),
+                    page,
+                    text: std::mem::take(&mut current),
+                });
+                headings.retain(|(l, _)| *l < level);
+                headings.push((level, title));
+            }
+            None => {
+                current.push_str(line);
+                current.push('\n');
+            }
+        }
+    }
+    sections.push(Section {
+        heading: heading_path(&headings),
+        page,
+        text: current,
+    });
+
+    sections.retain(|s| !s.text.trim().is_empty());
+    sections
+}
+
+fn parse_heading(line: &str) -> Option<(usize, String)> {
+    let trimmed = line.trim_start();
+    let level = trimmed.chars().take_while(|c| *c == '#').count();
+    if !(1..=6).contains(&level) {
+        return None;
+    }
+    let rest = &trimmed[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    let title = rest.trim().trim_end_matches('#').trim();
+    (!title.is_empty()).then(|| (level, title.to_string()))
+}
+
+/// Split text into chunks of at most `max_chars`, breaking at paragraph,
+/// line, sentence or word boundaries. Consecutive chunks share about
+/// `overlap` characters.
+fn split_text(text: &str, max_chars: usize, overlap: usize) -> Vec<String> {
+    let chars: Vec<char> = text.trim().chars().collect();
+    if chars.is_empty() {
+        return Vec::new();
+    }
+    if chars.len() <= max_chars {
+        return vec![chars.iter().collect()];
+    }
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    loop {
+        let mut end = (start + max_chars).min(chars.len());
+        if end < chars.len() {
+            if let Some(offset) = find_break(&chars[start..end]) {
+                end = start + offset;
+            }
+        }
+
+        let chunk: String = chars[start..end].iter().collect();
+        let chunk = chunk.trim();
+        if !chunk.is_empty() {
+            chunks.push(chunk.to_string());
+        }
+        if end >= chars.len() {
+            break;
+        }
+
+        // Start the next chunk `overlap` characters back, at a word start
+        let mut next = end.saturating_sub(overlap).max(start + 1);
+        while next > start + 1 && next < end && !chars[next - 1].is_whitespace() {
+            next += 1;
+        }
+        start = next;
+    }
+    chunks
+}
+
+/// Best break position in the second half of a window.
+fn find_break(window: &[char]) -> Option<usize> {
+    let min = window.len() / 2;
+    let last = |pred: &dyn Fn(usize) -> bool| (min..window.len()).rev().find(|&i| pred(i));
+
+    last(&|i| i > 0 && window[i] == '\n' && window[i - 1] == '\n')
+        .or_else(|| last(&|i| window[i] == '\n'))
+        .or_else(|| {
+            last(&|i| {
+                i > 0
+                    && window[i].is_whitespace()
+                    && matches!(window[i - 1], '.' | '!' | '?' | '。')
+            })
+        })
+        .or_else(|| last(&|i| window[i].is_whitespace()))
+        .map(|i| i + 1)
+}
+
+/// Convert HTML to Markdown-like text: headings become `#` lines, block
+/// elements become line breaks and everything else is dropped.
+fn html_to_markdown(html: &str) -> String {
+    let lower = html.to_ascii_lowercase();
+    let mut out = String::with_capacity(html.len() / 2);
+    let mut pos = 0;
+
+    while pos < html.len() {
+        let Some(offset) = html[pos..].find('<') else {
+            out.push_str(&decode_entities(&html[pos..]));
+            break;
+        };
+        out.push_str(&decode_entities(&html[pos..pos + offset]));
+        pos += offset;
+
+        if lower[pos..].starts_with("<!--") {
+            pos = lower[pos..].find("-->").map_or(html.len(), |i| pos + i + 3);
+            continue;
+        }
+
+        let Some(close) = html[pos..].find('>') else {
+            break;
+        };
+        let tag = &lower[pos + 1..pos + close];
+        pos += close + 1;
+
+        let closing = tag.starts_with('/');
+        let name: String = tag
+            .trim_start_matches('/')
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric())
+            .collect();
+
+        match name.as_str() {
+            "script" | "style" | "head" if !closing => {
+                let end_tag = format!("</{}", name);
+                pos = lower[pos..]
+                    .find(&end_tag)
+                    .and_then(|i| lower[pos + i..].find('>').map(|j| pos + i + j + 1))
+                    .unwrap_or(html.len());
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                if closing {
+                    out.push_str("\n\n");
+                } else {
+                    let level = name[1..].parse::<usize>().unwrap_or(1);
+                    out.push_str("\n\n");
+                    out.push_str(&"#".repeat(level));
+                    out.push(' ');
+                }
+            }
+            "li" if !closing => out.push_str("\n- "),
+            "p" | "div" | "br" | "tr" | "ul" | "ol" | "table" | "section" | "article" | "pre"
+            | "blockquote" | "li" => out.push('\n'),
+            "td" | "th" if !closing => out.push(' '),
+            _ => {}
+        }
+    }
+
+    // Normalize whitespace line by line, keeping at most one blank line
+    let mut text = String::with_capacity(out.len());
+    let mut blank = true;
+    for line in out.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if line.is_empty() {
+            if !blank {
+                text.push('\n');
+            }
+            blank = true;
+        } else {
+            text.push_str(&line);
+            text.push('\n');
+            blank = false;
+        }
+    }
+    text
+}
+
+fn decode_entities(text: &str) -> String {
+    if !text.contains('&') {
+        return text.to_string();
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let c = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" | "#39" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .and_then(|hex| u32::from_str_radix(hex, 16).ok())
+                    .or_else(|| entity.strip_prefix('#').and_then(|n| n.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            c.map(|c| (c, end))
+        });
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(feature = "pdf")]
+fn pdf_sections(data: &[u8]) -> Result<Vec<Section>> {
+    let document = lopdf::Document::load_mem(data)
+        .map_err(|e| MemoryError::InvalidFormat(format!("Invalid PDF: {}", e)))?;
+    if document.is_encrypted() {
+        return Err(MemoryError::InvalidFormat(
+            "Encrypted PDFs are not supported".to_string(),
+        ));
+    }
+
+    let mut sections = Vec::new();
+    for page in document.get_pages().keys() {
+        match document.extract_text(&[*page]) {
+            Ok(text) if !text.trim().is_empty() => sections.push(Section {
+                heading: None,
+                page: Some(*page),
+                text,
+            }),
+            Ok(_) => {}
+            Err(e) => {
+                tracing::warn!(category = "memory", page, error = %e, "Skipping unreadable PDF page")
+            }
+        }
+    }
+    Ok(sections)
+}
+
+#[cfg(not(feature = "pdf"))]
+fn pdf_sections(_data: &[u8]) -> Result<Vec<Section>> {
+    Err(MemoryError::InvalidFormat(
+        "PDF support is not enabled in this build".to_string(),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mid_term::SimpleEmbeddingWrapper;
+    use crate::SimpleEmbedding;
+
+    fn ingestor() -> DocumentIngestor {
+        DocumentIngestor::new(
+            Arc::new(LongTermMemory::new()),
+            Arc::new(SimpleEmbeddingWrapper(SimpleEmbedding::new(32))),
+        )
+    }
+
+    #[test]
+    fn test_format_detection() {
+        assert_eq!(
+            DocumentFormat::from_filename("Manual.PDF"),
+            Some(DocumentFormat::Pdf)
+        );
+        assert_eq!(
+            DocumentFormat::from_content_type("text/html; charset=utf-8"),
+            Some(DocumentFormat::Html)
+        );
+        assert_eq!(DocumentFormat::from_filename("archive.zip"), None);
+        assert_eq!(
+            DocumentIngestor::document_id_for("Pump Manual v2.pdf"),
+            "pump-manual-v2-pdf"
+        );
+    }
+
+    #[test]
+    fn test_markdown_chunks_follow_headings() {
+        let markdown = "Intro text\n# Install\nMount it.\n## Wiring\nConnect L and N.\n```\n# not a heading\n```\n# Maintenance\nClean monthly.\n";
+        let chunks = chunk_document(
+            DocumentFormat::Markdown,
+            markdown.as_bytes(),
+            &ChunkingConfig::default(),
+        )
+        .unwrap();
+
+        let headings: Vec<_> = chunks.iter().map(|c| c.heading.as_deref()).collect();
+        assert_eq!(
+            headings,
+            vec![
+                None,
+                Some("Install"),
+                Some("Install > Wiring"),
+                Some("Maintenance")
+            ]
+        );
+        assert!(chunks[2].text.contains("# not a heading"));
+    }
+
+    #[test]
+    fn test_long_sections_split_with_overlap() {
+        let sentence = "The pump must be primed before the first start. ";
+        let text = sentence.repeat(60);
+        let chunks = split_text(&text, 300, 60);
+
+        assert!(chunks.len() > 1);
+        assert!(chunks.iter().all(|c| c.chars().count() <= 300));
+        // Consecutive chunks share text
+        let tail: String = chunks[0]
+            .chars()
+            .rev()
+            .take(20)
+            .collect::<Vec<_>>()
+            .into_iter()
+            .rev()
+            .collect();
+        assert!(chunks[1].contains(tail.trim()));
+    }
+
+    #[test]
+    fn test_html_extraction() {
+        let html = "<html><head><title>x</title><style>p{}</style></head><body><h1>Valve</h1><p>Open &amp; close <b>slowly</b>.</p><script>var a = '<h2>';</script><h2>Faults</h2><ul><li>E1: no flow</li></ul></body></html>";
+        let chunks = chunk_document(
+            DocumentFormat::Html,
+            html.as_bytes(),
+            &ChunkingConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(chunks.len(), 2);
+        assert_eq!(chunks[0].heading.as_deref(), Some("Valve"));
+        assert_eq!(chunks[0].text, "Open & close slowly.");
+        assert_eq!(chunks[1].heading.as_deref(), Some("Valve > Faults"));
+        assert_eq!(chunks[1].text, "- E1: no flow");
+    }
+
+    #[tokio::test]
+    async fn test_ingest_and_replace() {
+        let ingestor = ingestor();
+        let input = DocumentInput::new(
+            "pump.md",
+            DocumentFormat::Markdown,
+            b"# Start\nPrime the pump.\n# Stop\nClose the valve.".to_vec(),
+        )
+        .with_device_types(vec!["pump".to_string()]);
+
+        let report = ingestor.ingest(input.clone()).await.unwrap();
+        assert_eq!(report.document_id, "pump-md");
+        assert_eq!(report.chunks, 2);
+        assert_eq!(report.replaced, 0);
+
+        let entries = ingestor.long_term.get_by_tag("pump").await;
+        assert_eq!(entries.len(), 2);
+        assert!(entries.iter().all(|e| e.embedding.is_some()));
+        assert_eq!(entries[0].citation().as_deref(), Some("pump.md"));
+
+        // Re-ingesting the updated document replaces its chunks
+        let updated = DocumentInput {
+            data: b"# Start\nPrime the pump and open the valve.".to_vec(),
+            ..input
+        };
+        let previous_ids: Vec<String> = entries.iter().map(|e| e.id.clone()).collect();
+        let report = ingestor.ingest(updated).await.unwrap();
+        assert_eq!(report.chunks, 1);
+        assert_eq!(report.replaced, 2);
+        let entries = ingestor.long_term.get_all().await;
+        assert_eq!(entries.len(), 1);
+        assert!(!previous_ids.contains(&entries[0].id));
+
+        let documents = ingestor.list().await;
+        assert_eq!(documents.len(), 1);
+        assert_eq!(documents[0].device_types, vec!["pump".to_string()]);
+
+        assert_eq!(ingestor.remove("pump-md").await.unwrap(), 1);
+        assert!(ingestor.long_term.is_empty().await);
+    }
+
+    #[cfg(feature = "pdf")]
+    #[tokio::test]
+    async fn test_pdf_pages_are_cited() {
+        use lopdf::content::{Content, Operation};
+        use lopdf::{dictionary, Document, Object, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font_id = doc.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => "Helvetica",
+        });
+        let resources_id = doc.add_object(dictionary! {
+            "Font" => dictionary! { "F1" => font_id },
+        });
+        let mut kids = Vec::new();
+        for text in ["Check the fuse.", "Replace the seal."] {
+            let content = Content {
+                operations: vec![
+                    Operation::new("BT", vec![]),
+                    Operation::new("Tf", vec!["F1".into(), 12.into()]),
+                    Operation::new("Td", vec![50.into(), 700.into()]),
+                    Operation::new("Tj", vec![Object::string_literal(text)]),
+                    Operation::new("ET", vec![]),
+                ],
+            };
+            let content_id = doc.add_object(Stream::new(dictionary! {}, content.encode().unwrap()));
+            let page_id = doc.add_object(dictionary! {
+                "Type" => "Page",
+                "Parent" => pages_id,
+                "Contents" => content_id,
+            });
+            kids.push(page_id.into());
+        }
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => 2,
+                "Resources" => resources_id,
+                "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            }),
+        );
+        let catalog_id = doc.add_object(dictionary! {
+            "Type" => "Catalog",
+            "Pages" => pages_id,
+        });
+        doc.trailer.set("Root", catalog_id);
+        let mut data = Vec::new();
+        doc.save_to(&mut data).unwrap();
+
+        let ingestor = ingestor();
+        let report = ingestor
+            .ingest(DocumentInput::new("valve.pdf", DocumentFormat::Pdf, data))
+            .await
+            .unwrap();
+        assert_eq!(report.pages, Some(2));
+
+        let results = ingestor.long_term.search("seal").await;
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].citation().as_deref(), Some("valve.pdf, p. 2"));
+    }
+}
@@ -1,28 +1,33 @@
 //! Real embedding model support for semantic search.
 //!
 //! This module provides embedding generation using actual ML models:
-//! - **Local**: Placeholder for native Rust embeddings (currently uses hash-based fallback)
-//!   - Recommended for future: fastembed-rs for pure Rust local embeddings
+//! - **Local**: BERT-style sentence-embedding models run on the CPU, loaded
+//!   from a local directory (no network access needed)
 //!   - Chinese models: bge-small-zh-v1.5 (24M params, 512 dim)
-//!   - English models: BAAI/bge-small-en-v1.5, nomic-embed-text
+//!   - English models: BAAI/bge-small-en-v1.5, all-MiniLM-L6-v2
 //! - **Ollama**: Local embedding service (requires separate Ollama installation)
 //! - **Cloud (OpenAI)**: Cloud embedding models for production use
 //! - **Fallback**: Simple hash-based embedding when no model is configured
 //!
 //! ## Local Embeddings
 //!
-//! The `Local` provider provides a stub implementation that uses hash-based embeddings.
-//! For true local ML embeddings, consider:
-//! 1. **Ollama** (recommended): `EmbeddingConfig::ollama("nomic-embed-text")`
-//! 2. **fastembed-rs** (future): Will be integrated in a future update
+//! The `Local` provider (feature `local-embeddings`, on by default) loads a
+//! Hugging Face style model directory containing `config.json`,
+//! `tokenizer.json` and `model.safetensors`, and runs it with mean pooling
+//! and L2 normalisation. This suits air-gapped deployments: download the
+//! model once and copy the directory to the device.
+//!
+//! Model directories are looked up as `<models dir>/<model name>`, where the
+//! models dir is `$NEOMIND_DATA_DIR/models` or `data/models`. A model name that
+//! is itself a directory path is used as is.
 //!
 //! ### Usage
 //! ```rust,no_run
 //! use neomind_memory::embeddings::{EmbeddingConfig, create_embedding_model};
 //!
 //! # fn main() -> Result<(), Box<dyn std::error::Error>> {
-//! // Local Chinese model (stub - uses hash-based fallback)
-//! let config = EmbeddingConfig::local("bge-small-zh-v1.5");
+//! // Local Chinese model from data/models/bge-small-zh-v1.5, on 2 threads
+//! let config = EmbeddingConfig::local("bge-small-zh-v1.5").with_threads(2);
 //! let model = create_embedding_model(config)?;
 //!
 //! // Ollama local model (requires Ollama to be installed)
@@ -46,7 +51,7 @@ use lru::LruCache;
 use serde::{Deserialize, Serialize};
 use std::hash::{Hash, Hasher};
 use std::num::NonZeroUsize;
-use std::path::PathBuf as StdPathBuf;
+use std::path::{Path, PathBuf as StdPathBuf};
 use std::sync::Arc;
 use std::time::Duration;
 
@@ -56,8 +61,7 @@ pub use super::error::MemoryError as Error;
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 pub enum EmbeddingProvider {
-    /// Local embeddings (currently stub, uses hash-based fallback)
-    /// For true ML embeddings, use Ollama provider instead.
+    /// Local CPU inference of a BERT-style model from a model directory
     Local,
     /// Ollama local embedding models (requires separate Ollama installation)
     Ollama,
@@ -70,13 +74,13 @@ pub enum EmbeddingProvider {
 /// Embedding model configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EmbeddingConfig {
-    /// Provider (ollama, openai, simple)
+    /// Provider (local, ollama, openai, simple)
     pub provider: EmbeddingProvider,
 
     /// Model name
     pub model: String,
 
-    /// API endpoint (for Ollama)
+    /// API endpoint (for Ollama), or models directory (for Local)
     pub endpoint: Option<String>,
 
     /// API key (for OpenAI)
@@ -87,6 +91,14 @@ pub struct EmbeddingConfig {
 
     /// Cache size (number of embeddings to cache)
     pub cache_size: Option<usize>,
+
+    /// Inference threads (for Local; defaults to all cores)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threads: Option<usize>,
+
+    /// Texts per inference batch (for Local)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub batch_size: Option<usize>,
 }
 
 impl Default for EmbeddingConfig {
@@ -98,6 +110,8 @@ impl Default for EmbeddingConfig {
             api_key: None,
             timeout_secs: Some(30),
             cache_size: Some(1000),
+            threads: None,
+            batch_size: None,
         }
     }
 }
@@ -112,6 +126,8 @@ impl EmbeddingConfig {
             api_key: None,
             timeout_secs: Some(60),
             cache_size: Some(1000),
+            threads: None,
+            batch_size: None,
         }
     }
 
@@ -124,6 +140,8 @@ impl EmbeddingConfig {
             api_key: None,
             timeout_secs: Some(60),
             cache_size: Some(1000),
+            threads: None,
+            batch_size: None,
         }
     }
 
@@ -136,6 +154,8 @@ impl EmbeddingConfig {
             api_key: Some(api_key.into()),
             timeout_secs: Some(30),
             cache_size: Some(1000),
+            threads: None,
+            batch_size: None,
         }
     }
 
@@ -148,26 +168,23 @@ impl EmbeddingConfig {
             api_key: None,
             timeout_secs: None,
             cache_size: None,
+            threads: None,
+            batch_size: None,
         }
     }
 
-    /// Create local embedding configuration (currently uses hash-based fallback).
+    /// Create local embedding configuration.
     ///
-    /// **Note**: This is currently a stub implementation that provides hash-based
-    /// embeddings. For true ML-based local embeddings, use Ollama instead:
-    /// ```rust,no_run
-    /// # use neomind_memory::embeddings::EmbeddingConfig;
-    /// let config = EmbeddingConfig::ollama("nomic-embed-text");
-    /// ```
+    /// The model is loaded from the default models directory; see
+    /// [`LocalEmbedding::new`].
     ///
     /// # Arguments
-    /// * `model` - Model name (for future use, e.g., "bge-small-zh-v1.5")
+    /// * `model` - Model name (e.g., "bge-small-zh-v1.5") or model directory
     ///
     /// # Example
     /// ```
     /// use neomind_memory::embeddings::EmbeddingConfig;
     ///
-    /// // Uses hash-based fallback
     /// let config = EmbeddingConfig::local("bge-small-zh-v1.5");
     /// ```
     pub fn local(model: impl Into<String>) -> Self {
@@ -178,10 +195,12 @@ impl EmbeddingConfig {
             api_key: None,
             timeout_secs: Some(60),
             cache_size: Some(1000),
+            threads: None,
+            batch_size: None,
         }
     }
 
-    /// Create local embedding with custom cache directory (for future use).
+    /// Create local embedding configuration with a custom models directory.
     pub fn local_with_cache(model: impl Into<String>, cache_dir: impl Into<String>) -> Self {
         Self {
             provider: EmbeddingProvider::Local,
@@ -190,6 +209,8 @@ impl EmbeddingConfig {
             api_key: None,
             timeout_secs: Some(60),
             cache_size: Some(1000),
+            threads: None,
+            batch_size: None,
         }
     }
 
@@ -216,6 +237,18 @@ impl EmbeddingConfig {
         self.cache_size = Some(size);
         self
     }
+
+    /// Set the number of inference threads (Local only).
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = Some(threads);
+        self
+    }
+
+    /// Set the inference batch size (Local only).
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = Some(batch_size);
+        self
+    }
 }
 
 /// Trait for embedding models.
@@ -234,86 +267,149 @@ pub trait EmbeddingModel: Send + Sync {
     fn model_name(&self) -> &str;
 }
 
-/// Local embedding support (stub implementation).
+/// Default number of texts per local inference batch.
+pub const DEFAULT_LOCAL_BATCH_SIZE: usize = 32;
+
+/// Local sentence-embedding model, run on the CPU.
 ///
-/// **Note**: This is currently a stub that uses hash-based embeddings as a fallback.
-/// The model metadata and information are provided for future integration with
-/// true ML-based embedding libraries like fastembed-rs or Candle.
+/// Loads a BERT-architecture model from a local directory containing
+/// `config.json`, `tokenizer.json` and `model.safetensors` (the layout of a
+/// Hugging Face checkpoint), so neither network access nor an external
+/// service is needed. Requires the `local-embeddings` feature.
 ///
-/// For production ML-based embeddings, use Ollama instead:
-/// ```rust,no_run
-/// use neomind_memory::embeddings::OllamaEmbedding;
-/// let model = OllamaEmbedding::new("nomic-embed-text", "http://localhost:11434");
-/// ```
+/// Inference runs on blocking threads in batches of
+/// [`DEFAULT_LOCAL_BATCH_SIZE`] texts; use [`with_threads`](Self::with_threads)
+/// to cap the CPU cores it uses.
 ///
 /// # Model Information
 ///
-/// The following models are supported for future integration:
+/// Tested models:
 ///
 /// **Chinese:**
 /// - `bge-small-zh-v1.5` - 24M params, 512 dimensions, ~100MB
 ///
 /// **English:**
 /// - `BAAI/bge-small-en-v1.5` - 33M params, 384 dimensions, ~130MB
-/// - `nomic-embed-text-v1.5` - 137M params, 768 dimensions, ~500MB
 /// - `all-MiniLM-L6-v2` - 23M params, 384 dimensions, ~90MB
-///
-/// **Multilingual:**
-/// - `intfloat/multilingual-e5-large` - 560M params, 1024 dimensions, ~2GB
 pub struct LocalEmbedding {
     model_name: String,
     dimension: usize,
-    _private: (),
+    batch_size: usize,
+    #[cfg(feature = "local-embeddings")]
+    encoder: Arc<super::local_model::BertEncoder>,
+    #[cfg(feature = "local-embeddings")]
+    pool: Option<Arc<rayon::ThreadPool>>,
 }
 
 impl LocalEmbedding {
-    /// Create a new local embedding model.
+    /// Load a local embedding model by name.
     ///
-    /// The model will be downloaded from HuggingFace on first use
-    /// and cached locally for future use.
+    /// The model directory is `<models dir>/<model_name>` (see
+    /// [`models_dir`](Self::models_dir)), or `model_name` itself when it is
+    /// a directory path.
     ///
     /// # Arguments
     /// * `model_name` - Name of the model (e.g., "bge-small-zh-v1.5")
     ///
     /// # Example
-    /// ```
+    /// ```rust,no_run
     /// use neomind_memory::embeddings::LocalEmbedding;
     ///
     /// let model = LocalEmbedding::new("bge-small-zh-v1.5").unwrap();
     /// ```
     pub fn new(model_name: impl Into<String>) -> Result<Self, Error> {
-        let model_name_str = model_name.into();
-        let dimension = Self::default_dimension(&model_name_str);
-
-        Ok(Self {
-            model_name: model_name_str,
-            dimension,
-            _private: (),
-        })
+        Self::new_with_cache(model_name, Self::models_dir())
     }
 
-    /// Create with custom cache directory.
+    /// Load a local embedding model by name from a custom models directory.
     pub fn new_with_cache(
         model_name: impl Into<String>,
-        _cache_dir: impl Into<StdPathBuf>,
+        cache_dir: impl Into<StdPathBuf>,
     ) -> Result<Self, Error> {
-        Self::new(model_name)
+        let model_name = model_name.into();
+        let path = Path::new(&model_name);
+        let dir = if path.is_dir() {
+            path.to_path_buf()
+        } else {
+            cache_dir.into().join(&model_name)
+        };
+        Self::load(model_name, &dir)
     }
 
-    /// Get default dimension for known models.
-    fn default_dimension(name: &str) -> usize {
-        let name_lower = name.to_lowercase();
-        if name_lower.contains("bge-small-zh") {
-            512
-        } else if name_lower.contains("bge-small-en") || name_lower.contains("minilm") {
-            384
-        } else if name_lower.contains("nomic-embed") {
-            768
-        } else if name_lower.contains("multilingual-e5-large") || name_lower.contains("bge-large") {
-            1024
-        } else {
-            384 // Default for most small models
+    /// Load a local embedding model from its directory.
+    pub fn from_dir(dir: impl Into<StdPathBuf>) -> Result<Self, Error> {
+        let dir = dir.into();
+        let model_name = dir
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| dir.display().to_string());
+        Self::load(model_name, &dir)
+    }
+
+    /// Default models directory: `$NEOMIND_DATA_DIR/models`, or `data/models`.
+    pub fn models_dir() -> StdPathBuf {
+        neomind_storage::data_path("models")
+    }
+
+    #[cfg(feature = "local-embeddings")]
+    fn load(model_name: String, dir: &Path) -> Result<Self, Error> {
+        if !dir.is_dir() {
+            return Err(Error::Config(format!(
+                "Local embedding model '{}' not found at {}",
+                model_name,
+                dir.display()
+            )));
         }
+
+        let encoder = super::local_model::BertEncoder::load(dir)?;
+        tracing::info!(
+            category = "memory",
+            model = %model_name,
+            dimension = encoder.dimension(),
+            "Loaded local embedding model"
+        );
+
+        Ok(Self {
+            model_name,
+            dimension: encoder.dimension(),
+            batch_size: DEFAULT_LOCAL_BATCH_SIZE,
+            encoder: Arc::new(encoder),
+            pool: None,
+        })
+    }
+
+    #[cfg(not(feature = "local-embeddings"))]
+    fn load(model_name: String, _dir: &Path) -> Result<Self, Error> {
+        Err(Error::Config(format!(
+            "Cannot load local embedding model '{}': built without the local-embeddings feature",
+            model_name
+        )))
+    }
+
+    /// Set the number of texts per inference batch.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Run inference on a dedicated pool of `threads` threads instead of
+    /// all cores.
+    #[cfg(feature = "local-embeddings")]
+    pub fn with_threads(mut self, threads: usize) -> Result<Self, Error> {
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(threads.max(1))
+            .thread_name(|i| format!("neomind-embed-{}", i))
+            .build()
+            .map_err(|e| Error::Config(format!("Failed to create inference threads: {}", e)))?;
+        self.pool = Some(Arc::new(pool));
+        Ok(self)
+    }
+
+    /// Run inference on a dedicated pool of `threads` threads instead of
+    /// all cores.
+    #[cfg(not(feature = "local-embeddings"))]
+    pub fn with_threads(self, _threads: usize) -> Result<Self, Error> {
+        Ok(self)
     }
 
     /// Get recommended models for a language.
@@ -414,18 +510,63 @@ pub struct ModelInfo {
     pub description: String,
 }
 
+#[cfg(feature = "local-embeddings")]
 #[async_trait]
 impl EmbeddingModel for LocalEmbedding {
     async fn embed(&self, text: &str) -> Result<Vec<f32>, Error> {
-        // Stub implementation - uses SimpleEmbedding as fallback
-        // In full implementation, this would call the fastembed model
-        let simple = SimpleEmbedding::new(self.dimension);
-        Ok(simple.embed(text))
+        self.embed_batch(&[text.to_string()])
+            .await?
+            .pop()
+            .ok_or_else(|| Error::Embedding("No embedding returned".to_string()))
     }
 
     async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>, Error> {
-        let simple = SimpleEmbedding::new(self.dimension);
-        Ok(texts.iter().map(|t| simple.embed(t)).collect())
+        // Batch texts of similar length together to limit padding
+        let mut order: Vec<usize> = (0..texts.len()).collect();
+        order.sort_by_key(|&i| texts[i].len());
+
+        let mut results = vec![Vec::new(); texts.len()];
+        for indices in order.chunks(self.batch_size) {
+            let batch: Vec<String> = indices.iter().map(|&i| texts[i].clone()).collect();
+            let encoder = self.encoder.clone();
+            let pool = self.pool.clone();
+            let embeddings = tokio::task::spawn_blocking(move || match pool {
+                Some(pool) => pool.install(|| encoder.encode(&batch)),
+                None => encoder.encode(&batch),
+            })
+            .await
+            .map_err(|e| Error::Embedding(format!("Inference task failed: {}", e)))??;
+
+            for (&i, embedding) in indices.iter().zip(embeddings) {
+                results[i] = embedding;
+            }
+        }
+
+        Ok(results)
+    }
+
+    fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    fn model_name(&self) -> &str {
+        &self.model_name
+    }
+}
+
+#[cfg(not(feature = "local-embeddings"))]
+#[async_trait]
+impl EmbeddingModel for LocalEmbedding {
+    async fn embed(&self, _text: &str) -> Result<Vec<f32>, Error> {
+        Err(Error::Config(
+            "Built without the local-embeddings feature".to_string(),
+        ))
+    }
+
+    async fn embed_batch(&self, _texts: &[String]) -> Result<Vec<Vec<f32>>, Error> {
+        Err(Error::Config(
+            "Built without the local-embeddings feature".to_string(),
+        ))
     }
 
     fn dimension(&self) -> usize {
@@ -795,12 +936,18 @@ impl EmbeddingModel for CachedEmbeddingModel {
 pub fn create_embedding_model(config: EmbeddingConfig) -> Result<Box<dyn EmbeddingModel>, Error> {
     let model: Box<dyn EmbeddingModel> = match config.provider {
         EmbeddingProvider::Local => {
-            // Native local embeddings using fastembed (pure Rust, no external service)
-            if let Some(cache_dir) = config.endpoint {
-                Box::new(LocalEmbedding::new_with_cache(&config.model, cache_dir)?)
-            } else {
-                Box::new(LocalEmbedding::new(&config.model)?)
+            // Native local embeddings on the CPU (no external service)
+            let mut local = match config.endpoint {
+                Some(models_dir) => LocalEmbedding::new_with_cache(&config.model, models_dir)?,
+                None => LocalEmbedding::new(&config.model)?,
+            };
+            if let Some(batch_size) = config.batch_size {
+                local = local.with_batch_size(batch_size);
             }
+            if let Some(threads) = config.threads {
+                local = local.with_threads(threads)?;
+            }
+            Box::new(local)
         }
         EmbeddingProvider::Ollama => {
             let endpoint = config
@@ -984,7 +1131,22 @@ mod tests {
         let config = EmbeddingConfig::local("bge-small-zh-v1.5");
         assert_eq!(config.provider, EmbeddingProvider::Local);
         assert_eq!(config.model, "bge-small-zh-v1.5");
-        assert_eq!(config.endpoint, None); // Not used for fastembed
+        assert_eq!(config.endpoint, None); // Default models directory
+    }
+
+    #[test]
+    fn test_local_embedding_missing_model() {
+        let config = EmbeddingConfig::local_with_cache("no-such-model", "/nonexistent")
+            .with_threads(2)
+            .with_batch_size(8);
+        assert_eq!(config.threads, Some(2));
+        assert_eq!(config.batch_size, Some(8));
+
+        // A missing model is an error, not a silent hash-based fallback
+        assert!(matches!(
+            create_embedding_model(config),
+            Err(Error::Config(_))
+        ));
     }
 
     #[test]
@@ -79,6 +79,12 @@ impl From<serde_json::Error> for MemoryError {
     }
 }
 
+impl From<neomind_storage::Error> for MemoryError {
+    fn from(err: neomind_storage::Error) -> Self {
+        MemoryError::Storage(err.to_string())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -10,6 +10,7 @@
 //! - **Memory Associations**: Link memories to entities
 //! - **Graph Traversal**: Find related memories through graph paths
 //! - **Centrality Measures**: Identify important entities
+//! - **Persistence**: Optional write-through to a [`MemoryStore`](crate::store::MemoryStore)
 //!
 //! ## Example
 //!
@@ -38,9 +39,11 @@
 //! ```
 
 use crate::error::{MemoryError, Result};
+use crate::store::MemoryStore;
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Arc;
 
 /// Unique identifier for an entity in the graph.
 pub type EntityId = String;
@@ -375,6 +378,8 @@ pub struct MemoryGraph {
     adj_in: DashMap<EntityId, Vec<(EntityId, RelationId)>>,
     /// Configuration
     config: GraphConfig,
+    /// Persistent store (write-through)
+    store: Option<Arc<MemoryStore>>,
 }
 
 impl MemoryGraph {
@@ -391,12 +396,35 @@ impl MemoryGraph {
             adj_out: DashMap::with_capacity(64),
             adj_in: DashMap::with_capacity(64),
             config,
+            store: None,
+        }
+    }
+
+    /// Load the graph from a store and write all changes through to it.
+    pub fn with_store(mut self, store: Arc<MemoryStore>) -> Result<Self> {
+        for entity in store.load_entities()? {
+            self.entities.insert(entity.id.clone(), entity);
+        }
+        for relationship in store.load_relationships()? {
+            self.insert_relationship(relationship);
+        }
+        self.store = Some(store);
+        Ok(self)
+    }
+
+    /// Persist an entity. `add_entity` cannot fail, so errors are logged.
+    fn persist_entity(&self, entity: &Entity) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.save_entity(entity) {
+                tracing::warn!(category = "memory", id = %entity.id, error = %e, "Failed to persist graph entity");
+            }
         }
     }
 
     /// Add an entity to the graph.
     pub fn add_entity(&self, entity: Entity) -> EntityId {
         let id = entity.id.clone();
+        self.persist_entity(&entity);
         self.entities.insert(id.clone(), entity);
         id
     }
@@ -445,6 +473,13 @@ impl MemoryGraph {
 
         // Remove the entity
         let removed = self.entities.remove(id).is_some();
+        if removed {
+            if let Some(store) = &self.store {
+                if let Err(e) = store.delete_entity(id) {
+                    tracing::warn!(category = "memory", id, error = %e, "Failed to delete persisted graph entity");
+                }
+            }
+        }
 
         // Clean up adjacency lists
         self.adj_out.remove(id);
@@ -479,19 +514,10 @@ impl MemoryGraph {
             relation_type,
         );
 
-        // Add relationship - DashMap is lock-free
-        self.relationships.insert(rel_id.clone(), relationship);
-
-        // Update adjacency lists - DashMap is lock-free
-        self.adj_out
-            .entry(from_id.clone())
-            .or_default()
-            .push((to_id.clone(), rel_id.clone()));
-
-        self.adj_in
-            .entry(to_id)
-            .or_default()
-            .push((from_id, rel_id.clone()));
+        if let Some(store) = &self.store {
+            store.save_relationship(&relationship)?;
+        }
+        self.insert_relationship(relationship);
 
         Ok(rel_id)
     }
@@ -515,6 +541,20 @@ impl MemoryGraph {
         )
         .with_weight(weight);
 
+        if let Some(store) = &self.store {
+            store.save_relationship(&relationship)?;
+        }
+        self.insert_relationship(relationship);
+
+        Ok(rel_id)
+    }
+
+    /// Add a relationship to the maps and adjacency lists.
+    fn insert_relationship(&self, relationship: Relationship) {
+        let rel_id = relationship.id.clone();
+        let from_id = relationship.from.clone();
+        let to_id = relationship.to.clone();
+
         // Add relationship - DashMap is lock-free
         self.relationships.insert(rel_id.clone(), relationship);
 
@@ -527,9 +567,7 @@ impl MemoryGraph {
         self.adj_in
             .entry(to_id)
             .or_default()
-            .push((from_id, rel_id.clone()));
-
-        Ok(rel_id)
+            .push((from_id, rel_id));
     }
 
     /// Get a relationship by ID.
@@ -542,6 +580,12 @@ impl MemoryGraph {
         let relationship = self.relationships.remove(id).map(|(_, v)| v);
 
         if let Some(rel) = relationship {
+            if let Some(store) = &self.store {
+                if let Err(e) = store.delete_relationship(id) {
+                    tracing::warn!(category = "memory", id, error = %e, "Failed to delete persisted relationship");
+                }
+            }
+
             // Update adjacency lists - DashMap is lock-free
             if let Some(mut neighbors) = self.adj_out.get_mut(&rel.from) {
                 neighbors.retain(|(to_id, rel_id)| to_id != &rel.to || rel_id != id);
@@ -745,6 +789,12 @@ impl MemoryGraph {
                 Entity::new(entity_id, entity_id).with_memory(mem_id.clone())
             });
 
+        if let Some(store) = &self.store {
+            if let Some(entity) = self.get_entity(entity_id) {
+                store.save_entity(&entity)?;
+            }
+        }
+
         Ok(())
     }
 
@@ -760,6 +810,11 @@ impl MemoryGraph {
 
     /// Clear all entities and relationships.
     pub fn clear(&self) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.clear_graph() {
+                tracing::warn!(category = "memory", error = %e, "Failed to clear persisted graph");
+            }
+        }
         self.entities.clear();
         self.relationships.clear();
         self.adj_out.clear();
@@ -962,4 +1017,31 @@ mod tests {
         assert_eq!(graph.entity_count(), 0);
         assert_eq!(graph.relationship_count(), 0);
     }
+
+    #[tokio::test]
+    async fn test_graph_persists_across_reopen() {
+        let store = MemoryStore::memory().unwrap();
+        let graph = MemoryGraph::new().with_store(store.clone()).unwrap();
+        graph.create_entity("pump", "Pump", EntityType::Device);
+        graph.create_entity("tank", "Tank", EntityType::Device);
+        graph.create_entity("valve", "Valve", EntityType::Device);
+        graph
+            .add_relationship("pump", "tank", RelationType::Controls)
+            .await
+            .unwrap();
+        graph.associate_memory("pump", "case-1").unwrap();
+        graph.remove_entity("valve");
+        drop(graph);
+
+        let reopened = MemoryGraph::new().with_store(store).unwrap();
+        assert_eq!(reopened.entity_count(), 2);
+        assert_eq!(reopened.relationship_count(), 1);
+        assert_eq!(
+            reopened
+                .find_related("pump", RelationType::Controls, 1)
+                .await,
+            vec!["tank".to_string()]
+        );
+        assert_eq!(reopened.get_entities_for_memory("case-1").len(), 1);
+    }
 }
@@ -7,6 +7,9 @@
 //! - **Short-term Memory**: Current conversation context with token limits
 //! - **Mid-term Memory**: Recent conversation history with semantic search
 //! - **Long-term Memory**: Device knowledge base and troubleshooting guides
+//! - **Document Ingestion**: Manuals and SOPs chunked and embedded into long-term memory
+//! - **Hybrid Retrieval**: BM25 and vector search fused with RRF, with filters and reranking
+//! - **Persistence**: redb-backed [`MemoryStore`] for mid-term, long-term and graph data
 //! - **Unified Interface**: Single interface to all memory layers
 //!
 //! ## Example
@@ -47,14 +50,19 @@
 pub mod bm25;
 pub mod budget;
 pub mod compression;
+pub mod documents;
 pub mod embeddings;
 pub mod error;
 pub mod graph;
 pub mod importance;
+#[cfg(feature = "local-embeddings")]
+mod local_model;
 pub mod long_term;
 pub mod mid_term;
+pub mod retrieval;
 pub mod semantic;
 pub mod short_term;
+pub mod store;
 pub mod tiered;
 pub mod unified;
 
@@ -68,6 +76,10 @@ pub use compression::{
     MemoryCompressor, MessageGroup, SummaryLevel, DEFAULT_MAX_SUMMARY_TOKENS, DEFAULT_TARGET_RATIO,
     MIN_GROUP_SIZE,
 };
+pub use documents::{
+    ChunkingConfig, DocumentChunk, DocumentFormat, DocumentIngestor, DocumentInput,
+    DocumentSummary, IngestReport,
+};
 pub use embeddings::{
     cosine_similarity, create_embedding_model, dot_similarity, CachedEmbeddingModel,
     EmbeddingConfig, EmbeddingModel, EmbeddingProvider, LocalEmbedding, ModelInfo, OllamaEmbedding,
@@ -89,11 +101,17 @@ pub use long_term::{
     KnowledgeCategory, KnowledgeEntry, LongTermMemory, SolutionStep, TroubleshootingCase,
 };
 pub use mid_term::{ConversationEntry, MidTermMemory, SearchResult};
+pub use retrieval::{
+    FusionMethod, HybridRetriever, LabelledQuery, Reranker, RetrievalConfig, RetrievalFilter,
+    RetrievalMetrics, RetrievalQuery, RetrievalSource, RetrievedContent, RetrievedItem,
+    DEFAULT_CANDIDATE_MULTIPLIER, DEFAULT_RRF_K,
+};
 pub use semantic::{
     SearchConfig, SearchExecutor, SemanticDocument, SemanticSearch, SemanticSearchResult,
     DEFAULT_HYBRID_ALPHA, DEFAULT_MAX_RESULTS,
 };
 pub use short_term::{MemoryMessage, ShortTermMemory, DEFAULT_MAX_MESSAGES, DEFAULT_MAX_TOKENS};
+pub use store::{MemoryStore, MEMORY_SCHEMA_VERSION};
 pub use tiered::{MemoryQueryResult, MemoryStats, SearchMethod, TieredMemory, TieredMemoryConfig};
 pub use unified::{
     MemoryItem, MemoryLayer, MemoryQuery, MemoryResults, PromotionPolicy, UnifiedMemory,
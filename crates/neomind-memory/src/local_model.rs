@@ -0,0 +1,172 @@
+//! CPU inference for BERT-style sentence embedding models.
+//!
+//! Loads a model directory laid out like a Hugging Face checkpoint:
+//! `config.json`, `tokenizer.json` and `model.safetensors`. Token states are
+//! pooled (mean over the attention mask by default, or the `[CLS]` token when
+//! a sentence-transformers `1_Pooling/config.json` asks for it) and L2
+//! normalised, so cosine similarity equals the dot product.
+
+use std::path::Path;
+
+use candle_core::{DType, Device, Tensor};
+use candle_nn::VarBuilder;
+use candle_transformers::models::bert::{BertModel, Config};
+use serde::Deserialize;
+use tokenizers::{PaddingParams, PaddingStrategy, Tokenizer, TruncationParams};
+
+use super::error::{MemoryError, Result};
+
+/// Longest input the position embeddings of most BERT models support.
+const MAX_SEQUENCE_LENGTH: usize = 512;
+
+/// How token states are reduced to one vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Pooling {
+    Mean,
+    Cls,
+}
+
+/// The subset of sentence-transformers' pooling config we honour.
+#[derive(Debug, Default, Deserialize)]
+struct PoolingConfig {
+    #[serde(default)]
+    pooling_mode_cls_token: bool,
+    #[serde(default)]
+    pooling_mode_mean_tokens: bool,
+}
+
+/// A loaded BERT encoder with its tokenizer.
+pub(crate) struct BertEncoder {
+    model: BertModel,
+    tokenizer: Tokenizer,
+    device: Device,
+    pooling: Pooling,
+    dimension: usize,
+}
+
+impl BertEncoder {
+    /// Load a model directory.
+    pub(crate) fn load(dir: &Path) -> Result<Self> {
+        let config_path = dir.join("config.json");
+        let config = std::fs::read_to_string(&config_path).map_err(|e| {
+            MemoryError::Config(format!("Failed to read {}: {}", config_path.display(), e))
+        })?;
+        let config: Config = serde_json::from_str(&config).map_err(|e| {
+            MemoryError::Config(format!("Invalid {}: {}", config_path.display(), e))
+        })?;
+
+        let tokenizer_path = dir.join("tokenizer.json");
+        let mut tokenizer = Tokenizer::from_file(&tokenizer_path).map_err(|e| {
+            MemoryError::Config(format!(
+                "Failed to load {}: {}",
+                tokenizer_path.display(),
+                e
+            ))
+        })?;
+        let pad_token = tokenizer
+            .id_to_token(config.pad_token_id as u32)
+            .unwrap_or_else(|| "[PAD]".to_string());
+        tokenizer.with_padding(Some(PaddingParams {
+            strategy: PaddingStrategy::BatchLongest,
+            pad_id: config.pad_token_id as u32,
+            pad_token,
+            ..Default::default()
+        }));
+        tokenizer
+            .with_truncation(Some(TruncationParams {
+                max_length: config.max_position_embeddings.min(MAX_SEQUENCE_LENGTH),
+                ..Default::default()
+            }))
+            .map_err(|e| MemoryError::Config(format!("Invalid truncation: {}", e)))?;
+
+        let weights_path = dir.join("model.safetensors");
+        if !weights_path.is_file() {
+            return Err(MemoryError::Config(format!(
+                "Model weights not found: {}",
+                weights_path.display()
+            )));
+        }
+        let device = Device::Cpu;
+        // SAFETY: the weights file is memory-mapped read-only and must not be
+        // modified while the model is loaded.
+        let vb =
+            unsafe { VarBuilder::from_mmaped_safetensors(&[weights_path], DType::F32, &device) }
+                .map_err(|e| MemoryError::Config(format!("Failed to load model weights: {}", e)))?;
+        let model = BertModel::load(vb, &config)
+            .map_err(|e| MemoryError::Config(format!("Failed to load model: {}", e)))?;
+
+        Ok(Self {
+            model,
+            tokenizer,
+            device,
+            pooling: Self::read_pooling(dir),
+            dimension: config.hidden_size,
+        })
+    }
+
+    fn read_pooling(dir: &Path) -> Pooling {
+        let config = std::fs::read_to_string(dir.join("1_Pooling").join("config.json"))
+            .ok()
+            .and_then(|s| serde_json::from_str::<PoolingConfig>(&s).ok())
+            .unwrap_or_default();
+        if config.pooling_mode_cls_token && !config.pooling_mode_mean_tokens {
+            Pooling::Cls
+        } else {
+            Pooling::Mean
+        }
+    }
+
+    /// Embedding dimension (the model's hidden size).
+    pub(crate) fn dimension(&self) -> usize {
+        self.dimension
+    }
+
+    /// Embed a batch of texts. Blocks while the model runs.
+    pub(crate) fn encode(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        if texts.is_empty() {
+            return Ok(Vec::new());
+        }
+        self.forward(texts)
+            .map_err(|e| MemoryError::Embedding(format!("Local inference failed: {}", e)))
+    }
+
+    fn forward(&self, texts: &[String]) -> candle_core::Result<Vec<Vec<f32>>> {
+        let encodings = self
+            .tokenizer
+            .encode_batch(texts.to_vec(), true)
+            .map_err(candle_core::Error::msg)?;
+
+        let mut ids = Vec::with_capacity(encodings.len());
+        let mut masks = Vec::with_capacity(encodings.len());
+        for encoding in &encodings {
+            ids.push(Tensor::new(encoding.get_ids(), &self.device)?);
+            masks.push(Tensor::new(encoding.get_attention_mask(), &self.device)?);
+        }
+        let input_ids = Tensor::stack(&ids, 0)?;
+        let attention_mask = Tensor::stack(&masks, 0)?;
+        let token_type_ids = input_ids.zeros_like()?;
+
+        // (batch, tokens, hidden)
+        let states = self
+            .model
+            .forward(&input_ids, &token_type_ids, Some(&attention_mask))?;
+
+        let pooled = match self.pooling {
+            Pooling::Cls => states.narrow(1, 0, 1)?.squeeze(1)?,
+            Pooling::Mean => {
+                // Padding tokens must not count towards the mean
+                let mask = attention_mask.to_dtype(DType::F32)?.unsqueeze(2)?;
+                let summed = states.broadcast_mul(&mask)?.sum(1)?;
+                let counts = mask.sum(1)?.clamp(1e-9, f64::MAX)?;
+                summed.broadcast_div(&counts)?
+            }
+        };
+
+        let norms = pooled
+            .sqr()?
+            .sum_keepdim(1)?
+            .sqrt()?
+            .clamp(1e-12, f64::MAX)?;
+        pooled.broadcast_div(&norms)?.to_vec2::<f32>()
+    }
+}
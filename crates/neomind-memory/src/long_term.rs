@@ -1,19 +1,29 @@
 //! Long-term memory for device knowledge and best practices.
 //!
 //! Long-term memory stores structured knowledge like device manuals,
-//! troubleshooting guides, and best practices.
+//! troubleshooting guides, and best practices. With a [`MemoryStore`] attached,
+//! every change is written through and the knowledge survives restarts.
+//! Access counts are the exception: reads batch them up and persist them at
+//! most every [`ACCESS_FLUSH_INTERVAL`], or on [`LongTermMemory::flush`].
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
+use tokio::sync::{Mutex, RwLock};
 
+use super::bm25::BM25Index;
+use super::embeddings::cosine_similarity;
 use super::error::{MemoryError, Result};
+use super::store::MemoryStore;
 
 /// Default max knowledge entries
 pub const DEFAULT_MAX_KNOWLEDGE: usize = 10000;
 
+/// How often reads persist the access counts they changed.
+pub const ACCESS_FLUSH_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Category of knowledge.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash)]
 pub enum KnowledgeCategory {
@@ -84,6 +94,9 @@ pub struct KnowledgeEntry {
     pub access_count: u64,
     /// Additional metadata
     pub metadata: Option<serde_json::Value>,
+    /// Embedding vector (for semantic search)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub embedding: Option<Vec<f32>>,
 }
 
 impl KnowledgeEntry {
@@ -105,6 +118,7 @@ impl KnowledgeEntry {
             updated_at: now,
             access_count: 0,
             metadata: None,
+            embedding: None,
         }
     }
 
@@ -126,6 +140,24 @@ impl KnowledgeEntry {
         self
     }
 
+    /// Set the embedding.
+    pub fn with_embedding(mut self, embedding: Vec<f32>) -> Self {
+        self.embedding = Some(embedding);
+        self
+    }
+
+    /// Source document and page, for entries created by document ingestion.
+    ///
+    /// Formatted as `manual.pdf, p. 12` or `manual.md` when there is no page.
+    pub fn citation(&self) -> Option<String> {
+        let metadata = self.metadata.as_ref()?;
+        let source = metadata.get("source")?.as_str()?;
+        match metadata.get("page").and_then(|p| p.as_u64()) {
+            Some(page) => Some(format!("{}, p. {}", source, page)),
+            None => Some(source.to_string()),
+        }
+    }
+
     /// Increment access count.
     pub fn increment_access(&mut self) {
         self.access_count += 1;
@@ -144,12 +176,26 @@ impl KnowledgeEntry {
         } else {
             self.content.clone()
         };
-        format!(
-            "[{}] {}: {}",
-            self.category.as_str(),
-            self.title,
-            content_preview
-        )
+        match self.citation() {
+            Some(citation) => format!(
+                "[{}] {}: {} (source: {})",
+                self.category.as_str(),
+                self.title,
+                content_preview,
+                citation
+            ),
+            None => format!(
+                "[{}] {}: {}",
+                self.category.as_str(),
+                self.title,
+                content_preview
+            ),
+        }
+    }
+
+    /// Text indexed for full-text search: title, content and tags.
+    pub fn search_text(&self) -> String {
+        format!("{} {} {}", self.title, self.content, self.tags.join(" "))
     }
 
     /// Check if matches a search query.
@@ -279,6 +325,27 @@ pub struct LongTermMemory {
     tag_index: Arc<RwLock<HashMap<String, Vec<String>>>>,
     /// Index by device
     device_index: Arc<RwLock<HashMap<String, Vec<String>>>>,
+    /// BM25 full-text index
+    bm25_index: Arc<RwLock<BM25Index>>,
+    /// Persistent store (write-through)
+    store: Option<Arc<MemoryStore>>,
+    /// Access counts changed since they were last persisted
+    access_log: Arc<Mutex<AccessLog>>,
+}
+
+/// Entries whose access counts are not persisted yet.
+struct AccessLog {
+    dirty: HashSet<String>,
+    last_flush: Instant,
+}
+
+impl AccessLog {
+    fn new() -> Self {
+        Self {
+            dirty: HashSet::new(),
+            last_flush: Instant::now(),
+        }
+    }
 }
 
 impl LongTermMemory {
@@ -291,6 +358,9 @@ impl LongTermMemory {
             category_index: Arc::new(RwLock::new(HashMap::new())),
             tag_index: Arc::new(RwLock::new(HashMap::new())),
             device_index: Arc::new(RwLock::new(HashMap::new())),
+            bm25_index: Arc::new(RwLock::new(BM25Index::new())),
+            store: None,
+            access_log: Arc::new(Mutex::new(AccessLog::new())),
         }
     }
 
@@ -300,12 +370,65 @@ impl LongTermMemory {
         self
     }
 
+    /// Maximum number of knowledge entries.
+    pub fn max_knowledge(&self) -> usize {
+        self.max_knowledge
+    }
+
+    /// Load knowledge and cases from a store and write all changes through to it.
+    pub async fn with_store(mut self, store: Arc<MemoryStore>) -> Result<Self> {
+        let mut entries = store.load_knowledge().await?;
+        entries.sort_by_key(|e| e.created_at);
+
+        let mut knowledge = HashMap::new();
+        let mut category_index: HashMap<KnowledgeCategory, Vec<String>> = HashMap::new();
+        let mut tag_index: HashMap<String, Vec<String>> = HashMap::new();
+        let mut device_index: HashMap<String, Vec<String>> = HashMap::new();
+        let mut bm25 = BM25Index::new();
+        for entry in entries {
+            category_index
+                .entry(entry.category.clone())
+                .or_default()
+                .push(entry.id.clone());
+            for tag in &entry.tags {
+                tag_index
+                    .entry(tag.clone())
+                    .or_default()
+                    .push(entry.id.clone());
+            }
+            for device_id in &entry.device_ids {
+                device_index
+                    .entry(device_id.clone())
+                    .or_default()
+                    .push(entry.id.clone());
+            }
+            bm25.add_document(&entry.id, &entry.search_text());
+            knowledge.insert(entry.id.clone(), entry);
+        }
+
+        let cases = store
+            .load_cases()?
+            .into_iter()
+            .map(|case| (case.id.clone(), case))
+            .collect();
+
+        self.knowledge = Arc::new(RwLock::new(knowledge));
+        self.cases = Arc::new(RwLock::new(cases));
+        self.category_index = Arc::new(RwLock::new(category_index));
+        self.tag_index = Arc::new(RwLock::new(tag_index));
+        self.device_index = Arc::new(RwLock::new(device_index));
+        self.bm25_index = Arc::new(RwLock::new(bm25));
+        self.store = Some(store);
+        Ok(self)
+    }
+
     /// Add a knowledge entry.
     pub async fn add(&self, entry: KnowledgeEntry) -> Result<()> {
         let id = entry.id.clone();
         let category = entry.category.clone();
         let tags = entry.tags.clone();
         let device_ids = entry.device_ids.clone();
+        let search_text = entry.search_text();
 
         // Check capacity
         {
@@ -316,10 +439,80 @@ impl LongTermMemory {
                     self.max_knowledge
                 )));
             }
+            if let Some(store) = &self.store {
+                store.save_knowledge(&entry).await?;
+            }
             knowledge.insert(id.clone(), entry);
         }
 
-        // Update indices
+        self.index(id, category, tags, device_ids, &search_text)
+            .await;
+        Ok(())
+    }
+
+    /// Replace a set of entries with new ones.
+    ///
+    /// The new entries are persisted first; the old ones are only removed
+    /// once all of them are stored, so a failure leaves the old set intact.
+    /// New IDs must not collide with the replaced ones.
+    pub async fn replace(&self, old_ids: &[String], entries: Vec<KnowledgeEntry>) -> Result<()> {
+        {
+            let knowledge = self.knowledge.read().await;
+            let kept = knowledge.len()
+                - old_ids
+                    .iter()
+                    .filter(|id| knowledge.contains_key(*id))
+                    .count();
+            if kept + entries.len() > self.max_knowledge {
+                return Err(MemoryError::CapacityExceeded(format!(
+                    "Knowledge limit reached: {}",
+                    self.max_knowledge
+                )));
+            }
+        }
+
+        if let Some(store) = &self.store {
+            for (saved, entry) in entries.iter().enumerate() {
+                if let Err(e) = store.save_knowledge(entry).await {
+                    for entry in &entries[..saved] {
+                        if let Err(e) = store.delete_knowledge(&entry.id).await {
+                            tracing::warn!(category = "memory", id = %entry.id, error = %e, "Failed to roll back knowledge entry");
+                        }
+                    }
+                    return Err(e);
+                }
+            }
+        }
+
+        for entry in entries {
+            let id = entry.id.clone();
+            let category = entry.category.clone();
+            let tags = entry.tags.clone();
+            let device_ids = entry.device_ids.clone();
+            let search_text = entry.search_text();
+            self.knowledge.write().await.insert(id.clone(), entry);
+            self.index(id, category, tags, device_ids, &search_text)
+                .await;
+        }
+
+        for id in old_ids {
+            match self.delete(id).await {
+                Ok(()) | Err(MemoryError::NotFound(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(())
+    }
+
+    /// Add an entry to the category, tag, device and BM25 indices.
+    async fn index(
+        &self,
+        id: String,
+        category: KnowledgeCategory,
+        tags: Vec<String>,
+        device_ids: Vec<String>,
+        search_text: &str,
+    ) {
         {
             let mut cat_idx = self.category_index.write().await;
             cat_idx.entry(category).or_default().push(id.clone());
@@ -335,18 +528,58 @@ impl LongTermMemory {
             dev_idx.entry(device_id).or_default().push(id.clone());
         }
 
-        Ok(())
+        self.bm25_index.write().await.add_document(&id, search_text);
     }
 
     /// Get a knowledge entry by ID.
+    ///
+    /// The access count is persisted with the next batch, not on every read.
     pub async fn get(&self, id: &str) -> Option<KnowledgeEntry> {
-        let mut knowledge = self.knowledge.write().await;
-        if let Some(entry) = knowledge.get_mut(id) {
+        let entry = {
+            let mut knowledge = self.knowledge.write().await;
+            let entry = knowledge.get_mut(id)?;
             entry.increment_access();
-            Some(entry.clone())
-        } else {
-            None
+            entry.clone()
+        };
+
+        if self.store.is_some() {
+            let due = {
+                let mut log = self.access_log.lock().await;
+                log.dirty.insert(id.to_string());
+                log.last_flush.elapsed() >= ACCESS_FLUSH_INTERVAL
+            };
+            if due {
+                if let Err(e) = self.flush().await {
+                    tracing::warn!(category = "memory", error = %e, "Failed to persist access counts");
+                }
+            }
         }
+        Some(entry)
+    }
+
+    /// Persist access counts changed since the last flush.
+    ///
+    /// Returns how many entries were written.
+    pub async fn flush(&self) -> Result<usize> {
+        let Some(store) = &self.store else {
+            return Ok(0);
+        };
+        let mut log = self.access_log.lock().await;
+        log.last_flush = Instant::now();
+        if log.dirty.is_empty() {
+            return Ok(0);
+        }
+
+        let records: Vec<KnowledgeEntry> = {
+            let knowledge = self.knowledge.read().await;
+            log.dirty
+                .iter()
+                .filter_map(|id| knowledge.get(id).cloned())
+                .collect()
+        };
+        store.save_knowledge_records(&records)?;
+        log.dirty.clear();
+        Ok(records.len())
     }
 
     /// Search knowledge by query.
@@ -363,6 +596,44 @@ impl LongTermMemory {
         results
     }
 
+    /// Search knowledge using BM25 full-text search.
+    pub async fn search_bm25(&self, query: &str, top_k: usize) -> Vec<(KnowledgeEntry, f32)> {
+        let bm25_results = self.bm25_index.read().await.search(query, top_k);
+
+        let knowledge = self.knowledge.read().await;
+        bm25_results
+            .into_iter()
+            .filter_map(|result| {
+                knowledge
+                    .get(&result.id)
+                    .map(|entry| (entry.clone(), result.score as f32))
+            })
+            .collect()
+    }
+
+    /// Search knowledge by embedding similarity.
+    ///
+    /// Only entries with an embedding of the same dimension are considered.
+    pub async fn search_similar(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> Vec<(KnowledgeEntry, f32)> {
+        let knowledge = self.knowledge.read().await;
+        let mut results: Vec<_> = knowledge
+            .values()
+            .filter_map(|entry| {
+                let embedding = entry.embedding.as_ref()?;
+                (embedding.len() == query_embedding.len())
+                    .then(|| (entry.clone(), cosine_similarity(query_embedding, embedding)))
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal));
+        results.truncate(top_k);
+        results
+    }
+
     /// Get knowledge by category.
     pub async fn get_by_category(&self, category: &KnowledgeCategory) -> Vec<KnowledgeEntry> {
         let cat_idx = self.category_index.read().await;
@@ -405,6 +676,9 @@ impl LongTermMemory {
     /// Add a troubleshooting case.
     pub async fn add_case(&self, case: TroubleshootingCase) -> Result<()> {
         let id = case.id.clone();
+        if let Some(store) = &self.store {
+            store.save_case(&case)?;
+        }
         self.cases.write().await.insert(id, case);
         Ok(())
     }
@@ -434,7 +708,18 @@ impl LongTermMemory {
     pub async fn update(&self, id: &str, content: String) -> Result<()> {
         let mut knowledge = self.knowledge.write().await;
         if let Some(entry) = knowledge.get_mut(id) {
-            entry.update_content(content);
+            let mut updated = entry.clone();
+            updated.update_content(content);
+            // The embedding no longer matches the content
+            updated.embedding = None;
+            if let Some(store) = &self.store {
+                store.delete_knowledge(id).await?;
+                store.save_knowledge(&updated).await?;
+            }
+            let mut bm25 = self.bm25_index.write().await;
+            bm25.remove_document(id);
+            bm25.add_document(id, &updated.search_text());
+            *entry = updated;
             Ok(())
         } else {
             Err(MemoryError::NotFound(id.to_string()))
@@ -444,6 +729,12 @@ impl LongTermMemory {
     /// Delete a knowledge entry.
     pub async fn delete(&self, id: &str) -> Result<()> {
         let mut knowledge = self.knowledge.write().await;
+        if !knowledge.contains_key(id) {
+            return Err(MemoryError::NotFound(id.to_string()));
+        }
+        if let Some(store) = &self.store {
+            store.delete_knowledge(id).await?;
+        }
         if knowledge.remove(id).is_some() {
             // Remove from indices
             let mut cat_idx = self.category_index.write().await;
@@ -461,6 +752,8 @@ impl LongTermMemory {
                 ids.retain(|x| x != id);
             }
 
+            self.bm25_index.write().await.remove_document(id);
+
             Ok(())
         } else {
             Err(MemoryError::NotFound(id.to_string()))
@@ -479,11 +772,17 @@ impl LongTermMemory {
 
     /// Clear all knowledge.
     pub async fn clear(&self) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.clear_long_term().await {
+                tracing::warn!(category = "memory", error = %e, "Failed to clear persisted knowledge");
+            }
+        }
         self.knowledge.write().await.clear();
         self.cases.write().await.clear();
         self.category_index.write().await.clear();
         self.tag_index.write().await.clear();
         self.device_index.write().await.clear();
+        self.bm25_index.write().await.clear();
     }
 
     /// Get all knowledge entries.
@@ -622,6 +921,37 @@ mod tests {
         assert!(results[0].title.contains("Temperature"));
     }
 
+    #[tokio::test]
+    async fn test_search_bm25() {
+        let memory = LongTermMemory::new();
+
+        let xr500 = KnowledgeEntry::new(
+            "XR-500 Pump",
+            "Replace the seal kit",
+            KnowledgeCategory::Troubleshooting,
+        );
+        let xr700 = KnowledgeEntry::new(
+            "XR-700 Pump",
+            "Tighten the housing",
+            KnowledgeCategory::Troubleshooting,
+        );
+        memory.add(xr500.clone()).await.unwrap();
+        memory.add(xr700.clone()).await.unwrap();
+
+        let results = memory.search_bm25("xr-700 pump", 10).await;
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].0.id, xr700.id);
+
+        // The index follows updates and deletes
+        memory
+            .update(&xr500.id, "Check the impeller".to_string())
+            .await
+            .unwrap();
+        assert!(memory.search_bm25("seal", 10).await.is_empty());
+        memory.delete(&xr700.id).await.unwrap();
+        assert!(memory.search_bm25("xr-700", 10).await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_get_by_category() {
         let memory = LongTermMemory::new();
@@ -784,6 +1114,92 @@ mod tests {
         assert!(most_accessed[0].access_count >= 5);
     }
 
+    #[tokio::test]
+    async fn test_replace_stores_new_entries_before_removing_old() {
+        let store = MemoryStore::memory().unwrap();
+        let memory = LongTermMemory::new()
+            .with_max_knowledge(3)
+            .with_store(store.clone())
+            .await
+            .unwrap();
+        let old = KnowledgeEntry::new("Old", "Old content", KnowledgeCategory::DeviceManual)
+            .with_tags(vec!["pump".to_string()]);
+        memory.add(old.clone()).await.unwrap();
+        memory
+            .add(KnowledgeEntry::new(
+                "Other",
+                "Other",
+                KnowledgeCategory::FAQ,
+            ))
+            .await
+            .unwrap();
+
+        // Too many new entries: nothing changes
+        let new: Vec<_> = (0..3)
+            .map(|i| {
+                KnowledgeEntry::new(format!("New {}", i), "New", KnowledgeCategory::DeviceManual)
+                    .with_tags(vec!["pump".to_string()])
+            })
+            .collect();
+        assert!(memory
+            .replace(std::slice::from_ref(&old.id), new.clone())
+            .await
+            .is_err());
+        assert!(memory.get_all().await.iter().any(|e| e.id == old.id));
+
+        memory
+            .replace(std::slice::from_ref(&old.id), new[..2].to_vec())
+            .await
+            .unwrap();
+        let by_tag = memory.get_by_tag("pump").await;
+        assert_eq!(by_tag.len(), 2);
+        assert!(by_tag.iter().all(|e| e.title.starts_with("New")));
+
+        let reopened = LongTermMemory::new().with_store(store).await.unwrap();
+        assert_eq!(reopened.len().await, 3);
+        assert_eq!(reopened.search_bm25("old content", 5).await.len(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_persists_across_reopen() {
+        let store = MemoryStore::memory().unwrap();
+        let memory = LongTermMemory::new()
+            .with_store(store.clone())
+            .await
+            .unwrap();
+
+        let entry = KnowledgeEntry::new("Pump Manual", "Content", KnowledgeCategory::DeviceManual)
+            .with_tags(vec!["pump".to_string()]);
+        let case = TroubleshootingCase::new("Pump stalls").with_symptom("No flow");
+        memory.add(entry.clone()).await.unwrap();
+        memory.add_case(case.clone()).await.unwrap();
+        memory.get(&entry.id).await;
+
+        // Reads don't write; the access count is persisted on flush
+        let reopened = LongTermMemory::new()
+            .with_store(store.clone())
+            .await
+            .unwrap();
+        assert_eq!(reopened.get_by_tag("pump").await[0].access_count, 0);
+        drop(reopened);
+        assert_eq!(memory.flush().await.unwrap(), 1);
+        assert_eq!(memory.flush().await.unwrap(), 0);
+        drop(memory);
+
+        let reopened = LongTermMemory::new()
+            .with_store(store.clone())
+            .await
+            .unwrap();
+        let by_tag = reopened.get_by_tag("pump").await;
+        assert_eq!(by_tag.len(), 1);
+        assert_eq!(by_tag[0].access_count, 1);
+        assert!(reopened.get_case(&case.id).await.is_some());
+
+        reopened.delete(&entry.id).await.unwrap();
+        let reopened = LongTermMemory::new().with_store(store).await.unwrap();
+        assert!(reopened.is_empty().await);
+    }
+
     #[tokio::test]
     async fn test_clear() {
         let memory = LongTermMemory::new();
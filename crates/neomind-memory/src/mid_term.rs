@@ -1,7 +1,8 @@
 //! Mid-term memory for recent conversation history.
 //!
 //! Mid-term memory stores recent conversations with vector-based semantic search.
-//! It helps retrieve relevant past conversations based on similarity.
+//! It helps retrieve relevant past conversations based on similarity. With a
+//! [`MemoryStore`] attached, conversations and their embeddings are persisted.
 
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -14,6 +15,7 @@ use super::embeddings::{
     cosine_similarity, create_embedding_model, EmbeddingConfig, EmbeddingModel, SimpleEmbedding,
 };
 use super::error::Result;
+use super::store::MemoryStore;
 
 /// Wrapper to make SimpleEmbedding implement EmbeddingModel.
 pub struct SimpleEmbeddingWrapper(pub SimpleEmbedding);
@@ -129,6 +131,8 @@ pub struct MidTermMemory {
     session_index: Arc<RwLock<HashMap<String, Vec<String>>>>,
     /// BM25 full-text search index
     bm25_index: Arc<RwLock<BM25Index>>,
+    /// Persistent store (write-through)
+    store: Option<Arc<MemoryStore>>,
 }
 
 impl MidTermMemory {
@@ -139,8 +143,13 @@ impl MidTermMemory {
 
     /// Create a new mid-term memory with custom embedding configuration.
     pub fn with_embedding_config(config: EmbeddingConfig) -> Self {
-        let embedding = create_embedding_model(config).unwrap_or_else(|_| {
+        let embedding = create_embedding_model(config).unwrap_or_else(|e| {
             // Fallback to simple embedding on error
+            tracing::warn!(
+                category = "memory",
+                error = %e,
+                "Failed to create embedding model, using hash-based embeddings"
+            );
             Box::new(SimpleEmbeddingWrapper(SimpleEmbedding::default()))
         });
 
@@ -150,6 +159,7 @@ impl MidTermMemory {
             embedding: Arc::from(embedding),
             session_index: Arc::new(RwLock::new(HashMap::new())),
             bm25_index: Arc::new(RwLock::new(BM25Index::new())),
+            store: None,
         }
     }
 
@@ -165,6 +175,54 @@ impl MidTermMemory {
         self
     }
 
+    /// Set the embedding model.
+    pub fn with_embedding_model(mut self, embedding: Arc<dyn EmbeddingModel>) -> Self {
+        self.embedding = embedding;
+        self
+    }
+
+    /// Get the embedding model.
+    pub fn embedding_model(&self) -> Arc<dyn EmbeddingModel> {
+        self.embedding.clone()
+    }
+
+    /// Load conversations from a store and write all changes through to it.
+    ///
+    /// Call this after choosing the embedding model: stored embeddings of a
+    /// different dimension are dropped, so those entries are only found by
+    /// BM25 search.
+    pub async fn with_store(mut self, store: Arc<MemoryStore>) -> Result<Self> {
+        let dimension = self.embedding.dimension();
+        let mut entries = HashMap::new();
+        let mut session_index: HashMap<String, Vec<String>> = HashMap::new();
+        let mut bm25 = BM25Index::new();
+
+        for mut entry in store.load_conversations().await? {
+            if entry
+                .embedding
+                .as_ref()
+                .is_some_and(|e| e.len() != dimension)
+            {
+                entry.embedding = None;
+            }
+            bm25.add_document(
+                &entry.id,
+                &extract_text_for_bm25(&entry.user_input, &entry.assistant_response),
+            );
+            session_index
+                .entry(entry.session_id.clone())
+                .or_default()
+                .push(entry.id.clone());
+            entries.insert(entry.id.clone(), entry);
+        }
+
+        self.entries = Arc::new(RwLock::new(entries));
+        self.session_index = Arc::new(RwLock::new(session_index));
+        self.bm25_index = Arc::new(RwLock::new(bm25));
+        self.store = Some(store);
+        Ok(self)
+    }
+
     /// Add a conversation entry.
     pub async fn add(&self, entry: ConversationEntry) -> Result<()> {
         let id = entry.id.clone();
@@ -194,6 +252,13 @@ impl MidTermMemory {
             }
         };
 
+        if let Some(store) = &self.store {
+            if let Some(evicted) = &evicted_id {
+                store.delete_conversation(evicted).await?;
+            }
+            store.save_conversation(&entry_with_embed).await?;
+        }
+
         // Insert new entry
         {
             let mut entries = self.entries.write().await;
@@ -254,6 +319,15 @@ impl MidTermMemory {
     /// Search for similar conversations using semantic search.
     pub async fn search(&self, query: &str, top_k: usize) -> Vec<SearchResult> {
         let query_embedding: Vec<f32> = self.embedding.embed(query).await.unwrap_or_default();
+        self.search_by_embedding(&query_embedding, top_k).await
+    }
+
+    /// Search for similar conversations with an already embedded query.
+    pub async fn search_by_embedding(
+        &self,
+        query_embedding: &[f32],
+        top_k: usize,
+    ) -> Vec<SearchResult> {
         let entries = self.entries.read().await;
 
         let mut results: Vec<SearchResult> = entries
@@ -261,7 +335,7 @@ impl MidTermMemory {
             .filter_map(|entry| {
                 entry.embedding.as_ref().map(|emb| SearchResult {
                     entry: entry.clone(),
-                    score: cosine_similarity(&query_embedding, emb),
+                    score: cosine_similarity(query_embedding, emb),
                 })
             })
             .collect();
@@ -368,6 +442,11 @@ impl MidTermMemory {
 
     /// Clear all entries.
     pub async fn clear(&self) {
+        if let Some(store) = &self.store {
+            if let Err(e) = store.clear_conversations().await {
+                tracing::warn!(category = "memory", error = %e, "Failed to clear persisted conversations");
+            }
+        }
         self.entries.write().await.clear();
         self.session_index.write().await.clear();
         self.bm25_index.write().await.clear();
@@ -383,6 +462,9 @@ impl MidTermMemory {
         let count = ids.len();
 
         for id in &ids {
+            if let Some(store) = &self.store {
+                store.delete_conversation(id).await?;
+            }
             entries.remove(id);
             bm25.remove_document(id);
         }
@@ -528,6 +610,29 @@ mod tests {
         assert_eq!(recent.len(), 3);
     }
 
+    #[tokio::test]
+    async fn test_persists_across_reopen() {
+        let store = MemoryStore::memory().unwrap();
+        let memory = MidTermMemory::new()
+            .with_max_entries(2)
+            .with_store(store.clone())
+            .await
+            .unwrap();
+        for i in 0..3 {
+            let mut entry = ConversationEntry::new("session1", format!("pump {}", i), "ok");
+            entry.timestamp = i;
+            memory.add(entry).await.unwrap();
+        }
+        drop(memory);
+
+        let reopened = MidTermMemory::new().with_store(store).await.unwrap();
+        let history = reopened.get_by_session("session1").await;
+        assert_eq!(history.len(), 2);
+        assert_eq!(history[0].user_input, "pump 1");
+        assert!(history.iter().all(|e| e.embedding.is_some()));
+        assert!(!reopened.search_bm25("pump", 5).await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_clear() {
         let memory = MidTermMemory::new();
@@ -0,0 +1,977 @@
+//! Hybrid retrieval over long-term knowledge and mid-term conversations.
+//!
+//! [`HybridRetriever`] runs BM25 and vector search in parallel, fuses the
+//! rankings with reciprocal rank fusion or a weighted score sum
+//! ([`FusionMethod`]), applies metadata filters ([`RetrievalFilter`]) and can
+//! hand the fused candidates to a pluggable [`Reranker`]. BM25 finds exact
+//! terms such as device model numbers that embeddings tend to blur; vector
+//! search finds paraphrases.
+//!
+//! [`HybridRetriever::evaluate`] scores retrieval against a labelled query set
+//! (recall, precision, MRR and nDCG at k).
+//!
+//! ## Example
+//!
+//! ```rust,no_run
+//! use neomind_memory::{RetrievalFilter, RetrievalQuery, TieredMemory};
+//!
+//! # async fn example() -> neomind_memory::Result<()> {
+//! let memory = TieredMemory::new();
+//! let query = RetrievalQuery::new("XR-500 pressure fault", 5)
+//!     .with_filter(RetrievalFilter::new().with_device_types(vec!["pump".to_string()]));
+//! for item in memory.retrieve(&query).await? {
+//!     println!("{:.3} {}", item.score, item.title());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+
+use super::embeddings::EmbeddingModel;
+use super::error::Result;
+use super::long_term::{KnowledgeCategory, KnowledgeEntry, LongTermMemory};
+use super::mid_term::{ConversationEntry, MidTermMemory};
+use super::tiered::SearchMethod;
+
+/// Default RRF rank constant.
+pub const DEFAULT_RRF_K: f32 = 60.0;
+
+/// Default number of candidates fetched per ranking, as a multiple of `top_k`.
+pub const DEFAULT_CANDIDATE_MULTIPLIER: usize = 4;
+
+/// How lexical and semantic rankings are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FusionMethod {
+    /// Reciprocal rank fusion: `sum(1 / (rrf_k + rank))` over all rankings
+    #[default]
+    Rrf,
+    /// `semantic_weight * cosine + bm25_weight * normalized_bm25`
+    Weighted,
+}
+
+impl FusionMethod {
+    /// Convert to string.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rrf => "rrf",
+            Self::Weighted => "weighted",
+        }
+    }
+}
+
+/// Retrieval settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievalConfig {
+    /// Fusion method
+    pub fusion: FusionMethod,
+    /// RRF rank constant
+    pub rrf_k: f32,
+    /// Semantic weight for weighted fusion
+    pub semantic_weight: f32,
+    /// BM25 weight for weighted fusion
+    pub bm25_weight: f32,
+    /// Candidates fetched per ranking, as a multiple of `top_k`
+    pub candidate_multiplier: usize,
+}
+
+impl Default for RetrievalConfig {
+    fn default() -> Self {
+        Self {
+            fusion: FusionMethod::Rrf,
+            rrf_k: DEFAULT_RRF_K,
+            semantic_weight: 0.7,
+            bm25_weight: 0.3,
+            candidate_multiplier: DEFAULT_CANDIDATE_MULTIPLIER,
+        }
+    }
+}
+
+/// Where a retrieved item comes from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetrievalSource {
+    /// Long-term knowledge base
+    Knowledge,
+    /// Mid-term conversation history
+    Conversation,
+}
+
+/// Metadata filters applied before fusion.
+///
+/// Device type and category filters only match knowledge entries, so setting
+/// either excludes conversations.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetrievalFilter {
+    /// Device types; matches entry tags or ingested `device_types` metadata
+    #[serde(default)]
+    pub device_types: Vec<String>,
+    /// Knowledge categories
+    #[serde(default)]
+    pub categories: Vec<KnowledgeCategory>,
+    /// Only items created or updated at or after this Unix timestamp
+    #[serde(default)]
+    pub since: Option<i64>,
+    /// Restrict to one source
+    #[serde(default)]
+    pub source: Option<RetrievalSource>,
+}
+
+impl RetrievalFilter {
+    /// Create an empty filter.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the device types.
+    pub fn with_device_types(mut self, device_types: Vec<String>) -> Self {
+        self.device_types = device_types;
+        self
+    }
+
+    /// Set the categories.
+    pub fn with_categories(mut self, categories: Vec<KnowledgeCategory>) -> Self {
+        self.categories = categories;
+        self
+    }
+
+    /// Only match items newer than a Unix timestamp.
+    pub fn since(mut self, timestamp: i64) -> Self {
+        self.since = Some(timestamp);
+        self
+    }
+
+    /// Restrict to one source.
+    pub fn with_source(mut self, source: RetrievalSource) -> Self {
+        self.source = Some(source);
+        self
+    }
+
+    /// Check if no filter is set.
+    pub fn is_empty(&self) -> bool {
+        self.device_types.is_empty()
+            && self.categories.is_empty()
+            && self.since.is_none()
+            && self.source.is_none()
+    }
+
+    fn allows_source(&self, source: RetrievalSource) -> bool {
+        match source {
+            RetrievalSource::Knowledge => self.source != Some(RetrievalSource::Conversation),
+            RetrievalSource::Conversation => {
+                self.source != Some(RetrievalSource::Knowledge)
+                    && self.device_types.is_empty()
+                    && self.categories.is_empty()
+            }
+        }
+    }
+
+    fn matches_knowledge(&self, entry: &KnowledgeEntry) -> bool {
+        if self.since.is_some_and(|since| entry.updated_at < since) {
+            return false;
+        }
+        if !self.categories.is_empty() && !self.categories.contains(&entry.category) {
+            return false;
+        }
+        if self.device_types.is_empty() {
+            return true;
+        }
+
+        let ingested: Vec<&str> = entry
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("device_types"))
+            .and_then(|v| v.as_array())
+            .map(|types| types.iter().filter_map(|t| t.as_str()).collect())
+            .unwrap_or_default();
+        self.device_types.iter().any(|wanted| {
+            entry
+                .tags
+                .iter()
+                .map(String::as_str)
+                .chain(ingested.iter().copied())
+                .any(|t| t.eq_ignore_ascii_case(wanted))
+        })
+    }
+
+    fn matches_conversation(&self, entry: &ConversationEntry) -> bool {
+        self.since.is_none_or(|since| entry.timestamp >= since)
+    }
+}
+
+/// A retrieval request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievalQuery {
+    /// Query text
+    pub text: String,
+    /// Maximum number of results
+    pub top_k: usize,
+    /// Lexical, semantic or hybrid retrieval
+    pub method: SearchMethod,
+    /// Metadata filters
+    #[serde(default)]
+    pub filter: RetrievalFilter,
+}
+
+impl RetrievalQuery {
+    /// Create a hybrid query.
+    pub fn new(text: impl Into<String>, top_k: usize) -> Self {
+        Self {
+            text: text.into(),
+            top_k,
+            method: SearchMethod::Hybrid,
+            filter: RetrievalFilter::default(),
+        }
+    }
+
+    /// Set the search method.
+    pub fn with_method(mut self, method: SearchMethod) -> Self {
+        self.method = method;
+        self
+    }
+
+    /// Set the filter.
+    pub fn with_filter(mut self, filter: RetrievalFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+}
+
+/// Retrieved content.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "source", content = "entry", rename_all = "snake_case")]
+pub enum RetrievedContent {
+    /// Long-term knowledge entry
+    Knowledge(KnowledgeEntry),
+    /// Mid-term conversation
+    Conversation(ConversationEntry),
+}
+
+impl RetrievedContent {
+    /// Entry ID.
+    pub fn id(&self) -> &str {
+        match self {
+            Self::Knowledge(entry) => &entry.id,
+            Self::Conversation(entry) => &entry.id,
+        }
+    }
+
+    /// Source of the entry.
+    pub fn source(&self) -> RetrievalSource {
+        match self {
+            Self::Knowledge(_) => RetrievalSource::Knowledge,
+            Self::Conversation(_) => RetrievalSource::Conversation,
+        }
+    }
+}
+
+/// A retrieved item with its fused score.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetrievedItem {
+    /// Entry ID
+    pub id: String,
+    /// Fused score (or the reranker's score), higher is better
+    pub score: f32,
+    /// Position in the BM25 ranking of its source (1-based)
+    pub lexical_rank: Option<usize>,
+    /// Position in the vector ranking of its source (1-based)
+    pub semantic_rank: Option<usize>,
+    /// The entry
+    #[serde(flatten)]
+    pub content: RetrievedContent,
+}
+
+impl RetrievedItem {
+    /// Source of the item.
+    pub fn source(&self) -> RetrievalSource {
+        self.content.source()
+    }
+
+    /// Short title: the knowledge title or the user question.
+    pub fn title(&self) -> &str {
+        match &self.content {
+            RetrievedContent::Knowledge(entry) => &entry.title,
+            RetrievedContent::Conversation(entry) => &entry.user_input,
+        }
+    }
+
+    /// Full text of the item, as passed to rerankers.
+    pub fn text(&self) -> String {
+        match &self.content {
+            RetrievedContent::Knowledge(entry) => format!("{}\n{}", entry.title, entry.content),
+            RetrievedContent::Conversation(entry) => {
+                format!("{}\n{}", entry.user_input, entry.assistant_response)
+            }
+        }
+    }
+
+    /// Source document and page for ingested knowledge.
+    pub fn citation(&self) -> Option<String> {
+        match &self.content {
+            RetrievedContent::Knowledge(entry) => entry.citation(),
+            RetrievedContent::Conversation(_) => None,
+        }
+    }
+}
+
+/// Reorders fused candidates, e.g. with a cross-encoder or an LLM.
+///
+/// The reranker receives up to `top_k * candidate_multiplier` candidates in
+/// fused order and returns them in its preferred order; it may rescore or
+/// drop items. The result is truncated to `top_k`. If reranking fails, the
+/// fused order is kept.
+#[async_trait]
+pub trait Reranker: Send + Sync {
+    /// Rerank candidates for a query.
+    async fn rerank(
+        &self,
+        query: &str,
+        candidates: Vec<RetrievedItem>,
+    ) -> Result<Vec<RetrievedItem>>;
+
+    /// Reranker name, for logs.
+    fn name(&self) -> &str;
+}
+
+/// A query with the IDs of the entries that should be retrieved for it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LabelledQuery {
+    /// Query text
+    pub query: String,
+    /// IDs of relevant entries
+    pub relevant: Vec<String>,
+    /// Filter applied to the query
+    #[serde(default)]
+    pub filter: RetrievalFilter,
+}
+
+/// Retrieval quality over a labelled query set, averaged over queries.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RetrievalMetrics {
+    /// Number of queries evaluated
+    pub queries: usize,
+    /// Cut-off rank
+    pub k: usize,
+    /// Share of relevant entries found in the top k
+    pub recall_at_k: f64,
+    /// Share of the top k that is relevant
+    pub precision_at_k: f64,
+    /// Share of queries with at least one relevant entry in the top k
+    pub hit_rate: f64,
+    /// Mean reciprocal rank of the first relevant entry
+    pub mrr: f64,
+    /// Normalized discounted cumulative gain at k
+    pub ndcg_at_k: f64,
+}
+
+/// One source's ranking: entries with their raw scores, best first.
+type Ranking = Vec<(RetrievedContent, f32)>;
+
+/// A candidate collected from the rankings before fusion.
+struct Candidate {
+    content: RetrievedContent,
+    lexical: Option<(usize, f32)>,
+    semantic: Option<(usize, f32)>,
+}
+
+/// Retrieves from long-term and mid-term memory with hybrid search.
+pub struct HybridRetriever {
+    embedding: Arc<dyn EmbeddingModel>,
+    long_term: Option<Arc<LongTermMemory>>,
+    mid_term: Option<Arc<MidTermMemory>>,
+    config: RetrievalConfig,
+    reranker: Option<Arc<dyn Reranker>>,
+}
+
+impl HybridRetriever {
+    /// Create a retriever without sources.
+    pub fn new(embedding: Arc<dyn EmbeddingModel>) -> Self {
+        Self {
+            embedding,
+            long_term: None,
+            mid_term: None,
+            config: RetrievalConfig::default(),
+            reranker: None,
+        }
+    }
+
+    /// Retrieve from long-term knowledge.
+    pub fn with_knowledge(mut self, long_term: Arc<LongTermMemory>) -> Self {
+        self.long_term = Some(long_term);
+        self
+    }
+
+    /// Retrieve from mid-term conversations.
+    pub fn with_conversations(mut self, mid_term: Arc<MidTermMemory>) -> Self {
+        self.mid_term = Some(mid_term);
+        self
+    }
+
+    /// Set the retrieval configuration.
+    pub fn with_config(mut self, config: RetrievalConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// Set the reranker.
+    pub fn with_reranker(mut self, reranker: Option<Arc<dyn Reranker>>) -> Self {
+        self.reranker = reranker;
+        self
+    }
+
+    /// Get the configuration.
+    pub fn config(&self) -> &RetrievalConfig {
+        &self.config
+    }
+
+    /// Retrieve the best matching items for a query.
+    pub async fn retrieve(&self, query: &RetrievalQuery) -> Result<Vec<RetrievedItem>> {
+        if query.top_k == 0 || query.text.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Filtered-out entries would otherwise use up the candidate pool
+        let pool = if query.filter.is_empty() {
+            query
+                .top_k
+                .saturating_mul(self.config.candidate_multiplier.max(1))
+        } else {
+            usize::MAX
+        };
+        let lexical = query.method != SearchMethod::Semantic;
+        let semantic = query.method != SearchMethod::BM25;
+
+        let (lexical_lists, semantic_lists) = tokio::join!(
+            async {
+                if lexical {
+                    self.lexical_rankings(&query.text, pool, &query.filter)
+                        .await
+                } else {
+                    Vec::new()
+                }
+            },
+            async {
+                if semantic {
+                    self.semantic_rankings(&query.text, pool, &query.filter)
+                        .await
+                } else {
+                    Vec::new()
+                }
+            }
+        );
+
+        let mut items = self.fuse(lexical_lists, semantic_lists);
+        items.truncate(pool);
+
+        if let Some(reranker) = &self.reranker {
+            let fused = items.clone();
+            items = match reranker.rerank(&query.text, items).await {
+                Ok(reranked) => reranked,
+                Err(e) => {
+                    tracing::warn!(
+                        category = "memory",
+                        reranker = reranker.name(),
+                        error = %e,
+                        "Reranking failed, keeping fused order"
+                    );
+                    fused
+                }
+            };
+        }
+
+        items.truncate(query.top_k);
+        Ok(items)
+    }
+
+    /// Evaluate retrieval against a labelled query set.
+    pub async fn evaluate(
+        &self,
+        queries: &[LabelledQuery],
+        k: usize,
+        method: SearchMethod,
+    ) -> Result<RetrievalMetrics> {
+        let mut metrics = RetrievalMetrics {
+            k,
+            ..Default::default()
+        };
+
+        for labelled in queries.iter().filter(|q| !q.relevant.is_empty()) {
+            let query = RetrievalQuery::new(&labelled.query, k)
+                .with_method(method)
+                .with_filter(labelled.filter.clone());
+            let ranked: Vec<String> = self
+                .retrieve(&query)
+                .await?
+                .into_iter()
+                .map(|item| item.id)
+                .collect();
+            let relevant: HashSet<&str> = labelled.relevant.iter().map(String::as_str).collect();
+
+            let hits: Vec<bool> = ranked
+                .iter()
+                .take(k)
+                .map(|id| relevant.contains(id.as_str()))
+                .collect();
+            let found = hits.iter().filter(|hit| **hit).count();
+
+            metrics.queries += 1;
+            metrics.recall_at_k += found as f64 / relevant.len() as f64;
+            metrics.precision_at_k += found as f64 / k.max(1) as f64;
+            if found > 0 {
+                metrics.hit_rate += 1.0;
+            }
+            if let Some(first) = hits.iter().position(|hit| *hit) {
+                metrics.mrr += 1.0 / (first + 1) as f64;
+            }
+
+            let dcg: f64 = hits
+                .iter()
+                .enumerate()
+                .filter(|(_, hit)| **hit)
+                .map(|(i, _)| 1.0 / (i as f64 + 2.0).log2())
+                .sum();
+            let ideal: f64 = (0..relevant.len().min(k))
+                .map(|i| 1.0 / (i as f64 + 2.0).log2())
+                .sum();
+            if ideal > 0.0 {
+                metrics.ndcg_at_k += dcg / ideal;
+            }
+        }
+
+        if metrics.queries > 0 {
+            let n = metrics.queries as f64;
+            metrics.recall_at_k /= n;
+            metrics.precision_at_k /= n;
+            metrics.hit_rate /= n;
+            metrics.mrr /= n;
+            metrics.ndcg_at_k /= n;
+        }
+        Ok(metrics)
+    }
+
+    /// BM25 rankings, one per source.
+    async fn lexical_rankings(
+        &self,
+        text: &str,
+        pool: usize,
+        filter: &RetrievalFilter,
+    ) -> Vec<Ranking> {
+        let knowledge = async {
+            match &self.long_term {
+                Some(long_term) if filter.allows_source(RetrievalSource::Knowledge) => {
+                    Self::knowledge_ranking(long_term.search_bm25(text, pool).await, filter)
+                }
+                _ => Vec::new(),
+            }
+        };
+        let conversations = async {
+            match &self.mid_term {
+                Some(mid_term) if filter.allows_source(RetrievalSource::Conversation) => {
+                    Self::conversation_ranking(mid_term.search_bm25(text, pool).await, filter)
+                }
+                _ => Vec::new(),
+            }
+        };
+
+        let (knowledge, conversations) = tokio::join!(knowledge, conversations);
+        vec![knowledge, conversations]
+    }
+
+    /// Vector rankings, one per source.
+    async fn semantic_rankings(
+        &self,
+        text: &str,
+        pool: usize,
+        filter: &RetrievalFilter,
+    ) -> Vec<Ranking> {
+        let embedding = match self.embedding.embed(text).await {
+            Ok(embedding) => embedding,
+            Err(e) => {
+                tracing::warn!(category = "memory", error = %e, "Query embedding failed, skipping vector search");
+                return Vec::new();
+            }
+        };
+
+        let knowledge = async {
+            match &self.long_term {
+                Some(long_term) if filter.allows_source(RetrievalSource::Knowledge) => {
+                    Self::knowledge_ranking(
+                        long_term.search_similar(&embedding, pool).await,
+                        filter,
+                    )
+                }
+                _ => Vec::new(),
+            }
+        };
+        let conversations = async {
+            match &self.mid_term {
+                Some(mid_term) if filter.allows_source(RetrievalSource::Conversation) => {
+                    Self::conversation_ranking(
+                        mid_term.search_by_embedding(&embedding, pool).await,
+                        filter,
+                    )
+                }
+                _ => Vec::new(),
+            }
+        };
+
+        let (knowledge, conversations) = tokio::join!(knowledge, conversations);
+        vec![knowledge, conversations]
+    }
+
+    fn knowledge_ranking(results: Vec<(KnowledgeEntry, f32)>, filter: &RetrievalFilter) -> Ranking {
+        results
+            .into_iter()
+            .filter(|(entry, _)| filter.matches_knowledge(entry))
+            .map(|(entry, score)| (RetrievedContent::Knowledge(entry), score))
+            .collect()
+    }
+
+    fn conversation_ranking(
+        results: Vec<super::mid_term::SearchResult>,
+        filter: &RetrievalFilter,
+    ) -> Ranking {
+        results
+            .into_iter()
+            .filter(|result| filter.matches_conversation(&result.entry))
+            .map(|result| (RetrievedContent::Conversation(result.entry), result.score))
+            .collect()
+    }
+
+    /// Fuse the rankings into one list, best first.
+    ///
+    /// Each source is ranked separately: BM25 scores from different indexes
+    /// are not comparable, and RRF only needs ranks.
+    fn fuse(
+        &self,
+        lexical_lists: Vec<Ranking>,
+        semantic_lists: Vec<Ranking>,
+    ) -> Vec<RetrievedItem> {
+        let mut candidates: HashMap<(RetrievalSource, String), Candidate> = HashMap::new();
+
+        for (lists, is_lexical) in [(lexical_lists, true), (semantic_lists, false)] {
+            for list in lists {
+                // Normalize BM25 scores per ranking for weighted fusion
+                let max_score = list.iter().map(|(_, score)| *score).fold(0.0_f32, f32::max);
+                for (rank, (content, score)) in list.into_iter().enumerate() {
+                    let key = (content.source(), content.id().to_string());
+                    let candidate = candidates.entry(key).or_insert_with(|| Candidate {
+                        content,
+                        lexical: None,
+                        semantic: None,
+                    });
+                    if is_lexical {
+                        let normalized = if max_score > 0.0 {
+                            score / max_score
+                        } else {
+                            0.0
+                        };
+                        candidate.lexical = Some((rank + 1, normalized));
+                    } else {
+                        candidate.semantic = Some((rank + 1, score));
+                    }
+                }
+            }
+        }
+
+        let mut items: Vec<RetrievedItem> = candidates
+            .into_iter()
+            .map(|((_, id), candidate)| {
+                let score = match self.config.fusion {
+                    FusionMethod::Rrf => [candidate.lexical, candidate.semantic]
+                        .iter()
+                        .flatten()
+                        .map(|(rank, _)| 1.0 / (self.config.rrf_k + *rank as f32))
+                        .sum(),
+                    FusionMethod::Weighted => {
+                        candidate.semantic.map_or(0.0, |(_, s)| s) * self.config.semantic_weight
+                            + candidate.lexical.map_or(0.0, |(_, s)| s) * self.config.bm25_weight
+                    }
+                };
+                RetrievedItem {
+                    id,
+                    score,
+                    lexical_rank: candidate.lexical.map(|(rank, _)| rank),
+                    semantic_rank: candidate.semantic.map(|(rank, _)| rank),
+                    content: candidate.content,
+                }
+            })
+            .collect();
+
+        items.sort_by(|a, b| {
+            b.score
+                .partial_cmp(&a.score)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| a.id.cmp(&b.id))
+        });
+        items
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::MemoryError;
+
+    /// Embeds texts by concept: synonyms share a dimension and every token
+    /// with a digit maps to the same dimension, so model numbers blur.
+    struct ConceptEmbedding;
+
+    #[async_trait]
+    impl EmbeddingModel for ConceptEmbedding {
+        async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+            let mut embedding = vec![0.0; 4];
+            for word in text.to_lowercase().split_whitespace() {
+                let dim = match word {
+                    w if w.chars().any(|c| c.is_ascii_digit()) => 0,
+                    "pump" | "impeller" => 1,
+                    "leak" | "leaking" | "drip" => 2,
+                    _ => 3,
+                };
+                embedding[dim] += 1.0;
+            }
+            Ok(embedding)
+        }
+
+        async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+            let mut embeddings = Vec::new();
+            for text in texts {
+                embeddings.push(self.embed(text).await?);
+            }
+            Ok(embeddings)
+        }
+
+        fn dimension(&self) -> usize {
+            4
+        }
+
+        fn model_name(&self) -> &str {
+            "concept"
+        }
+    }
+
+    struct ReverseReranker;
+
+    #[async_trait]
+    impl Reranker for ReverseReranker {
+        async fn rerank(
+            &self,
+            _query: &str,
+            mut candidates: Vec<RetrievedItem>,
+        ) -> Result<Vec<RetrievedItem>> {
+            candidates.reverse();
+            Ok(candidates)
+        }
+
+        fn name(&self) -> &str {
+            "reverse"
+        }
+    }
+
+    struct FailingReranker;
+
+    #[async_trait]
+    impl Reranker for FailingReranker {
+        async fn rerank(&self, _query: &str, _: Vec<RetrievedItem>) -> Result<Vec<RetrievedItem>> {
+            Err(MemoryError::Other("unavailable".to_string()))
+        }
+
+        fn name(&self) -> &str {
+            "failing"
+        }
+    }
+
+    async fn knowledge(
+        long_term: &LongTermMemory,
+        id: &str,
+        text: &str,
+        tags: &[&str],
+        category: KnowledgeCategory,
+    ) {
+        let mut entry = KnowledgeEntry::new(id, text, category)
+            .with_tags(tags.iter().map(|t| t.to_string()).collect())
+            .with_embedding(ConceptEmbedding.embed(text).await.unwrap());
+        entry.id = id.to_string();
+        long_term.add(entry).await.unwrap();
+    }
+
+    async fn retriever() -> HybridRetriever {
+        let long_term = Arc::new(LongTermMemory::new());
+        knowledge(
+            &long_term,
+            "xr500",
+            "XR-500 pump seal leak replace the seal kit",
+            &["pump"],
+            KnowledgeCategory::Troubleshooting,
+        )
+        .await;
+        knowledge(
+            &long_term,
+            "xr700",
+            "XR-700 pump seal leak tighten the housing",
+            &["pump"],
+            KnowledgeCategory::Troubleshooting,
+        )
+        .await;
+        knowledge(
+            &long_term,
+            "valve",
+            "V-20 valve leak check the gasket",
+            &["valve"],
+            KnowledgeCategory::DeviceManual,
+        )
+        .await;
+
+        HybridRetriever::new(Arc::new(ConceptEmbedding)).with_knowledge(long_term)
+    }
+
+    fn ids(items: &[RetrievedItem]) -> Vec<&str> {
+        items.iter().map(|item| item.id.as_str()).collect()
+    }
+
+    #[tokio::test]
+    async fn test_hybrid_finds_model_numbers() {
+        let retriever = retriever().await;
+        let query = RetrievalQuery::new("XR-700 leaking", 2);
+
+        let items = retriever.retrieve(&query).await.unwrap();
+        assert_eq!(items[0].id, "xr700");
+        assert_eq!(items[0].lexical_rank, Some(1));
+        assert!(items[0].semantic_rank.is_some());
+
+        // Embeddings alone cannot tell the models apart
+        let semantic = retriever
+            .retrieve(&query.clone().with_method(SearchMethod::Semantic))
+            .await
+            .unwrap();
+        assert!(semantic.iter().all(|item| item.lexical_rank.is_none()));
+        assert_eq!(semantic[0].semantic_rank, Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_weighted_fusion() {
+        let retriever = retriever().await.with_config(RetrievalConfig {
+            fusion: FusionMethod::Weighted,
+            ..Default::default()
+        });
+
+        let items = retriever
+            .retrieve(&RetrievalQuery::new("XR-500 leak", 3))
+            .await
+            .unwrap();
+        assert_eq!(items[0].id, "xr500");
+        assert!(items[0].score > items[1].score);
+        assert!(items[0].score <= 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_filters() {
+        let retriever = retriever().await;
+
+        let query = RetrievalQuery::new("leak", 5)
+            .with_filter(RetrievalFilter::new().with_device_types(vec!["VALVE".to_string()]));
+        assert_eq!(
+            ids(&retriever.retrieve(&query).await.unwrap()),
+            vec!["valve"]
+        );
+
+        let query = RetrievalQuery::new("leak", 5).with_filter(
+            RetrievalFilter::new().with_categories(vec![KnowledgeCategory::Troubleshooting]),
+        );
+        let items = retriever.retrieve(&query).await.unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(!ids(&items).contains(&"valve"));
+
+        let future = chrono::Utc::now().timestamp() + 3600;
+        let query =
+            RetrievalQuery::new("leak", 5).with_filter(RetrievalFilter::new().since(future));
+        assert!(retriever.retrieve(&query).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_conversations_are_fused() {
+        let mid_term =
+            Arc::new(MidTermMemory::new().with_embedding_model(Arc::new(ConceptEmbedding)));
+        mid_term
+            .add_conversation("s1", "XR-500 is leaking again", "Replaced the seal kit")
+            .await
+            .unwrap();
+        let retriever = retriever().await.with_conversations(mid_term);
+
+        let items = retriever
+            .retrieve(&RetrievalQuery::new("XR-500 leak", 5))
+            .await
+            .unwrap();
+        assert!(items
+            .iter()
+            .any(|item| item.source() == RetrievalSource::Conversation));
+
+        let query = RetrievalQuery::new("XR-500 leak", 5)
+            .with_filter(RetrievalFilter::new().with_source(RetrievalSource::Knowledge));
+        let items = retriever.retrieve(&query).await.unwrap();
+        assert!(items
+            .iter()
+            .all(|item| item.source() == RetrievalSource::Knowledge));
+    }
+
+    #[tokio::test]
+    async fn test_reranker_hook() {
+        let query = RetrievalQuery::new("XR-500 leak", 3);
+        let fused = retriever().await.retrieve(&query).await.unwrap();
+
+        let reranked = retriever()
+            .await
+            .with_reranker(Some(Arc::new(ReverseReranker)))
+            .retrieve(&query)
+            .await
+            .unwrap();
+        let mut expected = ids(&fused);
+        expected.reverse();
+        assert_eq!(ids(&reranked), expected);
+
+        let fallback = retriever()
+            .await
+            .with_reranker(Some(Arc::new(FailingReranker)))
+            .retrieve(&query)
+            .await
+            .unwrap();
+        assert_eq!(ids(&fallback), ids(&fused));
+    }
+
+    #[tokio::test]
+    async fn test_evaluate() {
+        let retriever = retriever().await;
+        let queries = vec![
+            LabelledQuery {
+                query: "XR-700 leaking".to_string(),
+                relevant: vec!["xr700".to_string()],
+                filter: RetrievalFilter::default(),
+            },
+            LabelledQuery {
+                query: "V-20 gasket".to_string(),
+                relevant: vec!["valve".to_string()],
+                filter: RetrievalFilter::default(),
+            },
+        ];
+
+        let metrics = retriever
+            .evaluate(&queries, 1, SearchMethod::Hybrid)
+            .await
+            .unwrap();
+        assert_eq!(metrics.queries, 2);
+        assert_eq!(metrics.recall_at_k, 1.0);
+        assert_eq!(metrics.mrr, 1.0);
+        assert_eq!(metrics.ndcg_at_k, 1.0);
+
+        let lexical = retriever
+            .evaluate(&queries, 1, SearchMethod::BM25)
+            .await
+            .unwrap();
+        assert_eq!(lexical.hit_rate, 1.0);
+    }
+}
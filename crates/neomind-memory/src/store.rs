@@ -0,0 +1,400 @@
+//! Persistent storage for the memory layers.
+//!
+//! Long-term knowledge, troubleshooting cases, mid-term conversations and the
+//! knowledge graph are written through to a redb database. Conversation and
+//! knowledge embeddings are kept in a [`PersistentVectorStore`] next to it.
+//!
+//! The database records a schema version so later format changes can migrate
+//! existing data when the store is opened.
+
+use std::path::Path;
+use std::sync::Arc;
+
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+use neomind_storage::snapshot::begin_write;
+use neomind_storage::{PersistentVectorStore, VectorDocument};
+
+use super::error::{MemoryError, Result};
+use super::graph::{Entity, Relationship};
+use super::long_term::{KnowledgeEntry, TroubleshootingCase};
+use super::mid_term::ConversationEntry;
+
+/// Current on-disk schema version.
+pub const MEMORY_SCHEMA_VERSION: u64 = 1;
+
+const META_TABLE: TableDefinition<&str, u64> = TableDefinition::new("memory_meta");
+const KNOWLEDGE_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("knowledge");
+const CASES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("troubleshooting_cases");
+const CONVERSATIONS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("conversations");
+const ENTITIES_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("graph_entities");
+const RELATIONSHIPS_TABLE: TableDefinition<&str, &[u8]> =
+    TableDefinition::new("graph_relationships");
+
+const SCHEMA_VERSION_KEY: &str = "schema_version";
+
+/// Vector store category for conversation embeddings.
+const CONVERSATION_CATEGORY: &str = "mid_term";
+
+/// Vector store category for knowledge embeddings.
+const KNOWLEDGE_CATEGORY: &str = "long_term";
+
+type StorageResult<T> = std::result::Result<T, neomind_storage::Error>;
+
+/// redb-backed store for the memory layers.
+pub struct MemoryStore {
+    db: Arc<Database>,
+    /// Conversation and knowledge embeddings
+    vectors: Arc<PersistentVectorStore>,
+}
+
+impl MemoryStore {
+    /// Open or create a memory store.
+    ///
+    /// Embeddings are stored in `<name>_vectors.redb` next to `path`.
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Arc<Self>> {
+        let path = path.as_ref();
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| MemoryError::Storage(e.to_string()))?;
+        }
+
+        let db = Database::create(path).map_err(neomind_storage::Error::from)?;
+        migrate(&db)?;
+
+        let stem = path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "memory".to_string());
+        let vectors =
+            PersistentVectorStore::open(path.with_file_name(format!("{}_vectors.redb", stem)))?;
+
+        Ok(Arc::new(Self {
+            db: Arc::new(db),
+            vectors,
+        }))
+    }
+
+    /// Create a store in a temporary file (for tests).
+    pub fn memory() -> Result<Arc<Self>> {
+        let temp_path =
+            std::env::temp_dir().join(format!("memory_test_{}.redb", uuid::Uuid::new_v4()));
+        Self::open(temp_path)
+    }
+
+    /// Schema version recorded in the database.
+    pub fn schema_version(&self) -> Result<u64> {
+        let read_txn = self.db.begin_read().map_err(neomind_storage::Error::from)?;
+        let table = read_txn
+            .open_table(META_TABLE)
+            .map_err(neomind_storage::Error::from)?;
+        let version = table
+            .get(SCHEMA_VERSION_KEY)
+            .map_err(neomind_storage::Error::from)?
+            .map(|v| v.value())
+            .unwrap_or(0);
+        Ok(version)
+    }
+
+    // ===== Long-term memory =====
+
+    /// Save a knowledge entry. Its embedding goes to the vector store.
+    pub async fn save_knowledge(&self, entry: &KnowledgeEntry) -> Result<()> {
+        if let Some(embedding) = &entry.embedding {
+            let doc = VectorDocument::new(entry.id.clone(), embedding.clone())
+                .with_category(KNOWLEDGE_CATEGORY)
+                .with_metadata(serde_json::json!({ "title": entry.title }));
+            self.vectors.insert(doc).await?;
+        }
+
+        let stored = KnowledgeEntry {
+            embedding: None,
+            ..entry.clone()
+        };
+        Ok(self.put(KNOWLEDGE_TABLE, &entry.id, &stored)?)
+    }
+
+    /// Save knowledge records in one transaction, leaving their embeddings as
+    /// they are.
+    pub fn save_knowledge_records(&self, entries: &[KnowledgeEntry]) -> Result<()> {
+        let write_txn = begin_write(&self.db).map_err(neomind_storage::Error::from)?;
+        {
+            let mut table = write_txn
+                .open_table(KNOWLEDGE_TABLE)
+                .map_err(neomind_storage::Error::from)?;
+            for entry in entries {
+                let stored = KnowledgeEntry {
+                    embedding: None,
+                    ..entry.clone()
+                };
+                let value = serde_json::to_vec(&stored).map_err(neomind_storage::Error::from)?;
+                table
+                    .insert(entry.id.as_str(), value.as_slice())
+                    .map_err(neomind_storage::Error::from)?;
+            }
+        }
+        write_txn.commit().map_err(neomind_storage::Error::from)?;
+        Ok(())
+    }
+
+    /// Delete a knowledge entry and its embedding.
+    pub async fn delete_knowledge(&self, id: &str) -> Result<()> {
+        self.remove(KNOWLEDGE_TABLE, id)?;
+        self.vectors.delete(id).await?;
+        Ok(())
+    }
+
+    /// Load all knowledge entries with their embeddings.
+    pub async fn load_knowledge(&self) -> Result<Vec<KnowledgeEntry>> {
+        let mut entries: Vec<KnowledgeEntry> = self.load_all(KNOWLEDGE_TABLE)?;
+        for entry in &mut entries {
+            entry.embedding = self.vectors.get(&entry.id).await?.map(|doc| doc.embedding);
+        }
+        Ok(entries)
+    }
+
+    /// Save a troubleshooting case.
+    pub fn save_case(&self, case: &TroubleshootingCase) -> Result<()> {
+        Ok(self.put(CASES_TABLE, &case.id, case)?)
+    }
+
+    /// Load all troubleshooting cases.
+    pub fn load_cases(&self) -> Result<Vec<TroubleshootingCase>> {
+        Ok(self.load_all(CASES_TABLE)?)
+    }
+
+    /// Delete all knowledge entries, their embeddings and troubleshooting cases.
+    pub async fn clear_long_term(&self) -> Result<()> {
+        let ids = self.keys(KNOWLEDGE_TABLE)?;
+        self.clear_tables(&[KNOWLEDGE_TABLE, CASES_TABLE])?;
+        for id in ids {
+            self.vectors.delete(&id).await?;
+        }
+        Ok(())
+    }
+
+    // ===== Mid-term memory =====
+
+    /// Save a conversation. Its embedding goes to the vector store.
+    pub async fn save_conversation(&self, entry: &ConversationEntry) -> Result<()> {
+        if let Some(embedding) = &entry.embedding {
+            let doc = VectorDocument::new(entry.id.clone(), embedding.clone())
+                .with_category(CONVERSATION_CATEGORY)
+                .with_metadata(serde_json::json!({ "session_id": entry.session_id }));
+            self.vectors.insert(doc).await?;
+        }
+
+        let stored = ConversationEntry {
+            embedding: None,
+            ..entry.clone()
+        };
+        Ok(self.put(CONVERSATIONS_TABLE, &entry.id, &stored)?)
+    }
+
+    /// Delete a conversation and its embedding.
+    pub async fn delete_conversation(&self, id: &str) -> Result<()> {
+        self.remove(CONVERSATIONS_TABLE, id)?;
+        self.vectors.delete(id).await?;
+        Ok(())
+    }
+
+    /// Load all conversations with their embeddings, oldest first.
+    pub async fn load_conversations(&self) -> Result<Vec<ConversationEntry>> {
+        let mut entries: Vec<ConversationEntry> = self.load_all(CONVERSATIONS_TABLE)?;
+        for entry in &mut entries {
+            entry.embedding = self.vectors.get(&entry.id).await?.map(|doc| doc.embedding);
+        }
+        entries.sort_by_key(|e| e.timestamp);
+        Ok(entries)
+    }
+
+    /// Delete all conversations and their embeddings.
+    pub async fn clear_conversations(&self) -> Result<()> {
+        let ids = self.keys(CONVERSATIONS_TABLE)?;
+        self.clear_tables(&[CONVERSATIONS_TABLE])?;
+        for id in ids {
+            self.vectors.delete(&id).await?;
+        }
+        Ok(())
+    }
+
+    // ===== Knowledge graph =====
+
+    /// Save an entity.
+    pub fn save_entity(&self, entity: &Entity) -> Result<()> {
+        Ok(self.put(ENTITIES_TABLE, &entity.id, entity)?)
+    }
+
+    /// Delete an entity.
+    pub fn delete_entity(&self, id: &str) -> Result<()> {
+        Ok(self.remove(ENTITIES_TABLE, id)?)
+    }
+
+    /// Load all entities.
+    pub fn load_entities(&self) -> Result<Vec<Entity>> {
+        Ok(self.load_all(ENTITIES_TABLE)?)
+    }
+
+    /// Save a relationship.
+    pub fn save_relationship(&self, relationship: &Relationship) -> Result<()> {
+        Ok(self.put(RELATIONSHIPS_TABLE, &relationship.id, relationship)?)
+    }
+
+    /// Delete a relationship.
+    pub fn delete_relationship(&self, id: &str) -> Result<()> {
+        Ok(self.remove(RELATIONSHIPS_TABLE, id)?)
+    }
+
+    /// Load all relationships, oldest first.
+    pub fn load_relationships(&self) -> Result<Vec<Relationship>> {
+        let mut relationships: Vec<Relationship> = self.load_all(RELATIONSHIPS_TABLE)?;
+        relationships.sort_by_key(|r| r.created_at);
+        Ok(relationships)
+    }
+
+    /// Delete all entities and relationships.
+    pub fn clear_graph(&self) -> Result<()> {
+        Ok(self.clear_tables(&[ENTITIES_TABLE, RELATIONSHIPS_TABLE])?)
+    }
+
+    // ===== Table helpers =====
+
+    fn put<T: Serialize>(
+        &self,
+        table: TableDefinition<&str, &[u8]>,
+        key: &str,
+        value: &T,
+    ) -> StorageResult<()> {
+        let value = serde_json::to_vec(value)?;
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(table)?;
+            table.insert(key, value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    fn remove(&self, table: TableDefinition<&str, &[u8]>, key: &str) -> StorageResult<()> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(table)?;
+            table.remove(key)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    fn load_all<T: DeserializeOwned>(
+        &self,
+        table: TableDefinition<&str, &[u8]>,
+    ) -> StorageResult<Vec<T>> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(table)?;
+        let mut values = Vec::new();
+        for result in table.iter()? {
+            let (key, value) = result?;
+            match serde_json::from_slice(value.value()) {
+                Ok(v) => values.push(v),
+                Err(e) => tracing::warn!(
+                    category = "memory",
+                    key = key.value(),
+                    error = %e,
+                    "Skipping unreadable memory record"
+                ),
+            }
+        }
+        Ok(values)
+    }
+
+    fn keys(&self, table: TableDefinition<&str, &[u8]>) -> StorageResult<Vec<String>> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(table)?;
+        let mut keys = Vec::new();
+        for result in table.iter()? {
+            let (key, _) = result?;
+            keys.push(key.value().to_string());
+        }
+        Ok(keys)
+    }
+
+    fn clear_tables(&self, tables: &[TableDefinition<&str, &[u8]>]) -> StorageResult<()> {
+        let write_txn = begin_write(&self.db)?;
+        for table in tables {
+            write_txn.delete_table(*table)?;
+            write_txn.open_table(*table)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+}
+
+/// Create missing tables and bring the schema up to [`MEMORY_SCHEMA_VERSION`].
+fn migrate(db: &Database) -> Result<()> {
+    let write_txn = begin_write(db).map_err(neomind_storage::Error::from)?;
+    {
+        for table in [
+            KNOWLEDGE_TABLE,
+            CASES_TABLE,
+            CONVERSATIONS_TABLE,
+            ENTITIES_TABLE,
+            RELATIONSHIPS_TABLE,
+        ] {
+            write_txn
+                .open_table(table)
+                .map_err(neomind_storage::Error::from)?;
+        }
+
+        let mut meta = write_txn
+            .open_table(META_TABLE)
+            .map_err(neomind_storage::Error::from)?;
+        let version = meta
+            .get(SCHEMA_VERSION_KEY)
+            .map_err(neomind_storage::Error::from)?
+            .map(|v| v.value());
+        match version {
+            Some(version) if version > MEMORY_SCHEMA_VERSION => {
+                return Err(MemoryError::Storage(format!(
+                    "Memory store schema version {} is newer than supported version {}",
+                    version, MEMORY_SCHEMA_VERSION
+                )));
+            }
+            Some(version) if version == MEMORY_SCHEMA_VERSION => {}
+            // New database. Migrations from older versions go here.
+            _ => {
+                meta.insert(SCHEMA_VERSION_KEY, MEMORY_SCHEMA_VERSION)
+                    .map_err(neomind_storage::Error::from)?;
+            }
+        }
+    }
+    write_txn.commit().map_err(neomind_storage::Error::from)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::long_term::KnowledgeCategory;
+
+    #[tokio::test]
+    async fn test_store_round_trip() {
+        let store = MemoryStore::memory().unwrap();
+        assert_eq!(store.schema_version().unwrap(), MEMORY_SCHEMA_VERSION);
+
+        let entry = KnowledgeEntry::new("Guide", "Content", KnowledgeCategory::BestPractice);
+        store.save_knowledge(&entry).await.unwrap();
+        assert_eq!(store.load_knowledge().await.unwrap()[0].id, entry.id);
+
+        let conversation =
+            ConversationEntry::new("s1", "Question", "Answer").with_embedding(vec![1.0, 0.0]);
+        store.save_conversation(&conversation).await.unwrap();
+        let loaded = store.load_conversations().await.unwrap();
+        assert_eq!(loaded[0].embedding, Some(vec![1.0, 0.0]));
+
+        store.clear_conversations().await.unwrap();
+        assert!(store.load_conversations().await.unwrap().is_empty());
+        store.clear_long_term().await.unwrap();
+        assert!(store.load_knowledge().await.unwrap().is_empty());
+    }
+}
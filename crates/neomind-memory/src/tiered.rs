@@ -1,16 +1,25 @@
 //! Tiered memory combining short-term, mid-term, and long-term memory.
 //!
-//! This module provides a unified interface to all three memory layers.
+//! This module provides a unified interface to all three memory layers and
+//! the knowledge graph. Use [`TieredMemory::with_store`] to persist the
+//! mid-term, long-term and graph data across restarts, and
+//! [`TieredMemory::retrieve`] for fused BM25 + vector retrieval.
 
 use std::sync::Arc;
 
 use serde::{Deserialize, Serialize};
 
-use super::embeddings::EmbeddingConfig;
+use super::embeddings::{EmbeddingConfig, EmbeddingModel};
 use super::error::Result;
+use super::graph::MemoryGraph;
 use super::long_term::{KnowledgeCategory, KnowledgeEntry, TroubleshootingCase};
 use super::mid_term::{ConversationEntry, SearchResult};
+use super::retrieval::{
+    FusionMethod, HybridRetriever, Reranker, RetrievalConfig, RetrievalFilter, RetrievalQuery,
+    RetrievalSource, RetrievedContent, RetrievedItem, DEFAULT_CANDIDATE_MULTIPLIER, DEFAULT_RRF_K,
+};
 use super::short_term::{MemoryMessage, ShortTermMemory};
+use super::store::MemoryStore;
 
 /// Configuration for tiered memory.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -37,6 +46,12 @@ pub struct TieredMemoryConfig {
     /// BM25 weight for hybrid search (0.0 - 1.0)
     #[serde(default = "default_bm25_weight")]
     pub bm25_weight: f32,
+    /// How [`TieredMemory::retrieve`] fuses lexical and semantic rankings
+    #[serde(default)]
+    pub fusion: FusionMethod,
+    /// RRF rank constant
+    #[serde(default = "default_rrf_k")]
+    pub rrf_k: f32,
 }
 
 fn default_use_hybrid_search() -> bool {
@@ -51,6 +66,10 @@ fn default_bm25_weight() -> f32 {
     0.3
 }
 
+fn default_rrf_k() -> f32 {
+    DEFAULT_RRF_K
+}
+
 impl Default for TieredMemoryConfig {
     fn default() -> Self {
         Self {
@@ -63,6 +82,21 @@ impl Default for TieredMemoryConfig {
             use_hybrid_search: true,
             semantic_weight: 0.7,
             bm25_weight: 0.3,
+            fusion: FusionMethod::Rrf,
+            rrf_k: DEFAULT_RRF_K,
+        }
+    }
+}
+
+impl TieredMemoryConfig {
+    /// Retrieval settings derived from this configuration.
+    pub fn retrieval_config(&self) -> RetrievalConfig {
+        RetrievalConfig {
+            fusion: self.fusion,
+            rrf_k: self.rrf_k,
+            semantic_weight: self.semantic_weight,
+            bm25_weight: self.bm25_weight,
+            candidate_multiplier: DEFAULT_CANDIDATE_MULTIPLIER,
         }
     }
 }
@@ -86,6 +120,10 @@ pub struct TieredMemory {
     mid_term: Arc<super::mid_term::MidTermMemory>,
     /// Long-term memory
     long_term: Arc<super::long_term::LongTermMemory>,
+    /// Knowledge graph
+    graph: Arc<MemoryGraph>,
+    /// Optional reranker for fused retrieval results
+    reranker: Option<Arc<dyn Reranker>>,
     /// Configuration
     config: TieredMemoryConfig,
 }
@@ -98,29 +136,62 @@ impl TieredMemory {
 
     /// Create a new tiered memory with custom config.
     pub fn with_config(config: TieredMemoryConfig) -> Self {
+        Self {
+            short_term: Self::short_term_layer(&config),
+            mid_term: Arc::new(Self::mid_term_layer(&config)),
+            long_term: Arc::new(Self::long_term_layer(&config)),
+            graph: Arc::new(MemoryGraph::new()),
+            reranker: None,
+            config,
+        }
+    }
+
+    /// Create a tiered memory backed by a persistent store.
+    ///
+    /// Mid-term conversations, long-term knowledge and the knowledge graph are
+    /// loaded from the store and every change is written through to it.
+    /// Short-term memory stays in process.
+    pub async fn with_store(config: TieredMemoryConfig, store: Arc<MemoryStore>) -> Result<Self> {
+        let mid_term = Self::mid_term_layer(&config)
+            .with_store(store.clone())
+            .await?;
+        let long_term = Self::long_term_layer(&config)
+            .with_store(store.clone())
+            .await?;
+        let graph = MemoryGraph::new().with_store(store)?;
+
+        Ok(Self {
+            short_term: Self::short_term_layer(&config),
+            mid_term: Arc::new(mid_term),
+            long_term: Arc::new(long_term),
+            graph: Arc::new(graph),
+            reranker: None,
+            config,
+        })
+    }
+
+    fn short_term_layer(config: &TieredMemoryConfig) -> ShortTermMemory {
+        ShortTermMemory::new()
+            .with_max_messages(config.max_short_term_messages)
+            .with_max_tokens(config.max_short_term_tokens)
+    }
+
+    fn mid_term_layer(config: &TieredMemoryConfig) -> super::mid_term::MidTermMemory {
         // Create mid-term memory with embedding config
-        let mid_term = if let Some(embed_config) = &config.embedding_config {
+        if let Some(embed_config) = &config.embedding_config {
             super::mid_term::MidTermMemory::with_embedding_config(embed_config.clone())
                 .with_max_entries(config.max_mid_term_entries)
         } else {
             super::mid_term::MidTermMemory::new()
                 .with_max_entries(config.max_mid_term_entries)
                 .with_embedding_dim(config.embedding_dim)
-        };
-
-        Self {
-            short_term: ShortTermMemory::new()
-                .with_max_messages(config.max_short_term_messages)
-                .with_max_tokens(config.max_short_term_tokens),
-            mid_term: Arc::new(mid_term),
-            long_term: Arc::new(
-                super::long_term::LongTermMemory::new()
-                    .with_max_knowledge(config.max_long_term_knowledge),
-            ),
-            config,
         }
     }
 
+    fn long_term_layer(config: &TieredMemoryConfig) -> super::long_term::LongTermMemory {
+        super::long_term::LongTermMemory::new().with_max_knowledge(config.max_long_term_knowledge)
+    }
+
     /// Create a new tiered memory with embedding configuration.
     pub fn with_embedding_config(embed_config: EmbeddingConfig) -> Self {
         let config = TieredMemoryConfig {
@@ -219,7 +290,19 @@ impl TieredMemory {
     // ===== Long-term memory operations =====
 
     /// Add a knowledge entry to long-term memory.
-    pub async fn add_knowledge(&self, entry: KnowledgeEntry) -> Result<()> {
+    ///
+    /// Entries without an embedding are embedded so that semantic retrieval
+    /// can find them.
+    pub async fn add_knowledge(&self, mut entry: KnowledgeEntry) -> Result<()> {
+        if entry.embedding.is_none() {
+            let text = format!("{}\n\n{}", entry.title, entry.content);
+            match self.embedding_model().embed(&text).await {
+                Ok(embedding) => entry.embedding = Some(embedding),
+                Err(e) => {
+                    tracing::warn!(category = "memory", id = %entry.id, error = %e, "Failed to embed knowledge entry")
+                }
+            }
+        }
         self.long_term.add(entry).await
     }
 
@@ -261,6 +344,58 @@ impl TieredMemory {
         self.long_term.clear().await;
     }
 
+    /// Persist batched knowledge access counts.
+    pub async fn flush(&self) -> Result<usize> {
+        self.long_term.flush().await
+    }
+
+    // ===== Retrieval =====
+
+    /// Set the reranker applied to fused retrieval results.
+    pub fn set_reranker(&mut self, reranker: Option<Arc<dyn Reranker>>) {
+        self.reranker = reranker;
+    }
+
+    /// Build a hybrid retriever over long-term knowledge and mid-term
+    /// conversations, using the configured fusion and reranker.
+    pub fn retriever(&self) -> HybridRetriever {
+        HybridRetriever::new(self.embedding_model())
+            .with_knowledge(self.long_term.clone())
+            .with_conversations(self.mid_term.clone())
+            .with_config(self.config.retrieval_config())
+            .with_reranker(self.reranker.clone())
+    }
+
+    /// Retrieve knowledge and conversations with fused BM25 + vector search.
+    pub async fn retrieve(&self, query: &RetrievalQuery) -> Result<Vec<RetrievedItem>> {
+        self.retriever().retrieve(query).await
+    }
+
+    /// Ranked long-term results for the combined queries.
+    async fn retrieve_knowledge(
+        &self,
+        query: &str,
+        top_k: usize,
+        method: SearchMethod,
+    ) -> Vec<KnowledgeEntry> {
+        let query = RetrievalQuery::new(query, top_k)
+            .with_method(method)
+            .with_filter(RetrievalFilter::new().with_source(RetrievalSource::Knowledge));
+        match self.retrieve(&query).await {
+            Ok(items) => items
+                .into_iter()
+                .filter_map(|item| match item.content {
+                    RetrievedContent::Knowledge(entry) => Some(entry),
+                    RetrievedContent::Conversation(_) => None,
+                })
+                .collect(),
+            Err(e) => {
+                tracing::warn!(category = "memory", error = %e, "Knowledge retrieval failed");
+                Vec::new()
+            }
+        }
+    }
+
     // ===== Combined operations =====
 
     /// Query all memory layers.
@@ -289,7 +424,14 @@ impl TieredMemory {
             }
         };
 
-        let long_term_future = self.long_term.search(query);
+        let long_term_future = async {
+            if self.config.use_hybrid_search {
+                self.retrieve_knowledge(query, top_k, SearchMethod::Hybrid)
+                    .await
+            } else {
+                self.long_term.search(query).await
+            }
+        };
 
         // Run all queries in parallel and wait for completion
         let (short_term_result, mid_term, long_term) =
@@ -334,7 +476,7 @@ impl TieredMemory {
             }
         };
 
-        let long_term_future = self.long_term.search(query);
+        let long_term_future = self.retrieve_knowledge(query, top_k, search_method);
 
         // Run all queries in parallel and wait for completion
         let (short_term_result, mid_term, long_term) =
@@ -404,6 +546,16 @@ impl TieredMemory {
         &self.long_term
     }
 
+    /// Get the embedding model used for semantic search.
+    pub fn embedding_model(&self) -> Arc<dyn EmbeddingModel> {
+        self.mid_term.embedding_model()
+    }
+
+    /// Get the knowledge graph reference.
+    pub fn graph_ref(&self) -> &Arc<MemoryGraph> {
+        &self.graph
+    }
+
     /// Get the configuration.
     pub fn config(&self) -> &TieredMemoryConfig {
         &self.config
@@ -421,14 +573,17 @@ impl Default for TieredMemory {
     }
 }
 
-/// Search method for mid-term memory queries.
+/// Search method for memory queries.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum SearchMethod {
     /// Hybrid search combining semantic and BM25
+    #[serde(alias = "hybrid")]
     Hybrid,
     /// Semantic search only (vector similarity)
+    #[serde(alias = "semantic")]
     Semantic,
     /// BM25 full-text search only
+    #[serde(alias = "bm25")]
     BM25,
 }
 
@@ -622,6 +777,8 @@ mod tests {
             use_hybrid_search: true,
             semantic_weight: 0.7,
             bm25_weight: 0.3,
+            fusion: FusionMethod::Rrf,
+            rrf_k: DEFAULT_RRF_K,
         };
 
         let mut memory = TieredMemory::with_config(config.clone());
@@ -724,6 +881,66 @@ mod tests {
         assert!(total_hybrid > 0 || total_semantic > 0 || total_bm25 > 0);
     }
 
+    #[tokio::test]
+    async fn test_retrieve_fuses_layers() {
+        struct KnowledgeFirst;
+
+        #[async_trait::async_trait]
+        impl Reranker for KnowledgeFirst {
+            async fn rerank(
+                &self,
+                _query: &str,
+                mut candidates: Vec<RetrievedItem>,
+            ) -> Result<Vec<RetrievedItem>> {
+                candidates.sort_by_key(|item| item.source() != RetrievalSource::Knowledge);
+                Ok(candidates)
+            }
+
+            fn name(&self) -> &str {
+                "knowledge_first"
+            }
+        }
+
+        let mut memory = TieredMemory::new();
+        for model in ["XR-500", "XR-700", "XR-900"] {
+            let entry = KnowledgeEntry::new(
+                format!("{} Pump", model),
+                "Check the seal kit when the pump leaks.",
+                KnowledgeCategory::Troubleshooting,
+            );
+            memory.add_knowledge(entry).await.unwrap();
+        }
+        memory
+            .add_conversation("s1", "XR-700 pump leaking", "Replaced the seal kit.")
+            .await
+            .unwrap();
+
+        // Knowledge entries are embedded on insert
+        let all = memory.long_term_ref().get_all().await;
+        assert!(all.iter().all(|entry| entry.embedding.is_some()));
+
+        let results = memory.query_all("XR-700", 2).await;
+        assert_eq!(results.long_term[0].title, "XR-700 Pump");
+
+        let items = memory
+            .retrieve(&RetrievalQuery::new("XR-700", 3))
+            .await
+            .unwrap();
+        assert_eq!(items.len(), 3);
+        assert!(items
+            .iter()
+            .any(|item| item.source() == RetrievalSource::Conversation));
+
+        memory.set_reranker(Some(Arc::new(KnowledgeFirst)));
+        let items = memory
+            .retrieve(&RetrievalQuery::new("XR-700", 3))
+            .await
+            .unwrap();
+        assert!(items
+            .iter()
+            .all(|item| item.source() == RetrievalSource::Knowledge));
+    }
+
     #[tokio::test]
     async fn test_embedding_config() {
         use super::super::embeddings::EmbeddingConfig;
@@ -743,6 +960,8 @@ mod tests {
             use_hybrid_search: false,
             semantic_weight: 0.5,
             bm25_weight: 0.5,
+            fusion: FusionMethod::Weighted,
+            rrf_k: DEFAULT_RRF_K,
         };
 
         let memory_custom = TieredMemory::with_config(config);
@@ -787,4 +1006,33 @@ mod tests {
             assert!(results[i].score >= results.get(i + 1).map(|r| r.score).unwrap_or(0.0));
         }
     }
+
+    #[tokio::test]
+    async fn test_with_store_restores_layers() {
+        let store = MemoryStore::memory().unwrap();
+        let memory = TieredMemory::with_store(TieredMemoryConfig::default(), store.clone())
+            .await
+            .unwrap();
+        memory
+            .add_conversation("session1", "Is the pump running?", "Yes")
+            .await
+            .unwrap();
+        memory
+            .add_knowledge(KnowledgeEntry::new(
+                "Pump",
+                "Pump manual",
+                KnowledgeCategory::DeviceManual,
+            ))
+            .await
+            .unwrap();
+        drop(memory);
+
+        let reopened = TieredMemory::with_store(TieredMemoryConfig::default(), store)
+            .await
+            .unwrap();
+        let stats = reopened.get_stats().await;
+        assert_eq!(stats.mid_term_entries, 1);
+        assert_eq!(stats.long_term_entries, 1);
+        assert!(!reopened.search_mid_term("pump", 5).await.is_empty());
+    }
 }
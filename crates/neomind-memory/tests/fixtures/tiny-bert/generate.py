@@ -0,0 +1,169 @@
+#!/usr/bin/env python3
+"""Generate the tiny randomly initialised BERT model used by the local
+embedding tests. Needs only the Python standard library.
+
+    python3 generate.py
+"""
+
+import json
+import random
+import struct
+from pathlib import Path
+
+HIDDEN = 32
+LAYERS = 2
+HEADS = 2
+INTERMEDIATE = 64
+MAX_POSITIONS = 64
+
+SPECIAL = ["[PAD]", "[UNK]", "[CLS]", "[SEP]", "[MASK]"]
+WORDS = [
+    "the", "is", "a", "of", "and", "to", "in", "at",
+    "pump", "seal", "leak", "leaks", "valve", "sensor", "gateway",
+    "temperature", "pressure", "high", "low", "reset", "replace", "check",
+    "泵", "温", "度", "高",
+]
+CHARS = list("abcdefghijklmnopqrstuvwxyz0123456789")
+PUNCTUATION = list(".,-!?")
+
+here = Path(__file__).parent
+rng = random.Random(42)
+
+vocab = SPECIAL + WORDS + CHARS + ["##" + c for c in CHARS] + PUNCTUATION
+ids = {token: i for i, token in enumerate(vocab)}
+
+config = {
+    "architectures": ["BertModel"],
+    "model_type": "bert",
+    "vocab_size": len(vocab),
+    "hidden_size": HIDDEN,
+    "num_hidden_layers": LAYERS,
+    "num_attention_heads": HEADS,
+    "intermediate_size": INTERMEDIATE,
+    "hidden_act": "gelu",
+    "hidden_dropout_prob": 0.1,
+    "attention_probs_dropout_prob": 0.1,
+    "max_position_embeddings": MAX_POSITIONS,
+    "type_vocab_size": 2,
+    "initializer_range": 0.2,
+    "layer_norm_eps": 1e-12,
+    "pad_token_id": 0,
+    "position_embedding_type": "absolute",
+    "classifier_dropout": None,
+}
+
+
+def special(token):
+    return {"SpecialToken": {"id": token, "type_id": 0}}
+
+
+tokenizer = {
+    "version": "1.0",
+    "truncation": None,
+    "padding": None,
+    "added_tokens": [
+        {
+            "id": ids[token],
+            "content": token,
+            "single_word": False,
+            "lstrip": False,
+            "rstrip": False,
+            "normalized": False,
+            "special": True,
+        }
+        for token in SPECIAL
+    ],
+    "normalizer": {
+        "type": "BertNormalizer",
+        "clean_text": True,
+        "handle_chinese_chars": True,
+        "strip_accents": None,
+        "lowercase": True,
+    },
+    "pre_tokenizer": {"type": "BertPreTokenizer"},
+    "post_processor": {
+        "type": "TemplateProcessing",
+        "single": [special("[CLS]"), {"Sequence": {"id": "A", "type_id": 0}}, special("[SEP]")],
+        "pair": [
+            special("[CLS]"),
+            {"Sequence": {"id": "A", "type_id": 0}},
+            special("[SEP]"),
+            {"Sequence": {"id": "B", "type_id": 1}},
+            {"SpecialToken": {"id": "[SEP]", "type_id": 1}},
+        ],
+        "special_tokens": {
+            token: {"id": token, "ids": [ids[token]], "tokens": [token]}
+            for token in ("[CLS]", "[SEP]")
+        },
+    },
+    "decoder": {"type": "WordPiece", "prefix": "##", "cleanup": True},
+    "model": {
+        "type": "WordPiece",
+        "unk_token": "[UNK]",
+        "continuing_subword_prefix": "##",
+        "max_input_chars_per_word": 100,
+        "vocab": ids,
+    },
+}
+
+tensors = {}
+
+
+def add(name, shape, sample):
+    count = 1
+    for dim in shape:
+        count *= dim
+    tensors[name] = (list(shape), [sample() for _ in range(count)])
+
+
+def normal(name, *shape):
+    add(name, shape, lambda: rng.gauss(0.0, config["initializer_range"]))
+
+
+def constant(name, value, *shape):
+    add(name, shape, lambda: value)
+
+
+def layer_norm(prefix):
+    constant(f"{prefix}.weight", 1.0, HIDDEN)
+    constant(f"{prefix}.bias", 0.0, HIDDEN)
+
+
+normal("embeddings.word_embeddings.weight", len(vocab), HIDDEN)
+normal("embeddings.position_embeddings.weight", MAX_POSITIONS, HIDDEN)
+normal("embeddings.token_type_embeddings.weight", 2, HIDDEN)
+layer_norm("embeddings.LayerNorm")
+
+for layer in range(LAYERS):
+    prefix = f"encoder.layer.{layer}"
+    for linear, out_dim, in_dim in [
+        ("attention.self.query", HIDDEN, HIDDEN),
+        ("attention.self.key", HIDDEN, HIDDEN),
+        ("attention.self.value", HIDDEN, HIDDEN),
+        ("attention.output.dense", HIDDEN, HIDDEN),
+        ("intermediate.dense", INTERMEDIATE, HIDDEN),
+        ("output.dense", HIDDEN, INTERMEDIATE),
+    ]:
+        normal(f"{prefix}.{linear}.weight", out_dim, in_dim)
+        constant(f"{prefix}.{linear}.bias", 0.0, out_dim)
+    layer_norm(f"{prefix}.attention.output.LayerNorm")
+    layer_norm(f"{prefix}.output.LayerNorm")
+
+header = {"__metadata__": {"format": "pt"}}
+data = bytearray()
+for name in sorted(tensors):
+    shape, values = tensors[name]
+    start = len(data)
+    data += struct.pack(f"<{len(values)}f", *values)
+    header[name] = {"dtype": "F32", "shape": shape, "data_offsets": [start, len(data)]}
+
+header_bytes = json.dumps(header, separators=(",", ":")).encode()
+header_bytes += b" " * (-len(header_bytes) % 8)
+
+with open(here / "model.safetensors", "wb") as f:
+    f.write(struct.pack("<Q", len(header_bytes)))
+    f.write(header_bytes)
+    f.write(data)
+
+(here / "config.json").write_text(json.dumps(config, indent=2) + "\n")
+(here / "tokenizer.json").write_text(json.dumps(tokenizer, indent=2, ensure_ascii=False) + "\n")
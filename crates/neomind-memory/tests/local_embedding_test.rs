@@ -0,0 +1,77 @@
+//! Local CPU embedding inference against a tiny randomly initialised BERT.
+//!
+//! The model in `fixtures/tiny-bert` is produced by `generate.py` there.
+
+#![cfg(feature = "local-embeddings")]
+
+use neomind_memory::{
+    cosine_similarity, create_embedding_model, EmbeddingConfig, EmbeddingModel, LocalEmbedding,
+};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+
+fn tiny_bert() -> LocalEmbedding {
+    LocalEmbedding::from_dir(format!("{}/tiny-bert", FIXTURES)).unwrap()
+}
+
+fn assert_close(a: &[f32], b: &[f32]) {
+    assert_eq!(a.len(), b.len());
+    for (x, y) in a.iter().zip(b) {
+        assert!((x - y).abs() < 1e-4, "{} != {}", x, y);
+    }
+}
+
+#[tokio::test]
+async fn test_embeddings_are_normalised() {
+    let model = tiny_bert();
+    assert_eq!(model.dimension(), 32);
+    assert_eq!(model.model_name(), "tiny-bert");
+
+    let embedding = model.embed("the pump leaks at the seal").await.unwrap();
+    assert_eq!(embedding.len(), 32);
+    let norm: f32 = embedding.iter().map(|x| x * x).sum::<f32>().sqrt();
+    assert!((norm - 1.0).abs() < 1e-4);
+
+    // Deterministic, and sensitive to the input
+    let again = model.embed("the pump leaks at the seal").await.unwrap();
+    assert_close(&embedding, &again);
+    let other = model.embed("温度 高").await.unwrap();
+    assert!(cosine_similarity(&embedding, &other) < 0.999);
+}
+
+#[tokio::test]
+async fn test_batches_match_single_embeddings() {
+    // Small batches of mixed lengths exercise padding and reordering
+    let model = tiny_bert().with_batch_size(2).with_threads(1).unwrap();
+    let texts: Vec<String> = [
+        "check the valve",
+        "a",
+        "the temperature sensor is high and the pressure is low",
+        "reset the gateway",
+        "泵",
+    ]
+    .iter()
+    .map(|t| t.to_string())
+    .collect();
+
+    let batch = model.embed_batch(&texts).await.unwrap();
+    assert_eq!(batch.len(), texts.len());
+    for (text, embedding) in texts.iter().zip(&batch) {
+        assert_close(embedding, &model.embed(text).await.unwrap());
+    }
+
+    assert!(model.embed_batch(&[]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_local_provider_from_config() {
+    let config = EmbeddingConfig::local_with_cache("tiny-bert", FIXTURES)
+        .with_threads(2)
+        .with_batch_size(4);
+    let model = create_embedding_model(config).unwrap();
+    assert_eq!(model.dimension(), 32);
+
+    let a = model.embed("replace the seal").await.unwrap();
+    let b = tiny_bert().embed("replace the seal").await.unwrap();
+    assert_close(&a, &b);
+}
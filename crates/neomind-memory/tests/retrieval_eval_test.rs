@@ -0,0 +1,221 @@
+//! Retrieval quality on a labelled query set.
+//!
+//! The corpus mixes device models whose manuals only differ by model number
+//! with paraphrased queries, so neither BM25 nor embeddings alone retrieve
+//! everything.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use neomind_memory::{
+    EmbeddingModel, HybridRetriever, KnowledgeCategory, KnowledgeEntry, LabelledQuery,
+    LongTermMemory, Result, RetrievalFilter, SearchMethod,
+};
+
+/// Maps words to concepts so that synonyms embed alike. Tokens containing
+/// digits all share one dimension, like model numbers in real embeddings.
+struct ConceptEmbedding;
+
+const CONCEPTS: &[&[&str]] = &[
+    &["pump", "pumps", "impeller"],
+    &["leak", "leaks", "leaking", "drip", "dripping", "seal"],
+    &["pressure", "overpressure", "bar"],
+    &["valve", "valves", "actuator"],
+    &["sensor", "sensors", "probe"],
+    &["temperature", "hot", "overheating", "thermal"],
+    &["calibrate", "calibration", "offset", "drift"],
+    &["wifi", "wireless", "network", "connection"],
+    &["reset", "restore", "factory"],
+];
+
+#[async_trait]
+impl EmbeddingModel for ConceptEmbedding {
+    async fn embed(&self, text: &str) -> Result<Vec<f32>> {
+        let mut embedding = vec![0.0; CONCEPTS.len() + 1];
+        for word in text.to_lowercase().split_whitespace() {
+            let word = word.trim_matches(|c: char| !c.is_alphanumeric());
+            if word.chars().any(|c| c.is_ascii_digit()) {
+                embedding[CONCEPTS.len()] += 1.0;
+            } else if let Some(concept) = CONCEPTS.iter().position(|c| c.contains(&word)) {
+                embedding[concept] += 1.0;
+            }
+        }
+        Ok(embedding)
+    }
+
+    async fn embed_batch(&self, texts: &[String]) -> Result<Vec<Vec<f32>>> {
+        let mut embeddings = Vec::with_capacity(texts.len());
+        for text in texts {
+            embeddings.push(self.embed(text).await?);
+        }
+        Ok(embeddings)
+    }
+
+    fn dimension(&self) -> usize {
+        CONCEPTS.len() + 1
+    }
+
+    fn model_name(&self) -> &str {
+        "concept"
+    }
+}
+
+const CORPUS: &[(&str, &str, &str, &str)] = &[
+    (
+        "xr500-seal",
+        "pump",
+        "XR-500 seal replacement",
+        "If the XR-500 leaks at the shaft, replace the gasket.",
+    ),
+    (
+        "xr700-seal",
+        "pump",
+        "XR-700 seal replacement",
+        "If the XR-700 leaks at the shaft, replace the seal kit.",
+    ),
+    (
+        "xr500-pressure",
+        "pump",
+        "XR-500 overpressure",
+        "The XR-500 stops at 6 bar. Check the discharge valve.",
+    ),
+    (
+        "v20-actuator",
+        "valve",
+        "V-20 actuator",
+        "A V-20 valve that does not close needs a new actuator.",
+    ),
+    (
+        "ts100-calibration",
+        "sensor",
+        "TS-100 calibration",
+        "Calibrate the TS-100 probe when readings drift by more than 0.5 degrees.",
+    ),
+    (
+        "ts100-overheating",
+        "sensor",
+        "TS-100 thermal limits",
+        "The TS-100 reports hot readings above 85 degrees.",
+    ),
+    (
+        "gw1-wifi",
+        "gateway",
+        "GW-1 wireless setup",
+        "Join the GW-1 to the wifi network from the setup page.",
+    ),
+    (
+        "gw1-reset",
+        "gateway",
+        "GW-1 factory reset",
+        "Hold the GW-1 button for 10 seconds to restore factory settings.",
+    ),
+];
+
+fn labelled(query: &str, relevant: &[&str]) -> LabelledQuery {
+    LabelledQuery {
+        query: query.to_string(),
+        relevant: relevant.iter().map(|id| id.to_string()).collect(),
+        filter: RetrievalFilter::default(),
+    }
+}
+
+fn model_number_queries() -> Vec<LabelledQuery> {
+    vec![
+        labelled("XR-700 leaking", &["xr700-seal"]),
+        labelled("XR-500 pressure", &["xr500-pressure"]),
+        labelled("TS-100 drift", &["ts100-calibration"]),
+    ]
+}
+
+fn queries() -> Vec<LabelledQuery> {
+    let mut queries = model_number_queries();
+    queries.extend([
+        // Paraphrases without shared keywords
+        labelled("impeller dripping", &["xr500-seal", "xr700-seal"]),
+        labelled("probe overheating", &["ts100-overheating"]),
+        labelled("restore wireless connection", &["gw1-wifi", "gw1-reset"]),
+        // Filtered
+        LabelledQuery {
+            query: "replace".to_string(),
+            relevant: vec!["v20-actuator".to_string()],
+            filter: RetrievalFilter::new().with_device_types(vec!["valve".to_string()]),
+        },
+    ]);
+    queries
+}
+
+async fn retriever() -> HybridRetriever {
+    let long_term = Arc::new(LongTermMemory::new());
+    for (id, device_type, title, content) in CORPUS {
+        let text = format!("{}\n\n{}", title, content);
+        let mut entry = KnowledgeEntry::new(*title, *content, KnowledgeCategory::DeviceManual)
+            .with_tags(vec![device_type.to_string()])
+            .with_embedding(ConceptEmbedding.embed(&text).await.unwrap());
+        entry.id = id.to_string();
+        long_term.add(entry).await.unwrap();
+    }
+
+    HybridRetriever::new(Arc::new(ConceptEmbedding)).with_knowledge(long_term)
+}
+
+#[tokio::test]
+async fn test_hybrid_beats_single_retrievers() {
+    let retriever = retriever().await;
+    let queries = queries();
+
+    let bm25 = retriever
+        .evaluate(&queries, 3, SearchMethod::BM25)
+        .await
+        .unwrap();
+    let semantic = retriever
+        .evaluate(&queries, 3, SearchMethod::Semantic)
+        .await
+        .unwrap();
+    let hybrid = retriever
+        .evaluate(&queries, 3, SearchMethod::Hybrid)
+        .await
+        .unwrap();
+    assert_eq!(hybrid.queries, queries.len());
+    assert!(hybrid.recall_at_k > bm25.recall_at_k);
+    assert!(hybrid.recall_at_k >= semantic.recall_at_k);
+    assert!(hybrid.mrr > bm25.mrr);
+    assert_eq!(hybrid.hit_rate, 1.0);
+}
+
+#[tokio::test]
+async fn test_exact_model_numbers() {
+    let retriever = retriever().await;
+    let queries = model_number_queries();
+
+    let semantic = retriever
+        .evaluate(&queries, 1, SearchMethod::Semantic)
+        .await
+        .unwrap();
+    let hybrid = retriever
+        .evaluate(&queries, 1, SearchMethod::Hybrid)
+        .await
+        .unwrap();
+
+    // Embeddings confuse the XR-500 and XR-700 manuals; BM25 does not
+    assert!(semantic.hit_rate < 1.0);
+    assert_eq!(hybrid.hit_rate, 1.0);
+    assert_eq!(hybrid.mrr, 1.0);
+}
+
+#[tokio::test]
+async fn test_labelled_queries_from_json() {
+    let json = r#"[
+        {"query": "GW-1 reset", "relevant": ["gw1-reset"]},
+        {"query": "TS-100 hot", "relevant": ["ts100-overheating"], "filter": {"device_types": ["sensor"]}}
+    ]"#;
+    let queries: Vec<LabelledQuery> = serde_json::from_str(json).unwrap();
+
+    let metrics = retriever()
+        .await
+        .evaluate(&queries, 1, SearchMethod::Hybrid)
+        .await
+        .unwrap();
+    assert_eq!(metrics.queries, 2);
+    assert_eq!(metrics.precision_at_k, 1.0);
+    assert_eq!(metrics.ndcg_at_k, 1.0);
+}
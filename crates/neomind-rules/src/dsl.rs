@@ -41,7 +41,18 @@
 //!     NOTIFY "温度在舒适范围内"
 //! END
 //! ```
+//!
+//! ## Device Group Condition
+//! `ANY(...)` / `ALL(...)` take a device selector (`group:`, `tag:`, `location:` terms).
+//! ```text
+//! RULE "冷柜温度告警"
+//! WHEN ANY(group:freezers).temperature > -10
+//! DO
+//!     NOTIFY "有冷柜温度过高"
+//! END
+//! ```
 
+use neomind_devices::DeviceSelector;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
@@ -94,6 +105,14 @@ pub enum RuleCondition {
         min: f64,
         max: f64,
     },
+    /// Device group condition: ANY/ALL(selector).metric operator value
+    DeviceGroup {
+        selector: DeviceSelector,
+        quantifier: GroupQuantifier,
+        metric: String,
+        operator: ComparisonOperator,
+        threshold: f64,
+    },
     /// Logical AND of multiple conditions
     And(Vec<RuleCondition>),
     /// Logical OR of multiple conditions
@@ -121,8 +140,11 @@ impl RuleCondition {
                 .flat_map(|c| c.get_device_metrics())
                 .collect(),
             RuleCondition::Not(condition) => condition.get_device_metrics(),
-            // Extension conditions don't contribute device metrics
-            RuleCondition::Extension { .. } | RuleCondition::ExtensionRange { .. } => vec![],
+            // Extension conditions don't contribute device metrics; group members
+            // are only known at evaluation time
+            RuleCondition::Extension { .. }
+            | RuleCondition::ExtensionRange { .. }
+            | RuleCondition::DeviceGroup { .. } => vec![],
         }
     }
 
@@ -149,7 +171,9 @@ impl RuleCondition {
                 .collect(),
             RuleCondition::Not(condition) => condition.get_extension_metrics(),
             // Device conditions don't contribute extension metrics
-            RuleCondition::Device { .. } | RuleCondition::DeviceRange { .. } => vec![],
+            RuleCondition::Device { .. }
+            | RuleCondition::DeviceRange { .. }
+            | RuleCondition::DeviceGroup { .. } => vec![],
         }
     }
 
@@ -161,14 +185,18 @@ impl RuleCondition {
                 conditions.iter().any(|c| c.has_extension())
             }
             RuleCondition::Not(condition) => condition.has_extension(),
-            RuleCondition::Device { .. } | RuleCondition::DeviceRange { .. } => false,
+            RuleCondition::Device { .. }
+            | RuleCondition::DeviceRange { .. }
+            | RuleCondition::DeviceGroup { .. } => false,
         }
     }
 
     /// Check if this condition references any device.
     pub fn has_device(&self) -> bool {
         match self {
-            RuleCondition::Device { .. } | RuleCondition::DeviceRange { .. } => true,
+            RuleCondition::Device { .. }
+            | RuleCondition::DeviceRange { .. }
+            | RuleCondition::DeviceGroup { .. } => true,
             RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
                 conditions.iter().any(|c| c.has_device())
             }
@@ -178,6 +206,25 @@ impl RuleCondition {
     }
 }
 
+/// How a device group condition combines its members' results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GroupQuantifier {
+    /// At least one matching device satisfies the comparison
+    Any,
+    /// Every matching device satisfies it (false when no device matches)
+    All,
+}
+
+impl GroupQuantifier {
+    /// Get quantifier as DSL keyword.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Any => "ANY",
+            Self::All => "ALL",
+        }
+    }
+}
+
 /// Comparison operators.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ComparisonOperator {
@@ -774,6 +821,11 @@ impl RuleDslParser {
             return Ok(RuleCondition::Or(vec![left, right]));
         }
 
+        // Device group condition: ANY(selector).metric op value
+        if let Some(condition) = Self::parse_group_condition(input)? {
+            return Ok(condition);
+        }
+
         // Simple condition
         let (source_id, metric, operator, threshold) =
             Self::parse_simple_condition(input, is_extension)?;
@@ -795,6 +847,48 @@ impl RuleDslParser {
         }
     }
 
+    /// Parse "ANY(group:freezers).temperature > -10" style conditions.
+    /// Returns `Ok(None)` when the input does not start with ANY( or ALL(.
+    fn parse_group_condition(input: &str) -> Result<Option<RuleCondition>, RuleError> {
+        let upper = input.to_uppercase();
+        let quantifier = if upper.starts_with("ANY(") {
+            GroupQuantifier::Any
+        } else if upper.starts_with("ALL(") {
+            GroupQuantifier::All
+        } else {
+            return Ok(None);
+        };
+
+        // Selectors never contain parentheses, so the first ')' closes it
+        let rest = &input[4..];
+        let close_pos = rest
+            .find(')')
+            .ok_or_else(|| RuleError::Parse(format!("Unclosed selector: {}", input)))?;
+        let selector = DeviceSelector::parse(&rest[..close_pos])
+            .map_err(|e| RuleError::Parse(e.to_string()))?;
+        if selector.is_empty() {
+            return Err(RuleError::Parse(format!(
+                "Empty device selector: {}",
+                input
+            )));
+        }
+
+        let comparison = rest[close_pos + 1..].trim();
+        let comparison = comparison.strip_prefix('.').ok_or_else(|| {
+            RuleError::Parse(format!("Expected .metric after selector: {}", input))
+        })?;
+        let (_, metric, operator, threshold) =
+            Self::parse_simple_condition(&format!(".{}", comparison), false)?;
+
+        Ok(Some(RuleCondition::DeviceGroup {
+            selector,
+            quantifier,
+            metric,
+            operator,
+            threshold,
+        }))
+    }
+
     /// Find matching closing parenthesis.
     fn find_matching_paren(input: &str, _start: usize) -> Option<usize> {
         let mut depth = 0;
@@ -1441,6 +1535,66 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_parse_device_group_condition() {
+        let dsl = r#"
+            RULE "Freezers"
+            WHEN ALL(group:freezers,location:store-17/main).temperature >= -10
+            DO
+                NOTIFY "Freezers warming up"
+            END
+        "#;
+
+        let rule = RuleDslParser::parse(dsl).unwrap();
+        match rule.condition {
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                assert_eq!(selector.group.as_deref(), Some("freezers"));
+                assert_eq!(selector.location.unwrap().building.as_deref(), Some("main"));
+                assert_eq!(quantifier, GroupQuantifier::All);
+                assert_eq!(metric, "temperature");
+                assert_eq!(operator, ComparisonOperator::GreaterEqual);
+                assert_eq!(threshold, -10.0);
+            }
+            _ => panic!("Expected DeviceGroup condition"),
+        }
+
+        let dsl = r#"
+            RULE "Mixed"
+            WHEN ANY(tag:outdoor).humidity < 20 AND sensor.temperature > 30
+            DO
+                NOTIFY "Dry and hot"
+            END
+        "#;
+        let rule = RuleDslParser::parse(dsl).unwrap();
+        match rule.condition {
+            RuleCondition::And(conditions) => {
+                assert!(matches!(
+                    conditions[0],
+                    RuleCondition::DeviceGroup {
+                        quantifier: GroupQuantifier::Any,
+                        ..
+                    }
+                ));
+            }
+            _ => panic!("Expected AND condition"),
+        }
+
+        let dsl = r#"
+            RULE "Bad"
+            WHEN ANY().temperature > 1
+            DO
+                NOTIFY "x"
+            END
+        "#;
+        assert!(RuleDslParser::parse(dsl).is_err());
+    }
+
     #[test]
     fn test_parse_set_action() {
         let dsl = r#"
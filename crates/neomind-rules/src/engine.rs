@@ -15,9 +15,11 @@ use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 use uuid::Uuid;
 
+use neomind_devices::{DeviceConfig, DeviceSelector};
+
 use super::dependencies::DependencyManager;
 use super::device_integration::DeviceActionExecutor;
-use super::dsl::{ParsedRule, RuleAction, RuleCondition, RuleError};
+use super::dsl::{GroupQuantifier, ParsedRule, RuleAction, RuleCondition, RuleError};
 use super::extension_integration::{try_parse_extension_action, ExtensionActionExecutor};
 use super::store::RuleStore;
 
@@ -149,7 +151,9 @@ impl CompiledRule {
     /// Check if the rule should trigger based on current values.
     /// This now supports complex conditions through value provider.
     pub fn should_trigger(&self, value_provider: &dyn ValueProvider) -> bool {
-        let condition_met = self.evaluate_condition(&self.condition, value_provider);
+        let Some(condition_met) = self.evaluate_condition(&self.condition, value_provider) else {
+            return false;
+        };
 
         if let Some(duration) = self.for_duration {
             if condition_met {
@@ -167,8 +171,12 @@ impl CompiledRule {
     }
 
     /// Update the rule's state based on current evaluation.
+    /// Left unchanged while the condition cannot be decided.
     pub fn update_state(&mut self, value_provider: &dyn ValueProvider) {
-        let condition_met = self.evaluate_condition(&self.condition, value_provider);
+        let Some(condition_met) = self.evaluate_condition(&self.condition, value_provider) else {
+            tracing::debug!(rule_id = %self.id, "Skipping rule evaluation: device selector unresolved");
+            return;
+        };
 
         if condition_met {
             if self.state.condition_true_since.is_none() {
@@ -243,11 +251,14 @@ impl CompiledRule {
     }
 
     /// Evaluate a condition with the given value provider.
+    ///
+    /// Returns `None` if the condition cannot be decided right now because a device
+    /// selector could not be resolved.
     fn evaluate_condition(
         &self,
         condition: &RuleCondition,
         value_provider: &dyn ValueProvider,
-    ) -> bool {
+    ) -> Option<bool> {
         // Build device ID mapping from source (cache this for efficiency)
         let device_id_mapping = self.build_device_id_mapping();
 
@@ -260,8 +271,8 @@ impl CompiledRule {
         condition: &RuleCondition,
         value_provider: &dyn ValueProvider,
         device_id_mapping: &std::collections::HashMap<String, String>,
-    ) -> bool {
-        match condition {
+    ) -> Option<bool> {
+        let met = match condition {
             RuleCondition::Device {
                 device_id,
                 metric,
@@ -314,16 +325,45 @@ impl CompiledRule {
                     false
                 }
             }
-            RuleCondition::And(conditions) => conditions.iter().all(|c| {
-                self.evaluate_condition_with_mapping(c, value_provider, device_id_mapping)
-            }),
-            RuleCondition::Or(conditions) => conditions.iter().any(|c| {
-                self.evaluate_condition_with_mapping(c, value_provider, device_id_mapping)
-            }),
-            RuleCondition::Not(condition) => {
-                !self.evaluate_condition_with_mapping(condition, value_provider, device_id_mapping)
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                metric,
+                operator,
+                threshold,
+            } => {
+                let device_ids = value_provider.resolve_devices(selector)?;
+                let satisfied = |id: &String| {
+                    value_provider
+                        .get_value(id, metric)
+                        .is_some_and(|value| operator.evaluate(value, *threshold))
+                };
+                match quantifier {
+                    GroupQuantifier::Any => device_ids.iter().any(satisfied),
+                    GroupQuantifier::All => {
+                        !device_ids.is_empty() && device_ids.iter().all(satisfied)
+                    }
+                }
             }
-        }
+            RuleCondition::And(conditions) => conditions
+                .iter()
+                .map(|c| self.evaluate_condition_with_mapping(c, value_provider, device_id_mapping))
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .all(|met| met),
+            RuleCondition::Or(conditions) => conditions
+                .iter()
+                .map(|c| self.evaluate_condition_with_mapping(c, value_provider, device_id_mapping))
+                .collect::<Option<Vec<_>>>()?
+                .into_iter()
+                .any(|met| met),
+            RuleCondition::Not(condition) => !self.evaluate_condition_with_mapping(
+                condition,
+                value_provider,
+                device_id_mapping,
+            )?,
+        };
+        Some(met)
     }
 }
 
@@ -349,6 +389,15 @@ pub trait ValueProvider: Send + Sync {
     /// Get the current value for a device metric.
     fn get_value(&self, device_id: &str, metric: &str) -> Option<f64>;
 
+    /// Resolve the IDs of devices matching a group/tag/location selector.
+    ///
+    /// Returns `None` if the devices cannot be resolved right now (no registry, or
+    /// the registry is busy); group conditions then skip the evaluation instead of
+    /// matching an empty group.
+    fn resolve_devices(&self, _selector: &DeviceSelector) -> Option<Vec<String>> {
+        None
+    }
+
     /// Get as Any for downcasting.
     fn as_any(&self) -> &dyn Any;
 }
@@ -1234,15 +1283,22 @@ impl RuleEngine {
 /// Simple in-memory value provider for testing.
 pub struct InMemoryValueProvider {
     values: Arc<StdRwLock<HashMap<String, f64>>>,
+    devices: Arc<StdRwLock<Vec<DeviceConfig>>>,
 }
 
 impl InMemoryValueProvider {
     pub fn new() -> Self {
         Self {
             values: Arc::new(StdRwLock::new(HashMap::new())),
+            devices: Arc::new(StdRwLock::new(Vec::new())),
         }
     }
 
+    /// Add a device so group conditions can select it.
+    pub fn add_device(&self, config: DeviceConfig) {
+        self.devices.write().unwrap().push(config);
+    }
+
     /// Set a value for a device metric.
     pub fn set_value(&self, device_id: &str, metric: &str, value: f64) {
         let mut values = self.values.write().unwrap();
@@ -1264,6 +1320,17 @@ impl ValueProvider for InMemoryValueProvider {
         values.get(&key).copied()
     }
 
+    fn resolve_devices(&self, selector: &DeviceSelector) -> Option<Vec<String>> {
+        let devices = self.devices.read().unwrap();
+        Some(
+            devices
+                .iter()
+                .filter(|d| selector.matches(d))
+                .map(|d| d.device_id.clone())
+                .collect(),
+        )
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -1348,6 +1415,95 @@ mod tests {
         assert_eq!(triggered.len(), 1);
     }
 
+    #[tokio::test]
+    async fn test_device_group_condition() {
+        let provider = Arc::new(InMemoryValueProvider::new());
+        for id in ["freezer1", "freezer2"] {
+            provider.add_device(DeviceConfig {
+                device_id: id.to_string(),
+                name: id.to_string(),
+                device_type: "freezer".to_string(),
+                adapter_type: "mqtt".to_string(),
+                connection_config: Default::default(),
+                adapter_id: None,
+                location: None,
+                tags: Vec::new(),
+                groups: vec!["freezers".to_string()],
+            });
+        }
+        provider.set_value("freezer1", "temperature", -18.0);
+        provider.set_value("freezer2", "temperature", -20.0);
+        let engine = RuleEngine::new(provider.clone());
+
+        let any_id = engine
+            .add_rule_from_dsl(
+                r#"
+            RULE "Any freezer warm"
+            WHEN ANY(group:freezers).temperature > -10
+            DO
+                NOTIFY "Freezer too warm"
+            END
+        "#,
+            )
+            .await
+            .unwrap();
+        let all_id = engine
+            .add_rule_from_dsl(
+                r#"
+            RULE "All freezers warm"
+            WHEN ALL(group:freezers).temperature > -10
+            DO
+                NOTIFY "All freezers too warm"
+            END
+        "#,
+            )
+            .await
+            .unwrap();
+
+        engine.update_states().await;
+        assert!(engine.evaluate_rules().await.is_empty());
+
+        provider.set_value("freezer1", "temperature", -5.0);
+        engine.update_states().await;
+        assert_eq!(engine.evaluate_rules().await, vec![any_id.clone()]);
+
+        provider.set_value("freezer2", "temperature", -2.0);
+        engine.update_states().await;
+        let triggered = engine.evaluate_rules().await;
+        assert!(triggered.contains(&any_id) && triggered.contains(&all_id));
+    }
+
+    #[tokio::test]
+    async fn test_unresolved_device_group_skips_evaluation() {
+        // A provider that cannot resolve selectors must not look like an empty group
+        struct Unresolved;
+        impl ValueProvider for Unresolved {
+            fn get_value(&self, _device_id: &str, _metric: &str) -> Option<f64> {
+                None
+            }
+            fn as_any(&self) -> &dyn Any {
+                self
+            }
+        }
+
+        let engine = RuleEngine::new(Arc::new(Unresolved));
+        engine
+            .add_rule_from_dsl(
+                r#"
+            RULE "No freezer warm"
+            WHEN NOT ANY(group:freezers).temperature > -10
+            DO
+                NOTIFY "All freezers cold"
+            END
+        "#,
+            )
+            .await
+            .unwrap();
+
+        engine.update_states().await;
+        assert!(engine.evaluate_rules().await.is_empty());
+    }
+
     #[tokio::test]
     async fn test_scheduler_start_stop() {
         let provider = Arc::new(InMemoryValueProvider::new());
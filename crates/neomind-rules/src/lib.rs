@@ -53,7 +53,10 @@ pub use device_integration::{
     CoreExtensionRegistryAdapter, DeviceActionExecutor, DeviceIntegratedRuleEngine,
     DeviceIntegrationError, DeviceIntegrationResult, DeviceValueProvider,
 };
-pub use dsl::{ComparisonOperator, LogLevel, ParsedRule, RuleAction, RuleCondition, RuleDslParser};
+pub use dsl::{
+    ComparisonOperator, GroupQuantifier, LogLevel, ParsedRule, RuleAction, RuleCondition,
+    RuleDslParser,
+};
 pub use engine::{
     CompiledRule, InMemoryValueProvider, RuleEngine, RuleExecutionResult, RuleId, RuleState,
     RuleStatus, ValueProvider,
@@ -4,6 +4,7 @@
 
 use crate::engine::{CompiledRule, RuleId};
 use crate::history::RuleHistoryEntry;
+use neomind_storage::snapshot::begin_write;
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
@@ -195,7 +196,7 @@ impl RuleStore {
         let key = format!("rule:{}", rule.id);
         let value = serde_json::to_vec(rule)?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(RULES_TABLE)?;
             table.insert(key.as_str(), value.as_slice())?;
@@ -224,7 +225,7 @@ impl RuleStore {
     pub fn delete(&self, id: &RuleId) -> Result<bool> {
         let key = format!("rule:{}", id);
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(RULES_TABLE)?;
             let result = table.remove(key.as_str())?.is_some();
@@ -310,7 +311,7 @@ impl RuleStore {
         let key = format!("history:{}", entry.id);
         let value = serde_json::to_vec(entry)?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(RULE_HISTORY_TABLE)?;
             table.insert(key.as_str(), value.as_slice())?;
@@ -387,7 +388,7 @@ impl RuleStore {
         let rule_id_str = rule_id.to_string();
         let mut removed = 0;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(RULE_HISTORY_TABLE)?;
             let mut iter = table.iter()?;
@@ -417,7 +418,7 @@ impl RuleStore {
 
     /// Clear all rules.
     pub fn clear_all(&self) -> Result<()> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut rules_table = write_txn.open_table(RULES_TABLE)?;
             let mut history_table = write_txn.open_table(RULE_HISTORY_TABLE)?;
@@ -14,6 +14,7 @@ use tokio::sync::RwLock;
 
 use crate::engine::ValueProvider;
 use neomind_core::datasource::{DataSourceId, DataSourceType};
+use neomind_devices::{DeviceRegistry, DeviceSelector};
 
 /// Cache entry for metric values.
 #[derive(Debug, Clone)]
@@ -57,6 +58,8 @@ pub struct UnifiedValueProvider {
     device_storage: Arc<RwLock<Option<Arc<dyn DeviceStorageLike>>>>,
     /// Optional extension storage for querying current values
     extension_storage: Arc<RwLock<Option<Arc<dyn ExtensionStorageLike>>>>,
+    /// Optional device registry for resolving device group conditions
+    device_registry: Arc<RwLock<Option<Arc<DeviceRegistry>>>>,
 }
 
 /// Trait for device storage abstraction.
@@ -79,6 +82,7 @@ impl UnifiedValueProvider {
             default_ttl_ms: 5000, // 5 seconds default TTL
             device_storage: Arc::new(RwLock::new(None)),
             extension_storage: Arc::new(RwLock::new(None)),
+            device_registry: Arc::new(RwLock::new(None)),
         }
     }
 
@@ -100,6 +104,12 @@ impl UnifiedValueProvider {
         *es = Some(storage);
     }
 
+    /// Set the device registry used to resolve group/tag/location selectors (async setter).
+    pub async fn set_device_registry(&self, registry: Arc<DeviceRegistry>) {
+        let mut dr = self.device_registry.write().await;
+        *dr = Some(registry);
+    }
+
     /// Set both storages (async setter).
     pub async fn set_storages(
         &self,
@@ -303,6 +313,15 @@ impl ValueProvider for UnifiedValueProvider {
         None
     }
 
+    fn resolve_devices(&self, selector: &DeviceSelector) -> Option<Vec<String>> {
+        // Synchronous access - a registry that is being updated is reported as unresolved
+        self.device_registry
+            .try_read()
+            .ok()?
+            .as_ref()?
+            .try_select_device_ids(selector)
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -360,6 +360,23 @@ impl RuleValidator {
                 // Note: More detailed extension validation could be added here
                 let _ = (extension_id, metric);
             }
+            RuleCondition::DeviceGroup {
+                selector,
+                quantifier,
+                ..
+            } => {
+                // Group members are resolved from the device registry at evaluation time
+                issues.push(ValidationIssue {
+                    code: "DEVICE_GROUP".to_string(),
+                    message: format!(
+                        "Condition applies to {} devices matching '{}'",
+                        quantifier.as_str(),
+                        selector
+                    ),
+                    field: Some("condition.selector".to_string()),
+                    severity: ValidationSeverity::Info,
+                });
+            }
             RuleCondition::And(conditions) | RuleCondition::Or(conditions) => {
                 // Recursively validate each sub-condition
                 for cond in conditions {
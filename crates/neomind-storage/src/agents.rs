@@ -11,13 +11,17 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 
-use crate::Error;
+use crate::snapshot::begin_write;
+use crate::{Error, ResponseCacheConfig};
 
 // Tables for agent storage
 const AGENTS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("agents");
 const AGENT_EXECUTIONS_TABLE: TableDefinition<&str, &[u8]> =
     TableDefinition::new("agent_executions");
 const AGENT_MEMORY_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("agent_memory");
+const AGENT_APPROVALS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("agent_approvals");
+const EXECUTION_CHECKPOINTS_TABLE: TableDefinition<&str, &[u8]> =
+    TableDefinition::new("agent_execution_checkpoints");
 
 /// AI Agent store for persisting autonomous agents.
 pub struct AgentStore {
@@ -44,6 +48,12 @@ pub struct AiAgent {
     /// Optional LLM backend ID for this agent (uses default if not specified)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub llm_backend_id: Option<String>,
+    /// Optional LLM routing policy ID (takes precedence over `llm_backend_id`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub llm_routing_policy: Option<String>,
+    /// Opt-in LLM response caching for this agent's executions
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub response_cache: Option<ResponseCacheConfig>,
     /// AI-generated understanding of the requirements
     pub parsed_intent: Option<ParsedIntent>,
     /// Selected resources (devices, metrics, commands)
@@ -83,6 +93,9 @@ pub struct AiAgent {
     /// Maximum chain depth (prevents infinite loops)
     #[serde(default = "default_max_chain_depth")]
     pub max_chain_depth: usize,
+    /// Actions that must be approved by a person before they run
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<ApprovalPolicy>,
     /// Error message (if status is error)
     pub error_message: Option<String>,
 }
@@ -156,6 +169,12 @@ pub enum ResourceType {
     DataStream,
     ExtensionTool,
     ExtensionMetric,
+    /// Devices matching a selector such as `group:freezers` (resource_id holds the selector)
+    DeviceGroup,
+    /// Tool of an external MCP server (resource_id is `mcp:{server_id}:{tool_name}`)
+    McpTool,
+    /// Another agent that tasks can be delegated to (resource_id is `agent:{agent_id}`)
+    Agent,
 }
 
 /// Agent schedule configuration.
@@ -734,6 +753,9 @@ pub struct ConversationTurn {
     pub duration_ms: u64,
     /// Whether this turn completed successfully
     pub success: bool,
+    /// Tasks delegated to or received from other agents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_messages: Vec<AgentMessage>,
 }
 
 /// User message sent to an agent between executions.
@@ -773,6 +795,9 @@ pub struct AgentExecutionRecord {
     pub duration_ms: u64,
     /// Error message if failed
     pub error: Option<String>,
+    /// Dry run: actions in `result` were recorded, not executed
+    #[serde(default)]
+    pub simulated: bool,
 }
 
 /// Execution status.
@@ -867,6 +892,9 @@ pub struct ExecutionResult {
     pub summary: String,
     /// Success rate (0-1)
     pub success_rate: f32,
+    /// Tasks delegated to or received from other agents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub agent_messages: Vec<AgentMessage>,
 }
 
 /// An action that was executed.
@@ -884,6 +912,9 @@ pub struct ActionExecuted {
     pub success: bool,
     /// Result or error
     pub result: Option<String>,
+    /// Approval request holding this action back, if approval was required
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_id: Option<String>,
 }
 
 /// A generated report.
@@ -927,6 +958,37 @@ pub struct NotificationSent {
     pub success: bool,
 }
 
+/// A task delegated from one agent to another, with the delegate's response.
+///
+/// The same message is recorded in the executions and conversation history
+/// of both agents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AgentMessage {
+    /// Delegating agent ID
+    pub from_agent_id: String,
+    /// Delegating agent name
+    pub from_agent_name: String,
+    /// ID of the agent the task was delegated to
+    pub to_agent_id: String,
+    /// Name of the agent the task was delegated to
+    pub to_agent_name: String,
+    /// Task description
+    pub task: String,
+    /// Structured task arguments
+    #[serde(default)]
+    pub arguments: serde_json::Value,
+    /// Delegate's answer (its execution summary), or the reason it failed
+    pub response: Option<String>,
+    /// Whether the delegate completed the task
+    pub success: bool,
+    /// Execution of the delegating agent
+    pub caller_execution_id: String,
+    /// Execution of the delegate, if it ran
+    pub execution_id: Option<String>,
+    /// When the task was sent
+    pub timestamp: i64,
+}
+
 /// Query filter for agents.
 #[derive(Debug, Clone, Default)]
 pub struct AgentFilter {
@@ -961,16 +1023,264 @@ pub struct ExecutionFilter {
     pub offset: Option<usize>,
 }
 
+/// Actions of an agent that must be approved by a person before they run.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalPolicy {
+    /// An action needs approval when any of these rules matches it
+    #[serde(default)]
+    pub rules: Vec<ApprovalRule>,
+    /// Seconds a request stays open before it expires
+    #[serde(default = "default_approval_expiry")]
+    pub expires_after_secs: u64,
+}
+
+/// Default value for approval request expiry.
+fn default_approval_expiry() -> u64 {
+    3600 // One hour
+}
+
+impl Default for ApprovalPolicy {
+    fn default() -> Self {
+        Self {
+            rules: Vec::new(),
+            expires_after_secs: default_approval_expiry(),
+        }
+    }
+}
+
+impl ApprovalPolicy {
+    /// Check whether an action needs approval.
+    pub fn requires_approval(&self, action: &PendingAction) -> bool {
+        self.rules.iter().any(|rule| rule.matches(action))
+    }
+}
+
+/// A condition on actions that need approval.
+///
+/// Unset fields match any action. Values compare case-insensitively and a
+/// trailing `*` matches a prefix, so `delete_*` covers every delete command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    /// Action type (`device_command`, `extension_command` or `mcp_tool`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action_type: Option<String>,
+    /// Device type of the command target, e.g. `boiler`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// Device ID, extension ID or `mcp:{server_id}:{tool_name}`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// Command or tool name
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl ApprovalRule {
+    /// Check whether this rule matches an action.
+    pub fn matches(&self, action: &PendingAction) -> bool {
+        fn field_matches(pattern: &Option<String>, value: Option<&str>) -> bool {
+            let Some(pattern) = pattern else {
+                return true;
+            };
+            let Some(value) = value else {
+                return false;
+            };
+            let pattern = pattern.to_lowercase();
+            let value = value.to_lowercase();
+            match pattern.strip_suffix('*') {
+                Some(prefix) => value.starts_with(prefix),
+                None => value == pattern,
+            }
+        }
+
+        field_matches(&self.action_type, Some(&action.action_type))
+            && field_matches(&self.device_type, action.device_type.as_deref())
+            && field_matches(&self.target, Some(&action.target))
+            && field_matches(&self.command, Some(&action.command))
+    }
+}
+
+/// An action suspended until it is approved.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PendingAction {
+    /// Action type (`device_command`, `extension_command` or `mcp_tool`)
+    pub action_type: String,
+    /// Device ID, extension ID or `mcp:{server_id}:{tool_name}`
+    pub target: String,
+    /// Command or tool name
+    pub command: String,
+    /// Device type of the target, for device commands
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_type: Option<String>,
+    /// Parameters the action will run with
+    pub parameters: serde_json::Value,
+}
+
+/// State of an approval request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ApprovalStatus {
+    /// Waiting for a decision
+    Pending,
+    /// Approved; the action runs once the decision is stored
+    Approved,
+    /// Denied by an approver
+    Denied,
+    /// Not decided before it expired
+    Expired,
+}
+
+/// An agent action waiting in the approval queue.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRequest {
+    /// Unique request ID
+    pub id: String,
+    /// Agent ID
+    pub agent_id: String,
+    /// Agent name
+    pub agent_name: String,
+    /// Execution that proposed the action
+    pub execution_id: String,
+    /// The suspended action
+    pub action: PendingAction,
+    /// LLM decision that led to the action
+    pub decision: Decision,
+    /// Situation analysis of the execution
+    pub reasoning: String,
+    /// Request state
+    pub status: ApprovalStatus,
+    /// Creation timestamp
+    pub created_at: i64,
+    /// Timestamp after which the request can no longer be approved
+    pub expires_at: i64,
+    /// Decision timestamp
+    pub decided_at: Option<i64>,
+    /// User who approved or denied the request
+    pub decided_by: Option<String>,
+    /// Comment of the approver
+    pub comment: Option<String>,
+    /// Outcome of the action once approved
+    pub result: Option<ActionExecuted>,
+}
+
+impl ApprovalRequest {
+    /// Check whether a pending request is past its expiry.
+    pub fn is_expired(&self, now: i64) -> bool {
+        self.status == ApprovalStatus::Pending && now >= self.expires_at
+    }
+}
+
+/// Query filter for approval requests.
+#[derive(Debug, Clone, Default)]
+pub struct ApprovalFilter {
+    /// Filter by agent ID
+    pub agent_id: Option<String>,
+    /// Filter by request state
+    pub status: Option<ApprovalStatus>,
+    /// Maximum number of results
+    pub limit: Option<usize>,
+}
+
+/// Progress of a running execution, kept so it can be recovered after a
+/// crash or restart.
+///
+/// The checkpoint is written when the execution starts, after each LLM turn
+/// and after each decision, and removed once the execution record is saved.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionCheckpoint {
+    /// Execution ID
+    pub execution_id: String,
+    /// Agent ID
+    pub agent_id: String,
+    /// Trigger type (schedule, event, manual)
+    pub trigger_type: String,
+    /// Event data (if event-triggered)
+    #[serde(default)]
+    pub event_data: Option<serde_json::Value>,
+    /// Start timestamp
+    pub started_at: i64,
+    /// Last update timestamp
+    pub updated_at: i64,
+    /// Tool chaining rounds completed so far
+    pub chain_depth: usize,
+    /// Prompt of the next round, including the tool chaining context so far
+    pub prompt: String,
+    /// Actions of the completed rounds
+    #[serde(default)]
+    pub actions_executed: Vec<ActionExecuted>,
+    /// Notifications of the completed rounds
+    #[serde(default)]
+    pub notifications_sent: Vec<NotificationSent>,
+    /// Delegated tasks of the completed rounds
+    #[serde(default)]
+    pub agent_messages: Vec<AgentMessage>,
+    /// Round in progress, once its LLM turn has completed
+    #[serde(default)]
+    pub round: Option<RoundCheckpoint>,
+}
+
+/// LLM turn of a tool chaining round and the progress of its decisions.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundCheckpoint {
+    /// Data given to the LLM
+    pub data_collected: Vec<DataCollected>,
+    /// Situation analysis of the LLM
+    pub situation_analysis: String,
+    /// Reasoning steps of the LLM
+    pub reasoning_steps: Vec<ReasoningStep>,
+    /// Decisions of the LLM, executed in order
+    pub decisions: Vec<Decision>,
+    /// Conclusion of the LLM
+    pub conclusion: String,
+    /// Number of decisions that have finished
+    #[serde(default)]
+    pub completed_decisions: usize,
+    /// Decision that was executing when the checkpoint was written.
+    ///
+    /// Its side effects may already have happened, so it is never re-run.
+    #[serde(default)]
+    pub in_flight: Option<usize>,
+    /// Actions of this round so far
+    #[serde(default)]
+    pub actions_executed: Vec<ActionExecuted>,
+    /// Notifications of this round so far
+    #[serde(default)]
+    pub notifications_sent: Vec<NotificationSent>,
+    /// Delegated tasks of this round so far
+    #[serde(default)]
+    pub agent_messages: Vec<AgentMessage>,
+}
+
+impl RoundCheckpoint {
+    /// Decisions that have not finished yet, including one in flight.
+    pub fn pending_decisions(&self) -> &[Decision] {
+        &self.decisions[self.completed_decisions.min(self.decisions.len())..]
+    }
+}
+
+/// What to do on startup with executions interrupted by a crash or restart.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InterruptedExecutionPolicy {
+    /// Continue from the last checkpoint
+    #[default]
+    Resume,
+    /// Record the execution as failed
+    Fail,
+}
+
 impl AgentStore {
     /// Open or create an agent store at the given path.
     pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Arc<Self>, Error> {
         let db = Database::create(path)?;
-        let write_txn = db.begin_write()?;
+        let write_txn = begin_write(&db)?;
 
         // Create tables if they don't exist
         write_txn.open_table(AGENTS_TABLE)?;
         write_txn.open_table(AGENT_EXECUTIONS_TABLE)?;
         write_txn.open_table(AGENT_MEMORY_TABLE)?;
+        write_txn.open_table(AGENT_APPROVALS_TABLE)?;
+        write_txn.open_table(EXECUTION_CHECKPOINTS_TABLE)?;
         write_txn.commit()?;
 
         Ok(Arc::new(Self { db: Arc::new(db) }))
@@ -985,7 +1295,7 @@ impl AgentStore {
 
     /// Save an agent to the store.
     pub async fn save_agent(&self, agent: &AiAgent) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
 
@@ -1086,7 +1396,7 @@ impl AgentStore {
         drop(table);
         drop(read_txn);
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
 
@@ -1121,7 +1431,7 @@ impl AgentStore {
         drop(table);
         drop(read_txn);
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
 
@@ -1151,7 +1461,7 @@ impl AgentStore {
         };
 
         // Now start write transaction and update both tables
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
 
         // Update memory in dedicated table
         {
@@ -1233,7 +1543,7 @@ impl AgentStore {
 
         // Only write to AGENTS_TABLE, not to AGENT_MEMORY_TABLE
         // (update_agent_memory handles writing to AGENT_MEMORY_TABLE)
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
 
@@ -1248,7 +1558,7 @@ impl AgentStore {
 
     /// Delete an agent by ID.
     pub async fn delete_agent(&self, id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
             table.remove(id)?;
@@ -1263,7 +1573,7 @@ impl AgentStore {
 
     /// Save an execution record.
     pub async fn save_execution(&self, execution: &AgentExecutionRecord) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENT_EXECUTIONS_TABLE)?;
 
@@ -1284,7 +1594,7 @@ impl AgentStore {
         agent_id: Option<&str>,
         conversation_turn: Option<&ConversationTurn>,
     ) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
 
         // Save execution record
         {
@@ -1459,7 +1769,7 @@ impl AgentStore {
             return Ok(0);
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENT_EXECUTIONS_TABLE)?;
             for key in &to_remove {
@@ -1471,6 +1781,240 @@ impl AgentStore {
         Ok(to_remove.len())
     }
 
+    // ========== Approval Queue Methods ==========
+
+    /// Save an approval request.
+    pub async fn save_approval(&self, request: &ApprovalRequest) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(AGENT_APPROVALS_TABLE)?;
+            let value =
+                serde_json::to_vec(request).map_err(|e| Error::Serialization(e.to_string()))?;
+            table.insert(request.id.as_str(), value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Get an approval request by ID.
+    pub async fn get_approval(&self, id: &str) -> Result<Option<ApprovalRequest>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(AGENT_APPROVALS_TABLE)?;
+
+        match table.get(id)? {
+            Some(bytes) => {
+                let request: ApprovalRequest = serde_json::from_slice(bytes.value())
+                    .map_err(|e| Error::Serialization(e.to_string()))?;
+                Ok(Some(request))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Query approval requests, newest first.
+    pub async fn query_approvals(
+        &self,
+        filter: ApprovalFilter,
+    ) -> Result<Vec<ApprovalRequest>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(AGENT_APPROVALS_TABLE)?;
+
+        let mut requests = Vec::new();
+        for item in table.iter()? {
+            let (_id, bytes) = item?;
+            let request: ApprovalRequest = serde_json::from_slice(bytes.value())
+                .map_err(|e| Error::Serialization(e.to_string()))?;
+
+            let agent_matches = filter
+                .agent_id
+                .as_ref()
+                .is_none_or(|agent_id| &request.agent_id == agent_id);
+            let status_matches = filter.status.is_none_or(|status| request.status == status);
+            if agent_matches && status_matches {
+                requests.push(request);
+            }
+        }
+
+        requests.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        if let Some(limit) = filter.limit {
+            requests.truncate(limit);
+        }
+
+        Ok(requests)
+    }
+
+    /// Decide on a pending approval request.
+    ///
+    /// The request moves from pending to `status` in a single write
+    /// transaction, so of concurrent decisions exactly one succeeds. Fails
+    /// with [`Error::InvalidInput`] if the request is no longer pending or
+    /// has expired.
+    pub async fn decide_approval(
+        &self,
+        id: &str,
+        status: ApprovalStatus,
+        decided_by: &str,
+        comment: Option<String>,
+        now: i64,
+    ) -> Result<ApprovalRequest, Error> {
+        let write_txn = begin_write(&self.db)?;
+        let request = {
+            let mut table = write_txn.open_table(AGENT_APPROVALS_TABLE)?;
+            let mut request: ApprovalRequest = match table.get(id)? {
+                Some(bytes) => serde_json::from_slice(bytes.value())
+                    .map_err(|e| Error::Serialization(e.to_string()))?,
+                None => return Err(Error::NotFound(format!("Approval request {}", id))),
+            };
+            if request.status != ApprovalStatus::Pending {
+                return Err(Error::InvalidInput(format!(
+                    "Approval request {} is no longer pending",
+                    id
+                )));
+            }
+            if request.is_expired(now) {
+                return Err(Error::InvalidInput(format!(
+                    "Approval request {} has expired",
+                    id
+                )));
+            }
+
+            request.status = status;
+            request.decided_at = Some(now);
+            request.decided_by = Some(decided_by.to_string());
+            request.comment = comment;
+            let value =
+                serde_json::to_vec(&request).map_err(|e| Error::Serialization(e.to_string()))?;
+            table.insert(id, value.as_slice())?;
+            request
+        };
+        write_txn.commit()?;
+        Ok(request)
+    }
+
+    /// Mark pending requests past their expiry as expired and return them,
+    /// newest first.
+    pub async fn expire_approvals(&self, now: i64) -> Result<Vec<ApprovalRequest>, Error> {
+        let write_txn = begin_write(&self.db)?;
+        let mut expired = Vec::new();
+        {
+            let mut table = write_txn.open_table(AGENT_APPROVALS_TABLE)?;
+            for item in table.iter()? {
+                let (_id, bytes) = item?;
+                let request: ApprovalRequest = serde_json::from_slice(bytes.value())
+                    .map_err(|e| Error::Serialization(e.to_string()))?;
+                if request.is_expired(now) {
+                    expired.push(request);
+                }
+            }
+
+            for request in &mut expired {
+                request.status = ApprovalStatus::Expired;
+                request.decided_at = Some(now);
+                let value =
+                    serde_json::to_vec(request).map_err(|e| Error::Serialization(e.to_string()))?;
+                table.insert(request.id.as_str(), value.as_slice())?;
+            }
+        }
+        write_txn.commit()?;
+
+        expired.sort_by(|a, b| b.created_at.cmp(&a.created_at));
+        Ok(expired)
+    }
+
+    /// Replace an action held back for approval in its execution record.
+    ///
+    /// The action is found by its `approval_id`. Returns `false` if the
+    /// record or the action no longer exists.
+    pub async fn update_execution_action(
+        &self,
+        execution_id: &str,
+        action: &ActionExecuted,
+    ) -> Result<bool, Error> {
+        let Some(approval_id) = action.approval_id.as_deref() else {
+            return Ok(false);
+        };
+        let Some(mut execution) = self.get_execution(execution_id).await? else {
+            return Ok(false);
+        };
+        let Some(result) = execution.result.as_mut() else {
+            return Ok(false);
+        };
+        let Some(slot) = result
+            .actions_executed
+            .iter_mut()
+            .find(|a| a.approval_id.as_deref() == Some(approval_id))
+        else {
+            return Ok(false);
+        };
+
+        *slot = action.clone();
+        let success_count = result.actions_executed.iter().filter(|a| a.success).count();
+        result.success_rate = success_count as f32 / result.actions_executed.len() as f32;
+        self.save_execution(&execution).await?;
+        Ok(true)
+    }
+
+    // ========== Execution Checkpoint Methods ==========
+
+    /// Save (or replace) the checkpoint of a running execution.
+    pub async fn save_checkpoint(&self, checkpoint: &ExecutionCheckpoint) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(EXECUTION_CHECKPOINTS_TABLE)?;
+            let value =
+                serde_json::to_vec(checkpoint).map_err(|e| Error::Serialization(e.to_string()))?;
+            table.insert(checkpoint.execution_id.as_str(), value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Get the checkpoint of an execution.
+    pub async fn get_checkpoint(
+        &self,
+        execution_id: &str,
+    ) -> Result<Option<ExecutionCheckpoint>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(EXECUTION_CHECKPOINTS_TABLE)?;
+
+        match table.get(execution_id)? {
+            Some(bytes) => {
+                let checkpoint: ExecutionCheckpoint = serde_json::from_slice(bytes.value())
+                    .map_err(|e| Error::Serialization(e.to_string()))?;
+                Ok(Some(checkpoint))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// List the checkpoints of unfinished executions, oldest first.
+    pub async fn list_checkpoints(&self) -> Result<Vec<ExecutionCheckpoint>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(EXECUTION_CHECKPOINTS_TABLE)?;
+
+        let mut checkpoints = Vec::new();
+        for item in table.iter()? {
+            let (_id, bytes) = item?;
+            let checkpoint: ExecutionCheckpoint = serde_json::from_slice(bytes.value())
+                .map_err(|e| Error::Serialization(e.to_string()))?;
+            checkpoints.push(checkpoint);
+        }
+
+        checkpoints.sort_by_key(|c| c.started_at);
+        Ok(checkpoints)
+    }
+
+    /// Remove the checkpoint of a finished execution.
+    pub async fn delete_checkpoint(&self, execution_id: &str) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(EXECUTION_CHECKPOINTS_TABLE)?;
+            table.remove(execution_id)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
     /// Check if an agent matches the given filter.
     fn matches_agent_filter(&self, agent: &AiAgent, filter: &AgentFilter) -> bool {
         if let Some(status) = filter.status {
@@ -1571,7 +2115,7 @@ impl AgentStore {
         agent.updated_at = chrono::Utc::now().timestamp();
 
         // Save the updated agent
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
             let value =
@@ -1626,7 +2170,7 @@ impl AgentStore {
             agent.updated_at = chrono::Utc::now().timestamp();
 
             // Save the updated agent
-            let write_txn = self.db.begin_write()?;
+            let write_txn = begin_write(&self.db)?;
             {
                 let mut table = write_txn.open_table(AGENTS_TABLE)?;
                 let value =
@@ -1651,7 +2195,7 @@ impl AgentStore {
         agent.updated_at = chrono::Utc::now().timestamp();
 
         // Save the updated agent
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
             let value =
@@ -1705,7 +2249,7 @@ impl AgentStore {
         agent.updated_at = chrono::Utc::now().timestamp();
 
         // Save the updated agent
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
             let value =
@@ -1757,7 +2301,7 @@ impl AgentStore {
             agent.updated_at = chrono::Utc::now().timestamp();
 
             // Save the updated agent
-            let write_txn = self.db.begin_write()?;
+            let write_txn = begin_write(&self.db)?;
             {
                 let mut table = write_txn.open_table(AGENTS_TABLE)?;
                 let value =
@@ -1784,7 +2328,7 @@ impl AgentStore {
         agent.updated_at = chrono::Utc::now().timestamp();
 
         // Save the updated agent
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(AGENTS_TABLE)?;
             let value =
@@ -1815,6 +2359,8 @@ mod tests {
             description: None,
             user_prompt: "Monitor warehouse temperatures and alert if above 30°C".to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -1837,6 +2383,7 @@ mod tests {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
             error_message: None,
         };
 
@@ -1856,6 +2403,8 @@ mod tests {
             description: None,
             user_prompt: "Test".to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -1878,6 +2427,7 @@ mod tests {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
             error_message: None,
         };
 
@@ -1912,6 +2462,7 @@ mod tests {
             result: None,
             duration_ms: 150,
             error: None,
+            simulated: false,
         };
 
         store.save_execution(&execution).await.unwrap();
@@ -1930,6 +2481,8 @@ mod tests {
             description: None,
             user_prompt: "Learn patterns".to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -1952,6 +2505,7 @@ mod tests {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
             error_message: None,
         };
 
@@ -1994,6 +2548,8 @@ mod tests {
             description: None,
             user_prompt: "Test stats".to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: None,
             resources: vec![],
             schedule: AgentSchedule {
@@ -2016,6 +2572,7 @@ mod tests {
             context_window_size: 10,
             enable_tool_chaining: false,
             max_chain_depth: 3,
+            approval_policy: None,
             error_message: None,
         };
 
@@ -2043,4 +2600,287 @@ mod tests {
         assert_eq!(retrieved.stats.failed_executions, 1);
         assert_eq!(retrieved.stats.avg_duration_ms, 150); // (200 + 100) / 2
     }
+
+    fn boiler_command() -> PendingAction {
+        PendingAction {
+            action_type: "device_command".to_string(),
+            target: "boiler-1".to_string(),
+            command: "set_temperature".to_string(),
+            device_type: Some("Boiler".to_string()),
+            parameters: serde_json::json!({"value": 70}),
+        }
+    }
+
+    #[test]
+    fn test_approval_rule_matching() {
+        let policy = ApprovalPolicy {
+            rules: vec![
+                ApprovalRule {
+                    action_type: Some("device_command".to_string()),
+                    device_type: Some("boiler".to_string()),
+                    ..Default::default()
+                },
+                ApprovalRule {
+                    command: Some("delete_*".to_string()),
+                    ..Default::default()
+                },
+            ],
+            ..Default::default()
+        };
+        assert_eq!(policy.expires_after_secs, 3600);
+
+        let mut action = boiler_command();
+        assert!(policy.requires_approval(&action));
+
+        action.device_type = Some("sensor".to_string());
+        assert!(!policy.requires_approval(&action));
+
+        // Rules on device type never match actions without one
+        action.device_type = None;
+        assert!(!policy.requires_approval(&action));
+
+        action.action_type = "mcp_tool".to_string();
+        action.command = "delete_rule".to_string();
+        assert!(policy.requires_approval(&action));
+
+        assert!(!ApprovalPolicy::default().requires_approval(&action));
+    }
+
+    #[tokio::test]
+    async fn test_approval_queue() {
+        let store = test_store();
+        let now = chrono::Utc::now().timestamp();
+
+        let decision = Decision {
+            decision_type: "command".to_string(),
+            description: "Raise boiler temperature".to_string(),
+            action: "boiler-1:set_temperature".to_string(),
+            rationale: "Water temperature is low".to_string(),
+            expected_outcome: "Warmer water".to_string(),
+        };
+        let request = ApprovalRequest {
+            id: "approval-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            agent_name: "Boiler Agent".to_string(),
+            execution_id: "exec-1".to_string(),
+            action: boiler_command(),
+            decision,
+            reasoning: "Water temperature dropped to 40°C".to_string(),
+            status: ApprovalStatus::Pending,
+            created_at: now,
+            expires_at: now + 60,
+            decided_at: None,
+            decided_by: None,
+            comment: None,
+            result: None,
+        };
+        store.save_approval(&request).await.unwrap();
+        store
+            .save_approval(&ApprovalRequest {
+                id: "approval-2".to_string(),
+                agent_id: "agent-2".to_string(),
+                created_at: now + 1,
+                expires_at: now - 1,
+                ..request.clone()
+            })
+            .await
+            .unwrap();
+
+        let all = store
+            .query_approvals(ApprovalFilter::default())
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].id, "approval-2");
+
+        let expired = store.expire_approvals(now).await.unwrap();
+        assert_eq!(expired.len(), 1);
+        assert_eq!(expired[0].id, "approval-2");
+
+        let pending = store
+            .query_approvals(ApprovalFilter {
+                status: Some(ApprovalStatus::Pending),
+                ..Default::default()
+            })
+            .await
+            .unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].id, "approval-1");
+        assert_eq!(
+            store
+                .get_approval("approval-2")
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            ApprovalStatus::Expired
+        );
+
+        // Only the first decision on a pending request wins
+        let approved = store
+            .decide_approval(
+                "approval-1",
+                ApprovalStatus::Approved,
+                "admin",
+                Some("go".to_string()),
+                now,
+            )
+            .await
+            .unwrap();
+        assert_eq!(approved.status, ApprovalStatus::Approved);
+        assert_eq!(approved.decided_by.as_deref(), Some("admin"));
+        for id in ["approval-1", "approval-2"] {
+            let err = store
+                .decide_approval(id, ApprovalStatus::Denied, "operator", None, now)
+                .await
+                .unwrap_err();
+            assert!(matches!(err, Error::InvalidInput(_)));
+        }
+        assert!(matches!(
+            store
+                .decide_approval("missing", ApprovalStatus::Denied, "operator", None, now)
+                .await,
+            Err(Error::NotFound(_))
+        ));
+        assert_eq!(
+            store
+                .get_approval("approval-1")
+                .await
+                .unwrap()
+                .unwrap()
+                .status,
+            ApprovalStatus::Approved
+        );
+    }
+
+    #[tokio::test]
+    async fn test_update_execution_action() {
+        let store = test_store();
+
+        let pending = ActionExecuted {
+            action_type: "device_command".to_string(),
+            description: "Execute set_temperature on boiler-1".to_string(),
+            target: "boiler-1".to_string(),
+            parameters: serde_json::json!({}),
+            success: false,
+            result: Some("Awaiting approval".to_string()),
+            approval_id: Some("approval-1".to_string()),
+        };
+        let execution = AgentExecutionRecord {
+            id: "exec-1".to_string(),
+            agent_id: "agent-1".to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+            trigger_type: "schedule".to_string(),
+            status: ExecutionStatus::Completed,
+            decision_process: DecisionProcess {
+                situation_analysis: String::new(),
+                data_collected: vec![],
+                reasoning_steps: vec![],
+                decisions: vec![],
+                conclusion: String::new(),
+                confidence: 1.0,
+            },
+            result: Some(ExecutionResult {
+                actions_executed: vec![pending.clone()],
+                report: None,
+                notifications_sent: vec![],
+                summary: String::new(),
+                success_rate: 0.0,
+                agent_messages: vec![],
+            }),
+            duration_ms: 10,
+            error: None,
+            simulated: false,
+        };
+        store.save_execution(&execution).await.unwrap();
+
+        let executed = ActionExecuted {
+            success: true,
+            result: Some("Command sent successfully".to_string()),
+            ..pending
+        };
+        assert!(store
+            .update_execution_action("exec-1", &executed)
+            .await
+            .unwrap());
+        assert!(!store
+            .update_execution_action("exec-missing", &executed)
+            .await
+            .unwrap());
+
+        let result = store
+            .get_execution("exec-1")
+            .await
+            .unwrap()
+            .unwrap()
+            .result
+            .unwrap();
+        assert!(result.actions_executed[0].success);
+        assert_eq!(result.success_rate, 1.0);
+    }
+
+    #[tokio::test]
+    async fn test_execution_checkpoints() {
+        let store = test_store();
+
+        let decision = |action: &str| Decision {
+            decision_type: "command".to_string(),
+            description: format!("Run {}", action),
+            action: action.to_string(),
+            rationale: String::new(),
+            expected_outcome: String::new(),
+        };
+        let checkpoint = |id: &str, started_at: i64| ExecutionCheckpoint {
+            execution_id: id.to_string(),
+            agent_id: "agent-1".to_string(),
+            trigger_type: "schedule".to_string(),
+            event_data: None,
+            started_at,
+            updated_at: started_at,
+            chain_depth: 0,
+            prompt: "Keep the boiler warm".to_string(),
+            actions_executed: vec![],
+            notifications_sent: vec![],
+            agent_messages: vec![],
+            round: None,
+        };
+
+        store
+            .save_checkpoint(&checkpoint("exec-2", 20))
+            .await
+            .unwrap();
+        let mut first = checkpoint("exec-1", 10);
+        first.round = Some(RoundCheckpoint {
+            data_collected: vec![],
+            situation_analysis: "Boiler is cold".to_string(),
+            reasoning_steps: vec![],
+            decisions: vec![decision("boiler-1:heat"), decision("boiler-1:pump")],
+            conclusion: "Heat up".to_string(),
+            completed_decisions: 1,
+            in_flight: Some(1),
+            actions_executed: vec![],
+            notifications_sent: vec![],
+            agent_messages: vec![],
+        });
+        store.save_checkpoint(&first).await.unwrap();
+
+        let loaded = store.get_checkpoint("exec-1").await.unwrap().unwrap();
+        let round = loaded.round.unwrap();
+        assert_eq!(round.in_flight, Some(1));
+        assert_eq!(round.pending_decisions().len(), 1);
+        assert_eq!(round.pending_decisions()[0].action, "boiler-1:pump");
+
+        let ids: Vec<_> = store
+            .list_checkpoints()
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|c| c.execution_id)
+            .collect();
+        assert_eq!(ids, vec!["exec-1", "exec-2"]);
+
+        store.delete_checkpoint("exec-1").await.unwrap();
+        assert!(store.get_checkpoint("exec-1").await.unwrap().is_none());
+        assert_eq!(store.list_checkpoints().await.unwrap().len(), 1);
+    }
 }
@@ -10,6 +10,7 @@ use std::sync::{Arc, RwLock as StdRwLock};
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 
+use crate::snapshot::begin_write;
 use crate::Result;
 
 // Single unified table for all data - using namespaced keys
@@ -113,7 +114,7 @@ impl RedbBackend {
 impl StorageBackend for RedbBackend {
     fn write(&self, table: &str, key: &str, value: &[u8]) -> Result<()> {
         let namespaced = make_key(table, key);
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         {
             let mut t = txn.open_table(UNIFIED_TABLE)?;
             t.insert(&*namespaced, value)?;
@@ -135,7 +136,7 @@ impl StorageBackend for RedbBackend {
 
     fn delete(&self, table: &str, key: &str) -> Result<bool> {
         let namespaced = make_key(table, key);
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         let removed = {
             let mut t = txn.open_table(UNIFIED_TABLE)?;
             let result = t.remove(&*namespaced)?.is_some();
@@ -168,7 +169,7 @@ impl StorageBackend for RedbBackend {
     }
 
     fn write_batch(&self, table: &str, items: Vec<(String, Vec<u8>)>) -> Result<()> {
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         {
             let mut t = txn.open_table(UNIFIED_TABLE)?;
             for (key, value) in items {
@@ -2,6 +2,7 @@
 //!
 //! Provides persistent storage using the redb embedded database.
 
+use crate::snapshot::begin_write;
 use lru::LruCache;
 use neomind_core::storage::{Result as CoreResult, StorageBackend, StorageError};
 use redb::{Database, ReadableTable, TableDefinition};
@@ -174,10 +175,7 @@ impl StorageBackend for RedbBackend {
             cache.put(namespaced.clone(), value.to_vec());
         }
 
-        let txn = self
-            .db
-            .begin_write()
-            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let txn = begin_write(&self.db).map_err(|e| StorageError::Backend(e.to_string()))?;
         {
             let mut t = txn
                 .open_table(UNIFIED_TABLE)
@@ -233,10 +231,7 @@ impl StorageBackend for RedbBackend {
             cache.pop(&namespaced);
         }
 
-        let txn = self
-            .db
-            .begin_write()
-            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let txn = begin_write(&self.db).map_err(|e| StorageError::Backend(e.to_string()))?;
         let removed = {
             let mut t = txn
                 .open_table(UNIFIED_TABLE)
@@ -280,10 +275,7 @@ impl StorageBackend for RedbBackend {
     }
 
     fn write_batch(&self, table: &str, items: Vec<(String, Vec<u8>)>) -> Result<()> {
-        let txn = self
-            .db
-            .begin_write()
-            .map_err(|e| StorageError::Backend(e.to_string()))?;
+        let txn = begin_write(&self.db).map_err(|e| StorageError::Backend(e.to_string()))?;
         {
             let mut t = txn
                 .open_table(UNIFIED_TABLE)
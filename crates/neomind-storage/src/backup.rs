@@ -7,6 +7,7 @@
 //! - Import from JSON format
 //! - Incremental backups
 //! - Backup management and cleanup
+//! - Whole-system snapshots (see [`crate::snapshot`])
 
 use std::fs::{self, File};
 use std::io::{BufReader, BufWriter, Read, Write};
@@ -30,7 +31,7 @@ pub struct BackupMetadata {
     pub backup_type: BackupType,
     /// Size in bytes.
     pub size_bytes: u64,
-    /// Number of tables included.
+    /// Number of tables included (files, for snapshots).
     pub table_count: usize,
     /// Optional description.
     pub description: Option<String>,
@@ -47,10 +48,13 @@ pub enum BackupType {
     Incremental,
     /// Export to JSON format.
     JsonExport,
+    /// Archive of every store in the data directory.
+    Snapshot,
 }
 
 /// Backup configuration.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BackupConfig {
     /// Directory where backups are stored.
     pub backup_dir: PathBuf,
@@ -60,6 +64,8 @@ pub struct BackupConfig {
     pub compress: bool,
     /// Tables to exclude from backups.
     pub exclude_tables: Vec<String>,
+    /// Take a snapshot this often (None disables scheduled snapshots).
+    pub schedule_interval_secs: Option<u64>,
 }
 
 impl Default for BackupConfig {
@@ -69,6 +75,7 @@ impl Default for BackupConfig {
             max_backups: 10,
             compress: false,
             exclude_tables: vec![],
+            schedule_interval_secs: None,
         }
     }
 }
@@ -109,6 +116,11 @@ impl BackupManager {
         })
     }
 
+    /// Get the configuration.
+    pub fn config(&self) -> &BackupConfig {
+        &self.config
+    }
+
     /// Set the storage backend.
     pub fn with_storage(mut self, storage: Arc<UnifiedStorage>) -> Self {
         self.storage = Some(storage);
@@ -239,6 +251,10 @@ impl BackupManager {
         if metadata.backup_type == BackupType::Full {
             // Copy the backup file to the target
             self.copy_file(&metadata.path, target_path)?;
+        } else if metadata.backup_type == BackupType::Snapshot {
+            return Err(Error::InvalidInput(
+                "Snapshots must be restored via stage_restore".to_string(),
+            ));
         } else {
             return Err(Error::InvalidInput(
                 "Incremental backups must be restored via import_from_json".to_string(),
@@ -321,7 +337,7 @@ impl BackupManager {
     }
 
     /// Generate a unique backup ID.
-    fn generate_backup_id(&self) -> String {
+    pub(crate) fn generate_backup_id(&self) -> String {
         format!(
             "{:x}",
             SystemTime::now()
@@ -332,7 +348,7 @@ impl BackupManager {
     }
 
     /// Save backup metadata.
-    fn save_metadata(&self, metadata: &BackupMetadata) -> Result<()> {
+    pub(crate) fn save_metadata(&self, metadata: &BackupMetadata) -> Result<()> {
         let meta_path = self.config.backup_dir.join(format!("{}.meta", metadata.id));
         let file = File::create(&meta_path)?;
         let writer = BufWriter::new(file);
@@ -494,6 +510,7 @@ mod tests {
         assert_eq!(config.max_backups, 10);
         assert!(!config.compress);
         assert!(config.exclude_tables.is_empty());
+        assert!(config.schedule_interval_secs.is_none());
     }
 
     #[test]
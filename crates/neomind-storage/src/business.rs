@@ -13,6 +13,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use crate::snapshot::begin_write;
 use crate::Result;
 
 // Table definitions
@@ -116,7 +117,7 @@ impl RuleHistoryStore {
         let key = format!("{}:{}", execution.rule_id, execution.id);
         let value = serde_json::to_vec(execution)?;
 
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         {
             let mut table = txn.open_table(RULE_HISTORY_TABLE)?;
             table.insert(&*key, &*value)?;
@@ -351,7 +352,7 @@ impl AlertStore {
         let key = format!("{}:{}", alert.created_at, alert.id);
         let value = serde_json::to_vec(alert)?;
 
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         {
             let mut table = txn.open_table(ALERT_TABLE)?;
             table.insert(&*key, &*value)?;
@@ -430,7 +431,7 @@ impl AlertStore {
                 let key = format!("{}:{}", alert.created_at, alert.id);
                 let value = serde_json::to_vec(&alert)?;
 
-                let txn = self.db.begin_write()?;
+                let txn = begin_write(&self.db)?;
                 {
                     let mut table = txn.open_table(ALERT_TABLE)?;
                     table.insert(&*key, &*value)?;
@@ -453,7 +454,7 @@ impl AlertStore {
                 let key = format!("{}:{}", alert.created_at, alert.id);
                 let value = serde_json::to_vec(&alert)?;
 
-                let txn = self.db.begin_write()?;
+                let txn = begin_write(&self.db)?;
                 {
                     let mut table = txn.open_table(ALERT_TABLE)?;
                     table.insert(&*key, &*value)?;
@@ -468,7 +469,7 @@ impl AlertStore {
 
     /// Delete an alert.
     pub fn delete(&self, alert_id: &str) -> Result<bool> {
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         let table = txn.open_table(ALERT_TABLE)?;
 
         let start_key = format!("{}:", i64::MIN);
@@ -11,6 +11,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Dashboard table: key = dashboard_id, value = JSON dashboard (serialized)
@@ -286,7 +287,7 @@ impl DashboardStore {
 
     /// Save a dashboard.
     pub fn save(&self, dashboard: &Dashboard) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
 
         // Serialize dashboard
         let serialized = serde_json::to_vec(dashboard)?;
@@ -363,7 +364,7 @@ impl DashboardStore {
 
     /// Delete a dashboard.
     pub fn delete(&self, id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
 
         {
             let mut table = write_txn.open_table(DASHBOARDS_TABLE)?;
@@ -383,7 +384,7 @@ impl DashboardStore {
     /// Performance optimization: Uses a single transaction with batch updates
     /// to avoid N+1 query problem. Only deserializes/serializes data once per dashboard.
     pub fn set_default(&self, id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
 
         {
             let mut table = write_txn.open_table(DASHBOARDS_TABLE)?;
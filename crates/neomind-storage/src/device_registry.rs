@@ -9,6 +9,8 @@ use std::sync::Mutex as StdMutex;
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 
+use crate::device_shadow::SHADOWS_TABLE;
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Templates table: key = device_type, value = DeviceTypeTemplate (JSON)
@@ -216,6 +218,79 @@ pub struct DeviceConfig {
     pub connection_config: ConnectionConfig,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub adapter_id: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<DeviceLocation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+}
+
+/// Hierarchical device location: site → building → floor → room.
+///
+/// Levels are optional, but a level is only meaningful when its parents are set.
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct DeviceLocation {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub building: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub floor: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room: Option<String>,
+}
+
+impl DeviceLocation {
+    /// Parse a `site/building/floor/room` path; trailing levels may be omitted.
+    pub fn from_path(path: &str) -> Self {
+        let mut parts = path
+            .split('/')
+            .map(str::trim)
+            .map(|p| (!p.is_empty()).then(|| p.to_string()));
+        Self {
+            site: parts.next().flatten(),
+            building: parts.next().flatten(),
+            floor: parts.next().flatten(),
+            room: parts.next().flatten(),
+        }
+    }
+
+    /// Location as a `site/building/floor/room` path (up to the deepest set level).
+    pub fn path(&self) -> String {
+        self.levels()
+            .iter()
+            .map(|l| l.unwrap_or(""))
+            .collect::<Vec<_>>()
+            .join("/")
+            .trim_end_matches('/')
+            .to_string()
+    }
+
+    /// Whether `other` lies within this location.
+    ///
+    /// Every level set here must match; unset levels match anything, so
+    /// `site=s17` contains every building, floor and room of site `s17`.
+    pub fn contains(&self, other: &DeviceLocation) -> bool {
+        self.levels()
+            .iter()
+            .zip(other.levels())
+            .all(|(mine, theirs)| mine.is_none() || *mine == theirs)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.levels().iter().all(Option::is_none)
+    }
+
+    fn levels(&self) -> [Option<&str>; 4] {
+        [
+            self.site.as_deref(),
+            self.building.as_deref(),
+            self.floor.as_deref(),
+            self.room.as_deref(),
+        ]
+    }
 }
 
 /// Connection configuration.
@@ -267,7 +342,7 @@ pub enum CommandStatus {
 
 /// Device registry store using redb.
 pub struct DeviceRegistryStore {
-    db: Arc<Database>,
+    pub(crate) db: Arc<Database>,
     path: String,
 }
 
@@ -306,13 +381,14 @@ impl DeviceRegistryStore {
         // Create tables if this is a new database OR verify/create for existing databases
         // This handles cases where a database file exists but tables weren't created properly
         let _tables_created = if is_new {
-            let write_txn = db.begin_write()?;
+            let write_txn = begin_write(&db)?;
             {
                 // Create all tables
                 let _templates = write_txn.open_table(TEMPLATES_TABLE)?;
                 let _devices = write_txn.open_table(DEVICES_TABLE)?;
                 let _type_index = write_txn.open_table(TYPE_INDEX_TABLE)?;
                 let _commands = write_txn.open_table(COMMAND_HISTORY_TABLE)?;
+                let _shadows = write_txn.open_table(SHADOWS_TABLE)?;
             }
             write_txn.commit()?;
             true
@@ -326,12 +402,13 @@ impl DeviceRegistryStore {
                     drop(db);
                     std::fs::remove_file(path_ref)?;
                     let new_db = Database::create(path_ref)?;
-                    let write_txn = new_db.begin_write()?;
+                    let write_txn = begin_write(&new_db)?;
                     {
                         let _templates = write_txn.open_table(TEMPLATES_TABLE)?;
                         let _devices = write_txn.open_table(DEVICES_TABLE)?;
                         let _type_index = write_txn.open_table(TYPE_INDEX_TABLE)?;
                         let _commands = write_txn.open_table(COMMAND_HISTORY_TABLE)?;
+                        let _shadows = write_txn.open_table(SHADOWS_TABLE)?;
                     }
                     write_txn.commit()?;
                     return Ok(Arc::new(DeviceRegistryStore {
@@ -355,7 +432,7 @@ impl DeviceRegistryStore {
 
     /// Save a device type template.
     pub fn save_template(&self, template: &DeviceTypeTemplate) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(TEMPLATES_TABLE)?;
             let json = serde_json::to_string(template)?;
@@ -399,7 +476,7 @@ impl DeviceRegistryStore {
 
     /// Delete a device type template.
     pub fn delete_template(&self, device_type: &str) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let deleted = {
             let mut table = write_txn.open_table(TEMPLATES_TABLE)?;
             let result = table.remove(device_type)?.is_some();
@@ -423,7 +500,7 @@ impl DeviceRegistryStore {
         let device_id = config.device_id.clone();
         let device_type = config.device_type.clone();
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             // Save device config
             let mut devices_table = write_txn.open_table(DEVICES_TABLE)?;
@@ -530,7 +607,7 @@ impl DeviceRegistryStore {
 
     /// Delete a device configuration.
     pub fn delete_device(&self, device_id: &str) -> Result<Option<String>, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
 
         // First get the device to find its type
         let device_type = {
@@ -595,7 +672,7 @@ impl DeviceRegistryStore {
 
         let new_device_type = config.device_type.clone();
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             // Update device config
             let mut devices_table = write_txn.open_table(DEVICES_TABLE)?;
@@ -674,7 +751,7 @@ impl DeviceRegistryStore {
 
     /// Save a command history record.
     pub fn save_command(&self, record: &CommandHistoryRecord) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(COMMAND_HISTORY_TABLE)?;
             let json = serde_json::to_string(record)?;
@@ -761,7 +838,7 @@ impl DeviceRegistryStore {
 
     /// Delete a command history record.
     pub fn delete_command(&self, device_id: &str, command_id: &str) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let deleted = {
             let mut table = write_txn.open_table(COMMAND_HISTORY_TABLE)?;
             let result = table.remove((device_id, command_id))?.is_some();
@@ -773,7 +850,7 @@ impl DeviceRegistryStore {
 
     /// Clear all command history for a device.
     pub fn clear_device_commands(&self, device_id: &str) -> Result<usize, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let count = {
             let mut table = write_txn.open_table(COMMAND_HISTORY_TABLE)?;
             let start_key = (device_id, "");
@@ -857,7 +934,7 @@ impl DeviceRegistryStore {
         devices: &::std::collections::HashMap<String, DeviceConfig>,
         type_index: &::std::collections::HashMap<String, Vec<String>>,
     ) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             // Save templates
             let mut templates_table = write_txn.open_table(TEMPLATES_TABLE)?;
@@ -951,6 +1028,7 @@ mod tests {
                 ..Default::default()
             },
             adapter_id: Some("main-mqtt".to_string()),
+            ..Default::default()
         };
 
         store.save_device(&config).unwrap();
@@ -968,6 +1046,31 @@ mod tests {
         assert!(!store.device_exists("sensor1").unwrap());
     }
 
+    #[test]
+    fn test_device_location_tags_groups_persisted() {
+        let store = create_temp_store();
+
+        let config = DeviceConfig {
+            device_id: "freezer1".to_string(),
+            name: "Freezer 1".to_string(),
+            device_type: "freezer".to_string(),
+            adapter_type: "mqtt".to_string(),
+            location: Some(DeviceLocation::from_path("store-17/main/2/cold-room")),
+            tags: vec!["cold".to_string()],
+            groups: vec!["freezers".to_string()],
+            ..Default::default()
+        };
+        store.save_device(&config).unwrap();
+
+        let loaded = store.load_device("freezer1").unwrap().unwrap();
+        let location = loaded.location.unwrap();
+        assert_eq!(location.path(), "store-17/main/2/cold-room");
+        assert!(DeviceLocation::from_path("store-17").contains(&location));
+        assert!(!DeviceLocation::from_path("store-17/annex").contains(&location));
+        assert_eq!(loaded.tags, vec!["cold"]);
+        assert_eq!(loaded.groups, vec!["freezers"]);
+    }
+
     #[test]
     fn test_list_devices_by_type() {
         let store = create_temp_store();
@@ -980,6 +1083,7 @@ mod tests {
                 adapter_type: "mqtt".to_string(),
                 connection_config: Default::default(),
                 adapter_id: None,
+                ..Default::default()
             })
             .unwrap();
 
@@ -991,6 +1095,7 @@ mod tests {
                 adapter_type: "mqtt".to_string(),
                 connection_config: Default::default(),
                 adapter_id: None,
+                ..Default::default()
             })
             .unwrap();
 
@@ -1002,6 +1107,7 @@ mod tests {
                 adapter_type: "mqtt".to_string(),
                 connection_config: Default::default(),
                 adapter_id: None,
+                ..Default::default()
             })
             .unwrap();
 
@@ -0,0 +1,318 @@
+//! Device shadow documents.
+//!
+//! A shadow tracks what state a device *should* be in (`desired`, written by
+//! commands and the API) next to what it last told us (`reported`, written by
+//! telemetry). The `delta` is every desired key the device has not yet
+//! reported back with the same value; a reconciler re-sends commands for it
+//! when the device comes back online.
+//!
+//! Commands that do not set any reported metric (a parameterless `turn_on`,
+//! say) are tracked as `desired_commands` instead: each stays pending until it
+//! has been delivered to a connected device.
+//!
+//! Shadows are persisted in the device registry database (`device_shadows` table).
+
+use std::collections::BTreeMap;
+
+use redb::{ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+
+use crate::device_registry::DeviceRegistryStore;
+use crate::snapshot::begin_write;
+use crate::Error;
+
+// Shadows table: key = device_id, value = DeviceShadow (JSON)
+pub(crate) const SHADOWS_TABLE: TableDefinition<&str, &str> =
+    TableDefinition::new("device_shadows");
+
+/// Per-device shadow document.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct DeviceShadow {
+    pub device_id: String,
+    /// State the device should be in
+    #[serde(default)]
+    pub desired: BTreeMap<String, serde_json::Value>,
+    /// State the device last reported
+    #[serde(default)]
+    pub reported: BTreeMap<String, serde_json::Value>,
+    /// Command that set each desired key, used to re-send it
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub desired_by: BTreeMap<String, String>,
+    /// Latest request of each command that has no reported state of its own
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub desired_commands: BTreeMap<String, DesiredCommand>,
+    /// Incremented on every change
+    #[serde(default)]
+    pub version: u64,
+    /// Last change to `desired` (unix seconds)
+    #[serde(default)]
+    pub desired_updated_at: i64,
+    /// Last change to `reported` (unix seconds)
+    #[serde(default)]
+    pub reported_updated_at: i64,
+}
+
+/// A command the device should have received.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DesiredCommand {
+    #[serde(default)]
+    pub params: BTreeMap<String, serde_json::Value>,
+    /// Last time the command was requested (unix seconds)
+    pub issued_at: i64,
+    /// When that request reached a connected device; `None` while pending
+    #[serde(default)]
+    pub delivered_at: Option<i64>,
+}
+
+impl DeviceShadow {
+    pub fn new(device_id: impl Into<String>) -> Self {
+        Self {
+            device_id: device_id.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Desired keys whose reported value is missing or different.
+    pub fn delta(&self) -> BTreeMap<String, serde_json::Value> {
+        self.desired
+            .iter()
+            .filter(|(key, desired)| {
+                !self
+                    .reported
+                    .get(*key)
+                    .is_some_and(|reported| values_match(desired, reported))
+            })
+            .map(|(key, value)| (key.clone(), value.clone()))
+            .collect()
+    }
+
+    /// Commands not yet delivered, oldest request first.
+    pub fn pending_commands(&self) -> Vec<(&str, &DesiredCommand)> {
+        let mut pending: Vec<_> = self
+            .desired_commands
+            .iter()
+            .filter(|(_, command)| command.delivered_at.is_none())
+            .map(|(name, command)| (name.as_str(), command))
+            .collect();
+        pending.sort_by_key(|(_, command)| command.issued_at);
+        pending
+    }
+
+    /// Whether the device has reported every desired value and received every
+    /// desired command.
+    pub fn in_sync(&self) -> bool {
+        self.delta().is_empty() && self.pending_commands().is_empty()
+    }
+
+    /// Set a desired value, remembering the command that carries it.
+    ///
+    /// A `null` value removes the key from `desired`.
+    pub fn set_desired(&mut self, key: &str, value: serde_json::Value, command: Option<&str>) {
+        if value.is_null() {
+            self.desired.remove(key);
+            self.desired_by.remove(key);
+        } else {
+            self.desired.insert(key.to_string(), value);
+            match command {
+                Some(command) => {
+                    self.desired_by.insert(key.to_string(), command.to_string());
+                }
+                None => {
+                    self.desired_by.remove(key);
+                }
+            }
+        }
+        self.version += 1;
+        self.desired_updated_at = chrono::Utc::now().timestamp();
+    }
+
+    /// Record a request for `command`, pending until [`Self::mark_delivered`].
+    pub fn set_desired_command(
+        &mut self,
+        command: &str,
+        params: BTreeMap<String, serde_json::Value>,
+    ) {
+        let now = chrono::Utc::now().timestamp();
+        self.desired_commands.insert(
+            command.to_string(),
+            DesiredCommand {
+                params,
+                issued_at: now,
+                delivered_at: None,
+            },
+        );
+        self.version += 1;
+        self.desired_updated_at = now;
+    }
+
+    /// Mark the latest request of `command` as delivered. Returns false if it
+    /// was not pending.
+    pub fn mark_delivered(&mut self, command: &str) -> bool {
+        match self.desired_commands.get_mut(command) {
+            Some(desired) if desired.delivered_at.is_none() => {
+                desired.delivered_at = Some(chrono::Utc::now().timestamp());
+                self.version += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Record a reported value. Returns false if it was already known.
+    pub fn set_reported(&mut self, key: &str, value: serde_json::Value) -> bool {
+        if self.reported.get(key) == Some(&value) {
+            return false;
+        }
+        self.reported.insert(key.to_string(), value);
+        self.version += 1;
+        self.reported_updated_at = chrono::Utc::now().timestamp();
+        true
+    }
+}
+
+/// Compare a desired and a reported value, tolerating numeric representation
+/// differences (`1` vs `1.0`) and devices that report booleans as 0/1.
+fn values_match(desired: &serde_json::Value, reported: &serde_json::Value) -> bool {
+    use serde_json::Value;
+    match (desired, reported) {
+        (Value::Number(a), Value::Number(b)) => match (a.as_f64(), b.as_f64()) {
+            (Some(a), Some(b)) => (a - b).abs() < 1e-6,
+            _ => a == b,
+        },
+        (Value::Bool(a), Value::Number(n)) | (Value::Number(n), Value::Bool(a)) => {
+            n.as_f64() == Some(if *a { 1.0 } else { 0.0 })
+        }
+        _ => desired == reported,
+    }
+}
+
+impl DeviceRegistryStore {
+    /// Save a device shadow.
+    pub fn save_shadow(&self, shadow: &DeviceShadow) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(SHADOWS_TABLE)?;
+            let json = serde_json::to_string(shadow)?;
+            table.insert(shadow.device_id.as_str(), json.as_str())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load a device shadow.
+    pub fn load_shadow(&self, device_id: &str) -> Result<Option<DeviceShadow>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = match read_txn.open_table(SHADOWS_TABLE) {
+            Ok(t) => t,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(e.into()),
+        };
+
+        match table.get(device_id)? {
+            Some(value) => Ok(Some(serde_json::from_str(value.value())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// List all device shadows.
+    pub fn list_shadows(&self) -> Result<Vec<DeviceShadow>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = match read_txn.open_table(SHADOWS_TABLE) {
+            Ok(t) => t,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut shadows = Vec::new();
+        for result in table.iter()? {
+            let (_key, value) = result?;
+            if let Ok(shadow) = serde_json::from_str::<DeviceShadow>(value.value()) {
+                shadows.push(shadow);
+            }
+        }
+        Ok(shadows)
+    }
+
+    /// Delete a device shadow.
+    pub fn delete_shadow(&self, device_id: &str) -> Result<bool, Error> {
+        let write_txn = begin_write(&self.db)?;
+        let deleted = {
+            let mut table = write_txn.open_table(SHADOWS_TABLE)?;
+            let removed = table.remove(device_id)?.is_some();
+            removed
+        };
+        write_txn.commit()?;
+        Ok(deleted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_delta_tracks_unreported_desired_values() {
+        let mut shadow = DeviceShadow::new("lamp1");
+        shadow.set_desired("on", json!(true), Some("set_power"));
+        shadow.set_desired("brightness", json!(80), Some("set_brightness"));
+        assert_eq!(shadow.delta().len(), 2);
+
+        shadow.set_reported("on", json!(1));
+        shadow.set_reported("brightness", json!(50.0));
+        assert_eq!(
+            shadow.delta(),
+            BTreeMap::from([("brightness".to_string(), json!(80))])
+        );
+
+        shadow.set_reported("brightness", json!(80.0));
+        assert!(shadow.in_sync());
+
+        // Clearing a desired key drops it and its command
+        shadow.set_desired("on", serde_json::Value::Null, None);
+        assert!(!shadow.desired.contains_key("on"));
+        assert!(!shadow.desired_by.contains_key("on"));
+    }
+
+    #[test]
+    fn test_desired_commands_pending_until_delivered() {
+        let mut shadow = DeviceShadow::new("lamp1");
+        shadow.set_desired_command("turn_on", BTreeMap::new());
+        shadow.set_desired_command("blink", BTreeMap::from([("times".to_string(), json!(3))]));
+        shadow
+            .desired_commands
+            .get_mut("turn_on")
+            .unwrap()
+            .issued_at -= 10;
+        assert!(!shadow.in_sync());
+
+        let pending: Vec<_> = shadow.pending_commands().iter().map(|(n, _)| *n).collect();
+        assert_eq!(pending, vec!["turn_on", "blink"]);
+
+        assert!(shadow.mark_delivered("turn_on"));
+        assert!(!shadow.mark_delivered("turn_on"));
+        assert!(shadow.mark_delivered("blink"));
+        assert!(shadow.in_sync());
+
+        // A new request of the same command is pending again
+        shadow.set_desired_command("turn_on", BTreeMap::new());
+        assert_eq!(shadow.pending_commands().len(), 1);
+    }
+
+    #[test]
+    fn test_shadow_persistence() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let store = DeviceRegistryStore::open(temp_dir.path().join("shadows.redb")).unwrap();
+
+        assert!(store.load_shadow("lamp1").unwrap().is_none());
+
+        let mut shadow = DeviceShadow::new("lamp1");
+        shadow.set_desired("on", json!(true), Some("set_power"));
+        store.save_shadow(&shadow).unwrap();
+
+        assert_eq!(store.load_shadow("lamp1").unwrap(), Some(shadow));
+        assert_eq!(store.list_shadows().unwrap().len(), 1);
+        assert!(store.delete_shadow("lamp1").unwrap());
+        assert!(store.load_shadow("lamp1").unwrap().is_none());
+    }
+}
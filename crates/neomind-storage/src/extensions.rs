@@ -11,6 +11,7 @@ use chrono::Utc;
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Extensions table: key = extension_id, value = ExtensionRecord (serialized)
@@ -238,7 +239,7 @@ impl ExtensionStore {
 
     /// Ensure all required tables exist
     fn ensure_tables(&self) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let _ = write_txn.open_table(EXTENSIONS_TABLE)?;
         }
@@ -255,7 +256,7 @@ impl ExtensionStore {
         let mut record = record.clone();
         record.touch();
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(EXTENSIONS_TABLE)?;
             let value =
@@ -328,7 +329,7 @@ impl ExtensionStore {
         }
         drop(read_txn);
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(EXTENSIONS_TABLE)?;
             let mut record: ExtensionRecord = serde_json::from_slice(
@@ -356,7 +357,7 @@ impl ExtensionStore {
         }
         drop(read_txn);
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(EXTENSIONS_TABLE)?;
             let mut record: ExtensionRecord = serde_json::from_slice(
@@ -400,7 +401,7 @@ impl ExtensionStore {
 
     /// Delete an extension record
     pub fn delete(&self, id: &str) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(EXTENSIONS_TABLE)?;
             let result = table.remove(id)?.is_some();
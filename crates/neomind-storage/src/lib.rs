@@ -65,29 +65,39 @@ pub mod backup;
 pub mod business;
 pub mod dashboards;
 pub mod device_registry;
+pub mod device_shadow;
 pub mod device_state;
 pub mod error;
 pub mod extensions;
 pub mod knowledge;
 pub mod llm_backends;
 pub mod llm_data;
+pub mod llm_usage;
 pub mod maintenance;
 pub mod messages;
 pub mod monitoring;
 pub mod multimodal;
+pub mod paths;
 pub mod session;
 pub mod settings;
 pub mod singleton;
+pub mod snapshot;
 pub mod timeseries;
+pub mod timeseries_chunk;
+pub mod timeseries_ingest;
 pub mod vector;
 
 // Re-exports
 pub use error::{Error, Result};
 
+pub use paths::{data_dir, data_path, DATA_DIR_ENV};
+
 pub use timeseries::{
-    BatchWriteRequest, DataPoint, PerformanceStats, RetentionPolicy, RetentionPolicyCleanupResult,
-    TimeSeriesBucket, TimeSeriesConfig, TimeSeriesResult, TimeSeriesStore,
+    BatchWriteRequest, ChunkMigrationResult, DataPoint, PerformanceStats, RetentionPolicy,
+    RetentionPolicyCleanupResult, TimeSeriesBucket, TimeSeriesConfig, TimeSeriesLayout,
+    TimeSeriesResult, TimeSeriesStore,
 };
+pub use timeseries_ingest::GroupCommitConfig;
 
 pub use vector::{
     Embedding, PersistentVectorStore, SearchResult, SimilarityMetric, VectorDocument, VectorStore,
@@ -103,10 +113,15 @@ pub use multimodal::{DocumentMetadata, ImageMetadata, MultimodalStore};
 pub use messages::{MessageStats, MessageStore, StoredMessage};
 
 pub use settings::{
+    BridgeDirection,
+    BridgeRule,
+    BrokerBridge,
     ConfigChangeEntry,
     ExternalBroker,
     LlmBackendType,
     LlmSettings,
+    McpServerConfig,
+    McpTransportConfig,
     MqttSettings,
     SecurityLevel,
     SecurityWarning,
@@ -120,6 +135,12 @@ pub use settings::{
 
 pub use llm_backends::{
     BackendCapabilities, ConnectionTestResult, LlmBackendInstance, LlmBackendStats, LlmBackendStore,
+    ResponseCacheConfig, RoutingPolicy, RoutingStrategy,
+};
+
+pub use llm_usage::{
+    BudgetAction, BudgetPeriod, BudgetScope, LlmUsageStore, ModelPricing, UsageBucket, UsageBudget,
+    UsageDimension, UsageGrouping, UsageQuery, UsageRecord, UsageReport, UsageTotals,
 };
 
 pub use extensions::{ExtensionRecord, ExtensionStats, ExtensionStore};
@@ -129,31 +150,41 @@ pub use agents::{
     AgentExecutionRecord,
     AgentFilter,
     AgentMemory,
+    AgentMessage,
     AgentResource,
     AgentSchedule,
     AgentStats,
     AgentStatus,
     AgentStore,
     AiAgent,
+    ApprovalFilter,
+    ApprovalPolicy,
+    ApprovalRequest,
+    ApprovalRule,
+    ApprovalStatus,
     // Conversation types
     ConversationTurn,
     DataCollected,
     DataSummary,
     Decision,
     DecisionProcess,
+    ExecutionCheckpoint,
     ExecutionFilter,
     ExecutionResult,
     ExecutionStatus,
     GeneratedReport,
     ImportantMemory,
     IntentType,
+    InterruptedExecutionPolicy,
     LearnedPattern,
     LongTermMemory,
     MemorySummary,
     NotificationSent,
     ParsedIntent,
+    PendingAction,
     ReasoningStep,
     ResourceType,
+    RoundCheckpoint,
     ScheduleType,
     ShortTermMemory,
     TrendPoint,
@@ -177,6 +208,10 @@ pub use business::{
 pub use llm_data::{LongTermMemoryStore, MemoryEntry, MemoryFilter, MemoryStats};
 
 pub use backup::{BackupConfig, BackupHandler, BackupManager, BackupMetadata, BackupType};
+pub use snapshot::{
+    apply_pending_restore, stage_snapshot_restore, verify_snapshot_archive, RestoreOutcome,
+    SnapshotEntry, SnapshotManifest, SnapshotVerification,
+};
 
 pub use maintenance::{CleanupUtils, MaintenanceConfig, MaintenanceResult, MaintenanceScheduler};
 
@@ -191,6 +226,8 @@ pub use device_registry::{
     ParameterDefinition,
 };
 
+pub use device_shadow::{DesiredCommand, DeviceShadow};
+
 pub use dashboards::{
     default_templates, ComponentPosition, Dashboard, DashboardLayout, DashboardStore,
     DashboardTemplate, LayoutBreakpoints, RequiredResources, RowsValue,
@@ -3,6 +3,7 @@
 //! This module provides storage for multiple LLM backend instances,
 //! supporting dynamic backend switching and multi-configuration management.
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
@@ -11,6 +12,7 @@ use chrono::Utc;
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 
+use crate::snapshot::begin_write;
 use crate::{settings::LlmBackendType, Error};
 
 // LLM backend instances table: key = instance_id, value = LlmBackendInstance (serialized)
@@ -20,6 +22,13 @@ const LLM_BACKENDS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("l
 const ACTIVE_BACKEND_TABLE: TableDefinition<&str, &str> =
     TableDefinition::new("active_llm_backend");
 
+// Key of the routing policy used by chat sessions in the active backend table
+const CHAT_POLICY_KEY: &str = "chat_policy";
+
+// Routing policies table: key = policy_id, value = RoutingPolicy (serialized)
+const ROUTING_POLICIES_TABLE: TableDefinition<&str, &[u8]> =
+    TableDefinition::new("llm_routing_policies");
+
 /// Singleton for LLM backend storage
 static LLM_BACKEND_STORE_SINGLETON: StdMutex<Option<Arc<LlmBackendStore>>> = StdMutex::new(None);
 
@@ -433,6 +442,173 @@ impl LlmBackendInstance {
     }
 }
 
+/// How a routing policy orders its backends for a request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RoutingStrategy {
+    /// Try backends in the configured order
+    #[default]
+    Failover,
+    /// Prefer the backend with the lowest observed average latency
+    LowestLatency,
+    /// Prefer the cheapest backend (see `RoutingPolicy::costs`)
+    LowestCost,
+    /// Prefer local backends, but cloud backends for requests with images
+    LocalFirst,
+}
+
+/// Named policy for routing LLM requests across backend instances
+///
+/// Backends that lack a capability the request needs (images, tools, context
+/// length) are skipped, and each backend has a circuit breaker that takes it
+/// out of rotation after `failure_threshold` consecutive failures.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoutingPolicy {
+    /// Unique policy ID (e.g., "local-first")
+    pub id: String,
+
+    /// Display name
+    pub name: String,
+
+    /// Description
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Ordering strategy
+    #[serde(default)]
+    pub strategy: RoutingStrategy,
+
+    /// Backend instance IDs, in failover order
+    pub backends: Vec<String>,
+
+    /// Relative cost per backend instance ID for `LowestCost`.
+    /// Backends without an entry cost 0 when local and 1 otherwise.
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub costs: HashMap<String, f64>,
+
+    /// Consecutive failures before a backend's circuit opens
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+
+    /// Seconds an open circuit waits before letting a trial request through
+    #[serde(default = "default_open_secs")]
+    pub open_secs: u64,
+
+    /// Last updated timestamp
+    pub updated_at: i64,
+}
+
+fn default_failure_threshold() -> u32 {
+    3
+}
+
+fn default_open_secs() -> u64 {
+    30
+}
+
+impl RoutingPolicy {
+    /// Create a new routing policy
+    pub fn new(
+        id: impl Into<String>,
+        name: impl Into<String>,
+        strategy: RoutingStrategy,
+        backends: Vec<String>,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            description: None,
+            strategy,
+            backends,
+            costs: HashMap::new(),
+            failure_threshold: default_failure_threshold(),
+            open_secs: default_open_secs(),
+            updated_at: Utc::now().timestamp(),
+        }
+    }
+
+    /// Update the timestamp
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now().timestamp();
+    }
+
+    /// Validate the policy configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("Policy ID cannot be empty".to_string());
+        }
+
+        if self.name.is_empty() {
+            return Err("Name cannot be empty".to_string());
+        }
+
+        if self.backends.is_empty() {
+            return Err("Policy must reference at least one backend".to_string());
+        }
+
+        if self.failure_threshold == 0 {
+            return Err("Failure threshold must be at least 1".to_string());
+        }
+
+        if self.costs.values().any(|cost| *cost < 0.0) {
+            return Err("Backend costs cannot be negative".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Opt-in LLM response caching for an agent or API call
+///
+/// Responses are reused for identical normalised requests and, when
+/// `similarity_threshold` is set and an embedding model is configured, for
+/// prompts whose embedding is at least that similar to a cached one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ResponseCacheConfig {
+    /// Seconds a cached response stays valid
+    #[serde(default = "default_cache_ttl_secs")]
+    pub ttl_secs: u64,
+
+    /// Minimum cosine similarity (0-1) for a similarity hit; exact hits only when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub similarity_threshold: Option<f32>,
+
+    /// Tools without side effects; turns offering any other tool are never cached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub read_only_tools: Vec<String>,
+}
+
+fn default_cache_ttl_secs() -> u64 {
+    300
+}
+
+impl Default for ResponseCacheConfig {
+    fn default() -> Self {
+        Self {
+            ttl_secs: default_cache_ttl_secs(),
+            similarity_threshold: None,
+            read_only_tools: Vec::new(),
+        }
+    }
+}
+
+impl ResponseCacheConfig {
+    /// Validate the cache configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.ttl_secs == 0 {
+            return Err("Cache TTL must be at least 1 second".to_string());
+        }
+
+        if let Some(threshold) = self.similarity_threshold {
+            if !(0.0..=1.0).contains(&threshold) {
+                return Err("Similarity threshold must be between 0 and 1".to_string());
+            }
+        }
+
+        Ok(())
+    }
+}
+
 /// LLM backend storage
 pub struct LlmBackendStore {
     db: Arc<Database>,
@@ -479,10 +655,11 @@ impl LlmBackendStore {
 
     /// Ensure all required tables exist
     fn ensure_tables(&self) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let _ = write_txn.open_table(LLM_BACKENDS_TABLE)?;
             let _ = write_txn.open_table(ACTIVE_BACKEND_TABLE)?;
+            let _ = write_txn.open_table(ROUTING_POLICIES_TABLE)?;
         }
         write_txn.commit()?;
         Ok(())
@@ -494,7 +671,7 @@ impl LlmBackendStore {
             .validate()
             .map_err(|e| Error::InvalidInput(e.to_string()))?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(LLM_BACKENDS_TABLE)?;
             let value =
@@ -549,7 +726,7 @@ impl LlmBackendStore {
             }
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(LLM_BACKENDS_TABLE)?;
             let result = table.remove(id)?.is_some();
@@ -578,7 +755,7 @@ impl LlmBackendStore {
             return Err(Error::NotFound(format!("Backend instance {}", id)));
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(ACTIVE_BACKEND_TABLE)?;
             table.insert("active_backend", id)?;
@@ -688,6 +865,97 @@ impl LlmBackendStore {
         Ok(())
     }
 
+    /// Save a routing policy
+    pub fn save_policy(&self, policy: &RoutingPolicy) -> Result<(), Error> {
+        policy.validate().map_err(Error::InvalidInput)?;
+
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(ROUTING_POLICIES_TABLE)?;
+            let value =
+                serde_json::to_vec(policy).map_err(|e| Error::Serialization(e.to_string()))?;
+            table.insert(policy.id.as_str(), value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load a routing policy by ID
+    pub fn load_policy(&self, id: &str) -> Result<Option<RoutingPolicy>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(ROUTING_POLICIES_TABLE)?;
+
+        match table.get(id)? {
+            Some(data) => serde_json::from_slice(data.value())
+                .map(Some)
+                .map_err(|e| Error::Serialization(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Load all routing policies
+    pub fn load_all_policies(&self) -> Result<Vec<RoutingPolicy>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(ROUTING_POLICIES_TABLE)?;
+
+        let mut policies = Vec::new();
+        for result in table.iter()? {
+            let (_, data) = result?;
+            let policy: RoutingPolicy = serde_json::from_slice(data.value())
+                .map_err(|e| Error::Serialization(e.to_string()))?;
+            policies.push(policy);
+        }
+
+        Ok(policies)
+    }
+
+    /// Delete a routing policy, and stop routing chat through it
+    pub fn delete_policy(&self, id: &str) -> Result<bool, Error> {
+        let write_txn = begin_write(&self.db)?;
+        let existed = {
+            let mut table = write_txn.open_table(ROUTING_POLICIES_TABLE)?;
+            let result = table.remove(id)?.is_some();
+            result
+        };
+        {
+            let mut table = write_txn.open_table(ACTIVE_BACKEND_TABLE)?;
+            let chat_policy = table.get(CHAT_POLICY_KEY)?.is_some_and(|v| v.value() == id);
+            if chat_policy {
+                table.remove(CHAT_POLICY_KEY)?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(existed)
+    }
+
+    /// Get the ID of the routing policy used by chat sessions
+    pub fn get_chat_policy_id(&self) -> Result<Option<String>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(ACTIVE_BACKEND_TABLE)?;
+
+        Ok(table.get(CHAT_POLICY_KEY)?.map(|id| id.value().to_string()))
+    }
+
+    /// Set the routing policy used by chat sessions, or clear it with `None`
+    pub fn set_chat_policy(&self, id: Option<&str>) -> Result<(), Error> {
+        if let Some(id) = id {
+            if self.load_policy(id)?.is_none() {
+                return Err(Error::NotFound(format!("Routing policy {}", id)));
+            }
+        }
+
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(ACTIVE_BACKEND_TABLE)?;
+            match id {
+                Some(id) => table.insert(CHAT_POLICY_KEY, id)?,
+                None => table.remove(CHAT_POLICY_KEY)?,
+            };
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
     /// Get statistics about backend instances
     pub fn get_stats(&self) -> Result<LlmBackendStats, Error> {
         let instances = self.load_all_instances()?;
@@ -781,4 +1049,43 @@ mod tests {
         assert_ne!(id1, id2);
         assert_eq!(id1.len(), "ollama_".len() + 8);
     }
+
+    #[test]
+    fn test_routing_policy_storage() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = LlmBackendStore {
+            db: Arc::new(Database::create(dir.path().join("backends.redb")).unwrap()),
+            path: String::new(),
+        };
+        store.ensure_tables().unwrap();
+
+        let mut policy = RoutingPolicy::new(
+            "local-first",
+            "Local first",
+            RoutingStrategy::LocalFirst,
+            vec!["ollama-default".to_string(), "openai-primary".to_string()],
+        );
+        store.save_policy(&policy).unwrap();
+
+        let loaded = store.load_policy("local-first").unwrap().unwrap();
+        assert_eq!(loaded.strategy, RoutingStrategy::LocalFirst);
+        assert_eq!(loaded.backends.len(), 2);
+        assert_eq!(loaded.failure_threshold, 3);
+        assert_eq!(store.load_all_policies().unwrap().len(), 1);
+
+        policy.backends.clear();
+        assert!(store.save_policy(&policy).is_err());
+
+        assert!(store.set_chat_policy(Some("missing")).is_err());
+        store.set_chat_policy(Some("local-first")).unwrap();
+        assert_eq!(
+            store.get_chat_policy_id().unwrap().as_deref(),
+            Some("local-first")
+        );
+
+        // Deleting the policy also stops routing chat through it
+        assert!(store.delete_policy("local-first").unwrap());
+        assert!(store.load_policy("local-first").unwrap().is_none());
+        assert!(store.get_chat_policy_id().unwrap().is_none());
+    }
 }
@@ -15,6 +15,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use tokio::sync::RwLock;
 
+use crate::snapshot::begin_write;
 use crate::vector::{VectorDocument, VectorStore};
 use crate::{Error, Result};
 
@@ -265,7 +266,7 @@ impl LongTermMemoryStore {
         let key = memory.id.as_str();
         let value = serde_json::to_vec(memory)?;
 
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         {
             let mut table = txn.open_table(MEMORY_TABLE)?;
             table.insert(key, &*value)?;
@@ -337,7 +338,7 @@ impl LongTermMemoryStore {
         let id = id.to_string();
         tokio::spawn(async move {
             // First, read the current memory
-            let memory_data = match db.begin_write() {
+            let memory_data = match begin_write(&db) {
                 Ok(txn) => match txn.open_table(MEMORY_TABLE) {
                     Ok(table) => match table.get(&*id) {
                         Ok(Some(value)) => value.value().to_vec(),
@@ -354,7 +355,7 @@ impl LongTermMemoryStore {
                 memory.access_count += 1;
 
                 if let Ok(updated) = serde_json::to_vec(&memory) {
-                    if let Ok(txn) = db.begin_write() {
+                    if let Ok(txn) = begin_write(&db) {
                         {
                             let mut table = match txn.open_table(MEMORY_TABLE) {
                                 Ok(t) => t,
@@ -443,7 +444,7 @@ impl LongTermMemoryStore {
 
     /// Delete a memory entry.
     pub async fn delete(&self, id: &str) -> Result<bool> {
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         let mut table = txn.open_table(MEMORY_TABLE)?;
 
         // Get memory before deleting to update indexes
@@ -485,7 +486,7 @@ impl LongTermMemoryStore {
 
     /// Clean up expired memories.
     pub async fn cleanup_expired(&self) -> Result<usize> {
-        let txn = self.db.begin_write()?;
+        let txn = begin_write(&self.db)?;
         let table = txn.open_table(MEMORY_TABLE)?;
 
         let mut ids_to_delete: Vec<String> = Vec::new();
@@ -0,0 +1,762 @@
+//! LLM Usage Storage
+//!
+//! Records every LLM call with its token counts, latency, cost and
+//! attribution, and stores the per-model pricing and the per-agent/per-user
+//! budgets used to price and limit those calls.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use redb::{Database, ReadableTable, TableDefinition};
+use serde::{Deserialize, Serialize};
+
+use crate::snapshot::begin_write;
+use crate::Error;
+
+// Usage records table: key = "{timestamp:012}-{id}", value = UsageRecord (serialized)
+const USAGE_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("llm_usage");
+
+// Model pricing table: key = model pattern, value = ModelPricing (serialized)
+const PRICING_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("llm_pricing");
+
+// Budgets table: key = budget_id, value = UsageBudget (serialized)
+const BUDGETS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("llm_budgets");
+
+/// A single recorded LLM call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRecord {
+    /// Unique record ID
+    pub id: String,
+
+    /// Unix timestamp (seconds) when the call finished
+    pub timestamp: i64,
+
+    /// Backend type (e.g. "ollama", "openai")
+    pub backend: String,
+
+    /// Model name
+    pub model: String,
+
+    pub prompt_tokens: u32,
+
+    pub completion_tokens: u32,
+
+    /// Wall-clock latency in milliseconds
+    pub latency_ms: u64,
+
+    pub success: bool,
+
+    /// Cost according to the model pricing at the time of the call
+    #[serde(default)]
+    pub cost: f64,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_id: Option<String>,
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extension_id: Option<String>,
+}
+
+impl UsageRecord {
+    /// Create a record for a call that finished now
+    pub fn new(backend: impl Into<String>, model: impl Into<String>) -> Self {
+        Self {
+            id: uuid::Uuid::new_v4().to_string(),
+            timestamp: Utc::now().timestamp(),
+            backend: backend.into(),
+            model: model.into(),
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            latency_ms: 0,
+            success: true,
+            cost: 0.0,
+            session_id: None,
+            agent_id: None,
+            user_id: None,
+            extension_id: None,
+        }
+    }
+
+    /// Total tokens of the call
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens as u64 + self.completion_tokens as u64
+    }
+
+    /// The record's value for a report dimension
+    pub fn dimension(&self, dimension: UsageDimension) -> Option<&str> {
+        match dimension {
+            UsageDimension::Backend => Some(&self.backend),
+            UsageDimension::Model => Some(&self.model),
+            UsageDimension::Agent => self.agent_id.as_deref(),
+            UsageDimension::User => self.user_id.as_deref(),
+            UsageDimension::Session => self.session_id.as_deref(),
+            UsageDimension::Extension => self.extension_id.as_deref(),
+        }
+    }
+
+    fn key(&self) -> String {
+        format!("{:012}-{}", self.timestamp.max(0), self.id)
+    }
+}
+
+/// Price of a model, in currency units per million tokens
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPricing {
+    /// Model name, or a prefix ending in `*` (e.g. "gpt-4o*")
+    pub model: String,
+
+    pub input_per_million: f64,
+
+    pub output_per_million: f64,
+
+    /// Last updated timestamp
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+impl ModelPricing {
+    /// Create a pricing entry
+    pub fn new(model: impl Into<String>, input_per_million: f64, output_per_million: f64) -> Self {
+        Self {
+            model: model.into(),
+            input_per_million,
+            output_per_million,
+            updated_at: Utc::now().timestamp(),
+        }
+    }
+
+    /// Whether this entry applies to `model`
+    pub fn matches(&self, model: &str) -> bool {
+        match self.model.strip_suffix('*') {
+            Some(prefix) => model.starts_with(prefix),
+            None => self.model == model,
+        }
+    }
+
+    /// Cost of a call with the given token counts
+    pub fn cost(&self, prompt_tokens: u32, completion_tokens: u32) -> f64 {
+        (prompt_tokens as f64 * self.input_per_million
+            + completion_tokens as f64 * self.output_per_million)
+            / 1_000_000.0
+    }
+
+    /// Find the entry for `model`: an exact match, else the longest matching prefix
+    pub fn find<'a>(pricing: &'a [ModelPricing], model: &str) -> Option<&'a ModelPricing> {
+        pricing.iter().find(|p| p.model == model).or_else(|| {
+            pricing
+                .iter()
+                .filter(|p| p.model.ends_with('*') && p.matches(model))
+                .max_by_key(|p| p.model.len())
+        })
+    }
+
+    /// Validate the pricing entry
+    pub fn validate(&self) -> Result<(), String> {
+        if self.model.is_empty() {
+            return Err("Model cannot be empty".to_string());
+        }
+
+        if self.input_per_million < 0.0 || self.output_per_million < 0.0 {
+            return Err("Prices cannot be negative".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Who a budget limits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetScope {
+    Agent,
+    User,
+}
+
+/// The period a budget resets after
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetPeriod {
+    Daily,
+    Monthly,
+}
+
+impl BudgetPeriod {
+    /// Start (UTC, seconds) of the period containing `now`
+    pub fn start(&self, now: i64) -> i64 {
+        match self {
+            BudgetPeriod::Daily => now - now.rem_euclid(86_400),
+            BudgetPeriod::Monthly => month_start(now),
+        }
+    }
+}
+
+/// What happens to calls once a budget is spent
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BudgetAction {
+    /// Delay each call by the budget's throttle delay
+    Throttle,
+    /// Refuse calls until the period resets
+    #[default]
+    Block,
+}
+
+/// A daily or monthly limit on an agent's or user's LLM usage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBudget {
+    /// Unique budget ID
+    pub id: String,
+
+    pub scope: BudgetScope,
+
+    /// Agent ID or user name the budget applies to
+    pub target: String,
+
+    pub period: BudgetPeriod,
+
+    /// Maximum cost per period
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_cost: Option<f64>,
+
+    /// Maximum tokens (prompt + completion) per period
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_tokens: Option<u64>,
+
+    #[serde(default)]
+    pub action: BudgetAction,
+
+    /// Delay applied to each call when throttling
+    #[serde(default = "default_throttle_delay_secs")]
+    pub throttle_delay_secs: u64,
+
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+
+    /// Last updated timestamp
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+fn default_throttle_delay_secs() -> u64 {
+    10
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl UsageBudget {
+    /// Create a blocking budget with no limits set
+    pub fn new(
+        id: impl Into<String>,
+        scope: BudgetScope,
+        target: impl Into<String>,
+        period: BudgetPeriod,
+    ) -> Self {
+        Self {
+            id: id.into(),
+            scope,
+            target: target.into(),
+            period,
+            max_cost: None,
+            max_tokens: None,
+            action: BudgetAction::Block,
+            throttle_delay_secs: default_throttle_delay_secs(),
+            enabled: true,
+            updated_at: Utc::now().timestamp(),
+        }
+    }
+
+    /// Update the timestamp
+    pub fn touch(&mut self) {
+        self.updated_at = Utc::now().timestamp();
+    }
+
+    /// Whether the budget covers a call made for this agent and user
+    pub fn applies_to(&self, agent_id: Option<&str>, user_id: Option<&str>) -> bool {
+        let id = match self.scope {
+            BudgetScope::Agent => agent_id,
+            BudgetScope::User => user_id,
+        };
+        self.enabled && id == Some(self.target.as_str())
+    }
+
+    /// Whether `totals` have used up the budget
+    pub fn is_exceeded(&self, totals: &UsageTotals) -> bool {
+        self.max_cost.is_some_and(|max| totals.cost >= max)
+            || self
+                .max_tokens
+                .is_some_and(|max| totals.total_tokens() >= max)
+    }
+
+    /// Validate the budget configuration
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty() {
+            return Err("Budget ID cannot be empty".to_string());
+        }
+
+        if self.target.is_empty() {
+            return Err("Budget target cannot be empty".to_string());
+        }
+
+        if self.max_cost.is_none() && self.max_tokens.is_none() {
+            return Err("Budget must set max_cost or max_tokens".to_string());
+        }
+
+        if self.max_cost.is_some_and(|max| max < 0.0) {
+            return Err("Budget cost cannot be negative".to_string());
+        }
+
+        Ok(())
+    }
+}
+
+/// Time bucket size for usage reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageGrouping {
+    Hour,
+    #[default]
+    Day,
+    Month,
+}
+
+impl UsageGrouping {
+    /// Start (UTC, seconds) of the bucket containing `timestamp`
+    pub fn bucket_start(&self, timestamp: i64) -> i64 {
+        match self {
+            UsageGrouping::Hour => timestamp - timestamp.rem_euclid(3_600),
+            UsageGrouping::Day => timestamp - timestamp.rem_euclid(86_400),
+            UsageGrouping::Month => month_start(timestamp),
+        }
+    }
+}
+
+/// Attribute usage reports can be broken down by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UsageDimension {
+    Backend,
+    Model,
+    Agent,
+    User,
+    Session,
+    Extension,
+}
+
+/// Parameters of a usage report
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageQuery {
+    /// Start timestamp (inclusive)
+    pub from: Option<i64>,
+    /// End timestamp (exclusive)
+    pub to: Option<i64>,
+    pub group_by: UsageGrouping,
+    /// Break each bucket down by this attribute
+    pub dimension: Option<UsageDimension>,
+}
+
+/// Aggregated usage
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub calls: u64,
+    pub failed_calls: u64,
+    pub prompt_tokens: u64,
+    pub completion_tokens: u64,
+    pub cost: f64,
+    pub avg_latency_ms: f64,
+}
+
+impl UsageTotals {
+    /// Add a record to the totals
+    pub fn add(&mut self, record: &UsageRecord) {
+        self.calls += 1;
+        if !record.success {
+            self.failed_calls += 1;
+        }
+        self.prompt_tokens += record.prompt_tokens as u64;
+        self.completion_tokens += record.completion_tokens as u64;
+        self.cost += record.cost;
+        self.avg_latency_ms += (record.latency_ms as f64 - self.avg_latency_ms) / self.calls as f64;
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.prompt_tokens + self.completion_tokens
+    }
+}
+
+/// Usage within one time bucket
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageBucket {
+    /// Bucket start timestamp
+    pub start: i64,
+    pub totals: UsageTotals,
+    /// Totals per dimension value ("unknown" when unattributed)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub breakdown: BTreeMap<String, UsageTotals>,
+}
+
+/// Usage report over a time range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageReport {
+    pub query: UsageQuery,
+    pub totals: UsageTotals,
+    pub buckets: Vec<UsageBucket>,
+}
+
+fn month_start(timestamp: i64) -> i64 {
+    let date = DateTime::<Utc>::from_timestamp(timestamp, 0).unwrap_or_default();
+    Utc.with_ymd_and_hms(date.year(), date.month(), 1, 0, 0, 0)
+        .single()
+        .map(|start| start.timestamp())
+        .unwrap_or(timestamp)
+}
+
+fn time_key(timestamp: i64) -> String {
+    format!("{:012}", timestamp.max(0))
+}
+
+/// LLM usage storage
+pub struct LlmUsageStore {
+    db: Arc<Database>,
+}
+
+impl LlmUsageStore {
+    /// Open or create a usage store at the given path
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Arc<Self>, Error> {
+        let path_ref = path.as_ref();
+        let db = if path_ref.exists() {
+            Database::open(path_ref)?
+        } else {
+            if let Some(parent) = path_ref.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            Database::create(path_ref)?
+        };
+
+        let store = Arc::new(LlmUsageStore { db: Arc::new(db) });
+        store.ensure_tables()?;
+        Ok(store)
+    }
+
+    /// Create a store backed by a temporary file, for testing.
+    pub fn memory() -> Result<Arc<Self>, Error> {
+        let temp_path =
+            std::env::temp_dir().join(format!("llm_usage_test_{}.redb", uuid::Uuid::new_v4()));
+        Self::open(temp_path)
+    }
+
+    /// Ensure all required tables exist
+    fn ensure_tables(&self) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let _ = write_txn.open_table(USAGE_TABLE)?;
+            let _ = write_txn.open_table(PRICING_TABLE)?;
+            let _ = write_txn.open_table(BUDGETS_TABLE)?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Record an LLM call
+    pub fn record(&self, record: &UsageRecord) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(USAGE_TABLE)?;
+            let value = serde_json::to_vec(record)?;
+            table.insert(record.key().as_str(), value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Visit the records in `[from, to)` in time order
+    fn scan(
+        &self,
+        from: Option<i64>,
+        to: Option<i64>,
+        mut f: impl FnMut(UsageRecord),
+    ) -> Result<(), Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(USAGE_TABLE)?;
+
+        let start_key = time_key(from.unwrap_or(0));
+        let end_key = to.map(time_key);
+        for result in table.range(start_key.as_str()..)? {
+            let (key, data) = result?;
+            if end_key.as_deref().is_some_and(|end| key.value() >= end) {
+                break;
+            }
+            f(serde_json::from_slice(data.value())?);
+        }
+        Ok(())
+    }
+
+    /// Load the raw records in `[from, to)`
+    pub fn list(&self, from: Option<i64>, to: Option<i64>) -> Result<Vec<UsageRecord>, Error> {
+        let mut records = Vec::new();
+        self.scan(from, to, |record| records.push(record))?;
+        Ok(records)
+    }
+
+    /// Aggregate usage into time buckets
+    pub fn report(&self, query: &UsageQuery) -> Result<UsageReport, Error> {
+        let mut totals = UsageTotals::default();
+        let mut buckets: BTreeMap<i64, UsageBucket> = BTreeMap::new();
+
+        self.scan(query.from, query.to, |record| {
+            totals.add(&record);
+
+            let start = query.group_by.bucket_start(record.timestamp);
+            let bucket = buckets.entry(start).or_insert_with(|| UsageBucket {
+                start,
+                totals: UsageTotals::default(),
+                breakdown: BTreeMap::new(),
+            });
+            bucket.totals.add(&record);
+
+            if let Some(dimension) = query.dimension {
+                let value = record.dimension(dimension).unwrap_or("unknown").to_string();
+                bucket.breakdown.entry(value).or_default().add(&record);
+            }
+        })?;
+
+        Ok(UsageReport {
+            query: query.clone(),
+            totals,
+            buckets: buckets.into_values().collect(),
+        })
+    }
+
+    /// Usage of an agent or user since `since`
+    pub fn totals_since(
+        &self,
+        scope: BudgetScope,
+        target: &str,
+        since: i64,
+    ) -> Result<UsageTotals, Error> {
+        let mut totals = UsageTotals::default();
+        self.scan(Some(since), None, |record| {
+            let id = match scope {
+                BudgetScope::Agent => record.agent_id.as_deref(),
+                BudgetScope::User => record.user_id.as_deref(),
+            };
+            if id == Some(target) {
+                totals.add(&record);
+            }
+        })?;
+        Ok(totals)
+    }
+
+    /// Delete records older than `before`, returning how many were removed
+    pub fn prune(&self, before: i64) -> Result<usize, Error> {
+        let end_key = time_key(before);
+        let write_txn = begin_write(&self.db)?;
+        let removed = {
+            let mut table = write_txn.open_table(USAGE_TABLE)?;
+            let keys = table
+                .range(..end_key.as_str())?
+                .map(|result| result.map(|(key, _)| key.value().to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            for key in &keys {
+                table.remove(key.as_str())?;
+            }
+            keys.len()
+        };
+        write_txn.commit()?;
+        Ok(removed)
+    }
+
+    /// Load all model pricing entries
+    pub fn load_pricing(&self) -> Result<Vec<ModelPricing>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(PRICING_TABLE)?;
+
+        let mut pricing = Vec::new();
+        for result in table.iter()? {
+            let (_, data) = result?;
+            pricing.push(serde_json::from_slice(data.value())?);
+        }
+        Ok(pricing)
+    }
+
+    /// Replace all model pricing entries
+    pub fn replace_pricing(&self, pricing: &[ModelPricing]) -> Result<(), Error> {
+        for entry in pricing {
+            entry.validate().map_err(Error::InvalidInput)?;
+        }
+
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(PRICING_TABLE)?;
+            let keys = table
+                .iter()?
+                .map(|result| result.map(|(key, _)| key.value().to_string()))
+                .collect::<Result<Vec<_>, _>>()?;
+            for key in &keys {
+                table.remove(key.as_str())?;
+            }
+            for entry in pricing {
+                let value = serde_json::to_vec(entry)?;
+                table.insert(entry.model.as_str(), value.as_slice())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Pricing for a model: an exact entry, else the longest matching prefix
+    pub fn pricing_for(&self, model: &str) -> Result<Option<ModelPricing>, Error> {
+        let pricing = self.load_pricing()?;
+        Ok(ModelPricing::find(&pricing, model).cloned())
+    }
+
+    /// Save a budget
+    pub fn save_budget(&self, budget: &UsageBudget) -> Result<(), Error> {
+        budget.validate().map_err(Error::InvalidInput)?;
+
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(BUDGETS_TABLE)?;
+            let value = serde_json::to_vec(budget)?;
+            table.insert(budget.id.as_str(), value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load a budget by ID
+    pub fn load_budget(&self, id: &str) -> Result<Option<UsageBudget>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(BUDGETS_TABLE)?;
+
+        match table.get(id)? {
+            Some(data) => Ok(Some(serde_json::from_slice(data.value())?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Load all budgets
+    pub fn load_all_budgets(&self) -> Result<Vec<UsageBudget>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(BUDGETS_TABLE)?;
+
+        let mut budgets = Vec::new();
+        for result in table.iter()? {
+            let (_, data) = result?;
+            budgets.push(serde_json::from_slice(data.value())?);
+        }
+        Ok(budgets)
+    }
+
+    /// Delete a budget
+    pub fn delete_budget(&self, id: &str) -> Result<bool, Error> {
+        let write_txn = begin_write(&self.db)?;
+        let existed = {
+            let mut table = write_txn.open_table(BUDGETS_TABLE)?;
+            let result = table.remove(id)?.is_some();
+            result
+        };
+        write_txn.commit()?;
+        Ok(existed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: i64, model: &str, agent: Option<&str>, tokens: u32) -> UsageRecord {
+        let mut record = UsageRecord::new("openai", model);
+        record.timestamp = timestamp;
+        record.prompt_tokens = tokens;
+        record.completion_tokens = tokens;
+        record.latency_ms = 100;
+        record.agent_id = agent.map(str::to_string);
+        record
+    }
+
+    #[test]
+    fn test_pricing_lookup() {
+        let pricing = vec![
+            ModelPricing::new("gpt-4o*", 2.5, 10.0),
+            ModelPricing::new("gpt-4o-mini*", 0.15, 0.6),
+            ModelPricing::new("gpt-4o-mini-2024-07-18", 0.1, 0.5),
+        ];
+
+        let mini = ModelPricing::find(&pricing, "gpt-4o-mini").unwrap();
+        assert_eq!(mini.model, "gpt-4o-mini*");
+        let exact = ModelPricing::find(&pricing, "gpt-4o-mini-2024-07-18").unwrap();
+        assert_eq!(exact.input_per_million, 0.1);
+        assert!(ModelPricing::find(&pricing, "qwen3:8b").is_none());
+
+        let cost = ModelPricing::find(&pricing, "gpt-4o")
+            .unwrap()
+            .cost(1_000_000, 100_000);
+        assert!((cost - 3.5).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_usage_report_and_budgets() {
+        let store = LlmUsageStore::memory().unwrap();
+        let day = 86_400;
+        store
+            .record(&record(day + 10, "gpt-4o", Some("agent-1"), 100))
+            .unwrap();
+        store
+            .record(&record(day + 3_700, "gpt-4o", None, 50))
+            .unwrap();
+        store
+            .record(&record(2 * day + 5, "qwen3:8b", Some("agent-1"), 10))
+            .unwrap();
+
+        let report = store
+            .report(&UsageQuery {
+                from: Some(day),
+                to: Some(3 * day),
+                group_by: UsageGrouping::Day,
+                dimension: Some(UsageDimension::Agent),
+            })
+            .unwrap();
+        assert_eq!(report.totals.calls, 3);
+        assert_eq!(report.totals.total_tokens(), 320);
+        assert_eq!(report.buckets.len(), 2);
+        assert_eq!(report.buckets[0].start, day);
+        assert_eq!(report.buckets[0].breakdown["agent-1"].calls, 1);
+        assert_eq!(report.buckets[0].breakdown["unknown"].calls, 1);
+
+        let hourly = store
+            .report(&UsageQuery {
+                group_by: UsageGrouping::Hour,
+                ..Default::default()
+            })
+            .unwrap();
+        assert_eq!(hourly.buckets.len(), 3);
+
+        let totals = store
+            .totals_since(BudgetScope::Agent, "agent-1", 2 * day)
+            .unwrap();
+        assert_eq!(totals.calls, 1);
+
+        let mut budget = UsageBudget::new("b1", BudgetScope::Agent, "agent-1", BudgetPeriod::Daily);
+        assert!(store.save_budget(&budget).is_err());
+        budget.max_tokens = Some(20);
+        store.save_budget(&budget).unwrap();
+        assert!(budget.applies_to(Some("agent-1"), None));
+        assert!(budget.is_exceeded(&totals));
+        assert_eq!(budget.period.start(2 * day + 5), 2 * day);
+        assert_eq!(store.load_all_budgets().unwrap().len(), 1);
+        assert!(store.delete_budget("b1").unwrap());
+
+        assert_eq!(store.prune(2 * day).unwrap(), 2);
+        assert_eq!(store.list(None, None).unwrap().len(), 1);
+    }
+}
@@ -9,6 +9,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Messages table: key = message_id, value = Message (serialized as JSON)
@@ -118,8 +119,7 @@ impl MessageStore {
             .map_err(|e| Error::Storage(format!("Failed to open message database: {}", e)))?;
 
         // Create tables
-        let write_txn = db
-            .begin_write()
+        let write_txn = begin_write(&db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         {
@@ -150,8 +150,7 @@ impl MessageStore {
             .map_err(|e| Error::Storage(format!("Failed to create test database: {}", e)))?;
 
         // Create tables
-        let write_txn = db
-            .begin_write()
+        let write_txn = begin_write(&db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         {
@@ -175,9 +174,7 @@ impl MessageStore {
 
     /// Insert a message.
     pub fn insert(&self, msg: &StoredMessage) -> Result<(), Error> {
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         let json = serde_json::to_string(msg)
@@ -243,9 +240,7 @@ impl MessageStore {
 
     /// Update a message.
     pub fn update(&self, msg: &StoredMessage) -> Result<(), Error> {
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         let json = serde_json::to_string(msg)
@@ -306,9 +301,7 @@ impl MessageStore {
 
     /// Delete a message.
     pub fn delete(&self, id: &str) -> Result<bool, Error> {
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         let existed = {
@@ -452,9 +445,7 @@ impl MessageStore {
     pub fn cleanup_old(&self, older_than_days: i64) -> Result<usize, Error> {
         let cutoff = chrono::Utc::now().timestamp() - (older_than_days * 86400);
 
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         let to_remove = {
@@ -511,9 +502,7 @@ impl MessageStore {
 
     /// Clear all messages.
     pub fn clear(&self) -> Result<(), Error> {
-        let write_txn = self
-            .db
-            .begin_write()
+        let write_txn = begin_write(&self.db)
             .map_err(|e| Error::Storage(format!("Failed to begin write: {}", e)))?;
 
         // Collect all keys to delete first
@@ -11,6 +11,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use tokio::fs;
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Image metadata table: key = image_id, value = ImageMetadata (serialized)
@@ -154,7 +155,7 @@ impl MultimodalStore {
         };
 
         // Save metadata to database
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(IMAGE_METADATA_TABLE)?;
             let serialized = bincode::serialize(&metadata)?;
@@ -193,7 +194,7 @@ impl MultimodalStore {
             updated_metadata.embedding_id = Some(eid.to_string());
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(IMAGE_METADATA_TABLE)?;
             let serialized = bincode::serialize(&updated_metadata)?;
@@ -253,7 +254,7 @@ impl MultimodalStore {
         };
 
         // Save metadata to database
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(DOCUMENT_METADATA_TABLE)?;
             let serialized = bincode::serialize(&metadata)?;
@@ -292,7 +293,7 @@ impl MultimodalStore {
             updated_metadata.embedding_id = Some(eid.to_string());
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(DOCUMENT_METADATA_TABLE)?;
             let serialized = bincode::serialize(&updated_metadata)?;
@@ -344,7 +345,7 @@ impl MultimodalStore {
         }
 
         // Delete from database
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(IMAGE_METADATA_TABLE)?;
             table.remove(image_id)?;
@@ -378,7 +379,7 @@ impl MultimodalStore {
         }
 
         // Delete from database
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(DOCUMENT_METADATA_TABLE)?;
             table.remove(doc_id)?;
@@ -0,0 +1,22 @@
+//! Location of the data directory.
+//!
+//! Every store lives under one directory so snapshots can capture and restore
+//! all of them together. It is `$NEOMIND_DATA_DIR`, or `data` relative to the
+//! working directory.
+
+use std::path::PathBuf;
+
+/// Environment variable overriding the data directory.
+pub const DATA_DIR_ENV: &str = "NEOMIND_DATA_DIR";
+
+/// Get the data directory holding every store (`NEOMIND_DATA_DIR` > `data`).
+pub fn data_dir() -> PathBuf {
+    std::env::var(DATA_DIR_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|_| PathBuf::from("data"))
+}
+
+/// Get the path of a file or directory inside the data directory.
+pub fn data_path(name: &str) -> PathBuf {
+    data_dir().join(name)
+}
@@ -13,6 +13,7 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Session table: key = session_id, value = timestamp
@@ -337,7 +338,7 @@ impl SessionStore {
 
     /// Save a session ID.
     pub fn save_session_id(&self, session_id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(SESSIONS_TABLE)?;
             let timestamp = chrono::Utc::now().timestamp();
@@ -374,7 +375,7 @@ impl SessionStore {
             }
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(HISTORY_TABLE)?;
 
@@ -427,7 +428,7 @@ impl SessionStore {
     /// Clear message history for a session.
     /// This is the ONLY method that should be used to intentionally clear history.
     pub fn clear_history(&self, session_id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(HISTORY_TABLE)?;
 
@@ -501,7 +502,7 @@ impl SessionStore {
     /// Append a single message to session history (incremental save).
     /// This is more efficient than save_history for adding new messages.
     pub fn append_message(&self, session_id: &str, message: &SessionMessage) -> Result<u64, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let index = {
             let mut table = write_txn.open_table(HISTORY_TABLE)?;
 
@@ -540,7 +541,7 @@ impl SessionStore {
             return Ok(0);
         }
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let count = {
             let mut table = write_txn.open_table(HISTORY_TABLE)?;
 
@@ -586,7 +587,7 @@ impl SessionStore {
     /// Delete a session.
     pub fn delete_session(&self, session_id: &str) -> Result<(), Error> {
         tracing::debug!("[SessionStore] delete_session called for: {}", session_id);
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         tracing::debug!("[SessionStore] write transaction started");
 
         // Delete from sessions table
@@ -672,7 +673,7 @@ impl SessionStore {
         session_id: &str,
         metadata: &SessionMetadata,
     ) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(SESSIONS_META_TABLE)?;
             let value = serde_json::to_vec(metadata)?;
@@ -703,7 +704,7 @@ impl SessionStore {
 
     /// Delete session metadata.
     pub fn delete_session_metadata(&self, session_id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(SESSIONS_META_TABLE)?;
             table.remove(session_id)?;
@@ -721,7 +722,7 @@ impl SessionStore {
             Error::Storage(format!("Failed to serialize pending stream state: {}", e))
         })?;
 
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             // Use open_table which creates the table if it doesn't exist
             let mut table = write_txn.open_table(PENDING_STREAM_TABLE)
@@ -771,7 +772,7 @@ impl SessionStore {
     /// Delete the pending stream state for a session.
     /// Returns Ok(()) even if the table doesn't exist.
     pub fn delete_pending_stream(&self, session_id: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = match write_txn.open_table(PENDING_STREAM_TABLE) {
                 Ok(t) => t,
@@ -872,7 +873,7 @@ impl SessionStore {
         // Delete stale states
         let count = stale_session_ids.len();
         if !stale_session_ids.is_empty() {
-            let write_txn = self.db.begin_write()?;
+            let write_txn = begin_write(&self.db)?;
             {
                 let mut table = write_txn.open_table(PENDING_STREAM_TABLE)?;
                 for session_id in stale_session_ids {
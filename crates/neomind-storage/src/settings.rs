@@ -2,6 +2,7 @@
 //!
 //! Provides persistent storage for LLM and MQTT configuration.
 
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex as StdMutex;
@@ -9,6 +10,7 @@ use std::sync::Mutex as StdMutex;
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Settings table: key = "llm_config", value = LlmSettings (serialized)
@@ -26,6 +28,9 @@ pub const DEFAULT_GLOBAL_TIMEZONE: &str = "Asia/Shanghai";
 const EXTERNAL_BROKERS_TABLE: TableDefinition<&str, &[u8]> =
     TableDefinition::new("external_brokers");
 
+// MCP servers table: key = server_id, value = McpServerConfig (serialized)
+const MCP_SERVERS_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("mcp_servers");
+
 // Config history table: key = timestamp_id, value = ConfigChangeEntry (serialized)
 const CONFIG_HISTORY_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("config_history");
 
@@ -359,12 +364,100 @@ pub struct ExternalBroker {
     #[serde(default = "default_external_broker_subscribe_topics")]
     #[serde(skip_serializing_if = "is_default_subscribe_topics")]
     pub subscribe_topics: Vec<String>,
+
+    /// Decode Sparkplug B (`spBv1.0/...`) payloads from this broker.
+    #[serde(default)]
+    pub sparkplug: bool,
+
+    /// Buffer outbound publishes while this broker is unreachable and replay them
+    /// on reconnect.
+    #[serde(default = "default_external_broker_store_forward")]
+    pub store_forward: bool,
+
+    /// Republish device telemetry to this broker under `{prefix}/{device_id}/{metric}`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub forward_telemetry: Option<String>,
+
+    /// Mirror topics between the embedded broker and this broker.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bridge: Option<BrokerBridge>,
+}
+
+/// Direction a bridge rule mirrors messages in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum BridgeDirection {
+    /// Embedded broker → upstream broker
+    #[default]
+    Out,
+    /// Upstream broker → embedded broker
+    In,
+    /// Both directions
+    Both,
+}
+
+/// A single bridge rule: which local topics to mirror and how.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BridgeRule {
+    /// Topic filter on the embedded broker (MQTT wildcards allowed).
+    pub topic: String,
+
+    #[serde(default)]
+    pub direction: BridgeDirection,
+
+    #[serde(default = "default_bridge_qos")]
+    pub qos: u8,
+
+    /// Topic filters excluded from this rule.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub exclude: Vec<String>,
+}
+
+/// Bridge between the embedded broker and an external broker.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BrokerBridge {
+    #[serde(default = "default_external_broker_enabled")]
+    pub enabled: bool,
+
+    /// Gateway identifier; used as the upstream topic prefix by default.
+    pub gateway_id: String,
+
+    /// Upstream topic prefix overriding `{gateway_id}/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub remote_prefix: Option<String>,
+
+    #[serde(default)]
+    pub rules: Vec<BridgeRule>,
+
+    /// Initial reconnect delay in seconds.
+    #[serde(default = "default_bridge_reconnect_min")]
+    pub reconnect_min_secs: u64,
+
+    /// Maximum reconnect delay in seconds.
+    #[serde(default = "default_bridge_reconnect_max")]
+    pub reconnect_max_secs: u64,
+}
+
+fn default_bridge_qos() -> u8 {
+    1
+}
+
+fn default_bridge_reconnect_min() -> u64 {
+    1
+}
+
+fn default_bridge_reconnect_max() -> u64 {
+    60
 }
 
 fn default_external_broker_port() -> u16 {
     1883
 }
 
+fn default_external_broker_store_forward() -> bool {
+    true
+}
+
 fn default_external_broker_enabled() -> bool {
     true
 }
@@ -403,6 +496,10 @@ impl ExternalBroker {
             last_error: None,
             updated_at: chrono::Utc::now().timestamp(),
             subscribe_topics: default_external_broker_subscribe_topics(),
+            sparkplug: false,
+            store_forward: default_external_broker_store_forward(),
+            forward_telemetry: None,
+            bridge: None,
         }
     }
 
@@ -554,6 +651,113 @@ pub struct SecurityWarning {
     pub recommendation: String,
 }
 
+/// How NeoMind reaches an external MCP server.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum McpTransportConfig {
+    /// Child process speaking newline-delimited JSON-RPC on stdin/stdout
+    Stdio {
+        /// Executable to launch
+        command: String,
+        /// Command-line arguments
+        #[serde(default, skip_serializing_if = "Vec::is_empty")]
+        args: Vec<String>,
+        /// Extra environment variables
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        env: HashMap<String, String>,
+    },
+    /// Streamable HTTP endpoint
+    Http {
+        /// Endpoint URL
+        url: String,
+        /// Extra request headers (e.g. Authorization)
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        headers: HashMap<String, String>,
+    },
+}
+
+/// External MCP server whose tools are imported into NeoMind.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct McpServerConfig {
+    /// Unique identifier, also used as the tool namespace.
+    pub id: String,
+
+    /// Display name.
+    pub name: String,
+
+    /// Connection settings.
+    pub transport: McpTransportConfig,
+
+    /// Whether the server is connected and its tools offered.
+    #[serde(default = "default_mcp_server_enabled")]
+    pub enabled: bool,
+
+    /// Timeout for each request, in seconds.
+    #[serde(default = "default_mcp_timeout_secs")]
+    pub timeout_secs: u64,
+
+    /// Last updated timestamp.
+    pub updated_at: i64,
+}
+
+fn default_mcp_server_enabled() -> bool {
+    true
+}
+
+fn default_mcp_timeout_secs() -> u64 {
+    30
+}
+
+impl McpServerConfig {
+    /// Create a new enabled MCP server configuration.
+    pub fn new(id: String, name: String, transport: McpTransportConfig) -> Self {
+        Self {
+            id,
+            name,
+            transport,
+            enabled: default_mcp_server_enabled(),
+            timeout_secs: default_mcp_timeout_secs(),
+            updated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    /// Update the timestamp.
+    pub fn touch(&mut self) {
+        self.updated_at = chrono::Utc::now().timestamp();
+    }
+
+    /// Validate the configuration.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.id.is_empty()
+            || !self
+                .id
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err("Server ID may only contain letters, digits, '_' and '-'".to_string());
+        }
+        if self.timeout_secs == 0 {
+            return Err("Timeout must be at least 1 second".to_string());
+        }
+        match &self.transport {
+            McpTransportConfig::Stdio { command, .. } if command.trim().is_empty() => {
+                Err("Command is required for stdio servers".to_string())
+            }
+            McpTransportConfig::Http { url, .. }
+                if !url.starts_with("http://") && !url.starts_with("https://") =>
+            {
+                Err("URL must start with http:// or https://".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Generate a unique ID for a new server.
+    pub fn generate_id() -> String {
+        format!("mcp_{}", uuid::Uuid::new_v4().to_string().split_at(8).0)
+    }
+}
+
 /// Settings storage using redb.
 pub struct SettingsStore {
     db: Arc<Database>,
@@ -600,12 +804,14 @@ impl SettingsStore {
 
     /// Ensure all required tables exist in the database.
     fn ensure_tables(&self) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             // Open or create the settings table
             let _ = write_txn.open_table(SETTINGS_TABLE)?;
             // Open or create the external_brokers table
             let _ = write_txn.open_table(EXTERNAL_BROKERS_TABLE)?;
+            // Open or create the mcp_servers table
+            let _ = write_txn.open_table(MCP_SERVERS_TABLE)?;
             // Open or create the config_history table
             let _ = write_txn.open_table(CONFIG_HISTORY_TABLE)?;
         }
@@ -672,7 +878,7 @@ impl SettingsStore {
 
     /// Record a configuration change in history.
     pub fn record_config_change(&self, entry: &ConfigChangeEntry) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(CONFIG_HISTORY_TABLE)?;
             let value =
@@ -757,7 +963,7 @@ impl SettingsStore {
         // Delete entries beyond keep_count
         let mut deleted = 0;
         if entries.len() > keep_count {
-            let write_txn = self.db.begin_write()?;
+            let write_txn = begin_write(&self.db)?;
             {
                 let mut table = write_txn.open_table(CONFIG_HISTORY_TABLE)?;
                 for (key, _) in entries.iter().skip(keep_count) {
@@ -788,6 +994,10 @@ impl SettingsStore {
         export["external_brokers"] =
             serde_json::to_value(brokers).unwrap_or(serde_json::Value::Null);
 
+        let mcp_servers = self.load_all_mcp_servers()?;
+        export["mcp_servers"] =
+            serde_json::to_value(mcp_servers).unwrap_or(serde_json::Value::Null);
+
         Ok(export)
     }
 
@@ -818,6 +1028,17 @@ impl SettingsStore {
             }
         }
 
+        // Import MCP servers
+        if let Some(servers_value) = import.get("mcp_servers") {
+            if let Ok(servers) =
+                serde_json::from_value::<Vec<McpServerConfig>>(servers_value.clone())
+            {
+                for server in servers {
+                    self.save_mcp_server(&server)?;
+                }
+            }
+        }
+
         Ok(())
     }
 
@@ -878,7 +1099,7 @@ impl SettingsStore {
 
     /// Save LLM settings.
     pub fn save_llm_settings(&self, settings: &LlmSettings) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(SETTINGS_TABLE)?;
             let value =
@@ -910,7 +1131,7 @@ impl SettingsStore {
 
     /// Delete LLM settings.
     pub fn delete_llm_settings(&self) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(SETTINGS_TABLE)?;
             let result = table.remove("llm_config")?.is_some();
@@ -927,7 +1148,7 @@ impl SettingsStore {
 
     /// Save arbitrary settings value.
     pub fn save(&self, key: &str, value: &str) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(SETTINGS_TABLE)?;
             table.insert(key, value.as_bytes())?;
@@ -954,7 +1175,7 @@ impl SettingsStore {
 
     /// Save MQTT settings.
     pub fn save_mqtt_settings(&self, settings: &MqttSettings) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(SETTINGS_TABLE)?;
             let value =
@@ -986,7 +1207,7 @@ impl SettingsStore {
 
     /// Delete MQTT settings.
     pub fn delete_mqtt_settings(&self) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(SETTINGS_TABLE)?;
             let result = table.remove("mqtt_config")?.is_some();
@@ -1003,7 +1224,7 @@ impl SettingsStore {
 
     /// Save an external broker configuration.
     pub fn save_external_broker(&self, broker: &ExternalBroker) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(EXTERNAL_BROKERS_TABLE)?;
             let value =
@@ -1046,7 +1267,7 @@ impl SettingsStore {
 
     /// Delete an external broker by ID.
     pub fn delete_external_broker(&self, id: &str) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         let existed = {
             let mut table = write_txn.open_table(EXTERNAL_BROKERS_TABLE)?;
             let result = table.remove(id)?.is_some();
@@ -1062,6 +1283,64 @@ impl SettingsStore {
         Ok(all.into_iter().filter(|b| b.enabled).collect())
     }
 
+    // ========================================================================
+    // MCP Servers
+    // ========================================================================
+
+    /// Save an MCP server configuration.
+    pub fn save_mcp_server(&self, server: &McpServerConfig) -> Result<(), Error> {
+        let write_txn = begin_write(&self.db)?;
+        {
+            let mut table = write_txn.open_table(MCP_SERVERS_TABLE)?;
+            let value =
+                serde_json::to_vec(server).map_err(|e| Error::Serialization(e.to_string()))?;
+            table.insert(server.id.as_str(), value.as_slice())?;
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load an MCP server by ID.
+    pub fn load_mcp_server(&self, id: &str) -> Result<Option<McpServerConfig>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(MCP_SERVERS_TABLE)?;
+
+        if let Some(data) = table.get(id)? {
+            let server: McpServerConfig = serde_json::from_slice(data.value())
+                .map_err(|e| Error::Serialization(e.to_string()))?;
+            Ok(Some(server))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Load all MCP servers.
+    pub fn load_all_mcp_servers(&self) -> Result<Vec<McpServerConfig>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let table = read_txn.open_table(MCP_SERVERS_TABLE)?;
+
+        let mut servers = Vec::new();
+        for result in table.iter()? {
+            let (_, data) = result?;
+            let server: McpServerConfig = serde_json::from_slice(data.value())
+                .map_err(|e| Error::Serialization(e.to_string()))?;
+            servers.push(server);
+        }
+        Ok(servers)
+    }
+
+    /// Delete an MCP server by ID.
+    pub fn delete_mcp_server(&self, id: &str) -> Result<bool, Error> {
+        let write_txn = begin_write(&self.db)?;
+        let existed = {
+            let mut table = write_txn.open_table(MCP_SERVERS_TABLE)?;
+            let result = table.remove(id)?.is_some();
+            result
+        };
+        write_txn.commit()?;
+        Ok(existed)
+    }
+
     // ========================================================================
     // Global Timezone Settings
     // ========================================================================
@@ -1136,4 +1415,55 @@ mod tests {
         assert!(store.delete_llm_settings().unwrap());
         assert!(!store.has_llm_settings());
     }
+
+    #[test]
+    fn test_external_broker_bridge_defaults() {
+        let broker: ExternalBroker = serde_json::from_value(serde_json::json!({
+            "id": "b1",
+            "name": "central",
+            "broker": "mqtt.example.com",
+            "updated_at": 0,
+            "bridge": {
+                "gateway_id": "gw-17",
+                "rules": [{ "topic": "sensors/#" }]
+            }
+        }))
+        .unwrap();
+
+        assert!(broker.store_forward);
+        let bridge = broker.bridge.unwrap();
+        assert!(bridge.enabled);
+        assert_eq!(bridge.reconnect_min_secs, 1);
+        assert_eq!(bridge.rules[0].direction, BridgeDirection::Out);
+        assert_eq!(bridge.rules[0].qos, 1);
+    }
+
+    #[test]
+    fn test_mcp_server_config() {
+        let server: McpServerConfig = serde_json::from_value(serde_json::json!({
+            "id": "weather",
+            "name": "Weather",
+            "transport": { "type": "stdio", "command": "weather-mcp" },
+            "updated_at": 0
+        }))
+        .unwrap();
+        assert!(server.enabled);
+        assert_eq!(server.timeout_secs, 30);
+        assert!(server.validate().is_ok());
+
+        let mut invalid = server.clone();
+        invalid.transport = McpTransportConfig::Http {
+            url: "ftp://example.com".to_string(),
+            headers: HashMap::new(),
+        };
+        assert!(invalid.validate().is_err());
+        invalid.id = "bad id".to_string();
+        assert!(invalid.validate().is_err());
+
+        let store = SettingsStore::open(":memory:").unwrap();
+        store.save_mcp_server(&server).unwrap();
+        assert_eq!(store.load_mcp_server("weather").unwrap(), Some(server));
+        assert!(store.delete_mcp_server("weather").unwrap());
+        assert!(store.load_mcp_server("weather").unwrap().is_none());
+    }
 }
@@ -0,0 +1,757 @@
+//! Whole-system snapshots.
+//!
+//! A snapshot captures every file under the data directory (all redb stores,
+//! extension data, settings) into one zip archive with a versioned manifest
+//! and SHA-256 checksums. Every store opens its write transactions through
+//! [`begin_write`], so [`pause_writes`] holds all of them off while the files
+//! are copied.
+//!
+//! Restores are staged: the archive is verified and extracted inside the data
+//! directory, and [`apply_pending_restore`] swaps it in before any store is
+//! opened — on the next startup, or right away when nothing is running. The
+//! swap is journaled in a marker file so an interrupted restore completes on
+//! the next attempt; the replaced files are kept in a `.pre-restore-*` directory.
+
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::path::{Path, PathBuf};
+use std::sync::{Condvar, Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::backup::{BackupManager, BackupMetadata, BackupType};
+use crate::{Error, Result};
+
+/// Archive format version written to the manifest.
+pub const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_NAME: &str = "manifest.json";
+const DATA_PREFIX: &str = "data/";
+const STAGING_DIR: &str = ".restore-staging";
+const PENDING_MARKER: &str = ".restore-pending";
+const PRE_RESTORE_PREFIX: &str = ".pre-restore-";
+
+/// Open write transactions, and whether a snapshot has paused new ones.
+struct GateState {
+    writers: usize,
+    paused: bool,
+}
+
+static WRITE_GATE: Mutex<GateState> = Mutex::new(GateState {
+    writers: 0,
+    paused: false,
+});
+static WRITES_RESUMED: Condvar = Condvar::new();
+
+fn gate_state() -> MutexGuard<'static, GateState> {
+    WRITE_GATE.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Counts an open write transaction until dropped.
+struct WriterPass;
+
+impl Drop for WriterPass {
+    fn drop(&mut self) {
+        gate_state().writers -= 1;
+    }
+}
+
+/// A redb write transaction that keeps snapshots out until it ends.
+pub struct WriteTransaction {
+    txn: redb::WriteTransaction,
+    _pass: WriterPass,
+}
+
+impl WriteTransaction {
+    /// Commit the transaction.
+    pub fn commit(self) -> std::result::Result<(), redb::CommitError> {
+        self.txn.commit()
+    }
+
+    /// Abort the transaction.
+    pub fn abort(self) -> std::result::Result<(), redb::StorageError> {
+        self.txn.abort()
+    }
+}
+
+impl Deref for WriteTransaction {
+    type Target = redb::WriteTransaction;
+
+    fn deref(&self) -> &Self::Target {
+        &self.txn
+    }
+}
+
+impl DerefMut for WriteTransaction {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.txn
+    }
+}
+
+/// Begin a write transaction on a store in the data directory.
+///
+/// Waits while a snapshot is copying the files. On a multi-threaded tokio
+/// runtime the wait hands the worker's other tasks off first, so a paused
+/// writer does not stall the runtime. Returns the same error type as
+/// [`redb::Database::begin_write`] so callers keep their conversions.
+#[allow(clippy::result_large_err)]
+pub fn begin_write(
+    db: &redb::Database,
+) -> std::result::Result<WriteTransaction, redb::TransactionError> {
+    let mut state = gate_state();
+    if state.paused {
+        drop(state);
+        let on_worker = tokio::runtime::Handle::try_current().is_ok_and(|handle| {
+            handle.runtime_flavor() == tokio::runtime::RuntimeFlavor::MultiThread
+        });
+        if on_worker {
+            tokio::task::block_in_place(wait_for_resume);
+        } else {
+            wait_for_resume();
+        }
+    } else {
+        state.writers += 1;
+        drop(state);
+    }
+
+    let pass = WriterPass;
+    Ok(WriteTransaction {
+        txn: db.begin_write()?,
+        _pass: pass,
+    })
+}
+
+/// Block until writes are no longer paused, then count a new writer.
+fn wait_for_resume() {
+    let mut state = gate_state();
+    while state.paused {
+        state = WRITES_RESUMED
+            .wait(state)
+            .unwrap_or_else(PoisonError::into_inner);
+    }
+    state.writers += 1;
+}
+
+/// Writes held off by [`pause_writes`]; they resume when this is dropped.
+pub struct WritesPaused(());
+
+impl Drop for WritesPaused {
+    fn drop(&mut self) {
+        gate_state().paused = false;
+        WRITES_RESUMED.notify_all();
+    }
+}
+
+/// Wait for open write transactions to finish and hold off new ones.
+///
+/// Returns `None` if transactions are still open after `timeout`. Writers are
+/// only paused once none is open, so a writer that opens a transaction while
+/// holding another never deadlocks behind a waiting snapshot.
+pub fn pause_writes(timeout: Duration) -> Option<WritesPaused> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        {
+            let mut state = gate_state();
+            if state.writers == 0 {
+                state.paused = true;
+                return Some(WritesPaused(()));
+            }
+        }
+        if Instant::now() >= deadline {
+            return None;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Manifest stored in every snapshot archive.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotManifest {
+    /// Archive format version
+    pub format_version: u32,
+    /// Backup ID
+    pub id: String,
+    /// Creation time (unix seconds)
+    pub created_at: i64,
+    /// Version of the application that wrote the snapshot
+    pub app_version: String,
+    /// Optional description
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Captured files
+    pub entries: Vec<SnapshotEntry>,
+}
+
+/// A file captured in a snapshot.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SnapshotEntry {
+    /// Path relative to the data directory, `/`-separated
+    pub path: String,
+    /// File size in bytes
+    pub size_bytes: u64,
+    /// Hex SHA-256 of the file contents
+    pub sha256: String,
+}
+
+/// Result of verifying a snapshot archive.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SnapshotVerification {
+    /// Backup ID from the manifest
+    pub id: String,
+    /// Entries whose checksum was checked
+    pub entries_checked: usize,
+    /// redb stores that were opened successfully
+    pub stores_opened: usize,
+    /// Problems found
+    pub errors: Vec<String>,
+}
+
+impl SnapshotVerification {
+    /// Whether the archive passed every check.
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Result of applying a staged restore.
+#[derive(Debug, Clone, Serialize)]
+pub struct RestoreOutcome {
+    /// Number of top-level entries moved into the data directory
+    pub entries_restored: usize,
+    /// Where the replaced data was moved
+    pub previous_data: PathBuf,
+}
+
+impl BackupManager {
+    /// Capture every file under `data_dir` into a snapshot archive.
+    ///
+    /// The caller is responsible for pausing writers (see [`pause_writes`]).
+    pub fn create_snapshot(
+        &self,
+        data_dir: &Path,
+        description: Option<String>,
+    ) -> Result<BackupMetadata> {
+        let backup_id = self.generate_backup_id();
+        let timestamp = chrono::Utc::now().timestamp();
+        let backup_path = self
+            .config()
+            .backup_dir
+            .join(format!("snapshot_{}.zip", backup_id));
+        let partial_path = backup_path.with_extension("zip.partial");
+
+        let skip = fs::canonicalize(&self.config().backup_dir).ok();
+        let files = collect_data_files(data_dir, skip.as_deref())?;
+
+        let mut zip = zip::ZipWriter::new(BufWriter::new(File::create(&partial_path)?));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Deflated)
+            .large_file(true);
+
+        let mut entries = Vec::with_capacity(files.len());
+        for (relative, path) in files {
+            zip.start_file(format!("{}{}", DATA_PREFIX, relative), options)
+                .map_err(zip_error)?;
+            let (size_bytes, sha256) = copy_hashed(&mut File::open(&path)?, &mut zip)?;
+            entries.push(SnapshotEntry {
+                path: relative,
+                size_bytes,
+                sha256,
+            });
+        }
+
+        let manifest = SnapshotManifest {
+            format_version: SNAPSHOT_FORMAT_VERSION,
+            id: backup_id.clone(),
+            created_at: timestamp,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            description: description.clone(),
+            entries,
+        };
+        zip.start_file(MANIFEST_NAME, zip::write::SimpleFileOptions::default())
+            .map_err(zip_error)?;
+        serde_json::to_writer_pretty(&mut zip, &manifest)?;
+        zip.finish().map_err(zip_error)?.flush()?;
+
+        // Only complete archives get their final name
+        fs::rename(&partial_path, &backup_path)?;
+
+        let metadata = BackupMetadata {
+            id: backup_id,
+            timestamp,
+            backup_type: BackupType::Snapshot,
+            size_bytes: fs::metadata(&backup_path)?.len(),
+            table_count: manifest.entries.len(),
+            description,
+            path: backup_path,
+        };
+
+        self.save_metadata(&metadata)?;
+        self.cleanup_old_backups()?;
+
+        Ok(metadata)
+    }
+
+    /// Verify a snapshot by ID (see [`verify_snapshot_archive`]).
+    pub fn verify_snapshot(&self, backup_id: &str) -> Result<SnapshotVerification> {
+        verify_snapshot_archive(&self.snapshot_path(backup_id)?)
+    }
+
+    /// Verify a snapshot by ID and stage it for restore into `data_dir`
+    /// (see [`stage_snapshot_restore`]).
+    pub fn stage_restore(&self, backup_id: &str, data_dir: &Path) -> Result<SnapshotManifest> {
+        stage_snapshot_restore(&self.snapshot_path(backup_id)?, data_dir)
+    }
+
+    fn snapshot_path(&self, backup_id: &str) -> Result<PathBuf> {
+        match self.get_backup(backup_id)? {
+            Some(metadata) if metadata.backup_type == BackupType::Snapshot => Ok(metadata.path),
+            Some(_) => Err(Error::InvalidInput(format!(
+                "Backup {} is not a snapshot",
+                backup_id
+            ))),
+            None => Err(Error::NotFound(format!("Backup {}", backup_id))),
+        }
+    }
+}
+
+/// Check every entry's checksum and open every redb store in the archive.
+pub fn verify_snapshot_archive(archive_path: &Path) -> Result<SnapshotVerification> {
+    let mut archive = open_archive(archive_path)?;
+    let manifest = read_manifest(&mut archive)?;
+    let mut report = SnapshotVerification {
+        id: manifest.id.clone(),
+        ..Default::default()
+    };
+
+    let scratch = std::env::temp_dir().join(format!("neomind_verify_{}", uuid::Uuid::new_v4()));
+    fs::create_dir_all(&scratch)?;
+
+    for (index, entry) in manifest.entries.iter().enumerate() {
+        let is_store = entry.path.ends_with(".redb");
+        let scratch_file = scratch.join(format!("{}.redb", index));
+
+        let copied = {
+            let mut file = match archive.by_name(&format!("{}{}", DATA_PREFIX, entry.path)) {
+                Ok(file) => file,
+                Err(e) => {
+                    report
+                        .errors
+                        .push(format!("{}: missing ({})", entry.path, e));
+                    continue;
+                }
+            };
+            if is_store {
+                let mut out = File::create(&scratch_file)?;
+                copy_hashed(&mut file, &mut out)
+            } else {
+                copy_hashed(&mut file, &mut std::io::sink())
+            }
+        };
+
+        let (size_bytes, sha256) = match copied {
+            Ok(copied) => copied,
+            Err(e) => {
+                report
+                    .errors
+                    .push(format!("{}: unreadable ({})", entry.path, e));
+                continue;
+            }
+        };
+        report.entries_checked += 1;
+        if size_bytes != entry.size_bytes || sha256 != entry.sha256 {
+            report
+                .errors
+                .push(format!("{}: checksum mismatch", entry.path));
+            continue;
+        }
+
+        if is_store {
+            match open_store(&scratch_file) {
+                Ok(()) => report.stores_opened += 1,
+                Err(e) => report.errors.push(format!("{}: {}", entry.path, e)),
+            }
+            let _ = fs::remove_file(&scratch_file);
+        }
+    }
+
+    let _ = fs::remove_dir_all(&scratch);
+    Ok(report)
+}
+
+/// Verify an archive and extract it into the data directory's staging area.
+///
+/// The restore is applied by [`apply_pending_restore`], which must run before
+/// any store in `data_dir` is opened.
+pub fn stage_snapshot_restore(archive_path: &Path, data_dir: &Path) -> Result<SnapshotManifest> {
+    let report = verify_snapshot_archive(archive_path)?;
+    if !report.is_ok() {
+        return Err(Error::InvalidInput(format!(
+            "Snapshot {} failed verification: {}",
+            report.id,
+            report.errors.join("; ")
+        )));
+    }
+
+    let mut archive = open_archive(archive_path)?;
+    let manifest = read_manifest(&mut archive)?;
+
+    fs::create_dir_all(data_dir)?;
+    let staging = data_dir.join(STAGING_DIR);
+    if staging.exists() {
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::create_dir_all(&staging)?;
+
+    for entry in &manifest.entries {
+        let target = staging.join(safe_relative_path(&entry.path)?);
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = archive
+            .by_name(&format!("{}{}", DATA_PREFIX, entry.path))
+            .map_err(zip_error)?;
+        let mut out = BufWriter::new(File::create(&target)?);
+        std::io::copy(&mut file, &mut out)?;
+        out.flush()?;
+    }
+
+    // The marker is written last: a restore is only pending once fully staged
+    fs::write(data_dir.join(PENDING_MARKER), "staged\n")?;
+    Ok(manifest)
+}
+
+/// Swap a staged restore into `data_dir`.
+///
+/// Returns `None` when no restore is pending. Safe to call again after an
+/// interruption; it picks up where the previous attempt stopped.
+pub fn apply_pending_restore(data_dir: &Path) -> Result<Option<RestoreOutcome>> {
+    let marker = data_dir.join(PENDING_MARKER);
+    if !marker.exists() {
+        return Ok(None);
+    }
+    let staging = data_dir.join(STAGING_DIR);
+
+    let journal = fs::read_to_string(&marker)?;
+    let mut lines = journal.lines();
+    let phase = lines.next().unwrap_or("staged");
+    let previous_name = match lines.next() {
+        Some(name) => name.to_string(),
+        None => format!(
+            "{}{}",
+            PRE_RESTORE_PREFIX,
+            chrono::Utc::now().format("%Y%m%d_%H%M%S")
+        ),
+    };
+    let previous = data_dir.join(&previous_name);
+
+    if phase != "moving-in" {
+        fs::write(&marker, format!("moving-out\n{}\n", previous_name))?;
+        fs::create_dir_all(&previous)?;
+        for entry in fs::read_dir(data_dir)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            if is_reserved(&name.to_string_lossy()) {
+                continue;
+            }
+            fs::rename(entry.path(), previous.join(&name))?;
+        }
+        fs::write(&marker, format!("moving-in\n{}\n", previous_name))?;
+    }
+
+    let mut entries_restored = 0;
+    if staging.exists() {
+        for entry in fs::read_dir(&staging)? {
+            let entry = entry?;
+            fs::rename(entry.path(), data_dir.join(entry.file_name()))?;
+            entries_restored += 1;
+        }
+        fs::remove_dir_all(&staging)?;
+    }
+    fs::remove_file(&marker)?;
+
+    Ok(Some(RestoreOutcome {
+        entries_restored,
+        previous_data: previous,
+    }))
+}
+
+/// Names in the data directory that belong to the restore machinery.
+fn is_reserved(name: &str) -> bool {
+    name == STAGING_DIR || name == PENDING_MARKER || name.starts_with(PRE_RESTORE_PREFIX)
+}
+
+/// Every regular file under `data_dir` as (relative `/` path, absolute path), sorted.
+fn collect_data_files(data_dir: &Path, skip: Option<&Path>) -> Result<Vec<(String, PathBuf)>> {
+    let mut files = Vec::new();
+    if !data_dir.exists() {
+        return Ok(files);
+    }
+
+    let mut pending = vec![data_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        for entry in fs::read_dir(&dir)? {
+            let entry = entry?;
+            let path = entry.path();
+            if dir == data_dir && is_reserved(&entry.file_name().to_string_lossy()) {
+                continue;
+            }
+            let file_type = entry.file_type()?;
+            if file_type.is_dir() {
+                // A backup directory inside the data directory is not captured
+                if skip.is_some_and(|skip| fs::canonicalize(&path).ok().as_deref() == Some(skip)) {
+                    continue;
+                }
+                pending.push(path);
+            } else if file_type.is_file() {
+                let relative = path
+                    .strip_prefix(data_dir)
+                    .map_err(|e| Error::Storage(e.to_string()))?
+                    .components()
+                    .map(|c| c.as_os_str().to_string_lossy())
+                    .collect::<Vec<_>>()
+                    .join("/");
+                files.push((relative, path));
+            }
+        }
+    }
+
+    files.sort();
+    Ok(files)
+}
+
+/// Reject manifest paths that would escape the staging directory.
+fn safe_relative_path(path: &str) -> Result<PathBuf> {
+    let relative = Path::new(path);
+    if path.is_empty()
+        || relative
+            .components()
+            .any(|c| !matches!(c, std::path::Component::Normal(_)))
+    {
+        return Err(Error::InvalidInput(format!(
+            "Invalid path in snapshot: {}",
+            path
+        )));
+    }
+    Ok(relative.to_path_buf())
+}
+
+fn open_archive(path: &Path) -> Result<zip::ZipArchive<BufReader<File>>> {
+    zip::ZipArchive::new(BufReader::new(File::open(path)?)).map_err(zip_error)
+}
+
+fn read_manifest<R: Read + std::io::Seek>(
+    archive: &mut zip::ZipArchive<R>,
+) -> Result<SnapshotManifest> {
+    let file = archive
+        .by_name(MANIFEST_NAME)
+        .map_err(|_| Error::InvalidInput("Snapshot has no manifest".to_string()))?;
+    let manifest: SnapshotManifest = serde_json::from_reader(file)?;
+    if manifest.format_version > SNAPSHOT_FORMAT_VERSION {
+        return Err(Error::InvalidInput(format!(
+            "Snapshot format version {} is newer than supported version {}",
+            manifest.format_version, SNAPSHOT_FORMAT_VERSION
+        )));
+    }
+    Ok(manifest)
+}
+
+/// Open a redb file and list its tables.
+fn open_store(path: &Path) -> Result<()> {
+    let db = redb::Database::open(path)?;
+    let read_txn = db.begin_read()?;
+    read_txn.list_tables()?.for_each(drop);
+    Ok(())
+}
+
+/// Copy `reader` into `writer`, returning the byte count and hex SHA-256.
+fn copy_hashed<R: Read, W: Write>(reader: &mut R, writer: &mut W) -> Result<(u64, String)> {
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0u8; 64 * 1024];
+    let mut total = 0u64;
+    loop {
+        let read = reader.read(&mut buffer)?;
+        if read == 0 {
+            break;
+        }
+        hasher.update(&buffer[..read]);
+        writer.write_all(&buffer[..read])?;
+        total += read as u64;
+    }
+    Ok((total, format!("{:x}", hasher.finalize())))
+}
+
+fn zip_error(e: zip::result::ZipError) -> Error {
+    Error::Storage(format!("Snapshot archive error: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backup::BackupConfig;
+    use redb::TableDefinition;
+
+    const TEST_TABLE: TableDefinition<&str, u64> = TableDefinition::new("test");
+
+    fn write_store(path: &Path, value: u64) {
+        let db = redb::Database::create(path).unwrap();
+        let write_txn = db.begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(TEST_TABLE).unwrap();
+            table.insert("key", value).unwrap();
+        }
+        write_txn.commit().unwrap();
+    }
+
+    fn read_store(path: &Path) -> u64 {
+        let db = redb::Database::open(path).unwrap();
+        let read_txn = db.begin_read().unwrap();
+        let table = read_txn.open_table(TEST_TABLE).unwrap();
+        let value = table.get("key").unwrap().unwrap().value();
+        value
+    }
+
+    #[test]
+    fn test_snapshot_verify_and_restore() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(data_dir.join("extensions/demo")).unwrap();
+        write_store(&data_dir.join("agents.redb"), 1);
+        fs::write(data_dir.join("extensions/demo/state.json"), "{}").unwrap();
+
+        let manager = BackupManager::with_config(BackupConfig {
+            backup_dir: temp_dir.path().join("backups"),
+            ..Default::default()
+        })
+        .unwrap();
+        let metadata = manager
+            .create_snapshot(&data_dir, Some("nightly".to_string()))
+            .unwrap();
+        assert_eq!(metadata.backup_type, BackupType::Snapshot);
+        assert_eq!(metadata.table_count, 2);
+
+        let report = manager.verify_snapshot(&metadata.id).unwrap();
+        assert!(report.is_ok(), "{:?}", report.errors);
+        assert_eq!(report.entries_checked, 2);
+        assert_eq!(report.stores_opened, 1);
+
+        // Change the live data, then restore the snapshot over it
+        write_store(&data_dir.join("agents.redb"), 2);
+        fs::write(data_dir.join("rules.redb"), "not in the snapshot").unwrap();
+
+        manager.stage_restore(&metadata.id, &data_dir).unwrap();
+        let outcome = apply_pending_restore(&data_dir).unwrap().unwrap();
+        assert_eq!(outcome.entries_restored, 2);
+
+        assert_eq!(read_store(&data_dir.join("agents.redb")), 1);
+        assert!(!data_dir.join("rules.redb").exists());
+        assert!(data_dir.join("extensions/demo/state.json").exists());
+        assert_eq!(read_store(&outcome.previous_data.join("agents.redb")), 2);
+        assert!(apply_pending_restore(&data_dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_corrupt_snapshot_fails_verification() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        fs::create_dir_all(&data_dir).unwrap();
+        fs::write(data_dir.join("sessions.redb"), "not a database").unwrap();
+
+        let manager = BackupManager::new(temp_dir.path().join("backups")).unwrap();
+        let metadata = manager.create_snapshot(&data_dir, None).unwrap();
+
+        let report = manager.verify_snapshot(&metadata.id).unwrap();
+        assert!(!report.is_ok());
+        assert!(manager.stage_restore(&metadata.id, &data_dir).is_err());
+        assert!(apply_pending_restore(&data_dir).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_pause_writes_waits_for_open_transactions() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db =
+            std::sync::Arc::new(redb::Database::create(temp_dir.path().join("a.redb")).unwrap());
+
+        let write_txn = begin_write(&db).unwrap();
+        assert!(pause_writes(Duration::from_millis(50)).is_none());
+        write_txn.commit().unwrap();
+
+        // New transactions wait until the pause ends
+        let paused = pause_writes(Duration::from_secs(5)).unwrap();
+        let writer = {
+            let db = db.clone();
+            std::thread::spawn(move || {
+                let write_txn = begin_write(&db).unwrap();
+                write_txn
+                    .open_table(TEST_TABLE)
+                    .unwrap()
+                    .insert("key", 7)
+                    .unwrap();
+                write_txn.commit().unwrap();
+            })
+        };
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!writer.is_finished());
+        drop(paused);
+        writer.join().unwrap();
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+    async fn test_paused_writer_does_not_stall_runtime() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let db =
+            std::sync::Arc::new(redb::Database::create(temp_dir.path().join("b.redb")).unwrap());
+
+        let paused = pause_writes(Duration::from_secs(5)).unwrap();
+        let writer = {
+            let db = db.clone();
+            tokio::spawn(async move { begin_write(&db).unwrap().commit().unwrap() })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        // The only worker is waiting on the gate; other tasks still run
+        let other = tokio::spawn(async { 1 });
+        assert_eq!(
+            tokio::time::timeout(Duration::from_secs(1), other)
+                .await
+                .unwrap()
+                .unwrap(),
+            1
+        );
+        assert!(!writer.is_finished());
+
+        drop(paused);
+        writer.await.unwrap();
+    }
+
+    #[test]
+    fn test_interrupted_restore_completes() {
+        let temp_dir = tempfile::tempdir().unwrap();
+        let data_dir = temp_dir.path();
+        fs::create_dir_all(data_dir.join(STAGING_DIR)).unwrap();
+        fs::write(data_dir.join(STAGING_DIR).join("a.json"), "new").unwrap();
+        fs::write(data_dir.join("a.json"), "old").unwrap();
+
+        // Stopped after moving the old data out
+        let previous = format!("{}test", PRE_RESTORE_PREFIX);
+        fs::create_dir_all(data_dir.join(&previous)).unwrap();
+        fs::rename(
+            data_dir.join("a.json"),
+            data_dir.join(&previous).join("a.json"),
+        )
+        .unwrap();
+        fs::write(
+            data_dir.join(PENDING_MARKER),
+            format!("moving-in\n{}\n", previous),
+        )
+        .unwrap();
+
+        let outcome = apply_pending_restore(data_dir).unwrap().unwrap();
+        assert_eq!(outcome.previous_data, data_dir.join(&previous));
+        assert_eq!(fs::read_to_string(data_dir.join("a.json")).unwrap(), "new");
+        assert!(!data_dir.join(STAGING_DIR).exists());
+        assert!(!data_dir.join(PENDING_MARKER).exists());
+    }
+}
@@ -8,10 +8,19 @@
 //! - **Memory Cache**: Latest values cached for fast access
 //! - **Batch Optimization**: Group writes by device for efficiency
 //! - **Performance Monitoring**: Track operation latency and throughput
-
+//! - **Chunked Layout**: Seal per-series time buckets into compressed chunks
+//!   (see [`crate::timeseries_chunk`])
+//! - **Rollups**: min/max/avg/count/sum/last tiers maintained on write, with
+//!   their own retention, used by aggregated queries
+//! - **Group Commits**: opt-in via [`TimeSeriesConfig::group_commit`]; writes
+//!   are buffered and committed in groups by a background flusher (see
+//!   [`crate::timeseries_ingest`])
+
+use std::collections::{BTreeMap, BTreeSet};
 use std::path::Path;
-use std::sync::Arc;
+use std::sync::atomic::Ordering;
 use std::sync::Mutex as StdMutex;
+use std::sync::{Arc, Weak};
 use std::time::{Duration, Instant};
 
 use chrono::{DateTime, Utc};
@@ -21,12 +30,30 @@ use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 use tokio::sync::{RwLock, Semaphore};
 
+use crate::snapshot::begin_write;
+use crate::timeseries_chunk::{decode_chunk, encode_chunk, read_chunk_header};
+use crate::timeseries_ingest::{FlusherGuard, GroupCommitConfig, IngestBuffer, SeriesPoints};
 use crate::Error;
 
 // redb table definition: key = (device_id, metric, timestamp), value = DataPoint (serialized)
+// In the chunked layout this table is the head: rows not sealed into a chunk yet.
 const TIMESERIES_TABLE: TableDefinition<(&str, &str, i64), &[u8]> =
     TableDefinition::new("timeseries");
 
+// Compressed chunks: key = (device_id, metric, last timestamp in chunk), value = encoded chunk
+const CHUNKS_TABLE: TableDefinition<(&str, &str, i64), &[u8]> =
+    TableDefinition::new("timeseries_chunks");
+
+// Rollups: key = (device_id, metric, tier_secs, bucket_start), value = RollupBucket (bincode)
+const ROLLUPS_TABLE: TableDefinition<(&str, &str, i64, i64), &[u8]> =
+    TableDefinition::new("timeseries_rollups");
+
+// Chunk storage totals, kept in the same transaction as the chunks they describe
+const TIMESERIES_META_TABLE: TableDefinition<&str, u64> = TableDefinition::new("timeseries_meta");
+const META_CHUNK_COUNT: &str = "chunk_count";
+const META_CHUNK_RAW_BYTES: &str = "chunk_raw_bytes";
+const META_CHUNK_STORED_BYTES: &str = "chunk_stored_bytes";
+
 /// A single data point in time series.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DataPoint {
@@ -131,6 +158,9 @@ pub struct TimeSeriesBucket {
     pub max: Option<f64>,
     /// Average value (only for numeric data).
     pub avg: Option<f64>,
+    /// Latest value in the bucket (only for numeric data).
+    #[serde(default)]
+    pub last: Option<f64>,
     /// Sample values (for non-numeric data).
     pub sample_values: Vec<serde_json::Value>,
 }
@@ -146,6 +176,7 @@ impl TimeSeriesBucket {
             min: None,
             max: None,
             avg: None,
+            last: None,
             sample_values: Vec::new(),
         }
     }
@@ -158,6 +189,7 @@ impl TimeSeriesBucket {
             self.min = Some(self.min.map_or(num, |m| m.min(num)));
             self.max = Some(self.max.map_or(num, |m| m.max(num)));
             self.avg = self.sum.map(|s| s / self.count as f64);
+            self.last = Some(num);
         } else {
             // For non-numeric values, keep samples (up to 10)
             if self.sample_values.len() < 10 {
@@ -170,8 +202,73 @@ impl TimeSeriesBucket {
     pub fn is_empty(&self) -> bool {
         self.count == 0
     }
+
+    /// Merge a stored rollup into the bucket.
+    fn merge_rollup(&mut self, rollup: &RollupBucket, last_ts: &mut i64) {
+        self.count += rollup.count as u32;
+        self.sum = Some(self.sum.unwrap_or(0.0) + rollup.sum);
+        self.min = Some(self.min.map_or(rollup.min, |m| m.min(rollup.min)));
+        self.max = Some(self.max.map_or(rollup.max, |m| m.max(rollup.max)));
+        self.avg = self.sum.map(|s| s / self.count as f64);
+        if rollup.last_ts >= *last_ts {
+            *last_ts = rollup.last_ts;
+            self.last = Some(rollup.last);
+        }
+    }
+}
+
+/// Pre-aggregated numeric values of one series in one rollup bucket.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+struct RollupBucket {
+    count: u64,
+    sum: f64,
+    min: f64,
+    max: f64,
+    last: f64,
+    last_ts: i64,
+}
+
+impl RollupBucket {
+    fn new(timestamp: i64, value: f64) -> Self {
+        Self {
+            count: 1,
+            sum: value,
+            min: value,
+            max: value,
+            last: value,
+            last_ts: timestamp,
+        }
+    }
+
+    fn add(&mut self, timestamp: i64, value: f64) {
+        self.count += 1;
+        self.sum += value;
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        // Points may arrive out of order; keep the newest as `last`
+        if timestamp >= self.last_ts {
+            self.last = value;
+            self.last_ts = timestamp;
+        }
+    }
+
+    /// Take a point back out of the bucket.
+    ///
+    /// Returns false when that cannot be done in place because the point set
+    /// the min, max or last value, or was the only one in the bucket.
+    fn remove(&mut self, timestamp: i64, value: f64) -> bool {
+        if self.count <= 1 || value <= self.min || value >= self.max || timestamp == self.last_ts {
+            return false;
+        }
+        self.count -= 1;
+        self.sum -= value;
+        true
+    }
 }
 
+/// Rollup buckets to rebuild from raw points, as `(device_id, metric, tier, bucket_start)`.
+type StaleRollups = BTreeSet<(String, String, i64, i64)>;
+
 /// Time series query result.
 #[derive(Debug, Clone)]
 pub struct TimeSeriesResult {
@@ -201,6 +298,19 @@ pub struct RetentionPolicy {
     pub metric_overrides: std::collections::HashMap<String, Option<u64>>,
     /// Per-device-type retention overrides
     pub device_type_overrides: std::collections::HashMap<String, Option<u64>>,
+    /// Retention per rollup tier (tier seconds -> hours, None = forever).
+    /// Applies independently of raw data, so aggregates can outlive it.
+    #[serde(default = "default_rollup_retention")]
+    pub rollup_hours: std::collections::BTreeMap<i64, Option<u64>>,
+}
+
+/// Default rollup retention: 1 min for 90 days, 1 h for 2 years, 1 day forever.
+fn default_rollup_retention() -> std::collections::BTreeMap<i64, Option<u64>> {
+    std::collections::BTreeMap::from([
+        (60, Some(24 * 90)),
+        (3600, Some(24 * 365 * 2)),
+        (86400, None),
+    ])
 }
 
 impl RetentionPolicy {
@@ -210,6 +320,7 @@ impl RetentionPolicy {
             default_hours,
             metric_overrides: std::collections::HashMap::with_capacity(16),  // Pre-allocate for typical use
             device_type_overrides: std::collections::HashMap::with_capacity(8),  // Pre-allocate for typical use
+            rollup_hours: default_rollup_retention(),
         }
     }
 
@@ -243,6 +354,22 @@ impl RetentionPolicy {
         let now = Utc::now().timestamp();
         Some(now - (hours as i64 * 3600))
     }
+
+    /// Set retention for a rollup tier.
+    pub fn set_rollup_retention(&mut self, tier_secs: i64, hours: Option<u64>) {
+        self.rollup_hours.insert(tier_secs, hours);
+    }
+
+    /// Get retention hours for a rollup tier (tiers without an entry are kept forever).
+    pub fn get_rollup_retention_hours(&self, tier_secs: i64) -> Option<u64> {
+        self.rollup_hours.get(&tier_secs).copied().flatten()
+    }
+
+    /// Calculate the cutoff timestamp for a rollup tier.
+    pub fn rollup_cutoff_timestamp(&self, tier_secs: i64) -> Option<i64> {
+        let hours = self.get_rollup_retention_hours(tier_secs)?;
+        Some(Utc::now().timestamp() - (hours as i64 * 3600))
+    }
 }
 
 impl Default for RetentionPolicy {
@@ -281,6 +408,16 @@ pub struct PerformanceStats {
     pub cleanup_points_removed: u64,
     /// Last cleanup timestamp
     pub last_cleanup_timestamp: Option<i64>,
+    /// Number of compressed chunks on disk
+    pub chunk_count: u64,
+    /// Size the chunked points would take as JSON rows
+    pub chunk_raw_bytes: u64,
+    /// Actual size of the compressed chunks
+    pub chunk_stored_bytes: u64,
+    /// Group commits of buffered writes
+    pub group_commits: u64,
+    /// Points committed by group commits
+    pub group_commit_points: u64,
 }
 
 impl PerformanceStats {
@@ -330,6 +467,76 @@ impl PerformanceStats {
     pub fn record_cache_miss(&mut self) {
         self.cache_misses += 1;
     }
+
+    /// Bytes saved by storing data in compressed chunks instead of JSON rows.
+    pub fn storage_saved_bytes(&self) -> u64 {
+        self.chunk_raw_bytes.saturating_sub(self.chunk_stored_bytes)
+    }
+
+    /// Compression ratio of chunked data (raw size / stored size).
+    pub fn compression_ratio(&self) -> f64 {
+        if self.chunk_stored_bytes == 0 {
+            return 0.0;
+        }
+        self.chunk_raw_bytes as f64 / self.chunk_stored_bytes as f64
+    }
+
+    fn apply_chunk_delta(&mut self, delta: &ChunkDelta) {
+        self.chunk_count = self.chunk_count.saturating_add_signed(delta.chunks);
+        self.chunk_raw_bytes = self.chunk_raw_bytes.saturating_add_signed(delta.raw_bytes);
+        self.chunk_stored_bytes = self
+            .chunk_stored_bytes
+            .saturating_add_signed(delta.stored_bytes);
+    }
+}
+
+/// Change to chunk storage totals made by one transaction.
+#[derive(Debug, Clone, Copy, Default)]
+struct ChunkDelta {
+    chunks: i64,
+    raw_bytes: i64,
+    stored_bytes: i64,
+    /// Head rows sealed into chunks
+    points_sealed: u64,
+}
+
+impl ChunkDelta {
+    fn add_chunk(&mut self, data: &[u8]) -> Result<(), Error> {
+        let header = read_chunk_header(data)?;
+        self.chunks += 1;
+        self.raw_bytes += header.raw_size as i64;
+        self.stored_bytes += data.len() as i64;
+        Ok(())
+    }
+
+    fn remove_chunk(&mut self, data: &[u8]) -> Result<(), Error> {
+        let header = read_chunk_header(data)?;
+        self.chunks -= 1;
+        self.raw_bytes -= header.raw_size as i64;
+        self.stored_bytes -= data.len() as i64;
+        Ok(())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.chunks == 0 && self.raw_bytes == 0 && self.stored_bytes == 0
+    }
+
+    /// Persist the delta into the meta table of an open write transaction.
+    fn persist(&self, write_txn: &redb::WriteTransaction) -> Result<(), Error> {
+        if self.is_empty() {
+            return Ok(());
+        }
+        let mut meta = write_txn.open_table(TIMESERIES_META_TABLE)?;
+        for (key, delta) in [
+            (META_CHUNK_COUNT, self.chunks),
+            (META_CHUNK_RAW_BYTES, self.raw_bytes),
+            (META_CHUNK_STORED_BYTES, self.stored_bytes),
+        ] {
+            let current = meta.get(key)?.map(|v| v.value()).unwrap_or(0);
+            meta.insert(key, current.saturating_add_signed(delta))?;
+        }
+        Ok(())
+    }
 }
 
 /// Batch write request grouped by device.
@@ -394,6 +601,18 @@ pub struct TimeSeriesStore {
     max_cache_size: usize,
     /// Storage path for singleton
     path: String,
+    /// Layout used for new data
+    layout: TimeSeriesLayout,
+    /// Current head bucket per series (chunked layout): (device_id, metric) -> bucket start
+    head_buckets: DashMap<(String, String), i64>,
+    /// Rollup tier sizes in seconds, ascending
+    rollup_tiers: Arc<Vec<i64>>,
+    /// Group commit buffer (None commits every write on its own)
+    ingest: Option<Arc<IngestBuffer>>,
+    /// Handle to this store for the background flusher
+    self_ref: Weak<TimeSeriesStore>,
+    /// Configuration the store was opened with
+    config: TimeSeriesConfig,
 }
 
 /// Global time series store singleton (thread-safe).
@@ -407,6 +626,9 @@ impl TimeSeriesStore {
     }
 
     /// Open or create a time series store with custom configuration.
+    ///
+    /// Fails if the store is already open with a different configuration
+    /// (the retention policy aside, which can be changed at runtime).
     pub fn with_config<P: AsRef<Path>>(
         path: P,
         config: TimeSeriesConfig,
@@ -418,6 +640,12 @@ impl TimeSeriesStore {
             let singleton = TIMESERIES_STORE_SINGLETON.lock().unwrap();
             if let Some(store) = singleton.as_ref() {
                 if store.path == path_str {
+                    if !store.config.same_setup(&config) {
+                        return Err(Error::InvalidInput(format!(
+                            "Time series store at {} is already open with a different configuration",
+                            path_str
+                        )));
+                    }
                     return Ok(store.clone());
                 }
             }
@@ -431,16 +659,31 @@ impl TimeSeriesStore {
             Database::create(path_ref)?
         };
 
-        let store = Arc::new(TimeSeriesStore {
+        let mut stats = PerformanceStats::default();
+        load_chunk_totals(&db, &mut stats)?;
+        if let TimeSeriesLayout::Chunked { chunk_secs } = config.layout {
+            seal_stale_buckets(&db, chunk_secs, Utc::now().timestamp(), &mut stats)?;
+        }
+
+        let opened_with = config.clone();
+        let store = Arc::new_cyclic(|self_ref| TimeSeriesStore {
             db: Arc::new(db),
             metrics_info: DashMap::with_capacity(64),  // Pre-allocate for typical metrics
             latest_cache: DashMap::with_capacity(config.max_cache_size.min(500)),
             retention_policy: RwLock::new(config.retention_policy),
-            stats: Arc::new(RwLock::new(PerformanceStats::default())),
+            stats: Arc::new(RwLock::new(stats)),
             write_semaphore: Arc::new(Semaphore::new(config.max_concurrent_writes)),
             cache_ttl: config.cache_ttl,
             max_cache_size: config.max_cache_size,
             path: path_str,
+            layout: config.layout,
+            head_buckets: DashMap::new(),
+            rollup_tiers: Arc::new(normalize_tiers(config.rollup_tiers)),
+            ingest: config
+                .group_commit
+                .map(|group_commit| Arc::new(IngestBuffer::new(group_commit))),
+            self_ref: self_ref.clone(),
+            config: opened_with,
         });
 
         *TIMESERIES_STORE_SINGLETON.lock().unwrap() = Some(store.clone());
@@ -459,9 +702,21 @@ impl TimeSeriesStore {
     }
 
     /// Reset performance statistics.
+    ///
+    /// Chunk storage totals describe what is on disk and are kept.
     pub async fn reset_stats(&self) {
         let mut stats = self.stats.write().await;
-        *stats = PerformanceStats::default();
+        *stats = PerformanceStats {
+            chunk_count: stats.chunk_count,
+            chunk_raw_bytes: stats.chunk_raw_bytes,
+            chunk_stored_bytes: stats.chunk_stored_bytes,
+            ..PerformanceStats::default()
+        };
+    }
+
+    /// Get the layout used for new data.
+    pub fn layout(&self) -> TimeSeriesLayout {
+        self.layout
     }
 
     /// Get retention policy.
@@ -503,21 +758,33 @@ impl TimeSeriesStore {
         point: DataPoint,
     ) -> Result<(), Error> {
         let start = Instant::now();
-        let _permit = self
-            .write_semaphore
-            .acquire()
-            .await
-            .map_err(|_| Error::Storage("Write semaphore closed".to_string()))?;
-
-        let key = (device_id, metric, point.timestamp);
-        let value = serde_json::to_vec(&point)?;
-
-        let write_txn = self.db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
-            table.insert(key, value.as_slice())?;
+        if let Some(ingest) = &self.ingest {
+            self.ensure_flusher(ingest);
+            ingest.push(device_id, metric, [point.clone()]).await?;
+        } else {
+            let _permit = self
+                .write_semaphore
+                .acquire()
+                .await
+                .map_err(|_| Error::Storage("Write semaphore closed".to_string()))?;
+
+            let write_txn = begin_write(&self.db)?;
+            let mut stale = StaleRollups::new();
+            {
+                let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
+                insert_point(
+                    &write_txn,
+                    &mut table,
+                    &self.rollup_tiers,
+                    device_id,
+                    metric,
+                    &point,
+                    &mut stale,
+                )?;
+            }
+            rebuild_rollups(&write_txn, &stale)?;
+            write_txn.commit()?;
         }
-        write_txn.commit()?;
 
         // Update cache - DashMap is lock-free
         self.update_cache(device_id, metric, point.clone()).await;
@@ -536,12 +803,191 @@ impl TimeSeriesStore {
             });
 
         // Record stats
-        let mut stats = self.stats.write().await;
-        stats.record_write(start.elapsed());
+        {
+            let mut stats = self.stats.write().await;
+            stats.record_write(start.elapsed());
+        }
+
+        // Buffered points are sealed after their group commit
+        if self.ingest.is_none() {
+            self.seal_if_bucket_closed(device_id, metric, point.timestamp)
+                .await;
+        }
 
         Ok(())
     }
 
+    /// Start the background flusher if it is not running.
+    fn ensure_flusher(&self, ingest: &Arc<IngestBuffer>) {
+        if ingest.flusher_running.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        let store = self.self_ref.clone();
+        let ingest = Arc::clone(ingest);
+        tokio::spawn(async move {
+            let _guard = FlusherGuard(Arc::clone(&ingest));
+            loop {
+                // Woken early when a batch fills up
+                let _ = tokio::time::timeout(ingest.max_latency(), ingest.notify.notified()).await;
+                let Some(store) = store.upgrade() else {
+                    break;
+                };
+                if let Err(e) = store.flush_buffered().await {
+                    tracing::warn!("Time series group commit failed: {}", e);
+                }
+            }
+        });
+    }
+
+    /// Commit buffered writes now, returning the number of points committed.
+    ///
+    /// Called by the background flusher and on shutdown.
+    pub async fn flush_buffered(&self) -> Result<usize, Error> {
+        let Some((count, series)) = self.commit_buffered()? else {
+            return Ok(0);
+        };
+
+        {
+            let mut stats = self.stats.write().await;
+            stats.group_commits += 1;
+            stats.group_commit_points += count as u64;
+        }
+
+        // A group can span several buckets of a series; touching its oldest
+        // point first makes the seal cover all of them
+        for ((device_id, metric), (min_ts, max_ts)) in series {
+            self.seal_if_bucket_closed(&device_id, &metric, min_ts)
+                .await;
+            self.seal_if_bucket_closed(&device_id, &metric, max_ts)
+                .await;
+        }
+        Ok(count)
+    }
+
+    /// Number of buffered points waiting for a group commit.
+    pub fn buffered_points(&self) -> usize {
+        self.ingest.as_ref().map_or(0, |ingest| ingest.len())
+    }
+
+    /// Commit the buffered points in one transaction. Returns the point count
+    /// and the oldest and newest timestamp of each committed series.
+    fn commit_buffered(
+        &self,
+    ) -> Result<Option<(usize, Vec<((String, String), (i64, i64))>)>, Error> {
+        let Some(ingest) = &self.ingest else {
+            return Ok(None);
+        };
+        let _commit = ingest.commit_lock.lock().unwrap();
+        let Some(group) = ingest.take() else {
+            return Ok(None);
+        };
+
+        let result = write_group(&self.db, &self.rollup_tiers, &group.points);
+        let count = group.count;
+        let series: Vec<((String, String), (i64, i64))> = group
+            .points
+            .iter()
+            .filter_map(|(key, points)| {
+                let min_ts = *points.first_key_value()?.0;
+                let max_ts = *points.last_key_value()?.0;
+                Some((key.clone(), (min_ts, max_ts)))
+            })
+            .collect();
+        ingest.finish(group, result.is_ok());
+        result?;
+        Ok(Some((count, series)))
+    }
+
+    /// Buffered points of a series in `[start, end]`.
+    fn buffered_range(
+        &self,
+        device_id: &str,
+        metric: &str,
+        start: i64,
+        end: i64,
+    ) -> Vec<DataPoint> {
+        self.ingest
+            .as_ref()
+            .map(|ingest| ingest.range(device_id, metric, start, end))
+            .unwrap_or_default()
+    }
+
+    /// In the chunked layout, seal the previous head bucket of a series into a
+    /// chunk once a point for a later bucket arrives.
+    async fn seal_if_bucket_closed(&self, device_id: &str, metric: &str, timestamp: i64) {
+        let TimeSeriesLayout::Chunked { chunk_secs } = self.layout else {
+            return;
+        };
+        let bucket = timestamp.div_euclid(chunk_secs) * chunk_secs;
+
+        // The first write of a series after startup seals everything before its
+        // bucket, which picks up the bucket that was still open when the store
+        // was opened.
+        let previous = {
+            let mut head = self
+                .head_buckets
+                .entry((device_id.to_string(), metric.to_string()))
+                .or_insert(i64::MIN);
+            let previous = *head;
+            if bucket > previous {
+                *head = bucket;
+            }
+            previous
+        };
+        if bucket <= previous {
+            return;
+        }
+
+        match seal_series(&self.db, device_id, metric, previous, bucket, chunk_secs) {
+            Ok(delta) => {
+                if delta.points_sealed > 0 {
+                    tracing::debug!(
+                        "Sealed {} points of {}:{} into chunks",
+                        delta.points_sealed,
+                        device_id,
+                        metric
+                    );
+                    self.stats.write().await.apply_chunk_delta(&delta);
+                }
+            }
+            Err(e) => tracing::warn!(
+                "Failed to seal time series chunk for {}:{}: {}",
+                device_id,
+                metric,
+                e
+            ),
+        }
+    }
+
+    /// Convert every head row into compressed chunks.
+    ///
+    /// This is the migration path for databases written with the raw layout;
+    /// it is safe to run repeatedly and on live stores.
+    pub async fn migrate_to_chunked(&self, chunk_secs: i64) -> Result<ChunkMigrationResult, Error> {
+        if chunk_secs <= 0 {
+            return Err(Error::InvalidInput(format!(
+                "chunk_secs must be positive, got {}",
+                chunk_secs
+            )));
+        }
+
+        self.flush_buffered().await?;
+        let series = list_series(&self.db, TIMESERIES_TABLE)?;
+        let mut result = ChunkMigrationResult::default();
+
+        for (device_id, metric) in &series {
+            let delta = seal_series(&self.db, device_id, metric, i64::MIN, i64::MAX, chunk_secs)?;
+            result.series += 1;
+            result.points_migrated += delta.points_sealed;
+            self.stats.write().await.apply_chunk_delta(&delta);
+        }
+
+        let stats = self.stats.read().await;
+        result.chunk_raw_bytes = stats.chunk_raw_bytes;
+        result.chunk_stored_bytes = stats.chunk_stored_bytes;
+        Ok(result)
+    }
+
     /// Update the latest value cache.
     async fn update_cache(&self, device_id: &str, metric: &str, point: DataPoint) {
         let key = (device_id.to_string(), metric.to_string());
@@ -587,32 +1033,39 @@ impl TimeSeriesStore {
         metric: &str,
         points: Vec<DataPoint>,
     ) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
-            for point in &points {
-                let key = (device_id, metric, point.timestamp);
-                let value = serde_json::to_vec(point)?;
-                table.insert(key, value.as_slice())?;
+        if let Some(ingest) = &self.ingest {
+            self.ensure_flusher(ingest);
+            ingest
+                .push(device_id, metric, points.iter().cloned())
+                .await?;
+        } else {
+            let write_txn = begin_write(&self.db)?;
+            let mut stale = StaleRollups::new();
+            {
+                let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
+                for point in &points {
+                    insert_point(
+                        &write_txn,
+                        &mut table,
+                        &self.rollup_tiers,
+                        device_id,
+                        metric,
+                        point,
+                        &mut stale,
+                    )?;
+                }
             }
+            rebuild_rollups(&write_txn, &stale)?;
+            write_txn.commit()?;
         }
-        write_txn.commit()?;
 
-        // Update metrics info - DashMap entry API is lock-free
-        let metric_key = format!("{}:{}", device_id, metric);
-        let now = Utc::now().timestamp();
-        let last_ts = points.last().map(|p| p.timestamp).unwrap_or(now);
-        
-        self.metrics_info
-            .entry(metric_key)
-            .and_modify(|entry| {
-                entry.last_update = last_ts;
-                entry.point_count += points.len() as u64;
-            })
-            .or_insert_with(|| MetricInfo {
-                last_update: last_ts,
-                point_count: points.len() as u64,
-            });
+        self.record_metric_info(device_id, metric, &points);
+
+        if self.ingest.is_none() {
+            if let Some(max_ts) = points.iter().map(|p| p.timestamp).max() {
+                self.seal_if_bucket_closed(device_id, metric, max_ts).await;
+            }
+        }
 
         Ok(())
     }
@@ -625,64 +1078,18 @@ impl TimeSeriesStore {
         start: i64,
         end: i64,
     ) -> Result<TimeSeriesResult, Error> {
-        let read_txn = self.db.begin_read()?;
-
-        // Handle case where table doesn't exist yet (no data has been written)
-        let table = match read_txn.open_table(TIMESERIES_TABLE) {
-            Ok(t) => t,
-            Err(redb::TableError::TableDoesNotExist(_)) => {
-                tracing::debug!(
-                    "query_range: table 'timeseries' does not exist yet, returning empty result for device_id={}, metric={}",
-                    device_id,
-                    metric
-                );
-                return Ok(TimeSeriesResult {
-                    device_id: device_id.to_string(),
-                    metric: metric.to_string(),
-                    points: Vec::new(),
-                    total_count: None,
-                });
-            }
-            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
-        };
-
-        let start_key = (device_id, metric, start);
-        let end_key = (device_id, metric, end);
-
-        tracing::debug!(
-            "query_range: device_id={}, metric={}, start={}, end={}, start_key={:?}, end_key={:?}",
-            device_id,
-            metric,
-            start,
-            end,
-            start_key,
-            end_key
+        let points = merge_buffered(
+            read_series(&self.db, device_id, metric, start, end)?,
+            self.buffered_range(device_id, metric, start, end),
         );
 
-        let mut points = Vec::new();
-        let mut count = 0u32;
-        for result in table.range(start_key..=end_key)? {
-            count += 1;
-            let (key, value) = result?;
-            let (did, met, ts) = key.value();
-            tracing::trace!(
-                "query_range: found key=({},{},{}), value_len={}",
-                did,
-                met,
-                ts,
-                value.value().len()
-            );
-            let point: DataPoint = serde_json::from_slice(value.value())?;
-            points.push(point);
-        }
-
         tracing::debug!(
             "query_range: device_id={}, metric={}, start={}, end={}, found {} points",
             device_id,
             metric,
             start,
             end,
-            count
+            points.len()
         );
 
         Ok(TimeSeriesResult {
@@ -700,40 +1107,9 @@ impl TimeSeriesStore {
         metric: &str,
         start: i64,
         end: i64,
+        buffered: Vec<DataPoint>,
     ) -> Result<TimeSeriesResult, Error> {
-        let read_txn = db.begin_read()?;
-        let table = match read_txn.open_table(TIMESERIES_TABLE) {
-            Ok(t) => t,
-            Err(redb::TableError::TableDoesNotExist(_)) => {
-                return Ok(TimeSeriesResult {
-                    device_id: device_id.to_string(),
-                    metric: metric.to_string(),
-                    points: Vec::new(),
-                    total_count: None,
-                });
-            }
-            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
-        };
-
-        let start_key = (device_id, metric, start);
-        let end_key = (device_id, metric, end);
-
-        let mut points = Vec::new();
-        let mut count = 0u32;
-
-        for result in table.range(start_key..=end_key)? {
-            count += 1;
-            let (_key, value) = result?;
-            match serde_json::from_slice(value.value()) {
-                Ok(point) => points.push(point),
-                Err(e) => {
-                    tracing::warn!(
-                        "query_single_metric: failed to deserialize data point: {}",
-                        e
-                    );
-                }
-            }
-        }
+        let points = merge_buffered(read_series(&db, device_id, metric, start, end)?, buffered);
 
         tracing::debug!(
             "query_single_metric: device_id={}, metric={}, start={}, end={}, found {} points",
@@ -741,7 +1117,7 @@ impl TimeSeriesStore {
             metric,
             start,
             end,
-            count
+            points.len()
         );
 
         Ok(TimeSeriesResult {
@@ -776,7 +1152,8 @@ impl TimeSeriesStore {
 
         // Check if table exists first
         let read_txn = self.db.begin_read()?;
-        let table_exists = read_txn.open_table(TIMESERIES_TABLE).is_ok();
+        let table_exists = read_txn.open_table(TIMESERIES_TABLE).is_ok()
+            || read_txn.open_table(CHUNKS_TABLE).is_ok();
         drop(read_txn);
 
         if !table_exists {
@@ -793,7 +1170,7 @@ impl TimeSeriesStore {
                     TimeSeriesResult {
                         device_id: device_id.to_string(),
                         metric: metric.to_string(),
-                        points: Vec::new(),
+                        points: self.buffered_range(device_id, metric, start, end),
                         total_count: None,
                     },
                 );
@@ -805,14 +1182,15 @@ impl TimeSeriesStore {
         let db = Arc::clone(&self.db);
         let device_id = device_id.to_string();
         let metrics: Vec<String> = metrics.iter().map(|s| s.to_string()).collect();
-        
+
         let query_tasks: Vec<_> = metrics.iter().map(|metric| {
             let db = Arc::clone(&db);
+            let buffered = self.buffered_range(&device_id, metric, start, end);
             let device_id = device_id.clone();
             let metric = metric.clone();
             
             tokio::spawn(async move {
-                Self::query_single_metric(db, &device_id, &metric, start, end).await
+                Self::query_single_metric(db, &device_id, &metric, start, end, buffered).await
             })
         }).collect();
 
@@ -867,33 +1245,48 @@ impl TimeSeriesStore {
 
         // Cache miss - query from database
         let read_txn = self.db.begin_read()?;
+        let start_key = (device_id, metric, i64::MIN);
+        let end_key = (device_id, metric, i64::MAX);
 
-        // Handle case where table doesn't exist yet (no data has been written)
-        let table = match read_txn.open_table(TIMESERIES_TABLE) {
-            Ok(t) => t,
-            Err(redb::TableError::TableDoesNotExist(_)) => {
-                tracing::debug!(
-                    "query_latest: table 'timeseries' does not exist yet, returning None for device_id={}, metric={}",
-                    device_id,
-                    metric
-                );
-                return Ok(None);
-            }
+        // Latest head row (most recent timestamp)
+        let head_latest: Option<DataPoint> = match read_txn.open_table(TIMESERIES_TABLE) {
+            Ok(table) => table
+                .range(start_key..=end_key)?
+                .next_back()
+                .map(|result| -> Result<DataPoint, Error> {
+                    let (_key, value) = result?;
+                    Ok(serde_json::from_slice(value.value())?)
+                })
+                .transpose()?,
+            Err(redb::TableError::TableDoesNotExist(_)) => None,
             Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
         };
 
-        let start_key = (device_id, metric, i64::MIN);
-        let end_key = (device_id, metric, i64::MAX);
+        // Last point of the newest chunk
+        let chunk_latest: Option<DataPoint> = match read_txn.open_table(CHUNKS_TABLE) {
+            Ok(table) => match table.range(start_key..=end_key)?.next_back() {
+                Some(result) => {
+                    let (_key, value) = result?;
+                    decode_chunk(value.value())?.pop()
+                }
+                None => None,
+            },
+            Err(redb::TableError::TableDoesNotExist(_)) => None,
+            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+        };
 
-        // Get the latest data point (most recent timestamp)
-        let latest: Option<DataPoint> = table
-            .range(start_key..=end_key)?
-            .next_back()
-            .map(|result| -> Result<DataPoint, Error> {
-                let (_key, value) = result?;
-                Ok(serde_json::from_slice(value.value())?)
-            })
-            .transpose()?;
+        let latest = match (head_latest, chunk_latest) {
+            (Some(h), Some(c)) => Some(if c.timestamp > h.timestamp { c } else { h }),
+            (h, c) => h.or(c),
+        };
+        let buffered_latest = self
+            .ingest
+            .as_ref()
+            .and_then(|ingest| ingest.latest(device_id, metric));
+        let latest = match (latest, buffered_latest) {
+            (Some(s), Some(b)) => Some(if s.timestamp > b.timestamp { s } else { b }),
+            (s, b) => s.or(b),
+        };
 
         // Update cache with result
         if let Some(ref point) = latest {
@@ -917,13 +1310,59 @@ impl TimeSeriesStore {
         end: i64,
         bucket_size_secs: i64,
     ) -> Result<Vec<TimeSeriesBucket>, Error> {
+        if let Some(tier) =
+            self.plan_rollup_tier(device_id, metric, start, end, bucket_size_secs)?
+        {
+            // Rollups only cover whole tier buckets; partial buckets at the
+            // edges of the range are built from raw points
+            let inner_start = start.saturating_add(tier - 1).div_euclid(tier) * tier;
+            let inner_end = end.saturating_add(1).div_euclid(tier) * tier - 1;
+            if inner_start <= inner_end {
+                tracing::debug!(
+                    "query_aggregated: serving {}:{} from {}s rollups",
+                    device_id,
+                    metric,
+                    tier
+                );
+                let leading = if start < inner_start {
+                    self.query_range(device_id, metric, start, inner_start - 1)
+                        .await?
+                        .points
+                } else {
+                    Vec::new()
+                };
+                let mut buckets = read_rollups(
+                    &self.db,
+                    device_id,
+                    metric,
+                    tier,
+                    inner_start..=inner_end,
+                    bucket_size_secs,
+                    add_to_buckets(Vec::new(), &leading, bucket_size_secs),
+                )?;
+                // Buffered points are not in the rollups yet
+                let mut rest = self.buffered_range(device_id, metric, inner_start, inner_end);
+                if end > inner_end {
+                    rest.extend(
+                        self.query_range(device_id, metric, inner_end + 1, end)
+                            .await?
+                            .points,
+                    );
+                }
+                if !rest.is_empty() {
+                    buckets = add_to_buckets(buckets, &rest, bucket_size_secs);
+                }
+                return Ok(buckets);
+            }
+        }
+
         let result = self.query_range(device_id, metric, start, end).await?;
 
         let mut buckets: std::collections::HashMap<i64, TimeSeriesBucket> =
             std::collections::HashMap::new();
 
         for point in result.points {
-            let bucket_key = (point.timestamp / bucket_size_secs) * bucket_size_secs;
+            let bucket_key = point.timestamp.div_euclid(bucket_size_secs) * bucket_size_secs;
             let bucket_end = bucket_key + bucket_size_secs;
             buckets
                 .entry(bucket_key)
@@ -937,7 +1376,57 @@ impl TimeSeriesStore {
         Ok(bucket_list)
     }
 
+    /// Pick the coarsest rollup tier that can answer an aggregated query.
+    ///
+    /// A tier qualifies when its size divides the bucket size and it covers the
+    /// range: either its first bucket is at or before `start`, or there is no
+    /// raw data between `start` and its first bucket (e.g. raw data has expired,
+    /// or the series predates rollups).
+    fn plan_rollup_tier(
+        &self,
+        device_id: &str,
+        metric: &str,
+        start: i64,
+        end: i64,
+        bucket_size_secs: i64,
+    ) -> Result<Option<i64>, Error> {
+        let read_txn = self.db.begin_read()?;
+        let rollups = match read_txn.open_table(ROLLUPS_TABLE) {
+            Ok(t) => t,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(None),
+            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+        };
+
+        for &tier in self.rollup_tiers.iter().rev() {
+            if tier > bucket_size_secs || bucket_size_secs % tier != 0 {
+                continue;
+            }
+            let first_bucket = match rollups
+                .range((device_id, metric, tier, i64::MIN)..=(device_id, metric, tier, i64::MAX))?
+                .next()
+            {
+                Some(result) => result?.0.value().3,
+                None => continue,
+            };
+            if first_bucket <= start.div_euclid(tier) * tier
+                || !has_raw_points(
+                    &read_txn,
+                    device_id,
+                    metric,
+                    start,
+                    end.min(first_bucket - 1),
+                )?
+            {
+                return Ok(Some(tier));
+            }
+        }
+        Ok(None)
+    }
+
     /// Delete data points in a time range.
+    ///
+    /// The deleted points are taken out of the rollups as well; use
+    /// [`Self::delete_metric`] to drop a series entirely.
     pub async fn delete_range(
         &self,
         device_id: &str,
@@ -945,8 +1434,28 @@ impl TimeSeriesStore {
         start: i64,
         end: i64,
     ) -> Result<usize, Error> {
-        let write_txn = self.db.begin_write()?;
+        self.remove_range(device_id, metric, start, end, true).await
+    }
+
+    /// Delete raw points in a time range, optionally retracting them from the
+    /// rollups. Retention keeps the rollups so aggregates outlive raw data.
+    async fn remove_range(
+        &self,
+        device_id: &str,
+        metric: &str,
+        start: i64,
+        end: i64,
+        retract: bool,
+    ) -> Result<usize, Error> {
+        // Commit buffered points first so they cannot land after the delete
+        self.flush_buffered().await?;
+
+        let write_txn = begin_write(&self.db)?;
         let mut count = 0;
+        let mut delta = ChunkDelta::default();
+        // Removed numeric points by timestamp; head rows shadow chunked ones
+        let mut removed: BTreeMap<i64, DataPoint> = BTreeMap::new();
+        let retract = retract && !self.rollup_tiers.is_empty();
 
         {
             let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
@@ -957,11 +1466,16 @@ impl TimeSeriesStore {
             let mut keys_to_delete: Vec<(String, String, i64)> = Vec::new();
             let mut range = table.range(start_key..=end_key)?;
             for result in range.by_ref() {
-                let (key_ref, _val_ref) = result?;
+                let (key_ref, val_ref) = result?;
                 let did: &str = key_ref.value().0;
                 let met: &str = key_ref.value().1;
                 let ts: i64 = key_ref.value().2;
                 keys_to_delete.push((did.to_string(), met.to_string(), ts));
+                if retract {
+                    if let Ok(point) = serde_json::from_slice::<DataPoint>(val_ref.value()) {
+                        removed.insert(ts, point);
+                    }
+                }
             }
             drop(range);
 
@@ -971,50 +1485,149 @@ impl TimeSeriesStore {
             }
         }
 
-        write_txn.commit()?;
-        Ok(count)
-    }
+        {
+            // Chunks are keyed by their last timestamp, so every chunk that can
+            // overlap the range has a key >= start
+            let mut chunks = write_txn.open_table(CHUNKS_TABLE)?;
+            let mut affected: Vec<(i64, Vec<u8>)> = Vec::new();
+            for result in
+                chunks.range((device_id, metric, start)..=(device_id, metric, i64::MAX))?
+            {
+                let (key, value) = result?;
+                let header = read_chunk_header(value.value())?;
+                if header.first_ts > end {
+                    break;
+                }
+                affected.push((key.value().2, value.value().to_vec()));
+            }
 
-    /// Flush all data to disk (redb auto-flushes).
-    pub fn flush(&self) -> Result<(), Error> {
-        // redb auto-manages, no manual flush needed
-        Ok(())
-    }
+            for (last_ts, data) in affected {
+                let header = read_chunk_header(&data)?;
+                chunks.remove((device_id, metric, last_ts))?;
+                delta.remove_chunk(&data)?;
 
-    /// Get all metrics for a device.
-    pub async fn list_metrics(&self, device_id: &str) -> Result<Vec<String>, Error> {
-        let read_txn = self.db.begin_read()?;
+                if header.first_ts >= start && header.last_ts <= end && !retract {
+                    count += header.count;
+                    continue;
+                }
 
-        // Handle case where table doesn't exist yet (no data has been written)
-        let table = match read_txn.open_table(TIMESERIES_TABLE) {
-            Ok(t) => t,
-            Err(redb::TableError::TableDoesNotExist(_)) => {
-                tracing::debug!(
-                    "list_metrics: table 'timeseries' does not exist yet, returning empty list for device_id={}",
-                    device_id
-                );
-                return Ok(Vec::new());
+                // Keep the points outside the range
+                let (deleted, kept): (Vec<DataPoint>, Vec<DataPoint>) = decode_chunk(&data)?
+                    .into_iter()
+                    .partition(|p| p.timestamp >= start && p.timestamp <= end);
+                count += deleted.len();
+                if retract {
+                    for point in deleted {
+                        removed.entry(point.timestamp).or_insert(point);
+                    }
+                }
+                if let Some(last) = kept.last() {
+                    let encoded = encode_chunk(&kept);
+                    chunks.insert((device_id, metric, last.timestamp), encoded.as_slice())?;
+                    delta.add_chunk(&encoded)?;
+                }
             }
-            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
-        };
+        }
+        delta.persist(&write_txn)?;
+
+        if retract {
+            let mut stale = StaleRollups::new();
+            for point in removed.values() {
+                retract_rollups(
+                    &write_txn,
+                    &self.rollup_tiers,
+                    device_id,
+                    metric,
+                    point,
+                    &mut stale,
+                )?;
+            }
+            rebuild_rollups(&write_txn, &stale)?;
+        }
+
+        write_txn.commit()?;
+        self.stats.write().await.apply_chunk_delta(&delta);
+        Ok(count)
+    }
+
+    /// Commit buffered writes to disk.
+    ///
+    /// Unlike [`Self::flush_buffered`] this does not update statistics or seal
+    /// chunks; the next group commit picks that up.
+    pub fn flush(&self) -> Result<(), Error> {
+        self.commit_buffered()?;
+        Ok(())
+    }
+
+    /// Get all metrics for a device.
+    pub async fn list_metrics(&self, device_id: &str) -> Result<Vec<String>, Error> {
+        let read_txn = self.db.begin_read()?;
 
         let start_key = (device_id, "", i64::MIN);
         let end_key = (device_id, "\u{FF}", i64::MAX);
 
         let mut metrics = std::collections::HashSet::new();
-        for result in table.range(start_key..=end_key)? {
-            let (key, _value) = result?;
-            let (_, metric, _) = key.value();
-            metrics.insert(metric.to_string());
+        for definition in [TIMESERIES_TABLE, CHUNKS_TABLE] {
+            // Handle case where table doesn't exist yet (no data has been written)
+            let table = match read_txn.open_table(definition) {
+                Ok(t) => t,
+                Err(redb::TableError::TableDoesNotExist(_)) => continue,
+                Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+            };
+            for result in table.range(start_key..=end_key)? {
+                let (key, _value) = result?;
+                let (_, metric, _) = key.value();
+                metrics.insert(metric.to_string());
+            }
+        }
+        // Series whose raw data has expired still have rollups
+        match read_txn.open_table(ROLLUPS_TABLE) {
+            Ok(table) => {
+                let range =
+                    (device_id, "", i64::MIN, i64::MIN)..=(device_id, "\u{FF}", i64::MAX, i64::MAX);
+                for result in table.range(range)? {
+                    let (key, _value) = result?;
+                    metrics.insert(key.value().1.to_string());
+                }
+            }
+            Err(redb::TableError::TableDoesNotExist(_)) => {}
+            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+        }
+        if let Some(ingest) = &self.ingest {
+            metrics.extend(ingest.metrics(device_id));
         }
 
         Ok(metrics.into_iter().collect())
     }
 
-    /// Delete all data for a specific metric.
+    /// Delete all data for a specific metric, including its rollups.
     pub async fn delete_metric(&self, device_id: &str, metric: &str) -> Result<usize, Error> {
-        self.delete_range(device_id, metric, i64::MIN, i64::MAX)
-            .await
+        let removed = self
+            .remove_range(device_id, metric, i64::MIN, i64::MAX, false)
+            .await?;
+        delete_rollups(&self.db, device_id, metric, None, i64::MAX)?;
+        self.latest_cache
+            .remove(&(device_id.to_string(), metric.to_string()));
+        Ok(removed)
+    }
+
+    /// Track the last update and point count of a metric.
+    fn record_metric_info(&self, device_id: &str, metric: &str, points: &[DataPoint]) {
+        // DashMap entry API is lock-free
+        let metric_key = format!("{}:{}", device_id, metric);
+        let now = Utc::now().timestamp();
+        let last_ts = points.last().map(|p| p.timestamp).unwrap_or(now);
+
+        self.metrics_info
+            .entry(metric_key)
+            .and_modify(|entry| {
+                entry.last_update = last_ts;
+                entry.point_count += points.len() as u64;
+            })
+            .or_insert_with(|| MetricInfo {
+                last_update: last_ts,
+                point_count: points.len() as u64,
+            });
     }
 
     /// Write multiple batch requests concurrently.
@@ -1022,9 +1635,41 @@ impl TimeSeriesStore {
         &self,
         requests: Vec<BatchWriteRequest>,
     ) -> Result<usize, Error> {
+        if let Some(ingest) = &self.ingest {
+            self.ensure_flusher(ingest);
+            let start = Instant::now();
+            let mut written = 0;
+            for request in requests {
+                for (metric, points) in request.metrics {
+                    self.record_metric_info(&request.device_id, &metric, &points);
+                    let last = points.last().cloned();
+                    written += ingest.push(&request.device_id, &metric, points).await?;
+                    if let Some(last) = last {
+                        self.update_cache(&request.device_id, &metric, last).await;
+                    }
+                }
+            }
+            let mut stats = self.stats.write().await;
+            stats.write_count += 1;
+            stats.total_write_ns += start.elapsed().as_nanos() as u64;
+            return Ok(written);
+        }
+
         let mut handles = Vec::new();
+        let mut sealed_series: std::collections::HashMap<(String, String), i64> =
+            std::collections::HashMap::new();
 
         for request in requests {
+            for (metric, points) in &request.metrics {
+                self.record_metric_info(&request.device_id, metric, points);
+                if let Some(max_ts) = points.iter().map(|p| p.timestamp).max() {
+                    let entry = sealed_series
+                        .entry((request.device_id.clone(), metric.clone()))
+                        .or_insert(max_ts);
+                    *entry = (*entry).max(max_ts);
+                }
+            }
+
             let db: Arc<Database> = Arc::clone(&self.db);
             let semaphore: Arc<Semaphore> = Arc::clone(&self.write_semaphore);
             // DashMap implements Clone (internally uses Arc), so we can clone it for the spawned task
@@ -1032,6 +1677,7 @@ impl TimeSeriesStore {
             // RwLock doesn't implement Clone, wrap in Arc for sharing
             let stats = Arc::clone(&self.stats);
             let max_cache_size = self.max_cache_size;
+            let rollup_tiers = Arc::clone(&self.rollup_tiers);
 
             let device_id = request.device_id.clone();
             let _device_type = request.device_type.clone().unwrap_or_default();
@@ -1045,19 +1691,27 @@ impl TimeSeriesStore {
                 let start = Instant::now();
                 let mut written = 0;
 
-                let write_txn = db.begin_write()?;
+                let write_txn = begin_write(&db)?;
+                let mut stale = StaleRollups::new();
                 {
                     let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
 
                     for (metric, points) in &metrics {
                         for point in points {
-                            let key = (&*device_id, &**metric, point.timestamp);
-                            let value = serde_json::to_vec(point)?;
-                            table.insert(key, &*value)?;
+                            insert_point(
+                                &write_txn,
+                                &mut table,
+                                &rollup_tiers,
+                                &device_id,
+                                metric,
+                                point,
+                                &mut stale,
+                            )?;
                             written += 1;
                         }
                     }
                 }
+                rebuild_rollups(&write_txn, &stale)?;
                 write_txn.commit()?;
 
                 // Update cache for latest values - DashMap is lock-free
@@ -1106,47 +1760,31 @@ impl TimeSeriesStore {
             results.push(handle.await??);
         }
 
+        for ((device_id, metric), max_ts) in sealed_series {
+            self.seal_if_bucket_closed(&device_id, &metric, max_ts)
+                .await;
+        }
+
         Ok(results.into_iter().sum())
     }
 
     /// Apply retention policy and clean up old data.
     pub async fn apply_retention(&self) -> Result<RetentionPolicyCleanupResult, Error> {
         // DashMap and RwLock access - no async needed for DashMap
+        self.flush_buffered().await?;
         let policy = self.retention_policy.read().await;
         // metrics_info is now DashMap, iterate directly when needed
 
         let mut total_removed: u64 = 0;
         let mut metrics_cleaned: std::collections::HashSet<String> = std::collections::HashSet::new();
 
-        let read_txn = self.db.begin_read()?;
-
-        // Handle case where table doesn't exist yet (no data has been written)
-        let table = match read_txn.open_table(TIMESERIES_TABLE) {
-            Ok(t) => t,
-            Err(redb::TableError::TableDoesNotExist(_)) => {
-                tracing::debug!(
-                    "apply_retention: table 'timeseries' does not exist yet, returning empty result"
-                );
-                return Ok(RetentionPolicyCleanupResult {
-                    points_removed: 0,
-                    metrics_cleaned: Vec::new(),
-                });
-            }
-            Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
-        };
-
         // Collect all (device_id, metric) pairs
-        let mut metric_pairs: std::collections::HashSet<(String, String)> = std::collections::HashSet::new();
-        let start_key = ("", "", i64::MIN);
-        let end_key = ("\u{FF}", "\u{FF}", i64::MAX);
-
-        for result in table.range(start_key..=end_key)? {
-            let (key, _) = result?;
-            let (device_id, metric, _) = key.value();
-            metric_pairs.insert((device_id.to_string(), metric.to_string()));
-        }
-        drop(read_txn);
-        drop(table);
+        let mut metric_pairs: std::collections::HashSet<(String, String)> =
+            list_series(&self.db, TIMESERIES_TABLE)?
+                .into_iter()
+                .collect();
+        metric_pairs.extend(list_series(&self.db, CHUNKS_TABLE)?);
+        let rollup_pairs = list_rollup_series(&self.db)?;
 
         let now = Utc::now().timestamp();
 
@@ -1159,7 +1797,7 @@ impl TimeSeriesStore {
             if let Some(cutoff) = policy.cutoff_timestamp(device_type, metric) {
                 if cutoff < now {
                     let removed = self
-                        .delete_range(device_id, metric, i64::MIN, cutoff)
+                        .remove_range(device_id, metric, i64::MIN, cutoff, false)
                         .await?;
                     if removed > 0 {
                         total_removed += removed as u64;
@@ -1169,6 +1807,17 @@ impl TimeSeriesStore {
             }
         }
 
+        // Rollup tiers expire on their own schedule
+        let mut rollups_removed: u64 = 0;
+        for &tier in self.rollup_tiers.iter() {
+            let Some(cutoff) = policy.rollup_cutoff_timestamp(tier) else {
+                continue;
+            };
+            for (device_id, metric) in &rollup_pairs {
+                rollups_removed += delete_rollups(&self.db, device_id, metric, Some(tier), cutoff)?;
+            }
+        }
+
         // Update stats
         let mut stats = self.stats.write().await;
         stats.cleanup_points_removed += total_removed;
@@ -1177,6 +1826,7 @@ impl TimeSeriesStore {
         Ok(RetentionPolicyCleanupResult {
             points_removed: total_removed,
             metrics_cleaned: metrics_cleaned.into_iter().collect(),
+            rollups_removed,
         })
     }
 }
@@ -1188,6 +1838,33 @@ pub struct RetentionPolicyCleanupResult {
     pub points_removed: u64,
     /// List of metrics that were cleaned
     pub metrics_cleaned: Vec<String>,
+    /// Number of expired rollup buckets removed
+    pub rollups_removed: u64,
+}
+
+/// Result of converting head rows into compressed chunks.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ChunkMigrationResult {
+    /// Number of series converted
+    pub series: usize,
+    /// Number of rows moved into chunks
+    pub points_migrated: u64,
+    /// Size of all chunked data as JSON rows
+    pub chunk_raw_bytes: u64,
+    /// Size of all chunks on disk
+    pub chunk_stored_bytes: u64,
+}
+
+/// On-disk layout for newly written data.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeSeriesLayout {
+    /// One JSON row per point.
+    #[default]
+    Raw,
+    /// Points land in the head table and are sealed into compressed chunks,
+    /// one per series and `chunk_secs` bucket, once the bucket is closed.
+    Chunked { chunk_secs: i64 },
 }
 
 /// Configuration for time series store.
@@ -1201,6 +1878,12 @@ pub struct TimeSeriesConfig {
     pub max_cache_size: usize,
     /// Maximum concurrent writes
     pub max_concurrent_writes: usize,
+    /// Layout for new data (reads always cover both layouts)
+    pub layout: TimeSeriesLayout,
+    /// Rollup tier sizes in seconds (empty disables rollups)
+    pub rollup_tiers: Vec<i64>,
+    /// Group commit settings (None, the default, commits every write on its own)
+    pub group_commit: Option<GroupCommitConfig>,
 }
 
 impl Default for TimeSeriesConfig {
@@ -1210,8 +1893,591 @@ impl Default for TimeSeriesConfig {
             cache_ttl: Duration::from_secs(60), // 1 minute
             max_cache_size: 1000,
             max_concurrent_writes: 10,
+            layout: TimeSeriesLayout::default(),
+            rollup_tiers: vec![60, 3600, 86400],
+            group_commit: None,
+        }
+    }
+}
+
+impl TimeSeriesConfig {
+    /// Whether two configurations open a store the same way, ignoring the
+    /// retention policy.
+    fn same_setup(&self, other: &Self) -> bool {
+        self.cache_ttl == other.cache_ttl
+            && self.max_cache_size == other.max_cache_size
+            && self.max_concurrent_writes == other.max_concurrent_writes
+            && self.layout == other.layout
+            && normalize_tiers(self.rollup_tiers.clone())
+                == normalize_tiers(other.rollup_tiers.clone())
+            && self.group_commit == other.group_commit
+    }
+}
+
+/// Insert a committed group into the head table, updating rollups.
+fn write_group(db: &Database, rollup_tiers: &[i64], group: &SeriesPoints) -> Result<(), Error> {
+    let write_txn = begin_write(db)?;
+    let mut stale = StaleRollups::new();
+    {
+        let mut table = write_txn.open_table(TIMESERIES_TABLE)?;
+        for ((device_id, metric), points) in group {
+            for point in points.values() {
+                insert_point(
+                    &write_txn,
+                    &mut table,
+                    rollup_tiers,
+                    device_id,
+                    metric,
+                    point,
+                    &mut stale,
+                )?;
+            }
+        }
+    }
+    rebuild_rollups(&write_txn, &stale)?;
+    write_txn.commit()?;
+    Ok(())
+}
+
+/// Merge buffered points into stored ones; buffered points win on equal timestamps.
+fn merge_buffered(stored: Vec<DataPoint>, buffered: Vec<DataPoint>) -> Vec<DataPoint> {
+    if buffered.is_empty() {
+        return stored;
+    }
+    let mut merged: BTreeMap<i64, DataPoint> =
+        stored.into_iter().map(|p| (p.timestamp, p)).collect();
+    for point in buffered {
+        merged.insert(point.timestamp, point);
+    }
+    merged.into_values().collect()
+}
+
+/// Add points to aggregated buckets, creating buckets as needed.
+fn add_to_buckets(
+    buckets: Vec<TimeSeriesBucket>,
+    points: &[DataPoint],
+    bucket_size_secs: i64,
+) -> Vec<TimeSeriesBucket> {
+    let mut by_start: BTreeMap<i64, TimeSeriesBucket> =
+        buckets.into_iter().map(|b| (b.start, b)).collect();
+    for point in points {
+        let bucket_key = point.timestamp.div_euclid(bucket_size_secs) * bucket_size_secs;
+        by_start
+            .entry(bucket_key)
+            .or_insert_with(|| TimeSeriesBucket::new(bucket_key, bucket_key + bucket_size_secs))
+            .add(&point.value);
+    }
+    by_start.into_values().collect()
+}
+
+/// Sort tiers ascending and drop invalid or duplicate sizes.
+fn normalize_tiers(mut tiers: Vec<i64>) -> Vec<i64> {
+    tiers.retain(|&t| t > 0);
+    tiers.sort_unstable();
+    tiers.dedup();
+    tiers
+}
+
+/// Fold a numeric point into every rollup tier, inside the write transaction.
+fn update_rollups(
+    write_txn: &redb::WriteTransaction,
+    tiers: &[i64],
+    device_id: &str,
+    metric: &str,
+    point: &DataPoint,
+) -> Result<(), Error> {
+    let Some(value) = point.as_f64() else {
+        return Ok(());
+    };
+    if tiers.is_empty() {
+        return Ok(());
+    }
+
+    let mut table = write_txn.open_table(ROLLUPS_TABLE)?;
+    for &tier in tiers {
+        let key = (
+            device_id,
+            metric,
+            tier,
+            point.timestamp.div_euclid(tier) * tier,
+        );
+        let existing: Option<RollupBucket> = table
+            .get(key)?
+            .map(|v| bincode::deserialize(v.value()))
+            .transpose()?;
+        let rollup = match existing {
+            Some(mut rollup) => {
+                rollup.add(point.timestamp, value);
+                rollup
+            }
+            None => RollupBucket::new(point.timestamp, value),
+        };
+        table.insert(key, bincode::serialize(&rollup)?.as_slice())?;
+    }
+    Ok(())
+}
+
+/// Insert a point into the head table and fold it into the rollups.
+///
+/// A point replacing a stored one, in the head or in a sealed chunk, first
+/// takes the old value back out of the rollups so they never count both.
+/// Buckets that need a rebuild are collected in `stale` for [`rebuild_rollups`].
+fn insert_point(
+    write_txn: &redb::WriteTransaction,
+    head: &mut redb::Table<(&'static str, &'static str, i64), &'static [u8]>,
+    tiers: &[i64],
+    device_id: &str,
+    metric: &str,
+    point: &DataPoint,
+    stale: &mut StaleRollups,
+) -> Result<(), Error> {
+    let value = serde_json::to_vec(point)?;
+    let replaced: Option<DataPoint> = head
+        .insert((device_id, metric, point.timestamp), value.as_slice())?
+        .and_then(|old| serde_json::from_slice(old.value()).ok());
+    if tiers.is_empty() {
+        return Ok(());
+    }
+
+    let replaced = match replaced {
+        Some(old) => Some(old),
+        None => chunked_point(write_txn, device_id, metric, point.timestamp)?,
+    };
+    if let Some(old) = replaced {
+        retract_rollups(write_txn, tiers, device_id, metric, &old, stale)?;
+    }
+    update_rollups(write_txn, tiers, device_id, metric, point)
+}
+
+/// The point a sealed chunk holds at `timestamp`, if any.
+fn chunked_point(
+    write_txn: &redb::WriteTransaction,
+    device_id: &str,
+    metric: &str,
+    timestamp: i64,
+) -> Result<Option<DataPoint>, Error> {
+    let chunks = write_txn.open_table(CHUNKS_TABLE)?;
+    let mut range = chunks.range((device_id, metric, timestamp)..=(device_id, metric, i64::MAX))?;
+    let Some(result) = range.next() else {
+        return Ok(None);
+    };
+    let (_key, value) = result?;
+    if read_chunk_header(value.value())?.first_ts > timestamp {
+        return Ok(None);
+    }
+    let point = decode_chunk(value.value())?
+        .into_iter()
+        .find(|p| p.timestamp == timestamp);
+    Ok(point)
+}
+
+/// Take a replaced or deleted point back out of every rollup tier.
+fn retract_rollups(
+    write_txn: &redb::WriteTransaction,
+    tiers: &[i64],
+    device_id: &str,
+    metric: &str,
+    point: &DataPoint,
+    stale: &mut StaleRollups,
+) -> Result<(), Error> {
+    let Some(value) = point.as_f64() else {
+        return Ok(());
+    };
+    if tiers.is_empty() {
+        return Ok(());
+    }
+
+    let mut table = write_txn.open_table(ROLLUPS_TABLE)?;
+    for &tier in tiers {
+        let bucket = point.timestamp.div_euclid(tier) * tier;
+        let key = (device_id, metric, tier, bucket);
+        let existing: Option<RollupBucket> = table
+            .get(key)?
+            .map(|v| bincode::deserialize(v.value()))
+            .transpose()?;
+        // Buckets past their retention have nothing left to adjust
+        let Some(mut rollup) = existing else {
+            continue;
+        };
+        if rollup.remove(point.timestamp, value) {
+            table.insert(key, bincode::serialize(&rollup)?.as_slice())?;
+        } else {
+            stale.insert((device_id.to_string(), metric.to_string(), tier, bucket));
+        }
+    }
+    Ok(())
+}
+
+/// Recompute stale rollup buckets from the raw points still stored.
+///
+/// Must run after the head table handle of the transaction is dropped.
+fn rebuild_rollups(write_txn: &redb::WriteTransaction, stale: &StaleRollups) -> Result<(), Error> {
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    let chunks = write_txn.open_table(CHUNKS_TABLE)?;
+    let head = write_txn.open_table(TIMESERIES_TABLE)?;
+    let mut table = write_txn.open_table(ROLLUPS_TABLE)?;
+    for (device_id, metric, tier, bucket) in stale {
+        let points = collect_series(
+            Some(&chunks),
+            Some(&head),
+            device_id,
+            metric,
+            *bucket,
+            bucket + tier - 1,
+        )?;
+        let mut rollup: Option<RollupBucket> = None;
+        for point in &points {
+            let Some(value) = point.as_f64() else {
+                continue;
+            };
+            match rollup.as_mut() {
+                Some(rollup) => rollup.add(point.timestamp, value),
+                None => rollup = Some(RollupBucket::new(point.timestamp, value)),
+            }
+        }
+
+        let key = (device_id.as_str(), metric.as_str(), *tier, *bucket);
+        match rollup {
+            Some(rollup) => {
+                table.insert(key, bincode::serialize(&rollup)?.as_slice())?;
+            }
+            None => {
+                table.remove(key)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Build aggregated buckets from a rollup tier, merging into `earlier`
+/// buckets that hold points older than the range.
+fn read_rollups(
+    db: &Database,
+    device_id: &str,
+    metric: &str,
+    tier: i64,
+    range: std::ops::RangeInclusive<i64>,
+    bucket_size_secs: i64,
+    earlier: Vec<TimeSeriesBucket>,
+) -> Result<Vec<TimeSeriesBucket>, Error> {
+    let read_txn = db.begin_read()?;
+    let table = read_txn.open_table(ROLLUPS_TABLE)?;
+
+    let first = range.start().div_euclid(tier) * tier;
+    let end = *range.end();
+    let mut buckets: BTreeMap<i64, (TimeSeriesBucket, i64)> = earlier
+        .into_iter()
+        .map(|bucket| (bucket.start, (bucket, i64::MIN)))
+        .collect();
+    for result in table.range((device_id, metric, tier, first)..=(device_id, metric, tier, end))? {
+        let (key, value) = result?;
+        let rollup: RollupBucket = bincode::deserialize(value.value())?;
+        let bucket_key = key.value().3.div_euclid(bucket_size_secs) * bucket_size_secs;
+        let (bucket, last_ts) = buckets.entry(bucket_key).or_insert_with(|| {
+            (
+                TimeSeriesBucket::new(bucket_key, bucket_key + bucket_size_secs),
+                i64::MIN,
+            )
+        });
+        bucket.merge_rollup(&rollup, last_ts);
+    }
+
+    Ok(buckets.into_values().map(|(bucket, _)| bucket).collect())
+}
+
+/// Whether a series has raw (head or chunked) points in `[from, to]`.
+fn has_raw_points(
+    read_txn: &redb::ReadTransaction,
+    device_id: &str,
+    metric: &str,
+    from: i64,
+    to: i64,
+) -> Result<bool, Error> {
+    if from > to {
+        return Ok(false);
+    }
+    match read_txn.open_table(TIMESERIES_TABLE) {
+        Ok(table) => {
+            if table
+                .range((device_id, metric, from)..=(device_id, metric, to))?
+                .next()
+                .is_some()
+            {
+                return Ok(true);
+            }
+        }
+        Err(redb::TableError::TableDoesNotExist(_)) => {}
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    }
+    match read_txn.open_table(CHUNKS_TABLE) {
+        Ok(chunks) => {
+            if let Some(result) = chunks
+                .range((device_id, metric, from)..=(device_id, metric, i64::MAX))?
+                .next()
+            {
+                let (_key, value) = result?;
+                return Ok(read_chunk_header(value.value())?.first_ts <= to);
+            }
+        }
+        Err(redb::TableError::TableDoesNotExist(_)) => {}
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    }
+    Ok(false)
+}
+
+/// Delete rollup buckets starting before `before`, for one tier or all tiers.
+fn delete_rollups(
+    db: &Database,
+    device_id: &str,
+    metric: &str,
+    tier: Option<i64>,
+    before: i64,
+) -> Result<u64, Error> {
+    let write_txn = begin_write(db)?;
+    let mut removed = 0;
+    {
+        let mut table = write_txn.open_table(ROLLUPS_TABLE)?;
+        let range = match tier {
+            Some(t) => (device_id, metric, t, i64::MIN)..=(device_id, metric, t, before - 1),
+            None => {
+                (device_id, metric, i64::MIN, i64::MIN)..=(device_id, metric, i64::MAX, i64::MAX)
+            }
+        };
+        let mut keys = Vec::new();
+        for result in table.range(range)? {
+            let (key, _) = result?;
+            let (_, _, t, bucket) = key.value();
+            if bucket < before {
+                keys.push((t, bucket));
+            }
+        }
+        for (t, bucket) in keys {
+            table.remove((device_id, metric, t, bucket))?;
+            removed += 1;
+        }
+    }
+    write_txn.commit()?;
+    Ok(removed)
+}
+
+/// List the distinct (device_id, metric) pairs that have rollups.
+fn list_rollup_series(db: &Database) -> Result<Vec<(String, String)>, Error> {
+    let read_txn = db.begin_read()?;
+    let table = match read_txn.open_table(ROLLUPS_TABLE) {
+        Ok(t) => t,
+        Err(redb::TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    };
+
+    let mut pairs = std::collections::BTreeSet::new();
+    for result in table.iter()? {
+        let (key, _) = result?;
+        let (device_id, metric, _, _) = key.value();
+        pairs.insert((device_id.to_string(), metric.to_string()));
+    }
+    Ok(pairs.into_iter().collect())
+}
+
+/// Read the points of a series in `[start, end]`, merging chunks and head rows.
+fn read_series(
+    db: &Database,
+    device_id: &str,
+    metric: &str,
+    start: i64,
+    end: i64,
+) -> Result<Vec<DataPoint>, Error> {
+    let read_txn = db.begin_read()?;
+    let chunks = match read_txn.open_table(CHUNKS_TABLE) {
+        Ok(table) => Some(table),
+        Err(redb::TableError::TableDoesNotExist(_)) => None,
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    };
+    let head = match read_txn.open_table(TIMESERIES_TABLE) {
+        Ok(table) => Some(table),
+        Err(redb::TableError::TableDoesNotExist(_)) => None,
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    };
+    collect_series(
+        chunks.as_ref(),
+        head.as_ref(),
+        device_id,
+        metric,
+        start,
+        end,
+    )
+}
+
+/// Merge the chunked and head points of a series in `[start, end]`.
+fn collect_series(
+    chunks: Option<&impl ReadableTable<(&'static str, &'static str, i64), &'static [u8]>>,
+    head: Option<&impl ReadableTable<(&'static str, &'static str, i64), &'static [u8]>>,
+    device_id: &str,
+    metric: &str,
+    start: i64,
+    end: i64,
+) -> Result<Vec<DataPoint>, Error> {
+    let mut merged: BTreeMap<i64, DataPoint> = BTreeMap::new();
+
+    if let Some(chunks) = chunks {
+        for result in chunks.range((device_id, metric, start)..=(device_id, metric, i64::MAX))? {
+            let (_key, value) = result?;
+            if read_chunk_header(value.value())?.first_ts > end {
+                break;
+            }
+            for point in decode_chunk(value.value())? {
+                if point.timestamp >= start && point.timestamp <= end {
+                    merged.insert(point.timestamp, point);
+                }
+            }
+        }
+    }
+
+    if let Some(table) = head {
+        for result in table.range((device_id, metric, start)..=(device_id, metric, end))? {
+            let (_key, value) = result?;
+            match serde_json::from_slice::<DataPoint>(value.value()) {
+                // Head rows are newer writes and win over chunked points
+                Ok(point) => {
+                    merged.insert(point.timestamp, point);
+                }
+                Err(e) => {
+                    tracing::warn!("read_series: failed to deserialize data point: {}", e);
+                }
+            }
+        }
+    }
+
+    Ok(merged.into_values().collect())
+}
+
+/// Move the head rows of a series in `[from, to)` into chunks, merging with
+/// chunks already covering the same buckets.
+fn seal_series(
+    db: &Database,
+    device_id: &str,
+    metric: &str,
+    from: i64,
+    to: i64,
+    chunk_secs: i64,
+) -> Result<ChunkDelta, Error> {
+    let mut delta = ChunkDelta::default();
+    let write_txn = begin_write(db)?;
+    {
+        let mut head = write_txn.open_table(TIMESERIES_TABLE)?;
+        let mut buckets: BTreeMap<i64, BTreeMap<i64, DataPoint>> = BTreeMap::new();
+        for result in head.range((device_id, metric, from)..(device_id, metric, to))? {
+            let (key, value) = result?;
+            let ts = key.value().2;
+            match serde_json::from_slice::<DataPoint>(value.value()) {
+                Ok(point) => {
+                    buckets
+                        .entry(ts.div_euclid(chunk_secs) * chunk_secs)
+                        .or_default()
+                        .insert(ts, point);
+                }
+                Err(e) => tracing::warn!("seal_series: skipping unreadable row at {}: {}", ts, e),
+            }
+        }
+        if buckets.is_empty() {
+            return Ok(delta);
+        }
+
+        let mut chunks = write_txn.open_table(CHUNKS_TABLE)?;
+        for (bucket, rows) in buckets {
+            let bucket_end = bucket.saturating_add(chunk_secs - 1);
+
+            // Merge any chunk already holding points of this bucket
+            let mut existing: Vec<(i64, Vec<u8>)> = Vec::new();
+            for result in
+                chunks.range((device_id, metric, bucket)..=(device_id, metric, bucket_end))?
+            {
+                let (key, value) = result?;
+                existing.push((key.value().2, value.value().to_vec()));
+            }
+            let mut points: BTreeMap<i64, DataPoint> = BTreeMap::new();
+            for (last_ts, data) in existing {
+                for point in decode_chunk(&data)? {
+                    points.insert(point.timestamp, point);
+                }
+                chunks.remove((device_id, metric, last_ts))?;
+                delta.remove_chunk(&data)?;
+            }
+
+            for (ts, point) in rows {
+                head.remove((device_id, metric, ts))?;
+                points.insert(ts, point);
+                delta.points_sealed += 1;
+            }
+
+            let points: Vec<DataPoint> = points.into_values().collect();
+            let last_ts = points.last().map(|p| p.timestamp).unwrap_or(bucket);
+            let encoded = encode_chunk(&points);
+            chunks.insert((device_id, metric, last_ts), encoded.as_slice())?;
+            delta.add_chunk(&encoded)?;
         }
     }
+    delta.persist(&write_txn)?;
+    write_txn.commit()?;
+    Ok(delta)
+}
+
+/// List the distinct (device_id, metric) pairs in a series table.
+fn list_series(
+    db: &Database,
+    definition: TableDefinition<(&str, &str, i64), &[u8]>,
+) -> Result<Vec<(String, String)>, Error> {
+    let read_txn = db.begin_read()?;
+    let table = match read_txn.open_table(definition) {
+        Ok(t) => t,
+        Err(redb::TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    };
+
+    let mut pairs = std::collections::BTreeSet::new();
+    for result in table.iter()? {
+        let (key, _) = result?;
+        let (device_id, metric, _) = key.value();
+        pairs.insert((device_id.to_string(), metric.to_string()));
+    }
+    Ok(pairs.into_iter().collect())
+}
+
+/// Seal every head bucket older than the one containing `now`.
+///
+/// Run when a chunked store is opened, so buckets left open by a restart are
+/// sealed even for series that never report again. On a database written with
+/// the raw layout this converts all existing rows.
+fn seal_stale_buckets(
+    db: &Database,
+    chunk_secs: i64,
+    now: i64,
+    stats: &mut PerformanceStats,
+) -> Result<(), Error> {
+    let current = now.div_euclid(chunk_secs) * chunk_secs;
+    for (device_id, metric) in list_series(db, TIMESERIES_TABLE)? {
+        let delta = seal_series(db, &device_id, &metric, i64::MIN, current, chunk_secs)?;
+        stats.apply_chunk_delta(&delta);
+    }
+    Ok(())
+}
+
+/// Load chunk storage totals from the meta table.
+fn load_chunk_totals(db: &Database, stats: &mut PerformanceStats) -> Result<(), Error> {
+    let read_txn = db.begin_read()?;
+    let meta = match read_txn.open_table(TIMESERIES_META_TABLE) {
+        Ok(t) => t,
+        Err(redb::TableError::TableDoesNotExist(_)) => return Ok(()),
+        Err(e) => return Err(Error::Storage(format!("Failed to open table: {}", e))),
+    };
+    let get =
+        |key: &str| -> Result<u64, Error> { Ok(meta.get(key)?.map(|v| v.value()).unwrap_or(0)) };
+    stats.chunk_count = get(META_CHUNK_COUNT)?;
+    stats.chunk_raw_bytes = get(META_CHUNK_RAW_BYTES)?;
+    stats.chunk_stored_bytes = get(META_CHUNK_STORED_BYTES)?;
+    Ok(())
 }
 
 #[cfg(test)]
@@ -1340,4 +2606,483 @@ mod tests {
         assert_eq!(first.end, 1100);
         assert_eq!(first.count, 10);
     }
+
+    fn chunked_store(layout: TimeSeriesLayout) -> Arc<TimeSeriesStore> {
+        let path = std::env::temp_dir().join(format!("ts_test_{}.redb", uuid::Uuid::new_v4()));
+        TimeSeriesStore::with_config(
+            path,
+            TimeSeriesConfig {
+                layout,
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_reopen_with_different_config_fails() {
+        let path = std::env::temp_dir().join(format!("ts_test_{}.redb", uuid::Uuid::new_v4()));
+        let _store = TimeSeriesStore::with_config(
+            &path,
+            TimeSeriesConfig {
+                layout: TimeSeriesLayout::Chunked { chunk_secs: 100 },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert!(TimeSeriesStore::open(&path).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_chunked_layout_seals_closed_buckets() {
+        let store = chunked_store(TimeSeriesLayout::Chunked { chunk_secs: 100 });
+
+        // 3 buckets of 10s readings
+        for i in 0..30 {
+            let point = DataPoint::new(1000 + i * 10, 20.0 + (i % 4) as f64 * 0.5);
+            store.write("device1", "temp", point).await.unwrap();
+        }
+        store.flush_buffered().await.unwrap();
+
+        let stats = store.get_stats().await;
+        assert_eq!(stats.chunk_count, 2);
+        assert!(stats.chunk_stored_bytes < stats.chunk_raw_bytes);
+        assert!(stats.storage_saved_bytes() > 0);
+
+        // Reads span chunks and head
+        let result = store
+            .query_range("device1", "temp", 1050, 1250)
+            .await
+            .unwrap();
+        assert_eq!(result.points.len(), 21);
+        assert!(result
+            .points
+            .windows(2)
+            .all(|w| w[0].timestamp < w[1].timestamp));
+
+        store.clear_cache();
+        let latest = store
+            .query_latest("device1", "temp")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.timestamp, 1290);
+
+        // A late point for a sealed bucket is merged on the next seal
+        store
+            .write("device1", "temp", DataPoint::new(1005, 99.0))
+            .await
+            .unwrap();
+        store
+            .write("device1", "temp", DataPoint::new(1300, 1.0))
+            .await
+            .unwrap();
+        let result = store
+            .query_range("device1", "temp", 1000, 1010)
+            .await
+            .unwrap();
+        assert_eq!(result.points.len(), 3);
+        assert_eq!(result.points[1].as_f64(), Some(99.0));
+    }
+
+    #[tokio::test]
+    async fn test_chunked_open_seals_stale_buckets() {
+        // Head rows left behind by a previous run, across three old buckets
+        let path = std::env::temp_dir().join(format!("ts_test_{}.redb", uuid::Uuid::new_v4()));
+        {
+            let db = Database::create(&path).unwrap();
+            let write_txn = db.begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(TIMESERIES_TABLE).unwrap();
+                for i in 0..30 {
+                    let point = DataPoint::new(1000 + i * 10, i as f64);
+                    let value = serde_json::to_vec(&point).unwrap();
+                    table
+                        .insert(("device1", "temp", point.timestamp), value.as_slice())
+                        .unwrap();
+                }
+            }
+            write_txn.commit().unwrap();
+        }
+
+        let store = TimeSeriesStore::with_config(
+            path,
+            TimeSeriesConfig {
+                layout: TimeSeriesLayout::Chunked { chunk_secs: 100 },
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(store.get_stats().await.chunk_count, 3);
+        assert!(list_series(&store.db, TIMESERIES_TABLE).unwrap().is_empty());
+
+        let result = store.query_range("device1", "temp", 0, 2000).await.unwrap();
+        assert_eq!(result.points.len(), 30);
+    }
+
+    #[test]
+    fn test_default_layout_is_raw() {
+        assert_eq!(TimeSeriesLayout::default(), TimeSeriesLayout::Raw);
+        assert_eq!(TimeSeriesConfig::default().layout, TimeSeriesLayout::Raw);
+    }
+
+    #[tokio::test]
+    async fn test_chunked_delete_range() {
+        let store = chunked_store(TimeSeriesLayout::Chunked { chunk_secs: 100 });
+        for i in 0..30 {
+            store
+                .write("device1", "temp", DataPoint::new(1000 + i * 10, i as f64))
+                .await
+                .unwrap();
+        }
+
+        // Whole first chunk, half of the second
+        let removed = store
+            .delete_range("device1", "temp", 0, 1140)
+            .await
+            .unwrap();
+        assert_eq!(removed, 15);
+
+        let result = store.query_range("device1", "temp", 0, 2000).await.unwrap();
+        assert_eq!(result.points.len(), 15);
+        assert_eq!(result.points[0].timestamp, 1150);
+        assert_eq!(store.get_stats().await.chunk_count, 1);
+
+        assert_eq!(store.delete_metric("device1", "temp").await.unwrap(), 15);
+        assert!(store.list_metrics("device1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_raw_database_to_chunks() {
+        let store = chunked_store(TimeSeriesLayout::Raw);
+        for i in 0..500 {
+            store
+                .write("device1", "temp", DataPoint::new(i * 10, 21.0))
+                .await
+                .unwrap();
+            store
+                .write(
+                    "device1",
+                    "state",
+                    DataPoint::new_string(i * 10, "idle".to_string()),
+                )
+                .await
+                .unwrap();
+        }
+        assert_eq!(store.get_stats().await.chunk_count, 0);
+
+        let report = store.migrate_to_chunked(3600).await.unwrap();
+        assert_eq!(report.series, 2);
+        assert_eq!(report.points_migrated, 1000);
+        assert!(report.chunk_stored_bytes * 10 < report.chunk_raw_bytes);
+
+        let result = store
+            .query_range("device1", "state", 0, 10_000)
+            .await
+            .unwrap();
+        assert_eq!(result.points.len(), 500);
+        assert_eq!(result.points[499].as_str(), Some("idle"));
+
+        // Running it again is a no-op
+        let report = store.migrate_to_chunked(3600).await.unwrap();
+        assert_eq!(report.points_migrated, 0);
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_query_uses_rollups() {
+        let store = TimeSeriesStore::memory().unwrap();
+        // Two hours of 1-minute readings starting on an hour boundary
+        let base = 1_700_000_000 - 1_700_000_000 % 3600;
+        for i in 0..120 {
+            store
+                .write("device1", "power", DataPoint::new(base + i * 60, i as f64))
+                .await
+                .unwrap();
+        }
+
+        let buckets = store
+            .query_aggregated("device1", "power", base, base + 7199, 3600)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].count, 60);
+        assert_eq!(buckets[0].min, Some(0.0));
+        assert_eq!(buckets[0].max, Some(59.0));
+        assert_eq!(buckets[0].last, Some(59.0));
+        assert_eq!(buckets[1].avg, Some(89.5));
+
+        // Raw data expires, the hourly aggregates stay
+        let mut policy = store.get_retention_policy().await;
+        policy.default_hours = Some(1);
+        policy.set_rollup_retention(60, None);
+        policy.set_rollup_retention(3600, None);
+        store.set_retention_policy(policy).await;
+        store.apply_retention().await.unwrap();
+        assert!(store
+            .query_range("device1", "power", base, base + 7199)
+            .await
+            .unwrap()
+            .points
+            .is_empty());
+        let buckets = store
+            .query_aggregated("device1", "power", base, base + 7199, 3600)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[1].sum, Some((60..120).sum::<i64>() as f64));
+        assert_eq!(store.list_metrics("device1").await.unwrap(), vec!["power"]);
+
+        // Tiers expire on their own retention
+        let mut policy = store.get_retention_policy().await;
+        policy.set_rollup_retention(60, Some(1));
+        policy.set_rollup_retention(3600, None);
+        store.set_retention_policy(policy).await;
+        let result = store.apply_retention().await.unwrap();
+        assert_eq!(result.rollups_removed, 120);
+        let buckets = store
+            .query_aggregated("device1", "power", base, base + 7199, 3600)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 2);
+
+        store.delete_metric("device1", "power").await.unwrap();
+        assert!(store.list_metrics("device1").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_query_edges_use_raw_points() {
+        let store = TimeSeriesStore::memory().unwrap();
+        let base = 1_700_000_000 - 1_700_000_000 % 3600;
+        for i in 0..180 {
+            store
+                .write("device1", "power", DataPoint::new(base + i * 60, i as f64))
+                .await
+                .unwrap();
+        }
+
+        // Half an hour into the first bucket, ten minutes into the third
+        let buckets = store
+            .query_aggregated("device1", "power", base + 1800, base + 7800, 3600)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 3);
+        assert_eq!(buckets[0].count, 30);
+        assert_eq!(buckets[0].min, Some(30.0));
+        assert_eq!(buckets[0].last, Some(59.0));
+        assert_eq!(buckets[1].count, 60);
+        assert_eq!(buckets[2].count, 11);
+        assert_eq!(buckets[2].last, Some(130.0));
+
+        // Rollup keys before the epoch land in the right bucket
+        store
+            .write("device2", "power", DataPoint::new(-30, 1.0))
+            .await
+            .unwrap();
+        store
+            .write("device2", "power", DataPoint::new(-3630, 2.0))
+            .await
+            .unwrap();
+        let buckets = store
+            .query_aggregated("device2", "power", -7200, -1, 7200)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].start, -7200);
+        assert_eq!(buckets[0].count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_rollups_follow_overwrites_and_deletes() {
+        let store = TimeSeriesStore::memory().unwrap();
+        let base = 1_700_000_000 - 1_700_000_000 % 3600;
+        for i in 0..60 {
+            store
+                .write("device1", "power", DataPoint::new(base + i * 60, i as f64))
+                .await
+                .unwrap();
+        }
+
+        // One rewrite sets a new max, the other is adjusted in place
+        store
+            .write("device1", "power", DataPoint::new(base + 600, 100.0))
+            .await
+            .unwrap();
+        store
+            .write_batch("device1", "power", vec![DataPoint::new(base + 1200, 20.5)])
+            .await
+            .unwrap();
+        let buckets = store
+            .query_aggregated("device1", "power", base, base + 3599, 3600)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 1);
+        assert_eq!(buckets[0].count, 60);
+        assert_eq!(buckets[0].max, Some(100.0));
+        let expected = (0..60).sum::<i64>() as f64 - 10.0 + 100.0 - 20.0 + 20.5;
+        assert_eq!(buckets[0].sum, Some(expected));
+
+        // Deleted points leave the aggregates too
+        store
+            .delete_range("device1", "power", base, base + 29 * 60)
+            .await
+            .unwrap();
+        let buckets = store
+            .query_aggregated("device1", "power", base, base + 3599, 3600)
+            .await
+            .unwrap();
+        assert_eq!(buckets[0].count, 30);
+        assert_eq!(buckets[0].min, Some(30.0));
+
+        store
+            .delete_range("device1", "power", i64::MIN, i64::MAX)
+            .await
+            .unwrap();
+        assert!(store
+            .query_aggregated("device1", "power", base, base + 3599, 3600)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_aggregated_query_falls_back_to_raw_without_coverage() {
+        let path = std::env::temp_dir().join(format!("ts_test_{}.redb", uuid::Uuid::new_v4()));
+        let store = TimeSeriesStore::with_config(
+            path,
+            TimeSeriesConfig {
+                rollup_tiers: vec![],
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for i in 0..10 {
+            store
+                .write("device1", "temp", DataPoint::new(i * 60, 1.0))
+                .await
+                .unwrap();
+        }
+        let buckets = store
+            .query_aggregated("device1", "temp", 0, 599, 300)
+            .await
+            .unwrap();
+        assert_eq!(buckets.len(), 2);
+        assert_eq!(buckets[0].count, 5);
+    }
+
+    fn buffered_store(group_commit: GroupCommitConfig) -> Arc<TimeSeriesStore> {
+        let path = std::env::temp_dir().join(format!("ts_test_{}.redb", uuid::Uuid::new_v4()));
+        TimeSeriesStore::with_config(
+            path,
+            TimeSeriesConfig {
+                group_commit: Some(group_commit),
+                ..Default::default()
+            },
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_group_commit_reads_see_buffered_points() {
+        let store = buffered_store(GroupCommitConfig {
+            max_batch_points: 1000,
+            max_latency: Duration::from_secs(3600),
+            max_buffered_points: 1000,
+        });
+
+        for i in 0..10 {
+            store
+                .write("device1", "temp", DataPoint::new(1000 + i * 10, i as f64))
+                .await
+                .unwrap();
+        }
+        assert_eq!(store.buffered_points(), 10);
+        assert!(read_series(&store.db, "device1", "temp", 0, i64::MAX)
+            .unwrap()
+            .is_empty());
+
+        // Buffered points are visible before they are committed
+        let result = store.query_range("device1", "temp", 0, 2000).await.unwrap();
+        assert_eq!(result.points.len(), 10);
+        store.clear_cache();
+        let latest = store
+            .query_latest("device1", "temp")
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.timestamp, 1090);
+        assert_eq!(store.list_metrics("device1").await.unwrap(), vec!["temp"]);
+
+        // One transaction commits the whole group
+        assert_eq!(store.flush_buffered().await.unwrap(), 10);
+        assert_eq!(store.buffered_points(), 0);
+        assert_eq!(
+            read_series(&store.db, "device1", "temp", 0, i64::MAX)
+                .unwrap()
+                .len(),
+            10
+        );
+        let stats = store.get_stats().await;
+        assert_eq!(stats.group_commits, 1);
+        assert_eq!(stats.group_commit_points, 10);
+
+        // Committed rollups and newly buffered points are aggregated together
+        store
+            .write("device1", "temp", DataPoint::new(1100, 10.0))
+            .await
+            .unwrap();
+        let buckets = store
+            .query_aggregated("device1", "temp", 1000, 1199, 60)
+            .await
+            .unwrap();
+        assert_eq!(buckets.iter().map(|b| b.count).sum::<u32>(), 11);
+    }
+
+    #[tokio::test]
+    async fn test_group_commit_flushes_full_batches() {
+        let store = buffered_store(GroupCommitConfig {
+            max_batch_points: 5,
+            max_latency: Duration::from_secs(3600),
+            max_buffered_points: 5,
+        });
+
+        // Twice the buffer size: writers wait for the flusher to make room
+        for i in 0..10 {
+            store
+                .write("device1", "temp", DataPoint::new(1000 + i, i as f64))
+                .await
+                .unwrap();
+        }
+        tokio::time::timeout(Duration::from_secs(5), async {
+            while store.buffered_points() > 0 {
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(
+            read_series(&store.db, "device1", "temp", 0, i64::MAX)
+                .unwrap()
+                .len(),
+            10
+        );
+        assert_eq!(store.get_stats().await.group_commits, 2);
+    }
+
+    #[tokio::test]
+    async fn test_group_commit_is_opt_in() {
+        assert!(TimeSeriesConfig::default().group_commit.is_none());
+        assert!(TimeSeriesStore::memory().unwrap().ingest.is_none());
+
+        // Buffered batch writes still track metric info
+        let store = buffered_store(GroupCommitConfig::default());
+        let mut request = BatchWriteRequest::new("device1".to_string());
+        request.add_point("temp".to_string(), DataPoint::new(1000, 1.0));
+        request.add_point("temp".to_string(), DataPoint::new(1010, 2.0));
+        assert_eq!(store.write_batch_concurrent(vec![request]).await.unwrap(), 2);
+        let info = store.metrics_info.get("device1:temp").unwrap();
+        assert_eq!(info.point_count, 2);
+        assert_eq!(info.last_update, 1010);
+    }
 }
@@ -0,0 +1,617 @@
+//! Compressed chunk encoding for time series data.
+//!
+//! A chunk holds the points of one series inside one time bucket, stored
+//! column by column:
+//!
+//! - **Timestamps**: delta-of-delta, zigzag varints (regular sampling costs one byte per point)
+//! - **Floats**: Gorilla XOR encoding against the previous float
+//! - **Integers**: zigzag varint deltas
+//! - **Booleans**: one bit each
+//! - **Strings**: per-chunk dictionary plus varint indices
+//! - **Other JSON** (objects, arrays): JSON text
+//!
+//! Quality flags and metadata are rare and stored in an optional trailing section.
+
+use std::collections::HashMap;
+
+use crate::timeseries::DataPoint;
+use crate::Error;
+
+const CHUNK_MAGIC: u8 = 0xC7;
+const CHUNK_VERSION: u8 = 1;
+
+const FLAG_QUALITY: u8 = 0x01;
+const FLAG_METADATA: u8 = 0x02;
+
+/// Value kind of a point, stored run-length encoded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Kind {
+    Float = 0,
+    Int = 1,
+    Bool = 2,
+    Str = 3,
+    Null = 4,
+    Json = 5,
+}
+
+impl Kind {
+    fn of(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Number(n) if n.is_i64() => Kind::Int,
+            serde_json::Value::Number(n) if n.is_f64() => Kind::Float,
+            serde_json::Value::Bool(_) => Kind::Bool,
+            serde_json::Value::String(_) => Kind::Str,
+            serde_json::Value::Null => Kind::Null,
+            _ => Kind::Json,
+        }
+    }
+
+    fn from_u64(v: u64) -> Option<Self> {
+        Some(match v {
+            0 => Kind::Float,
+            1 => Kind::Int,
+            2 => Kind::Bool,
+            3 => Kind::Str,
+            4 => Kind::Null,
+            5 => Kind::Json,
+            _ => return None,
+        })
+    }
+}
+
+/// Summary stored at the front of every chunk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkHeader {
+    /// Number of points in the chunk
+    pub count: usize,
+    /// Size the points would take as one JSON row each (the raw layout)
+    pub raw_size: u64,
+    /// Timestamp of the first point
+    pub first_ts: i64,
+    /// Timestamp of the last point
+    pub last_ts: i64,
+}
+
+/// Encode points into a chunk. Points must be sorted by timestamp.
+pub fn encode_chunk(points: &[DataPoint]) -> Vec<u8> {
+    let raw_size: u64 = points
+        .iter()
+        .map(|p| serde_json::to_vec(p).map(|v| v.len() as u64).unwrap_or(0))
+        .sum();
+
+    let mut timestamps = Vec::new();
+    let mut kinds = Vec::new();
+    let mut floats = BitWriter::default();
+    let mut float_state = XorState::default();
+    let mut ints = Vec::new();
+    let mut prev_int = 0i64;
+    let mut bools = BitWriter::default();
+    let mut dict: Vec<&str> = Vec::new();
+    let mut dict_index: HashMap<&str, u64> = HashMap::new();
+    let mut string_ids = Vec::new();
+    let mut json = Vec::new();
+
+    let mut prev_ts = 0i64;
+    let mut prev_delta = 0i64;
+    let mut run: Option<(Kind, u64)> = None;
+
+    for (i, point) in points.iter().enumerate() {
+        // Timestamps: first value, first delta, then delta-of-delta
+        match i {
+            0 => write_ivarint(&mut timestamps, point.timestamp),
+            _ => {
+                let delta = point.timestamp.wrapping_sub(prev_ts);
+                write_ivarint(&mut timestamps, delta.wrapping_sub(prev_delta));
+                prev_delta = delta;
+            }
+        }
+        prev_ts = point.timestamp;
+
+        let kind = Kind::of(&point.value);
+        run = match run {
+            Some((k, n)) if k == kind => Some((k, n + 1)),
+            Some((k, n)) => {
+                write_uvarint(&mut kinds, k as u64);
+                write_uvarint(&mut kinds, n);
+                Some((kind, 1))
+            }
+            None => Some((kind, 1)),
+        };
+
+        match (&point.value, kind) {
+            (serde_json::Value::Number(n), Kind::Float) => {
+                float_state.write(&mut floats, n.as_f64().unwrap_or_default().to_bits());
+            }
+            (serde_json::Value::Number(n), Kind::Int) => {
+                let v = n.as_i64().unwrap_or_default();
+                write_ivarint(&mut ints, v.wrapping_sub(prev_int));
+                prev_int = v;
+            }
+            (serde_json::Value::Bool(b), _) => bools.write_bit(*b),
+            (serde_json::Value::String(s), _) => {
+                let id = *dict_index.entry(s.as_str()).or_insert_with(|| {
+                    dict.push(s.as_str());
+                    (dict.len() - 1) as u64
+                });
+                write_uvarint(&mut string_ids, id);
+            }
+            (serde_json::Value::Null, _) => {}
+            (value, _) => {
+                let text = value.to_string();
+                write_uvarint(&mut json, text.len() as u64);
+                json.extend_from_slice(text.as_bytes());
+            }
+        }
+    }
+    if let Some((k, n)) = run {
+        write_uvarint(&mut kinds, k as u64);
+        write_uvarint(&mut kinds, n);
+    }
+
+    let mut strings = Vec::new();
+    write_uvarint(&mut strings, dict.len() as u64);
+    for s in &dict {
+        write_uvarint(&mut strings, s.len() as u64);
+        strings.extend_from_slice(s.as_bytes());
+    }
+    strings.extend_from_slice(&string_ids);
+
+    let mut flags = 0u8;
+    let mut extras = Vec::new();
+    if points.iter().any(|p| p.quality.is_some()) {
+        flags |= FLAG_QUALITY;
+        for point in points {
+            match point.quality {
+                Some(q) => {
+                    extras.push(1);
+                    extras.extend_from_slice(&q.to_le_bytes());
+                }
+                None => extras.push(0),
+            }
+        }
+    }
+    if points.iter().any(|p| p.metadata.is_some()) {
+        flags |= FLAG_METADATA;
+        for point in points {
+            match &point.metadata {
+                Some(meta) => {
+                    let text = meta.to_string();
+                    write_uvarint(&mut extras, text.len() as u64 + 1);
+                    extras.extend_from_slice(text.as_bytes());
+                }
+                None => write_uvarint(&mut extras, 0),
+            }
+        }
+    }
+
+    let mut out = vec![CHUNK_MAGIC, CHUNK_VERSION, flags];
+    write_uvarint(&mut out, points.len() as u64);
+    write_uvarint(&mut out, raw_size);
+    write_ivarint(&mut out, points.first().map_or(0, |p| p.timestamp));
+    write_ivarint(&mut out, points.last().map_or(0, |p| p.timestamp));
+    for section in [
+        &timestamps,
+        &kinds,
+        &floats.into_bytes(),
+        &ints,
+        &bools.into_bytes(),
+        &strings,
+        &json,
+        &extras,
+    ] {
+        write_uvarint(&mut out, section.len() as u64);
+        out.extend_from_slice(section);
+    }
+    out
+}
+
+/// Read only the header of a chunk.
+pub fn read_chunk_header(data: &[u8]) -> Result<ChunkHeader, Error> {
+    let mut pos = 0;
+    read_header(data, &mut pos).map(|(header, _)| header)
+}
+
+/// Decode a chunk back into points.
+pub fn decode_chunk(data: &[u8]) -> Result<Vec<DataPoint>, Error> {
+    let mut pos = 0;
+    let (header, flags) = read_header(data, &mut pos)?;
+    let count = header.count;
+
+    let mut sections = Vec::with_capacity(8);
+    for _ in 0..8 {
+        let len = read_uvarint(data, &mut pos)? as usize;
+        let section = data
+            .get(
+                pos..pos
+                    .checked_add(len)
+                    .ok_or_else(|| corrupt("section length"))?,
+            )
+            .ok_or_else(|| corrupt("truncated section"))?;
+        sections.push(section);
+        pos += len;
+    }
+    let [timestamps, kinds, floats, ints, bools, strings, json, extras] = sections[..] else {
+        return Err(corrupt("missing sections"));
+    };
+
+    // Timestamps
+    let mut ts = Vec::with_capacity(count);
+    let mut p = 0;
+    let mut prev_delta = 0i64;
+    for i in 0..count {
+        let v = read_ivarint(timestamps, &mut p)?;
+        match i {
+            0 => ts.push(v),
+            _ => {
+                prev_delta = prev_delta.wrapping_add(v);
+                ts.push(ts[i - 1].wrapping_add(prev_delta));
+            }
+        }
+    }
+
+    // Kinds
+    let mut point_kinds = Vec::with_capacity(count);
+    let mut p = 0;
+    while point_kinds.len() < count {
+        let kind = Kind::from_u64(read_uvarint(kinds, &mut p)?).ok_or_else(|| corrupt("kind"))?;
+        let run = read_uvarint(kinds, &mut p)? as usize;
+        if run == 0 || point_kinds.len() + run > count {
+            return Err(corrupt("kind run"));
+        }
+        point_kinds.extend(std::iter::repeat_n(kind, run));
+    }
+
+    // String dictionary
+    let mut sp = 0;
+    let dict_len = read_uvarint(strings, &mut sp)? as usize;
+    let mut dict = Vec::with_capacity(dict_len.min(count));
+    for _ in 0..dict_len {
+        let len = read_uvarint(strings, &mut sp)? as usize;
+        let bytes = strings
+            .get(sp..sp + len)
+            .ok_or_else(|| corrupt("string dictionary"))?;
+        dict.push(String::from_utf8(bytes.to_vec()).map_err(|_| corrupt("string utf8"))?);
+        sp += len;
+    }
+
+    let mut float_reader = BitReader::new(floats);
+    let mut float_state = XorState::default();
+    let mut ip = 0;
+    let mut prev_int = 0i64;
+    let mut bool_reader = BitReader::new(bools);
+    let mut jp = 0;
+
+    let mut points = Vec::with_capacity(count);
+    for (timestamp, kind) in ts.into_iter().zip(point_kinds) {
+        let value = match kind {
+            Kind::Float => {
+                let bits = float_state
+                    .read(&mut float_reader)
+                    .ok_or_else(|| corrupt("float column"))?;
+                serde_json::Number::from_f64(f64::from_bits(bits))
+                    .map(serde_json::Value::Number)
+                    .unwrap_or(serde_json::Value::Null)
+            }
+            Kind::Int => {
+                prev_int = prev_int.wrapping_add(read_ivarint(ints, &mut ip)?);
+                serde_json::json!(prev_int)
+            }
+            Kind::Bool => serde_json::Value::Bool(
+                bool_reader
+                    .read_bit()
+                    .ok_or_else(|| corrupt("bool column"))?,
+            ),
+            Kind::Str => {
+                let id = read_uvarint(strings, &mut sp)? as usize;
+                serde_json::Value::String(
+                    dict.get(id).cloned().ok_or_else(|| corrupt("string id"))?,
+                )
+            }
+            Kind::Null => serde_json::Value::Null,
+            Kind::Json => {
+                let len = read_uvarint(json, &mut jp)? as usize;
+                let bytes = json
+                    .get(jp..jp + len)
+                    .ok_or_else(|| corrupt("json column"))?;
+                jp += len;
+                serde_json::from_slice(bytes)?
+            }
+        };
+        points.push(DataPoint {
+            timestamp,
+            value,
+            quality: None,
+            metadata: None,
+        });
+    }
+
+    let mut ep = 0;
+    if flags & FLAG_QUALITY != 0 {
+        for point in &mut points {
+            let present = *extras.get(ep).ok_or_else(|| corrupt("quality"))?;
+            ep += 1;
+            if present == 1 {
+                let bytes = extras.get(ep..ep + 4).ok_or_else(|| corrupt("quality"))?;
+                point.quality = Some(f32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]));
+                ep += 4;
+            }
+        }
+    }
+    if flags & FLAG_METADATA != 0 {
+        for point in &mut points {
+            let len = read_uvarint(extras, &mut ep)? as usize;
+            if len > 0 {
+                let bytes = extras
+                    .get(ep..ep + len - 1)
+                    .ok_or_else(|| corrupt("metadata"))?;
+                point.metadata = Some(serde_json::from_slice(bytes)?);
+                ep += len - 1;
+            }
+        }
+    }
+
+    Ok(points)
+}
+
+fn read_header(data: &[u8], pos: &mut usize) -> Result<(ChunkHeader, u8), Error> {
+    if data.len() < 3 || data[0] != CHUNK_MAGIC {
+        return Err(corrupt("bad magic"));
+    }
+    if data[1] != CHUNK_VERSION {
+        return Err(Error::Storage(format!(
+            "Unsupported time series chunk version {}",
+            data[1]
+        )));
+    }
+    let flags = data[2];
+    *pos = 3;
+    let count = read_uvarint(data, pos)? as usize;
+    let raw_size = read_uvarint(data, pos)?;
+    let first_ts = read_ivarint(data, pos)?;
+    let last_ts = read_ivarint(data, pos)?;
+    Ok((
+        ChunkHeader {
+            count,
+            raw_size,
+            first_ts,
+            last_ts,
+        },
+        flags,
+    ))
+}
+
+fn corrupt(what: &str) -> Error {
+    Error::Storage(format!("Corrupt time series chunk: {}", what))
+}
+
+fn write_uvarint(buf: &mut Vec<u8>, mut v: u64) {
+    while v >= 0x80 {
+        buf.push((v as u8) | 0x80);
+        v >>= 7;
+    }
+    buf.push(v as u8);
+}
+
+fn read_uvarint(data: &[u8], pos: &mut usize) -> Result<u64, Error> {
+    let mut result = 0u64;
+    let mut shift = 0;
+    loop {
+        let byte = *data.get(*pos).ok_or_else(|| corrupt("truncated varint"))?;
+        *pos += 1;
+        if shift >= 64 {
+            return Err(corrupt("varint overflow"));
+        }
+        result |= u64::from(byte & 0x7F) << shift;
+        if byte & 0x80 == 0 {
+            return Ok(result);
+        }
+        shift += 7;
+    }
+}
+
+fn write_ivarint(buf: &mut Vec<u8>, v: i64) {
+    write_uvarint(buf, ((v << 1) ^ (v >> 63)) as u64);
+}
+
+fn read_ivarint(data: &[u8], pos: &mut usize) -> Result<i64, Error> {
+    let v = read_uvarint(data, pos)?;
+    Ok(((v >> 1) as i64) ^ -((v & 1) as i64))
+}
+
+/// MSB-first bit writer.
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    used: u8,
+}
+
+impl BitWriter {
+    fn write_bit(&mut self, bit: bool) {
+        if self.used == 0 {
+            self.bytes.push(0);
+        }
+        if bit {
+            *self.bytes.last_mut().unwrap() |= 0x80 >> self.used;
+        }
+        self.used = (self.used + 1) % 8;
+    }
+
+    fn write_bits(&mut self, value: u64, n: u32) {
+        for i in (0..n).rev() {
+            self.write_bit((value >> i) & 1 == 1);
+        }
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        self.bytes
+    }
+}
+
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, pos: 0 }
+    }
+
+    fn read_bit(&mut self) -> Option<bool> {
+        let byte = self.bytes.get(self.pos / 8)?;
+        let bit = byte & (0x80 >> (self.pos % 8)) != 0;
+        self.pos += 1;
+        Some(bit)
+    }
+
+    fn read_bits(&mut self, n: u32) -> Option<u64> {
+        let mut value = 0u64;
+        for _ in 0..n {
+            value = (value << 1) | u64::from(self.read_bit()?);
+        }
+        Some(value)
+    }
+}
+
+/// Gorilla XOR state shared by the float encoder and decoder.
+#[derive(Default)]
+struct XorState {
+    prev: Option<u64>,
+    leading: u32,
+    trailing: u32,
+}
+
+impl XorState {
+    fn write(&mut self, w: &mut BitWriter, bits: u64) {
+        let Some(prev) = self.prev else {
+            w.write_bits(bits, 64);
+            self.prev = Some(bits);
+            self.leading = u32::MAX;
+            return;
+        };
+        self.prev = Some(bits);
+
+        let xor = bits ^ prev;
+        if xor == 0 {
+            w.write_bit(false);
+            return;
+        }
+        w.write_bit(true);
+
+        let leading = xor.leading_zeros().min(31);
+        let trailing = xor.trailing_zeros();
+        if self.leading != u32::MAX && leading >= self.leading && trailing >= self.trailing {
+            // Meaningful bits fit in the previous window
+            w.write_bit(false);
+            w.write_bits(xor >> self.trailing, 64 - self.leading - self.trailing);
+        } else {
+            let significant = 64 - leading - trailing;
+            w.write_bit(true);
+            w.write_bits(u64::from(leading), 5);
+            w.write_bits(u64::from(significant - 1), 6);
+            w.write_bits(xor >> trailing, significant);
+            self.leading = leading;
+            self.trailing = trailing;
+        }
+    }
+
+    fn read(&mut self, r: &mut BitReader<'_>) -> Option<u64> {
+        let Some(prev) = self.prev else {
+            let bits = r.read_bits(64)?;
+            self.prev = Some(bits);
+            self.leading = u32::MAX;
+            return Some(bits);
+        };
+
+        if !r.read_bit()? {
+            return Some(prev);
+        }
+        let xor = if !r.read_bit()? {
+            if self.leading == u32::MAX {
+                return None;
+            }
+            r.read_bits(64 - self.leading - self.trailing)? << self.trailing
+        } else {
+            let leading = r.read_bits(5)? as u32;
+            let significant = r.read_bits(6)? as u32 + 1;
+            if leading + significant > 64 {
+                return None;
+            }
+            let trailing = 64 - leading - significant;
+            self.leading = leading;
+            self.trailing = trailing;
+            r.read_bits(significant)? << trailing
+        };
+        let bits = prev ^ xor;
+        self.prev = Some(bits);
+        Some(bits)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_chunk_roundtrip_mixed_values() {
+        let points = vec![
+            DataPoint::new(1000, 21.5),
+            DataPoint::new(1001, 21.5),
+            DataPoint::new(1002, 21.75).with_quality(0.5),
+            DataPoint::new_with_value(1004, json!(42)),
+            DataPoint::new_with_value(1005, json!(-7)),
+            DataPoint::new_bool(1006, true),
+            DataPoint::new_string(1007, "idle".to_string()),
+            DataPoint::new_string(1008, "running".to_string()),
+            DataPoint::new_string(1009, "idle".to_string()),
+            DataPoint::new_with_value(1010, serde_json::Value::Null),
+            DataPoint::new_with_value(1011, json!({"x": [1, 2]}))
+                .with_metadata(json!({"source": "test"})),
+            DataPoint::new(1012, -0.000123),
+        ];
+
+        let encoded = encode_chunk(&points);
+        let decoded = decode_chunk(&encoded).unwrap();
+
+        assert_eq!(decoded.len(), points.len());
+        for (a, b) in points.iter().zip(&decoded) {
+            assert_eq!(a.timestamp, b.timestamp);
+            assert_eq!(a.value, b.value);
+            assert_eq!(a.quality, b.quality);
+            assert_eq!(a.metadata, b.metadata);
+        }
+        let header = read_chunk_header(&encoded).unwrap();
+        assert_eq!(header.count, points.len());
+        assert_eq!((header.first_ts, header.last_ts), (1000, 1012));
+    }
+
+    #[test]
+    fn test_chunk_compresses_regular_series() {
+        // One hour of 1 Hz readings of a slowly changing temperature
+        let points: Vec<DataPoint> = (0..3600)
+            .map(|i| DataPoint::new(1_700_000_000 + i, 20.0 + ((i / 60) as f64) * 0.25))
+            .collect();
+
+        let encoded = encode_chunk(&points);
+        let header = read_chunk_header(&encoded).unwrap();
+        assert!(
+            encoded.len() * 10 < header.raw_size as usize,
+            "chunk {} bytes vs raw {} bytes",
+            encoded.len(),
+            header.raw_size
+        );
+        assert_eq!(
+            decode_chunk(&encoded).unwrap()[3599].value,
+            points[3599].value
+        );
+    }
+
+    #[test]
+    fn test_decode_rejects_garbage() {
+        assert!(decode_chunk(b"{\"timestamp\":1}").is_err());
+        let mut encoded = encode_chunk(&[DataPoint::new(1, 1.0), DataPoint::new(2, 2.0)]);
+        encoded.truncate(encoded.len() - 3);
+        assert!(decode_chunk(&encoded).is_err());
+    }
+}
@@ -0,0 +1,336 @@
+//! Ingest buffer for group commits.
+//!
+//! Writes from every adapter land here first and are committed to redb by a
+//! background flusher in one transaction per group, instead of one
+//! transaction per point. A group is committed when `max_batch_points` are
+//! buffered or `max_latency` has passed, whichever comes first. Writers wait
+//! (backpressure) once `max_buffered_points` are waiting to be committed.
+//!
+//! Buffered points stay visible to reads: the store merges [`IngestBuffer::range`]
+//! and [`IngestBuffer::latest`] into its query results, including points of a
+//! group that is being committed.
+
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use tokio::sync::{Notify, Semaphore};
+
+use crate::timeseries::DataPoint;
+use crate::Error;
+
+/// Buffered points: (device_id, metric) -> timestamp -> point.
+pub(crate) type SeriesPoints = HashMap<(String, String), BTreeMap<i64, DataPoint>>;
+
+/// Group commit settings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GroupCommitConfig {
+    /// Commit as soon as this many points are buffered
+    pub max_batch_points: usize,
+    /// Longest a buffered point waits before it is committed
+    pub max_latency: Duration,
+    /// Writers wait once this many points are buffered
+    pub max_buffered_points: usize,
+}
+
+impl Default for GroupCommitConfig {
+    fn default() -> Self {
+        Self {
+            max_batch_points: 1000,
+            max_latency: Duration::from_millis(100),
+            max_buffered_points: 20_000,
+        }
+    }
+}
+
+#[derive(Default)]
+struct BufferState {
+    /// Points waiting for the next group commit
+    pending: SeriesPoints,
+    /// Number of points in `pending`
+    pending_count: usize,
+    /// Capacity permits held by `pending`
+    permits: usize,
+    /// Group currently being committed, still visible to reads
+    committing: Option<Arc<SeriesPoints>>,
+}
+
+/// A group taken from the buffer for commit.
+pub(crate) struct PendingGroup {
+    pub(crate) points: Arc<SeriesPoints>,
+    pub(crate) count: usize,
+    permits: usize,
+}
+
+pub(crate) struct IngestBuffer {
+    config: GroupCommitConfig,
+    state: Mutex<BufferState>,
+    capacity: Semaphore,
+    /// Wakes the flusher when a batch is full
+    pub(crate) notify: Notify,
+    /// Whether a flusher task is running
+    pub(crate) flusher_running: AtomicBool,
+    /// Serializes group commits
+    pub(crate) commit_lock: Mutex<()>,
+}
+
+impl IngestBuffer {
+    pub(crate) fn new(mut config: GroupCommitConfig) -> Self {
+        config.max_batch_points = config.max_batch_points.max(1);
+        config.max_buffered_points = config.max_buffered_points.max(config.max_batch_points);
+        let capacity = Semaphore::new(config.max_buffered_points);
+        Self {
+            config,
+            state: Mutex::new(BufferState::default()),
+            capacity,
+            notify: Notify::new(),
+            flusher_running: AtomicBool::new(false),
+            commit_lock: Mutex::new(()),
+        }
+    }
+
+    pub(crate) fn max_latency(&self) -> Duration {
+        self.config.max_latency
+    }
+
+    /// Buffer points of one series, waiting while the buffer is full.
+    pub(crate) async fn push(
+        &self,
+        device_id: &str,
+        metric: &str,
+        points: impl IntoIterator<Item = DataPoint>,
+    ) -> Result<usize, Error> {
+        let points: Vec<DataPoint> = points.into_iter().collect();
+        if points.is_empty() {
+            return Ok(0);
+        }
+
+        // A batch larger than the whole buffer only waits for an empty buffer
+        let permits = points.len().min(self.config.max_buffered_points);
+        self.capacity
+            .acquire_many(permits as u32)
+            .await
+            .map_err(|_| Error::Storage("Ingest buffer closed".to_string()))?
+            .forget();
+
+        let count = points.len();
+        let batch_full = {
+            let mut state = self.state.lock().unwrap();
+            let series = state
+                .pending
+                .entry((device_id.to_string(), metric.to_string()))
+                .or_default();
+            let mut added = 0;
+            for point in points {
+                if series.insert(point.timestamp, point).is_none() {
+                    added += 1;
+                }
+            }
+            state.pending_count += added;
+            state.permits += permits;
+            state.pending_count >= self.config.max_batch_points
+        };
+        if batch_full {
+            self.notify.notify_one();
+        }
+        Ok(count)
+    }
+
+    /// Take the pending points for commit. They stay visible to reads until
+    /// [`Self::finish`] is called.
+    pub(crate) fn take(&self) -> Option<PendingGroup> {
+        let mut state = self.state.lock().unwrap();
+        if state.pending.is_empty() {
+            return None;
+        }
+        let points = Arc::new(std::mem::take(&mut state.pending));
+        let group = PendingGroup {
+            points: Arc::clone(&points),
+            count: std::mem::take(&mut state.pending_count),
+            permits: std::mem::take(&mut state.permits),
+        };
+        state.committing = Some(points);
+        Some(group)
+    }
+
+    /// Finish a commit. A failed group goes back into the buffer; points
+    /// written since it was taken are newer and win.
+    pub(crate) fn finish(&self, group: PendingGroup, committed: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.committing = None;
+        if committed {
+            drop(state);
+            self.capacity.add_permits(group.permits);
+            return;
+        }
+
+        let state = &mut *state;
+        let points = Arc::try_unwrap(group.points).unwrap_or_else(|shared| (*shared).clone());
+        for (series, buffered) in points {
+            let pending = state.pending.entry(series).or_default();
+            for (timestamp, point) in buffered {
+                if let std::collections::btree_map::Entry::Vacant(entry) = pending.entry(timestamp)
+                {
+                    entry.insert(point);
+                    state.pending_count += 1;
+                }
+            }
+        }
+        state.permits += group.permits;
+    }
+
+    /// Buffered points of a series in `[start, end]`, ascending.
+    pub(crate) fn range(
+        &self,
+        device_id: &str,
+        metric: &str,
+        start: i64,
+        end: i64,
+    ) -> Vec<DataPoint> {
+        if start > end {
+            return Vec::new();
+        }
+        let key = (device_id.to_string(), metric.to_string());
+        let state = self.state.lock().unwrap();
+        let mut merged: BTreeMap<i64, DataPoint> = BTreeMap::new();
+        let sources = state
+            .committing
+            .as_deref()
+            .into_iter()
+            .chain([&state.pending]);
+        for source in sources {
+            if let Some(points) = source.get(&key) {
+                for (timestamp, point) in points.range(start..=end) {
+                    merged.insert(*timestamp, point.clone());
+                }
+            }
+        }
+        merged.into_values().collect()
+    }
+
+    /// Newest buffered point of a series.
+    pub(crate) fn latest(&self, device_id: &str, metric: &str) -> Option<DataPoint> {
+        let key = (device_id.to_string(), metric.to_string());
+        let state = self.state.lock().unwrap();
+        let sources = state
+            .committing
+            .as_deref()
+            .into_iter()
+            .chain([&state.pending]);
+        sources
+            .filter_map(|source| source.get(&key)?.last_key_value())
+            .max_by_key(|(timestamp, _)| **timestamp)
+            .map(|(_, point)| point.clone())
+    }
+
+    /// Metrics of a device that have buffered points.
+    pub(crate) fn metrics(&self, device_id: &str) -> Vec<String> {
+        let state = self.state.lock().unwrap();
+        let sources = state
+            .committing
+            .as_deref()
+            .into_iter()
+            .chain([&state.pending]);
+        sources
+            .flat_map(|source| source.keys())
+            .filter(|(device, _)| device == device_id)
+            .map(|(_, metric)| metric.clone())
+            .collect()
+    }
+
+    /// Number of points waiting for a commit.
+    pub(crate) fn len(&self) -> usize {
+        self.state.lock().unwrap().pending_count
+    }
+}
+
+/// Clears [`IngestBuffer::flusher_running`] when the flusher task ends,
+/// including when its runtime shuts down, so the next write starts a new one.
+pub(crate) struct FlusherGuard(pub(crate) Arc<IngestBuffer>);
+
+impl Drop for FlusherGuard {
+    fn drop(&mut self) {
+        self.0.flusher_running.store(false, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_buffered_points: usize) -> GroupCommitConfig {
+        GroupCommitConfig {
+            max_batch_points: 2,
+            max_latency: Duration::from_millis(10),
+            max_buffered_points,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_buffered_points_visible_until_committed() {
+        let buffer = IngestBuffer::new(config(10));
+        buffer
+            .push(
+                "d1",
+                "temp",
+                [DataPoint::new(1, 1.0), DataPoint::new(2, 2.0)],
+            )
+            .await
+            .unwrap();
+        assert_eq!(buffer.len(), 2);
+
+        let group = buffer.take().unwrap();
+        assert_eq!(group.count, 2);
+        assert_eq!(buffer.len(), 0);
+
+        // Newer writes during the commit win over the group being committed
+        buffer
+            .push("d1", "temp", [DataPoint::new(2, 20.0)])
+            .await
+            .unwrap();
+        let points = buffer.range("d1", "temp", 0, 10);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[1].as_f64(), Some(20.0));
+        assert_eq!(buffer.latest("d1", "temp").unwrap().timestamp, 2);
+
+        // A failed commit puts the group back without clobbering newer points
+        buffer.finish(group, false);
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(buffer.range("d1", "temp", 2, 2)[0].as_f64(), Some(20.0));
+
+        let group = buffer.take().unwrap();
+        buffer.finish(group, true);
+        assert!(buffer.range("d1", "temp", 0, 10).is_empty());
+        assert!(buffer.metrics("d1").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_backpressure_when_full() {
+        let buffer = Arc::new(IngestBuffer::new(config(2)));
+        buffer
+            .push(
+                "d1",
+                "temp",
+                [DataPoint::new(1, 1.0), DataPoint::new(2, 2.0)],
+            )
+            .await
+            .unwrap();
+
+        // The buffer is full, so the next write waits for a commit
+        let blocked = tokio::time::timeout(
+            Duration::from_millis(20),
+            buffer.push("d1", "temp", [DataPoint::new(3, 3.0)]),
+        )
+        .await;
+        assert!(blocked.is_err());
+
+        let group = buffer.take().unwrap();
+        buffer.finish(group, true);
+        buffer
+            .push("d1", "temp", [DataPoint::new(3, 3.0)])
+            .await
+            .unwrap();
+        assert_eq!(buffer.len(), 1);
+    }
+}
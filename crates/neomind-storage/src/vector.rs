@@ -19,6 +19,7 @@ use rand::random;
 use redb::{Database, ReadableTable, TableDefinition};
 use serde::{Deserialize, Serialize};
 
+use crate::snapshot::begin_write;
 use crate::Error;
 
 // Vector table: key = document_id, value = VectorDocument (serialized)
@@ -572,6 +573,11 @@ impl PersistentVectorStore {
             Database::create(path_ref)?
         };
 
+        // Create the table so reads on a fresh database succeed
+        let write_txn = begin_write(&db)?;
+        write_txn.open_table(VECTORS_TABLE)?;
+        write_txn.commit()?;
+
         let index = VectorStore::new();
         let store = Arc::new(PersistentVectorStore {
             db: Arc::new(db),
@@ -602,7 +608,7 @@ impl PersistentVectorStore {
 
     /// Insert a document and persist to disk.
     pub async fn insert(&self, doc: VectorDocument) -> Result<(), Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(VECTORS_TABLE)?;
             let value = serde_json::to_vec(&doc)?;
@@ -627,7 +633,7 @@ impl PersistentVectorStore {
 
     /// Delete a document.
     pub async fn delete(&self, id: &str) -> Result<bool, Error> {
-        let write_txn = self.db.begin_write()?;
+        let write_txn = begin_write(&self.db)?;
         {
             let mut table = write_txn.open_table(VECTORS_TABLE)?;
             table.remove(id)?;
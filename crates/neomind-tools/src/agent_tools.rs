@@ -200,6 +200,10 @@ impl Tool for ListAgentsTool {
             "agents": agent_list
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for getting detailed information about a specific agent.
@@ -344,6 +348,10 @@ impl Tool for GetAgentTool {
 
         Ok(ToolOutput::success(response))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -1108,6 +1116,8 @@ impl Tool for CreateAgentTool {
             description: Some(description.to_string()),
             user_prompt: description.to_string(),
             llm_backend_id: None,
+            llm_routing_policy: None,
+            response_cache: None,
             parsed_intent: Some(parsed_intent),
             resources: resolved_resources,
             schedule: schedule.unwrap_or(neomind_storage::agents::AgentSchedule {
@@ -1131,6 +1141,7 @@ impl Tool for CreateAgentTool {
             error_message: None,
             enable_tool_chaining: false, // Default disabled for backward compatibility
             max_chain_depth: 3,          // Default max depth
+            approval_policy: None,
         };
 
         // Save the agent
@@ -1753,6 +1764,10 @@ impl Tool for AgentMemoryTool {
             }))),
         }
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -1949,6 +1964,10 @@ impl Tool for GetAgentExecutionsTool {
             }
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for getting detailed information about a specific execution.
@@ -2131,6 +2150,10 @@ impl Tool for GetAgentExecutionDetailTool {
 
         Ok(ToolOutput::success(response))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for querying agent conversation history.
@@ -2288,6 +2311,10 @@ impl Tool for GetAgentConversationTool {
             })))
         }
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
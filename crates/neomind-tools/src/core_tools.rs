@@ -1090,6 +1090,10 @@ impl Tool for DeviceDiscoverTool {
 
         Ok(ToolOutput::success(result))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -1430,6 +1434,10 @@ impl Tool for DeviceQueryTool {
             "queried_at": chrono::Utc::now().to_rfc3339()
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -2510,6 +2518,10 @@ impl Tool for DeviceAnalyzeTool {
 
         Ok(ToolOutput::success(serde_json::to_value(result).unwrap()))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -2971,4 +2983,8 @@ impl Tool for RuleFromContextTool {
 
         Ok(ToolOutput::success(response))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
@@ -0,0 +1,195 @@
+//! Knowledge base tools.
+//!
+//! Lets agents search ingested manuals and SOPs in long-term memory. Results
+//! carry a citation (source document and page) so answers can reference them.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::error::Result;
+use super::error::ToolError;
+use super::tool::{
+    number_property, object_schema, string_property, Tool, ToolDefinition, ToolOutput,
+};
+use neomind_core::tools::{ToolCategory, ToolExample, ToolRelationships, UsageScenario};
+
+use neomind_memory::{
+    EmbeddingModel, HybridRetriever, LongTermMemory, RetrievalFilter, RetrievalQuery,
+    RetrievedContent,
+};
+
+/// Default number of chunks returned.
+const DEFAULT_TOP_K: usize = 5;
+
+/// Tool for searching the long-term knowledge base.
+pub struct SearchKnowledgeTool {
+    retriever: HybridRetriever,
+}
+
+impl SearchKnowledgeTool {
+    /// Create a new search knowledge tool.
+    pub fn new(long_term: Arc<LongTermMemory>, embedding: Arc<dyn EmbeddingModel>) -> Self {
+        Self {
+            retriever: HybridRetriever::new(embedding).with_knowledge(long_term),
+        }
+    }
+}
+
+#[async_trait]
+impl Tool for SearchKnowledgeTool {
+    fn name(&self) -> &str {
+        "search_knowledge"
+    }
+
+    fn description(&self) -> &str {
+        "搜索知识库（已上传的设备手册、操作规程等文档）。返回相关段落及出处（文档名和页码）。回答时请引用出处，例如“（来源：pump.pdf, p. 12）”。"
+    }
+
+    fn parameters(&self) -> Value {
+        object_schema(
+            serde_json::json!({
+                "query": string_property("搜索内容，例如故障现象或操作步骤"),
+                "device_type": string_property("可选，只返回适用于该设备类型的文档"),
+                "top_k": number_property("可选，返回段落数量，默认5")
+            }),
+            vec!["query".to_string()],
+        )
+    }
+
+    fn definition(&self) -> ToolDefinition {
+        ToolDefinition {
+            name: self.name().to_string(),
+            description: self.description().to_string(),
+            parameters: self.parameters(),
+            example: Some(ToolExample {
+                arguments: serde_json::json!({
+                    "query": "水泵无法启动",
+                    "device_type": "pump"
+                }),
+                result: serde_json::json!({
+                    "count": 1,
+                    "results": [
+                        {
+                            "title": "pump.pdf (p. 12)",
+                            "content": "启动前必须先灌泵……",
+                            "citation": "pump.pdf, p. 12",
+                            "score": 0.82
+                        }
+                    ]
+                }),
+                description: "查找水泵手册中的相关段落".to_string(),
+            }),
+            category: ToolCategory::System,
+            scenarios: vec![UsageScenario {
+                description: "根据设备手册排查故障".to_string(),
+                example_query: "水泵报E1错误怎么处理？".to_string(),
+                suggested_call: Some(
+                    r#"{"tool": "search_knowledge", "arguments": {"query": "E1 错误", "device_type": "pump"}}"#
+                        .to_string(),
+                ),
+            }],
+            relationships: ToolRelationships {
+                call_after: vec![],
+                output_to: vec![],
+                exclusive_with: vec![],
+            },
+            deprecated: false,
+            replaced_by: None,
+            version: "1.0.0".to_string(),
+            examples: vec![],
+            response_format: Some("concise".to_string()),
+            namespace: Some("system".to_string()),
+        }
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        Some("system")
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolOutput> {
+        let query = args["query"]
+            .as_str()
+            .filter(|q| !q.trim().is_empty())
+            .ok_or_else(|| ToolError::InvalidArguments("query is required".to_string()))?;
+        let device_type = args["device_type"].as_str().filter(|t| !t.is_empty());
+        let top_k = args["top_k"]
+            .as_u64()
+            .map(|k| k.clamp(1, 20) as usize)
+            .unwrap_or(DEFAULT_TOP_K);
+
+        // Lexical and semantic matches are fused, so exact model numbers and
+        // paraphrases both find their chunks
+        let mut filter = RetrievalFilter::new();
+        if let Some(device_type) = device_type {
+            filter = filter.with_device_types(vec![device_type.to_string()]);
+        }
+        let query = RetrievalQuery::new(query, top_k).with_filter(filter);
+        let items = self
+            .retriever
+            .retrieve(&query)
+            .await
+            .map_err(|e| ToolError::Execution(format!("Knowledge search failed: {}", e)))?;
+
+        let results: Vec<Value> = items
+            .into_iter()
+            .filter_map(|item| match item.content {
+                RetrievedContent::Knowledge(entry) => Some(serde_json::json!({
+                    "id": entry.id,
+                    "title": entry.title,
+                    "content": entry.content,
+                    "category": entry.category.as_str(),
+                    "citation": entry.citation(),
+                    "score": item.score,
+                })),
+                RetrievedContent::Conversation(_) => None,
+            })
+            .collect();
+
+        Ok(ToolOutput::success(serde_json::json!({
+            "count": results.len(),
+            "results": results,
+        })))
+    }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use neomind_memory::{KnowledgeCategory, KnowledgeEntry, TieredMemory};
+
+    #[tokio::test]
+    async fn test_search_knowledge_returns_citations() {
+        let memory = TieredMemory::new();
+        let entry = KnowledgeEntry::new(
+            "pump.pdf (p. 3)",
+            "Prime the pump before the first start.",
+            KnowledgeCategory::DeviceManual,
+        )
+        .with_tags(vec!["pump".to_string()])
+        .with_metadata(serde_json::json!({"source": "pump.pdf", "page": 3}));
+        memory.add_knowledge(entry).await.unwrap();
+
+        let tool =
+            SearchKnowledgeTool::new(memory.long_term_ref().clone(), memory.embedding_model());
+        let output = tool
+            .execute(serde_json::json!({"query": "prime pump", "device_type": "pump"}))
+            .await
+            .unwrap();
+        assert_eq!(output.data["count"], 1);
+        assert_eq!(output.data["results"][0]["citation"], "pump.pdf, p. 3");
+
+        let output = tool
+            .execute(serde_json::json!({"query": "prime pump", "device_type": "valve"}))
+            .await
+            .unwrap();
+        assert_eq!(output.data["count"], 0);
+
+        assert!(tool.execute(serde_json::json!({})).await.is_err());
+    }
+}
@@ -55,6 +55,8 @@ pub mod agent_tools;
 pub mod core_tools;
 pub mod error;
 pub mod extension_tools;
+pub mod knowledge_tools;
+pub mod mcp;
 pub mod real;
 pub mod registry;
 pub mod simplified;
@@ -166,6 +168,12 @@ pub use agent_tools::{
     GetAgentExecutionDetailTool, GetAgentExecutionsTool, GetAgentTool, ListAgentsTool,
 };
 
+// ============================================================================
+// Knowledge Tools
+// ============================================================================
+
+pub use knowledge_tools::SearchKnowledgeTool;
+
 // ============================================================================
 // Extension Tools
 // ============================================================================
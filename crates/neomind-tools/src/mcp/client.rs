@@ -0,0 +1,680 @@
+//! MCP client for external servers.
+//!
+//! [`McpClient`] connects to a server over stdio (a child process speaking
+//! newline-delimited JSON-RPC) or Streamable HTTP, performs the
+//! initialization handshake and lists and calls the server's tools.
+
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, ACCEPT, CONTENT_TYPE};
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::sync::{oneshot, Mutex as AsyncMutex};
+use tokio::task::JoinHandle;
+
+use neomind_storage::{McpServerConfig, McpTransportConfig};
+
+use super::{
+    JsonRpcError, JsonRpcRequest, JsonRpcResponse, McpTool, PROTOCOL_VERSION,
+    SUPPORTED_PROTOCOL_VERSIONS,
+};
+
+/// Header carrying the Streamable HTTP session.
+const SESSION_HEADER: &str = "mcp-session-id";
+
+/// Header carrying the negotiated protocol revision.
+const PROTOCOL_VERSION_HEADER: &str = "mcp-protocol-version";
+
+/// MCP client errors.
+#[derive(Debug, Clone, thiserror::Error)]
+pub enum McpClientError {
+    /// The server could not be started or reached
+    #[error("Failed to connect: {0}")]
+    Connect(String),
+
+    /// The connection was closed by the server
+    #[error("Connection closed")]
+    Closed,
+
+    /// The connection was already closed, so the request was not sent
+    #[error("Connection closed before sending")]
+    NotSent,
+
+    /// The server no longer knows the session and did not handle the request
+    #[error("Session expired")]
+    SessionExpired,
+
+    /// No response within the configured timeout
+    #[error("Request timed out after {0:?}")]
+    Timeout(Duration),
+
+    /// I/O or HTTP failure
+    #[error("Transport error: {0}")]
+    Transport(String),
+
+    /// The server answered with a JSON-RPC error
+    #[error(transparent)]
+    Rpc(#[from] JsonRpcError),
+
+    /// The server answered with something that is not a valid response
+    #[error("Invalid response: {0}")]
+    InvalidResponse(String),
+}
+
+impl McpClientError {
+    /// Whether the connection must be re-established before the next request.
+    pub fn is_connection_error(&self) -> bool {
+        matches!(
+            self,
+            Self::Connect(_)
+                | Self::Closed
+                | Self::NotSent
+                | Self::SessionExpired
+                | Self::Transport(_)
+        )
+    }
+
+    /// Whether the request certainly did not reach the server, so sending it
+    /// again cannot repeat its effects.
+    pub fn is_unsent(&self) -> bool {
+        matches!(
+            self,
+            Self::Connect(_) | Self::NotSent | Self::SessionExpired
+        )
+    }
+}
+
+type ClientResult<T> = std::result::Result<T, McpClientError>;
+
+/// A way of exchanging JSON-RPC messages with a server.
+#[async_trait]
+trait Transport: Send + Sync {
+    /// Send a message; requests wait for the matching response.
+    async fn send(
+        &self,
+        message: JsonRpcRequest,
+        timeout: Duration,
+    ) -> ClientResult<Option<JsonRpcResponse>>;
+
+    /// Whether the connection is known to be unusable.
+    fn is_closed(&self) -> bool;
+
+    /// Shut the connection down.
+    async fn close(&self);
+
+    /// Remember the protocol revision negotiated during initialization.
+    fn set_protocol_version(&self, _version: &str) {}
+}
+
+/// Answer a request sent by the server; notifications need no answer.
+fn answer_server_request(request: &JsonRpcRequest) -> Option<JsonRpcResponse> {
+    let id = request.id.clone()?;
+    Some(match request.method.as_str() {
+        "ping" => JsonRpcResponse::success(id, json!({})),
+        method => JsonRpcResponse::failure(id, JsonRpcError::method_not_found(method)),
+    })
+}
+
+/// Find the response to `id` in a message, which may be a batch.
+fn find_response(message: Value, id: &Value) -> Option<JsonRpcResponse> {
+    let candidates = match message {
+        Value::Array(items) => items,
+        item => vec![item],
+    };
+    candidates
+        .into_iter()
+        .filter(|item| item.get("method").is_none())
+        .filter_map(|item| serde_json::from_value::<JsonRpcResponse>(item).ok())
+        .find(|response| &response.id == id)
+}
+
+type SharedWriter = Arc<AsyncMutex<Box<dyn AsyncWrite + Send + Unpin>>>;
+type PendingRequests = Arc<Mutex<HashMap<String, oneshot::Sender<JsonRpcResponse>>>>;
+
+async fn write_line<T: serde::Serialize>(
+    writer: &SharedWriter,
+    message: &T,
+) -> std::io::Result<()> {
+    let mut line = serde_json::to_vec(message)?;
+    line.push(b'\n');
+    let mut writer = writer.lock().await;
+    writer.write_all(&line).await?;
+    writer.flush().await
+}
+
+/// Newline-delimited JSON-RPC over a byte stream pair.
+struct StdioTransport {
+    writer: SharedWriter,
+    pending: PendingRequests,
+    closed: Arc<AtomicBool>,
+    reader: JoinHandle<()>,
+    child: AsyncMutex<Option<tokio::process::Child>>,
+}
+
+impl StdioTransport {
+    /// Launch a server process; it is killed when the transport is dropped.
+    fn spawn(command: &str, args: &[String], env: &HashMap<String, String>) -> ClientResult<Self> {
+        let mut child = tokio::process::Command::new(command)
+            .args(args)
+            .envs(env)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| McpClientError::Connect(format!("{}: {}", command, e)))?;
+
+        let (Some(stdin), Some(stdout)) = (child.stdin.take(), child.stdout.take()) else {
+            return Err(McpClientError::Connect(format!(
+                "{}: stdio not available",
+                command
+            )));
+        };
+
+        if let Some(stderr) = child.stderr.take() {
+            let command = command.to_string();
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(stderr).lines();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    tracing::debug!(command = %command, "MCP server: {}", line);
+                }
+            });
+        }
+
+        let mut transport = Self::from_io(stdout, stdin);
+        transport.child = AsyncMutex::new(Some(child));
+        Ok(transport)
+    }
+
+    fn from_io<R, W>(reader: R, writer: W) -> Self
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let writer: SharedWriter = Arc::new(AsyncMutex::new(Box::new(writer)));
+        let pending = PendingRequests::default();
+        let closed = Arc::new(AtomicBool::new(false));
+        let reader = tokio::spawn(Self::read_loop(
+            reader,
+            writer.clone(),
+            pending.clone(),
+            closed.clone(),
+        ));
+
+        Self {
+            writer,
+            pending,
+            closed,
+            reader,
+            child: AsyncMutex::new(None),
+        }
+    }
+
+    /// Route responses to waiting requests and answer server requests.
+    async fn read_loop<R: AsyncRead + Unpin>(
+        reader: R,
+        writer: SharedWriter,
+        pending: PendingRequests,
+        closed: Arc<AtomicBool>,
+    ) {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let message: Value = match serde_json::from_str(line) {
+                Ok(message) => message,
+                Err(e) => {
+                    tracing::debug!("Ignoring malformed MCP message: {}", e);
+                    continue;
+                }
+            };
+
+            if message.get("method").is_some() {
+                if let Ok(request) = serde_json::from_value::<JsonRpcRequest>(message) {
+                    if let Some(reply) = answer_server_request(&request) {
+                        let _ = write_line(&writer, &reply).await;
+                    }
+                }
+                continue;
+            }
+
+            match serde_json::from_value::<JsonRpcResponse>(message) {
+                Ok(response) => {
+                    let waiter = pending.lock().unwrap().remove(&response.id.to_string());
+                    if let Some(waiter) = waiter {
+                        let _ = waiter.send(response);
+                    }
+                }
+                Err(e) => tracing::debug!("Ignoring invalid MCP response: {}", e),
+            }
+        }
+
+        closed.store(true, Ordering::SeqCst);
+        // Dropping the senders fails every outstanding request
+        pending.lock().unwrap().clear();
+    }
+}
+
+#[async_trait]
+impl Transport for StdioTransport {
+    async fn send(
+        &self,
+        message: JsonRpcRequest,
+        timeout: Duration,
+    ) -> ClientResult<Option<JsonRpcResponse>> {
+        if self.is_closed() {
+            return Err(McpClientError::NotSent);
+        }
+
+        let Some(key) = message.id.as_ref().map(|id| id.to_string()) else {
+            write_line(&self.writer, &message)
+                .await
+                .map_err(|e| McpClientError::Transport(e.to_string()))?;
+            return Ok(None);
+        };
+
+        let (tx, rx) = oneshot::channel();
+        self.pending.lock().unwrap().insert(key.clone(), tx);
+        if self.is_closed() {
+            self.pending.lock().unwrap().remove(&key);
+            return Err(McpClientError::NotSent);
+        }
+        if let Err(e) = write_line(&self.writer, &message).await {
+            self.pending.lock().unwrap().remove(&key);
+            self.closed.store(true, Ordering::SeqCst);
+            return Err(McpClientError::Transport(e.to_string()));
+        }
+
+        match tokio::time::timeout(timeout, rx).await {
+            Ok(Ok(response)) => Ok(Some(response)),
+            Ok(Err(_)) => Err(McpClientError::Closed),
+            Err(_) => {
+                self.pending.lock().unwrap().remove(&key);
+                Err(McpClientError::Timeout(timeout))
+            }
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    async fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        self.reader.abort();
+        if let Some(mut child) = self.child.lock().await.take() {
+            let _ = child.kill().await;
+        }
+    }
+}
+
+impl Drop for StdioTransport {
+    fn drop(&mut self) {
+        self.reader.abort();
+    }
+}
+
+/// Streamable HTTP: one POST per message, answered with JSON or SSE.
+struct HttpTransport {
+    client: reqwest::Client,
+    url: String,
+    headers: HeaderMap,
+    session_id: Mutex<Option<String>>,
+    protocol_version: Mutex<Option<String>>,
+    closed: AtomicBool,
+}
+
+impl HttpTransport {
+    fn new(url: &str, headers: &HashMap<String, String>) -> ClientResult<Self> {
+        let mut header_map = HeaderMap::new();
+        for (name, value) in headers {
+            let header_name = HeaderName::from_bytes(name.as_bytes())
+                .map_err(|e| McpClientError::Connect(format!("Invalid header {}: {}", name, e)))?;
+            let header_value = HeaderValue::from_str(value)
+                .map_err(|e| McpClientError::Connect(format!("Invalid header {}: {}", name, e)))?;
+            header_map.insert(header_name, header_value);
+        }
+
+        Ok(Self {
+            client: reqwest::Client::new(),
+            url: url.to_string(),
+            headers: header_map,
+            session_id: Mutex::new(None),
+            protocol_version: Mutex::new(None),
+            closed: AtomicBool::new(false),
+        })
+    }
+
+    fn request(&self, method: reqwest::Method) -> reqwest::RequestBuilder {
+        let mut request = self
+            .client
+            .request(method, &self.url)
+            .headers(self.headers.clone());
+        if let Some(session_id) = self.session_id.lock().unwrap().clone() {
+            request = request.header(SESSION_HEADER, session_id);
+        }
+        if let Some(version) = self.protocol_version.lock().unwrap().clone() {
+            request = request.header(PROTOCOL_VERSION_HEADER, version);
+        }
+        request
+    }
+
+    /// Read an SSE stream until the response to `id` arrives.
+    async fn read_event_stream(
+        response: reqwest::Response,
+        id: &Value,
+        timeout: Duration,
+    ) -> ClientResult<JsonRpcResponse> {
+        let mut stream = response.bytes_stream();
+        let mut buffer: Vec<u8> = Vec::new();
+        let mut data = String::new();
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk.map_err(|e| map_http_error(e, timeout))?;
+            buffer.extend_from_slice(&chunk);
+
+            while let Some(end) = buffer.iter().position(|b| *b == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let line = line.trim_end_matches(['\r', '\n']);
+
+                if line.is_empty() {
+                    // Blank line dispatches the event
+                    if let Ok(message) = serde_json::from_str::<Value>(&data) {
+                        if let Some(response) = find_response(message, id) {
+                            return Ok(response);
+                        }
+                    }
+                    data.clear();
+                } else if let Some(value) = line.strip_prefix("data:") {
+                    if !data.is_empty() {
+                        data.push('\n');
+                    }
+                    data.push_str(value.strip_prefix(' ').unwrap_or(value));
+                }
+            }
+        }
+
+        Err(McpClientError::InvalidResponse(
+            "Event stream ended without a response".to_string(),
+        ))
+    }
+}
+
+fn map_http_error(error: reqwest::Error, timeout: Duration) -> McpClientError {
+    if error.is_timeout() {
+        McpClientError::Timeout(timeout)
+    } else if error.is_connect() {
+        McpClientError::Connect(error.to_string())
+    } else {
+        McpClientError::Transport(error.to_string())
+    }
+}
+
+#[async_trait]
+impl Transport for HttpTransport {
+    async fn send(
+        &self,
+        message: JsonRpcRequest,
+        timeout: Duration,
+    ) -> ClientResult<Option<JsonRpcResponse>> {
+        if self.is_closed() {
+            return Err(McpClientError::NotSent);
+        }
+
+        let had_session = self.session_id.lock().unwrap().is_some();
+        let response = self
+            .request(reqwest::Method::POST)
+            .header(ACCEPT, "application/json, text/event-stream")
+            .json(&message)
+            .timeout(timeout)
+            .send()
+            .await
+            .map_err(|e| map_http_error(e, timeout))?;
+
+        if let Some(session_id) = response
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|v| v.to_str().ok())
+        {
+            *self.session_id.lock().unwrap() = Some(session_id.to_string());
+        }
+
+        let status = response.status();
+        if status == reqwest::StatusCode::NOT_FOUND && had_session {
+            // The server no longer knows the session
+            self.closed.store(true, Ordering::SeqCst);
+            return Err(McpClientError::SessionExpired);
+        }
+
+        let Some(id) = message.id else {
+            if status.is_success() {
+                return Ok(None);
+            }
+            return Err(McpClientError::Transport(format!("HTTP {}", status)));
+        };
+
+        let is_event_stream = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .is_some_and(|v| v.starts_with("text/event-stream"));
+        if status.is_success() && is_event_stream {
+            return Self::read_event_stream(response, &id, timeout)
+                .await
+                .map(Some);
+        }
+
+        let body = response
+            .text()
+            .await
+            .map_err(|e| map_http_error(e, timeout))?;
+        // Error statuses may still carry a JSON-RPC error
+        let parsed = serde_json::from_str::<Value>(&body)
+            .ok()
+            .and_then(|message| find_response(message, &id));
+        match parsed {
+            Some(response) => Ok(Some(response)),
+            None if status.is_success() => Err(McpClientError::InvalidResponse(body)),
+            None => Err(McpClientError::Transport(format!(
+                "HTTP {}: {}",
+                status, body
+            ))),
+        }
+    }
+
+    fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    async fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+        if self.session_id.lock().unwrap().is_some() {
+            // Best effort: let the server release the session
+            let _ = self
+                .request(reqwest::Method::DELETE)
+                .timeout(Duration::from_secs(5))
+                .send()
+                .await;
+        }
+    }
+
+    fn set_protocol_version(&self, version: &str) {
+        *self.protocol_version.lock().unwrap() = Some(version.to_string());
+    }
+}
+
+/// An initialized connection to an MCP server.
+pub struct McpClient {
+    server_id: String,
+    transport: Box<dyn Transport>,
+    timeout: Duration,
+    next_id: AtomicU64,
+    server_info: Value,
+    tools: Vec<McpTool>,
+}
+
+impl McpClient {
+    /// Connect to a configured server and discover its tools.
+    pub async fn connect(config: &McpServerConfig) -> ClientResult<Self> {
+        let transport: Box<dyn Transport> = match &config.transport {
+            McpTransportConfig::Stdio { command, args, env } => {
+                Box::new(StdioTransport::spawn(command, args, env)?)
+            }
+            McpTransportConfig::Http { url, headers } => {
+                Box::new(HttpTransport::new(url, headers)?)
+            }
+        };
+        Self::initialize(
+            config.id.clone(),
+            transport,
+            Duration::from_secs(config.timeout_secs),
+        )
+        .await
+    }
+
+    /// Connect over an existing newline-delimited JSON-RPC stream pair.
+    pub async fn connect_io<R, W>(
+        server_id: impl Into<String>,
+        reader: R,
+        writer: W,
+        timeout: Duration,
+    ) -> ClientResult<Self>
+    where
+        R: AsyncRead + Send + Unpin + 'static,
+        W: AsyncWrite + Send + Unpin + 'static,
+    {
+        let transport = Box::new(StdioTransport::from_io(reader, writer));
+        Self::initialize(server_id.into(), transport, timeout).await
+    }
+
+    async fn initialize(
+        server_id: String,
+        transport: Box<dyn Transport>,
+        timeout: Duration,
+    ) -> ClientResult<Self> {
+        let mut client = Self {
+            server_id,
+            transport,
+            timeout,
+            next_id: AtomicU64::new(1),
+            server_info: Value::Null,
+            tools: Vec::new(),
+        };
+
+        let result = client
+            .request(
+                "initialize",
+                Some(json!({
+                    "protocolVersion": PROTOCOL_VERSION,
+                    "capabilities": {},
+                    "clientInfo": { "name": "neomind", "version": env!("CARGO_PKG_VERSION") },
+                })),
+            )
+            .await?;
+
+        let version = result
+            .get("protocolVersion")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default();
+        if !SUPPORTED_PROTOCOL_VERSIONS.contains(&version) {
+            client.close().await;
+            return Err(McpClientError::Connect(format!(
+                "Unsupported protocol version: {}",
+                version
+            )));
+        }
+        client.transport.set_protocol_version(version);
+        client
+            .transport
+            .send(
+                JsonRpcRequest::notification("notifications/initialized", None),
+                timeout,
+            )
+            .await?;
+
+        client.server_info = result.get("serverInfo").cloned().unwrap_or(Value::Null);
+        client.tools = client.list_tools().await?;
+        tracing::info!(
+            server = %client.server_id,
+            tools = client.tools.len(),
+            "Connected to MCP server"
+        );
+        Ok(client)
+    }
+
+    /// Fetch every page of the server's tool list.
+    pub async fn list_tools(&self) -> ClientResult<Vec<McpTool>> {
+        let mut tools = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let params = cursor.as_ref().map(|cursor| json!({ "cursor": cursor }));
+            let result = self.request("tools/list", params).await?;
+            let page: Vec<McpTool> =
+                serde_json::from_value(result.get("tools").cloned().unwrap_or(json!([])))
+                    .map_err(|e| McpClientError::InvalidResponse(format!("tools/list: {}", e)))?;
+            tools.extend(page);
+
+            let next = result
+                .get("nextCursor")
+                .and_then(|c| c.as_str())
+                .map(String::from);
+            if next.is_none() || next == cursor {
+                return Ok(tools);
+            }
+            cursor = next;
+        }
+    }
+
+    /// Call a tool and return the raw `tools/call` result.
+    pub async fn call_tool(&self, name: &str, arguments: Value) -> ClientResult<Value> {
+        self.request(
+            "tools/call",
+            Some(json!({ "name": name, "arguments": arguments })),
+        )
+        .await
+    }
+
+    async fn request(&self, method: &str, params: Option<Value>) -> ClientResult<Value> {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let response = self
+            .transport
+            .send(JsonRpcRequest::new(id, method, params), self.timeout)
+            .await?
+            .ok_or_else(|| McpClientError::InvalidResponse(format!("No response to {}", method)))?;
+        Ok(response.into_result()?)
+    }
+
+    /// The configured server ID.
+    pub fn server_id(&self) -> &str {
+        &self.server_id
+    }
+
+    /// `serverInfo` reported during initialization.
+    pub fn server_info(&self) -> &Value {
+        &self.server_info
+    }
+
+    /// Tools discovered during initialization.
+    pub fn tools(&self) -> &[McpTool] {
+        &self.tools
+    }
+
+    /// Whether the connection is still usable.
+    pub fn is_connected(&self) -> bool {
+        !self.transport.is_closed()
+    }
+
+    /// Shut the connection down.
+    pub async fn close(&self) {
+        self.transport.close().await;
+    }
+}
@@ -0,0 +1,390 @@
+//! Connections to the external MCP servers configured in settings.
+//!
+//! [`McpClientManager`] keeps one connection per enabled server and exposes
+//! each discovered tool as an [`McpRemoteTool`] named
+//! `mcp:{server_id}:{tool_name}`. Broken connections are re-established on
+//! the next call.
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::Mutex as AsyncMutex;
+
+use neomind_storage::McpServerConfig;
+
+use super::client::{McpClient, McpClientError};
+use super::{output_from_call_result, McpTool};
+use crate::error::{Result, ToolError};
+use crate::tool::{DynTool, Tool, ToolOutput};
+
+/// Minimum delay between failed connection attempts to the same server.
+const RECONNECT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Connection state of a configured server.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct McpServerStatus {
+    pub id: String,
+    pub name: String,
+    pub enabled: bool,
+    pub connected: bool,
+    pub tool_count: usize,
+    pub last_error: Option<String>,
+}
+
+/// Tools and errors remembered across reconnects.
+#[derive(Default)]
+struct ServerInfo {
+    tools: Vec<McpTool>,
+    last_error: Option<String>,
+    last_failure: Option<Instant>,
+}
+
+struct ServerConnection {
+    config: McpServerConfig,
+    client: AsyncMutex<Option<Arc<McpClient>>>,
+    info: RwLock<ServerInfo>,
+}
+
+impl ServerConnection {
+    fn new(config: McpServerConfig) -> Self {
+        Self {
+            config,
+            client: AsyncMutex::new(None),
+            info: RwLock::new(ServerInfo::default()),
+        }
+    }
+
+    /// The live client, connecting first if necessary.
+    async fn client(&self) -> std::result::Result<Arc<McpClient>, McpClientError> {
+        let mut slot = self.client.lock().await;
+        if let Some(client) = slot.as_ref() {
+            if client.is_connected() {
+                return Ok(client.clone());
+            }
+            *slot = None;
+        }
+
+        {
+            let info = self.info.read().unwrap();
+            if let Some(failed_at) = info.last_failure {
+                if failed_at.elapsed() < RECONNECT_BACKOFF {
+                    return Err(McpClientError::Connect(
+                        info.last_error.clone().unwrap_or_default(),
+                    ));
+                }
+            }
+        }
+
+        match McpClient::connect(&self.config).await {
+            Ok(client) => {
+                let client = Arc::new(client);
+                let mut info = self.info.write().unwrap();
+                info.tools = client.tools().to_vec();
+                info.last_error = None;
+                info.last_failure = None;
+                *slot = Some(client.clone());
+                Ok(client)
+            }
+            Err(e) => {
+                tracing::warn!(server = %self.config.id, "MCP server connection failed: {}", e);
+                let mut info = self.info.write().unwrap();
+                info.last_error = Some(e.to_string());
+                info.last_failure = Some(Instant::now());
+                Err(e)
+            }
+        }
+    }
+
+    /// Forget a client whose connection failed.
+    async fn discard(&self, client: &Arc<McpClient>, error: &McpClientError) {
+        let mut slot = self.client.lock().await;
+        if slot
+            .as_ref()
+            .is_some_and(|current| Arc::ptr_eq(current, client))
+        {
+            *slot = None;
+            self.info.write().unwrap().last_error = Some(error.to_string());
+        }
+        drop(slot);
+        client.close().await;
+    }
+
+    async fn close(&self) {
+        if let Some(client) = self.client.lock().await.take() {
+            client.close().await;
+        }
+    }
+
+    fn status(&self) -> McpServerStatus {
+        let connected = self
+            .client
+            .try_lock()
+            .ok()
+            .and_then(|slot| slot.as_ref().map(|client| client.is_connected()))
+            .unwrap_or(false);
+        let info = self.info.read().unwrap();
+        McpServerStatus {
+            id: self.config.id.clone(),
+            name: self.config.name.clone(),
+            enabled: self.config.enabled,
+            connected,
+            tool_count: info.tools.len(),
+            last_error: info.last_error.clone(),
+        }
+    }
+}
+
+/// Registry of external MCP server connections.
+#[derive(Default)]
+pub struct McpClientManager {
+    servers: RwLock<HashMap<String, Arc<ServerConnection>>>,
+}
+
+impl McpClientManager {
+    /// Create an empty manager.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Connect to all enabled servers concurrently.
+    ///
+    /// Servers that fail to connect stay registered and are retried when one
+    /// of their tools is called or the server is reconnected explicitly.
+    pub async fn start(&self, configs: Vec<McpServerConfig>) {
+        futures::future::join_all(configs.into_iter().map(|config| self.upsert(config))).await;
+    }
+
+    /// Add or replace a server and connect to it if it is enabled.
+    pub async fn upsert(&self, config: McpServerConfig) -> std::result::Result<(), McpClientError> {
+        let enabled = config.enabled;
+        let connection = Arc::new(ServerConnection::new(config));
+        let previous = self
+            .servers
+            .write()
+            .unwrap()
+            .insert(connection.config.id.clone(), connection.clone());
+        if let Some(previous) = previous {
+            previous.close().await;
+        }
+
+        if enabled {
+            connection.client().await?;
+        }
+        Ok(())
+    }
+
+    /// Remove a server and close its connection.
+    pub async fn remove(&self, id: &str) -> bool {
+        let removed = self.servers.write().unwrap().remove(id);
+        match removed {
+            Some(connection) => {
+                connection.close().await;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Drop the current connection and connect again, ignoring the backoff.
+    pub async fn reconnect(&self, id: &str) -> std::result::Result<(), McpClientError> {
+        let connection = self
+            .connection(id)
+            .ok_or_else(|| McpClientError::Connect(format!("Unknown MCP server: {}", id)))?;
+        if !connection.config.enabled {
+            return Err(McpClientError::Connect(format!(
+                "MCP server {} is disabled",
+                id
+            )));
+        }
+        connection.close().await;
+        connection.info.write().unwrap().last_failure = None;
+        connection.client().await.map(|_| ())
+    }
+
+    fn connection(&self, id: &str) -> Option<Arc<ServerConnection>> {
+        self.servers.read().unwrap().get(id).cloned()
+    }
+
+    /// Status of every configured server, ordered by ID.
+    pub fn status(&self) -> Vec<McpServerStatus> {
+        let mut status: Vec<McpServerStatus> = self
+            .servers
+            .read()
+            .unwrap()
+            .values()
+            .map(|connection| connection.status())
+            .collect();
+        status.sort_by(|a, b| a.id.cmp(&b.id));
+        status
+    }
+
+    /// Status of one server.
+    pub fn server_status(&self, id: &str) -> Option<McpServerStatus> {
+        self.connection(id).map(|connection| connection.status())
+    }
+
+    /// Tools last discovered on a server.
+    pub fn server_tools(&self, id: &str) -> Option<Vec<McpTool>> {
+        self.connection(id)
+            .map(|connection| connection.info.read().unwrap().tools.clone())
+    }
+
+    /// Tools of all enabled servers.
+    pub fn tools(&self) -> Vec<DynTool> {
+        let servers = self.servers.read().unwrap();
+        let mut tools: Vec<DynTool> = Vec::new();
+        for connection in servers.values().filter(|c| c.config.enabled) {
+            for tool in connection.info.read().unwrap().tools.iter() {
+                tools.push(Arc::new(McpRemoteTool::new(
+                    connection.clone(),
+                    tool.clone(),
+                )));
+            }
+        }
+        tools
+    }
+
+    /// Look up one tool of an enabled server.
+    pub fn tool(&self, server_id: &str, tool_name: &str) -> Option<DynTool> {
+        let connection = self.connection(server_id)?;
+        if !connection.config.enabled {
+            return None;
+        }
+        let tool = connection
+            .info
+            .read()
+            .unwrap()
+            .tools
+            .iter()
+            .find(|tool| tool.name == tool_name)
+            .cloned()?;
+        Some(Arc::new(McpRemoteTool::new(connection, tool)))
+    }
+}
+
+static MCP_CLIENT_MANAGER: OnceLock<Arc<McpClientManager>> = OnceLock::new();
+
+/// Get the global MCP client manager.
+pub fn get_mcp_client_manager() -> Arc<McpClientManager> {
+    MCP_CLIENT_MANAGER
+        .get_or_init(|| Arc::new(McpClientManager::new()))
+        .clone()
+}
+
+/// A tool provided by an external MCP server.
+pub struct McpRemoteTool {
+    connection: Arc<ServerConnection>,
+    tool: McpTool,
+    /// Full tool name in format "mcp:{server_id}:{tool_name}"
+    full_name: String,
+    namespace: String,
+    description: String,
+}
+
+impl McpRemoteTool {
+    fn new(connection: Arc<ServerConnection>, tool: McpTool) -> Self {
+        let server_id = &connection.config.id;
+        let full_name = format!("mcp:{}:{}", server_id, tool.name);
+        let namespace = format!("mcp:{}", server_id);
+        let description = format!(
+            "[{}] {}",
+            connection.config.name,
+            tool.description.as_deref().unwrap_or(&tool.name)
+        );
+        Self {
+            connection,
+            tool,
+            full_name,
+            namespace,
+            description,
+        }
+    }
+
+    /// The server providing this tool.
+    pub fn server_id(&self) -> &str {
+        &self.connection.config.id
+    }
+
+    /// The tool name on the server.
+    pub fn remote_name(&self) -> &str {
+        &self.tool.name
+    }
+}
+
+#[async_trait]
+impl Tool for McpRemoteTool {
+    fn name(&self) -> &str {
+        &self.full_name
+    }
+
+    fn description(&self) -> &str {
+        &self.description
+    }
+
+    fn parameters(&self) -> Value {
+        self.tool.input_schema.clone()
+    }
+
+    async fn execute(&self, args: Value) -> Result<ToolOutput> {
+        let args = if args.is_null() { json!({}) } else { args };
+        self.validate_args(&args)?;
+
+        // One retry covers a connection that broke since the last call. A
+        // call that may have reached the server is only repeated for
+        // read-only tools, so side effects never run twice.
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            let client = self.connection.client().await.map_err(|e| {
+                ToolError::Execution(format!(
+                    "MCP server '{}' unavailable: {}",
+                    self.server_id(),
+                    e
+                ))
+            })?;
+
+            match client.call_tool(&self.tool.name, args.clone()).await {
+                Ok(result) => return Ok(output_from_call_result(&result)),
+                Err(McpClientError::Timeout(_)) => return Err(ToolError::Timeout),
+                Err(e) if e.is_connection_error() => {
+                    self.connection.discard(&client, &e).await;
+                    if attempts > 1 || !(e.is_unsent() || self.is_read_only()) {
+                        return Err(ToolError::Execution(e.to_string()));
+                    }
+                }
+                Err(e) => return Err(ToolError::Execution(e.to_string())),
+            }
+        }
+    }
+
+    fn is_read_only(&self) -> bool {
+        self.tool
+            .annotations
+            .as_ref()
+            .and_then(|a| a.read_only_hint)
+            .unwrap_or(false)
+    }
+
+    fn namespace(&self) -> Option<&str> {
+        Some(&self.namespace)
+    }
+
+    /// Check the arguments against the schema advertised by the server.
+    fn validate_args(&self, args: &Value) -> Result<()> {
+        let errors = neomind_llm::structured::validate(&self.tool.input_schema, args);
+        if errors.is_empty() {
+            return Ok(());
+        }
+        Err(ToolError::InvalidArguments(
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("; "),
+        ))
+    }
+}
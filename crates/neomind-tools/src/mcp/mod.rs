@@ -0,0 +1,337 @@
+//! Model Context Protocol (MCP) message types.
+//!
+//! JSON-RPC 2.0 envelopes and the mapping between [`Tool`] definitions and
+//! outputs and MCP tool schemas and `tools/call` results. The [`client`] and
+//! [`manager`] modules import the tools of external MCP servers.
+
+pub mod client;
+pub mod manager;
+
+pub use client::{McpClient, McpClientError};
+pub use manager::{get_mcp_client_manager, McpClientManager, McpRemoteTool, McpServerStatus};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::tool::{Tool, ToolOutput};
+
+/// Protocol revision implemented by NeoMind.
+pub const PROTOCOL_VERSION: &str = "2025-06-18";
+
+/// Protocol revisions accepted during initialization, newest first.
+pub const SUPPORTED_PROTOCOL_VERSIONS: &[&str] = &["2025-06-18", "2025-03-26", "2024-11-05"];
+
+/// JSON-RPC and MCP error codes.
+pub mod error_codes {
+    /// The message is not valid JSON
+    pub const PARSE_ERROR: i64 = -32700;
+    /// The message is not a valid request
+    pub const INVALID_REQUEST: i64 = -32600;
+    /// The method does not exist
+    pub const METHOD_NOT_FOUND: i64 = -32601;
+    /// Invalid method parameters
+    pub const INVALID_PARAMS: i64 = -32602;
+    /// Internal error
+    pub const INTERNAL_ERROR: i64 = -32603;
+    /// The requested resource does not exist
+    pub const RESOURCE_NOT_FOUND: i64 = -32002;
+}
+
+/// A JSON-RPC request, or a notification when `id` is absent.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcRequest {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub params: Option<Value>,
+}
+
+impl JsonRpcRequest {
+    /// Create a request
+    pub fn new(id: impl Into<Value>, method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: Some(id.into()),
+            method: method.into(),
+            params,
+        }
+    }
+
+    /// Create a notification
+    pub fn notification(method: impl Into<String>, params: Option<Value>) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id: None,
+            method: method.into(),
+            params,
+        }
+    }
+
+    /// Whether no response is expected
+    pub fn is_notification(&self) -> bool {
+        self.id.is_none()
+    }
+}
+
+/// A JSON-RPC response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct JsonRpcResponse {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<JsonRpcError>,
+}
+
+impl JsonRpcResponse {
+    /// Create a successful response
+    pub fn success(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: Some(result),
+            error: None,
+        }
+    }
+
+    /// Create an error response
+    pub fn failure(id: Value, error: JsonRpcError) -> Self {
+        Self {
+            jsonrpc: "2.0".to_string(),
+            id,
+            result: None,
+            error: Some(error),
+        }
+    }
+
+    /// The result, or the error reported by the peer
+    pub fn into_result(self) -> Result<Value, JsonRpcError> {
+        match self.error {
+            Some(error) => Err(error),
+            None => Ok(self.result.unwrap_or(Value::Null)),
+        }
+    }
+}
+
+/// A JSON-RPC error object.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, thiserror::Error)]
+#[error("JSON-RPC error {code}: {message}")]
+pub struct JsonRpcError {
+    pub code: i64,
+    pub message: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<Value>,
+}
+
+impl JsonRpcError {
+    /// Create an error without data
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    /// Unknown method
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(
+            error_codes::METHOD_NOT_FOUND,
+            format!("Method not found: {}", method),
+        )
+    }
+
+    /// Invalid method parameters
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(error_codes::INVALID_PARAMS, message)
+    }
+}
+
+/// Behaviour hints attached to an MCP tool.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpToolAnnotations {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// The tool does not modify its environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub read_only_hint: Option<bool>,
+    /// The tool may perform destructive updates
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destructive_hint: Option<bool>,
+}
+
+/// A tool as advertised by `tools/list`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct McpTool {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    /// JSON Schema of the arguments object
+    pub input_schema: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub annotations: Option<McpToolAnnotations>,
+}
+
+impl McpTool {
+    /// Describe a NeoMind tool
+    pub fn from_tool(tool: &dyn Tool) -> Self {
+        let definition = tool.definition();
+        Self {
+            name: definition.name,
+            description: Some(definition.description),
+            input_schema: input_schema(definition.parameters),
+            annotations: Some(McpToolAnnotations {
+                title: None,
+                read_only_hint: Some(tool.is_read_only()),
+                destructive_hint: None,
+            }),
+        }
+    }
+}
+
+/// MCP requires an object schema; tools without parameters get an empty one.
+fn input_schema(parameters: Value) -> Value {
+    match parameters {
+        Value::Object(mut schema) => {
+            schema
+                .entry("type")
+                .or_insert_with(|| Value::String("object".to_string()));
+            Value::Object(schema)
+        }
+        _ => json!({ "type": "object", "properties": {} }),
+    }
+}
+
+/// Build a `tools/call` result from a tool output.
+pub fn call_result(output: &ToolOutput) -> Value {
+    if !output.success {
+        return error_result(output.error.as_deref().unwrap_or("Tool execution failed"));
+    }
+
+    let text = match &output.data {
+        Value::String(text) => text.clone(),
+        data => serde_json::to_string(data).unwrap_or_default(),
+    };
+    let mut result = json!({
+        "content": [{ "type": "text", "text": text }],
+        "isError": false,
+    });
+    if output.data.is_object() {
+        result["structuredContent"] = output.data.clone();
+    }
+    result
+}
+
+/// A `tools/call` result reporting a failure to the model.
+pub fn error_result(message: &str) -> Value {
+    json!({
+        "content": [{ "type": "text", "text": message }],
+        "isError": true,
+    })
+}
+
+/// Convert a `tools/call` result from an external server into a tool output.
+pub fn output_from_call_result(result: &Value) -> ToolOutput {
+    let content = result
+        .get("content")
+        .and_then(|c| c.as_array())
+        .cloned()
+        .unwrap_or_default();
+    let texts: Vec<&str> = content
+        .iter()
+        .filter_map(|item| item.get("text").and_then(|t| t.as_str()))
+        .collect();
+    let text = texts.join("\n");
+
+    if result
+        .get("isError")
+        .and_then(|e| e.as_bool())
+        .unwrap_or(false)
+    {
+        if text.is_empty() {
+            return ToolOutput::error("Tool execution failed");
+        }
+        return ToolOutput::error(text);
+    }
+
+    match result.get("structuredContent") {
+        Some(data) if !data.is_null() => ToolOutput::success(data.clone()),
+        // Non-text content (images, resources) is passed through as-is
+        _ if texts.len() < content.len() => ToolOutput::success(Value::Array(content)),
+        _ => ToolOutput::success(text),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Result;
+    use async_trait::async_trait;
+
+    struct EchoTool;
+
+    #[async_trait]
+    impl Tool for EchoTool {
+        fn name(&self) -> &str {
+            "echo"
+        }
+
+        fn description(&self) -> &str {
+            "Echo the arguments"
+        }
+
+        fn parameters(&self) -> Value {
+            json!({ "properties": { "text": { "type": "string" } } })
+        }
+
+        async fn execute(&self, args: Value) -> Result<ToolOutput> {
+            Ok(ToolOutput::success(args))
+        }
+
+        fn is_read_only(&self) -> bool {
+            true
+        }
+    }
+
+    #[tokio::test]
+    async fn test_tool_mapping() {
+        let tool = McpTool::from_tool(&EchoTool);
+        let value = serde_json::to_value(&tool).unwrap();
+        assert_eq!(value["inputSchema"]["type"], "object");
+        assert_eq!(value["annotations"]["readOnlyHint"], true);
+
+        let output = EchoTool.execute(json!({ "text": "hi" })).await.unwrap();
+        let result = call_result(&output);
+        assert_eq!(result["isError"], false);
+        assert_eq!(result["content"][0]["text"], r#"{"text":"hi"}"#);
+        assert_eq!(result["structuredContent"]["text"], "hi");
+
+        assert_eq!(output_from_call_result(&result).data["text"], "hi");
+
+        let result = call_result(&ToolOutput::error("boom"));
+        assert_eq!(result["isError"], true);
+        assert_eq!(result["content"][0]["text"], "boom");
+        let output = output_from_call_result(&result);
+        assert!(!output.success);
+        assert_eq!(output.error.as_deref(), Some("boom"));
+    }
+
+    #[test]
+    fn test_json_rpc_envelopes() {
+        let request: JsonRpcRequest =
+            serde_json::from_str(r#"{"jsonrpc":"2.0","method":"notifications/initialized"}"#)
+                .unwrap();
+        assert!(request.is_notification());
+
+        let response = JsonRpcResponse::failure(json!(1), JsonRpcError::method_not_found("x"));
+        let value = serde_json::to_value(&response).unwrap();
+        assert_eq!(value["error"]["code"], error_codes::METHOD_NOT_FOUND);
+        assert!(value.get("result").is_none());
+        assert!(response.into_result().is_err());
+    }
+}
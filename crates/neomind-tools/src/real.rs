@@ -296,6 +296,10 @@ impl Tool for QueryDataTool {
 
         Ok(ToolOutput::success_with_metadata(result, metadata))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for creating rules using real rule engine.
@@ -660,6 +664,10 @@ impl Tool for ListRulesTool {
             "rules": rule_list
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for deleting rules using real rule engine.
@@ -913,6 +921,10 @@ impl Tool for QueryRuleHistoryTool {
             "history": history_list
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Resolve user input (device ID, name, or nickname like "ne101") to the actual device_id.
@@ -1237,6 +1249,10 @@ impl Tool for GetDeviceDataTool {
             "metric_count": metrics_data.len()
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 #[cfg(test)]
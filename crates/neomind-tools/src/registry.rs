@@ -518,6 +518,21 @@ impl ToolRegistryBuilder {
             .with_get_agent_conversation_tool(agent_store)
     }
 
+    // ============================================================================
+    // Knowledge Tools
+    // ============================================================================
+
+    /// Add the knowledge base search tool.
+    pub fn with_knowledge_tools(
+        self,
+        long_term: Arc<neomind_memory::LongTermMemory>,
+        embedding: Arc<dyn neomind_memory::EmbeddingModel>,
+    ) -> Self {
+        self.with_tool(Arc::new(super::knowledge_tools::SearchKnowledgeTool::new(
+            long_term, embedding,
+        )))
+    }
+
     // ============================================================================
     // System Tools
     // ============================================================================
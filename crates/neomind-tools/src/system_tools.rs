@@ -149,6 +149,10 @@ impl Tool for SystemInfoTool {
 
         Ok(ToolOutput::success(result))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -298,6 +302,10 @@ impl Tool for SystemHelpTool {
 
         Ok(ToolOutput::success(result))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 impl SystemHelpTool {
@@ -1169,6 +1177,10 @@ impl Tool for ListAlertsTool {
             "alerts": alerts_json
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for acknowledging alerts.
@@ -1458,6 +1470,10 @@ impl Tool for ExportToCsvTool {
             "data": csv
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for exporting data to JSON format.
@@ -1588,6 +1604,10 @@ impl Tool for ExportToJsonTool {
             "data": json_str
         })))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 /// Tool for generating reports.
@@ -1751,6 +1771,10 @@ impl Tool for GenerateReportTool {
 
         Ok(ToolOutput::success(report))
     }
+
+    fn is_read_only(&self) -> bool {
+        true
+    }
 }
 
 // ============================================================================
@@ -214,6 +214,14 @@ pub trait Tool: Send + Sync {
         false
     }
 
+    /// Check if this tool only reads state.
+    ///
+    /// Tools that control devices or change rules, agents or configuration
+    /// keep the default so callers can restrict them.
+    fn is_read_only(&self) -> bool {
+        false
+    }
+
     /// Get the tool's namespace (optional, for grouping related tools).
     fn namespace(&self) -> Option<&str> {
         None
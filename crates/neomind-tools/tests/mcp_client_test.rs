@@ -0,0 +1,328 @@
+//! Tests for importing tools from external MCP servers.
+//!
+//! A tiny in-process MCP server is served over an in-memory stdio pipe and
+//! over HTTP on a local port. It offers two tools, split over two
+//! `tools/list` pages:
+//! - `add`: returns the sum of `a` and `b`
+//! - `slow`: answers after two seconds
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use neomind_storage::{McpServerConfig, McpTransportConfig};
+use neomind_tools::mcp::{McpClient, McpClientError, McpClientManager};
+use neomind_tools::ToolError;
+use serde_json::{json, Value};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::TcpListener;
+
+#[derive(Default)]
+struct Fixture {
+    session: Mutex<String>,
+    initializations: AtomicUsize,
+    calls: AtomicUsize,
+}
+
+impl Fixture {
+    /// Forget the current session, as after a server restart.
+    fn expire_session(&self) {
+        *self.session.lock().unwrap() = uuid::Uuid::new_v4().to_string();
+    }
+
+    async fn handle(&self, message: Value) -> Option<Value> {
+        let id = message.get("id")?.clone();
+        let result = match message["method"].as_str().unwrap_or_default() {
+            "initialize" => {
+                self.initializations.fetch_add(1, Ordering::SeqCst);
+                json!({
+                    "protocolVersion": "2025-06-18",
+                    "capabilities": { "tools": {} },
+                    "serverInfo": { "name": "fixture", "version": "0.1.0" },
+                })
+            }
+            "tools/list" => match message["params"]["cursor"].as_str() {
+                None => json!({
+                    "tools": [{
+                        "name": "add",
+                        "description": "Add two numbers",
+                        "inputSchema": {
+                            "type": "object",
+                            "properties": {
+                                "a": { "type": "number" },
+                                "b": { "type": "number" },
+                            },
+                            "required": ["a", "b"],
+                        },
+                        "annotations": { "readOnlyHint": true },
+                    }],
+                    "nextCursor": "page-2",
+                }),
+                Some(_) => json!({
+                    "tools": [{
+                        "name": "slow",
+                        "inputSchema": { "type": "object", "properties": {} },
+                    }],
+                }),
+            },
+            "tools/call" => {
+                self.calls.fetch_add(1, Ordering::SeqCst);
+                let args = &message["params"]["arguments"];
+                match message["params"]["name"].as_str() {
+                    Some("add") => {
+                        let sum = args["a"].as_f64().unwrap() + args["b"].as_f64().unwrap();
+                        json!({
+                            "content": [{ "type": "text", "text": sum.to_string() }],
+                            "structuredContent": { "sum": sum },
+                        })
+                    }
+                    Some("slow") => {
+                        tokio::time::sleep(Duration::from_secs(2)).await;
+                        json!({ "content": [{ "type": "text", "text": "done" }] })
+                    }
+                    _ => json!({
+                        "content": [{ "type": "text", "text": "Unknown tool" }],
+                        "isError": true,
+                    }),
+                }
+            }
+            method => {
+                return Some(json!({
+                    "jsonrpc": "2.0",
+                    "id": id,
+                    "error": { "code": -32601, "message": format!("Method not found: {}", method) },
+                }))
+            }
+        };
+        Some(json!({ "jsonrpc": "2.0", "id": id, "result": result }))
+    }
+}
+
+/// Connect a client to the fixture over an in-memory newline-delimited pipe.
+async fn connect_stdio(fixture: Arc<Fixture>, timeout: Duration) -> McpClient {
+    let (client_io, server_io) = tokio::io::duplex(64 * 1024);
+    let (client_read, client_write) = tokio::io::split(client_io);
+    let (server_read, server_write) = tokio::io::split(server_io);
+    let server_write = Arc::new(tokio::sync::Mutex::new(server_write));
+
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(server_read).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let fixture = fixture.clone();
+            let writer = server_write.clone();
+            // Requests are answered concurrently so a slow call blocks nothing
+            tokio::spawn(async move {
+                let message: Value = serde_json::from_str(&line).unwrap();
+                if let Some(reply) = fixture.handle(message).await {
+                    let mut line = serde_json::to_vec(&reply).unwrap();
+                    line.push(b'\n');
+                    let _ = writer.lock().await.write_all(&line).await;
+                }
+            });
+        }
+    });
+
+    McpClient::connect_io("fixture", client_read, client_write, timeout)
+        .await
+        .unwrap()
+}
+
+/// Serve the fixture over Streamable HTTP; `tools/call` is answered as SSE.
+async fn start_http(fixture: Arc<Fixture>) -> String {
+    fixture.expire_session();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/mcp", listener.local_addr().unwrap());
+
+    tokio::spawn(async move {
+        loop {
+            let Ok((mut stream, _)) = listener.accept().await else {
+                return;
+            };
+            let fixture = fixture.clone();
+            tokio::spawn(async move {
+                let mut data = Vec::new();
+                let mut buf = [0u8; 4096];
+                let header_end = loop {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    if n == 0 {
+                        return;
+                    }
+                    data.extend_from_slice(&buf[..n]);
+                    if let Some(pos) = data.windows(4).position(|w| w == b"\r\n\r\n") {
+                        break pos + 4;
+                    }
+                };
+
+                let head = String::from_utf8_lossy(&data[..header_end]).to_string();
+                let header = |name: &str| {
+                    head.lines().find_map(|line| {
+                        let (key, value) = line.split_once(':')?;
+                        key.eq_ignore_ascii_case(name)
+                            .then(|| value.trim().to_string())
+                    })
+                };
+                let length: usize = header("content-length")
+                    .and_then(|v| v.parse().ok())
+                    .unwrap_or(0);
+                while data.len() < header_end + length {
+                    let n = stream.read(&mut buf).await.unwrap();
+                    data.extend_from_slice(&buf[..n]);
+                }
+
+                let session = fixture.session.lock().unwrap().clone();
+                let response = if head.starts_with("DELETE") {
+                    "HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string()
+                } else if header("mcp-session-id").is_some_and(|id| id != session) {
+                    "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                        .to_string()
+                } else {
+                    let message: Value =
+                        serde_json::from_slice(&data[header_end..header_end + length]).unwrap();
+                    let is_call = message["method"] == "tools/call";
+                    match fixture.handle(message).await {
+                        None => "HTTP/1.1 202 Accepted\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+                            .to_string(),
+                        Some(reply) => {
+                            let (content_type, body) = if is_call {
+                                ("text/event-stream", format!("event: message\ndata: {}\n\n", reply))
+                            } else {
+                                ("application/json", reply.to_string())
+                            };
+                            format!(
+                                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nMcp-Session-Id: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                                content_type,
+                                session,
+                                body.len(),
+                                body
+                            )
+                        }
+                    }
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    url
+}
+
+#[tokio::test]
+async fn test_stdio_discovery_and_calls() {
+    let fixture = Arc::new(Fixture::default());
+    let client = connect_stdio(fixture.clone(), Duration::from_secs(5)).await;
+
+    assert_eq!(client.server_info()["name"], "fixture");
+    let names: Vec<&str> = client.tools().iter().map(|t| t.name.as_str()).collect();
+    assert_eq!(names, vec!["add", "slow"]);
+
+    let result = client
+        .call_tool("add", json!({ "a": 1, "b": 2 }))
+        .await
+        .unwrap();
+    assert_eq!(result["structuredContent"]["sum"], 3.0);
+
+    let result = client.call_tool("missing", json!({})).await.unwrap();
+    assert_eq!(result["isError"], true);
+}
+
+#[tokio::test]
+async fn test_stdio_timeout_keeps_connection() {
+    let fixture = Arc::new(Fixture::default());
+    let client = connect_stdio(fixture, Duration::from_secs(1)).await;
+
+    let err = client.call_tool("slow", json!({})).await.unwrap_err();
+    assert!(matches!(err, McpClientError::Timeout(_)));
+    assert!(!err.is_connection_error());
+
+    assert!(client.is_connected());
+    let result = client
+        .call_tool("add", json!({ "a": 2, "b": 2 }))
+        .await
+        .unwrap();
+    assert_eq!(result["structuredContent"]["sum"], 4.0);
+}
+
+#[tokio::test]
+async fn test_http_tools_through_manager() {
+    let fixture = Arc::new(Fixture::default());
+    let url = start_http(fixture.clone()).await;
+    let mut config = McpServerConfig::new(
+        "fixture".to_string(),
+        "Fixture".to_string(),
+        McpTransportConfig::Http {
+            url,
+            headers: HashMap::new(),
+        },
+    );
+
+    let manager = McpClientManager::new();
+    manager.upsert(config.clone()).await.unwrap();
+    let status = manager.server_status("fixture").unwrap();
+    assert!(status.connected);
+    assert_eq!(status.tool_count, 2);
+    assert_eq!(manager.tools().len(), 2);
+
+    let tool = manager.tool("fixture", "add").unwrap();
+    assert_eq!(tool.name(), "mcp:fixture:add");
+    assert_eq!(tool.namespace(), Some("mcp:fixture"));
+    assert!(tool.is_read_only());
+    assert!(!manager.tool("fixture", "slow").unwrap().is_read_only());
+
+    // Arguments are checked against the advertised schema before sending
+    let err = tool.execute(json!({ "a": 1 })).await.unwrap_err();
+    assert!(matches!(err, ToolError::InvalidArguments(_)));
+    let err = tool.execute(json!({ "a": 1, "b": "2" })).await.unwrap_err();
+    assert!(matches!(err, ToolError::InvalidArguments(_)));
+    assert_eq!(fixture.calls.load(Ordering::SeqCst), 0);
+
+    let output = tool.execute(json!({ "a": 1, "b": 2 })).await.unwrap();
+    assert!(output.success);
+    assert_eq!(output.data["sum"], 3.0);
+
+    // A restarted server rejects the old session; the call reconnects
+    fixture.expire_session();
+    let output = tool.execute(json!({ "a": 5, "b": 5 })).await.unwrap();
+    assert_eq!(output.data["sum"], 10.0);
+    assert_eq!(fixture.initializations.load(Ordering::SeqCst), 2);
+
+    // The rejected call never ran, so tools with side effects retry it too
+    fixture.expire_session();
+    let calls = fixture.calls.load(Ordering::SeqCst);
+    let slow = manager.tool("fixture", "slow").unwrap();
+    assert!(slow.execute(json!({})).await.unwrap().success);
+    assert_eq!(fixture.calls.load(Ordering::SeqCst), calls + 1);
+    assert_eq!(fixture.initializations.load(Ordering::SeqCst), 3);
+
+    config.enabled = false;
+    manager.upsert(config).await.unwrap();
+    assert!(manager.tools().is_empty());
+    assert!(manager.tool("fixture", "add").is_none());
+    assert!(!manager.server_status("fixture").unwrap().connected);
+
+    assert!(manager.remove("fixture").await);
+    assert!(manager.status().is_empty());
+}
+
+#[tokio::test]
+async fn test_unreachable_server() {
+    let config = McpServerConfig::new(
+        "offline".to_string(),
+        "Offline".to_string(),
+        McpTransportConfig::Stdio {
+            command: "neomind-missing-mcp-server".to_string(),
+            args: vec![],
+            env: HashMap::new(),
+        },
+    );
+
+    let manager = McpClientManager::new();
+    let err = manager.upsert(config).await.unwrap_err();
+    assert!(err.is_connection_error());
+
+    let status = manager.server_status("offline").unwrap();
+    assert!(!status.connected);
+    assert!(status.last_error.is_some());
+    assert!(manager.tools().is_empty());
+}